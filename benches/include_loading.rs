@@ -0,0 +1,83 @@
+//! Benchmark: asynchronous vs. synchronous loading of a schema with many includes
+//!
+//! Run with `cargo bench --features tokio --bench include_loading`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use xmlschema::validators::XsdSchema;
+
+/// Number of included schema documents
+const INCLUDE_COUNT: usize = 32;
+
+/// Write a main schema including `INCLUDE_COUNT` schemas, each declaring
+/// a handful of types and elements
+fn write_schema_set(dir: &Path) -> PathBuf {
+    let mut main = String::from(
+        r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           targetNamespace="http://example.com/bench"
+           xmlns="http://example.com/bench"
+           elementFormDefault="qualified">
+"#,
+    );
+
+    for i in 0..INCLUDE_COUNT {
+        main.push_str(&format!("  <xs:include schemaLocation=\"part{}.xsd\"/>\n", i));
+
+        let mut part = String::from(
+            r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           targetNamespace="http://example.com/bench"
+           xmlns="http://example.com/bench"
+           elementFormDefault="qualified">
+"#,
+        );
+        for j in 0..10 {
+            part.push_str(&format!(
+                r#"  <xs:complexType name="type{i}_{j}">
+    <xs:sequence>
+      <xs:element name="name" type="xs:string"/>
+      <xs:element name="value" type="xs:integer" minOccurs="0"/>
+    </xs:sequence>
+    <xs:attribute name="id" type="xs:string"/>
+  </xs:complexType>
+  <xs:element name="elem{i}_{j}" type="type{i}_{j}"/>
+"#
+            ));
+        }
+        part.push_str("</xs:schema>\n");
+        fs::write(dir.join(format!("part{}.xsd", i)), part).unwrap();
+    }
+
+    main.push_str("</xs:schema>\n");
+    let main_path = dir.join("main.xsd");
+    fs::write(&main_path, main).unwrap();
+    main_path
+}
+
+fn bench_include_loading(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let main_path = write_schema_set(dir.path());
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("include_loading");
+
+    group.bench_function("sync", |b| {
+        b.iter(|| XsdSchema::from_file(&main_path).unwrap())
+    });
+
+    group.bench_function("async", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(XsdSchema::from_file_async(&main_path))
+                .unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_include_loading);
+criterion_main!(benches);
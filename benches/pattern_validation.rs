@@ -0,0 +1,52 @@
+//! Benchmark: pattern facet and lexical built-in type validation
+//!
+//! Run with `cargo bench --bench pattern_validation`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use xmlschema::namespaces::QName;
+use xmlschema::validators::builtins::{validate_builtin, XSD_DATETIME, XSD_DURATION, XSD_LANGUAGE};
+use xmlschema::validators::XsdSchema;
+
+const PATTERN_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="isbnType">
+    <xs:restriction base="xs:string">
+      <xs:pattern value="\d{3}-\d{1,5}-\d{1,7}-\d{1,6}-\d"/>
+    </xs:restriction>
+  </xs:simpleType>
+</xs:schema>"#;
+
+fn bench_builtin_patterns(c: &mut Criterion) {
+    let mut group = c.benchmark_group("builtin_patterns");
+
+    group.bench_function("language", |b| {
+        b.iter(|| validate_builtin(XSD_LANGUAGE, black_box("en-GB")).unwrap())
+    });
+    group.bench_function("dateTime", |b| {
+        b.iter(|| validate_builtin(XSD_DATETIME, black_box("2024-05-01T12:30:00Z")).unwrap())
+    });
+    group.bench_function("duration", |b| {
+        b.iter(|| validate_builtin(XSD_DURATION, black_box("P1Y2M3DT4H5M6S")).unwrap())
+    });
+
+    group.finish();
+}
+
+fn bench_schema_patterns(c: &mut Criterion) {
+    let mut schema = XsdSchema::from_string(PATTERN_XSD).unwrap();
+    let mut group = c.benchmark_group("schema_patterns");
+
+    group.bench_function("precompile", |b| {
+        b.iter(|| assert!(schema.precompile_patterns().is_empty()))
+    });
+
+    let regexes = schema.compiled_patterns(&QName::local("isbnType")).unwrap().to_vec();
+    group.bench_function("compiled_match", |b| {
+        b.iter(|| regexes.iter().all(|re| re.is_match(black_box("978-3-16-148410-0"))))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_builtin_patterns, bench_schema_patterns);
+criterion_main!(benches);
@@ -1,7 +1,8 @@
 //! XML Catalog support for schema location resolution
 //!
 //! This module implements OASIS XML Catalog support for resolving URN-based
-//! schema locations (like those used in DITA 1.3) to actual file paths.
+//! schema locations (like those used in DITA 1.3), system and public
+//! identifiers, and remote URIs to actual file paths.
 //!
 //! XML Catalogs are defined by OASIS:
 //! https://www.oasis-open.org/committees/entity/spec-2001-08-06.html
@@ -11,7 +12,10 @@
 //! - `<catalog>` - Root element
 //! - `<group>` - Grouping element (inherits base from parent)
 //! - `<system>` - Maps system identifiers to URIs
+//! - `<public>` - Maps public identifiers to URIs
 //! - `<uri>` - Maps URN names to URIs
+//! - `<rewriteURI>` - Maps URIs starting with a prefix to another prefix,
+//!   such as a local directory
 //! - `<nextCatalog>` - Includes another catalog file
 //!
 //! # Example
@@ -20,6 +24,8 @@
 //! <catalog xmlns="urn:oasis:names:tc:entity:xmlns:xml:catalog">
 //!   <system systemId="urn:oasis:names:tc:dita:xsd:topic.xsd:1.3"
 //!           uri="xsd/topic.xsd"/>
+//!   <public publicId="-//OASIS//DTD DITA Topic//EN" uri="dtd/topic.dtd"/>
+//!   <rewriteURI uriStartString="http://example.com/schemas/" rewritePrefix="schemas/"/>
 //!   <nextCatalog catalog="base/catalog.xml"/>
 //! </catalog>
 //! ```
@@ -39,8 +45,12 @@ const CATALOG_NS: &str = "urn:oasis:names:tc:entity:xmlns:xml:catalog";
 pub struct XmlCatalog {
     /// System ID to URI mappings (systemId -> uri)
     system_mappings: HashMap<String, String>,
+    /// Normalized public ID to URI mappings (publicId -> uri)
+    public_mappings: HashMap<String, String>,
     /// URI name to URI mappings (name -> uri)
     uri_mappings: HashMap<String, String>,
+    /// URI prefix rewrites (uriStartString -> rewritePrefix)
+    uri_rewrites: Vec<(String, String)>,
     /// Base directory for resolving relative URIs
     base_dir: Option<PathBuf>,
 }
@@ -104,13 +114,16 @@ impl XmlCatalog {
                         child.get_attribute("systemId"),
                         child.get_attribute("uri"),
                     ) {
-                        // Resolve relative URI against base_dir
-                        let resolved_uri = if let Some(base) = base_dir {
-                            base.join(uri).to_string_lossy().to_string()
-                        } else {
-                            uri.to_string()
-                        };
-                        self.system_mappings.insert(system_id.to_string(), resolved_uri);
+                        self.system_mappings.insert(system_id.to_string(), resolve_uri(uri, base_dir));
+                    }
+                }
+                "public" => {
+                    // <public publicId="..." uri="..."/>
+                    if let (Some(public_id), Some(uri)) = (
+                        child.get_attribute("publicId"),
+                        child.get_attribute("uri"),
+                    ) {
+                        self.public_mappings.insert(normalize_public_id(public_id), resolve_uri(uri, base_dir));
                     }
                 }
                 "uri" => {
@@ -119,13 +132,16 @@ impl XmlCatalog {
                         child.get_attribute("name"),
                         child.get_attribute("uri"),
                     ) {
-                        // Resolve relative URI against base_dir
-                        let resolved_uri = if let Some(base) = base_dir {
-                            base.join(uri).to_string_lossy().to_string()
-                        } else {
-                            uri.to_string()
-                        };
-                        self.uri_mappings.insert(name.to_string(), resolved_uri);
+                        self.uri_mappings.insert(name.to_string(), resolve_uri(uri, base_dir));
+                    }
+                }
+                "rewriteURI" => {
+                    // <rewriteURI uriStartString="..." rewritePrefix="..."/>
+                    if let (Some(start), Some(prefix)) = (
+                        child.get_attribute("uriStartString"),
+                        child.get_attribute("rewritePrefix"),
+                    ) {
+                        self.uri_rewrites.push((start.to_string(), resolve_uri(prefix, base_dir)));
                     }
                 }
                 "nextCatalog" => {
@@ -162,34 +178,58 @@ impl XmlCatalog {
         Ok(())
     }
 
-    /// Resolve a schema location using the catalog
+    /// Resolve a schema location or an external identifier using the catalog
     ///
     /// Tries to resolve the location in this order:
     /// 1. Check system ID mappings
     /// 2. Check URI name mappings
-    /// 3. Return None if not found
-    pub fn resolve(&self, location: &str) -> Option<&str> {
+    /// 3. Rewrite the longest matching `rewriteURI` prefix
+    /// 4. Check public ID mappings, with white space normalized
+    /// 5. Return None if not found
+    ///
+    /// `urn:publicid:` URNs are unwrapped into the public identifiers they
+    /// encode first.
+    pub fn resolve(&self, location: &str) -> Option<String> {
+        if let Some(public_id) = unwrap_public_id_urn(location) {
+            return self.public_mappings.get(&normalize_public_id(&public_id)).cloned();
+        }
+
         // Try system ID first
         if let Some(uri) = self.system_mappings.get(location) {
-            return Some(uri);
+            return Some(uri.clone());
         }
 
         // Try URI name
         if let Some(uri) = self.uri_mappings.get(location) {
-            return Some(uri);
+            return Some(uri.clone());
+        }
+
+        // Try URI prefix rewrites
+        let rewrite = self
+            .uri_rewrites
+            .iter()
+            .filter(|(start, _)| location.starts_with(start.as_str()))
+            .max_by_key(|(start, _)| start.len());
+        if let Some((start, prefix)) = rewrite {
+            let rest = &location[start.len()..];
+            if is_url(prefix) {
+                return Some(format!("{}{}", prefix, rest));
+            }
+            return Some(Path::new(prefix).join(rest.trim_start_matches('/')).to_string_lossy().to_string());
         }
 
-        None
+        // Try public ID
+        self.public_mappings.get(&normalize_public_id(location)).cloned()
     }
 
     /// Check if this catalog is empty (has no mappings)
     pub fn is_empty(&self) -> bool {
-        self.system_mappings.is_empty() && self.uri_mappings.is_empty()
+        self.len() == 0
     }
 
     /// Get the number of mappings
     pub fn len(&self) -> usize {
-        self.system_mappings.len() + self.uri_mappings.len()
+        self.system_mappings.len() + self.public_mappings.len() + self.uri_mappings.len() + self.uri_rewrites.len()
     }
 
     /// Merge another catalog into this one
@@ -197,10 +237,71 @@ impl XmlCatalog {
         for (k, v) in &other.system_mappings {
             self.system_mappings.entry(k.clone()).or_insert_with(|| v.clone());
         }
+        for (k, v) in &other.public_mappings {
+            self.public_mappings.entry(k.clone()).or_insert_with(|| v.clone());
+        }
         for (k, v) in &other.uri_mappings {
             self.uri_mappings.entry(k.clone()).or_insert_with(|| v.clone());
         }
+        for (start, prefix) in &other.uri_rewrites {
+            if !self.uri_rewrites.iter().any(|(existing, _)| existing == start) {
+                self.uri_rewrites.push((start.clone(), prefix.clone()));
+            }
+        }
+    }
+}
+
+/// Check whether a URI has a scheme, like `http://` or `file://`
+fn is_url(uri: &str) -> bool {
+    uri.contains("://")
+}
+
+/// Resolve a relative URI of a catalog entry against the catalog directory
+fn resolve_uri(uri: &str, base_dir: Option<&Path>) -> String {
+    match base_dir {
+        Some(base) if !is_url(uri) => base.join(uri).to_string_lossy().to_string(),
+        _ => uri.to_string(),
+    }
+}
+
+/// Normalize a public identifier, collapsing white space into single spaces
+fn normalize_public_id(public_id: &str) -> String {
+    public_id.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Decode the public identifier of a `urn:publicid:` URN
+///
+/// See RFC 3151 for the transcription rules.
+fn unwrap_public_id_urn(urn: &str) -> Option<String> {
+    let encoded = urn.strip_prefix("urn:publicid:")?;
+    let mut public_id = String::with_capacity(encoded.len());
+    let mut chars = encoded.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '+' => public_id.push(' '),
+            ':' => public_id.push_str("//"),
+            ';' => public_id.push_str("::"),
+            '%' => {
+                let code: String = chars.by_ref().take(2).collect();
+                match code.to_ascii_uppercase().as_str() {
+                    "2B" => public_id.push('+'),
+                    "3A" => public_id.push(':'),
+                    "2F" => public_id.push('/'),
+                    "3B" => public_id.push(';'),
+                    "27" => public_id.push('\''),
+                    "3F" => public_id.push('?'),
+                    "23" => public_id.push('#'),
+                    "25" => public_id.push('%'),
+                    _ => {
+                        public_id.push('%');
+                        public_id.push_str(&code);
+                    }
+                }
+            }
+            c => public_id.push(c),
+        }
     }
+    Some(public_id)
 }
 
 #[cfg(test)]
@@ -271,6 +372,41 @@ mod tests {
         assert!(catalog.resolve("urn:sub:schema.xsd").is_some());
     }
 
+    #[test]
+    fn test_public_and_rewrite_entries() {
+        let catalog_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<catalog xmlns="urn:oasis:names:tc:entity:xmlns:xml:catalog">
+    <public publicId="-//OASIS//DTD DocBook XML V4.5//EN" uri="docbook/docbookx.dtd"/>
+    <rewriteURI uriStartString="http://example.com/schemas/" rewritePrefix="local/schemas/"/>
+    <rewriteURI uriStartString="http://example.com/schemas/v2/" rewritePrefix="local/v2"/>
+</catalog>"#;
+
+        let temp_dir = TempDir::new().unwrap();
+        let catalog_path = temp_dir.path().join("catalog.xml");
+        std::fs::write(&catalog_path, catalog_xml).unwrap();
+
+        let catalog = XmlCatalog::from_file(&catalog_path).unwrap();
+        assert_eq!(catalog.len(), 3);
+
+        // Public identifiers are matched after normalization
+        let docbook = temp_dir.path().join("docbook/docbookx.dtd").to_string_lossy().to_string();
+        assert_eq!(catalog.resolve("-//OASIS//DTD DocBook XML V4.5//EN"), Some(docbook.clone()));
+        assert_eq!(catalog.resolve("  -//OASIS//DTD DocBook\n  XML V4.5//EN "), Some(docbook.clone()));
+        assert_eq!(
+            catalog.resolve("urn:publicid:-:OASIS:DTD+DocBook+XML+V4.5:EN"),
+            Some(docbook)
+        );
+
+        // The longest matching prefix is rewritten
+        let rewritten = catalog.resolve("http://example.com/schemas/common/types.xsd").unwrap();
+        assert_eq!(rewritten, temp_dir.path().join("local/schemas/common/types.xsd").to_string_lossy());
+        let rewritten = catalog.resolve("http://example.com/schemas/v2/main.xsd").unwrap();
+        assert_eq!(rewritten, temp_dir.path().join("local/v2/main.xsd").to_string_lossy());
+
+        assert!(catalog.resolve("http://example.org/schemas/main.xsd").is_none());
+        assert!(catalog.resolve("-//OASIS//DTD DocBook XML V5.0//EN").is_none());
+    }
+
     #[test]
     fn test_group_element() {
         let catalog_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -0,0 +1,509 @@
+//! Code generation from schemas
+//!
+//! This module generates TypeScript declarations from the XSD type system,
+//! for TypeScript and JavaScript consumers of the JSON produced by the
+//! converters:
+//! - named simple types become type aliases, and enumerations unions of
+//!   string literals;
+//! - complex types become interfaces, with optional properties for optional
+//!   elements and attributes, and arrays for repeated elements;
+//! - global elements become aliases of their type, or interfaces for
+//!   anonymous types.
+//!
+//! Requires the `codegen` feature.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::sync::Arc;
+
+use crate::namespaces::QName;
+use crate::validators::builtins::is_builtin_qname;
+use crate::validators::globals::qname_order;
+use crate::validators::groups::ElementParticle;
+use crate::validators::{
+    AttributeUse, ComplexContent, ElementType, GlobalType, SimpleType, SimpleTypeVariety, XsdComplexType,
+    XsdElement, XsdSchema,
+};
+
+/// Maximum nesting of anonymous types written inline
+const MAX_INLINE_DEPTH: usize = 16;
+
+/// Built-in types mapped to `number`
+const NUMBER_TYPES: &[&str] = &[
+    "decimal",
+    "integer",
+    "nonPositiveInteger",
+    "negativeInteger",
+    "long",
+    "int",
+    "short",
+    "byte",
+    "nonNegativeInteger",
+    "unsignedLong",
+    "unsignedInt",
+    "unsignedShort",
+    "unsignedByte",
+    "positiveInteger",
+    "float",
+    "double",
+];
+
+/// Options for generating TypeScript declarations
+///
+/// See [`XsdSchema::to_typescript`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeScriptOptions {
+    /// Prefix for the properties generated from attributes
+    pub attribute_prefix: String,
+    /// Name of the property holding simple or mixed text content
+    pub text_property: String,
+    /// Also declare a type for each global element
+    pub include_elements: bool,
+    /// Declare the types with `export`
+    pub export: bool,
+}
+
+impl Default for TypeScriptOptions {
+    fn default() -> Self {
+        Self {
+            attribute_prefix: String::new(),
+            text_property: "value".to_string(),
+            include_elements: true,
+            export: true,
+        }
+    }
+}
+
+impl TypeScriptOptions {
+    /// Set the prefix of attribute properties
+    pub fn with_attribute_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.attribute_prefix = prefix.into();
+        self
+    }
+
+    /// Set the name of the text content property
+    pub fn with_text_property(mut self, name: impl Into<String>) -> Self {
+        self.text_property = name.into();
+        self
+    }
+
+    /// Set whether global elements get a declaration
+    pub fn with_elements(mut self, include: bool) -> Self {
+        self.include_elements = include;
+        self
+    }
+
+    /// Set whether declarations are exported
+    pub fn with_export(mut self, export: bool) -> Self {
+        self.export = export;
+        self
+    }
+}
+
+impl XsdSchema {
+    /// Generate TypeScript declarations for the schema with default options
+    pub fn to_typescript_interfaces(&self) -> String {
+        self.to_typescript(TypeScriptOptions::default())
+    }
+
+    /// Generate TypeScript declarations for the global types and elements
+    ///
+    /// Declarations are written in QName order: simple types, then complex
+    /// types, then global elements. Built-in types are mapped to `string`,
+    /// `number` or `boolean`.
+    pub fn to_typescript(&self, options: TypeScriptOptions) -> String {
+        TypeScriptWriter::new(self, &options).write()
+    }
+}
+
+struct TypeScriptWriter<'a> {
+    schema: &'a XsdSchema,
+    options: &'a TypeScriptOptions,
+    /// Names declared for the global types
+    type_names: HashSet<String>,
+}
+
+impl<'a> TypeScriptWriter<'a> {
+    fn new(schema: &'a XsdSchema, options: &'a TypeScriptOptions) -> Self {
+        let type_names = schema
+            .types()
+            .filter(|(qname, _)| !is_builtin_qname(qname))
+            .map(|(qname, _)| pascal_case(&qname.local_name))
+            .collect();
+        Self {
+            schema,
+            options,
+            type_names,
+        }
+    }
+
+    fn write(&self) -> String {
+        let mut types: Vec<_> = self.schema.types().filter(|(qname, _)| !is_builtin_qname(qname)).collect();
+        types.sort_by(|a, b| qname_order(a.0, b.0));
+        let mut elements: Vec<_> = self.schema.elements().collect();
+        elements.sort_by(|a, b| qname_order(a.0, b.0));
+
+        let mut declarations = Vec::new();
+        for (qname, global_type) in &types {
+            if let GlobalType::Simple(simple_type) = global_type {
+                let alias = self.simple_type_ts(simple_type.as_ref(), false);
+                declarations.push(self.type_alias(&pascal_case(&qname.local_name), &alias));
+            }
+        }
+        for (qname, global_type) in &types {
+            if let GlobalType::Complex(complex_type) = global_type {
+                declarations.push(self.interface(&pascal_case(&qname.local_name), complex_type));
+            }
+        }
+        if self.options.include_elements {
+            for (qname, element) in &elements {
+                let name = self.element_name(qname);
+                let declaration = match (&element.type_name, &element.element_type) {
+                    (None, ElementType::Complex(complex_type)) => self.interface(&name, complex_type),
+                    _ => self.type_alias(&name, &self.element_ts(element, 0)),
+                };
+                declarations.push(declaration);
+            }
+        }
+
+        declarations.join("\n")
+    }
+
+    fn export(&self) -> &'static str {
+        if self.options.export {
+            "export "
+        } else {
+            ""
+        }
+    }
+
+    fn type_alias(&self, name: &str, alias: &str) -> String {
+        format!("{}type {} = {};\n", self.export(), name, alias)
+    }
+
+    fn interface(&self, name: &str, complex_type: &XsdComplexType) -> String {
+        format!("{}interface {} {}\n", self.export(), name, self.object_type(complex_type, 0))
+    }
+
+    /// Name of the declaration of a global element, kept apart from the type names
+    fn element_name(&self, qname: &QName) -> String {
+        let name = pascal_case(&qname.local_name);
+        if self.type_names.contains(&name) {
+            format!("{}Element", name)
+        } else {
+            name
+        }
+    }
+
+    /// Object type literal with a property for each attribute and child element
+    fn object_type(&self, complex_type: &XsdComplexType, depth: usize) -> String {
+        let indent = "  ".repeat(depth + 1);
+        let mut out = String::from("{\n");
+
+        if let Some(ts) = self.simple_content_ts(complex_type, 0) {
+            let _ = writeln!(out, "{}{}: {};", indent, property_name(&self.options.text_property), ts);
+        } else if let ComplexContent::Group(group) = &complex_type.content {
+            for (name, occurs) in self.schema.element_occurs(group) {
+                let mut ts = match self.schema.find_element_particle(group, &name) {
+                    Some(particle) => self.particle_ts(particle, depth + 1),
+                    None => "unknown".to_string(),
+                };
+                if occurs.max != Some(1) {
+                    ts = array_of(&ts);
+                }
+                let optional = if occurs.min == 0 { "?" } else { "" };
+                let _ = writeln!(out, "{}{}{}: {};", indent, property_name(&name.local_name), optional, ts);
+            }
+            if complex_type.mixed {
+                let _ = writeln!(out, "{}{}?: string;", indent, property_name(&self.options.text_property));
+            }
+        }
+
+        let mut attributes: Vec<_> = complex_type.attributes.iter_attributes().collect();
+        attributes.sort_by(|a, b| qname_order(a.name(), b.name()));
+        for attr in attributes {
+            let optional = match attr.use_mode() {
+                AttributeUse::Required => "",
+                AttributeUse::Optional => "?",
+                AttributeUse::Prohibited => continue,
+            };
+            let ts = attr
+                .simple_type()
+                .map(|simple_type| self.simple_type_ts(simple_type, true))
+                .unwrap_or_else(|| "string".to_string());
+            let name = format!("{}{}", self.options.attribute_prefix, attr.name().local_name);
+            let _ = writeln!(out, "{}{}{}: {};", indent, property_name(&name), optional, ts);
+        }
+
+        out.push_str(&"  ".repeat(depth));
+        out.push('}');
+        out
+    }
+
+    /// TypeScript type of the text of a complex type with simple content
+    fn simple_content_ts(&self, complex_type: &XsdComplexType, depth: usize) -> Option<String> {
+        if let ComplexContent::Simple(simple_type) = &complex_type.content {
+            return Some(self.simple_type_ts(simple_type.as_ref(), true));
+        }
+        if depth > MAX_INLINE_DEPTH || complex_type.model_group().is_some_and(|group| !group.is_empty()) {
+            return None;
+        }
+        let base = complex_type.base_type.as_ref()?;
+        match self.schema.lookup_type(base) {
+            Some(GlobalType::Simple(simple_type)) => Some(self.simple_type_ts(simple_type.as_ref(), true)),
+            Some(GlobalType::Complex(base_type)) if base_type.name != complex_type.name => {
+                self.simple_content_ts(base_type, depth + 1)
+            }
+            Some(GlobalType::Complex(_)) => None,
+            // Built-in simple types aren't all registered in the global maps
+            None if is_builtin_qname(base) && base.local_name != "anyType" => {
+                Some(primitive_ts(Some(&base.local_name)).to_string())
+            }
+            None => None,
+        }
+    }
+
+    /// TypeScript type of an element particle of a content model
+    fn particle_ts(&self, particle: &ElementParticle, depth: usize) -> String {
+        // References to global elements use the element's declaration
+        let global = match (&particle.element_ref, particle.element()) {
+            (Some(element_ref), _) => self.schema.lookup_element(element_ref),
+            (None, Some(local)) => self
+                .schema
+                .lookup_element(&particle.name)
+                .filter(|element| Arc::ptr_eq(element, local)),
+            (None, None) => self.schema.lookup_element(&particle.name),
+        };
+        match (global, particle.element()) {
+            (Some(element), _) => self.global_element_ts(element, depth),
+            (None, Some(element)) => self.element_ts(element, depth),
+            (None, None) => "unknown".to_string(),
+        }
+    }
+
+    fn global_element_ts(&self, element: &XsdElement, depth: usize) -> String {
+        if self.options.include_elements {
+            self.element_name(&element.name)
+        } else {
+            self.element_ts(element, depth)
+        }
+    }
+
+    /// TypeScript type of the content of an element declaration
+    fn element_ts(&self, element: &XsdElement, depth: usize) -> String {
+        if let Some(type_name) = &element.type_name {
+            if !is_builtin_qname(type_name) && self.schema.lookup_type(type_name).is_some() {
+                return pascal_case(&type_name.local_name);
+            }
+        }
+        match element.effective_type(self.schema) {
+            Some(GlobalType::Simple(simple_type)) => self.simple_type_ts(simple_type.as_ref(), true),
+            Some(GlobalType::Complex(complex_type)) => match &complex_type.name {
+                Some(name) if !is_builtin_qname(name) => pascal_case(&name.local_name),
+                _ if complex_type.is_empty() && complex_type.attributes.iter_attributes().next().is_none() => {
+                    "Record<string, never>".to_string()
+                }
+                _ if depth < MAX_INLINE_DEPTH => self.object_type(&complex_type, depth),
+                _ => "unknown".to_string(),
+            },
+            None => "unknown".to_string(),
+        }
+    }
+
+    /// TypeScript type of a simple type
+    ///
+    /// Named schema types are referred to by name when `by_name` is set.
+    fn simple_type_ts(&self, simple_type: &dyn SimpleType, by_name: bool) -> String {
+        if let Some(name) = simple_type.name() {
+            if by_name && !is_builtin_qname(name) && self.type_names.contains(&pascal_case(&name.local_name)) {
+                return pascal_case(&name.local_name);
+            }
+        }
+
+        match simple_type.variety() {
+            SimpleTypeVariety::List => {
+                let item = simple_type
+                    .item_type()
+                    .map(|item| self.simple_type_ts(item.as_ref(), true))
+                    .unwrap_or_else(|| "string".to_string());
+                array_of(&item)
+            }
+            SimpleTypeVariety::Union => {
+                let mut members: Vec<String> = Vec::new();
+                for member in simple_type.member_types() {
+                    let ts = self.simple_type_ts(member.as_ref(), true);
+                    if !members.contains(&ts) {
+                        members.push(ts);
+                    }
+                }
+                if members.is_empty() {
+                    "string".to_string()
+                } else {
+                    members.join(" | ")
+                }
+            }
+            SimpleTypeVariety::Atomic => {
+                let primitive = primitive_ts(simple_type.primitive_name());
+                match enumeration(simple_type) {
+                    Some(values) => values
+                        .iter()
+                        .map(|value| match primitive {
+                            "number" if value.trim().parse::<f64>().is_ok_and(f64::is_finite) => value.trim().to_string(),
+                            _ => serde_json::Value::String(value.clone()).to_string(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" | "),
+                    None => primitive.to_string(),
+                }
+            }
+        }
+    }
+}
+
+/// Map the name of a built-in type to a TypeScript primitive type
+fn primitive_ts(primitive_name: Option<&str>) -> &'static str {
+    match primitive_name {
+        Some("boolean") => "boolean",
+        Some(name) if NUMBER_TYPES.contains(&name) => "number",
+        _ => "string",
+    }
+}
+
+/// Find the nearest enumeration facet in the derivation chain
+fn enumeration(simple_type: &dyn SimpleType) -> Option<Vec<String>> {
+    let mut current = Some(simple_type);
+    while let Some(ty) = current {
+        if let Some(facet) = &ty.facets().enumeration {
+            if !facet.values.is_empty() {
+                return Some(facet.values.clone());
+            }
+        }
+        current = SimpleType::base_type(ty);
+    }
+    None
+}
+
+/// Array type of an element type, parenthesizing unions
+fn array_of(ts: &str) -> String {
+    if ts.contains(" | ") {
+        format!("({})[]", ts)
+    } else {
+        format!("{}[]", ts)
+    }
+}
+
+/// Convert an XML name to a PascalCase TypeScript identifier
+fn pascal_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = true;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            if upper {
+                out.push(c.to_ascii_uppercase());
+            } else {
+                out.push(c);
+            }
+            upper = false;
+        } else {
+            upper = true;
+        }
+    }
+    if out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// Write a property name, quoting names that aren't identifiers
+fn property_name(name: &str) -> String {
+    let is_identifier = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_identifier {
+        name.to_string()
+    } else {
+        serde_json::Value::String(name.to_string()).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATALOG_XSD: &str = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="genre">
+    <xs:restriction base="xs:string">
+      <xs:enumeration value="fiction"/>
+      <xs:enumeration value="non-fiction"/>
+      <xs:enumeration value="reference"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="ratings">
+    <xs:list itemType="xs:int"/>
+  </xs:simpleType>
+  <xs:complexType name="price">
+    <xs:simpleContent>
+      <xs:extension base="xs:decimal">
+        <xs:attribute name="currency" type="xs:string" use="required"/>
+      </xs:extension>
+    </xs:simpleContent>
+  </xs:complexType>
+  <xs:element name="catalog">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="item" maxOccurs="unbounded">
+          <xs:complexType>
+            <xs:choice>
+              <xs:element name="isbn" type="xs:string"/>
+              <xs:element name="issn" type="xs:string"/>
+            </xs:choice>
+            <xs:attribute name="genre" type="genre"/>
+            <xs:attribute name="in-stock" type="xs:boolean"/>
+          </xs:complexType>
+        </xs:element>
+        <xs:element name="price" type="price" minOccurs="0"/>
+        <xs:element name="ratings" type="ratings" minOccurs="0"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+
+    #[test]
+    fn test_pascal_case() {
+        assert_eq!(pascal_case("bookType"), "BookType");
+        assert_eq!(pascal_case("non-fiction_kind"), "NonFiction_kind");
+        assert_eq!(pascal_case("3d"), "_3d");
+    }
+
+    #[test]
+    fn test_property_name() {
+        assert_eq!(property_name("title"), "title");
+        assert_eq!(property_name("in-stock"), "\"in-stock\"");
+    }
+
+    #[test]
+    fn test_to_typescript() {
+        let schema = XsdSchema::from_string(CATALOG_XSD).unwrap();
+        let ts = schema.to_typescript_interfaces();
+
+        assert!(ts.contains(r#"export type Genre = "fiction" | "non-fiction" | "reference";"#));
+        assert!(ts.contains("export type Ratings = number[];"));
+        assert!(ts.contains("export interface Price {\n  value: number;\n  currency: string;\n}"));
+        assert!(ts.contains("export interface Catalog {\n  item: {\n    isbn?: string;\n    issn?: string;\n    genre?: Genre;\n    \"in-stock\"?: boolean;\n  }[];\n  price?: Price;"));
+        assert!(ts.contains("  ratings?: Ratings;\n}"));
+    }
+
+    #[test]
+    fn test_to_typescript_options() {
+        let schema = XsdSchema::from_string(CATALOG_XSD).unwrap();
+        let options = TypeScriptOptions::default()
+            .with_attribute_prefix("@")
+            .with_text_property("$")
+            .with_elements(false)
+            .with_export(false);
+        let ts = schema.to_typescript(options);
+
+        assert!(ts.contains("interface Price {\n  $: number;\n  \"@currency\": string;\n}"));
+        assert!(!ts.contains("export "));
+        assert!(!ts.contains("Catalog"));
+    }
+}
@@ -0,0 +1,300 @@
+//! Schema-aware XML document comparison
+//!
+//! Compares two XML documents and reports the differences as XPath
+//! locations. Without a schema the comparison is structural: element names,
+//! attribute values and text must match exactly. With a schema, values are
+//! compared through their declared simple types, so lexically different but
+//! equal values (`"1"` and `"01"` for `xs:integer`, `"a  b"` and `"a b"` for
+//! `xs:token`) compare equal.
+
+use std::collections::HashMap;
+
+use crate::documents::{Document, Element};
+use crate::namespaces::QName;
+use crate::validators::document_validation::find_child_declaration;
+use crate::validators::{GlobalType, SimpleType, XsdComplexType, XsdElement, XsdSchema};
+
+/// XPath location of a node in a compared document
+pub type XPath = String;
+
+/// Differences between two XML documents
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocumentDiff {
+    /// Nodes only present in the second document
+    pub added: Vec<XPath>,
+    /// Nodes only present in the first document
+    pub removed: Vec<XPath>,
+    /// Nodes present in both documents with different values
+    /// (path, value in the first document, value in the second document)
+    pub changed: Vec<(XPath, String, String)>,
+}
+
+impl DocumentDiff {
+    /// Create an empty diff
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if the documents are equivalent
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Total number of differences
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len()
+    }
+}
+
+/// Compare two XML documents
+///
+/// Child elements are paired by name and position among same-named siblings,
+/// so paths have the form `/book[1]/author[2]/@id`. When a schema is given,
+/// the root declarations are looked up in it and element text and attribute
+/// values are compared semantically according to their declared types; nodes
+/// without a known type are compared structurally.
+pub fn compare_documents(doc1: &Document, doc2: &Document, schema: Option<&XsdSchema>) -> DocumentDiff {
+    let mut diff = DocumentDiff::new();
+
+    match (&doc1.root, &doc2.root) {
+        (Some(root1), Some(root2)) => {
+            if root1.qname == root2.qname {
+                let decl = schema.and_then(|s| s.check_document_element(doc1).ok().cloned());
+                let path = format!("/{}[1]", root1.local_name());
+                compare_elements(root1, root2, &path, decl.as_deref(), schema, &mut diff);
+            } else {
+                diff.removed.push(format!("/{}[1]", root1.local_name()));
+                diff.added.push(format!("/{}[1]", root2.local_name()));
+            }
+        }
+        (Some(root1), None) => diff.removed.push(format!("/{}[1]", root1.local_name())),
+        (None, Some(root2)) => diff.added.push(format!("/{}[1]", root2.local_name())),
+        (None, None) => {}
+    }
+
+    diff
+}
+
+/// Compare two elements with the same name at `path`
+fn compare_elements(
+    elem1: &Element,
+    elem2: &Element,
+    path: &str,
+    decl: Option<&XsdElement>,
+    schema: Option<&XsdSchema>,
+    diff: &mut DocumentDiff,
+) {
+    let effective_type = match (decl, schema) {
+        (Some(decl), Some(schema)) => decl.effective_type(schema),
+        _ => None,
+    };
+    let (simple_type, complex_type) = match effective_type {
+        Some(GlobalType::Simple(st)) => (Some(st), None),
+        Some(GlobalType::Complex(ct)) => (ct.simple_type().cloned(), Some(ct)),
+        None => (None, None),
+    };
+
+    compare_attributes(elem1, elem2, path, complex_type.as_deref(), schema, diff);
+
+    // Text content
+    match (&elem1.text, &elem2.text) {
+        (Some(text1), Some(text2)) => {
+            if !values_equal(text1, text2, simple_type.as_deref()) {
+                diff.changed.push((format!("{}/text()", path), text1.clone(), text2.clone()));
+            }
+        }
+        (Some(_), None) => diff.removed.push(format!("{}/text()", path)),
+        (None, Some(_)) => diff.added.push(format!("{}/text()", path)),
+        (None, None) => {}
+    }
+
+    // Child elements, paired by name and position among same-named siblings
+    let children1 = indexed_children(elem1);
+    let children2 = indexed_children(elem2);
+
+    for (key, child1) in &children1 {
+        let child_path = format!("{}/{}[{}]", path, child1.local_name(), key.1);
+        match children2.iter().find(|(k, _)| k == key) {
+            Some((_, child2)) => {
+                let child_decl = schema.and_then(|s| find_child_declaration(s, child1, complex_type.as_deref()));
+                compare_elements(child1, child2, &child_path, child_decl.as_deref(), schema, diff);
+            }
+            None => diff.removed.push(child_path),
+        }
+    }
+    for (key, child2) in &children2 {
+        if !children1.iter().any(|(k, _)| k == key) {
+            diff.added.push(format!("{}/{}[{}]", path, child2.local_name(), key.1));
+        }
+    }
+}
+
+/// Compare the attributes of two elements at `path`
+fn compare_attributes(
+    elem1: &Element,
+    elem2: &Element,
+    path: &str,
+    complex_type: Option<&XsdComplexType>,
+    schema: Option<&XsdSchema>,
+    diff: &mut DocumentDiff,
+) {
+    let mut names1: Vec<&QName> = elem1.attributes.keys().collect();
+    names1.sort_by_key(|qname| qname.to_string());
+
+    for qname in names1 {
+        let attr_path = format!("{}/@{}", path, qname.local_name);
+        let value1 = &elem1.attributes[qname];
+        match elem2.attributes.get(qname) {
+            Some(value2) => {
+                let attr_type = match (complex_type, schema) {
+                    (Some(ct), Some(schema)) => ct
+                        .attributes
+                        .get_attribute(&QName::local(&qname.local_name))
+                        .or_else(|| ct.attributes.get_attribute(qname))
+                        .and_then(|attr| attr.effective_type(schema)),
+                    _ => None,
+                };
+                if !values_equal(value1, value2, attr_type.map(|t| t.as_ref())) {
+                    diff.changed.push((attr_path, value1.clone(), value2.clone()));
+                }
+            }
+            None => diff.removed.push(attr_path),
+        }
+    }
+
+    let mut added: Vec<&QName> = elem2
+        .attributes
+        .keys()
+        .filter(|qname| !elem1.attributes.contains_key(*qname))
+        .collect();
+    added.sort_by_key(|qname| qname.to_string());
+    for qname in added {
+        diff.added.push(format!("{}/@{}", path, qname.local_name));
+    }
+}
+
+/// Children keyed by (name, 1-based position among same-named siblings)
+fn indexed_children(elem: &Element) -> Vec<((QName, usize), &Element)> {
+    let mut counts: HashMap<&QName, usize> = HashMap::new();
+    elem.children
+        .iter()
+        .map(|child| {
+            let count = counts.entry(&child.qname).or_insert(0);
+            *count += 1;
+            ((child.qname.clone(), *count), child)
+        })
+        .collect()
+}
+
+/// Compare two lexical values, semantically if their type is known
+fn values_equal(value1: &str, value2: &str, simple_type: Option<&(dyn SimpleType + Send + Sync)>) -> bool {
+    if value1 == value2 {
+        return true;
+    }
+    let Some(simple_type) = simple_type else {
+        return false;
+    };
+
+    let white_space = simple_type.white_space();
+    let normalized1 = white_space.normalize(value1);
+    let normalized2 = white_space.normalize(value2);
+    if normalized1 == normalized2 {
+        return true;
+    }
+
+    match (simple_type.validate_value(&normalized1), simple_type.validate_value(&normalized2)) {
+        (Ok(decoded1), Ok(decoded2)) => decoded1 == decoded2,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATALOG_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="catalog">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="item" maxOccurs="unbounded">
+          <xs:complexType>
+            <xs:sequence>
+              <xs:element name="quantity" type="xs:integer"/>
+              <xs:element name="price" type="xs:decimal"/>
+              <xs:element name="label" type="xs:token"/>
+              <xs:element name="note" type="xs:string" minOccurs="0"/>
+            </xs:sequence>
+            <xs:attribute name="available" type="xs:boolean"/>
+          </xs:complexType>
+        </xs:element>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+
+    fn doc(xml: &str) -> Document {
+        Document::from_string(xml).unwrap()
+    }
+
+    #[test]
+    fn test_identical_documents() {
+        let xml = r#"<catalog><item available="true"><quantity>1</quantity></item></catalog>"#;
+        let diff = compare_documents(&doc(xml), &doc(xml), None);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_structural_differences() {
+        let doc1 = doc(r#"<catalog><item id="a"><quantity>1</quantity><note>x</note></item></catalog>"#);
+        let doc2 = doc(r#"<catalog><item id="b"><quantity>1</quantity></item><item/></catalog>"#);
+
+        let diff = compare_documents(&doc1, &doc2, None);
+        assert_eq!(diff.removed, vec!["/catalog[1]/item[1]/note[1]".to_string()]);
+        assert_eq!(diff.added, vec!["/catalog[1]/item[2]".to_string()]);
+        assert_eq!(
+            diff.changed,
+            vec![("/catalog[1]/item[1]/@id".to_string(), "a".to_string(), "b".to_string())]
+        );
+        assert_eq!(diff.len(), 3);
+    }
+
+    #[test]
+    fn test_semantically_equivalent_documents() {
+        let schema = XsdSchema::from_string(CATALOG_XSD).unwrap();
+        let doc1 = doc(
+            r#"<catalog><item available="true"><quantity>1</quantity><price>2.5</price><label>red  apple</label></item></catalog>"#,
+        );
+        let doc2 = doc(
+            r#"<catalog><item available="1"><quantity>01</quantity><price>2.50</price><label>red apple</label></item></catalog>"#,
+        );
+
+        // Lexically different without a schema
+        let structural = compare_documents(&doc1, &doc2, None);
+        assert_eq!(structural.changed.len(), 4);
+
+        // Equal values with the schema
+        let semantic = compare_documents(&doc1, &doc2, Some(&schema));
+        assert!(semantic.is_empty(), "unexpected differences: {:?}", semantic);
+    }
+
+    #[test]
+    fn test_semantic_differences() {
+        let schema = XsdSchema::from_string(CATALOG_XSD).unwrap();
+        let doc1 = doc(r#"<catalog><item><quantity>1</quantity><note>a  b</note></item></catalog>"#);
+        let doc2 = doc(r#"<catalog><item><quantity>2</quantity><note>a b</note></item></catalog>"#);
+
+        let diff = compare_documents(&doc1, &doc2, Some(&schema));
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.changed[0].0, "/catalog[1]/item[1]/quantity[1]/text()");
+        // xs:string preserves whitespace
+        assert_eq!(diff.changed[1].0, "/catalog[1]/item[1]/note[1]/text()");
+    }
+
+    #[test]
+    fn test_different_roots() {
+        let diff = compare_documents(&doc("<a/>"), &doc("<b/>"), None);
+        assert_eq!(diff.removed, vec!["/a[1]".to_string()]);
+        assert_eq!(diff.added, vec!["/b[1]".to_string()]);
+    }
+}
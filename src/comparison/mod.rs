@@ -1,8 +1,13 @@
 //! Comparison testing support for xmlschema-rs
 //!
 //! This module provides data structures and utilities for comparing
-//! the output of xmlschema-rs with Python xmlschema library.
+//! the output of xmlschema-rs with Python xmlschema library, as well as
+//! schema-aware comparison of XML documents and of schema versions.
 
+pub mod documents;
 pub mod schema_model;
+pub mod schemas;
 
+pub use documents::{compare_documents, DocumentDiff, XPath};
 pub use schema_model::*;
+pub use schemas::{ChangeKind, SchemaDiff};
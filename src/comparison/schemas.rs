@@ -0,0 +1,546 @@
+//! Comparison of schema versions
+//!
+//! Compares the global elements and types of two versions of a schema and
+//! classifies each change as breaking or not. A change is breaking when a
+//! document valid against the old version may be invalid against the new
+//! one: removing a component, changing a type, requiring an attribute or
+//! narrowing the occurrences of a child element.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::namespaces::QName;
+use crate::validators::globals::qname_order;
+use crate::validators::{
+    ElementType, GlobalType, GroupParticle, ModelType, Occurs, OccursCalculator,
+    XsdAttributeGroup, XsdComplexType, XsdElement, XsdGroup, XsdSchema,
+};
+
+/// A change of a global element or type between two schema versions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The type of the component, or of one of its child elements, changed
+    TypeChanged {
+        /// Name of the component or child element
+        name: QName,
+        /// Old type
+        old: String,
+        /// New type
+        new: String,
+    },
+    /// The occurrences of a child element changed; an added or removed
+    /// child element occurs zero times in the version without it
+    OccursChanged {
+        /// Name of the child element
+        name: QName,
+        /// Old occurrences
+        old: Occurs,
+        /// New occurrences
+        new: Occurs,
+    },
+    /// An attribute was added
+    AttributeAdded {
+        /// Name of the attribute
+        name: QName,
+        /// Whether the new attribute is required
+        required: bool,
+    },
+    /// An attribute was removed
+    AttributeRemoved {
+        /// Name of the attribute
+        name: QName,
+    },
+    /// The type of an attribute changed
+    AttributeTypeChanged {
+        /// Name of the attribute
+        name: QName,
+        /// Old type
+        old: String,
+        /// New type
+        new: String,
+    },
+    /// An attribute became required or optional
+    AttributeUseChanged {
+        /// Name of the attribute
+        name: QName,
+        /// Whether the attribute is now required
+        required: bool,
+    },
+}
+
+impl ChangeKind {
+    /// Check if documents valid against the old schema may be invalid against the new one
+    pub fn is_breaking(&self) -> bool {
+        match self {
+            ChangeKind::TypeChanged { .. }
+            | ChangeKind::AttributeRemoved { .. }
+            | ChangeKind::AttributeTypeChanged { .. } => true,
+            ChangeKind::OccursChanged { old, new, .. } => {
+                let narrower_max = match (old.max, new.max) {
+                    (_, None) => false,
+                    (None, Some(_)) => true,
+                    (Some(old_max), Some(new_max)) => new_max < old_max,
+                };
+                new.min > old.min || narrower_max
+            }
+            ChangeKind::AttributeAdded { required, .. } | ChangeKind::AttributeUseChanged { required, .. } => {
+                *required
+            }
+        }
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::TypeChanged { name, old, new } => {
+                write!(f, "type of '{}' changed from '{}' to '{}'", name.to_string(), old, new)
+            }
+            ChangeKind::OccursChanged { name, old, new } => write!(
+                f,
+                "occurrences of '{}' changed from {} to {}",
+                name.to_string(),
+                occurs_range(old),
+                occurs_range(new)
+            ),
+            ChangeKind::AttributeAdded { name, required: true } => {
+                write!(f, "required attribute '{}' added", name.to_string())
+            }
+            ChangeKind::AttributeAdded { name, required: false } => {
+                write!(f, "optional attribute '{}' added", name.to_string())
+            }
+            ChangeKind::AttributeRemoved { name } => write!(f, "attribute '{}' removed", name.to_string()),
+            ChangeKind::AttributeTypeChanged { name, old, new } => write!(
+                f,
+                "type of attribute '{}' changed from '{}' to '{}'",
+                name.to_string(),
+                old,
+                new
+            ),
+            ChangeKind::AttributeUseChanged { name, required: true } => {
+                write!(f, "attribute '{}' became required", name.to_string())
+            }
+            ChangeKind::AttributeUseChanged { name, required: false } => {
+                write!(f, "attribute '{}' became optional", name.to_string())
+            }
+        }
+    }
+}
+
+/// Differences between two versions of a schema
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    /// Global elements and types only present in the new version
+    pub added: Vec<QName>,
+    /// Global elements and types only present in the old version
+    pub removed: Vec<QName>,
+    /// Changes of global elements and types present in both versions
+    pub changed: Vec<(QName, ChangeKind)>,
+}
+
+impl SchemaDiff {
+    /// Create an empty diff
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if the schema versions are equivalent
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Total number of differences
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len()
+    }
+
+    /// Check if any difference is a breaking change
+    ///
+    /// Removed components and breaking changes are breaking; added
+    /// components are not.
+    pub fn is_breaking(&self) -> bool {
+        !self.removed.is_empty() || self.changed.iter().any(|(_, change)| change.is_breaking())
+    }
+}
+
+impl XsdSchema {
+    /// Compare the global elements and types of this schema (the old
+    /// version) with those of `other` (the new version)
+    ///
+    /// Elements are compared by type name, and the content of anonymous
+    /// complex types is compared under the name of their element. Child
+    /// elements of complex types are compared by their overall occurrences
+    /// in the content model, and attributes by type and use. Simple types
+    /// are compared through their facets.
+    pub fn diff(&self, other: &XsdSchema) -> SchemaDiff {
+        let mut diff = SchemaDiff::new();
+
+        let old_elements: HashMap<&QName, &XsdElement> =
+            self.elements().map(|(name, elem)| (name, elem.as_ref())).collect();
+        let new_elements: HashMap<&QName, &XsdElement> =
+            other.elements().map(|(name, elem)| (name, elem.as_ref())).collect();
+        for (name, old_elem) in &old_elements {
+            match new_elements.get(name) {
+                Some(new_elem) => compare_elements(name, old_elem, new_elem, &mut diff.changed),
+                None => diff.removed.push((*name).clone()),
+            }
+        }
+        diff.added.extend(
+            new_elements
+                .keys()
+                .filter(|name| !old_elements.contains_key(*name))
+                .map(|name| (*name).clone()),
+        );
+
+        let old_types: HashMap<&QName, &GlobalType> = self.types().collect();
+        let new_types: HashMap<&QName, &GlobalType> = other.types().collect();
+        for (name, old_type) in &old_types {
+            let Some(new_type) = new_types.get(name) else {
+                diff.removed.push((*name).clone());
+                continue;
+            };
+            match (old_type, new_type) {
+                (GlobalType::Complex(old_ct), GlobalType::Complex(new_ct)) => {
+                    compare_complex_types(name, old_ct, new_ct, &mut diff.changed);
+                    if old_ct.simple_type().is_some() || new_ct.simple_type().is_some() {
+                        compare_facets(name, self, other, &mut diff.changed);
+                    }
+                }
+                (GlobalType::Simple(_), GlobalType::Simple(_)) => {
+                    compare_facets(name, self, other, &mut diff.changed);
+                }
+                _ => diff.changed.push((
+                    (*name).clone(),
+                    ChangeKind::TypeChanged {
+                        name: (*name).clone(),
+                        old: type_kind(old_type).to_string(),
+                        new: type_kind(new_type).to_string(),
+                    },
+                )),
+            }
+        }
+        diff.added.extend(
+            new_types
+                .keys()
+                .filter(|name| !old_types.contains_key(*name))
+                .map(|name| (*name).clone()),
+        );
+
+        diff.added.sort_by(qname_order);
+        diff.removed.sort_by(qname_order);
+        diff.changed.sort_by(|(a, _), (b, _)| qname_order(a, b));
+        diff
+    }
+}
+
+/// Compare two declarations of a global element
+fn compare_elements(name: &QName, old: &XsdElement, new: &XsdElement, changes: &mut Vec<(QName, ChangeKind)>) {
+    let (old_type, new_type) = (element_type_name(old), element_type_name(new));
+    if old_type != new_type {
+        changes.push((
+            name.clone(),
+            ChangeKind::TypeChanged {
+                name: name.clone(),
+                old: old_type,
+                new: new_type,
+            },
+        ));
+        return;
+    }
+    if let (ElementType::Complex(old_ct), ElementType::Complex(new_ct)) = (&old.element_type, &new.element_type) {
+        if old_ct.name.is_none() && new_ct.name.is_none() {
+            compare_complex_types(name, old_ct, new_ct, changes);
+        }
+    }
+}
+
+/// Compare the child elements and attributes of two complex types
+fn compare_complex_types(
+    name: &QName,
+    old: &XsdComplexType,
+    new: &XsdComplexType,
+    changes: &mut Vec<(QName, ChangeKind)>,
+) {
+    let old_children = child_elements(old);
+    let new_children = child_elements(new);
+    let mut child_names: Vec<&QName> = old_children
+        .keys()
+        .chain(new_children.keys())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    child_names.sort_by(|a, b| qname_order(a, b));
+    for child in child_names {
+        let (old_occurs, old_type) = old_children.get(child).cloned().unwrap_or((Occurs::empty(), None));
+        let (new_occurs, new_type) = new_children.get(child).cloned().unwrap_or((Occurs::empty(), None));
+        if old_occurs != new_occurs {
+            changes.push((
+                name.clone(),
+                ChangeKind::OccursChanged {
+                    name: child.clone(),
+                    old: old_occurs,
+                    new: new_occurs,
+                },
+            ));
+        }
+        if let (Some(old_type), Some(new_type)) = (old_type, new_type) {
+            if old_type != new_type {
+                changes.push((
+                    name.clone(),
+                    ChangeKind::TypeChanged {
+                        name: child.clone(),
+                        old: old_type,
+                        new: new_type,
+                    },
+                ));
+            }
+        }
+    }
+    compare_attributes(name, &old.attributes, &new.attributes, changes);
+}
+
+/// Compare the attribute uses of two complex types
+fn compare_attributes(
+    name: &QName,
+    old: &XsdAttributeGroup,
+    new: &XsdAttributeGroup,
+    changes: &mut Vec<(QName, ChangeKind)>,
+) {
+    let old_attributes: HashMap<&QName, _> = old
+        .iter_attributes()
+        .filter(|attr| !attr.is_prohibited())
+        .map(|attr| (attr.name(), attr))
+        .collect();
+    let new_attributes: HashMap<&QName, _> = new
+        .iter_attributes()
+        .filter(|attr| !attr.is_prohibited())
+        .map(|attr| (attr.name(), attr))
+        .collect();
+
+    let mut old_names: Vec<&QName> = old_attributes.keys().copied().collect();
+    old_names.sort_by(|a, b| qname_order(a, b));
+    for attr_name in old_names {
+        let old_attr = old_attributes[attr_name];
+        let Some(new_attr) = new_attributes.get(attr_name) else {
+            changes.push((name.clone(), ChangeKind::AttributeRemoved { name: (*attr_name).clone() }));
+            continue;
+        };
+        let old_type = old_attr.type_name.as_ref().map(QName::to_string);
+        let new_type = new_attr.type_name.as_ref().map(QName::to_string);
+        if old_type != new_type {
+            changes.push((
+                name.clone(),
+                ChangeKind::AttributeTypeChanged {
+                    name: (*attr_name).clone(),
+                    old: old_type.unwrap_or_else(|| "anonymous".to_string()),
+                    new: new_type.unwrap_or_else(|| "anonymous".to_string()),
+                },
+            ));
+        }
+        if old_attr.is_required() != new_attr.is_required() {
+            changes.push((
+                name.clone(),
+                ChangeKind::AttributeUseChanged {
+                    name: (*attr_name).clone(),
+                    required: new_attr.is_required(),
+                },
+            ));
+        }
+    }
+    let mut new_names: Vec<&QName> = new_attributes.keys().copied().collect();
+    new_names.sort_by(|a, b| qname_order(a, b));
+    for attr_name in new_names {
+        let new_attr = new_attributes[attr_name];
+        if !old_attributes.contains_key(attr_name) {
+            changes.push((
+                name.clone(),
+                ChangeKind::AttributeAdded {
+                    name: (*attr_name).clone(),
+                    required: new_attr.is_required(),
+                },
+            ));
+        }
+    }
+}
+
+/// Compare the facets of a global type in two schemas
+fn compare_facets(name: &QName, old: &XsdSchema, new: &XsdSchema, changes: &mut Vec<(QName, ChangeKind)>) {
+    let (old_facets, new_facets) = (old.facet_summary(name), new.facet_summary(name));
+    if old_facets != new_facets {
+        changes.push((
+            name.clone(),
+            ChangeKind::TypeChanged {
+                name: name.clone(),
+                old: old_facets,
+                new: new_facets,
+            },
+        ));
+    }
+}
+
+/// The child elements of a complex type, with their overall occurrences
+/// in the content model and their type names
+fn child_elements(complex_type: &XsdComplexType) -> HashMap<QName, (Occurs, Option<String>)> {
+    let mut children = HashMap::new();
+    if let Some(group) = complex_type.model_group() {
+        collect_child_elements(group, group.occurs, &mut children);
+    }
+    children
+}
+
+fn collect_child_elements(group: &XsdGroup, occurs: Occurs, children: &mut HashMap<QName, (Occurs, Option<String>)>) {
+    for particle in &group.particles {
+        let mut particle_occurs = OccursCalculator::multiply(occurs, particle.occurs());
+        if group.model == ModelType::Choice && group.particles.len() > 1 {
+            particle_occurs.min = 0;
+        }
+        match particle {
+            GroupParticle::Element(ep) => {
+                let type_name = ep.element_decl.as_deref().map(element_type_name);
+                children
+                    .entry(ep.name.clone())
+                    .and_modify(|(total, _)| *total = OccursCalculator::add(*total, particle_occurs))
+                    .or_insert((particle_occurs, type_name));
+            }
+            GroupParticle::Group(nested) => collect_child_elements(nested, particle_occurs, children),
+            GroupParticle::Any(_) => {}
+        }
+    }
+}
+
+/// The type name of an element declaration, `anonymous` for local types
+fn element_type_name(elem: &XsdElement) -> String {
+    if let Some(type_name) = &elem.type_name {
+        return type_name.to_string();
+    }
+    match &elem.element_type {
+        ElementType::Complex(complex_type) => complex_type
+            .name
+            .as_ref()
+            .map(QName::to_string)
+            .unwrap_or_else(|| "anonymous".to_string()),
+        ElementType::Simple(simple_type) => simple_type
+            .qualified_name_string()
+            .unwrap_or_else(|| "anonymous".to_string()),
+        ElementType::Any => "xs:anyType".to_string(),
+    }
+}
+
+fn type_kind(global_type: &GlobalType) -> &'static str {
+    match global_type {
+        GlobalType::Simple(_) => "simple type",
+        GlobalType::Complex(_) => "complex type",
+    }
+}
+
+/// The occurrences of a child element, as `min..max`
+fn occurs_range(occurs: &Occurs) -> String {
+    match occurs.max {
+        Some(max) => format!("{}..{}", occurs.min, max),
+        None => format!("{}..unbounded", occurs.min),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDER_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="order" type="orderType"/>
+  <xs:complexType name="orderType">
+    <xs:sequence>
+      <xs:element name="item" type="xs:string" maxOccurs="unbounded"/>
+    </xs:sequence>
+    <xs:attribute name="id" type="xs:string" use="required"/>
+  </xs:complexType>
+</xs:schema>"#;
+
+    fn schema(xsd: &str) -> XsdSchema {
+        XsdSchema::from_string(xsd).unwrap()
+    }
+
+    fn order_type() -> QName {
+        QName::local("orderType")
+    }
+
+    #[test]
+    fn test_identical_schemas() {
+        let diff = schema(ORDER_XSD).diff(&schema(ORDER_XSD));
+        assert!(diff.is_empty());
+        assert!(!diff.is_breaking());
+    }
+
+    #[test]
+    fn test_required_attribute_is_breaking() {
+        let new = ORDER_XSD.replace(
+            r#"<xs:attribute name="id""#,
+            r#"<xs:attribute name="date" type="xs:date" use="required"/>
+    <xs:attribute name="id""#,
+        );
+        let diff = schema(ORDER_XSD).diff(&schema(&new));
+
+        assert_eq!(
+            diff.changed,
+            vec![(
+                order_type(),
+                ChangeKind::AttributeAdded {
+                    name: QName::local("date"),
+                    required: true
+                }
+            )]
+        );
+        assert!(diff.is_breaking());
+        assert_eq!(diff.changed[0].1.to_string(), "required attribute 'date' added");
+
+        let optional = new.replace(r#"type="xs:date" use="required""#, r#"type="xs:date""#);
+        assert!(!schema(ORDER_XSD).diff(&schema(&optional)).is_breaking());
+    }
+
+    #[test]
+    fn test_optional_element_is_not_breaking() {
+        let new = ORDER_XSD.replace(
+            r#"maxOccurs="unbounded"/>"#,
+            r#"maxOccurs="unbounded"/>
+      <xs:element name="note" type="xs:string" minOccurs="0"/>"#,
+        );
+        let diff = schema(ORDER_XSD).diff(&schema(&new));
+
+        assert_eq!(
+            diff.changed,
+            vec![(
+                order_type(),
+                ChangeKind::OccursChanged {
+                    name: QName::local("note"),
+                    old: Occurs::empty(),
+                    new: Occurs::optional()
+                }
+            )]
+        );
+        assert!(!diff.is_breaking());
+
+        // The reverse change removes an element that documents may contain
+        assert!(schema(&new).diff(&schema(ORDER_XSD)).is_breaking());
+
+        let required = new.replace(r#" minOccurs="0"/>"#, "/>");
+        assert!(schema(ORDER_XSD).diff(&schema(&required)).is_breaking());
+    }
+
+    #[test]
+    fn test_global_components() {
+        let new = ORDER_XSD
+            .replace(r#"<xs:element name="order" type="orderType"/>"#, r#"<xs:element name="invoice" type="orderType"/>"#)
+            .replace(r#"name="item" type="xs:string""#, r#"name="item" type="xs:token""#);
+        let diff = schema(ORDER_XSD).diff(&schema(&new));
+
+        assert_eq!(diff.added, vec![QName::local("invoice")]);
+        assert_eq!(diff.removed, vec![QName::local("order")]);
+        assert_eq!(diff.changed.len(), 1);
+        assert!(matches!(
+            &diff.changed[0].1,
+            ChangeKind::TypeChanged { name, .. } if name.local_name == "item"
+        ));
+        assert!(diff.is_breaking());
+        assert_eq!(diff.len(), 3);
+    }
+}
@@ -39,8 +39,8 @@ impl BadgerFishConverter {
     /// Create a new BadgerFish converter
     pub fn new() -> Self {
         let config = ConverterConfig::new()
-            .with_attr_prefix(Some("@".to_string()))
-            .with_text_key(Some("$".to_string()))
+            .with_attr_prefix("@")
+            .with_text_key("$")
             .with_cdata_prefix(Some("$".to_string()));
 
         Self { config }
@@ -43,6 +43,12 @@ pub struct ConverterConfig {
     process_namespaces: bool,
     /// Whether to strip namespaces
     strip_namespaces: bool,
+    /// Preferred prefixes for namespace URIs (URI -> prefix)
+    namespace_prefixes: HashMap<String, String>,
+    /// Tag of the elements that hold array items
+    array_item_tag: Option<String>,
+    /// Tag of the element that wraps array items
+    array_wrapper: Option<String>,
 }
 
 impl Default for ConverterConfig {
@@ -58,6 +64,9 @@ impl Default for ConverterConfig {
             xmlns_processing: XmlnsProcessing::default(),
             process_namespaces: true,
             strip_namespaces: false,
+            namespace_prefixes: HashMap::new(),
+            array_item_tag: None,
+            array_wrapper: None,
         }
     }
 }
@@ -108,15 +117,54 @@ impl ConverterConfig {
         self.xmlns_processing
     }
 
-    /// Set text key
-    pub fn with_text_key(mut self, key: Option<String>) -> Self {
-        self.text_key = key;
+    /// Check if namespaces should be stripped
+    pub fn strip_namespaces(&self) -> bool {
+        self.strip_namespaces
+    }
+
+    /// Get the preferred prefixes for namespace URIs
+    pub fn namespace_prefixes(&self) -> &HashMap<String, String> {
+        &self.namespace_prefixes
+    }
+
+    /// Get the preferred prefix for a namespace URI
+    pub fn preferred_prefix(&self, uri: &str) -> Option<&str> {
+        self.namespace_prefixes.get(uri).map(String::as_str)
+    }
+
+    /// Get the tag of the elements that hold array items
+    pub fn array_item_tag(&self) -> &str {
+        self.array_item_tag.as_deref().unwrap_or("item")
+    }
+
+    /// Get the tag of the element that wraps array items
+    pub fn array_wrapper(&self) -> Option<&str> {
+        self.array_wrapper.as_deref()
+    }
+
+    /// Check if array items are encoded as item elements
+    ///
+    /// When disabled, an array is encoded as repeated elements named after
+    /// its key.
+    pub fn wraps_array_items(&self) -> bool {
+        self.array_item_tag.is_some() || self.array_wrapper.is_some()
+    }
+
+    /// Set the key of text content, e.g. `$` or `#text`
+    pub fn with_text_key(mut self, key: impl Into<String>) -> Self {
+        self.text_key = Some(key.into());
         self
     }
 
-    /// Set attribute prefix
-    pub fn with_attr_prefix(mut self, prefix: Option<String>) -> Self {
-        self.attr_prefix = prefix;
+    /// Set the prefix of attribute names, e.g. `@` or `-`
+    pub fn with_attr_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.attr_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Unset the attribute prefix, for conventions that drop attributes
+    pub fn without_attr_prefix(mut self) -> Self {
+        self.attr_prefix = None;
         self
     }
 
@@ -155,6 +203,46 @@ impl ConverterConfig {
         self.xmlns_processing = mode;
         self
     }
+
+    /// Set whether namespace information is omitted from the output
+    ///
+    /// When enabled, xmlns declarations are dropped and prefixed names are
+    /// reduced to their local part.
+    pub fn with_strip_namespaces(mut self, strip: bool) -> Self {
+        self.strip_namespaces = strip;
+        self
+    }
+
+    /// Set preferred prefixes for namespace URIs
+    ///
+    /// The map is keyed by namespace URI, so `http://example.com -> ex`
+    /// makes that namespace always appear with the `ex:` prefix, whatever
+    /// prefix the document used. An empty prefix maps to the default
+    /// namespace. Unprefixed names are left unchanged.
+    pub fn with_namespace_prefix_map(mut self, preferred_prefixes: HashMap<String, String>) -> Self {
+        self.namespace_prefixes = preferred_prefixes;
+        self
+    }
+
+    /// Set the tag of the elements that hold array items
+    ///
+    /// `{"list": [1, 2]}` is then encoded as
+    /// `<list><item>1</item><item>2</item></list>` instead of repeated
+    /// `<list>` elements.
+    pub fn with_array_item_tag(mut self, tag: &str) -> Self {
+        self.array_item_tag = Some(tag.to_string());
+        self
+    }
+
+    /// Set the tag of an element that wraps array items
+    ///
+    /// `{"list": [1, 2]}` is then encoded as
+    /// `<list><wrapper><item>1</item><item>2</item></wrapper></list>`.
+    /// The item tag defaults to `item`.
+    pub fn with_array_wrapper(mut self, wrapper_tag: &str) -> Self {
+        self.array_wrapper = Some(wrapper_tag.to_string());
+        self
+    }
 }
 
 /// Content item in element data
@@ -339,16 +427,82 @@ impl XmlSchemaConverter {
     }
 
     /// Map a qname using namespace mappings
+    ///
+    /// Prefixed (`pfx:name`) and extended (`{uri}name`) names are rewritten
+    /// with the preferred prefix of their namespace, or reduced to the local
+    /// name when namespaces are stripped.
     pub fn map_qname(&self, name: &str) -> String {
-        // For now, just return the name as-is
-        // Full implementation would handle namespace prefix mapping
-        name.to_string()
+        self.map_name(name, &[])
+    }
+
+    /// Map a name, resolving prefixes against `xmlns` before the converter's
+    /// namespace mappings
+    fn map_name(&self, name: &str, xmlns: &[(String, String)]) -> String {
+        let (uri, local) = if let Some(rest) = name.strip_prefix('{') {
+            match rest.split_once('}') {
+                Some((uri, local)) => (Some(uri), local),
+                None => return name.to_string(),
+            }
+        } else if let Some((prefix, local)) = name.split_once(':') {
+            let uri = xmlns
+                .iter()
+                .rev()
+                .find(|(p, _)| p == prefix)
+                .map(|(_, uri)| uri.as_str())
+                .or_else(|| self.namespaces.get(prefix).map(String::as_str));
+            (uri, local)
+        } else {
+            return name.to_string();
+        };
+
+        if self.config.strip_namespaces {
+            return local.to_string();
+        }
+        match uri.and_then(|uri| self.config.preferred_prefix(uri)) {
+            Some("") => local.to_string(),
+            Some(prefix) => format!("{}:{}", prefix, local),
+            None => name.to_string(),
+        }
     }
 
     /// Unmap a qname back to qualified form
     pub fn unmap_qname(&self, name: &str) -> String {
         name.to_string()
     }
+
+    /// Add a child element, or one element per item for an array value
+    ///
+    /// When array items are wrapped, the array is kept as the value of a
+    /// single child and expanded into item elements when that child is
+    /// encoded.
+    fn push_child(&self, data: &mut ElementData, key: &str, value: &JsonValue) {
+        match value {
+            JsonValue::Array(arr) if !self.config.wraps_array_items() => {
+                for item in arr {
+                    data.content.push(ContentItem::Element(key.to_string(), item.clone()));
+                }
+            }
+            _ => data.content.push(ContentItem::Element(key.to_string(), value.clone())),
+        }
+    }
+
+    /// Recover an array from the decoded value of an element holding
+    /// wrapped array items
+    fn unwrap_array_items(&self, value: &JsonValue) -> Option<JsonValue> {
+        let single_child = |value: &JsonValue, tag: &str| match value {
+            JsonValue::Object(obj) if obj.len() == 1 => obj.get(tag).cloned(),
+            _ => None,
+        };
+
+        let value = match self.config.array_wrapper() {
+            Some(wrapper) => single_child(value, wrapper)?,
+            None => value.clone(),
+        };
+        match single_child(&value, self.config.array_item_tag())? {
+            JsonValue::Array(items) => Some(JsonValue::Array(items)),
+            item => Some(JsonValue::Array(vec![item])),
+        }
+    }
 }
 
 impl JsonConverter for XmlSchemaConverter {
@@ -358,8 +512,13 @@ impl JsonConverter for XmlSchemaConverter {
         let text_key = self.config.text_key();
 
         // Add xmlns declarations if at root and processing namespaces
-        if level == 0 && self.config.process_namespaces && !data.xmlns.is_empty() {
+        if level == 0
+            && self.config.process_namespaces
+            && !self.config.strip_namespaces
+            && !data.xmlns.is_empty()
+        {
             for (prefix, uri) in &data.xmlns {
+                let prefix = self.config.preferred_prefix(uri).unwrap_or(prefix);
                 let key = if prefix.is_empty() {
                     format!("{}xmlns", attr_prefix)
                 } else {
@@ -371,7 +530,7 @@ impl JsonConverter for XmlSchemaConverter {
 
         // Add attributes
         for (name, value) in &data.attributes {
-            let key = format!("{}{}", attr_prefix, self.map_qname(name));
+            let key = format!("{}{}", attr_prefix, self.map_name(name, &data.xmlns));
             result.insert(key, JsonValue::String(value.clone()));
         }
 
@@ -382,7 +541,7 @@ impl JsonConverter for XmlSchemaConverter {
                 if result.is_empty() && !self.config.force_dict {
                     if level == 0 && self.config.preserve_root {
                         let mut wrapper = Map::new();
-                        wrapper.insert(self.map_qname(data.tag()), JsonValue::String(text.clone()));
+                        wrapper.insert(self.map_name(data.tag(), &data.xmlns), JsonValue::String(text.clone()));
                         return JsonValue::Object(wrapper);
                     }
                     return JsonValue::String(text.clone());
@@ -398,7 +557,13 @@ impl JsonConverter for XmlSchemaConverter {
             for item in &data.content {
                 match item {
                     ContentItem::Element(name, value) => {
-                        let key = self.map_qname(name);
+                        let key = self.map_name(name, &data.xmlns);
+                        let unwrapped = self
+                            .config
+                            .wraps_array_items()
+                            .then(|| self.unwrap_array_items(value))
+                            .flatten();
+                        let value = unwrapped.as_ref().unwrap_or(value);
                         if let Some(existing) = result.get_mut(&key) {
                             // Convert to array if not already
                             if let JsonValue::Array(arr) = existing {
@@ -426,7 +591,7 @@ impl JsonConverter for XmlSchemaConverter {
         if level == 0 && self.config.preserve_root {
             let mut wrapper = Map::new();
             wrapper.insert(
-                self.map_qname(data.tag()),
+                self.map_name(data.tag(), &data.xmlns),
                 if result.is_empty() {
                     JsonValue::Null
                 } else {
@@ -488,39 +653,28 @@ impl JsonConverter for XmlSchemaConverter {
                             }
                         } else {
                             // Child element
-                            match val {
-                                JsonValue::Array(arr) => {
-                                    for item in arr {
-                                        data.content
-                                            .push(ContentItem::Element(key.clone(), item.clone()));
-                                    }
-                                }
-                                _ => {
-                                    data.content
-                                        .push(ContentItem::Element(key.clone(), val.clone()));
-                                }
-                            }
+                            self.push_child(&mut data, key, val);
                         }
                     } else {
                         // Child element
-                        match val {
-                            JsonValue::Array(arr) => {
-                                for item in arr {
-                                    data.content
-                                        .push(ContentItem::Element(key.clone(), item.clone()));
-                                }
-                            }
-                            _ => {
-                                data.content
-                                    .push(ContentItem::Element(key.clone(), val.clone()));
-                            }
-                        }
+                        self.push_child(&mut data, key, val);
                     }
                 }
             }
             JsonValue::String(s) => {
                 data.text = Some(s.clone());
             }
+            JsonValue::Array(arr) if self.config.wraps_array_items() => {
+                match self.config.array_wrapper() {
+                    Some(wrapper) if tag != wrapper => self.push_child(&mut data, wrapper, value),
+                    _ => {
+                        let item_tag = self.config.array_item_tag();
+                        for item in arr {
+                            data.content.push(ContentItem::Element(item_tag.to_string(), item.clone()));
+                        }
+                    }
+                }
+            }
             JsonValue::Null => {}
             _ => {
                 data.text = Some(value.to_string());
@@ -537,7 +691,7 @@ impl JsonConverter for XmlSchemaConverter {
     }
 
     fn loses_xmlns(&self) -> bool {
-        !self.config.process_namespaces
+        !self.config.process_namespaces || self.config.strip_namespaces
     }
 }
 
@@ -549,8 +703,8 @@ mod tests {
     #[test]
     fn test_converter_config_builder() {
         let config = ConverterConfig::new()
-            .with_text_key(Some("#text".to_string()))
-            .with_attr_prefix(Some("-".to_string()))
+            .with_text_key("#text")
+            .with_attr_prefix("-")
             .with_preserve_root(true)
             .with_indent(2);
 
@@ -599,6 +753,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_xml_schema_converter_custom_keys() {
+        let config = ConverterConfig::new().with_text_key("#text").with_attr_prefix("-");
+        let converter = XmlSchemaConverter::with_config(config);
+        let data = ElementData::new("element")
+            .with_text("value")
+            .with_attribute("id", "1");
+
+        let json = converter.decode(&data, 0);
+        assert_eq!(json, json!({"-id": "1", "#text": "value"}));
+
+        let encoded = converter.encode(&json, "element", 0);
+        assert_eq!(encoded.text(), Some("value"));
+        assert_eq!(encoded.attributes().get("id"), Some(&"1".to_string()));
+    }
+
     #[test]
     fn test_xml_schema_converter_with_children() {
         let converter = XmlSchemaConverter::new();
@@ -675,6 +845,127 @@ mod tests {
         assert!(!converter.is_lossy());
     }
 
+    #[test]
+    fn test_xml_schema_converter_preferred_prefixes() {
+        let prefixes = HashMap::from([("http://example.com".to_string(), "ex".to_string())]);
+        let config = ConverterConfig::new().with_namespace_prefix_map(prefixes);
+        let converter = XmlSchemaConverter::with_config(config);
+        let data = ElementData::new("doc:root")
+            .with_xmlns("doc", "http://example.com")
+            .with_xmlns("", "http://example.com/default")
+            .with_attribute("doc:id", "1")
+            .with_attribute("lang", "en")
+            .with_child("doc:item", json!("value"))
+            .with_child("{http://example.com}note", json!("text"));
+
+        let json = converter.decode(&data, 0);
+        assert_eq!(
+            json,
+            json!({
+                "@xmlns:ex": "http://example.com",
+                "@xmlns": "http://example.com/default",
+                "@ex:id": "1",
+                "@lang": "en",
+                "ex:item": "value",
+                "ex:note": "text",
+            })
+        );
+
+        // Prefixes bound only in the converter's namespace mappings
+        let converter = converter
+            .with_namespaces(HashMap::from([("d".to_string(), "http://example.com".to_string())]));
+        assert_eq!(converter.map_qname("d:item"), "ex:item");
+        assert_eq!(converter.map_qname("x:item"), "x:item");
+    }
+
+    #[test]
+    fn test_xml_schema_converter_strip_namespaces() {
+        let config = ConverterConfig::new().with_strip_namespaces(true);
+        let converter = XmlSchemaConverter::with_config(config);
+        assert!(converter.loses_xmlns());
+
+        let data = ElementData::new("doc:root")
+            .with_xmlns("doc", "http://example.com")
+            .with_attribute("doc:id", "1")
+            .with_child("doc:item", json!("value"))
+            .with_child("{http://example.com}note", json!("text"));
+
+        let json = converter.decode(&data, 0);
+        assert_eq!(json, json!({"@id": "1", "item": "value", "note": "text"}));
+    }
+
+    /// Encode a value into a tree of element data and decode it back
+    fn round_trip(converter: &XmlSchemaConverter, value: &JsonValue, tag: &str, level: usize) -> JsonValue {
+        let mut data = converter.encode(value, tag, level);
+        for item in &mut data.content {
+            if let ContentItem::Element(name, child) = item {
+                *child = round_trip(converter, child, name, level + 1);
+            }
+        }
+        converter.decode(&data, level)
+    }
+
+    /// Describe the element structure produced by encoding a value
+    fn element_tree(converter: &XmlSchemaConverter, value: &JsonValue, tag: &str) -> String {
+        let data = converter.encode(value, tag, 1);
+        let children: String = data
+            .content()
+            .iter()
+            .map(|item| match item {
+                ContentItem::Element(name, child) => element_tree(converter, child, name),
+                ContentItem::CData(_, text) => text.clone(),
+            })
+            .collect();
+        format!("<{tag}>{}{children}</{tag}>", data.text().unwrap_or(""))
+    }
+
+    #[test]
+    fn test_xml_schema_converter_array_items() {
+        let value = json!({"list": [1, 2, 3]});
+
+        let converter = XmlSchemaConverter::new();
+        assert_eq!(
+            element_tree(&converter, &value, "root"),
+            "<root><list>1</list><list>2</list><list>3</list></root>"
+        );
+
+        let config = ConverterConfig::new().with_array_item_tag("item");
+        let converter = XmlSchemaConverter::with_config(config);
+        assert_eq!(
+            element_tree(&converter, &value, "root"),
+            "<root><list><item>1</item><item>2</item><item>3</item></list></root>"
+        );
+
+        let config = ConverterConfig::new().with_array_wrapper("values");
+        let converter = XmlSchemaConverter::with_config(config);
+        assert_eq!(converter.config().array_item_tag(), "item");
+        assert_eq!(
+            element_tree(&converter, &value, "root"),
+            "<root><list><values><item>1</item><item>2</item><item>3</item></values></list></root>"
+        );
+    }
+
+    #[test]
+    fn test_xml_schema_converter_array_round_trip() {
+        let value = json!({
+            "@id": "1",
+            "title": "Lists",
+            "tags": ["a", "b"],
+            "single": ["only"],
+            "rows": [{"@n": "1", "cell": ["x", "y"]}, {"@n": "2", "cell": ["z"]}],
+        });
+
+        let configs = [
+            ConverterConfig::new().with_array_item_tag("entry"),
+            ConverterConfig::new().with_array_wrapper("items"),
+            ConverterConfig::new().with_array_item_tag("li").with_array_wrapper("ul"),
+        ];
+        for config in configs {
+            let converter = XmlSchemaConverter::with_config(config);
+            assert_eq!(round_trip(&converter, &value, "root", 0), value);
+        }
+    }
+
     #[test]
     fn test_xmlns_processing_default() {
         assert_eq!(XmlnsProcessing::default(), XmlnsProcessing::Stacked);
@@ -0,0 +1,253 @@
+//! Columnar Converter
+//!
+//! Implements a columnar conversion for data-table XML, where an element
+//! holds a sequence of rows with the same child elements:
+//! - The rows are pivoted into an object of parallel arrays, keyed by the
+//!   name of the row children
+//! - Attributes of the table and of the rows are dropped
+//! - Any other content is converted with the default convention
+//!
+//! For example `<table><row><a>1</a><b>x</b></row><row><a>2</a><b>y</b></row></table>`
+//! is decoded as `{"a": ["1", "2"], "b": ["x", "y"]}`.
+
+use serde_json::{Map, Value as JsonValue};
+use std::collections::BTreeSet;
+
+use super::base::{ContentItem, ConverterConfig, ElementData, XmlSchemaConverter};
+use super::JsonConverter;
+
+/// Columnar converter
+///
+/// A lossy converter that pivots homogeneous rows into parallel arrays,
+/// falling back to [`XmlSchemaConverter`] for heterogeneous content.
+#[derive(Debug, Clone)]
+pub struct ColumnarConverter {
+    fallback: XmlSchemaConverter,
+    row_tag: String,
+}
+
+impl Default for ColumnarConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ColumnarConverter {
+    /// Create a new columnar converter
+    pub fn new() -> Self {
+        Self::with_config(ConverterConfig::default())
+    }
+
+    /// Create with the configuration of the fallback converter
+    pub fn with_config(config: ConverterConfig) -> Self {
+        Self {
+            fallback: XmlSchemaConverter::with_config(config),
+            row_tag: "row".to_string(),
+        }
+    }
+
+    /// Set the tag of the row elements created when encoding columns
+    pub fn with_row_tag(mut self, tag: impl Into<String>) -> Self {
+        self.row_tag = tag.into();
+        self
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &ConverterConfig {
+        self.fallback.config()
+    }
+
+    /// Get the tag of the row elements created when encoding columns
+    pub fn row_tag(&self) -> &str {
+        &self.row_tag
+    }
+
+    /// Column names of a decoded row, without its attributes
+    fn row_columns<'a>(&self, row: &'a JsonValue) -> Option<BTreeSet<&'a str>> {
+        let attr_prefix = self.config().attr_prefix();
+        match row {
+            JsonValue::Object(obj) => Some(
+                obj.keys()
+                    .map(String::as_str)
+                    .filter(|key| !key.starts_with(attr_prefix))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Pivot the children of an element into parallel arrays
+    ///
+    /// Returns None unless the element only holds two or more rows with
+    /// the same tag and the same set of children.
+    fn pivot(&self, data: &ElementData) -> Option<JsonValue> {
+        if data.text().is_some_and(|text| !text.trim().is_empty()) {
+            return None;
+        }
+
+        let mut row_tag = None;
+        let mut rows = Vec::new();
+        for item in data.content() {
+            match item {
+                ContentItem::Element(name, value) => {
+                    if *row_tag.get_or_insert(name) != name {
+                        return None;
+                    }
+                    rows.push(value);
+                }
+                ContentItem::CData(_, text) if text.trim().is_empty() => {}
+                ContentItem::CData(..) => return None,
+            }
+        }
+
+        if rows.len() < 2 {
+            return None;
+        }
+        let columns = self.row_columns(rows[0])?;
+        if columns.is_empty() {
+            return None;
+        }
+        let mut cells = vec![Vec::with_capacity(rows.len()); columns.len()];
+        for row in &rows {
+            if self.row_columns(row).as_ref() != Some(&columns) {
+                return None;
+            }
+            for (values, column) in cells.iter_mut().zip(&columns) {
+                values.push(row[*column].clone());
+            }
+        }
+        let result = columns
+            .into_iter()
+            .zip(cells)
+            .map(|(column, values)| (column.to_string(), JsonValue::Array(values)))
+            .collect();
+        Some(JsonValue::Object(result))
+    }
+
+    /// Unpivot an object of parallel arrays of the same length into rows
+    fn unpivot(&self, obj: &Map<String, JsonValue>) -> Option<Vec<JsonValue>> {
+        let mut row_count = None;
+        for value in obj.values() {
+            let len = value.as_array()?.len();
+            if *row_count.get_or_insert(len) != len {
+                return None;
+            }
+        }
+
+        let rows = (0..row_count?)
+            .map(|i| {
+                let row = obj
+                    .iter()
+                    .map(|(column, values)| (column.clone(), values[i].clone()))
+                    .collect();
+                JsonValue::Object(row)
+            })
+            .collect();
+        Some(rows)
+    }
+}
+
+impl JsonConverter for ColumnarConverter {
+    fn decode(&self, data: &ElementData, level: usize) -> JsonValue {
+        match self.pivot(data) {
+            Some(columns) => columns,
+            None => self.fallback.decode(data, level),
+        }
+    }
+
+    fn encode(&self, value: &JsonValue, tag: &str, level: usize) -> ElementData {
+        if let JsonValue::Object(obj) = value {
+            let attr_prefix = self.config().attr_prefix();
+            let is_columns = !obj.is_empty() && obj.keys().all(|key| !key.starts_with(attr_prefix));
+            if let Some(rows) = is_columns.then(|| self.unpivot(obj)).flatten() {
+                let mut data = ElementData::new(tag);
+                for row in rows {
+                    data.content.push(ContentItem::Element(self.row_tag.clone(), row));
+                }
+                return data;
+            }
+        }
+        self.fallback.encode(value, tag, level)
+    }
+
+    fn is_lossy(&self) -> bool {
+        true // Attributes of the pivoted rows are dropped
+    }
+
+    fn loses_xmlns(&self) -> bool {
+        self.fallback.loses_xmlns()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_columnar_pivot() {
+        let converter = ColumnarConverter::new();
+        let data = ElementData::new("table")
+            .with_attribute("name", "t1")
+            .with_child("row", json!({"@id": "1", "a": "1", "b": "x"}))
+            .with_child("row", json!({"@id": "2", "a": "2", "b": "y"}))
+            .with_child("row", json!({"@id": "3", "a": "3", "b": "z"}));
+
+        let json = converter.decode(&data, 0);
+        assert_eq!(json, json!({"a": ["1", "2", "3"], "b": ["x", "y", "z"]}));
+    }
+
+    #[test]
+    fn test_columnar_heterogeneous_fallback() {
+        let converter = ColumnarConverter::new();
+        let default = XmlSchemaConverter::new();
+
+        // Rows with different children
+        let data = ElementData::new("table")
+            .with_child("row", json!({"a": "1", "b": "x"}))
+            .with_child("row", json!({"a": "2"}));
+        assert_eq!(converter.decode(&data, 0), default.decode(&data, 0));
+        assert_eq!(converter.decode(&data, 0), json!({"row": [{"a": "1", "b": "x"}, {"a": "2"}]}));
+
+        // Children with different tags
+        let data = ElementData::new("book")
+            .with_child("title", json!("Rust"))
+            .with_child("author", json!("Someone"));
+        assert_eq!(converter.decode(&data, 0), json!({"title": "Rust", "author": "Someone"}));
+
+        // A single child isn't a repeated row
+        let data = ElementData::new("order").with_child("address", json!({"city": "Paris"}));
+        assert_eq!(converter.decode(&data, 0), json!({"address": {"city": "Paris"}}));
+
+        // Simple content
+        let data = ElementData::new("a").with_text("1");
+        assert_eq!(converter.decode(&data, 0), json!("1"));
+    }
+
+    #[test]
+    fn test_columnar_encode() {
+        let converter = ColumnarConverter::new().with_row_tag("record");
+        let data = converter.encode(&json!({"a": ["1", "2"], "b": ["x", "y"]}), "table", 0);
+
+        assert_eq!(data.tag(), "table");
+        assert_eq!(data.content().len(), 2);
+        match &data.content()[1] {
+            ContentItem::Element(name, value) => {
+                assert_eq!(name, "record");
+                assert_eq!(value, &json!({"a": "2", "b": "y"}));
+            }
+            _ => panic!("Expected row element"),
+        }
+
+        // Columns of different lengths aren't a table
+        let data = converter.encode(&json!({"a": ["1", "2"], "b": ["x"]}), "table", 0);
+        assert_eq!(data.content().len(), 3);
+    }
+
+    #[test]
+    fn test_columnar_is_lossy() {
+        let converter = ColumnarConverter::new();
+        assert!(converter.is_lossy());
+        assert!(!converter.loses_xmlns());
+    }
+}
@@ -0,0 +1,277 @@
+//! JsonML Converter
+//!
+//! Implements the JSON Markup Language convention for XML to JSON conversion.
+//!
+//! References:
+//! - http://www.jsonml.org/
+//!
+//! Each element is an array `[tag, {attributes}, children...]`:
+//! - The tag and attribute names keep their prefixes
+//! - Namespace declarations are `xmlns` and `xmlns:prefix` attributes
+//! - The attributes object is omitted when there are no attributes
+//! - Children are strings for character data and arrays for elements,
+//!   in document order
+
+use serde_json::{Map, Value as JsonValue};
+
+use super::base::{ContentItem, ElementData};
+use super::JsonConverter;
+
+/// JsonML convention converter
+///
+/// A lossless converter that keeps the document order of mixed content.
+/// Text before the first child element is decoded from the element text,
+/// and encoded back as character data when the element has child elements.
+#[derive(Debug, Clone, Default)]
+pub struct JsonMlConverter;
+
+impl JsonMlConverter {
+    /// Create a new JsonML converter
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// JsonML array of a child element value
+    ///
+    /// Values that are already JsonML arrays for the child are used as is,
+    /// other values are taken as the text of the child.
+    fn child_element(name: &str, value: &JsonValue) -> JsonValue {
+        match value {
+            JsonValue::Array(arr) if arr.first().and_then(JsonValue::as_str) == Some(name) => value.clone(),
+            JsonValue::Null => JsonValue::Array(vec![JsonValue::String(name.to_string())]),
+            JsonValue::String(s) => JsonValue::Array(vec![
+                JsonValue::String(name.to_string()),
+                JsonValue::String(s.clone()),
+            ]),
+            _ => JsonValue::Array(vec![
+                JsonValue::String(name.to_string()),
+                JsonValue::String(value.to_string()),
+            ]),
+        }
+    }
+}
+
+impl JsonConverter for JsonMlConverter {
+    fn decode(&self, data: &ElementData, _level: usize) -> JsonValue {
+        let mut result = vec![JsonValue::String(data.tag().to_string())];
+
+        // Namespace declarations and attributes, prefixes preserved
+        let mut attrs = Map::new();
+        for (prefix, uri) in data.xmlns() {
+            let key = if prefix.is_empty() {
+                "xmlns".to_string()
+            } else {
+                format!("xmlns:{}", prefix)
+            };
+            attrs.insert(key, JsonValue::String(uri.clone()));
+        }
+        for (name, value) in data.attributes() {
+            attrs.insert(name.clone(), JsonValue::String(value.clone()));
+        }
+        if !attrs.is_empty() {
+            result.push(JsonValue::Object(attrs));
+        }
+
+        if let Some(text) = data.text() {
+            result.push(JsonValue::String(text.to_string()));
+        }
+        for item in data.content() {
+            match item {
+                ContentItem::Element(name, value) => result.push(Self::child_element(name, value)),
+                ContentItem::CData(_, text) => result.push(JsonValue::String(text.clone())),
+            }
+        }
+
+        JsonValue::Array(result)
+    }
+
+    fn encode(&self, value: &JsonValue, tag: &str, _level: usize) -> ElementData {
+        let arr = match value {
+            JsonValue::Array(arr) => arr,
+            JsonValue::String(s) => return ElementData::new(tag).with_text(s.clone()),
+            _ => return ElementData::new(tag),
+        };
+
+        let mut items = arr.iter().peekable();
+        let tag = match items.peek() {
+            Some(JsonValue::String(name)) => {
+                items.next();
+                name.as_str()
+            }
+            _ => tag,
+        };
+        let mut data = ElementData::new(tag);
+
+        if let Some(JsonValue::Object(attrs)) = items.peek() {
+            for (name, val) in attrs {
+                let val = match val {
+                    JsonValue::String(s) => s.clone(),
+                    _ => val.to_string(),
+                };
+                if name == "xmlns" {
+                    data.xmlns.push((String::new(), val));
+                } else if let Some(prefix) = name.strip_prefix("xmlns:") {
+                    data.xmlns.push((prefix.to_string(), val));
+                } else {
+                    data.attributes.insert(name.clone(), val);
+                }
+            }
+            items.next();
+        }
+
+        let children: Vec<&JsonValue> = items.collect();
+        let has_elements = children.iter().any(|child| child.is_array());
+        let mut cdata_index = 0;
+        for child in children {
+            match child {
+                JsonValue::Array(child_arr) => {
+                    let name = match child_arr.first() {
+                        Some(JsonValue::String(name)) => name.clone(),
+                        _ => continue,
+                    };
+                    data.content.push(ContentItem::Element(name, child.clone()));
+                }
+                JsonValue::Object(_) => {}
+                _ => {
+                    let text = match child {
+                        JsonValue::String(s) => s.clone(),
+                        _ => child.to_string(),
+                    };
+                    if has_elements {
+                        data.content.push(ContentItem::CData(cdata_index, text));
+                        cdata_index += 1;
+                    } else {
+                        // Simple content, adjacent strings are concatenated
+                        data.text.get_or_insert_with(String::new).push_str(&text);
+                    }
+                }
+            }
+        }
+
+        data
+    }
+
+    fn is_lossy(&self) -> bool {
+        false // JsonML preserves attributes and content order
+    }
+
+    fn loses_xmlns(&self) -> bool {
+        false // Namespace declarations are kept as attributes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Encode a JsonML value and decode it again, recursing into children
+    fn round_trip(converter: &JsonMlConverter, value: &JsonValue) -> JsonValue {
+        let mut data = converter.encode(value, "", 0);
+        for item in &mut data.content {
+            if let ContentItem::Element(_, child) = item {
+                *child = round_trip(converter, child);
+            }
+        }
+        converter.decode(&data, 0)
+    }
+
+    #[test]
+    fn test_jsonml_simple_text() {
+        let converter = JsonMlConverter::new();
+        let data = ElementData::new("element").with_text("value");
+
+        assert_eq!(converter.decode(&data, 0), json!(["element", "value"]));
+    }
+
+    #[test]
+    fn test_jsonml_attributes_and_xmlns() {
+        let converter = JsonMlConverter::new();
+        let data = ElementData::new("ns:root")
+            .with_xmlns("ns", "http://example.com/ns")
+            .with_xmlns("", "http://example.com")
+            .with_attribute("ns:id", "1")
+            .with_attribute("lang", "en");
+
+        let json = converter.decode(&data, 0);
+        assert_eq!(
+            json,
+            json!(["ns:root", {
+                "xmlns": "http://example.com",
+                "xmlns:ns": "http://example.com/ns",
+                "ns:id": "1",
+                "lang": "en"
+            }])
+        );
+
+        let encoded = converter.encode(&json, "ignored", 0);
+        assert_eq!(encoded.tag(), "ns:root");
+        assert_eq!(encoded.attributes().get("ns:id"), Some(&"1".to_string()));
+        assert_eq!(encoded.attributes().len(), 2);
+        assert_eq!(encoded.xmlns().len(), 2);
+        assert!(encoded.xmlns().contains(&("ns".to_string(), "http://example.com/ns".to_string())));
+        assert!(encoded.xmlns().contains(&(String::new(), "http://example.com".to_string())));
+    }
+
+    #[test]
+    fn test_jsonml_mixed_content() {
+        let converter = JsonMlConverter::new();
+        let name = converter.decode(&ElementData::new("b").with_text("World"), 1);
+        let data = ElementData::new("p")
+            .with_cdata(0, "Hello ")
+            .with_child("b", name)
+            .with_cdata(1, "!");
+
+        let json = converter.decode(&data, 0);
+        assert_eq!(json, json!(["p", "Hello ", ["b", "World"], "!"]));
+
+        let encoded = converter.encode(&json, "p", 0);
+        assert_eq!(encoded.text(), None);
+        assert_eq!(encoded.content().len(), 3);
+        assert!(matches!(&encoded.content()[0], ContentItem::CData(0, s) if s == "Hello "));
+        assert!(matches!(&encoded.content()[1], ContentItem::Element(n, _) if n == "b"));
+        assert!(matches!(&encoded.content()[2], ContentItem::CData(1, s) if s == "!"));
+    }
+
+    #[test]
+    fn test_jsonml_round_trip_nested() {
+        let converter = JsonMlConverter::new();
+        let json = json!(["catalog", {"xmlns:x": "http://example.com/x", "version": "2"},
+            ["x:book", {"id": "b1"},
+                ["title", "Rust"],
+                ["note", "See ", ["em", {"class": "hl"}, "chapter 3"], " for details"],
+                ["empty"]
+            ],
+            ["x:book", {"id": "b2"}, ["title", "XML"]]
+        ]);
+
+        assert_eq!(round_trip(&converter, &json), json);
+    }
+
+    #[test]
+    fn test_jsonml_decode_plain_child_values() {
+        let converter = JsonMlConverter::new();
+        let data = ElementData::new("root")
+            .with_child("a", JsonValue::String("text".to_string()))
+            .with_child("b", JsonValue::Null)
+            .with_child("c", json!(3));
+
+        assert_eq!(converter.decode(&data, 0), json!(["root", ["a", "text"], ["b"], ["c", "3"]]));
+    }
+
+    #[test]
+    fn test_jsonml_encode_non_array() {
+        let converter = JsonMlConverter::new();
+        let data = converter.encode(&json!("value"), "item", 0);
+        assert_eq!(data.tag(), "item");
+        assert_eq!(data.text(), Some("value"));
+    }
+
+    #[test]
+    fn test_jsonml_is_lossless() {
+        let converter = JsonMlConverter::new();
+        assert!(!converter.is_lossy());
+        assert!(!converter.loses_xmlns());
+        assert!(converter.is_lossless());
+    }
+}
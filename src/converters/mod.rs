@@ -9,19 +9,24 @@
 //! - BadgerFish: http://www.sklar.com/badgerfish/
 //! - Unordered: Unordered element content
 //! - JsonML: JSON Markup Language
+//! - Columnar: Repeated rows pivoted into parallel arrays
 
 mod base;
 mod parker;
 mod badgerfish;
 mod unordered;
+mod jsonml;
+mod columnar;
 
 pub use base::{
-    Converter, ConverterConfig, ElementData, XmlnsProcessing,
+    ContentItem, Converter, ConverterConfig, ElementData, XmlnsProcessing,
     XmlSchemaConverter,
 };
 pub use parker::ParkerConverter;
 pub use badgerfish::BadgerFishConverter;
 pub use unordered::UnorderedConverter;
+pub use jsonml::JsonMlConverter;
+pub use columnar::ColumnarConverter;
 
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
@@ -85,8 +90,8 @@ pub fn create_converter(conv_type: ConverterType) -> Box<dyn JsonConverter> {
         ConverterType::Parker => Box::new(ParkerConverter::new()),
         ConverterType::BadgerFish => Box::new(BadgerFishConverter::new()),
         ConverterType::Unordered => Box::new(UnorderedConverter::new()),
-        // Others can use default for now
-        ConverterType::JsonML | ConverterType::Columnar => Box::new(XmlSchemaConverter::new()),
+        ConverterType::JsonML => Box::new(JsonMlConverter::new()),
+        ConverterType::Columnar => Box::new(ColumnarConverter::new()),
     }
 }
 
@@ -141,4 +146,22 @@ mod tests {
         let converter = create_converter(ConverterType::BadgerFish);
         assert!(!converter.is_lossy());
     }
+
+    #[test]
+    fn test_create_converter_jsonml() {
+        let converter = create_converter(ConverterType::JsonML);
+        assert!(converter.is_lossless());
+        let data = ElementData::new("root").with_text("text");
+        assert_eq!(converter.decode(&data, 0), serde_json::json!(["root", "text"]));
+    }
+
+    #[test]
+    fn test_create_converter_columnar() {
+        let converter = create_converter(ConverterType::Columnar);
+        assert!(converter.is_lossy());
+        let data = ElementData::new("table")
+            .with_child("row", serde_json::json!({"a": "1"}))
+            .with_child("row", serde_json::json!({"a": "2"}));
+        assert_eq!(converter.decode(&data, 0), serde_json::json!({"a": ["1", "2"]}));
+    }
 }
@@ -39,8 +39,8 @@ impl ParkerConverter {
     pub fn new() -> Self {
         // Parker convention has no attributes, text key is empty, no cdata
         let config = ConverterConfig::new()
-            .with_attr_prefix(None)
-            .with_text_key(Some(String::new()))
+            .without_attr_prefix()
+            .with_text_key("")
             .with_cdata_prefix(None);
 
         Self { config }
@@ -49,8 +49,8 @@ impl ParkerConverter {
     /// Create with preserve_root option
     pub fn with_preserve_root(preserve: bool) -> Self {
         let config = ConverterConfig::new()
-            .with_attr_prefix(None)
-            .with_text_key(Some(String::new()))
+            .without_attr_prefix()
+            .with_text_key("")
             .with_cdata_prefix(None)
             .with_preserve_root(preserve);
 
@@ -0,0 +1,415 @@
+//! Markdown documentation of schemas
+//!
+//! This module renders a schema as GitHub-flavoured Markdown:
+//! - a table of the global elements with their types and the text of their
+//!   `xs:documentation` annotations;
+//! - a section for each complex type with tables of its attributes and
+//!   child elements;
+//! - a section for each simple type with its constraints;
+//! - a reference of the namespaces declared and imported by the schema.
+//!
+//! Schema types are linked to their sections using GitHub's heading anchors.
+
+use std::fmt::Write;
+
+use crate::namespaces::QName;
+use crate::validators::base::AttributeValidator;
+use crate::validators::builtins::{is_builtin_qname, XSD_NAMESPACE};
+use crate::validators::globals::qname_order;
+use crate::validators::particles::Occurs;
+use crate::validators::{
+    AttributeUse, ComplexContent, DerivationMethod, ElementType, GlobalType, SimpleType, SimpleTypeVariety,
+    XsdComplexType, XsdElement, XsdSchema,
+};
+
+impl XsdSchema {
+    /// Generate Markdown documentation for the schema
+    ///
+    /// See [`XsdSchema::to_markdown`].
+    pub fn generate_markdown_documentation(&self) -> String {
+        self.to_markdown()
+    }
+
+    /// Generate Markdown documentation for the schema
+    ///
+    /// Global elements, complex types and simple types are listed in QName
+    /// order. The anonymous complex types of global elements get a section
+    /// of their own. Descriptions come from `xs:documentation` annotations.
+    pub fn to_markdown(&self) -> String {
+        MarkdownWriter { schema: self }.write()
+    }
+}
+
+struct MarkdownWriter<'a> {
+    schema: &'a XsdSchema,
+}
+
+impl MarkdownWriter<'_> {
+    fn write(&self) -> String {
+        let mut elements: Vec<_> = self.schema.elements().collect();
+        elements.sort_by(|a, b| qname_order(a.0, b.0));
+        let mut types: Vec<_> = self.schema.types().filter(|(qname, _)| !is_builtin_qname(qname)).collect();
+        types.sort_by(|a, b| qname_order(a.0, b.0));
+
+        let mut out = String::from("# Schema documentation\n\n");
+        match &self.schema.target_namespace {
+            Some(namespace) => {
+                let _ = writeln!(out, "Target namespace: `{}`\n", namespace);
+            }
+            None => out.push_str("No target namespace.\n\n"),
+        }
+
+        out.push_str("## Global elements\n\n");
+        if elements.is_empty() {
+            out.push_str("*None.*\n\n");
+        } else {
+            out.push_str("| Element | Type | Description |\n| --- | --- | --- |\n");
+            for (qname, element) in &elements {
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {} |",
+                    cell(&self.prefixed(qname)),
+                    self.element_type_md(element),
+                    cell(element.documentation.as_deref().unwrap_or_default())
+                );
+            }
+            out.push('\n');
+        }
+
+        out.push_str("## Complex types\n\n");
+        let mut complex_count = 0;
+        for (qname, global_type) in &types {
+            if let GlobalType::Complex(ct) = global_type {
+                self.write_complex_type(&mut out, &qname.local_name, ct);
+                complex_count += 1;
+            }
+        }
+        for (qname, element) in &elements {
+            if let (None, ElementType::Complex(ct)) = (&element.type_name, &element.element_type) {
+                if ct.name.is_none() {
+                    self.write_complex_type(&mut out, &anonymous_heading(qname), ct);
+                    complex_count += 1;
+                }
+            }
+        }
+        if complex_count == 0 {
+            out.push_str("*None.*\n\n");
+        }
+
+        out.push_str("## Simple types\n\n");
+        let mut simple_count = 0;
+        for (qname, global_type) in &types {
+            if let GlobalType::Simple(st) = global_type {
+                self.write_simple_type(&mut out, &qname.local_name, st.as_ref());
+                simple_count += 1;
+            }
+        }
+        if simple_count == 0 {
+            out.push_str("*None.*\n\n");
+        }
+
+        self.write_namespaces(&mut out);
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+        out
+    }
+
+    fn write_complex_type(&self, out: &mut String, heading: &str, ct: &XsdComplexType) {
+        let _ = writeln!(out, "### {}\n", heading);
+        if let Some(documentation) = &ct.documentation {
+            let _ = writeln!(out, "{}\n", documentation);
+        }
+
+        let mut properties = Vec::new();
+        if let Some(base) = &ct.base_type {
+            let method = match ct.derivation {
+                Some(DerivationMethod::Restriction) => "restriction",
+                _ => "extension",
+            };
+            properties.push(format!("Derived by {} from {}.", method, self.type_md(base)));
+        }
+        if ct.abstract_type {
+            properties.push("Abstract.".to_string());
+        }
+        if ct.mixed {
+            properties.push("Mixed content.".to_string());
+        }
+        if let ComplexContent::Simple(st) = &ct.content {
+            properties.push(format!("Simple content of type {}.", self.simple_type_md(st.as_ref())));
+        }
+        if !properties.is_empty() {
+            let _ = writeln!(out, "{}\n", properties.join(" "));
+        }
+
+        let mut attributes: Vec<_> = ct.attributes.iter_attributes().collect();
+        attributes.sort_by(|a, b| qname_order(a.name(), b.name()));
+        if !attributes.is_empty() {
+            out.push_str("**Attributes**\n\n| Name | Type | Use | Default |\n| --- | --- | --- | --- |\n");
+            for attr in attributes {
+                let attr_type = match (&attr.type_name, attr.simple_type()) {
+                    (Some(type_name), _) => self.type_md(type_name),
+                    (None, Some(st)) => self.simple_type_md(st),
+                    (None, None) => "`xs:anySimpleType`".to_string(),
+                };
+                let use_mode = match attr.use_mode() {
+                    AttributeUse::Required => "required",
+                    AttributeUse::Optional => "optional",
+                    AttributeUse::Prohibited => "prohibited",
+                };
+                let default = match (attr.default(), attr.fixed_value()) {
+                    (_, Some(fixed)) => format!("`{}` (fixed)", cell(fixed)),
+                    (Some(default), None) => format!("`{}`", cell(default)),
+                    (None, None) => String::new(),
+                };
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {} | {} |",
+                    cell(&attr.name().local_name),
+                    attr_type,
+                    use_mode,
+                    default
+                );
+            }
+            out.push('\n');
+        }
+
+        let children = ct
+            .model_group()
+            .map(|group| {
+                self.schema
+                    .element_occurs(group)
+                    .into_iter()
+                    .map(|(name, occurs)| (self.schema.find_element_particle(group, &name), name, occurs))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if !children.is_empty() {
+            out.push_str("**Child elements**\n\n| Name | Type | Occurs | Description |\n| --- | --- | --- | --- |\n");
+            for (particle, name, occurs) in children {
+                let element = particle.and_then(|particle| match &particle.element_ref {
+                    Some(element_ref) => self.schema.lookup_element(element_ref).map(|element| element.as_ref()),
+                    None => particle.element().map(|element| element.as_ref()),
+                });
+                let (child_type, description) = match element {
+                    Some(element) => (self.element_type_md(element), element.documentation.as_deref().unwrap_or_default()),
+                    None => ("`xs:anyType`".to_string(), ""),
+                };
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {} | {} |",
+                    cell(&self.prefixed(&name)),
+                    child_type,
+                    occurs_md(&occurs),
+                    cell(description)
+                );
+            }
+            out.push('\n');
+        }
+
+        if ct.attributes.is_empty() && ct.model_group().is_none_or(|group| group.is_empty()) && ct.base_type.is_none() {
+            out.push_str("*No attributes or child elements.*\n\n");
+        }
+    }
+
+    fn write_simple_type(&self, out: &mut String, heading: &str, st: &dyn SimpleType) {
+        let _ = writeln!(out, "### {}\n", heading);
+        match st.variety() {
+            SimpleTypeVariety::Atomic => {
+                out.push_str("- **Variety:** atomic\n");
+                if let Some(base) = SimpleType::base_type(st) {
+                    let _ = writeln!(out, "- **Base type:** {}", self.simple_type_md(base));
+                }
+            }
+            SimpleTypeVariety::List => {
+                out.push_str("- **Variety:** list\n");
+                if let Some(item) = st.item_type() {
+                    let _ = writeln!(out, "- **Item type:** {}", self.simple_type_md(item.as_ref()));
+                }
+            }
+            SimpleTypeVariety::Union => {
+                out.push_str("- **Variety:** union\n");
+                let members: Vec<_> = st.member_types().iter().map(|member| self.simple_type_md(member.as_ref())).collect();
+                if !members.is_empty() {
+                    let _ = writeln!(out, "- **Member types:** {}", members.join(", "));
+                }
+            }
+        }
+        match st.effective_facets().enumeration {
+            Some(enumeration) if !enumeration.values.is_empty() => {
+                let values: Vec<_> = enumeration.values.iter().map(|value| format!("`{}`", value)).collect();
+                let _ = writeln!(out, "- **Values:** {}", values.join(", "));
+            }
+            _ => {
+                let _ = writeln!(out, "- **Constraints:** `{}`", st.facet_summary());
+            }
+        }
+        out.push('\n');
+    }
+
+    fn write_namespaces(&self, out: &mut String) {
+        out.push_str("## Namespace reference\n\n");
+        let mut declarations: Vec<_> = self.schema.namespace_declarations().iter().collect();
+        declarations.sort();
+        if declarations.is_empty() {
+            out.push_str("*No namespace declarations.*\n\n");
+        } else {
+            out.push_str("| Prefix | Namespace |\n| --- | --- |\n");
+            for (prefix, namespace) in declarations {
+                let prefix = if prefix.is_empty() {
+                    "*(default)*".to_string()
+                } else {
+                    format!("`{}`", prefix)
+                };
+                let _ = writeln!(out, "| {} | `{}` |", prefix, cell(namespace));
+            }
+            out.push('\n');
+        }
+
+        let mut imports: Vec<_> = self.schema.imports.values().collect();
+        imports.sort_by(|a, b| a.namespace.cmp(&b.namespace));
+        if !imports.is_empty() {
+            out.push_str("**Imports**\n\n| Namespace | Location |\n| --- | --- |\n");
+            for import in imports {
+                let location = import.location.as_deref().map(|location| format!("`{}`", cell(location)));
+                let _ = writeln!(out, "| `{}` | {} |", cell(&import.namespace), location.unwrap_or_default());
+            }
+            out.push('\n');
+        }
+    }
+
+    /// Type of an element declaration, linked when it is a schema type
+    fn element_type_md(&self, element: &XsdElement) -> String {
+        if let Some(type_name) = &element.type_name {
+            return self.type_md(type_name);
+        }
+        match &element.element_type {
+            ElementType::Simple(st) => self.simple_type_md(st.as_ref()),
+            ElementType::Complex(ct) => match &ct.name {
+                Some(name) => self.type_md(name),
+                None if self.schema.lookup_element(&element.name).is_some_and(|global| {
+                    matches!(&global.element_type, ElementType::Complex(global_ct) if std::ptr::eq(global_ct.as_ref(), ct.as_ref()))
+                }) =>
+                {
+                    format!("[anonymous](#{})", anchor(&anonymous_heading(&element.name)))
+                }
+                None => "anonymous complex type".to_string(),
+            },
+            ElementType::Any => "`xs:anyType`".to_string(),
+        }
+    }
+
+    /// Named or anonymous simple type
+    fn simple_type_md(&self, st: &dyn SimpleType) -> String {
+        match st.name() {
+            Some(name) => self.type_md(name),
+            None => format!("`{}`", cell(&st.facet_summary())),
+        }
+    }
+
+    /// Type name, linked to its section for the types of the schema
+    fn type_md(&self, qname: &QName) -> String {
+        let name = cell(&self.prefixed(qname));
+        let documented = !is_builtin_qname(qname) && self.schema.types().any(|(type_name, _)| type_name == qname);
+        if documented {
+            format!("[`{}`](#{})", name, anchor(&qname.local_name))
+        } else {
+            format!("`{}`", name)
+        }
+    }
+
+    /// Name with the prefix the schema uses for its namespace
+    fn prefixed(&self, qname: &QName) -> String {
+        let prefix = match qname.namespace.as_deref() {
+            Some(namespace) => match self.schema.preferred_prefix_for(namespace) {
+                Some(prefix) if !prefix.is_empty() => Some(prefix),
+                _ if namespace == XSD_NAMESPACE => Some("xs"),
+                _ => None,
+            },
+            None => None,
+        };
+        match prefix {
+            Some(prefix) => format!("{}:{}", prefix, qname.local_name),
+            None => qname.local_name.clone(),
+        }
+    }
+}
+
+/// Heading of the section of the anonymous type of a global element
+fn anonymous_heading(element: &QName) -> String {
+    format!("{} (anonymous type)", element.local_name)
+}
+
+/// GitHub anchor of a heading
+fn anchor(heading: &str) -> String {
+    heading
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c.to_ascii_lowercase()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Occurrence bounds, e.g. `1`, `0..1` or `1..*`
+fn occurs_md(occurs: &Occurs) -> String {
+    match occurs.max {
+        Some(max) if max == occurs.min => max.to_string(),
+        Some(max) => format!("{}..{}", occurs.min, max),
+        None => format!("{}..*", occurs.min),
+    }
+}
+
+/// Escape text for a table cell
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anchor() {
+        assert_eq!(anchor("bookType"), "booktype");
+        assert_eq!(anchor("catalog (anonymous type)"), "catalog-anonymous-type");
+        assert_eq!(anchor("non-fiction_kind"), "non-fiction_kind");
+    }
+
+    #[test]
+    fn test_occurs_md() {
+        assert_eq!(occurs_md(&Occurs::once()), "1");
+        assert_eq!(occurs_md(&Occurs::new(0, Some(1))), "0..1");
+        assert_eq!(occurs_md(&Occurs::new(1, None)), "1..*");
+    }
+
+    #[test]
+    fn test_to_markdown_documentation() {
+        let schema = XsdSchema::from_string(
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="catalog">
+    <xs:annotation>
+      <xs:documentation>
+        A catalog of
+        items.
+      </xs:documentation>
+    </xs:annotation>
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="item" type="xs:string" maxOccurs="unbounded">
+          <xs:annotation><xs:documentation>An item | with a pipe</xs:documentation></xs:annotation>
+        </xs:element>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#,
+        )
+        .unwrap();
+        let md = schema.to_markdown();
+
+        assert!(md.contains("| `catalog` | [anonymous](#catalog-anonymous-type) | A catalog of items. |"));
+        assert!(md.contains("### catalog (anonymous type)"));
+        assert!(md.contains("| `item` | `xs:string` | 1..* | An item \\| with a pipe |"));
+        assert_eq!(md, schema.generate_markdown_documentation());
+    }
+}
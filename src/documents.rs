@@ -2,25 +2,45 @@
 //!
 //! This module provides functionality for working with XML documents.
 
+use crate::catalog::XmlCatalog;
+use crate::dtd::{Doctype, DoctypeWarning};
 use crate::error::{Error, Result};
 use crate::namespaces::{NamespaceContext, QName};
+use crate::validators::document_validation::find_child_declaration;
+use crate::validators::schemas::XSI_NAMESPACE;
+use crate::validators::{ElementType, XsdElement, XsdSchema};
+use crate::xpath::{split_path, PathStep, PathStepKind};
+use indexmap::IndexMap;
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
 use std::collections::HashMap;
+use std::io::{BufRead, Read};
+
+mod serialization;
 
 /// XML Element in the document tree
 #[derive(Debug, Clone)]
 pub struct Element {
     /// Element qualified name
     pub qname: QName,
-    /// Element attributes
-    pub attributes: HashMap<QName, String>,
+    /// Element attributes, in document order
+    pub attributes: IndexMap<QName, String>,
+    /// Prefixes of the attribute names in the source document
+    pub attribute_prefixes: HashMap<QName, String>,
     /// Text content (if any)
     pub text: Option<String>,
+    /// Whether the text content was read from a CDATA section
+    pub cdata: bool,
     /// Child elements
     pub children: Vec<Element>,
     /// Namespace context for this element
     pub namespaces: NamespaceContext,
+    /// Prefix of the element's tag in the source document, if any
+    pub prefix: Option<String>,
+    /// Line of the element's start tag in the source document (1-based)
+    pub source_line: Option<u32>,
+    /// Column of the element's start tag in the source document (1-based, in characters)
+    pub source_column: Option<u32>,
 }
 
 impl Element {
@@ -28,10 +48,15 @@ impl Element {
     pub fn new(qname: QName) -> Self {
         Self {
             qname,
-            attributes: HashMap::new(),
+            attributes: IndexMap::new(),
+            attribute_prefixes: HashMap::new(),
             text: None,
+            cdata: false,
             children: Vec::new(),
             namespaces: NamespaceContext::new(),
+            prefix: None,
+            source_line: None,
+            source_column: None,
         }
     }
 
@@ -71,6 +96,18 @@ impl Element {
         self.text = Some(text);
     }
 
+    /// Add a part of the text content
+    ///
+    /// Text parts separated only by comments, processing instructions or
+    /// CDATA sections are concatenated. A part following a child element
+    /// replaces the text.
+    pub fn append_text(&mut self, text: String) {
+        match &mut self.text {
+            Some(current) if self.children.is_empty() => current.push_str(&text),
+            _ => self.text = Some(text),
+        }
+    }
+
     /// Find child elements by local name
     pub fn find_children(&self, local_name: &str) -> Vec<&Element> {
         self.children
@@ -87,6 +124,8 @@ pub struct Document {
     pub root: Option<Element>,
     /// Document namespace context
     pub namespaces: NamespaceContext,
+    /// Cached index of the element IDs, see [`Document::build_id_index`]
+    id_index: Option<IdIndex>,
 }
 
 impl Document {
@@ -95,6 +134,7 @@ impl Document {
         Self {
             root: None,
             namespaces: NamespaceContext::new(),
+            id_index: None,
         }
     }
 
@@ -103,9 +143,58 @@ impl Document {
         Self::parse(xml.as_bytes())
     }
 
+    /// Parse an XML document from a string and validate it against its DOCTYPE
+    ///
+    /// DTD validation problems, such as undeclared elements or attributes,
+    /// are returned as warnings rather than errors. Documents without a
+    /// DOCTYPE declaration produce no warnings.
+    pub fn from_string_validating_doctype(xml: &str) -> Result<(Self, Vec<DoctypeWarning>)> {
+        Self::from_string_validating_doctype_with_catalog(xml, None)
+    }
+
+    /// Parse an XML document and validate it against its DOCTYPE, resolving
+    /// the external DTD through a catalog
+    pub fn from_string_validating_doctype_with_catalog(
+        xml: &str,
+        catalog: Option<&XmlCatalog>,
+    ) -> Result<(Self, Vec<DoctypeWarning>)> {
+        let doc = Self::from_string(xml)?;
+        let Some(doctype) = Doctype::find(xml)? else {
+            return Ok((doc, Vec::new()));
+        };
+
+        let (dtd, mut warnings) = doctype.load_dtd(catalog)?;
+        if let Some(root) = doc.root() {
+            let expected = doctype.root_name.rsplit(':').next().unwrap_or(&doctype.root_name);
+            if root.local_name() != expected {
+                warnings.push(DoctypeWarning::at(
+                    root.local_name(),
+                    format!(
+                        "Document element '{}' does not match the DOCTYPE name '{}'",
+                        root.local_name(),
+                        doctype.root_name
+                    ),
+                ));
+            }
+            warnings.extend(dtd.validate(root));
+        }
+
+        Ok((doc, warnings))
+    }
+
     /// Parse an XML document from bytes
+    ///
+    /// Elements record the line and column of their start tag.
     pub fn parse(xml: &[u8]) -> Result<Self> {
-        let mut reader = Reader::from_reader(xml);
+        Self::from_reader(xml)
+    }
+
+    /// Parse an XML document from a buffered reader
+    ///
+    /// The content is parsed as it is read, so it doesn't have to be loaded
+    /// in memory first. Elements record the line and column of their start tag.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self> {
+        let mut reader = Reader::from_reader(LocatingReader::new(reader));
         reader.trim_text(true);
 
         let mut doc = Document::new();
@@ -113,9 +202,11 @@ impl Document {
         let mut buf = Vec::new();
 
         loop {
+            reader.get_mut().mark();
             match reader.read_event_into(&mut buf) {
                 Ok(Event::Start(e)) => {
-                    let element = Self::parse_element(&e, &reader)?;
+                    let mut element = Self::parse_element(&e)?;
+                    reader.get_ref().set_location(&mut element);
                     element_stack.push(element);
                 }
                 Ok(Event::End(_)) => {
@@ -129,7 +220,8 @@ impl Document {
                     }
                 }
                 Ok(Event::Empty(e)) => {
-                    let element = Self::parse_element(&e, &reader)?;
+                    let mut element = Self::parse_element(&e)?;
+                    reader.get_ref().set_location(&mut element);
                     if let Some(parent) = element_stack.last_mut() {
                         parent.add_child(element);
                     } else {
@@ -144,10 +236,19 @@ impl Document {
                             .map_err(|e| Error::Xml(format!("Failed to unescape text: {}", e)))?
                             .to_string();
                         if !text.trim().is_empty() {
-                            current.set_text(text);
+                            current.append_text(text);
                         }
                     }
                 }
+                Ok(Event::CData(e)) => {
+                    if let Some(current) = element_stack.last_mut() {
+                        let text = std::str::from_utf8(&e)
+                            .map_err(|e| Error::Xml(format!("Invalid CDATA section: {}", e)))?
+                            .to_string();
+                        current.append_text(text);
+                        current.cdata = true;
+                    }
+                }
                 Ok(Event::Eof) => break,
                 Err(e) => {
                     return Err(Error::Xml(format!(
@@ -165,20 +266,21 @@ impl Document {
     }
 
     /// Parse element from BytesStart event
-    fn parse_element(start: &BytesStart, _reader: &Reader<&[u8]>) -> Result<Element> {
+    fn parse_element(start: &BytesStart) -> Result<Element> {
         let name_bytes = start.name();
         let name = std::str::from_utf8(name_bytes.as_ref())
             .map_err(|e| Error::Xml(format!("Invalid element name: {}", e)))?
             .to_string();
 
         // Parse namespace and local name
-        let qname = if let Some((_prefix, local)) = name.split_once(':') {
-            QName::local(local) // Namespace will be resolved later
+        let (prefix, qname) = if let Some((prefix, local)) = name.split_once(':') {
+            (Some(prefix.to_string()), QName::local(local)) // Namespace will be resolved later
         } else {
-            QName::local(&name)
+            (None, QName::local(&name))
         };
 
         let mut element = Element::new(qname);
+        element.prefix = prefix;
 
         // Parse attributes
         for attr_result in start.attributes() {
@@ -200,8 +302,10 @@ impl Document {
                 element.namespaces.add_prefix(prefix, &attr_value);
             } else {
                 // Regular attribute
-                let attr_qname = if let Some((_prefix, local)) = attr_name.split_once(':') {
-                    QName::local(local) // Namespace will be resolved later
+                let attr_qname = if let Some((prefix, local)) = attr_name.split_once(':') {
+                    // Namespace will be resolved later
+                    element.attribute_prefixes.insert(QName::local(local), prefix.to_string());
+                    QName::local(local)
                 } else {
                     QName::local(attr_name)
                 };
@@ -221,6 +325,331 @@ impl Document {
     pub fn root_mut(&mut self) -> Option<&mut Element> {
         self.root.as_mut()
     }
+
+    /// Get the schema location hints of the root element
+    ///
+    /// Returns the `(namespace, location)` pairs of the `xsi:schemaLocation`
+    /// attribute in document order, followed by the location of the
+    /// `xsi:noNamespaceSchemaLocation` attribute with no namespace. A
+    /// trailing namespace without a location is ignored.
+    pub fn schema_location_hints(&self) -> Vec<(Option<String>, String)> {
+        let Some(root) = &self.root else {
+            return Vec::new();
+        };
+
+        let mut hints = Vec::new();
+        if let Some(value) = Self::xsi_attribute(root, "schemaLocation") {
+            let tokens: Vec<&str> = value.split_whitespace().collect();
+            for pair in tokens.chunks_exact(2) {
+                hints.push((Some(pair[0].to_string()), pair[1].to_string()));
+            }
+        }
+        let no_namespace = Self::xsi_attribute(root, "noNamespaceSchemaLocation");
+        if let Some(location) = no_namespace.and_then(|value| value.split_whitespace().next()) {
+            hints.push((None, location.to_string()));
+        }
+        hints
+    }
+
+    /// Get an XSI attribute of an element
+    ///
+    /// Attributes are parsed without their namespace, so unqualified names
+    /// are accepted when the element declares the XSI namespace.
+    fn xsi_attribute<'a>(elem: &'a Element, local_name: &str) -> Option<&'a str> {
+        elem.get_attribute_qname(&QName::namespaced(XSI_NAMESPACE, local_name)).or_else(|| {
+            elem.namespaces
+                .iter()
+                .any(|(_, namespace)| namespace == XSI_NAMESPACE)
+                .then(|| elem.get_attribute(local_name))
+                .flatten()
+        })
+    }
+
+    /// Find the element with an ID attribute of the given value
+    ///
+    /// When an ID index has been built with [`build_id_index`](Self::build_id_index),
+    /// the lookup goes through it. Otherwise the whole document is searched
+    /// for an `id` or `xml:id` attribute with that value.
+    pub fn find_element_by_id(&self, id: &str) -> Option<&Element> {
+        let root = self.root.as_ref()?;
+        match &self.id_index {
+            Some(index) => index.get(id)?.resolve(root),
+            None => Self::find_by_id_attribute(root, id),
+        }
+    }
+
+    /// Find the first element selected by a path expression
+    ///
+    /// The path is split into the steps of an identity constraint selector:
+    /// child steps with an optional positional predicate (`item[2]`), `//`,
+    /// `.`, and attribute steps (`@id`) keeping the elements that have the
+    /// attribute. Names are matched against the local name, and against the
+    /// source prefix when the step has one; `*` matches any name. Relative
+    /// paths start from the root element. Parent steps and other predicates
+    /// are not supported and select no element.
+    pub fn find_element_by_xpath(&self, xpath: &str) -> Option<&Element> {
+        let root = self.root.as_ref()?;
+        // The document node is the parent of the root element
+        let mut at_document = xpath.trim_start().starts_with('/');
+        let mut nodes: Vec<&Element> = if at_document { Vec::new() } else { vec![root] };
+
+        for step in split_path(xpath).into_iter().map(PathStep::parse) {
+            match step.kind {
+                PathStepKind::Self_ => {}
+                PathStepKind::DescendantOrSelf => {
+                    let mut descendants = Vec::new();
+                    if at_document {
+                        Self::collect_descendants(root, &mut descendants);
+                    } else {
+                        for node in nodes {
+                            Self::collect_descendants(node, &mut descendants);
+                        }
+                    }
+                    nodes = descendants;
+                }
+                PathStepKind::Child => {
+                    let position = match step.predicate.as_deref().map(str::trim) {
+                        Some(predicate) => Some(predicate.parse::<usize>().ok().filter(|&n| n > 0)?),
+                        None => None,
+                    };
+                    let mut parents: Vec<&[Element]> = nodes.iter().map(|node| node.children.as_slice()).collect();
+                    if at_document {
+                        parents.insert(0, std::slice::from_ref(root));
+                    }
+                    let mut children = Vec::new();
+                    for siblings in parents {
+                        let mut matching = siblings.iter().filter(|child| Self::step_matches_element(&step, child));
+                        match position {
+                            Some(n) => children.extend(matching.nth(n - 1)),
+                            None => children.extend(matching),
+                        }
+                    }
+                    nodes = children;
+                    at_document = false;
+                }
+                PathStepKind::Attribute => {
+                    nodes.retain(|node| Self::step_matches_attribute(&step, node));
+                    at_document = false;
+                }
+                PathStepKind::Parent => return None,
+            }
+        }
+        nodes.first().copied()
+    }
+
+    /// Collect an element and its descendants in document order
+    fn collect_descendants<'a>(elem: &'a Element, out: &mut Vec<&'a Element>) {
+        out.push(elem);
+        for child in &elem.children {
+            Self::collect_descendants(child, out);
+        }
+    }
+
+    /// Check whether a child step names an element
+    fn step_matches_element(step: &PathStep, elem: &Element) -> bool {
+        (step.is_wildcard() || elem.local_name() == step.name)
+            && (step.prefix.is_none() || elem.prefix == step.prefix)
+    }
+
+    /// Check whether an element has an attribute named by an attribute step
+    fn step_matches_attribute(step: &PathStep, elem: &Element) -> bool {
+        elem.attributes.keys().any(|qname| {
+            (step.is_wildcard() || qname.local_name == step.name)
+                && (step.prefix.is_none() || elem.attribute_prefixes.get(qname) == step.prefix.as_ref())
+        })
+    }
+
+    /// Search an element and its descendants, in document order, for an `id` attribute value
+    fn find_by_id_attribute<'a>(elem: &'a Element, id: &str) -> Option<&'a Element> {
+        if elem.get_attribute("id").map(str::trim) == Some(id) {
+            return Some(elem);
+        }
+        elem.children.iter().find_map(|child| Self::find_by_id_attribute(child, id))
+    }
+
+    /// Build and cache the index of the element IDs
+    ///
+    /// The ID attributes are those declared with a type derived from `xs:ID`
+    /// in the schema; elements the schema has no declaration for fall back to
+    /// their `id` or `xml:id` attribute. When an ID value occurs more than
+    /// once, the first element in document order is indexed. The index isn't
+    /// updated when the tree is modified, so it has to be built again.
+    pub fn build_id_index(&mut self, schema: &XsdSchema) -> &IdIndex {
+        let mut index = IdIndex::default();
+        if let Some(root) = &self.root {
+            let decl = schema.check_document_element(self).ok().cloned();
+            index.add_element(schema, root, decl.as_deref(), &mut ElementPath::default());
+        }
+        self.id_index.insert(index)
+    }
+
+    /// Get the cached ID index, if one was built
+    pub fn id_index(&self) -> Option<&IdIndex> {
+        self.id_index.as_ref()
+    }
+}
+
+/// Reader keeping the line and column of the bytes read from a source
+///
+/// The bytes read since the last mark are kept, so that the location of an
+/// event read from the mark can be found.
+struct LocatingReader<R> {
+    inner: R,
+    /// Line and column (1-based, in characters) of the mark
+    line: u32,
+    column: u32,
+    /// Bytes read since the mark
+    pending: Vec<u8>,
+}
+
+impl<R> LocatingReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            line: 1,
+            column: 1,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Move the mark after the bytes read so far
+    fn mark(&mut self) {
+        for &byte in &self.pending {
+            advance_location(&mut self.line, &mut self.column, byte);
+        }
+        self.pending.clear();
+    }
+
+    /// Set the location of an element whose event was read from the mark
+    ///
+    /// The reader skips the whitespace before a tag, so the tag starts at
+    /// the first non-whitespace byte.
+    fn set_location(&self, element: &mut Element) {
+        let (mut line, mut column) = (self.line, self.column);
+        for &byte in self.pending.iter().take_while(|b| b.is_ascii_whitespace()) {
+            advance_location(&mut line, &mut column, byte);
+        }
+        element.source_line = Some(line);
+        element.source_column = Some(column);
+    }
+}
+
+/// Move a location past a byte, counting the columns in UTF-8 characters
+fn advance_location(line: &mut u32, column: &mut u32, byte: u8) {
+    if byte == b'\n' {
+        *line += 1;
+        *column = 1;
+    } else if byte & 0xC0 != 0x80 {
+        *column += 1;
+    }
+}
+
+impl<R: Read> Read for LocatingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.pending.extend_from_slice(&buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for LocatingReader<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // The consumed bytes are still at the start of the inner buffer
+        if let Ok(buf) = self.inner.fill_buf() {
+            self.pending.extend_from_slice(&buf[..amt.min(buf.len())]);
+        }
+        self.inner.consume(amt);
+    }
+}
+
+/// Position of an element in a document, as child indices from the root
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ElementPath(Vec<usize>);
+
+impl ElementPath {
+    /// Get the child indices, from the root element down
+    pub fn indices(&self) -> &[usize] {
+        &self.0
+    }
+
+    /// Get the element at this path below a root element
+    pub fn resolve<'a>(&self, root: &'a Element) -> Option<&'a Element> {
+        self.0.iter().try_fold(root, |elem, &index| elem.children.get(index))
+    }
+}
+
+/// Index of the elements of a document by ID value
+#[derive(Debug, Clone, Default)]
+pub struct IdIndex {
+    paths: HashMap<String, ElementPath>,
+}
+
+impl IdIndex {
+    /// Get the path of the element with an ID value
+    pub fn get(&self, id: &str) -> Option<&ElementPath> {
+        self.paths.get(id)
+    }
+
+    /// Check whether an ID value is indexed
+    pub fn contains(&self, id: &str) -> bool {
+        self.paths.contains_key(id)
+    }
+
+    /// Iterate over the indexed ID values
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.paths.keys().map(|id| id.as_str())
+    }
+
+    /// Get the number of indexed IDs
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Check if the index is empty
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Index the ID attributes of an element and its descendants
+    fn add_element(&mut self, schema: &XsdSchema, elem: &Element, decl: Option<&XsdElement>, path: &mut ElementPath) {
+        let complex_type = match decl.map(|decl| &decl.element_type) {
+            Some(ElementType::Complex(ct)) => Some(ct.as_ref()),
+            _ => None,
+        };
+
+        let mut ids: Vec<&str> = match (decl, complex_type) {
+            (Some(_), Some(ct)) => elem
+                .attributes
+                .iter()
+                .filter(|(qname, _)| {
+                    ct.attributes
+                        .iter_attributes()
+                        .find(|attr| attr.name().local_name == qname.local_name)
+                        .and_then(|attr| attr.simple_type())
+                        .and_then(|simple_type| simple_type.primitive_name())
+                        .is_some_and(|name| name == "ID")
+                })
+                .map(|(_, value)| value.trim())
+                .collect(),
+            (Some(_), None) => Vec::new(),
+            (None, _) => elem.get_attribute("id").map(str::trim).into_iter().collect(),
+        };
+        ids.sort_unstable();
+        for id in ids {
+            self.paths.entry(id.to_string()).or_insert_with(|| path.clone());
+        }
+
+        for (index, child) in elem.children.iter().enumerate() {
+            let child_decl = find_child_declaration(schema, child, complex_type);
+            path.0.push(index);
+            self.add_element(schema, child, child_decl.as_deref(), path);
+            path.0.pop();
+        }
+    }
 }
 
 impl Default for Document {
@@ -252,6 +681,17 @@ mod tests {
         assert_eq!(root.children[0].text.as_deref(), Some("text"));
     }
 
+    #[test]
+    fn test_parse_element_prefix() {
+        let xml = r#"<p:root xmlns:p="http://example.com"><child/></p:root>"#;
+        let doc = Document::from_string(xml).unwrap();
+
+        let root = doc.root.unwrap();
+        assert_eq!(root.prefix.as_deref(), Some("p"));
+        assert_eq!(root.local_name(), "root");
+        assert_eq!(root.children[0].prefix, None);
+    }
+
     #[test]
     fn test_parse_with_attributes() {
         let xml = r#"<root attr1="value1" attr2="value2"><child/></root>"#;
@@ -278,6 +718,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_source_locations() {
+        let xml = "<root>\n  <a/>\n  <b>text</b><c/>\n</root>";
+        let doc = Document::from_string(xml).unwrap();
+
+        let root = doc.root.unwrap();
+        assert_eq!((root.source_line, root.source_column), (Some(1), Some(1)));
+        let locations: Vec<_> = root.children.iter().map(|c| (c.source_line, c.source_column)).collect();
+        assert_eq!(locations, vec![(Some(2), Some(3)), (Some(3), Some(3)), (Some(3), Some(14))]);
+        assert_eq!(Element::new(QName::local("x")).source_line, None);
+    }
+
+    #[test]
+    fn test_parse_from_reader() {
+        let xml = "<root>\n  <a k=\"é\"/><b>\n  text</b>\n  <c/>\n</root>";
+        let expected = Document::from_string(xml).unwrap();
+
+        // A small buffer splits the tags across reads
+        let doc = Document::from_reader(std::io::BufReader::with_capacity(3, xml.as_bytes())).unwrap();
+        let root = doc.root.unwrap();
+        assert_eq!(root.children.len(), 3);
+        assert_eq!(root.children[0].get_attribute("k"), Some("é"));
+        let locations: Vec<_> = root.children.iter().map(|c| (c.source_line, c.source_column)).collect();
+        assert_eq!(locations, vec![(Some(2), Some(3)), (Some(2), Some(13)), (Some(4), Some(3))]);
+        let expected: Vec<_> = expected.root.unwrap().children.iter().map(|c| (c.source_line, c.source_column)).collect();
+        assert_eq!(locations, expected);
+    }
+
     #[test]
     fn test_find_children() {
         let xml = r#"<root><child1/><child2/><child1/></root>"#;
@@ -297,4 +765,121 @@ mod tests {
         assert_eq!(elem.local_name(), "test");
         assert_eq!(elem.text.as_deref(), Some("content"));
     }
+
+    const ID_XML: &str = r#"<library xmlns:xml="http://www.w3.org/XML/1998/namespace">
+        <book id="b1" ref="b2"><title>One</title></book>
+        <book id="b2" ref="b1"><title xml:id="t2">Two</title></book>
+        <shelf code="s1"><book id="b3" ref="b1"/></shelf>
+    </library>"#;
+
+    #[test]
+    fn test_find_element_by_id_without_index() {
+        let doc = Document::from_string(ID_XML).unwrap();
+        assert!(doc.id_index().is_none());
+
+        let book = doc.find_element_by_id("b2").unwrap();
+        assert_eq!(book.get_attribute("ref"), Some("b1"));
+        assert_eq!(doc.find_element_by_id("t2").unwrap().text.as_deref(), Some("Two"));
+        assert_eq!(doc.find_element_by_id("b3").unwrap().get_attribute("ref"), Some("b1"));
+        assert!(doc.find_element_by_id("s1").is_none());
+    }
+
+    #[test]
+    fn test_build_id_index_with_schema() {
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:complexType name="bookType">
+    <xs:sequence>
+      <xs:element name="title" type="xs:string" minOccurs="0"/>
+    </xs:sequence>
+    <xs:attribute name="id" type="xs:string"/>
+    <xs:attribute name="ref" type="xs:IDREF"/>
+  </xs:complexType>
+  <xs:element name="library">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="book" type="bookType" maxOccurs="unbounded"/>
+        <xs:element name="shelf">
+          <xs:complexType>
+            <xs:sequence>
+              <xs:element name="book" type="bookType"/>
+            </xs:sequence>
+            <xs:attribute name="code" type="xs:ID"/>
+          </xs:complexType>
+        </xs:element>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let mut doc = Document::from_string(ID_XML).unwrap();
+
+        let index = doc.build_id_index(&schema);
+        // Only attributes typed as xs:ID are indexed
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.get("s1").unwrap().indices(), &[2]);
+        assert!(!index.contains("b1"));
+
+        let shelf = doc.find_element_by_id("s1").unwrap();
+        assert_eq!(shelf.local_name(), "shelf");
+        assert!(doc.find_element_by_id("b1").is_none());
+        assert!(doc.id_index().is_some());
+    }
+
+    #[test]
+    fn test_build_id_index_without_declarations() {
+        let schema = XsdSchema::new();
+        let mut doc = Document::from_string(ID_XML).unwrap();
+
+        let index = doc.build_id_index(&schema);
+        let mut ids: Vec<_> = index.ids().collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec!["b1", "b2", "b3", "t2"]);
+        assert_eq!(index.get("t2").unwrap().indices(), &[1, 0]);
+        assert_eq!(index.get("b3").unwrap().indices(), &[2, 0]);
+
+        let title = doc.find_element_by_id("t2").unwrap();
+        assert_eq!(title.local_name(), "title");
+    }
+
+    #[test]
+    fn test_find_element_by_xpath() {
+        let xml = r#"<root id="r"><child name="a"/><child name="b"><grandchild id="g1"/></child><other><grandchild id="g2"/></other></root>"#;
+        let doc = Document::from_string(xml).unwrap();
+
+        let child = doc.find_element_by_xpath("/root/child[2]").unwrap();
+        assert_eq!(child.get_attribute("name"), Some("b"));
+        let grandchild = doc.find_element_by_xpath("//grandchild").unwrap();
+        assert_eq!(grandchild.get_attribute("id"), Some("g1"));
+        assert_eq!(doc.find_element_by_xpath("@id").unwrap().local_name(), "root");
+
+        assert_eq!(doc.find_element_by_xpath("other/grandchild").unwrap().get_attribute("id"), Some("g2"));
+        assert_eq!(doc.find_element_by_xpath("/root/*[3]").unwrap().local_name(), "other");
+        assert_eq!(doc.find_element_by_xpath(".//child/@name").unwrap().get_attribute("name"), Some("a"));
+        assert!(doc.find_element_by_xpath("/root/child[3]").is_none());
+        assert!(doc.find_element_by_xpath("/child").is_none());
+        assert!(doc.find_element_by_xpath("child/@id").is_none());
+        assert!(doc.find_element_by_xpath("child/..").is_none());
+    }
+
+    #[test]
+    fn test_schema_location_hints() {
+        let xml = r#"<root xmlns="urn:a" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+      xsi:schemaLocation="urn:a  a.xsd
+                          urn:b b.xsd urn:c"
+      xsi:noNamespaceSchemaLocation="plain.xsd"/>"#;
+        let doc = Document::from_string(xml).unwrap();
+        assert_eq!(
+            doc.schema_location_hints(),
+            vec![
+                (Some("urn:a".to_string()), "a.xsd".to_string()),
+                (Some("urn:b".to_string()), "b.xsd".to_string()),
+                (None, "plain.xsd".to_string()),
+            ]
+        );
+
+        // Without the XSI namespace the attributes are not hints
+        let doc = Document::from_string(r#"<root schemaLocation="urn:a a.xsd"/>"#).unwrap();
+        assert!(doc.schema_location_hints().is_empty());
+        assert!(Document::new().schema_location_hints().is_empty());
+    }
 }
@@ -0,0 +1,271 @@
+//! Serialization of documents back to XML
+//!
+//! Elements parsed from a document keep the prefixes of their tags and
+//! attributes, with their namespace declarations, so they are written back
+//! as they were read. Namespaced names added through the [`Element`] API
+//! are written with a prefix in scope, declaring one when needed.
+
+use std::collections::HashMap;
+
+use quick_xml::escape::escape;
+
+use crate::validators::schemas::XML_NAMESPACE;
+
+use super::{Document, Element};
+
+impl Document {
+    /// Serialize the document to an XML string
+    ///
+    /// Namespace declarations come first in each start tag, followed by the
+    /// attributes in document order. Text read from a CDATA section is
+    /// written as a CDATA section. With `pretty`, child elements start on a
+    /// new line, indented by two spaces per level.
+    pub fn to_xml_string(&self, pretty: bool) -> String {
+        let mut writer = XmlWriter {
+            output: String::new(),
+            pretty,
+            scopes: Vec::new(),
+        };
+        if let Some(root) = &self.root {
+            writer.element(root, 0);
+        }
+        writer.output
+    }
+}
+
+/// Namespace declarations of an element, by prefix (empty for the default namespace)
+type Scope = HashMap<String, String>;
+
+struct XmlWriter {
+    output: String,
+    pretty: bool,
+    /// Namespace declarations of the open elements
+    scopes: Vec<Scope>,
+}
+
+impl XmlWriter {
+    fn element(&mut self, elem: &Element, depth: usize) {
+        let mut declarations: Vec<(String, String)> = Vec::new();
+        if let Some(uri) = elem.namespaces.get_default_namespace() {
+            declarations.push((String::new(), uri.to_string()));
+        }
+        declarations.extend(elem.namespaces.iter().map(|(prefix, uri)| (prefix.to_string(), uri.to_string())));
+        self.scopes.push(declarations.iter().cloned().collect());
+
+        let name = self.element_name(elem, &mut declarations);
+        let attributes: Vec<(String, &str)> = elem
+            .attributes
+            .iter()
+            .map(|(qname, value)| {
+                let attr_name = match (elem.attribute_prefixes.get(qname), qname.namespace.as_deref()) {
+                    (Some(prefix), _) => format!("{}:{}", prefix, qname.local_name),
+                    (None, Some(XML_NAMESPACE)) => format!("xml:{}", qname.local_name),
+                    (None, Some(namespace)) => {
+                        let prefix = self.prefix_for(namespace, &mut declarations);
+                        format!("{}:{}", prefix, qname.local_name)
+                    }
+                    (None, None) => qname.local_name.clone(),
+                };
+                (attr_name, value.as_str())
+            })
+            .collect();
+
+        self.output.push('<');
+        self.output.push_str(&name);
+        for (prefix, uri) in &declarations {
+            match prefix.as_str() {
+                "" => self.output.push_str(&format!(" xmlns=\"{}\"", escape(uri.as_str()))),
+                _ => self.output.push_str(&format!(" xmlns:{}=\"{}\"", prefix, escape(uri.as_str()))),
+            }
+        }
+        for (attr_name, value) in attributes {
+            self.output.push_str(&format!(" {}=\"{}\"", attr_name, escape_attribute(value)));
+        }
+
+        if elem.text.is_none() && elem.children.is_empty() {
+            self.output.push_str("/>");
+            self.scopes.pop();
+            return;
+        }
+        self.output.push('>');
+        if let Some(text) = &elem.text {
+            if elem.cdata {
+                self.output.push_str(&format!("<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>")));
+            } else {
+                self.output.push_str(&escape(text.as_str()));
+            }
+        }
+        for child in &elem.children {
+            self.newline(depth + 1);
+            self.element(child, depth + 1);
+        }
+        if !elem.children.is_empty() {
+            self.newline(depth);
+        }
+        self.output.push_str(&format!("</{}>", name));
+        self.scopes.pop();
+    }
+
+    /// The tag of an element, adding a declaration for its namespace if needed
+    fn element_name(&mut self, elem: &Element, declarations: &mut Vec<(String, String)>) -> String {
+        let local_name = elem.local_name();
+        if let Some(prefix) = &elem.prefix {
+            return format!("{}:{}", prefix, local_name);
+        }
+        let Some(namespace) = elem.namespace() else {
+            return local_name.to_string();
+        };
+        if self.lookup("") == Some(namespace) {
+            return local_name.to_string();
+        }
+        match self.in_scope_prefix(namespace) {
+            Some(prefix) => format!("{}:{}", prefix, local_name),
+            None => {
+                self.declare(String::new(), namespace, declarations);
+                local_name.to_string()
+            }
+        }
+    }
+
+    /// A prefix bound to a namespace, declared on the current element if none is in scope
+    fn prefix_for(&mut self, namespace: &str, declarations: &mut Vec<(String, String)>) -> String {
+        if let Some(prefix) = self.in_scope_prefix(namespace) {
+            return prefix;
+        }
+        let prefix = (0..)
+            .map(|n| format!("ns{}", n))
+            .find(|prefix| self.lookup(prefix).is_none())
+            .unwrap_or_default();
+        self.declare(prefix.clone(), namespace, declarations);
+        prefix
+    }
+
+    fn declare(&mut self, prefix: String, namespace: &str, declarations: &mut Vec<(String, String)>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(prefix.clone(), namespace.to_string());
+        }
+        declarations.push((prefix, namespace.to_string()));
+    }
+
+    /// The namespace bound to a prefix in the current scope
+    fn lookup(&self, prefix: &str) -> Option<&str> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(prefix)).map(String::as_str)
+    }
+
+    /// A non-empty prefix bound to a namespace, and not redeclared by an inner element
+    fn in_scope_prefix(&self, namespace: &str) -> Option<String> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter())
+            .find(|(prefix, uri)| !prefix.is_empty() && uri.as_str() == namespace && self.lookup(prefix) == Some(namespace))
+            .map(|(prefix, _)| prefix.clone())
+    }
+
+    fn newline(&mut self, depth: usize) {
+        if self.pretty {
+            self.output.push('\n');
+            self.output.push_str(&"  ".repeat(depth));
+        }
+    }
+}
+
+/// Escape an attribute value, keeping whitespace characters that attribute
+/// value normalization would replace with spaces
+fn escape_attribute(value: &str) -> String {
+    escape(value)
+        .replace('\t', "&#9;")
+        .replace('\n', "&#10;")
+        .replace('\r', "&#13;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::namespaces::QName;
+
+    const CATALOG_XML: &str = r#"<?xml version="1.0"?>
+<cat:catalog xmlns:cat="http://example.com/catalog" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://example.com/catalog catalog.xsd" version="2">
+  <cat:book id="b1" lang="en">
+    <cat:title>Fish &amp; Chips</cat:title>
+    <cat:note><![CDATA[<b>bold</b> & more]]></cat:note>
+    <cat:empty/>
+  </cat:book>
+  <item xmlns="http://example.com/items" code="x&lt;y"/>
+</cat:catalog>"#;
+
+    /// The structure of an element, as (name, attributes, text, children)
+    fn structure(elem: &Element) -> String {
+        let attributes: Vec<String> = elem
+            .attributes
+            .iter()
+            .map(|(qname, value)| format!("{}={}", qname.local_name, value))
+            .collect();
+        let children: Vec<String> = elem.children.iter().map(structure).collect();
+        format!(
+            "{:?}:{}[{}]({:?})<{}>",
+            elem.prefix,
+            elem.local_name(),
+            attributes.join(","),
+            elem.text,
+            children.join(",")
+        )
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let doc = Document::from_string(CATALOG_XML).unwrap();
+        let xml = doc.to_xml_string(false);
+        let reparsed = Document::from_string(&xml).unwrap();
+
+        assert_eq!(structure(reparsed.root().unwrap()), structure(doc.root().unwrap()));
+        assert_eq!(reparsed.to_xml_string(false), xml);
+        assert!(xml.starts_with(
+            r#"<cat:catalog xmlns:cat="http://example.com/catalog" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="#
+        ));
+        assert!(xml.contains(r#"<cat:book id="b1" lang="en">"#));
+        assert!(xml.contains("<cat:title>Fish &amp; Chips</cat:title>"));
+        assert!(xml.contains("<cat:note><![CDATA[<b>bold</b> & more]]></cat:note>"));
+        assert!(xml.contains(r#"<item xmlns="http://example.com/items" code="x&lt;y"/>"#));
+        assert!(!xml.contains('\n'));
+    }
+
+    #[test]
+    fn test_pretty_print() {
+        let doc = Document::from_string(CATALOG_XML).unwrap();
+        let xml = doc.to_xml_string(true);
+        let lines: Vec<&str> = xml.lines().collect();
+
+        assert_eq!(lines.len(), 8);
+        assert!(lines[1].starts_with(r#"  <cat:book id="b1""#));
+        assert_eq!(lines[2], "    <cat:title>Fish &amp; Chips</cat:title>");
+        assert_eq!(lines[4], "    <cat:empty/>");
+        assert_eq!(lines[5], "  </cat:book>");
+        assert_eq!(lines[7], "</cat:catalog>");
+
+        let reparsed = Document::from_string(&xml).unwrap();
+        assert_eq!(structure(reparsed.root().unwrap()), structure(doc.root().unwrap()));
+    }
+
+    #[test]
+    fn test_modified_document() {
+        let mut doc = Document::from_string(r#"<p:root xmlns:p="urn:p"><p:a/></p:root>"#).unwrap();
+        let root = doc.root_mut().unwrap();
+        root.attributes.insert(QName::namespaced("urn:q", "flag"), "on".to_string());
+        root.attributes.insert(QName::namespaced(XML_NAMESPACE, "lang"), "en".to_string());
+        let mut child = Element::new(QName::namespaced("urn:p", "b"));
+        child.set_text("1 < 2".to_string());
+        root.add_child(child);
+        root.add_child(Element::new(QName::namespaced("urn:other", "c")));
+
+        assert_eq!(
+            doc.to_xml_string(false),
+            r#"<p:root xmlns:p="urn:p" xmlns:ns0="urn:q" ns0:flag="on" xml:lang="en"><p:a/><p:b>1 &lt; 2</p:b><c xmlns="urn:other"/></p:root>"#
+        );
+    }
+
+    #[test]
+    fn test_empty_document() {
+        assert_eq!(Document::new().to_xml_string(true), "");
+    }
+}
@@ -0,0 +1,860 @@
+//! DTD parsing and validation
+//!
+//! This module provides a basic DTD implementation for documents that carry
+//! a DOCTYPE declaration, mainly legacy XSD files that reference the XSD
+//! meta-schema DTD. Element, attribute list and parameter entity declarations
+//! are supported; general entities and conditional sections are ignored.
+//!
+//! The document model does not keep namespace prefixes, so element and
+//! attribute names are matched by their local part.
+
+use crate::catalog::XmlCatalog;
+use crate::documents::Element;
+use crate::error::{Error, ParseError, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Public identifier of the XSD 1.0 meta-schema DTD
+pub const XSD_DTD_PUBLIC_ID: &str = "-//W3C//DTD XMLSCHEMA 200102//EN";
+
+/// Embedded DTD for XSD 1.0 schema documents
+///
+/// Declares the attributes of every XSD element. Content models are `ANY`,
+/// since the ordering rules of XSD are checked by the schema parser.
+const XSD_META_DTD: &str = r#"
+<!ENTITY % id "id ID #IMPLIED">
+<!ENTITY % occurs "minOccurs CDATA #IMPLIED maxOccurs CDATA #IMPLIED">
+<!ENTITY % form "form (qualified|unqualified) #IMPLIED">
+<!ENTITY % facet "value CDATA #REQUIRED fixed (true|false) #IMPLIED %id;">
+
+<!ELEMENT xs:schema ANY>
+<!ATTLIST xs:schema
+    targetNamespace CDATA #IMPLIED
+    version CDATA #IMPLIED
+    finalDefault CDATA #IMPLIED
+    blockDefault CDATA #IMPLIED
+    elementFormDefault (qualified|unqualified) #IMPLIED
+    attributeFormDefault (qualified|unqualified) #IMPLIED
+    xml:lang CDATA #IMPLIED
+    %id;>
+
+<!ELEMENT xs:complexType ANY>
+<!ATTLIST xs:complexType
+    name CDATA #IMPLIED
+    abstract (true|false) #IMPLIED
+    final CDATA #IMPLIED
+    block CDATA #IMPLIED
+    mixed (true|false) #IMPLIED
+    %id;>
+
+<!ELEMENT xs:complexContent ANY>
+<!ATTLIST xs:complexContent mixed (true|false) #IMPLIED %id;>
+<!ELEMENT xs:simpleContent ANY>
+<!ATTLIST xs:simpleContent %id;>
+<!ELEMENT xs:extension ANY>
+<!ATTLIST xs:extension base CDATA #REQUIRED %id;>
+<!ELEMENT xs:restriction ANY>
+<!ATTLIST xs:restriction base CDATA #IMPLIED %id;>
+
+<!ELEMENT xs:element ANY>
+<!ATTLIST xs:element
+    name CDATA #IMPLIED
+    ref CDATA #IMPLIED
+    type CDATA #IMPLIED
+    %occurs;
+    nillable (true|false) #IMPLIED
+    substitutionGroup CDATA #IMPLIED
+    abstract (true|false) #IMPLIED
+    final CDATA #IMPLIED
+    block CDATA #IMPLIED
+    default CDATA #IMPLIED
+    fixed CDATA #IMPLIED
+    %form;
+    %id;>
+
+<!ELEMENT xs:group ANY>
+<!ATTLIST xs:group name CDATA #IMPLIED ref CDATA #IMPLIED %occurs; %id;>
+<!ELEMENT xs:all ANY>
+<!ATTLIST xs:all %occurs; %id;>
+<!ELEMENT xs:choice ANY>
+<!ATTLIST xs:choice %occurs; %id;>
+<!ELEMENT xs:sequence ANY>
+<!ATTLIST xs:sequence %occurs; %id;>
+<!ELEMENT xs:any ANY>
+<!ATTLIST xs:any
+    namespace CDATA #IMPLIED
+    processContents (skip|lax|strict) #IMPLIED
+    %occurs;
+    %id;>
+<!ELEMENT xs:anyAttribute ANY>
+<!ATTLIST xs:anyAttribute
+    namespace CDATA #IMPLIED
+    processContents (skip|lax|strict) #IMPLIED
+    %id;>
+
+<!ELEMENT xs:attribute ANY>
+<!ATTLIST xs:attribute
+    name CDATA #IMPLIED
+    ref CDATA #IMPLIED
+    type CDATA #IMPLIED
+    use (prohibited|optional|required) #IMPLIED
+    default CDATA #IMPLIED
+    fixed CDATA #IMPLIED
+    %form;
+    %id;>
+<!ELEMENT xs:attributeGroup ANY>
+<!ATTLIST xs:attributeGroup name CDATA #IMPLIED ref CDATA #IMPLIED %id;>
+
+<!ELEMENT xs:unique ANY>
+<!ATTLIST xs:unique name CDATA #REQUIRED %id;>
+<!ELEMENT xs:key ANY>
+<!ATTLIST xs:key name CDATA #REQUIRED %id;>
+<!ELEMENT xs:keyref ANY>
+<!ATTLIST xs:keyref name CDATA #REQUIRED refer CDATA #REQUIRED %id;>
+<!ELEMENT xs:selector ANY>
+<!ATTLIST xs:selector xpath CDATA #REQUIRED %id;>
+<!ELEMENT xs:field ANY>
+<!ATTLIST xs:field xpath CDATA #REQUIRED %id;>
+
+<!ELEMENT xs:include ANY>
+<!ATTLIST xs:include schemaLocation CDATA #REQUIRED %id;>
+<!ELEMENT xs:import ANY>
+<!ATTLIST xs:import namespace CDATA #IMPLIED schemaLocation CDATA #IMPLIED %id;>
+<!ELEMENT xs:redefine ANY>
+<!ATTLIST xs:redefine schemaLocation CDATA #REQUIRED %id;>
+<!ELEMENT xs:notation ANY>
+<!ATTLIST xs:notation
+    name CDATA #REQUIRED
+    public CDATA #IMPLIED
+    system CDATA #IMPLIED
+    %id;>
+
+<!ELEMENT xs:annotation ANY>
+<!ATTLIST xs:annotation %id;>
+<!ELEMENT xs:appinfo ANY>
+<!ATTLIST xs:appinfo source CDATA #IMPLIED>
+<!ELEMENT xs:documentation ANY>
+<!ATTLIST xs:documentation source CDATA #IMPLIED xml:lang CDATA #IMPLIED>
+
+<!ELEMENT xs:simpleType ANY>
+<!ATTLIST xs:simpleType name CDATA #IMPLIED final CDATA #IMPLIED %id;>
+<!ELEMENT xs:list ANY>
+<!ATTLIST xs:list itemType CDATA #IMPLIED %id;>
+<!ELEMENT xs:union ANY>
+<!ATTLIST xs:union memberTypes CDATA #IMPLIED %id;>
+
+<!ELEMENT xs:minExclusive ANY>
+<!ATTLIST xs:minExclusive %facet;>
+<!ELEMENT xs:minInclusive ANY>
+<!ATTLIST xs:minInclusive %facet;>
+<!ELEMENT xs:maxExclusive ANY>
+<!ATTLIST xs:maxExclusive %facet;>
+<!ELEMENT xs:maxInclusive ANY>
+<!ATTLIST xs:maxInclusive %facet;>
+<!ELEMENT xs:totalDigits ANY>
+<!ATTLIST xs:totalDigits %facet;>
+<!ELEMENT xs:fractionDigits ANY>
+<!ATTLIST xs:fractionDigits %facet;>
+<!ELEMENT xs:length ANY>
+<!ATTLIST xs:length %facet;>
+<!ELEMENT xs:minLength ANY>
+<!ATTLIST xs:minLength %facet;>
+<!ELEMENT xs:maxLength ANY>
+<!ATTLIST xs:maxLength %facet;>
+<!ELEMENT xs:whiteSpace ANY>
+<!ATTLIST xs:whiteSpace %facet;>
+<!ELEMENT xs:enumeration ANY>
+<!ATTLIST xs:enumeration value CDATA #REQUIRED %id;>
+<!ELEMENT xs:pattern ANY>
+<!ATTLIST xs:pattern value CDATA #REQUIRED %id;>
+"#;
+
+/// Maximum number of parameter entity expansion passes
+const MAX_ENTITY_EXPANSIONS: usize = 32;
+
+/// A problem found while validating a document against its DTD
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoctypeWarning {
+    /// Warning message
+    pub message: String,
+    /// Local name of the element the warning is about
+    pub element: Option<String>,
+}
+
+impl DoctypeWarning {
+    /// Create a warning that is not tied to an element
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            element: None,
+        }
+    }
+
+    /// Create a warning about an element
+    pub fn at(element: &str, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            element: Some(element.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for DoctypeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// A DOCTYPE declaration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Doctype {
+    /// Name of the document element
+    pub root_name: String,
+    /// Public identifier of the external subset
+    pub public_id: Option<String>,
+    /// System identifier of the external subset
+    pub system_id: Option<String>,
+    /// Internal subset, without the surrounding brackets
+    pub internal_subset: Option<String>,
+}
+
+impl Doctype {
+    /// Find the DOCTYPE declaration of an XML document, if any
+    pub fn find(xml: &str) -> Result<Option<Self>> {
+        let mut reader = Reader::from_str(xml);
+        loop {
+            match reader.read_event() {
+                Ok(Event::DocType(text)) => {
+                    let decl = std::str::from_utf8(&text)
+                        .map_err(|e| Error::Xml(format!("Invalid DOCTYPE declaration: {}", e)))?;
+                    return Self::parse(decl).map(Some);
+                }
+                Ok(Event::Start(_)) | Ok(Event::Empty(_)) | Ok(Event::Eof) => return Ok(None),
+                Ok(_) => {}
+                Err(e) => return Err(Error::Xml(format!("Error reading DOCTYPE: {}", e))),
+            }
+        }
+    }
+
+    /// Parse the content of a DOCTYPE declaration
+    ///
+    /// The content is everything between `<!DOCTYPE` and the closing `>`.
+    pub fn parse(decl: &str) -> Result<Self> {
+        let (external, internal_subset) = match (decl.find('['), decl.rfind(']')) {
+            (Some(start), Some(end)) if start < end => {
+                (&decl[..start], Some(decl[start + 1..end].to_string()))
+            }
+            _ => (decl, None),
+        };
+
+        let tokens = tokenize(external);
+        let root_name = tokens
+            .first()
+            .ok_or_else(|| Error::Parse(ParseError::new("DOCTYPE declaration has no name")))?
+            .to_string();
+        let literal = |index: usize| tokens.get(index).map(|t| unquote(t).to_string());
+        let (public_id, system_id) = match tokens.get(1).map(|t| t.as_str()) {
+            Some("PUBLIC") => (literal(2), literal(3)),
+            Some("SYSTEM") => (None, literal(2)),
+            _ => (None, None),
+        };
+
+        Ok(Self {
+            root_name,
+            public_id,
+            system_id,
+            internal_subset,
+        })
+    }
+
+    /// Check if the external subset is the XSD meta-schema DTD
+    pub fn is_xsd_meta_schema(&self) -> bool {
+        self.public_id.as_deref() == Some(XSD_DTD_PUBLIC_ID)
+            || self
+                .system_id
+                .as_deref()
+                .is_some_and(|system| system.ends_with("XMLSchema.dtd"))
+    }
+
+    /// Load the DTD declared by this DOCTYPE
+    ///
+    /// The internal subset is always used. The external subset is the
+    /// embedded meta-schema DTD for XSD documents, or a file found through
+    /// the catalog by public or system identifier. An external subset that
+    /// cannot be loaded is reported as a warning.
+    pub fn load_dtd(&self, catalog: Option<&XmlCatalog>) -> Result<(Dtd, Vec<DoctypeWarning>)> {
+        let mut warnings = Vec::new();
+        let mut text = self.internal_subset.clone().unwrap_or_default();
+
+        let external_id = self.public_id.as_deref().or(self.system_id.as_deref());
+        if self.is_xsd_meta_schema() {
+            text.push_str(XSD_META_DTD);
+        } else if let Some(external_id) = external_id {
+            let resolved = catalog.and_then(|catalog| {
+                self.public_id
+                    .as_deref()
+                    .and_then(|id| catalog.resolve(id))
+                    .or_else(|| self.system_id.as_deref().and_then(|id| catalog.resolve(id)))
+            });
+            match resolved.map(std::fs::read_to_string) {
+                Some(Ok(external)) => text.push_str(&external),
+                _ => warnings.push(DoctypeWarning::new(format!(
+                    "External DTD '{}' could not be loaded; only the internal subset is validated",
+                    external_id
+                ))),
+            }
+        }
+
+        Ok((Dtd::parse(&text)?, warnings))
+    }
+}
+
+/// Content specification of an element declaration
+#[derive(Debug, Clone)]
+pub enum ContentSpec {
+    /// No content allowed
+    Empty,
+    /// Any declared elements and character data
+    Any,
+    /// Character data mixed with the listed elements
+    Mixed(Vec<String>),
+    /// Element content matching a content model
+    Children {
+        /// Content model as written in the DTD
+        model: String,
+        /// Compiled content model, matched against `<name>` tokens
+        regex: Regex,
+    },
+}
+
+/// An attribute default declaration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttributeDefault {
+    /// The attribute must be present
+    Required,
+    /// The attribute is optional
+    Implied,
+    /// The attribute must have the given value
+    Fixed(String),
+    /// The attribute defaults to the given value
+    Value(String),
+}
+
+/// An attribute declaration from an ATTLIST
+#[derive(Debug, Clone)]
+pub struct AttributeDecl {
+    /// Attribute name
+    pub name: String,
+    /// Attribute type (CDATA, ID, ... or an enumeration)
+    pub attr_type: String,
+    /// Allowed values of an enumerated type
+    pub values: Vec<String>,
+    /// Default declaration
+    pub default: AttributeDefault,
+}
+
+/// A parsed DTD
+#[derive(Debug, Clone, Default)]
+pub struct Dtd {
+    /// Element declarations by local name
+    elements: HashMap<String, ContentSpec>,
+    /// Attribute declarations by element local name
+    attributes: HashMap<String, Vec<AttributeDecl>>,
+}
+
+impl Dtd {
+    /// Parse DTD declarations
+    ///
+    /// When an element or attribute is declared more than once, the first
+    /// declaration is used, so an internal subset placed before the
+    /// external subset takes precedence.
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = expand_parameter_entities(&strip_comments(text));
+        let mut dtd = Dtd::default();
+
+        for decl in declarations(&text) {
+            if let Some(body) = decl.strip_prefix("ELEMENT") {
+                dtd.parse_element_decl(body)?;
+            } else if let Some(body) = decl.strip_prefix("ATTLIST") {
+                dtd.parse_attlist_decl(body)?;
+            }
+        }
+
+        Ok(dtd)
+    }
+
+    /// Get the content specification of an element
+    pub fn element(&self, name: &str) -> Option<&ContentSpec> {
+        self.elements.get(local_part(name))
+    }
+
+    /// Get the attribute declarations of an element
+    pub fn attributes(&self, element: &str) -> &[AttributeDecl] {
+        self.attributes
+            .get(local_part(element))
+            .map(|attrs| attrs.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Validate an element and its descendants
+    pub fn validate(&self, element: &Element) -> Vec<DoctypeWarning> {
+        let mut warnings = Vec::new();
+        self.validate_element(element, &mut warnings);
+        warnings
+    }
+
+    fn validate_element(&self, element: &Element, warnings: &mut Vec<DoctypeWarning>) {
+        let name = element.local_name();
+        match self.element(name) {
+            None => warnings.push(DoctypeWarning::at(
+                name,
+                format!("Element '{}' is not declared in the DTD", name),
+            )),
+            Some(spec) => self.validate_content(element, spec, warnings),
+        }
+        self.validate_attributes(element, warnings);
+
+        for child in &element.children {
+            self.validate_element(child, warnings);
+        }
+    }
+
+    fn validate_content(&self, element: &Element, spec: &ContentSpec, warnings: &mut Vec<DoctypeWarning>) {
+        let name = element.local_name();
+        match spec {
+            ContentSpec::Any => {}
+            ContentSpec::Empty => {
+                if !element.children.is_empty() || element.text.is_some() {
+                    warnings.push(DoctypeWarning::at(
+                        name,
+                        format!("Element '{}' is declared EMPTY but has content", name),
+                    ));
+                }
+            }
+            ContentSpec::Mixed(allowed) => {
+                for child in &element.children {
+                    if !allowed.iter().any(|a| a == child.local_name()) {
+                        warnings.push(DoctypeWarning::at(
+                            name,
+                            format!("Element '{}' is not allowed in '{}'", child.local_name(), name),
+                        ));
+                    }
+                }
+            }
+            ContentSpec::Children { model, regex } => {
+                if element.text.is_some() {
+                    warnings.push(DoctypeWarning::at(
+                        name,
+                        format!("Element '{}' has element-only content but contains text", name),
+                    ));
+                }
+                let children: String = element
+                    .children
+                    .iter()
+                    .map(|child| format!("<{}>", child.local_name()))
+                    .collect();
+                if !regex.is_match(&children) {
+                    warnings.push(DoctypeWarning::at(
+                        name,
+                        format!("Content of element '{}' does not match its content model {}", name, model),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn validate_attributes(&self, element: &Element, warnings: &mut Vec<DoctypeWarning>) {
+        let name = element.local_name();
+        let decls = self.attributes(name);
+
+        let mut present: Vec<&String> = element.attributes.keys().map(|q| &q.local_name).collect();
+        present.sort();
+        for attr_name in present {
+            if !decls.iter().any(|d| local_part(&d.name) == attr_name) {
+                warnings.push(DoctypeWarning::at(
+                    name,
+                    format!("Attribute '{}' is not declared for element '{}'", attr_name, name),
+                ));
+            }
+        }
+
+        for decl in decls {
+            let value = element.get_attribute(local_part(&decl.name));
+            match (value, &decl.default) {
+                (None, AttributeDefault::Required) => warnings.push(DoctypeWarning::at(
+                    name,
+                    format!("Required attribute '{}' is missing on element '{}'", decl.name, name),
+                )),
+                (Some(value), AttributeDefault::Fixed(fixed)) if value != fixed => {
+                    warnings.push(DoctypeWarning::at(
+                        name,
+                        format!("Attribute '{}' must have the fixed value '{}'", decl.name, fixed),
+                    ))
+                }
+                _ => {}
+            }
+            if let Some(value) = value {
+                if !decl.values.is_empty() && !decl.values.iter().any(|v| v == value) {
+                    warnings.push(DoctypeWarning::at(
+                        name,
+                        format!(
+                            "Attribute '{}' has value '{}', expected one of {}",
+                            decl.name,
+                            value,
+                            decl.values.join("|")
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn parse_element_decl(&mut self, body: &str) -> Result<()> {
+        let body = body.trim();
+        let (name, spec) = body
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| invalid_dtd(format!("incomplete element declaration '{}'", body)))?;
+        let spec = spec.trim();
+
+        let content = match spec {
+            "EMPTY" => ContentSpec::Empty,
+            "ANY" => ContentSpec::Any,
+            _ if spec.trim_start_matches('(').trim_start().starts_with("#PCDATA") => ContentSpec::Mixed(
+                spec.split(|c: char| matches!(c, '(' | ')' | '|' | '*') || c.is_whitespace())
+                    .filter(|n| !n.is_empty() && *n != "#PCDATA")
+                    .map(|n| local_part(n).to_string())
+                    .collect(),
+            ),
+            _ => ContentSpec::Children {
+                model: spec.to_string(),
+                regex: content_model_regex(spec)?,
+            },
+        };
+
+        self.elements.entry(local_part(name).to_string()).or_insert(content);
+        Ok(())
+    }
+
+    fn parse_attlist_decl(&mut self, body: &str) -> Result<()> {
+        let tokens = tokenize(body);
+        let (element, mut rest) = tokens
+            .split_first()
+            .ok_or_else(|| invalid_dtd("attribute list declaration has no element name"))?;
+        let decls = self.attributes.entry(local_part(element).to_string()).or_default();
+
+        while let [name, attr_type, tail @ ..] = rest {
+            let (attr_type, tail) = if attr_type == "NOTATION" {
+                match tail.split_first() {
+                    Some((values, tail)) => (values, tail),
+                    None => return Err(invalid_dtd(format!("incomplete NOTATION attribute '{}'", name))),
+                }
+            } else {
+                (attr_type, tail)
+            };
+            let values = if attr_type.starts_with('(') {
+                attr_type
+                    .trim_matches(|c| c == '(' || c == ')')
+                    .split('|')
+                    .map(|v| v.trim().to_string())
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            let (default, tail) = match tail {
+                [d, tail @ ..] if d == "#REQUIRED" => (AttributeDefault::Required, tail),
+                [d, tail @ ..] if d == "#IMPLIED" => (AttributeDefault::Implied, tail),
+                [d, value, tail @ ..] if d == "#FIXED" => (AttributeDefault::Fixed(unquote(value).to_string()), tail),
+                [value, tail @ ..] if is_quoted(value) => (AttributeDefault::Value(unquote(value).to_string()), tail),
+                _ => return Err(invalid_dtd(format!("missing default declaration for attribute '{}'", name))),
+            };
+
+            if !decls.iter().any(|d| local_part(&d.name) == local_part(name)) {
+                decls.push(AttributeDecl {
+                    name: name.clone(),
+                    attr_type: attr_type.clone(),
+                    values,
+                    default,
+                });
+            }
+            rest = tail;
+        }
+
+        if rest.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid_dtd(format!("incomplete attribute list for '{}'", element)))
+        }
+    }
+}
+
+fn invalid_dtd(message: impl fmt::Display) -> Error {
+    Error::Parse(ParseError::new(format!("Invalid DTD: {}", message)))
+}
+
+/// Strip the prefix of a qualified name
+fn local_part(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn is_quoted(token: &str) -> bool {
+    token.len() >= 2 && (token.starts_with('"') || token.starts_with('\''))
+}
+
+fn unquote(token: &str) -> &str {
+    if is_quoted(token) {
+        &token[1..token.len() - 1]
+    } else {
+        token
+    }
+}
+
+/// Split declaration text into names, quoted literals and parenthesized groups
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let end = match c {
+            '"' | '\'' => {
+                chars.next();
+                chars.find(|&(_, ch)| ch == c).map(|(i, _)| i + 1).unwrap_or(text.len())
+            }
+            '(' => {
+                let mut depth = 0;
+                let mut end = text.len();
+                for (i, ch) in chars.by_ref() {
+                    match ch {
+                        '(' => depth += 1,
+                        ')' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                end = i + 1;
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                end
+            }
+            _ => {
+                let mut end = text.len();
+                while let Some(&(i, ch)) = chars.peek() {
+                    if ch.is_whitespace() || ch == '(' {
+                        end = i;
+                        break;
+                    }
+                    chars.next();
+                }
+                end
+            }
+        };
+        tokens.push(text[start..end].to_string());
+    }
+
+    tokens
+}
+
+fn strip_comments(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("<!--") {
+        result.push_str(&rest[..start]);
+        rest = match rest[start..].find("-->") {
+            Some(end) => &rest[start + end + 3..],
+            None => "",
+        };
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Collect internal parameter entities and substitute their references
+fn expand_parameter_entities(text: &str) -> String {
+    let entity_decl = Regex::new(r#"<!ENTITY\s+%\s+([^\s"']+)\s+("[^"]*"|'[^']*')\s*>"#).expect("valid regex");
+    let entities: HashMap<String, String> = entity_decl
+        .captures_iter(text)
+        .map(|caps| (caps[1].to_string(), unquote(&caps[2]).to_string()))
+        .collect();
+
+    let mut text = entity_decl.replace_all(text, "").to_string();
+    let reference = Regex::new(r"%([A-Za-z_][\w.\-]*);").expect("valid regex");
+    for _ in 0..MAX_ENTITY_EXPANSIONS {
+        if !reference.is_match(&text) {
+            break;
+        }
+        text = reference
+            .replace_all(&text, |caps: &regex::Captures| {
+                entities.get(&caps[1]).cloned().unwrap_or_default()
+            })
+            .to_string();
+    }
+    text
+}
+
+/// Iterate over the markup declarations (`<!...>`), without the delimiters
+fn declarations(text: &str) -> Vec<&str> {
+    let mut decls = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("<!") {
+        let body = &rest[start + 2..];
+        let mut quote = None;
+        let end = body.char_indices().find(|&(_, c)| {
+            match (quote, c) {
+                (None, '"' | '\'') => quote = Some(c),
+                (Some(q), _) if q == c => quote = None,
+                (None, '>') => return true,
+                _ => {}
+            }
+            false
+        });
+        match end {
+            Some((end, _)) => {
+                decls.push(body[..end].trim());
+                rest = &body[end + 1..];
+            }
+            None => break,
+        }
+    }
+    decls
+}
+
+/// Compile a children content model into a regex over `<name>` tokens
+fn content_model_regex(model: &str) -> Result<Regex> {
+    let mut pattern = String::from("^");
+    let mut name = String::new();
+    let flush = |name: &mut String, pattern: &mut String| {
+        if !name.is_empty() {
+            pattern.push_str(&format!("(?:<{}>)", regex::escape(local_part(name))));
+            name.clear();
+        }
+    };
+
+    for c in model.chars() {
+        match c {
+            '(' | ')' | ',' | '|' | '?' | '*' | '+' => {
+                flush(&mut name, &mut pattern);
+                match c {
+                    '(' => pattern.push_str("(?:"),
+                    ',' => {}
+                    _ => pattern.push(c),
+                }
+            }
+            c if c.is_whitespace() => flush(&mut name, &mut pattern),
+            c => name.push(c),
+        }
+    }
+    flush(&mut name, &mut pattern);
+    pattern.push('$');
+
+    Regex::new(&pattern).map_err(|e| invalid_dtd(format!("content model {}: {}", model, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documents::Document;
+
+    const ARTICLE_DTD: &str = r#"
+<!-- A small article DTD -->
+<!ENTITY % inline "em | code">
+<!ELEMENT article (title, (para | list)+, note?)>
+<!ELEMENT title (#PCDATA)>
+<!ELEMENT para (#PCDATA | %inline;)*>
+<!ELEMENT em (#PCDATA)>
+<!ELEMENT code (#PCDATA)>
+<!ELEMENT list (item+)>
+<!ELEMENT item (#PCDATA)>
+<!ELEMENT note EMPTY>
+<!ATTLIST article
+    id ID #REQUIRED
+    status (draft|final) "draft"
+    version CDATA #FIXED "1.0">
+<!ATTLIST note ref CDATA #IMPLIED>
+"#;
+
+    fn validate(xml: &str) -> Vec<String> {
+        let dtd = Dtd::parse(ARTICLE_DTD).unwrap();
+        let doc = Document::from_string(xml).unwrap();
+        dtd.validate(doc.root().unwrap()).into_iter().map(|w| w.message).collect()
+    }
+
+    #[test]
+    fn test_parse_doctype() {
+        let doctype = Doctype::parse(
+            r#"xs:schema PUBLIC "-//W3C//DTD XMLSCHEMA 200102//EN" "XMLSchema.dtd" [ <!ENTITY % p "xs:"> ]"#,
+        )
+        .unwrap();
+        assert_eq!(doctype.root_name, "xs:schema");
+        assert_eq!(doctype.public_id.as_deref(), Some(XSD_DTD_PUBLIC_ID));
+        assert_eq!(doctype.system_id.as_deref(), Some("XMLSchema.dtd"));
+        assert_eq!(doctype.internal_subset.as_deref(), Some(r#" <!ENTITY % p "xs:"> "#));
+        assert!(doctype.is_xsd_meta_schema());
+
+        let doctype = Doctype::parse("note SYSTEM 'note.dtd'").unwrap();
+        assert_eq!(doctype.public_id, None);
+        assert_eq!(doctype.system_id.as_deref(), Some("note.dtd"));
+        assert!(!doctype.is_xsd_meta_schema());
+
+        assert_eq!(Doctype::find("<root/>").unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_dtd() {
+        let dtd = Dtd::parse(ARTICLE_DTD).unwrap();
+
+        assert!(matches!(dtd.element("note"), Some(ContentSpec::Empty)));
+        match dtd.element("para") {
+            Some(ContentSpec::Mixed(names)) => assert_eq!(names, &["em", "code"]),
+            other => panic!("unexpected content spec {:?}", other),
+        }
+        let attrs = dtd.attributes("article");
+        assert_eq!(attrs.len(), 3);
+        assert_eq!(attrs[0].default, AttributeDefault::Required);
+        assert_eq!(attrs[1].values, vec!["draft", "final"]);
+        assert_eq!(attrs[1].default, AttributeDefault::Value("draft".to_string()));
+        assert_eq!(attrs[2].default, AttributeDefault::Fixed("1.0".to_string()));
+
+        assert!(Dtd::parse("<!ATTLIST article id ID>").is_err());
+    }
+
+    #[test]
+    fn test_validate_against_dtd() {
+        assert!(validate(
+            r#"<article id="a1"><title>T</title><para>Some <em>text</em></para><list><item>x</item></list><note/></article>"#
+        )
+        .is_empty());
+
+        assert_eq!(
+            validate(r#"<article id="a1" status="old" lang="en"><title>T</title><para>p</para></article>"#),
+            vec![
+                "Attribute 'lang' is not declared for element 'article'",
+                "Attribute 'status' has value 'old', expected one of draft|final",
+            ]
+        );
+        assert_eq!(
+            validate(r#"<article version="2.0"><para>p</para><title>T</title></article>"#),
+            vec![
+                "Content of element 'article' does not match its content model (title, (para | list)+, note?)",
+                "Required attribute 'id' is missing on element 'article'",
+                "Attribute 'version' must have the fixed value '1.0'",
+            ]
+        );
+        assert_eq!(
+            validate(r#"<article id="a"><title>T</title><para><b>x</b></para><note>n</note></article>"#),
+            vec![
+                "Element 'b' is not allowed in 'para'",
+                "Element 'b' is not declared in the DTD",
+                "Element 'note' is declared EMPTY but has content",
+            ]
+        );
+    }
+}
@@ -8,6 +8,9 @@ use std::path::{Path, PathBuf};
 
 use crate::error::{Error, Result};
 
+mod json_schema;
+mod xsd_writer;
+
 /// Configuration for schema export
 #[derive(Debug, Clone)]
 pub struct ExportConfig {
@@ -0,0 +1,594 @@
+//! JSON Schema export
+//!
+//! This module describes the JSON produced by
+//! [`XsdSchema::decode_to_value`] with the default converter as a JSON
+//! Schema (draft 7):
+//! - attributes are `@`-prefixed properties, simple and mixed text the `$`
+//!   property;
+//! - booleans and numbers are typed, other simple values are strings,
+//!   restricted by the enumeration, pattern, length and bound facets;
+//! - a repeated element is either a single value or an array, and the
+//!   alternatives of a required choice are `oneOf` constraints;
+//! - named complex types and global elements with anonymous types are
+//!   written in `definitions` and referred to with `$ref`, so recursive types
+//!   are described too.
+//!
+//! Objects allow additional properties, for the namespace declarations
+//! of the root element and wildcard content.
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use serde_json::{json, Map, Number, Value as JsonValue};
+
+use crate::error::{Error, Result};
+use crate::namespaces::QName;
+use crate::validators::builtins::is_builtin_qname;
+use crate::validators::decoding::typed_value;
+use crate::validators::facets::NumericBound;
+use crate::validators::globals::qname_order;
+use crate::validators::groups::ElementParticle;
+use crate::validators::particles::Occurs;
+use crate::validators::{
+    AttributeUse, ComplexContent, ElementType, GlobalType, GroupParticle, ModelType, SimpleType, SimpleTypeVariety,
+    XsdComplexType, XsdElement, XsdGroup, XsdSchema,
+};
+
+/// Dialect of the exported JSON Schemas
+const JSON_SCHEMA_DIALECT: &str = "http://json-schema.org/draft-07/schema#";
+
+/// Prefix of the properties of attributes, as written by the default converter
+const ATTRIBUTE_PREFIX: &str = "@";
+
+/// Property of simple and mixed text, as written by the default converter
+const TEXT_KEY: &str = "$";
+
+/// Maximum nesting of anonymous types and model groups written inline
+const MAX_INLINE_DEPTH: usize = 16;
+
+/// Built-in types decoded as JSON integers
+const INTEGER_TYPES: &[&str] = &[
+    "integer",
+    "nonPositiveInteger",
+    "negativeInteger",
+    "long",
+    "int",
+    "short",
+    "byte",
+    "nonNegativeInteger",
+    "unsignedLong",
+    "unsignedInt",
+    "unsignedShort",
+    "unsignedByte",
+    "positiveInteger",
+];
+
+/// Built-in types decoded as other JSON numbers
+const NUMBER_TYPES: &[&str] = &["decimal", "float", "double"];
+
+impl XsdSchema {
+    /// Export a JSON Schema for the documents of a global element
+    ///
+    /// The JSON Schema describes the values decoded by
+    /// [`decode_to_value`](Self::decode_to_value) with the default
+    /// converter. Returns an error if `root` isn't a global element.
+    pub fn to_json_schema(&self, root: &QName) -> Result<JsonValue> {
+        let element = self
+            .lookup_element(root)
+            .ok_or_else(|| Error::Key(format!("Unknown root element: {}", root.to_string())))?;
+
+        let mut writer = JsonSchemaWriter::new(self);
+        let root_schema = writer.element_schema(element, 0);
+
+        let mut document = Map::new();
+        document.insert("$schema".to_string(), json!(JSON_SCHEMA_DIALECT));
+        document.insert("title".to_string(), json!(root.local_name));
+        if let JsonValue::Object(map) = root_schema {
+            document.extend(map);
+        }
+        if !writer.defs.is_empty() {
+            document.insert("definitions".to_string(), JsonValue::Object(writer.defs.into_iter().collect()));
+        }
+        Ok(JsonValue::Object(document))
+    }
+}
+
+struct JsonSchemaWriter<'a> {
+    schema: &'a XsdSchema,
+    /// Local names of the global types, kept apart from the element definitions
+    type_names: HashSet<String>,
+    /// Definitions written so far
+    defs: BTreeMap<String, JsonValue>,
+    /// Definitions written or being written
+    seen: HashSet<String>,
+}
+
+impl<'a> JsonSchemaWriter<'a> {
+    fn new(schema: &'a XsdSchema) -> Self {
+        let type_names = schema
+            .types()
+            .filter(|(qname, _)| !is_builtin_qname(qname))
+            .map(|(qname, _)| qname.local_name.clone())
+            .collect();
+        Self {
+            schema,
+            type_names,
+            defs: BTreeMap::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Reference to a definition, written the first time it's referred to
+    ///
+    /// A definition referring to itself gets the reference while it's being
+    /// written.
+    fn definition(&mut self, name: String, build: impl FnOnce(&mut Self) -> JsonValue) -> JsonValue {
+        let reference = json!({ "$ref": format!("#/definitions/{}", name) });
+        if self.seen.insert(name.clone()) {
+            let definition = build(self);
+            self.defs.insert(name, definition);
+        }
+        reference
+    }
+
+    /// Name of the definition of a global element, kept apart from the type names
+    fn element_def_name(&self, qname: &QName) -> String {
+        if self.type_names.contains(&qname.local_name) {
+            format!("{}Element", qname.local_name)
+        } else {
+            qname.local_name.clone()
+        }
+    }
+
+    /// Schema of the value of an element
+    ///
+    /// Anonymous complex types of global elements are written as definitions.
+    fn element_schema(&mut self, element: &XsdElement, depth: usize) -> JsonValue {
+        if let (None, ElementType::Complex(complex_type)) = (&element.type_name, &element.element_type) {
+            if self.schema.lookup_element(&element.name).is_some_and(|global| std::ptr::eq(global.as_ref(), element)) {
+                let complex_type = Arc::clone(complex_type);
+                let name = self.element_def_name(&element.name);
+                return self.definition(name, |writer| writer.complex_type_schema(&complex_type, 0));
+            }
+        }
+        self.element_type_schema(element, depth)
+    }
+
+    /// Schema of the value of an element, from its type
+    fn element_type_schema(&mut self, element: &XsdElement, depth: usize) -> JsonValue {
+        match element.effective_type(self.schema) {
+            Some(GlobalType::Simple(simple_type)) => self.simple_type_schema(simple_type.as_ref()),
+            Some(GlobalType::Complex(complex_type)) => match &complex_type.name {
+                // anyType content can be anything
+                Some(name) if is_builtin_qname(name) => json!({}),
+                Some(name) => {
+                    let name = name.local_name.clone();
+                    self.definition(name, |writer| writer.complex_type_schema(&complex_type, 0))
+                }
+                None if depth < MAX_INLINE_DEPTH => self.complex_type_schema(&complex_type, depth),
+                None => json!({}),
+            },
+            None => json!({}),
+        }
+    }
+
+    /// Schema of an element particle of a content model
+    fn particle_schema(&mut self, particle: &ElementParticle, depth: usize) -> JsonValue {
+        let schema = self.schema;
+        // References to global elements use the element's declaration
+        let global = match (&particle.element_ref, particle.element()) {
+            (Some(element_ref), _) => schema.lookup_element(element_ref),
+            (None, Some(local)) => schema
+                .lookup_element(&particle.name)
+                .filter(|element| Arc::ptr_eq(element, local)),
+            (None, None) => schema.lookup_element(&particle.name),
+        };
+        match (global, particle.element()) {
+            (Some(element), _) => self.element_schema(element, depth),
+            (None, Some(element)) => self.element_type_schema(element, depth),
+            (None, None) => json!({}),
+        }
+    }
+
+    /// Schema of the value of an element with a complex type
+    fn complex_type_schema(&mut self, complex_type: &XsdComplexType, depth: usize) -> JsonValue {
+        let schema = self.schema;
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        let mut choices = Vec::new();
+
+        let text = self.simple_content_schema(complex_type, 0);
+        if let Some(text) = &text {
+            properties.insert(TEXT_KEY.to_string(), text.clone());
+        } else if let ComplexContent::Group(group) = &complex_type.content {
+            for (name, occurs) in schema.element_occurs(group) {
+                if occurs.max == Some(0) {
+                    continue;
+                }
+                let item = match schema.find_element_particle(group, &name) {
+                    Some(particle) => self.particle_schema(particle, depth + 1),
+                    None => json!({}),
+                };
+                if occurs.min > 0 {
+                    required.push(json!(name.local_name));
+                }
+                properties.insert(name.local_name.clone(), repeated(item, occurs));
+            }
+            if complex_type.mixed {
+                properties.insert(TEXT_KEY.to_string(), json!({ "type": "string" }));
+            }
+            self.required_choices(group, group.occurs, 0, &mut choices);
+        }
+
+        let mut attributes: Vec<_> = complex_type.attributes.iter_attributes().collect();
+        attributes.sort_by(|a, b| qname_order(a.name(), b.name()));
+        let has_attributes = attributes.iter().any(|attr| !attr.is_prohibited());
+        for attr in attributes {
+            let key = format!("{}{}", ATTRIBUTE_PREFIX, attr.name().local_name);
+            match attr.use_mode() {
+                AttributeUse::Prohibited => continue,
+                // Missing attributes with a default value are added when decoding
+                AttributeUse::Required => required.push(json!(key)),
+                AttributeUse::Optional if attr.value_constraint().is_some() => required.push(json!(key)),
+                AttributeUse::Optional => {}
+            }
+            let value = attr
+                .effective_type(schema)
+                .map(|simple_type| self.simple_type_schema(simple_type.as_ref()))
+                .unwrap_or_else(|| json!({ "type": "string" }));
+            properties.insert(key, value);
+        }
+
+        // Simple content without attributes is decoded as the bare value
+        let nullable = required.is_empty() && choices.is_empty();
+        match text {
+            Some(text) if !has_attributes => return text,
+            Some(text) if nullable => {
+                return json!({ "anyOf": [text, object_schema(properties, required, choices, nullable)] })
+            }
+            _ => {}
+        }
+        object_schema(properties, required, choices, nullable)
+    }
+
+    /// Collect the choices of a model group that must have one of their elements
+    fn required_choices(&self, group: &XsdGroup, occurs: Occurs, depth: usize, out: &mut Vec<JsonValue>) {
+        if depth > MAX_INLINE_DEPTH || occurs.min == 0 {
+            return;
+        }
+        let group = match &group.group_ref {
+            Some(group_ref) if group.particles.is_empty() => match self.schema.lookup_group(group_ref) {
+                Some(referenced) => referenced.as_ref(),
+                None => return,
+            },
+            _ => group,
+        };
+
+        match group.model {
+            ModelType::Choice => {
+                let mut alternatives = Vec::new();
+                for particle in &group.particles {
+                    match particle {
+                        GroupParticle::Element(element) if element.occurs.min > 0 => {
+                            alternatives.push(json!({ "required": [element.name.local_name] }));
+                        }
+                        // Optional alternatives and nested groups may match no element
+                        _ => return,
+                    }
+                }
+                if alternatives.len() > 1 {
+                    // A repeated choice can have several of its alternatives
+                    let keyword = if occurs.max == Some(1) { "oneOf" } else { "anyOf" };
+                    out.push(json!({ keyword: alternatives }));
+                }
+            }
+            ModelType::Sequence | ModelType::All => {
+                for particle in &group.particles {
+                    if let GroupParticle::Group(nested) = particle {
+                        self.required_choices(nested, nested.occurs, depth + 1, out);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Schema of the text of a complex type with simple content
+    fn simple_content_schema(&self, complex_type: &XsdComplexType, depth: usize) -> Option<JsonValue> {
+        if let ComplexContent::Simple(simple_type) = &complex_type.content {
+            return Some(self.simple_type_schema(simple_type.as_ref()));
+        }
+        if depth > MAX_INLINE_DEPTH || complex_type.model_group().is_some_and(|group| !group.is_empty()) {
+            return None;
+        }
+        let base = complex_type.base_type.as_ref()?;
+        match self.schema.lookup_type(base) {
+            Some(GlobalType::Simple(simple_type)) => Some(self.simple_type_schema(simple_type.as_ref())),
+            Some(GlobalType::Complex(base_type)) if base_type.name != complex_type.name => {
+                self.simple_content_schema(base_type, depth + 1)
+            }
+            Some(GlobalType::Complex(_)) => None,
+            // Built-in simple types aren't all registered in the global maps
+            None if is_builtin_qname(base) && base.local_name != "anyType" => {
+                Some(JsonValue::Object(primitive_schema(Some(&base.local_name))))
+            }
+            None => None,
+        }
+    }
+
+    /// Schema of a simple value, with the facets of its type
+    fn simple_type_schema(&self, simple_type: &dyn SimpleType) -> JsonValue {
+        match simple_type.variety() {
+            // Lists are decoded as their text
+            SimpleTypeVariety::List => json!({ "type": "string" }),
+            SimpleTypeVariety::Union => {
+                let mut members: Vec<JsonValue> = Vec::new();
+                for member in simple_type.member_types() {
+                    let member = self.simple_type_schema(member.as_ref());
+                    if !members.contains(&member) {
+                        members.push(member);
+                    }
+                }
+                match members.len() {
+                    0 => json!({ "type": "string" }),
+                    1 => members.remove(0),
+                    _ => json!({ "anyOf": members }),
+                }
+            }
+            SimpleTypeVariety::Atomic => {
+                let primitive = simple_type.primitive_name();
+                let mut out = primitive_schema(primitive);
+                let facets = simple_type.effective_facets();
+
+                if let Some(enumeration) = facets.enumeration.as_ref().filter(|facet| !facet.values.is_empty()) {
+                    let values: Vec<JsonValue> = enumeration
+                        .values
+                        .iter()
+                        .map(|value| typed_value(simple_type, value).unwrap_or_else(|| json!(value)))
+                        .collect();
+                    out.insert("enum".to_string(), JsonValue::Array(values));
+                    return JsonValue::Object(out);
+                }
+
+                let patterns: Vec<&str> = facets.patterns.iter().map(|facet| facet.pattern.as_str()).collect();
+                match patterns.as_slice() {
+                    [] => {}
+                    [pattern] => {
+                        out.insert("pattern".to_string(), json!(pattern));
+                    }
+                    _ => {
+                        let all: Vec<JsonValue> =
+                            patterns.iter().map(|pattern| json!({ "pattern": pattern })).collect();
+                        out.insert("allOf".to_string(), JsonValue::Array(all));
+                    }
+                }
+
+                if out.get("type") == Some(&json!("string"))
+                    && !matches!(primitive, Some("hexBinary" | "base64Binary"))
+                {
+                    if let Some(length) = &facets.length {
+                        out.insert("minLength".to_string(), json!(length.value));
+                        out.insert("maxLength".to_string(), json!(length.value));
+                    }
+                    if let Some(min_length) = &facets.min_length {
+                        out.insert("minLength".to_string(), json!(min_length.value));
+                    }
+                    if let Some(max_length) = &facets.max_length {
+                        out.insert("maxLength".to_string(), json!(max_length.value));
+                    }
+                } else if out.get("type") != Some(&json!("boolean")) {
+                    let (minimum, maximum) = integer_range(primitive.unwrap_or_default());
+                    if let Some(minimum) = minimum {
+                        out.insert("minimum".to_string(), json!(minimum));
+                    }
+                    if let Some(maximum) = maximum {
+                        out.insert("maximum".to_string(), json!(maximum));
+                    }
+                    let bounds = [
+                        ("minimum", facets.min_inclusive.as_ref().map(|facet| &facet.value)),
+                        ("maximum", facets.max_inclusive.as_ref().map(|facet| &facet.value)),
+                        ("exclusiveMinimum", facets.min_exclusive.as_ref().map(|facet| &facet.value)),
+                        ("exclusiveMaximum", facets.max_exclusive.as_ref().map(|facet| &facet.value)),
+                    ];
+                    for (keyword, bound) in bounds {
+                        if let Some(number) = bound.and_then(bound_number) {
+                            out.insert(keyword.to_string(), JsonValue::Number(number));
+                        }
+                    }
+                }
+                JsonValue::Object(out)
+            }
+        }
+    }
+}
+
+/// Schema of the values of a built-in type
+fn primitive_schema(primitive_name: Option<&str>) -> Map<String, JsonValue> {
+    let json_type = match primitive_name {
+        Some("boolean") => "boolean",
+        Some(name) if INTEGER_TYPES.contains(&name) => "integer",
+        Some(name) if NUMBER_TYPES.contains(&name) => "number",
+        _ => "string",
+    };
+    let mut out = Map::new();
+    out.insert("type".to_string(), json!(json_type));
+    out
+}
+
+/// Value range of the built-in integer types
+fn integer_range(name: &str) -> (Option<i64>, Option<i64>) {
+    match name {
+        "nonPositiveInteger" => (None, Some(0)),
+        "negativeInteger" => (None, Some(-1)),
+        "long" => (Some(i64::MIN), Some(i64::MAX)),
+        "int" => (Some(i32::MIN.into()), Some(i32::MAX.into())),
+        "short" => (Some(i16::MIN.into()), Some(i16::MAX.into())),
+        "byte" => (Some(i8::MIN.into()), Some(i8::MAX.into())),
+        "nonNegativeInteger" | "unsignedLong" => (Some(0), None),
+        "unsignedInt" => (Some(0), Some(u32::MAX.into())),
+        "unsignedShort" => (Some(0), Some(u16::MAX.into())),
+        "unsignedByte" => (Some(0), Some(u8::MAX.into())),
+        "positiveInteger" => (Some(1), None),
+        _ => (None, None),
+    }
+}
+
+/// JSON number of a numeric facet bound, `None` for temporal bounds
+fn bound_number(bound: &NumericBound) -> Option<Number> {
+    match bound {
+        NumericBound::Integer(value) => Some((*value).into()),
+        NumericBound::Decimal(value) => value.to_string().parse().ok(),
+        NumericBound::Float(value) => Number::from_f64(*value),
+        NumericBound::Temporal(_) => None,
+    }
+}
+
+/// Schema of an element that may be repeated
+///
+/// The default converter decodes a single occurrence as the value itself,
+/// and several occurrences as an array.
+fn repeated(item: JsonValue, occurs: Occurs) -> JsonValue {
+    if occurs.max == Some(1) {
+        item
+    } else {
+        json!({ "anyOf": [item.clone(), { "type": "array", "items": item }] })
+    }
+}
+
+/// Schema of an object, `null` too when none of its properties is required
+///
+/// The default converter decodes an empty element as `null`.
+fn object_schema(
+    properties: Map<String, JsonValue>,
+    required: Vec<JsonValue>,
+    choices: Vec<JsonValue>,
+    nullable: bool,
+) -> JsonValue {
+    let mut out = Map::new();
+    out.insert(
+        "type".to_string(),
+        if nullable { json!(["object", "null"]) } else { json!("object") },
+    );
+    out.insert("properties".to_string(), JsonValue::Object(properties));
+    if !required.is_empty() {
+        out.insert("required".to_string(), JsonValue::Array(required));
+    }
+    match choices.len() {
+        0 => {}
+        1 => {
+            if let Some(JsonValue::Object(choice)) = choices.into_iter().next() {
+                out.extend(choice);
+            }
+        }
+        _ => {
+            out.insert("allOf".to_string(), JsonValue::Array(choices));
+        }
+    }
+    JsonValue::Object(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converters::ConverterType;
+
+    const BOOK_NS: &str = "http://example.com/book";
+
+    const TREE_XSD: &str = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="node">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:choice>
+          <xs:element name="label" type="xs:string"/>
+          <xs:element name="code" type="xs:integer"/>
+        </xs:choice>
+        <xs:element ref="node" minOccurs="0" maxOccurs="unbounded"/>
+      </xs:sequence>
+      <xs:attribute name="kind">
+        <xs:simpleType>
+          <xs:restriction base="xs:string">
+            <xs:enumeration value="leaf"/>
+            <xs:enumeration value="branch"/>
+          </xs:restriction>
+        </xs:simpleType>
+      </xs:attribute>
+      <xs:attribute name="open" type="xs:boolean"/>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+
+    fn is_valid(json_schema: &JsonValue, instance: &JsonValue) -> bool {
+        jsonschema::validator_for(json_schema).unwrap().is_valid(instance)
+    }
+
+    #[test]
+    fn test_to_json_schema_book() {
+        let schema = XsdSchema::from_string(include_str!("../../examples/data/book.xsd")).unwrap();
+        let json_schema = schema.to_json_schema(&QName::namespaced(BOOK_NS, "book")).unwrap();
+
+        assert_eq!(json_schema["$schema"], json!(JSON_SCHEMA_DIALECT));
+        assert_eq!(json_schema["$ref"], json!("#/definitions/bookType"));
+        let book_type = &json_schema["definitions"]["bookType"];
+        assert_eq!(book_type["type"], json!("object"));
+        assert_eq!(book_type["required"], json!(["title", "author", "@edition", "@isbn"]));
+        assert_eq!(book_type["properties"]["title"], json!({ "type": "string" }));
+        assert_eq!(book_type["properties"]["pages"], json!({ "type": "integer", "minimum": 1 }));
+        assert_eq!(
+            book_type["properties"]["@isbn"]["pattern"],
+            json!(r"^\d{3}-\d{1,5}-\d{1,7}-\d{1,7}-\d{1}$")
+        );
+        assert_eq!(
+            json_schema["definitions"]["personType"]["properties"]["email"]["maxLength"],
+            json!(255)
+        );
+
+        // Decoded documents are valid against the JSON Schema
+        let value = schema
+            .decode_to_value(include_str!("../../tests/fixtures/book.xml"), ConverterType::Default)
+            .unwrap();
+        assert!(is_valid(&json_schema, &value));
+        let sample = json!({
+            "@isbn": "978-1-59327-828-1",
+            "@edition": 2,
+            "title": "The Book of Rust",
+            "author": { "firstName": "Ann", "lastName": "Lee" }
+        });
+        assert!(is_valid(&json_schema, &sample));
+
+        let mut invalid = sample.clone();
+        invalid["@isbn"] = json!("not an isbn");
+        assert!(!is_valid(&json_schema, &invalid));
+        let mut invalid = sample.clone();
+        invalid["pages"] = json!(0);
+        assert!(!is_valid(&json_schema, &invalid));
+        let mut invalid = sample;
+        invalid.as_object_mut().unwrap().remove("title");
+        assert!(!is_valid(&json_schema, &invalid));
+
+        let unknown = QName::namespaced(BOOK_NS, "magazine");
+        assert!(matches!(schema.to_json_schema(&unknown), Err(Error::Key(_))));
+    }
+
+    #[test]
+    fn test_to_json_schema_recursive_type() {
+        let schema = XsdSchema::from_string(TREE_XSD).unwrap();
+        let json_schema = schema.to_json_schema(&QName::local("node")).unwrap();
+
+        assert_eq!(json_schema["$ref"], json!("#/definitions/node"));
+        let node = &json_schema["definitions"]["node"];
+        assert_eq!(node["oneOf"], json!([{ "required": ["label"] }, { "required": ["code"] }]));
+        assert_eq!(node["properties"]["@kind"], json!({ "type": "string", "enum": ["leaf", "branch"] }));
+        assert_eq!(
+            node["properties"]["node"]["anyOf"],
+            json!([{ "$ref": "#/definitions/node" }, { "type": "array", "items": { "$ref": "#/definitions/node" } }])
+        );
+
+        let xml = r#"<node kind="branch" open="true"><label>root</label><node><code>1</code></node><node><code>2</code><node><label>x</label></node></node></node>"#;
+        let value = schema.decode_to_value(xml, ConverterType::Default).unwrap();
+        assert!(is_valid(&json_schema, &value));
+
+        // One of the alternatives of the choice is required, but not both
+        assert!(!is_valid(&json_schema, &json!({ "@kind": "leaf" })));
+        assert!(!is_valid(&json_schema, &json!({ "label": "a", "code": 1 })));
+        assert!(!is_valid(&json_schema, &json!({ "label": "a", "node": { "code": "one" } })));
+    }
+}
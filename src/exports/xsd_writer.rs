@@ -0,0 +1,898 @@
+//! XSD serialization of schemas
+//!
+//! This module writes a parsed schema back to an XSD document:
+//! - the `xs:schema` element with the target namespace and the form
+//!   defaults, followed by the `xs:import` declarations;
+//! - the global elements, complex types, simple types, model groups,
+//!   attribute groups, attributes and notations of the target namespace.
+//!
+//! Derived complex types are written with their own content and attributes
+//! only, so that parsing the output derives the same types again. Included
+//! schemas are merged into the output; identity constraints and annotations
+//! are not serialized.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use quick_xml::escape::escape;
+
+use crate::error::{Error, Result};
+use crate::namespaces::QName;
+use crate::validators::attributes::{AttributeUse, XsdAttribute, XsdAttributeGroup};
+use crate::validators::base::AttributeValidator;
+use crate::validators::builtins::{get_builtin_type, XSD_NAMESPACE};
+use crate::validators::complex_types::DerivationFlags;
+use crate::validators::globals::qname_order;
+use crate::validators::particles::{Occurs, Particle};
+use crate::validators::schemas::{DerivationDefault, XML_NAMESPACE};
+use crate::validators::wildcards::{NamespaceConstraint, ProcessContents, XsdWildcard};
+use crate::validators::{
+    ComplexContent, DerivationMethod, ElementType, GlobalType, GroupParticle, ModelType, SimpleType, SimpleTypeVariety,
+    XsdComplexType, XsdElement, XsdGroup, XsdSchema,
+};
+
+impl XsdSchema {
+    /// Serialize the schema as an XSD document
+    ///
+    /// Components are written in QName order, simple types after the types
+    /// they are derived from. Parsing the output gives a schema with the same
+    /// components. Returns an error if a component refers to a namespace
+    /// that has no prefix in the schema.
+    pub fn to_xsd_string(&self) -> Result<String> {
+        XsdWriter::new(self).write()
+    }
+}
+
+struct XsdWriter<'a> {
+    schema: &'a XsdSchema,
+    /// Prefixes of the namespaces, by namespace URI
+    prefixes: HashMap<String, String>,
+}
+
+impl<'a> XsdWriter<'a> {
+    fn new(schema: &'a XsdSchema) -> Self {
+        let mut prefixes = HashMap::from([(XSD_NAMESPACE.to_string(), "xs".to_string())]);
+        for (prefix, namespace) in schema.namespace_declarations() {
+            if !prefix.is_empty() && prefix != "xs" && prefix != "xml" && namespace != XSD_NAMESPACE {
+                prefixes.entry(namespace.clone()).or_insert_with(|| prefix.clone());
+            }
+        }
+
+        // The target and imported namespaces need a prefix, even when the
+        // schema declares them as its default namespace
+        let mut imported: Vec<&String> = schema.imports.keys().collect();
+        imported.sort();
+        let namespaces = schema.target_namespace.iter().chain(imported);
+        for (index, namespace) in namespaces.enumerate() {
+            if prefixes.contains_key(namespace) {
+                continue;
+            }
+            let taken: HashSet<&String> = prefixes.values().collect();
+            let preferred = schema
+                .preferred_prefix_for(namespace)
+                .filter(|prefix| !prefix.is_empty() && !taken.contains(&prefix.to_string()))
+                .map(str::to_string);
+            let prefix = preferred.unwrap_or_else(|| {
+                let base = if index == 0 && schema.target_namespace.is_some() { "tns" } else { "ns" };
+                (0..)
+                    .map(|n| if n == 0 { base.to_string() } else { format!("{}{}", base, n) })
+                    .find(|candidate| !taken.contains(candidate))
+                    .expect("unbounded candidates")
+            });
+            prefixes.insert(namespace.clone(), prefix);
+        }
+
+        Self { schema, prefixes }
+    }
+
+    fn write(&self) -> Result<String> {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+        let mut declarations: Vec<(&String, &String)> = self.prefixes.iter().map(|(ns, prefix)| (prefix, ns)).collect();
+        declarations.sort();
+        let mut tag = Tag::new("schema");
+        for (prefix, namespace) in declarations {
+            tag = tag.attr(format!("xmlns:{}", prefix), namespace);
+        }
+        let form = |qualified: bool| if qualified { "qualified" } else { "unqualified" };
+        tag = tag
+            .attr_opt("targetNamespace", self.schema.target_namespace.as_deref())
+            .attr("elementFormDefault", form(self.schema.element_form_default.is_qualified()))
+            .attr("attributeFormDefault", form(self.schema.attribute_form_default.is_qualified()))
+            .attr_opt("blockDefault", default_value(&self.schema.block_default))
+            .attr_opt("finalDefault", default_value(&self.schema.final_default));
+        open(&mut out, 0, &tag);
+
+        let mut imports: Vec<_> = self.schema.imports.values().collect();
+        imports.sort_by(|a, b| a.namespace.cmp(&b.namespace));
+        for import in imports {
+            let tag = Tag::new("import")
+                .attr("namespace", &import.namespace)
+                .attr_opt("schemaLocation", import.location.as_deref());
+            empty(&mut out, 1, &tag);
+        }
+
+        for (_, element) in self.sorted(self.schema.elements()) {
+            self.write_element(&mut out, 1, element, None)?;
+        }
+        for (_, global_type) in self.sorted(self.schema.types()) {
+            if let GlobalType::Complex(ct) = global_type {
+                self.write_complex_type(&mut out, 1, ct)?;
+            }
+        }
+        for (qname, st) in self.simple_types_in_derivation_order() {
+            self.write_simple_type(&mut out, 1, Some(qname), st.as_ref())?;
+        }
+        for (qname, group) in self.sorted(self.schema.groups()) {
+            open(&mut out, 1, &Tag::new("group").attr("name", &qname.local_name));
+            self.write_model_group(&mut out, 2, group, false)?;
+            close(&mut out, 1, "group");
+        }
+        for (qname, group) in self.sorted(self.schema.attribute_groups()) {
+            let tag = Tag::new("attributeGroup").attr("name", &qname.local_name);
+            let mut content = String::new();
+            self.write_attribute_uses(&mut content, 2, group, None)?;
+            wrap(&mut out, 1, &tag, &content);
+        }
+        for (_, attribute) in self.sorted(self.schema.attributes()) {
+            self.write_attribute(&mut out, 1, attribute, true)?;
+        }
+        for (qname, notation) in self.sorted(self.schema.notations()) {
+            let tag = Tag::new("notation")
+                .attr("name", &qname.local_name)
+                .attr_opt("public", notation.public.as_deref())
+                .attr_opt("system", notation.system.as_deref());
+            empty(&mut out, 1, &tag);
+        }
+
+        close(&mut out, 0, "schema");
+        Ok(out)
+    }
+
+    /// Keep the components of the target namespace, in QName order
+    fn sorted<'b, T>(&self, components: impl Iterator<Item = (&'b QName, T)>) -> Vec<(&'b QName, T)> {
+        let mut components: Vec<_> = components
+            .filter(|(qname, _)| qname.namespace == self.schema.target_namespace)
+            .collect();
+        components.sort_by(|a, b| qname_order(a.0, b.0));
+        components
+    }
+
+    /// Get the simple types of the target namespace, each after its dependencies
+    ///
+    /// A simple type depends on its base type and on its item or member
+    /// types, which must be parsed first to be resolved.
+    fn simple_types_in_derivation_order(&self) -> Vec<(&'a QName, &'a Arc<dyn SimpleType + Send + Sync>)> {
+        let simple_types: HashMap<&QName, &Arc<dyn SimpleType + Send + Sync>> = self
+            .sorted(self.schema.types())
+            .into_iter()
+            .filter_map(|(qname, global_type)| global_type.as_simple().map(|st| (qname, st)))
+            .collect();
+        let mut names: Vec<&QName> = simple_types.keys().copied().collect();
+        names.sort_by(|a, b| qname_order(a, b));
+
+        fn visit<'t>(
+            qname: &'t QName,
+            simple_types: &HashMap<&'t QName, &'t Arc<dyn SimpleType + Send + Sync>>,
+            visited: &mut HashSet<&'t QName>,
+            ordered: &mut Vec<(&'t QName, &'t Arc<dyn SimpleType + Send + Sync>)>,
+        ) {
+            let Some((&qname, &st)) = simple_types.get_key_value(qname) else {
+                return;
+            };
+            if !visited.insert(qname) {
+                return;
+            }
+            let dependencies = SimpleType::base_type(st.as_ref())
+                .into_iter()
+                .chain(st.item_type().map(|item| item.as_ref() as &dyn SimpleType))
+                .chain(st.member_types().iter().map(|member| member.as_ref() as &dyn SimpleType));
+            for dependency in dependencies {
+                if let Some(name) = dependency.name() {
+                    if let Some((&name, _)) = simple_types.get_key_value(name) {
+                        visit(name, simple_types, visited, ordered);
+                    }
+                }
+            }
+            ordered.push((qname, st));
+        }
+
+        let mut visited = HashSet::new();
+        let mut ordered = Vec::with_capacity(names.len());
+        for qname in names {
+            visit(qname, &simple_types, &mut visited, &mut ordered);
+        }
+        ordered
+    }
+
+    fn write_element(&self, out: &mut String, depth: usize, element: &XsdElement, occurs: Option<Occurs>) -> Result<()> {
+        let mut tag = Tag::new("element").attr("name", &element.name.local_name);
+        let mut inline_type = String::new();
+        match (&element.type_name, &element.element_type) {
+            (Some(type_name), _) => tag = tag.attr("type", self.prefixed(type_name)?),
+            (None, ElementType::Complex(ct)) => match &ct.name {
+                Some(type_name) => tag = tag.attr("type", self.prefixed(type_name)?),
+                None => self.write_complex_type(&mut inline_type, depth + 1, ct)?,
+            },
+            (None, ElementType::Simple(st)) => match self.simple_type_name(st.as_ref()) {
+                Some(type_name) => tag = tag.attr("type", self.prefixed(&type_name)?),
+                None => self.write_simple_type(&mut inline_type, depth + 1, None, st.as_ref())?,
+            },
+            (None, ElementType::Any) => {}
+        }
+        if let Some(occurs) = occurs {
+            tag = tag.occurs(occurs);
+        }
+        if let Some(head) = &element.substitution_group {
+            tag = tag.attr("substitutionGroup", self.prefixed(head)?);
+        }
+        tag = tag
+            .attr_opt("default", element.default.as_deref())
+            .attr_opt("fixed", element.fixed.as_deref())
+            .attr_if("nillable", "true", element.nillable)
+            .attr_if("abstract", "true", element.abstract_element)
+            .attr_opt("block", self.block_value(element.block));
+
+        for alternative in &element.alternatives {
+            let tag = Tag::new("alternative")
+                .attr_opt("test", alternative.test.as_deref())
+                .attr("type", self.prefixed(&alternative.type_ref)?);
+            empty(&mut inline_type, depth + 1, &tag);
+        }
+        wrap(out, depth, &tag, &inline_type);
+        Ok(())
+    }
+
+    fn write_complex_type(&self, out: &mut String, depth: usize, ct: &XsdComplexType) -> Result<()> {
+        let tag = Tag::new("complexType")
+            .attr_opt("name", ct.name.as_ref().map(|name| name.local_name.as_str()))
+            .attr_if("abstract", "true", ct.abstract_type)
+            .attr_if("mixed", "true", ct.mixed)
+            .attr_opt("block", self.block_value(ct.block));
+        open(out, depth, &tag);
+
+        match (&ct.base_type, ct.derivation) {
+            (Some(base_name), Some(derivation)) => {
+                let base_type = self.schema.lookup_type(base_name);
+                let simple_content = match base_type {
+                    Some(GlobalType::Simple(_)) => true,
+                    Some(GlobalType::Complex(base)) => matches!(base.content, ComplexContent::Simple(_)),
+                    None => {
+                        let builtin = base_name.namespace.as_deref() == Some(XSD_NAMESPACE);
+                        (builtin && base_name.local_name != "anyType") || matches!(ct.content, ComplexContent::Simple(_))
+                    }
+                };
+                let base = base_type.and_then(GlobalType::as_complex).map(|base| (base.as_ref(), derivation));
+                let content_tag = if simple_content { "simpleContent" } else { "complexContent" };
+                open(out, depth + 1, &Tag::new(content_tag));
+                let derivation_tag = Tag::new(match derivation {
+                    DerivationMethod::Extension => "extension",
+                    DerivationMethod::Restriction => "restriction",
+                })
+                .attr("base", self.prefixed(base_name)?);
+                let mut content = String::new();
+                self.write_complex_content(&mut content, depth + 3, ct, base, simple_content)?;
+                wrap(out, depth + 2, &derivation_tag, &content);
+                close(out, depth + 1, content_tag);
+            }
+            _ => self.write_complex_content(out, depth + 1, ct, None, false)?,
+        }
+
+        close(out, depth, "complexType");
+        Ok(())
+    }
+
+    /// Write the content model, attributes and assertions of a complex type
+    ///
+    /// The content of a derived type includes what it inherited from its
+    /// base type; only the type's own particles, attributes, wildcard and
+    /// assertions are written.
+    fn write_complex_content(
+        &self,
+        out: &mut String,
+        depth: usize,
+        ct: &XsdComplexType,
+        base: Option<(&XsdComplexType, DerivationMethod)>,
+        simple_content: bool,
+    ) -> Result<()> {
+        if let (ComplexContent::Group(group), false) = (&ct.content, simple_content) {
+            if let Some(own) = self.own_group(group, base)? {
+                if !own.is_empty() || own.group_ref.is_some() || own.name.is_some() {
+                    self.write_group_particle(out, depth, own)?;
+                }
+            }
+        }
+
+        self.write_attribute_uses(out, depth, &ct.attributes, base)?;
+
+        for assertion in ct.assertions.iter() {
+            let inherited = base.is_some_and(|(base, _)| base.assertions.iter().any(|a| a.test() == assertion.test()));
+            if !inherited {
+                empty(out, depth, &Tag::new("assert").attr("test", assertion.test()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the part of a content model that is not inherited from the base type
+    fn own_group<'g>(
+        &self,
+        group: &'g Arc<XsdGroup>,
+        base: Option<(&XsdComplexType, DerivationMethod)>,
+    ) -> Result<Option<&'g Arc<XsdGroup>>> {
+        let Some((ComplexContent::Group(base_group), derivation)) = base.map(|(base, method)| (&base.content, method))
+        else {
+            return Ok(Some(group));
+        };
+        if self.same_group(group, base_group)? {
+            return Ok(None);
+        }
+        // An extension appends its content to the base content in a sequence
+        if let (DerivationMethod::Extension, [GroupParticle::Group(inherited), GroupParticle::Group(own)]) =
+            (derivation, group.particles.as_slice())
+        {
+            if group.name.is_none() && self.same_group(inherited, base_group)? {
+                return Ok(Some(own));
+            }
+        }
+        Ok(Some(group))
+    }
+
+    /// Check whether two groups serialize to the same content
+    ///
+    /// Resolving group references and element types rebuilds groups, so the
+    /// inherited content of a type isn't always shared with its base type.
+    fn same_group(&self, a: &Arc<XsdGroup>, b: &Arc<XsdGroup>) -> Result<bool> {
+        if Arc::ptr_eq(a, b) {
+            return Ok(true);
+        }
+        let (mut first, mut second) = (String::new(), String::new());
+        self.write_group_particle(&mut first, 0, a)?;
+        self.write_group_particle(&mut second, 0, b)?;
+        Ok(first == second)
+    }
+
+    fn write_group_particle(&self, out: &mut String, depth: usize, group: &XsdGroup) -> Result<()> {
+        match group.group_ref.as_ref().or(group.name.as_ref()) {
+            Some(name) => {
+                let tag = Tag::new("group").attr("ref", self.prefixed(name)?).occurs(group.occurs);
+                empty(out, depth, &tag);
+                Ok(())
+            }
+            None => self.write_model_group(out, depth, group, true),
+        }
+    }
+
+    fn write_model_group(&self, out: &mut String, depth: usize, group: &XsdGroup, with_occurs: bool) -> Result<()> {
+        let mut tag = Tag::new(match group.model {
+            ModelType::Sequence => "sequence",
+            ModelType::Choice => "choice",
+            ModelType::All => "all",
+        });
+        if with_occurs {
+            tag = tag.occurs(group.occurs);
+        }
+
+        let mut content = String::new();
+        for particle in &group.particles {
+            match particle {
+                GroupParticle::Element(particle) => match (&particle.element_ref, &particle.element_decl) {
+                    (Some(name), _) => {
+                        let tag = Tag::new("element").attr("ref", self.prefixed(name)?).occurs(particle.occurs);
+                        empty(&mut content, depth + 1, &tag);
+                    }
+                    (None, Some(decl)) => self.write_element(&mut content, depth + 1, decl, Some(particle.occurs))?,
+                    (None, None) => {
+                        let tag = Tag::new("element").attr("name", &particle.name.local_name).occurs(particle.occurs);
+                        empty(&mut content, depth + 1, &tag);
+                    }
+                },
+                GroupParticle::Any(any) => {
+                    let tag = self.wildcard_attributes(Tag::new("any"), &any.wildcard).occurs(any.occurs());
+                    empty(&mut content, depth + 1, &tag);
+                }
+                GroupParticle::Group(nested) => self.write_group_particle(&mut content, depth + 1, nested)?,
+            }
+        }
+        wrap(out, depth, &tag, &content);
+        Ok(())
+    }
+
+    /// Write the attributes and attribute wildcard of a type or group
+    ///
+    /// Attributes and wildcards that are the same as the base type's are
+    /// inherited and left out.
+    fn write_attribute_uses(
+        &self,
+        out: &mut String,
+        depth: usize,
+        attributes: &XsdAttributeGroup,
+        base: Option<(&XsdComplexType, DerivationMethod)>,
+    ) -> Result<()> {
+        let mut own: Vec<&Arc<XsdAttribute>> = attributes.iter_attributes().collect();
+        own.sort_by(|a, b| qname_order(a.name(), b.name()));
+        for attribute in own {
+            let mut written = String::new();
+            self.write_attribute(&mut written, depth, attribute, false)?;
+            let inherited = match base.and_then(|(base, _)| base.attributes.get_attribute(attribute.name())) {
+                Some(base_attribute) => {
+                    let mut base_written = String::new();
+                    self.write_attribute(&mut base_written, depth, base_attribute, false)?;
+                    written == base_written
+                }
+                None => false,
+            };
+            if !inherited {
+                out.push_str(&written);
+            }
+        }
+        for group_ref in attributes.pending_group_refs() {
+            empty(out, depth, &Tag::new("attributeGroup").attr("ref", self.prefixed(group_ref)?));
+        }
+
+        if let Some(any) = attributes.any_attribute() {
+            let tag = self.wildcard_attributes(Tag::new("anyAttribute"), &any.wildcard);
+            let inherited = match base {
+                Some((base, DerivationMethod::Extension)) => base
+                    .attributes
+                    .any_attribute()
+                    .is_some_and(|base_any| self.wildcard_attributes(Tag::new("anyAttribute"), &base_any.wildcard) == tag),
+                _ => false,
+            };
+            if !inherited {
+                empty(out, depth, &tag);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_attribute(&self, out: &mut String, depth: usize, attribute: &XsdAttribute, global: bool) -> Result<()> {
+        let use_mode = match attribute.use_mode() {
+            AttributeUse::Optional => None,
+            use_mode => Some(use_mode.as_str()),
+        };
+
+        // Local declarations have unqualified names; qualified ones are references
+        if !global && attribute.name().namespace.is_some() {
+            let tag = Tag::new("attribute").attr("ref", self.prefixed(attribute.name())?).attr_opt("use", use_mode);
+            empty(out, depth, &tag);
+            return Ok(());
+        }
+
+        let mut tag = Tag::new("attribute").attr("name", &attribute.name().local_name);
+        let mut inline_type = String::new();
+        match (&attribute.type_name, attribute.simple_type()) {
+            (Some(type_name), _) => tag = tag.attr("type", self.prefixed(type_name)?),
+            (None, Some(st)) => match self.simple_type_name(st) {
+                Some(type_name) => tag = tag.attr("type", self.prefixed(&type_name)?),
+                None => self.write_simple_type(&mut inline_type, depth + 1, None, st)?,
+            },
+            (None, None) => {}
+        }
+        if !global {
+            tag = tag.attr_opt("use", use_mode);
+        }
+        tag = tag.attr_opt("default", attribute.default()).attr_opt("fixed", attribute.fixed_value());
+        wrap(out, depth, &tag, &inline_type);
+        Ok(())
+    }
+
+    fn write_simple_type(&self, out: &mut String, depth: usize, name: Option<&QName>, st: &dyn SimpleType) -> Result<()> {
+        let tag = Tag::new("simpleType").attr_opt("name", name.map(|name| name.local_name.as_str()));
+        open(out, depth, &tag);
+
+        let mut content = String::new();
+        match (SimpleType::base_type(st), st.variety()) {
+            (None, SimpleTypeVariety::List) => {
+                let mut tag = Tag::new("list");
+                if let Some(item) = st.item_type() {
+                    match self.simple_type_name(item.as_ref()) {
+                        Some(item_name) => tag = tag.attr("itemType", self.prefixed(&item_name)?),
+                        None => self.write_simple_type(&mut content, depth + 2, None, item.as_ref())?,
+                    }
+                }
+                wrap(out, depth + 1, &tag, &content);
+            }
+            (None, SimpleTypeVariety::Union) => {
+                let mut member_names = Vec::new();
+                for member in st.member_types() {
+                    match self.simple_type_name(member.as_ref()) {
+                        Some(member_name) => member_names.push(self.prefixed(&member_name)?),
+                        None => self.write_simple_type(&mut content, depth + 2, None, member.as_ref())?,
+                    }
+                }
+                let tag = Tag::new("union").attr_if("memberTypes", member_names.join(" "), !member_names.is_empty());
+                wrap(out, depth + 1, &tag, &content);
+            }
+            (base, _) => {
+                let base_name = match base {
+                    Some(base) => self.simple_type_name(base),
+                    None => None,
+                }
+                .unwrap_or_else(|| QName::namespaced(XSD_NAMESPACE, st.primitive_name().unwrap_or("anySimpleType")));
+                for (facet, value) in own_facets(st) {
+                    empty(&mut content, depth + 2, &Tag::new(facet).attr("value", value));
+                }
+                let tag = Tag::new("restriction").attr("base", self.prefixed(&base_name)?);
+                wrap(out, depth + 1, &tag, &content);
+            }
+        }
+
+        close(out, depth, "simpleType");
+        Ok(())
+    }
+
+    /// Get the name to refer to a simple type with, if it isn't anonymous
+    ///
+    /// Anonymous restrictions of a built-in type without facets of their
+    /// own are referred to by the built-in type's name.
+    fn simple_type_name(&self, st: &dyn SimpleType) -> Option<QName> {
+        if let Some(name) = st.name() {
+            return Some(name.clone());
+        }
+        match (SimpleType::base_type(st), st.variety(), st.primitive_name()) {
+            (None, SimpleTypeVariety::Atomic, Some(primitive)) if own_facets(st).is_empty() => {
+                Some(QName::namespaced(XSD_NAMESPACE, primitive))
+            }
+            _ => None,
+        }
+    }
+
+    fn wildcard_attributes(&self, tag: Tag, wildcard: &XsdWildcard) -> Tag {
+        let namespaces = |set: &HashSet<String>| {
+            let mut values: Vec<&str> = set.iter().map(|ns| if ns.is_empty() { "##local" } else { ns.as_str() }).collect();
+            values.sort();
+            values.join(" ")
+        };
+        let tag = match &wildcard.namespace {
+            NamespaceConstraint::Any => tag,
+            NamespaceConstraint::Other { .. } => tag.attr("namespace", "##other"),
+            NamespaceConstraint::Enumeration(set) => tag.attr("namespace", namespaces(set)),
+            NamespaceConstraint::Not(set) => tag.attr("notNamespace", namespaces(set)),
+        };
+        let process_contents = wildcard.process_contents;
+        tag.attr_if("processContents", process_contents.to_string(), process_contents != ProcessContents::Strict)
+    }
+
+    /// Get the `block` attribute value of a component, if not the schema default
+    fn block_value(&self, block: DerivationFlags) -> Option<String> {
+        let default = &self.schema.block_default;
+        if block.restriction == default.restriction && block.extension == default.extension {
+            return None;
+        }
+        let mut tokens = Vec::new();
+        if block.extension {
+            tokens.push("extension");
+        }
+        if block.restriction {
+            tokens.push("restriction");
+        }
+        Some(tokens.join(" "))
+    }
+
+    /// Format a QName with the prefix of its namespace
+    fn prefixed(&self, qname: &QName) -> Result<String> {
+        let prefix = match qname.namespace.as_deref() {
+            None => return Ok(qname.local_name.clone()),
+            Some(XML_NAMESPACE) => "xml",
+            Some(namespace) => self.prefixes.get(namespace).ok_or_else(|| {
+                Error::Value(format!("No prefix for namespace '{}' of '{}'", namespace, qname.local_name))
+            })?,
+        };
+        Ok(format!("{}:{}", prefix, qname.local_name))
+    }
+}
+
+/// Get the facets declared by a simple type itself, as XSD facet elements
+///
+/// White space handling that is the same as the base type's is inherited.
+fn own_facets(st: &dyn SimpleType) -> Vec<(&'static str, String)> {
+    let facets = st.facets();
+    let mut values = Vec::new();
+    if let Some(facet) = &facets.length {
+        values.push(("length", facet.value.to_string()));
+    }
+    if let Some(facet) = &facets.min_length {
+        values.push(("minLength", facet.value.to_string()));
+    }
+    if let Some(facet) = &facets.max_length {
+        values.push(("maxLength", facet.value.to_string()));
+    }
+    for facet in &facets.patterns {
+        let pattern = match &facet.source {
+            Some(source) => source.clone(),
+            None => facet.pattern.trim_start_matches('^').trim_end_matches('$').to_string(),
+        };
+        values.push(("pattern", pattern));
+    }
+    if let Some(facet) = &facets.enumeration {
+        values.extend(facet.values.iter().map(|value| ("enumeration", value.clone())));
+    }
+    let inherited_white_space = match SimpleType::base_type(st) {
+        Some(base) => Some(base.white_space()),
+        None => st.primitive_name().and_then(get_builtin_type).map(|builtin| builtin.white_space),
+    };
+    if let Some(white_space) = facets.white_space.filter(|ws| Some(*ws) != inherited_white_space) {
+        values.push(("whiteSpace", white_space.as_str().to_string()));
+    }
+    if let Some(facet) = &facets.min_inclusive {
+        values.push(("minInclusive", facet.value.to_string()));
+    }
+    if let Some(facet) = &facets.min_exclusive {
+        values.push(("minExclusive", facet.value.to_string()));
+    }
+    if let Some(facet) = &facets.max_inclusive {
+        values.push(("maxInclusive", facet.value.to_string()));
+    }
+    if let Some(facet) = &facets.max_exclusive {
+        values.push(("maxExclusive", facet.value.to_string()));
+    }
+    if let Some(facet) = &facets.total_digits {
+        values.push(("totalDigits", facet.value.to_string()));
+    }
+    if let Some(facet) = &facets.fraction_digits {
+        values.push(("fractionDigits", facet.value.to_string()));
+    }
+    if let Some(facet) = &facets.explicit_timezone {
+        values.push(("explicitTimezone", facet.to_string()));
+    }
+    values
+}
+
+/// Get the value of a `blockDefault` or `finalDefault` attribute, if any
+fn default_value(default: &DerivationDefault) -> Option<String> {
+    let flags = [
+        ("extension", default.extension),
+        ("restriction", default.restriction),
+        ("substitution", default.substitution),
+        ("list", default.list),
+        ("union", default.union),
+    ];
+    let tokens: Vec<&str> = flags.iter().filter(|(_, set)| *set).map(|(token, _)| *token).collect();
+    (!tokens.is_empty()).then(|| tokens.join(" "))
+}
+
+/// An XSD element to write, with its attributes in order
+#[derive(PartialEq)]
+struct Tag {
+    name: &'static str,
+    attributes: Vec<(String, String)>,
+}
+
+impl Tag {
+    fn new(name: &'static str) -> Self {
+        Self { name, attributes: Vec::new() }
+    }
+
+    fn attr(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attributes.push((name.into(), value.into()));
+        self
+    }
+
+    fn attr_opt(self, name: &str, value: Option<impl Into<String>>) -> Self {
+        match value {
+            Some(value) => self.attr(name, value),
+            None => self,
+        }
+    }
+
+    fn attr_if(self, name: &str, value: impl Into<String>, condition: bool) -> Self {
+        if condition {
+            self.attr(name, value)
+        } else {
+            self
+        }
+    }
+
+    /// Add `minOccurs` and `maxOccurs` when they aren't the default of one
+    fn occurs(self, occurs: Occurs) -> Self {
+        let max = match occurs.max {
+            None => Some("unbounded".to_string()),
+            Some(1) => None,
+            Some(max) => Some(max.to_string()),
+        };
+        self.attr_if("minOccurs", occurs.min.to_string(), occurs.min != 1).attr_opt("maxOccurs", max)
+    }
+
+    fn start(&self) -> String {
+        let mut start = format!("<xs:{}", self.name);
+        for (name, value) in &self.attributes {
+            start.push_str(&format!(" {}=\"{}\"", name, escape(value.as_str())));
+        }
+        start
+    }
+}
+
+fn indent(out: &mut String, depth: usize) {
+    out.push_str(&"  ".repeat(depth));
+}
+
+fn empty(out: &mut String, depth: usize, tag: &Tag) {
+    indent(out, depth);
+    out.push_str(&tag.start());
+    out.push_str("/>\n");
+}
+
+fn open(out: &mut String, depth: usize, tag: &Tag) {
+    indent(out, depth);
+    out.push_str(&tag.start());
+    out.push_str(">\n");
+}
+
+fn close(out: &mut String, depth: usize, name: &str) {
+    indent(out, depth);
+    out.push_str(&format!("</xs:{}>\n", name));
+}
+
+/// Write an element around already written content, or an empty element
+fn wrap(out: &mut String, depth: usize, tag: &Tag, content: &str) {
+    if content.is_empty() {
+        empty(out, depth, tag);
+    } else {
+        open(out, depth, tag);
+        out.push_str(content);
+        close(out, depth, tag.name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIBRARY_XSD: &str = r###"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           xmlns="urn:library"
+           targetNamespace="urn:library"
+           elementFormDefault="qualified">
+  <xs:element name="library" type="libraryType"/>
+  <xs:element name="item" type="itemType"/>
+  <xs:element name="book" type="bookType"/>
+
+  <xs:complexType name="libraryType">
+    <xs:sequence>
+      <xs:element ref="item" maxOccurs="unbounded"/>
+    </xs:sequence>
+    <xs:attributeGroup ref="tracking"/>
+  </xs:complexType>
+
+  <xs:complexType name="itemType">
+    <xs:sequence>
+      <xs:group ref="titled"/>
+    </xs:sequence>
+    <xs:attribute name="id" type="xs:ID" use="required"/>
+  </xs:complexType>
+
+  <xs:complexType name="bookType">
+    <xs:complexContent>
+      <xs:extension base="itemType">
+        <xs:choice>
+          <xs:element name="isbn" type="isbnType"/>
+          <xs:element name="issn" type="xs:string"/>
+        </xs:choice>
+        <xs:attribute name="formats" type="formatList"/>
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+
+  <xs:complexType name="priceType">
+    <xs:simpleContent>
+      <xs:extension base="xs:decimal">
+        <xs:attribute name="currency" type="xs:string" use="required"/>
+      </xs:extension>
+    </xs:simpleContent>
+  </xs:complexType>
+
+  <xs:simpleType name="isbnType">
+    <xs:restriction base="codeType">
+      <xs:pattern value="\d{13}|\d{9}[\dX]"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="codeType">
+    <xs:restriction base="xs:token">
+      <xs:maxLength value="13"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="formatList">
+    <xs:list itemType="xs:token"/>
+  </xs:simpleType>
+  <xs:simpleType name="yearOrCode">
+    <xs:union memberTypes="xs:gYear codeType"/>
+  </xs:simpleType>
+
+  <xs:group name="titled">
+    <xs:sequence>
+      <xs:element name="title" type="xs:string"/>
+      <xs:element name="note" minOccurs="0">
+        <xs:simpleType>
+          <xs:restriction base="xs:string">
+            <xs:maxLength value="20"/>
+          </xs:restriction>
+        </xs:simpleType>
+      </xs:element>
+      <xs:any namespace="##other" processContents="lax" minOccurs="0"/>
+    </xs:sequence>
+  </xs:group>
+
+  <xs:attributeGroup name="tracking">
+    <xs:attribute name="id" type="xs:ID" use="required"/>
+    <xs:anyAttribute namespace="##other" processContents="skip"/>
+  </xs:attributeGroup>
+
+  <xs:attribute name="lang" type="xs:language"/>
+  <xs:notation name="jpeg" public="image/jpeg"/>
+</xs:schema>"###;
+
+    #[test]
+    fn test_round_trip_is_stable() {
+        let schema = XsdSchema::from_string(LIBRARY_XSD).unwrap();
+        let xsd = schema.to_xsd_string().unwrap();
+        let reparsed = XsdSchema::from_string(&xsd).unwrap();
+
+        assert_eq!(reparsed.element_count(), schema.element_count());
+        assert_eq!(reparsed.type_count(), schema.type_count());
+        assert_eq!(reparsed.groups().count(), schema.groups().count());
+        assert_eq!(reparsed.attribute_groups().count(), schema.attribute_groups().count());
+        assert_eq!(reparsed.attributes().count(), schema.attributes().count());
+        assert_eq!(reparsed.notations().count(), schema.notations().count());
+        assert_eq!(reparsed.to_xsd_string().unwrap(), xsd);
+    }
+
+    #[test]
+    fn test_round_trip_components() {
+        let schema = XsdSchema::from_string(LIBRARY_XSD).unwrap();
+        let xsd = schema.to_xsd_string().unwrap();
+
+        assert!(xsd.contains(r#"<xs:schema xmlns:tns="urn:library""#));
+        assert!(xsd.contains(r#"elementFormDefault="qualified" attributeFormDefault="unqualified""#));
+        assert!(xsd.contains(r#"<xs:simpleContent>
+      <xs:extension base="xs:decimal">"#));
+        assert!(xsd.contains(r#"<xs:group ref="tns:titled"/>"#));
+        assert!(xsd.contains(r#"<xs:pattern value="\d{13}|\d{9}[\dX]"/>"#));
+        assert!(xsd.contains(r#"<xs:list itemType="xs:token"/>"#));
+        assert!(xsd.contains(r#"<xs:union memberTypes="xs:gYear tns:codeType"/>"#));
+        assert!(xsd.contains(r###"<xs:any namespace="##other" processContents="lax" minOccurs="0"/>"###));
+        assert!(xsd.contains(r#"<xs:notation name="jpeg" public="image/jpeg"/>"#));
+        // The base type's content and attributes are inherited, not repeated
+        assert!(xsd.contains(
+            r#"<xs:extension base="tns:itemType">
+        <xs:choice>
+          <xs:element name="isbn" type="tns:isbnType"/>
+          <xs:element name="issn" type="xs:string"/>
+        </xs:choice>
+        <xs:attribute name="formats" type="tns:formatList"/>
+      </xs:extension>"#
+        ));
+        // Simple types come after the types they are derived from
+        assert!(xsd.find(r#"name="codeType""#) < xsd.find(r#"name="isbnType""#));
+
+        let reparsed = XsdSchema::from_string(&xsd).unwrap();
+        for document in [
+            r#"<library xmlns="urn:library" id="l"><item id="a"><title>T</title></item></library>"#,
+            r#"<book xmlns="urn:library" id="b" formats="pdf epub"><title>T</title><isbn>123456789X</isbn></book>"#,
+        ] {
+            assert!(schema.is_valid_string(document), "{}", document);
+            assert!(reparsed.is_valid_string(document), "{}", document);
+        }
+        for document in [
+            r#"<item xmlns="urn:library"><title>T</title></item>"#,
+            r#"<book xmlns="urn:library" id="b"><title>T</title><isbn>12345</isbn></book>"#,
+            r#"<item xmlns="urn:library" id="a"><title>T</title><note>a note that is far too long</note></item>"#,
+        ] {
+            assert!(!schema.is_valid_string(document), "{}", document);
+            assert!(!reparsed.is_valid_string(document), "{}", document);
+        }
+    }
+
+    #[test]
+    fn test_no_target_namespace() {
+        let schema = XsdSchema::from_string(
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="root" type="xs:string"/>
+</xs:schema>"#,
+        )
+        .unwrap();
+        let xsd = schema.to_xsd_string().unwrap();
+        assert!(xsd.contains(r#"<xs:element name="root" type="xs:string"/>"#));
+        assert!(!xsd.contains("targetNamespace"));
+    }
+}
@@ -0,0 +1,385 @@
+//! Form schema generation
+//!
+//! Flattens the element hierarchy of a schema into a list of form fields,
+//! one per simple value (element text or attribute), so that a web form can
+//! be generated from an XSD. Each field carries the XPath-like location of
+//! the value, a readable label, the built-in type it maps to, whether it is
+//! required, the allowed values of enumerations and a validation hint.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use quick_xml::escape::escape;
+use serde::Serialize;
+
+use crate::namespaces::QName;
+use crate::validators::{
+    GlobalType, GroupParticle, ModelType, SimpleType, SimpleTypeVariety, XsdAttribute,
+    XsdComplexType, XsdElement, XsdGroup, XsdSchema,
+};
+
+/// Built-in types rendered as numeric inputs
+const NUMERIC_TYPES: &[&str] = &[
+    "decimal", "integer", "float", "double", "long", "int", "short", "byte",
+    "nonNegativeInteger", "positiveInteger", "nonPositiveInteger", "negativeInteger",
+    "unsignedLong", "unsignedInt", "unsignedShort", "unsignedByte",
+];
+
+/// A flat list of form fields generated from a schema
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FormSchema {
+    /// Fields in document order
+    pub fields: Vec<FormField>,
+}
+
+impl FormSchema {
+    /// Get the field for a path
+    pub fn field(&self, path: &str) -> Option<&FormField> {
+        self.fields.iter().find(|field| field.path == path)
+    }
+
+    /// Render all fields as labelled HTML form controls
+    pub fn to_html(&self) -> String {
+        self.fields
+            .iter()
+            .map(|field| format!("<label>{} {}</label>\n", escape(field.label.as_str()), field.to_html()))
+            .collect()
+    }
+}
+
+/// A single form field
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FormField {
+    /// Location of the value, e.g. `/book/author/firstName` or `/book/@isbn`
+    pub path: String,
+    /// Human-readable label derived from the element or attribute name
+    pub label: String,
+    /// Built-in XSD type of the value, e.g. `xs:string`
+    pub type_name: String,
+    /// Whether a value must be provided
+    pub required: bool,
+    /// Allowed values for enumerated types
+    pub options: Option<Vec<String>>,
+    /// Summary of the type constraints
+    pub validation_hint: String,
+}
+
+impl FormField {
+    /// Get the HTML input type for this field
+    ///
+    /// Enumerations use a `select`, booleans a `checkbox`, numbers and dates
+    /// the matching input types; anything else is a `text` input.
+    pub fn input_type(&self) -> &'static str {
+        if self.options.is_some() {
+            return "select";
+        }
+        match self.type_name.strip_prefix("xs:").unwrap_or(&self.type_name) {
+            "boolean" => "checkbox",
+            "date" => "date",
+            "dateTime" => "datetime-local",
+            "time" => "time",
+            name if NUMERIC_TYPES.contains(&name) => "number",
+            _ => "text",
+        }
+    }
+
+    /// Render the field as an HTML form control
+    pub fn to_html(&self) -> String {
+        let name = escape(self.path.as_str());
+        let required = if self.required { " required" } else { "" };
+        match &self.options {
+            Some(options) => {
+                let options: String = options
+                    .iter()
+                    .map(|value| {
+                        let value = escape(value.as_str());
+                        format!("<option value=\"{}\">{}</option>", value, value)
+                    })
+                    .collect();
+                format!("<select name=\"{}\"{}>{}</select>", name, required, options)
+            }
+            // An unchecked checkbox submits nothing, so it is never required
+            None if self.input_type() == "checkbox" => {
+                format!("<input type=\"checkbox\" name=\"{}\">", name)
+            }
+            None => format!(
+                "<input type=\"{}\" name=\"{}\" title=\"{}\"{}>",
+                self.input_type(),
+                name,
+                escape(self.validation_hint.as_str()),
+                required
+            ),
+        }
+    }
+
+    /// Create a field for a value of a simple type
+    fn new(path: String, name: &str, simple_type: Option<&(dyn SimpleType + Send + Sync)>, required: bool) -> Self {
+        let (type_name, options, validation_hint) = match simple_type {
+            Some(st) => {
+                let type_name = match (st.variety(), st.primitive_name()) {
+                    (SimpleTypeVariety::Atomic, Some(primitive)) => format!("xs:{}", primitive),
+                    _ => "xs:anySimpleType".to_string(),
+                };
+                let options = st.effective_facets().enumeration.map(|e| e.values);
+                (type_name, options, st.facet_summary())
+            }
+            None => ("xs:anySimpleType".to_string(), None, "any text".to_string()),
+        };
+
+        Self {
+            path,
+            label: label_for(name),
+            type_name,
+            required,
+            options,
+            validation_hint,
+        }
+    }
+}
+
+/// Turn an XML name into a label: `firstName` and `first_name` become `First name`
+fn label_for(name: &str) -> String {
+    let mut label = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if c == '_' || c == '-' || c == '.' {
+            label.push(' ');
+            previous_lower = false;
+        } else if c.is_uppercase() && previous_lower {
+            label.push(' ');
+            label.extend(c.to_lowercase());
+            previous_lower = false;
+        } else {
+            label.push(c);
+            previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        }
+    }
+
+    let mut chars = label.trim().chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Walks element declarations collecting form fields
+struct FormBuilder<'a> {
+    schema: &'a XsdSchema,
+    fields: Vec<FormField>,
+    /// Complex types being expanded, to stop at recursive definitions
+    active: HashSet<*const XsdComplexType>,
+}
+
+impl<'a> FormBuilder<'a> {
+    fn add_element(&mut self, element: &XsdElement, parent_path: &str, required: bool) {
+        let name = &element.name.local_name;
+        let path = format!("{}/{}", parent_path, name);
+
+        match element.effective_type(self.schema) {
+            Some(GlobalType::Simple(st)) => {
+                self.fields.push(FormField::new(path, name, Some(st.as_ref()), required));
+            }
+            Some(GlobalType::Complex(ct)) => self.add_complex_type(&ct, name, &path, required),
+            None => self.fields.push(FormField::new(path, name, None, required)),
+        }
+    }
+
+    fn add_complex_type(&mut self, complex_type: &Arc<XsdComplexType>, name: &str, path: &str, required: bool) {
+        if !self.active.insert(Arc::as_ptr(complex_type)) {
+            return;
+        }
+
+        // Attributes are unordered in the schema, so sort them for a stable form
+        let mut attributes: Vec<&Arc<XsdAttribute>> = complex_type.attributes.iter_attributes().collect();
+        attributes.sort_by(|a, b| a.name().local_name.cmp(&b.name().local_name));
+        for attribute in attributes {
+            self.add_attribute(attribute, path, required);
+        }
+        if let Some(st) = complex_type.simple_type() {
+            self.fields.push(FormField::new(path.to_string(), name, Some(st.as_ref()), required));
+        }
+        if let Some(group) = complex_type.model_group() {
+            self.add_group(group, path, required);
+        }
+
+        self.active.remove(&Arc::as_ptr(complex_type));
+    }
+
+    fn add_attribute(&mut self, attribute: &XsdAttribute, path: &str, required: bool) {
+        if attribute.is_prohibited() {
+            return;
+        }
+        let name = &attribute.name().local_name;
+        let simple_type = attribute.effective_type(self.schema).map(|st| st.as_ref());
+        self.fields.push(FormField::new(
+            format!("{}/@{}", path, name),
+            name,
+            simple_type,
+            required && attribute.is_required(),
+        ));
+    }
+
+    fn add_group(&mut self, group: &XsdGroup, path: &str, required: bool) {
+        let schema = self.schema;
+        let group = match &group.group_ref {
+            Some(ref_name) if group.particles.is_empty() => match schema.lookup_group(ref_name) {
+                Some(referenced) => referenced.as_ref(),
+                None => return,
+            },
+            _ => group,
+        };
+        // Only one branch of a choice is filled in
+        let required = required
+            && group.occurs.min > 0
+            && (group.model != ModelType::Choice || group.particles.len() == 1);
+
+        for particle in &group.particles {
+            match particle {
+                GroupParticle::Element(ep) if ep.occurs.max != Some(0) => {
+                    let decl = match ep.element_decl.as_ref() {
+                        Some(decl) => Some(Arc::clone(decl)),
+                        None => self.schema.lookup_element(ep.element_ref.as_ref().unwrap_or(&ep.name)).cloned(),
+                    };
+                    if let Some(decl) = decl {
+                        self.add_element(&decl, path, required && ep.occurs.min > 0);
+                    }
+                }
+                GroupParticle::Group(nested) if nested.occurs.max != Some(0) => {
+                    self.add_group(nested, path, required);
+                }
+                // Wildcard content has no fixed fields
+                _ => {}
+            }
+        }
+    }
+}
+
+impl XsdSchema {
+    /// Flatten the element hierarchy of a global element into form fields
+    ///
+    /// Every simple value below `root` becomes a field: the text of elements
+    /// with simple content and every attribute. Paths use local names, e.g.
+    /// `/book/author/firstName` and `/book/@isbn`; the attributes of an
+    /// element come first, sorted by name. A field is required when
+    /// the value and all of its ancestors are required; fields inside a
+    /// choice are optional. Wildcards are skipped and recursive types are
+    /// expanded only once. Returns an empty form if `root` is not a global
+    /// element.
+    pub fn document_to_form_schema(&self, root: &QName) -> FormSchema {
+        let Some(element) = self.lookup_element(root) else {
+            return FormSchema::default();
+        };
+
+        let mut builder = FormBuilder {
+            schema: self,
+            fields: Vec::new(),
+            active: HashSet::new(),
+        };
+        builder.add_element(element, "", true);
+        FormSchema { fields: builder.fields }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOOK_NS: &str = "http://example.com/book";
+
+    fn book_form() -> FormSchema {
+        let schema = XsdSchema::from_string(include_str!("../examples/data/book.xsd")).unwrap();
+        schema.document_to_form_schema(&QName::namespaced(BOOK_NS, "book"))
+    }
+
+    #[test]
+    fn test_book_form_fields() {
+        let form = book_form();
+        let paths: Vec<&str> = form.fields.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/book/@edition",
+                "/book/@isbn",
+                "/book/title",
+                "/book/author/firstName",
+                "/book/author/lastName",
+                "/book/author/email",
+                "/book/published",
+                "/book/pages",
+            ]
+        );
+
+        let isbn = form.field("/book/@isbn").unwrap();
+        assert_eq!(isbn.label, "Isbn");
+        assert_eq!(isbn.type_name, "xs:string");
+        assert!(isbn.required);
+        assert!(isbn.validation_hint.starts_with("xs:string with pattern="));
+
+        let first_name = form.field("/book/author/firstName").unwrap();
+        assert_eq!(first_name.label, "First name");
+        assert!(first_name.required);
+        assert_eq!(first_name.input_type(), "text");
+
+        let email = form.field("/book/author/email").unwrap();
+        assert!(!email.required);
+        assert!(email.validation_hint.contains("length <= 255"));
+
+        assert!(!form.field("/book/@edition").unwrap().required);
+        assert_eq!(form.field("/book/published").unwrap().input_type(), "date");
+        assert_eq!(form.field("/book/pages").unwrap().input_type(), "number");
+    }
+
+    #[test]
+    fn test_form_field_inputs() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="order">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="gift_wrap" type="xs:boolean"/>
+        <xs:element name="size">
+          <xs:simpleType>
+            <xs:restriction base="xs:string">
+              <xs:enumeration value="small"/>
+              <xs:enumeration value="large"/>
+            </xs:restriction>
+          </xs:simpleType>
+        </xs:element>
+        <xs:choice>
+          <xs:element name="email" type="xs:string"/>
+          <xs:element name="phone" type="xs:string"/>
+        </xs:choice>
+        <xs:element name="order" minOccurs="0"/>
+        <xs:any processContents="lax" minOccurs="0"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let form = schema.document_to_form_schema(&QName::local("order"));
+
+        let gift_wrap = form.field("/order/gift_wrap").unwrap();
+        assert_eq!(gift_wrap.label, "Gift wrap");
+        assert_eq!(gift_wrap.type_name, "xs:boolean");
+        assert_eq!(gift_wrap.to_html(), r#"<input type="checkbox" name="/order/gift_wrap">"#);
+
+        let size = form.field("/order/size").unwrap();
+        assert_eq!(size.options, Some(vec!["small".to_string(), "large".to_string()]));
+        assert_eq!(
+            size.to_html(),
+            r#"<select name="/order/size" required><option value="small">small</option><option value="large">large</option></select>"#
+        );
+
+        // Choice branches are optional
+        let email = form.field("/order/email").unwrap();
+        assert!(!email.required);
+        assert_eq!(email.to_html(), r#"<input type="text" name="/order/email" title="xs:string">"#);
+        assert!(!form.field("/order/phone").unwrap().required);
+
+        // Untyped local elements are free text; wildcards add no fields
+        assert_eq!(form.field("/order/order").unwrap().type_name, "xs:anySimpleType");
+        assert_eq!(form.fields.len(), 5);
+
+        assert!(schema.document_to_form_schema(&QName::local("missing")).fields.is_empty());
+    }
+}
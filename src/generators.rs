@@ -0,0 +1,852 @@
+//! Schema-driven test data generation
+//!
+//! This module generates values and XML instances that conform to a schema,
+//! which is useful for property-based testing where the test data is
+//! derived from the schema itself.
+//!
+//! Every generated value is checked against its type before it is returned,
+//! so callers can rely on the output being valid.
+//!
+//! Requires the `testgen` feature.
+
+use std::sync::Arc;
+
+use quick_xml::escape::escape;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::error::{Error, Result};
+use crate::namespaces::QName;
+pub use crate::samples::SampleGeneratorOptions;
+use crate::validators::base::AttributeValidator;
+use crate::validators::builtins::XSD_NAMESPACE;
+use crate::validators::facets::NumericBound;
+use crate::validators::{
+    ComplexContent, GlobalType, GroupParticle, ModelType, SimpleType, XsdAtomicType,
+    XsdComplexType, XsdElement, XsdGroup, XsdSchema,
+};
+use crate::validators::simple_types::SimpleTypeVariety;
+
+/// Maximum number of candidates tried before giving up on a value
+const MAX_ATTEMPTS: usize = 100;
+
+/// Maximum repetition used when sampling unbounded regex quantifiers
+const MAX_REGEX_REPEAT: u32 = 8;
+
+/// Default range used for unbounded integer types
+const DEFAULT_INT_RANGE: (i64, i64) = (-1_000_000, 1_000_000);
+
+const ALPHANUMERIC: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Generator for schema-conforming values and documents
+#[derive(Debug)]
+pub struct TestDataGenerator {
+    /// Random number generator
+    rng: StdRng,
+    /// Counter used to produce unique ID values
+    id_counter: usize,
+    /// Maximum element nesting depth for generated instances
+    max_depth: usize,
+}
+
+impl Default for TestDataGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestDataGenerator {
+    /// Create a generator seeded from system entropy
+    pub fn new() -> Self {
+        Self::from_rng(StdRng::from_entropy())
+    }
+
+    /// Create a deterministic generator from a seed
+    pub fn with_seed(seed: u64) -> Self {
+        Self::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn from_rng(rng: StdRng) -> Self {
+        Self {
+            rng,
+            id_counter: 0,
+            max_depth: 8,
+        }
+    }
+
+    /// Set the maximum element nesting depth for generated instances
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Generate `count` valid values for a simple type
+    ///
+    /// Enumeration types cycle through their values in declaration order.
+    pub fn generate_values(
+        &mut self,
+        simple_type: &dyn SimpleType,
+        count: usize,
+    ) -> Result<Vec<String>> {
+        (0..count)
+            .map(|index| self.generate_value(simple_type, index))
+            .collect()
+    }
+
+    /// Generate a single valid value for a simple type
+    ///
+    /// The `index` selects the enumeration value to use, if any.
+    pub fn generate_value(&mut self, simple_type: &dyn SimpleType, index: usize) -> Result<String> {
+        for _ in 0..MAX_ATTEMPTS {
+            if let Some(candidate) = self.candidate_value(simple_type, index) {
+                if simple_type.validate_value(&candidate).is_ok() {
+                    return Ok(candidate);
+                }
+            }
+        }
+
+        Err(Error::Value(format!(
+            "Unable to generate a valid value for type '{}'",
+            simple_type
+                .qualified_name_string()
+                .unwrap_or_else(|| "anonymous".to_string())
+        )))
+    }
+
+    /// Generate a complete XML instance for an element declaration
+    pub fn generate_instance(&mut self, schema: &XsdSchema, element: &XsdElement) -> Result<String> {
+        let mut output = String::new();
+        self.write_element(schema, element, 0, true, &mut output)?;
+        Ok(output)
+    }
+
+    /// Produce a candidate value, which may still fail validation
+    fn candidate_value(&mut self, simple_type: &dyn SimpleType, index: usize) -> Option<String> {
+        match simple_type.variety() {
+            SimpleTypeVariety::List => {
+                let item_type = simple_type.item_type()?;
+                let length = facet_length(simple_type);
+                let count = self.pick_length(length, 1, 3);
+                let items = (0..count)
+                    .map(|i| self.candidate_value(item_type.as_ref(), index + i))
+                    .collect::<Option<Vec<_>>>()?;
+                return Some(items.join(" "));
+            }
+            SimpleTypeVariety::Union => {
+                if let Some(values) = enumeration(simple_type) {
+                    return values.get(index % values.len()).cloned();
+                }
+                let members = simple_type.member_types();
+                if members.is_empty() {
+                    return None;
+                }
+                let member = &members[(index + self.rng.gen_range(0..members.len())) % members.len()];
+                return self.candidate_value(member.as_ref(), index);
+            }
+            SimpleTypeVariety::Atomic => {}
+        }
+
+        if let Some(values) = enumeration(simple_type) {
+            return values.get(index % values.len()).cloned();
+        }
+
+        if let Some(pattern) = first_pattern(simple_type) {
+            return self.sample_pattern(&pattern);
+        }
+
+        let primitive = simple_type.primitive_name().unwrap_or("string").to_string();
+        let length = facet_length(simple_type);
+        Some(self.primitive_value(&primitive, simple_type, length))
+    }
+
+    /// Sample a string matching a pattern facet
+    fn sample_pattern(&mut self, pattern: &str) -> Option<String> {
+        let pattern = pattern.strip_prefix('^').unwrap_or(pattern);
+        let pattern = pattern.strip_suffix('$').unwrap_or(pattern);
+
+        // Prefer ASCII output, falling back to Unicode-aware parsing
+        let hir = regex_syntax::ParserBuilder::new()
+            .unicode(false)
+            .build()
+            .parse(pattern)
+            .ok()
+            .or_else(|| regex_syntax::Parser::new().parse(pattern).ok())?;
+        let generator = rand_regex::Regex::with_hir(hir, MAX_REGEX_REPEAT).ok()?;
+        Some(self.rng.sample::<String, _>(&generator))
+    }
+
+    /// Generate a value for a built-in primitive type
+    fn primitive_value(
+        &mut self,
+        primitive: &str,
+        simple_type: &dyn SimpleType,
+        length: (Option<usize>, Option<usize>),
+    ) -> String {
+        match primitive {
+            "boolean" => ["true", "false"][self.rng.gen_range(0..2)].to_string(),
+            "decimal" | "float" | "double" => {
+                let (lo, hi) = numeric_range(simple_type, DEFAULT_INT_RANGE);
+                let value = self.rng.gen_range(lo..=hi);
+                if value < hi {
+                    format!("{}.{}", value, self.rng.gen_range(1..10))
+                } else {
+                    value.to_string()
+                }
+            }
+            "integer" | "long" | "int" | "short" | "byte" | "nonNegativeInteger"
+            | "positiveInteger" | "unsignedLong" | "unsignedInt" | "unsignedShort"
+            | "unsignedByte" | "nonPositiveInteger" | "negativeInteger" => {
+                let (lo, hi) = numeric_range(simple_type, integer_limits(primitive));
+                self.rng.gen_range(lo..=hi).to_string()
+            }
+            "date" => format!(
+                "{:04}-{:02}-{:02}",
+                self.rng.gen_range(1970..=2030),
+                self.rng.gen_range(1..=12),
+                self.rng.gen_range(1..=28)
+            ),
+            "dateTime" => format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                self.rng.gen_range(1970..=2030),
+                self.rng.gen_range(1..=12),
+                self.rng.gen_range(1..=28),
+                self.rng.gen_range(0..24),
+                self.rng.gen_range(0..60),
+                self.rng.gen_range(0..60)
+            ),
+            "time" => format!(
+                "{:02}:{:02}:{:02}",
+                self.rng.gen_range(0..24),
+                self.rng.gen_range(0..60),
+                self.rng.gen_range(0..60)
+            ),
+            "gYear" => format!("{:04}", self.rng.gen_range(1970..=2030)),
+            "gYearMonth" => format!("{:04}-{:02}", self.rng.gen_range(1970..=2030), self.rng.gen_range(1..=12)),
+            "gMonth" => format!("--{:02}", self.rng.gen_range(1..=12)),
+            "gDay" => format!("---{:02}", self.rng.gen_range(1..=28)),
+            "gMonthDay" => format!("--{:02}-{:02}", self.rng.gen_range(1..=12), self.rng.gen_range(1..=28)),
+            "duration" => format!("P{}DT{}H", self.rng.gen_range(0..365), self.rng.gen_range(0..24)),
+            "hexBinary" => {
+                let octets = self.pick_length(length, 1, 8);
+                (0..octets)
+                    .map(|_| format!("{:02X}", self.rng.gen::<u8>()))
+                    .collect()
+            }
+            "base64Binary" => {
+                use base64::Engine;
+                let octets = self.pick_length(length, 1, 12);
+                let bytes: Vec<u8> = (0..octets).map(|_| self.rng.gen()).collect();
+                base64::engine::general_purpose::STANDARD.encode(bytes)
+            }
+            "anyURI" => format!("http://example.com/{}", self.random_string(LETTERS, 8)),
+            "language" => ["en", "en-US", "fr", "de-CH", "nl"][self.rng.gen_range(0..5)].to_string(),
+            "ID" | "IDREF" | "ENTITY" => {
+                self.id_counter += 1;
+                format!("id{}", self.id_counter)
+            }
+            "Name" | "NCName" | "QName" | "NOTATION" | "NMTOKEN" | "IDREFS" | "ENTITIES"
+            | "NMTOKENS" => {
+                let len = self.pick_length(length, 1, 8);
+                let mut name = self.random_string(LETTERS, 1);
+                name.push_str(&self.random_string(ALPHANUMERIC, len.saturating_sub(1)));
+                name
+            }
+            _ => {
+                let len = self.pick_length(length, 1, 10);
+                self.random_string(ALPHANUMERIC, len)
+            }
+        }
+    }
+
+    /// Pick a length honouring (min, max) length facets
+    fn pick_length(&mut self, length: (Option<usize>, Option<usize>), min: usize, max: usize) -> usize {
+        match length {
+            (Some(lo), Some(hi)) if lo <= hi => self.rng.gen_range(lo..=hi),
+            (Some(lo), None) => self.rng.gen_range(lo..=lo.max(min) + max),
+            (None, Some(hi)) => self.rng.gen_range(min.min(hi)..=hi),
+            _ => self.rng.gen_range(min..=max),
+        }
+    }
+
+    fn random_string(&mut self, alphabet: &[u8], len: usize) -> String {
+        (0..len)
+            .map(|_| alphabet[self.rng.gen_range(0..alphabet.len())] as char)
+            .collect()
+    }
+
+    /// Write an element and its content
+    fn write_element(
+        &mut self,
+        schema: &XsdSchema,
+        element: &XsdElement,
+        depth: usize,
+        is_root: bool,
+        output: &mut String,
+    ) -> Result<()> {
+        if depth > self.max_depth {
+            return Err(Error::Value(format!(
+                "Maximum depth {} exceeded while generating element '{}'",
+                self.max_depth, element.name.local_name
+            )));
+        }
+
+        let tag = &element.name.local_name;
+        output.push('<');
+        output.push_str(tag);
+        if is_root {
+            if let Some(ns) = &element.name.namespace {
+                output.push_str(&format!(" xmlns=\"{}\"", escape(ns)));
+            }
+        }
+
+        // Named types of local declarations may only be resolved through the schema
+        match element.effective_type(schema) {
+            Some(GlobalType::Simple(simple_type)) => {
+                let text = match &element.fixed {
+                    Some(fixed) => fixed.clone(),
+                    None => self.generate_value(simple_type.as_ref(), 0)?,
+                };
+                output.push('>');
+                output.push_str(&escape(text.as_str()));
+            }
+            Some(GlobalType::Complex(complex_type)) => {
+                self.write_attributes(schema, &complex_type, output)?;
+                output.push('>');
+                match &complex_type.content {
+                    ComplexContent::Simple(simple_type) => {
+                        let text = match &element.fixed {
+                            Some(fixed) => fixed.clone(),
+                            None => self.generate_value(simple_type.as_ref(), 0)?,
+                        };
+                        output.push_str(&escape(text.as_str()));
+                    }
+                    ComplexContent::Group(group) => {
+                        self.write_group(schema, group, depth, output)?;
+                    }
+                }
+            }
+            None => {
+                output.push('>');
+            }
+        }
+
+        output.push_str("</");
+        output.push_str(tag);
+        output.push('>');
+        Ok(())
+    }
+
+    /// Write the required attributes of a complex type
+    fn write_attributes(
+        &mut self,
+        schema: &XsdSchema,
+        complex_type: &XsdComplexType,
+        output: &mut String,
+    ) -> Result<()> {
+        let (required, _) = schema.content_parts(complex_type);
+        for (name, _) in required.required_attributes {
+            let Some(attr) = complex_type.attributes.get_attribute(&name) else {
+                continue;
+            };
+            let value = match (attr.fixed_value(), attr.effective_type(schema)) {
+                (Some(fixed), _) => fixed.to_string(),
+                (None, Some(simple_type)) => self.generate_value(simple_type.as_ref(), 0)?,
+                (None, None) => self.random_string(LETTERS, 6),
+            };
+            output.push_str(&format!(
+                " {}=\"{}\"",
+                attr.name().local_name,
+                escape(value.as_str())
+            ));
+        }
+        Ok(())
+    }
+
+    /// Write the particles of a model group
+    fn write_group(
+        &mut self,
+        schema: &XsdSchema,
+        group: &XsdGroup,
+        depth: usize,
+        output: &mut String,
+    ) -> Result<()> {
+        // Unresolved group references are looked up in the schema
+        if group.particles.is_empty() {
+            if let Some(ref_name) = &group.group_ref {
+                if let Some(referenced) = schema.lookup_group(ref_name) {
+                    let referenced = Arc::clone(referenced);
+                    for _ in 0..self.repetitions(group.occurs.min, group.occurs.max, depth) {
+                        self.write_group(schema, &referenced, depth, output)?;
+                    }
+                }
+                return Ok(());
+            }
+        }
+
+        let particles: Vec<&GroupParticle> = match group.model {
+            ModelType::Choice => {
+                if group.particles.is_empty() {
+                    return Ok(());
+                }
+                // Stay shallow by preferring emptiable branches near the depth limit
+                let preferred = if depth + 1 >= self.max_depth {
+                    group.particles.iter().find(|p| p.is_emptiable())
+                } else {
+                    None
+                };
+                let chosen = match preferred {
+                    Some(particle) => particle,
+                    None => &group.particles[self.rng.gen_range(0..group.particles.len())],
+                };
+                vec![chosen]
+            }
+            ModelType::Sequence | ModelType::All => group.particles.iter().collect(),
+        };
+
+        for particle in particles {
+            let occurs = particle.occurs();
+            for _ in 0..self.repetitions(occurs.min, occurs.max, depth) {
+                match particle {
+                    GroupParticle::Element(ep) => {
+                        let decl = ep
+                            .element_decl
+                            .clone()
+                            .or_else(|| {
+                                ep.element_ref
+                                    .as_ref()
+                                    .and_then(|r| schema.lookup_element(r).cloned())
+                            })
+                            .or_else(|| schema.lookup_element(&ep.name).cloned())
+                            .ok_or_else(|| {
+                                Error::Value(format!(
+                                    "No declaration found for element '{}'",
+                                    ep.name.local_name
+                                ))
+                            })?;
+                        self.write_element(schema, &decl, depth + 1, false, output)?;
+                    }
+                    GroupParticle::Group(nested) => {
+                        self.write_group(schema, nested, depth, output)?;
+                    }
+                    // Wildcard content is left out
+                    GroupParticle::Any(_) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of times to emit a particle
+    ///
+    /// Optional particles are emitted once while below the depth limit.
+    fn repetitions(&self, min: u32, max: Option<u32>, depth: usize) -> u32 {
+        if min > 0 || depth + 1 >= self.max_depth {
+            min
+        } else {
+            max.unwrap_or(1).min(1)
+        }
+    }
+}
+
+/// Find the nearest enumeration facet in the derivation chain
+fn enumeration(simple_type: &dyn SimpleType) -> Option<Vec<String>> {
+    let mut current = Some(simple_type);
+    while let Some(ty) = current {
+        if let Some(facet) = &ty.facets().enumeration {
+            if !facet.values.is_empty() {
+                return Some(facet.values.clone());
+            }
+        }
+        current = SimpleType::base_type(ty);
+    }
+    None
+}
+
+/// Find the nearest pattern facet in the derivation chain
+fn first_pattern(simple_type: &dyn SimpleType) -> Option<String> {
+    let mut current = Some(simple_type);
+    while let Some(ty) = current {
+        if let Some(facet) = ty.facets().patterns.first() {
+            return Some(facet.pattern.clone());
+        }
+        current = SimpleType::base_type(ty);
+    }
+    None
+}
+
+/// Collect the effective (min, max) length from length facets
+fn facet_length(simple_type: &dyn SimpleType) -> (Option<usize>, Option<usize>) {
+    let mut min = None;
+    let mut max = None;
+    let mut current = Some(simple_type);
+    while let Some(ty) = current {
+        let facets = ty.facets();
+        if let Some(length) = &facets.length {
+            return (Some(length.value), Some(length.value));
+        }
+        if min.is_none() {
+            min = facets.min_length.as_ref().map(|f| f.value);
+        }
+        if max.is_none() {
+            max = facets.max_length.as_ref().map(|f| f.value);
+        }
+        current = SimpleType::base_type(ty);
+    }
+    (min, max)
+}
+
+/// Narrow an integer range using the bound facets in the derivation chain
+fn numeric_range(simple_type: &dyn SimpleType, default: (i64, i64)) -> (i64, i64) {
+    let (mut lo, mut hi) = default;
+    let mut current = Some(simple_type);
+    while let Some(ty) = current {
+        let facets = ty.facets();
+        if let Some(f) = &facets.min_inclusive {
+            lo = lo.max(bound_ceil(&f.value));
+        }
+        if let Some(f) = &facets.min_exclusive {
+            lo = lo.max(bound_floor(&f.value).saturating_add(1));
+        }
+        if let Some(f) = &facets.max_inclusive {
+            hi = hi.min(bound_floor(&f.value));
+        }
+        if let Some(f) = &facets.max_exclusive {
+            hi = hi.min(bound_ceil(&f.value).saturating_sub(1));
+        }
+        current = SimpleType::base_type(ty);
+    }
+    if lo > hi {
+        (lo, lo)
+    } else {
+        (lo, hi)
+    }
+}
+
+fn bound_floor(bound: &NumericBound) -> i64 {
+    match bound {
+        NumericBound::Integer(v) => *v,
+        NumericBound::Decimal(d) => d.floor().try_into().unwrap_or(i64::MAX),
+        NumericBound::Float(f) => f.floor() as i64,
+        NumericBound::Temporal(_) => i64::MAX,
+    }
+}
+
+fn bound_ceil(bound: &NumericBound) -> i64 {
+    match bound {
+        NumericBound::Integer(v) => *v,
+        NumericBound::Decimal(d) => d.ceil().try_into().unwrap_or(i64::MIN),
+        NumericBound::Float(f) => f.ceil() as i64,
+        NumericBound::Temporal(_) => i64::MIN,
+    }
+}
+
+/// Value range of the built-in integer types, clamped to a readable default
+fn integer_limits(primitive: &str) -> (i64, i64) {
+    let (lo, hi) = DEFAULT_INT_RANGE;
+    match primitive {
+        "byte" => (i8::MIN as i64, i8::MAX as i64),
+        "short" => (i16::MIN as i64, i16::MAX as i64),
+        "unsignedByte" => (0, u8::MAX as i64),
+        "unsignedShort" => (0, u16::MAX as i64),
+        "nonNegativeInteger" | "unsignedLong" | "unsignedInt" => (0, hi),
+        "positiveInteger" => (1, hi),
+        "nonPositiveInteger" => (lo, 0),
+        "negativeInteger" => (lo, -1),
+        _ => (lo, hi),
+    }
+}
+
+impl XsdSchema {
+    /// Generate `count` valid values for a simple type
+    ///
+    /// The type is looked up in the schema; built-in types in the XSD
+    /// namespace are also accepted. Returns fewer than `count` values
+    /// (possibly none) if the type is unknown, is not a simple type, or
+    /// no valid value could be produced.
+    pub fn generate_test_data(&self, type_qname: &QName, count: usize) -> Vec<String> {
+        let simple_type: Arc<dyn SimpleType + Send + Sync> = match self.lookup_simple_type(type_qname) {
+            Some(st) => Arc::clone(st),
+            None if type_qname.namespace.as_deref() == Some(XSD_NAMESPACE) => {
+                match XsdAtomicType::new(&type_qname.local_name) {
+                    Ok(atomic) => Arc::new(atomic),
+                    Err(_) => return Vec::new(),
+                }
+            }
+            None => return Vec::new(),
+        };
+
+        let mut generator = TestDataGenerator::new();
+        (0..count)
+            .map_while(|index| generator.generate_value(simple_type.as_ref(), index).ok())
+            .collect()
+    }
+
+    /// Generate a complete XML document that is valid for a global element
+    pub fn generate_valid_xml_instance(&self, element_qname: &QName) -> Result<String> {
+        let element = self.lookup_element(element_qname).ok_or_else(|| {
+            Error::Value(format!(
+                "No global element declaration found for '{}'",
+                element_qname.to_string()
+            ))
+        })?;
+
+        TestDataGenerator::new().generate_instance(self, element)
+    }
+
+    /// Generate a minimal valid XML document for a global element
+    ///
+    /// Only required elements and attributes are emitted, using the first
+    /// enumeration value, the minimum of bounded types and a string
+    /// matching any pattern. See [`XsdSchema::sample_document`].
+    pub fn generate_sample_xml(&self, root_element: &QName) -> Result<String> {
+        self.generate_sample_xml_with_options(root_element, &SampleGeneratorOptions::default())
+    }
+
+    /// Generate a sample XML document for a global element with explicit options
+    ///
+    /// See [`XsdSchema::sample_document_with_options`].
+    pub fn generate_sample_xml_with_options(
+        &self,
+        root_element: &QName,
+        options: &SampleGeneratorOptions,
+    ) -> Result<String> {
+        self.sample_document_with_options(root_element, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documents::Document;
+    use crate::validators::builtins::{XSD_INTEGER, XSD_STRING};
+
+    const BOOK_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           targetNamespace="http://example.com/book"
+           xmlns="http://example.com/book"
+           elementFormDefault="qualified">
+  <xs:element name="book" type="bookType"/>
+  <xs:complexType name="bookType">
+    <xs:sequence>
+      <xs:element name="title" type="xs:string"/>
+      <xs:element name="author" type="xs:string" maxOccurs="unbounded"/>
+      <xs:element name="year" type="xs:gYear" minOccurs="0"/>
+      <xs:element name="isbn" type="isbnType"/>
+      <xs:element name="category" type="categoryType"/>
+    </xs:sequence>
+    <xs:attribute name="id" type="xs:ID" use="required"/>
+  </xs:complexType>
+  <xs:simpleType name="isbnType">
+    <xs:restriction base="xs:string">
+      <xs:pattern value="\d{13}"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="categoryType">
+    <xs:restriction base="xs:string">
+      <xs:enumeration value="fiction"/>
+      <xs:enumeration value="non-fiction"/>
+      <xs:enumeration value="reference"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="codeType">
+    <xs:restriction base="xs:string">
+      <xs:length value="5"/>
+    </xs:restriction>
+  </xs:simpleType>
+</xs:schema>"#;
+
+    fn book_qname(local: &str) -> QName {
+        QName::namespaced("http://example.com/book", local)
+    }
+
+    #[test]
+    fn test_generate_integer_values() {
+        let atomic = XsdAtomicType::new(XSD_INTEGER).unwrap();
+        let mut generator = TestDataGenerator::with_seed(7);
+        let values = generator.generate_values(&atomic, 20).unwrap();
+        assert_eq!(values.len(), 20);
+        for value in &values {
+            assert!(value.parse::<i64>().is_ok(), "not an integer: {}", value);
+        }
+    }
+
+    #[test]
+    fn test_generate_length_restricted_values() {
+        let atomic = XsdAtomicType::new(XSD_STRING).unwrap().with_min_length(3).with_max_length(6);
+        let mut generator = TestDataGenerator::with_seed(1);
+        for value in generator.generate_values(&atomic, 20).unwrap() {
+            let len = value.chars().count();
+            assert!((3..=6).contains(&len), "bad length: {}", value);
+        }
+    }
+
+    #[test]
+    fn test_generate_pattern_values() {
+        let schema = XsdSchema::from_string(BOOK_XSD).unwrap();
+        let isbn = schema.lookup_simple_type(&book_qname("isbnType")).unwrap();
+        let values = schema.generate_test_data(&book_qname("isbnType"), 10);
+        assert_eq!(values.len(), 10);
+        for value in values {
+            assert_eq!(value.len(), 13);
+            assert!(isbn.validate_value(&value).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_generate_enumeration_cycles() {
+        let schema = XsdSchema::from_string(BOOK_XSD).unwrap();
+        let values = schema.generate_test_data(&book_qname("categoryType"), 4);
+        assert_eq!(values, vec!["fiction", "non-fiction", "reference", "fiction"]);
+    }
+
+    #[test]
+    fn test_generate_fixed_length() {
+        let schema = XsdSchema::from_string(BOOK_XSD).unwrap();
+        let values = schema.generate_test_data(&book_qname("codeType"), 5);
+        assert_eq!(values.len(), 5);
+        assert!(values.iter().all(|v| v.chars().count() == 5));
+    }
+
+    #[test]
+    fn test_generate_builtin_and_unknown_types() {
+        let schema = XsdSchema::from_string(BOOK_XSD).unwrap();
+        let dates = schema.generate_test_data(&QName::namespaced(XSD_NAMESPACE, "date"), 3);
+        assert_eq!(dates.len(), 3);
+        assert!(schema.generate_test_data(&book_qname("missing"), 3).is_empty());
+    }
+
+    #[test]
+    fn test_generate_valid_xml_instance() {
+        let schema = XsdSchema::from_string(BOOK_XSD).unwrap();
+        for _ in 0..10 {
+            let xml = schema.generate_valid_xml_instance(&book_qname("book")).unwrap();
+            let doc = Document::from_string(&xml).unwrap();
+            let result = schema.validate(&doc);
+            assert!(result.valid, "{}: {:?}", xml, result.errors);
+        }
+    }
+
+    #[test]
+    fn test_generate_instance_unknown_element() {
+        let schema = XsdSchema::from_string(BOOK_XSD).unwrap();
+        assert!(schema.generate_valid_xml_instance(&book_qname("magazine")).is_err());
+    }
+
+    const REPORT_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="report">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="title" type="xs:string"/>
+        <xs:element name="priority" type="priorityType"/>
+        <xs:element name="status" type="statusType"/>
+        <xs:element name="code" type="codeType"/>
+        <xs:element name="tag" type="tagType"/>
+        <xs:element name="summary" type="xs:string" minOccurs="0"/>
+        <xs:element ref="section" minOccurs="0" maxOccurs="unbounded"/>
+      </xs:sequence>
+      <xs:attribute name="version" type="versionType" use="required"/>
+      <xs:attribute name="draft" type="xs:boolean"/>
+    </xs:complexType>
+  </xs:element>
+  <xs:element name="section">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="heading" type="xs:string"/>
+        <xs:element ref="section" minOccurs="0"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+  <xs:simpleType name="priorityType">
+    <xs:restriction base="xs:positiveInteger"/>
+  </xs:simpleType>
+  <xs:simpleType name="statusType">
+    <xs:restriction base="xs:string">
+      <xs:enumeration value="open"/>
+      <xs:enumeration value="closed"/>
+      <xs:enumeration value="pending"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="codeType">
+    <xs:restriction base="xs:string">
+      <xs:pattern value="[A-Z]{2}-\d{3}"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="tagType">
+    <xs:restriction base="xs:string">
+      <xs:minLength value="3"/>
+      <xs:maxLength value="8"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="versionType">
+    <xs:restriction base="xs:positiveInteger"/>
+  </xs:simpleType>
+</xs:schema>"#;
+
+    fn assert_valid(schema: &XsdSchema, xml: &str) -> Document {
+        let doc = Document::from_string(xml).unwrap();
+        let result = schema.validate(&doc);
+        assert!(result.valid, "{}: {:?}", xml, result.errors);
+        doc
+    }
+
+    #[test]
+    fn test_generate_minimal_sample() {
+        let schema = XsdSchema::from_string(REPORT_XSD).unwrap();
+        let xml = schema.generate_sample_xml(&QName::local("report")).unwrap();
+        let doc = assert_valid(&schema, &xml);
+
+        let root = doc.root.unwrap();
+        let names: Vec<_> = root.children.iter().map(|c| c.local_name()).collect();
+        assert_eq!(names, vec!["title", "priority", "status", "code", "tag"]);
+        assert_eq!(root.children[1].text.as_deref(), Some("1"));
+        assert_eq!(root.children[2].text.as_deref(), Some("open"));
+        assert_eq!(root.children[3].text.as_deref(), Some("AA-000"));
+        assert_eq!(root.children[4].text.as_deref(), Some("example"));
+        assert_eq!(root.get_attribute("version"), Some("1"));
+        assert_eq!(root.get_attribute("draft"), None);
+
+        // Deterministic without randomized enumerations
+        assert_eq!(schema.generate_sample_xml(&QName::local("report")).unwrap(), xml);
+    }
+
+    #[test]
+    fn test_generate_sample_with_all_optional() {
+        let schema = XsdSchema::from_string(REPORT_XSD).unwrap();
+        let options = SampleGeneratorOptions {
+            use_all_optional: true,
+            depth_limit: 4,
+            ..Default::default()
+        };
+        let xml = schema.generate_sample_xml_with_options(&QName::local("report"), &options).unwrap();
+        let doc = assert_valid(&schema, &xml);
+
+        let root = doc.root.unwrap();
+        assert!(root.get_attribute("draft").is_some());
+        assert!(root.children.iter().any(|c| c.local_name() == "summary"));
+
+        // The recursive section content model stops at the depth limit
+        let mut depth = 0;
+        let mut section = root.children.iter().find(|c| c.local_name() == "section");
+        while let Some(current) = section {
+            depth += 1;
+            section = current.children.iter().find(|c| c.local_name() == "section");
+        }
+        assert_eq!(depth, 3);
+    }
+
+    #[test]
+    fn test_generate_sample_randomized_enums() {
+        let schema = XsdSchema::from_string(REPORT_XSD).unwrap();
+        let options = SampleGeneratorOptions {
+            randomize_enums: true,
+            ..Default::default()
+        };
+        for _ in 0..10 {
+            let xml = schema.generate_sample_xml_with_options(&QName::local("report"), &options).unwrap();
+            assert_valid(&schema, &xml);
+        }
+        assert!(schema.generate_sample_xml_with_options(&QName::local("missing"), &options).is_err());
+    }
+}
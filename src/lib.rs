@@ -48,6 +48,7 @@ pub mod locations;
 pub mod loaders;
 pub mod documents;
 pub mod catalog;
+pub mod dtd;
 
 // Validators - Wave 4+
 pub mod validators;
@@ -55,10 +56,21 @@ pub mod validators;
 // Data conversion - Wave 9
 pub mod converters;
 pub mod exports;
+pub mod forms;
+pub mod reporting;
+pub mod samples;
+pub mod sizing;
 // pub mod dataobjects;  // Later
 
 // Testing support
 pub mod comparison;
+#[cfg(feature = "testgen")]
+pub mod generators;
+
+// Code generation
+#[cfg(feature = "codegen")]
+pub mod codegen;
+pub mod documentation;
 
 // XPath support - Wave 11
 pub mod xpath;
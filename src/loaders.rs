@@ -5,7 +5,28 @@
 use crate::error::{Error, Result};
 use crate::limits::Limits;
 use crate::locations::Location;
+use crate::validators::XsdSchema;
+use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+/// Downloaded resources keyed by URL
+///
+/// The schemas loaded together share a cache, so a document imported by
+/// several of them is only downloaded once.
+///
+/// A plain mutex is enough here: the lock is only held to look up or store
+/// an entry, never during a download, so a concurrent map would add two
+/// dependencies for no gain. A poisoned lock is recovered with
+/// [`lock_http_cache`], since entries are inserted whole.
+pub type HttpCache = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+/// Lock a cache of downloaded resources, recovering it if a panic poisoned it
+pub fn lock_http_cache(cache: &HttpCache) -> MutexGuard<'_, HashMap<String, Vec<u8>>> {
+    cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
 
 /// Resource loader for schemas and documents
 #[derive(Debug)]
@@ -51,17 +72,9 @@ impl Loader {
                 Ok(content)
             }
             Location::Url(url) => {
-                if !self.allow_remote {
-                    return Err(Error::Resource(
-                        "Remote resources are not allowed".to_string(),
-                    ));
-                }
-
-                // TODO: Implement HTTP/HTTPS loading
-                Err(Error::Resource(format!(
-                    "URL loading not yet implemented: {}",
-                    url
-                )))
+                let content = self.load_bytes(location)?;
+                String::from_utf8(content)
+                    .map_err(|e| Error::Resource(format!("Resource '{}' is not valid UTF-8: {}", url, e)))
             }
             Location::String(s) => Ok(s.clone()),
         }
@@ -80,15 +93,16 @@ impl Loader {
 
                 Ok(content)
             }
-            Location::Url(_url) => {
+            Location::Url(url) => {
                 if !self.allow_remote {
                     return Err(Error::Resource(
                         "Remote resources are not allowed".to_string(),
                     ));
                 }
 
-                // TODO: Implement HTTP/HTTPS loading
-                Err(Error::Resource("URL loading not yet implemented".to_string()))
+                let content = load_http_resource(url.as_str(), &HttpCache::default())?;
+                self.limits.check_xml_size(content.len())?;
+                Ok(content)
             }
             Location::String(s) => Ok(s.as_bytes().to_vec()),
         }
@@ -101,9 +115,287 @@ impl Default for Loader {
     }
 }
 
+/// Source of schema documents used to resolve includes and imports
+pub trait ResourceLoader: std::fmt::Debug + Send + Sync {
+    /// Load the document at a resolved schema location
+    fn load_resource(&self, location: &str) -> Result<String>;
+}
+
+/// Resource loader serving documents from memory
+///
+/// Documents are keyed by their base URI. Requested locations are matched
+/// after normalizing `.` and `..` segments, so `schemaLocation` references
+/// resolved relative to another in-memory document find their target.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryLoader {
+    resources: HashMap<String, String>,
+}
+
+impl InMemoryLoader {
+    /// Create an empty in-memory loader
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a document under a base URI
+    ///
+    /// If the URI was already added, the first document is kept.
+    pub fn with_resource(mut self, base_uri: &str, content: &str) -> Self {
+        self.resources
+            .entry(normalize_uri(base_uri))
+            .or_insert_with(|| content.to_string());
+        self
+    }
+}
+
+impl ResourceLoader for InMemoryLoader {
+    fn load_resource(&self, location: &str) -> Result<String> {
+        self.resources
+            .get(&normalize_uri(location))
+            .cloned()
+            .ok_or_else(|| Error::Resource(format!("No in-memory resource for '{}'", location)))
+    }
+}
+
+/// Cache of parsed schemas shared across threads
+///
+/// Schemas are keyed by their canonical path together with the modification
+/// time of the file when it was parsed; a schema whose file was modified
+/// since is parsed again. Clones of the cache share the same schemas.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaCache {
+    schemas: Arc<Mutex<HashMap<PathBuf, CachedSchema>>>,
+}
+
+/// A parsed schema with the modification time of its file
+type CachedSchema = (Arc<XsdSchema>, Option<SystemTime>);
+
+impl SchemaCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the schema of a file, parsing it if absent or modified
+    ///
+    /// The lock isn't held while parsing, so two threads loading the same
+    /// new schema may both parse it; the last one parsed is kept.
+    pub fn get_or_load(&self, path: impl AsRef<Path>) -> Result<Arc<XsdSchema>> {
+        let path = path.as_ref();
+        let canonical = path
+            .canonicalize()
+            .map_err(|e| Error::Resource(format!("Failed to read schema '{}': {}", path.display(), e)))?;
+        let modified = fs::metadata(&canonical).and_then(|metadata| metadata.modified()).ok();
+
+        if let Some((schema, cached_modified)) = self.schemas.lock().unwrap().get(&canonical) {
+            if *cached_modified == modified {
+                return Ok(Arc::clone(schema));
+            }
+        }
+
+        let schema = Arc::new(XsdSchema::from_file(&canonical)?);
+        self.schemas
+            .lock()
+            .unwrap()
+            .insert(canonical, (Arc::clone(&schema), modified));
+        Ok(schema)
+    }
+
+    /// Remove the schema of a file, returning whether it was cached
+    pub fn invalidate(&self, path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref();
+        let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.schemas.lock().unwrap().remove(&key).is_some()
+    }
+
+    /// Remove all schemas
+    pub fn clear(&self) {
+        self.schemas.lock().unwrap().clear();
+    }
+
+    /// Get the number of cached schemas
+    pub fn len(&self) -> usize {
+        self.schemas.lock().unwrap().len()
+    }
+
+    /// Check whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Resource loader downloading `http://` and `https://` URLs
+///
+/// Downloads are cached by URL, after normalizing `.` and `..` segments;
+/// the cache can be shared with other loaders through
+/// [`HttpLoader::with_cache`]. Other locations are read from the file
+/// system, so the loader can serve the local documents of a schema too.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone)]
+pub struct HttpLoader {
+    client: reqwest::blocking::Client,
+    cache: HttpCache,
+    limits: Limits,
+}
+
+#[cfg(feature = "http")]
+impl HttpLoader {
+    /// Create a loader with an empty cache
+    pub fn new() -> Self {
+        Self::with_cache(HttpCache::default())
+    }
+
+    /// Create a loader using an existing cache
+    pub fn with_cache(cache: HttpCache) -> Self {
+        Self {
+            client: reqwest::blocking::Client::new(),
+            cache,
+            limits: Limits::default(),
+        }
+    }
+
+    /// Set the limits checked on downloaded resources
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Get the cache of downloaded resources
+    pub fn cache(&self) -> &HttpCache {
+        &self.cache
+    }
+
+    /// Download a resource, or get it from the cache
+    pub fn load_bytes(&self, url: &str) -> Result<Vec<u8>> {
+        self.download(url, false)
+    }
+
+    /// Download an XML document, or get it from the cache
+    ///
+    /// The download is rejected unless its `Content-Type` is
+    /// `application/xml`, `text/xml` or another `+xml` media type.
+    pub fn load_xml_bytes(&self, url: &str) -> Result<Vec<u8>> {
+        self.download(url, true)
+    }
+
+    fn download(&self, url: &str, xml_only: bool) -> Result<Vec<u8>> {
+        let url = normalize_uri(url);
+        if let Some(content) = lock_http_cache(&self.cache).get(&url) {
+            return Ok(content.clone());
+        }
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| Error::Resource(format!("Failed to download '{}': {}", url, e)))?;
+        if xml_only {
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or("");
+            if !is_xml_media_type(content_type) {
+                return Err(Error::Resource(format!(
+                    "Resource '{}' is not XML: Content-Type is '{}'",
+                    url, content_type
+                )));
+            }
+        }
+        let content = response
+            .bytes()
+            .map_err(|e| Error::Resource(format!("Failed to download '{}': {}", url, e)))?
+            .to_vec();
+        self.limits.check_xml_size(content.len())?;
+        lock_http_cache(&self.cache).insert(url, content.clone());
+        Ok(content)
+    }
+}
+
+/// Check whether a `Content-Type` header value is an XML media type
+#[cfg(feature = "http")]
+fn is_xml_media_type(content_type: &str) -> bool {
+    let media_type = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    media_type == "application/xml" || media_type == "text/xml" || media_type.ends_with("+xml")
+}
+
+#[cfg(feature = "http")]
+impl Default for HttpLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "http")]
+impl ResourceLoader for HttpLoader {
+    fn load_resource(&self, location: &str) -> Result<String> {
+        if !is_http_location(location) {
+            return fs::read_to_string(location)
+                .map_err(|e| Error::Resource(format!("Failed to read file '{}': {}", location, e)));
+        }
+        let content = self.load_bytes(location)?;
+        String::from_utf8(content)
+            .map_err(|e| Error::Resource(format!("Resource '{}' is not valid UTF-8: {}", location, e)))
+    }
+}
+
+/// Check whether a location is an `http://` or `https://` URL
+pub(crate) fn is_http_location(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+/// Download an HTTP resource through a cache
+///
+/// Fails without the `http` feature.
+pub(crate) fn load_http_resource(url: &str, cache: &HttpCache) -> Result<Vec<u8>> {
+    #[cfg(feature = "http")]
+    {
+        HttpLoader::with_cache(cache.clone()).load_bytes(url)
+    }
+    #[cfg(not(feature = "http"))]
+    {
+        let _ = cache;
+        Err(Error::Resource(format!("Loading '{}' requires the `http` feature", url)))
+    }
+}
+
+/// Read a schema document from a resolved location
+///
+/// HTTP and HTTPS URLs are downloaded through `cache`; other locations are
+/// read from the file system. Errors are returned as messages, for the
+/// callers to say which schema failed to load.
+pub(crate) fn read_schema_resource(path: &Path, cache: &HttpCache) -> std::result::Result<String, String> {
+    let location = path.to_string_lossy();
+    if !is_http_location(&location) {
+        return fs::read_to_string(path).map_err(|e| e.to_string());
+    }
+    let content = load_http_resource(&location, cache).map_err(|e| e.to_string())?;
+    String::from_utf8(content).map_err(|e| e.to_string())
+}
+
+/// Normalize a URI or path by removing `.` segments and resolving `..` segments
+pub(crate) fn normalize_uri(uri: &str) -> String {
+    let (scheme, path) = match uri.find("://") {
+        Some(pos) => uri.split_at(pos + 3),
+        None => ("", uri),
+    };
+
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "." => {}
+            ".." if matches!(segments.last(), Some(last) if !last.is_empty() && *last != "..") => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    format!("{}{}", scheme, segments.join("/"))
+}
+
 // TODO: Implement
-// - HTTP/HTTPS resource loading
-// - Resource caching
 // - Access control
 // - Progress callbacks
 // - Timeout handling
@@ -114,6 +406,21 @@ mod tests {
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    #[test]
+    fn test_poisoned_http_cache_is_recovered() {
+        let cache = HttpCache::default();
+        lock_http_cache(&cache).insert("http://example.com/a.xsd".to_string(), b"<a/>".to_vec());
+        let poisoner = Arc::clone(&cache);
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poison the cache");
+        })
+        .join();
+
+        assert!(cache.is_poisoned());
+        assert_eq!(lock_http_cache(&cache).get("http://example.com/a.xsd").map(Vec::as_slice), Some(&b"<a/>"[..]));
+    }
+
     #[test]
     fn test_load_from_file() {
         let mut file = NamedTempFile::new().unwrap();
@@ -148,4 +455,51 @@ mod tests {
         // Strict limits (10 MB max) should reject 11MB file
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_schema_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schema.xsd");
+        let schema_doc = |element: &str| {
+            format!(
+                r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"><xs:element name="{}"/></xs:schema>"#,
+                element
+            )
+        };
+        fs::write(&path, schema_doc("first")).unwrap();
+
+        let cache = SchemaCache::new();
+        let first = XsdSchema::from_file_cached(&path, &cache).unwrap();
+        let again = cache.clone().get_or_load(dir.path().join(".").join("schema.xsd")).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        assert_eq!(cache.len(), 1);
+
+        // A modified file is parsed again
+        fs::write(&path, schema_doc("second")).unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(60);
+        fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        let reloaded = cache.get_or_load(&path).unwrap();
+        assert!(!Arc::ptr_eq(&first, &reloaded));
+        assert!(reloaded.is_valid_string("<second/>"));
+        assert!(Arc::ptr_eq(&reloaded, &cache.get_or_load(&path).unwrap()));
+
+        assert!(cache.invalidate(&path));
+        assert!(cache.is_empty());
+        assert!(cache.get_or_load(dir.path().join("missing.xsd")).is_err());
+    }
+
+    #[test]
+    fn test_in_memory_loader() {
+        let loader = InMemoryLoader::new()
+            .with_resource("schemas/main.xsd", "<main/>")
+            .with_resource("http://example.com/common/types.xsd", "<types/>");
+
+        assert_eq!(loader.load_resource("schemas/./main.xsd").unwrap(), "<main/>");
+        assert_eq!(loader.load_resource("schemas/sub/../main.xsd").unwrap(), "<main/>");
+        assert_eq!(
+            loader.load_resource("http://example.com/common/../common/types.xsd").unwrap(),
+            "<types/>"
+        );
+        assert!(loader.load_resource("main.xsd").is_err());
+    }
 }
@@ -9,11 +9,21 @@ use std::fs;
 use std::path::PathBuf;
 
 #[cfg(feature = "cli")]
-use xmlschema::converters::{create_converter, ConverterType, ElementData};
+use xmlschema::codegen::TypeScriptOptions;
+#[cfg(feature = "cli")]
+use xmlschema::comparison::compare_documents;
+#[cfg(feature = "cli")]
+use xmlschema::converters::{
+    create_converter, ConverterConfig, ConverterType, ElementData, JsonConverter, XmlSchemaConverter,
+};
 #[cfg(feature = "cli")]
 use xmlschema::documents::{Document, Element};
 #[cfg(feature = "cli")]
-use xmlschema::validators::XsdSchema;
+use xmlschema::namespaces::QName;
+#[cfg(feature = "cli")]
+use xmlschema::reporting::{create_reporter, ReporterType};
+#[cfg(feature = "cli")]
+use xmlschema::validators::{ValidationResult, XsdSchema};
 
 #[cfg(feature = "cli")]
 #[derive(Parser, Debug)]
@@ -60,6 +70,26 @@ enum Commands {
         /// Output as JSON
         #[arg(short, long)]
         json: bool,
+
+        /// Output TypeScript declarations for the schema types
+        #[arg(long, conflicts_with = "json")]
+        typescript: bool,
+
+        /// Report schema quality warnings (redundant facets, unused components)
+        #[arg(long, conflicts_with = "typescript")]
+        lint: bool,
+
+        /// Output Markdown documentation for the schema
+        #[arg(long, conflicts_with_all = ["json", "typescript", "lint"])]
+        markdown: bool,
+
+        /// Show the content models of the complex types, or of the type given with --type-name
+        #[arg(long, conflicts_with_all = ["json", "typescript", "lint", "markdown"])]
+        content_model: bool,
+
+        /// Explain what a type accepts: derivation, child elements, attributes and facets
+        #[arg(long, value_name = "TYPE", conflicts_with_all = ["typescript", "lint", "markdown", "content_model"])]
+        explain_type: Option<String>,
     },
 
     /// Convert an XML document to JSON
@@ -69,7 +99,7 @@ enum Commands {
         #[arg(value_name = "FILE")]
         file: PathBuf,
 
-        /// Conversion format: default, parker, badgerfish, unordered
+        /// Conversion format: default, parker, badgerfish, unordered, jsonml, columnar
         #[arg(short, long, default_value = "default")]
         format: String,
 
@@ -80,6 +110,10 @@ enum Commands {
         /// Output file (defaults to stdout)
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Preferred prefix for a namespace as PREFIX=URI (default format only, repeatable)
+        #[arg(long = "namespace-prefix", value_name = "PREFIX=URI")]
+        namespace_prefixes: Vec<String>,
     },
 
     /// Validate an XML document against an XSD schema
@@ -88,13 +122,51 @@ enum Commands {
         #[arg(short, long, value_name = "SCHEMA")]
         schema: PathBuf,
 
-        /// Path to the XML file to validate
-        #[arg(value_name = "FILE")]
-        file: PathBuf,
+        /// Paths to the XML files to validate
+        #[arg(value_name = "FILE", required = true)]
+        files: Vec<PathBuf>,
 
         /// Validation mode: strict or lax
         #[arg(short, long, default_value = "strict")]
         mode: String,
+
+        /// Output format: terminal, json, junit, sarif
+        #[arg(short, long, default_value = "terminal")]
+        reporter: String,
+    },
+
+    /// Compare two XML documents, optionally using a schema for typed values
+    Compare {
+        /// First XML document
+        #[arg(value_name = "FILE1")]
+        file1: PathBuf,
+
+        /// Second XML document
+        #[arg(value_name = "FILE2")]
+        file2: PathBuf,
+
+        /// XSD schema used to compare values semantically
+        #[arg(short, long, value_name = "SCHEMA")]
+        schema: Option<PathBuf>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Compare two versions of an XSD schema and report breaking changes
+    Diff {
+        /// Old version of the schema
+        #[arg(value_name = "OLD")]
+        old: PathBuf,
+
+        /// New version of the schema
+        #[arg(value_name = "NEW")]
+        new: PathBuf,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 }
 
@@ -112,14 +184,46 @@ fn main() {
             attributes,
             groups,
             json,
-        } => cmd_inspect(schema, element, type_name, elements, types, attributes, groups, json),
+            typescript,
+            lint,
+            markdown,
+            content_model,
+            explain_type,
+        } => {
+            if let Some(name) = explain_type {
+                cmd_explain_type(schema, &name, json)
+            } else if typescript {
+                cmd_typescript(schema)
+            } else if markdown {
+                cmd_markdown(schema)
+            } else if content_model {
+                cmd_content_model(schema, type_name)
+            } else if lint {
+                cmd_lint(schema, json)
+            } else {
+                cmd_inspect(schema, element, type_name, elements, types, attributes, groups, json)
+            }
+        }
         Commands::XmlToJson {
             file,
             format,
             pretty,
             output,
-        } => cmd_xml2json(file, format, pretty, output),
-        Commands::Validate { schema, file, mode } => cmd_validate(schema, file, mode),
+            namespace_prefixes,
+        } => cmd_xml2json(file, format, pretty, output, namespace_prefixes),
+        Commands::Validate {
+            schema,
+            files,
+            mode,
+            reporter,
+        } => cmd_validate(schema, files, mode, reporter),
+        Commands::Compare {
+            file1,
+            file2,
+            schema,
+            json,
+        } => cmd_compare(file1, file2, schema, json),
+        Commands::Diff { old, new, json } => cmd_diff(old, new, json),
     };
 
     if let Err(e) = result {
@@ -197,6 +301,191 @@ fn cmd_inspect(
                 println!("  {}", qname.to_string());
             }
         }
+
+        if show_all && schema.notations().next().is_some() {
+            println!("\n=== Notations ===");
+            for (qname, notation) in schema.notations() {
+                let mut line = format!("  {}", qname.to_string());
+                if let Some(public) = notation.public() {
+                    line.push_str(&format!(" public=\"{}\"", public));
+                }
+                if let Some(system) = notation.system() {
+                    line.push_str(&format!(" system=\"{}\"", system));
+                }
+                println!("{}", line);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+fn cmd_typescript(schema_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = XsdSchema::from_file(&schema_path)?;
+    print!("{}", schema.to_typescript(TypeScriptOptions::default()));
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+fn cmd_markdown(schema_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = XsdSchema::from_file(&schema_path)?;
+    print!("{}", schema.to_markdown());
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+fn cmd_content_model(schema_path: PathBuf, type_name: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = XsdSchema::from_file(&schema_path)?;
+
+    if let Some(name) = type_name {
+        let qname = find_type_name(&schema, &name)?;
+        println!("{}", schema.content_model_summary(&qname));
+        return Ok(());
+    }
+
+    let mut complex_types: Vec<_> = schema
+        .types()
+        .filter(|(_, global_type)| matches!(global_type, xmlschema::validators::GlobalType::Complex(_)))
+        .map(|(qname, _)| qname)
+        .collect();
+    complex_types.sort_by_key(|qname| qname.to_string());
+    for qname in complex_types {
+        println!("{}: {}", qname.local_name, schema.content_model_summary(qname));
+    }
+
+    Ok(())
+}
+
+/// Find a global type by its exact qualified name or, failing that, its local name
+#[cfg(feature = "cli")]
+fn find_type_name(schema: &XsdSchema, name: &str) -> Result<QName, Box<dyn std::error::Error>> {
+    if let Some((qname, _)) = schema.types().find(|(qname, _)| qname.to_string() == name) {
+        return Ok(qname.clone());
+    }
+    let matches: Vec<_> = schema
+        .types()
+        .map(|(qname, _)| qname)
+        .filter(|qname| qname.local_name == name)
+        .collect();
+    match matches.len() {
+        0 => Err(format!("Type '{}' not found in schema", name).into()),
+        1 => Ok(matches[0].clone()),
+        _ => {
+            let mut names: Vec<_> = matches.iter().map(|qname| qname.to_string()).collect();
+            names.sort();
+            Err(format!("Type '{}' is ambiguous: {}", name, names.join(", ")).into())
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+fn cmd_explain_type(schema_path: PathBuf, name: &str, json_output: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = XsdSchema::from_file(&schema_path)?;
+
+    let qname = find_type_name(&schema, name)?;
+    let explanation = schema
+        .explain_type(&qname)
+        .ok_or_else(|| format!("Type '{}' not found in schema", name))?;
+    let optional_name = |qname: &Option<QName>| qname.as_ref().map(|qname| qname.to_string());
+
+    if json_output {
+        let allowed_content: Vec<_> = explanation
+            .allowed_content
+            .iter()
+            .map(|element| {
+                serde_json::json!({
+                    "name": element.name.to_string(),
+                    "type": optional_name(&element.type_name),
+                    "minOccurs": element.occurs.min,
+                    "maxOccurs": element.occurs.max,
+                })
+            })
+            .collect();
+        let attributes: Vec<_> = explanation
+            .attributes
+            .iter()
+            .map(|attribute| {
+                serde_json::json!({
+                    "name": attribute.name.to_string(),
+                    "type": optional_name(&attribute.type_name),
+                    "required": attribute.required,
+                })
+            })
+            .collect();
+        let json = serde_json::json!({
+            "name": explanation.name.to_string(),
+            "kind": explanation.kind.to_string(),
+            "baseType": optional_name(&explanation.base_type),
+            "derivationChain": explanation.derivation_chain.iter().map(|qname| qname.to_string()).collect::<Vec<_>>(),
+            "allowedContent": allowed_content,
+            "attributes": attributes,
+            "facets": explanation.facets,
+            "documentation": explanation.documentation,
+        });
+        println!("{}", serde_json::to_string_pretty(&json)?);
+        return Ok(());
+    }
+
+    println!("Type: {}", explanation.name.local_name);
+    println!("  Kind: {}", explanation.kind);
+    if explanation.derivation_chain.len() > 1 {
+        let chain: Vec<_> = explanation.derivation_chain.iter().map(|qname| qname.local_name.as_str()).collect();
+        println!("  Derivation: {}", chain.join(" -> "));
+    }
+    if !explanation.allowed_content.is_empty() {
+        println!("  Content:");
+        for element in &explanation.allowed_content {
+            let max = element.occurs.max.map_or("unbounded".to_string(), |max| max.to_string());
+            let type_name = element.type_name.as_ref().map_or("(anonymous)", |qname| qname.local_name.as_str());
+            println!("    {}: {} [{}..{}]", element.name.local_name, type_name, element.occurs.min, max);
+        }
+    }
+    if !explanation.attributes.is_empty() {
+        println!("  Attributes:");
+        for attribute in &explanation.attributes {
+            let type_name = attribute.type_name.as_ref().map_or("(anonymous)", |qname| qname.local_name.as_str());
+            let requiredness = if attribute.required { "required" } else { "optional" };
+            println!("    {}: {} ({})", attribute.name.local_name, type_name, requiredness);
+        }
+    }
+    if !explanation.facets.is_empty() {
+        println!("  Facets: {}", explanation.facets.join("; "));
+    }
+    for documentation in &explanation.documentation {
+        println!("  Documentation: {}", documentation.trim());
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+fn cmd_lint(schema_path: PathBuf, json_output: bool) -> Result<(), Box<dyn std::error::Error>> {
+    use serde_json::json;
+
+    let schema = XsdSchema::from_file(&schema_path)?;
+    let report = schema.lint();
+
+    if json_output {
+        let warnings: Vec<_> = report
+            .warnings
+            .iter()
+            .map(|warning| {
+                json!({
+                    "kind": format!("{:?}", warning.kind),
+                    "component": warning.component.to_string(),
+                    "message": warning.message,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&json!({ "warnings": warnings }))?);
+    } else if report.is_empty() {
+        println!("No lint warnings");
+    } else {
+        for warning in &report.warnings {
+            println!("warning: {}", warning);
+        }
+        println!("\n{} lint warning(s)", report.warnings.len());
     }
 
     Ok(())
@@ -222,6 +511,7 @@ fn print_schema_summary(schema: &XsdSchema) {
     println!("  Global Attributes: {}", schema.attributes().count());
     println!("  Model Groups: {}", schema.groups().count());
     println!("  Attribute Groups: {}", schema.attribute_groups().count());
+    println!("  Notations: {}", schema.notations().count());
 }
 
 #[cfg(feature = "cli")]
@@ -241,8 +531,20 @@ fn print_schema_json(schema: &XsdSchema, include_elements: bool, include_types:
     stats.insert("globalAttributes".to_string(), json!(schema.attributes().count()));
     stats.insert("modelGroups".to_string(), json!(schema.groups().count()));
     stats.insert("attributeGroups".to_string(), json!(schema.attribute_groups().count()));
+    stats.insert("notations".to_string(), json!(schema.notations().count()));
     output.insert("statistics".to_string(), Value::Object(stats));
 
+    let notations: Vec<Value> = schema.notations()
+        .map(|(qname, notation)| {
+            json!({
+                "name": qname.to_string(),
+                "public": notation.public(),
+                "system": notation.system(),
+            })
+        })
+        .collect();
+    output.insert("notations".to_string(), Value::Array(notations));
+
     if include_elements {
         let elements: Vec<Value> = schema.elements()
             .map(|(qname, elem)| {
@@ -323,6 +625,7 @@ fn print_type_details(schema: &XsdSchema, name: &str, json_output: bool) -> Resu
             xmlschema::validators::GlobalType::Simple(_) => "simple",
             xmlschema::validators::GlobalType::Complex(_) => "complex",
         };
+        let constraints = schema.facet_summary(qname);
 
         if json_output {
             let json = serde_json::json!({
@@ -330,6 +633,7 @@ fn print_type_details(schema: &XsdSchema, name: &str, json_output: bool) -> Resu
                 "localName": qname.local_name.clone(),
                 "namespace": qname.namespace.clone(),
                 "kind": kind,
+                "constraints": constraints,
             });
             println!("{}", serde_json::to_string_pretty(&json)?);
         } else {
@@ -339,6 +643,7 @@ fn print_type_details(schema: &XsdSchema, name: &str, json_output: bool) -> Resu
                 println!("  Namespace: {}", ns);
             }
             println!("  Kind: {}", kind);
+            println!("  Constraints: {}", constraints);
         }
     } else {
         return Err(format!("Type '{}' not found in schema", name).into());
@@ -353,6 +658,7 @@ fn cmd_xml2json(
     format: String,
     pretty: bool,
     output: Option<PathBuf>,
+    namespace_prefixes: Vec<String>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Read the XML file
     let xml_content = fs::read_to_string(&file)?;
@@ -370,21 +676,54 @@ fn cmd_xml2json(
         "parker" => ConverterType::Parker,
         "badgerfish" => ConverterType::BadgerFish,
         "unordered" => ConverterType::Unordered,
-        _ => return Err(format!("Unknown format: {}. Use: default, parker, badgerfish, unordered", format).into()),
+        "jsonml" => ConverterType::JsonML,
+        "columnar" => ConverterType::Columnar,
+        _ => {
+            return Err(format!(
+                "Unknown format: {}. Use: default, parker, badgerfish, unordered, jsonml, columnar",
+                format
+            )
+            .into())
+        }
     };
 
-    let converter = create_converter(conv_type);
+    // Preferred namespace prefixes, keyed by namespace URI
+    let mut prefix_map = std::collections::HashMap::new();
+    for arg in &namespace_prefixes {
+        let (prefix, uri) = arg
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid namespace prefix: {}. Use: PREFIX=URI", arg))?;
+        prefix_map.insert(uri.to_string(), prefix.to_string());
+    }
+
+    let converter: Box<dyn JsonConverter> = if prefix_map.is_empty() {
+        create_converter(conv_type)
+    } else if conv_type == ConverterType::Default {
+        let config = ConverterConfig::new().with_namespace_prefix_map(prefix_map);
+        Box::new(XmlSchemaConverter::with_config(config))
+    } else {
+        return Err("--namespace-prefix is only supported with the default format".into());
+    };
 
-    // Convert Element to ElementData
-    let element_data = element_to_element_data(root);
+    // Convert Element to ElementData; JsonML children are JsonML arrays,
+    // other formats get the children in the default convention
+    let child_converter = match conv_type {
+        ConverterType::JsonML => create_converter(ConverterType::JsonML),
+        _ => create_converter(ConverterType::Default),
+    };
+    let element_data = element_to_element_data(root, child_converter.as_ref());
 
     // Convert to JSON
     let json_value = converter.decode(&element_data, 0);
 
-    // Wrap in root element if needed (preserve root element name)
-    let output_json = serde_json::json!({
-        root.local_name(): json_value
-    });
+    // Wrap in root element if needed (preserve root element name); a JsonML
+    // array already starts with it
+    let output_json = match conv_type {
+        ConverterType::JsonML => json_value,
+        _ => serde_json::json!({
+            root.local_name(): json_value
+        }),
+    };
 
     // Format output
     let json_str = if pretty {
@@ -404,7 +743,7 @@ fn cmd_xml2json(
 }
 
 #[cfg(feature = "cli")]
-fn element_to_element_data(elem: &Element) -> ElementData {
+fn element_to_element_data(elem: &Element, child_converter: &dyn JsonConverter) -> ElementData {
     let mut data = ElementData::new(elem.local_name());
 
     // Add text content
@@ -418,14 +757,17 @@ fn element_to_element_data(elem: &Element) -> ElementData {
     }
 
     // Add xmlns declarations
+    if let Some(uri) = elem.namespaces.get_default_namespace() {
+        data = data.with_xmlns("", uri);
+    }
     for (prefix, uri) in elem.namespaces.iter() {
         data = data.with_xmlns(prefix.clone(), uri.clone());
     }
 
     // Add child elements recursively
     for child in &elem.children {
-        let child_data = element_to_element_data(child);
-        let child_json = create_converter(ConverterType::Default).decode(&child_data, 1);
+        let child_data = element_to_element_data(child, child_converter);
+        let child_json = child_converter.decode(&child_data, 1);
         data = data.with_child(child.local_name(), child_json);
     }
 
@@ -435,20 +777,12 @@ fn element_to_element_data(elem: &Element) -> ElementData {
 #[cfg(feature = "cli")]
 fn cmd_validate(
     schema_path: PathBuf,
-    file: PathBuf,
+    files: Vec<PathBuf>,
     mode: String,
+    reporter: String,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use xmlschema::validators::ValidationMode;
 
-    // Load the schema
-    let schema = XsdSchema::from_file(&schema_path)?;
-
-    // Read the XML file
-    let xml_content = fs::read_to_string(&file)?;
-
-    // Parse the XML document
-    let doc = Document::from_string(&xml_content)?;
-
     // Determine validation mode
     let validation_mode = match mode.to_lowercase().as_str() {
         "strict" => ValidationMode::Strict,
@@ -456,21 +790,134 @@ fn cmd_validate(
         _ => return Err(format!("Unknown validation mode: {}. Use: strict, lax", mode).into()),
     };
 
-    // Validate
-    let result = schema.validate_with_mode(&doc, validation_mode);
+    let reporter = create_reporter(reporter.parse::<ReporterType>()?);
+
+    // Load the schema
+    let schema = XsdSchema::from_file(&schema_path)?;
+
+    let mut invalid = 0;
+    for file in &files {
+        // Unreadable or malformed documents are reported as invalid
+        let result = match fs::read_to_string(file)
+            .map_err(|e| e.to_string())
+            .and_then(|content| Document::from_string(&content).map_err(|e| e.to_string()))
+        {
+            Ok(doc) => schema.validate_with_mode(&doc, validation_mode),
+            Err(e) => ValidationResult::invalid(vec![e]),
+        };
+
+        if !result.valid {
+            invalid += 1;
+        }
+        reporter.report_result(&file.display().to_string(), &result);
+    }
+
+    reporter.report_summary(files.len(), files.len() - invalid, invalid);
+
+    if invalid > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+fn cmd_compare(
+    file1: PathBuf,
+    file2: PathBuf,
+    schema_path: Option<PathBuf>,
+    json_output: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = schema_path.map(XsdSchema::from_file).transpose()?;
+    let doc1 = Document::from_string(&fs::read_to_string(&file1)?)?;
+    let doc2 = Document::from_string(&fs::read_to_string(&file2)?)?;
+
+    let diff = compare_documents(&doc1, &doc2, schema.as_ref());
 
-    if result.valid {
-        println!("✓ Document is valid");
-        Ok(())
+    if json_output {
+        let changed: Vec<_> = diff
+            .changed
+            .iter()
+            .map(|(path, old, new)| serde_json::json!({"path": path, "old": old, "new": new}))
+            .collect();
+        let output = serde_json::json!({
+            "equal": diff.is_empty(),
+            "added": diff.added,
+            "removed": diff.removed,
+            "changed": changed,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else if diff.is_empty() {
+        println!("Documents are equivalent");
     } else {
-        println!("✗ Document is invalid");
-        println!();
-        println!("Errors:");
-        for error in &result.errors {
-            println!("  - {}", error);
+        for path in &diff.removed {
+            println!("- {}", path);
+        }
+        for path in &diff.added {
+            println!("+ {}", path);
+        }
+        for (path, old, new) in &diff.changed {
+            println!("~ {}: '{}' -> '{}'", path, old, new);
         }
+        println!("{} difference(s)", diff.len());
+    }
+
+    if !diff.is_empty() {
         std::process::exit(1);
     }
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+fn cmd_diff(
+    old_path: PathBuf,
+    new_path: PathBuf,
+    json_output: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let old = XsdSchema::from_file(&old_path)?;
+    let new = XsdSchema::from_file(&new_path)?;
+
+    let diff = old.diff(&new);
+
+    if json_output {
+        let changed: Vec<_> = diff
+            .changed
+            .iter()
+            .map(|(name, change)| {
+                serde_json::json!({
+                    "component": name.to_string(),
+                    "change": change.to_string(),
+                    "breaking": change.is_breaking(),
+                })
+            })
+            .collect();
+        let output = serde_json::json!({
+            "equal": diff.is_empty(),
+            "breaking": diff.is_breaking(),
+            "added": diff.added.iter().map(|name| name.to_string()).collect::<Vec<_>>(),
+            "removed": diff.removed.iter().map(|name| name.to_string()).collect::<Vec<_>>(),
+            "changed": changed,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else if diff.is_empty() {
+        println!("Schemas are equivalent");
+    } else {
+        for name in &diff.removed {
+            println!("- {} (breaking)", name.to_string());
+        }
+        for name in &diff.added {
+            println!("+ {}", name.to_string());
+        }
+        for (name, change) in &diff.changed {
+            let marker = if change.is_breaking() { " (breaking)" } else { "" };
+            println!("~ {}: {}{}", name.to_string(), change, marker);
+        }
+        println!("{} difference(s)", diff.len());
+    }
+
+    if diff.is_breaking() {
+        std::process::exit(1);
+    }
+    Ok(())
 }
 
 #[cfg(not(feature = "cli"))]
@@ -4,7 +4,7 @@
 //! qualified names (QNames), and namespace prefix mappings.
 
 use crate::error::{Error, Result};
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 /// XML Namespace URI
 pub type NamespaceUri = String;
@@ -58,8 +58,8 @@ impl QName {
 /// Namespace context for resolving prefixes
 #[derive(Debug, Clone)]
 pub struct NamespaceContext {
-    /// Mapping from prefix to namespace URI
-    prefixes: HashMap<Prefix, NamespaceUri>,
+    /// Mapping from prefix to namespace URI, in declaration order
+    prefixes: IndexMap<Prefix, NamespaceUri>,
     /// Default namespace (no prefix)
     default_namespace: Option<NamespaceUri>,
 }
@@ -68,7 +68,7 @@ impl NamespaceContext {
     /// Create a new empty namespace context
     pub fn new() -> Self {
         Self {
-            prefixes: HashMap::new(),
+            prefixes: IndexMap::new(),
             default_namespace: None,
         }
     }
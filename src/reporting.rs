@@ -0,0 +1,504 @@
+//! Validation Result Reporting
+//!
+//! This module provides pluggable output formats for validation results.
+//! A reporter receives each validated file's result followed by a summary
+//! of the whole run.
+//!
+//! Supported formats:
+//! - Terminal: human readable output with ✓/✗ markers
+//! - JSON: one JSON object per line (NDJSON)
+//! - JUnit: JUnit XML for CI systems
+//! - SARIF: Static Analysis Results Interchange Format (e.g. GitHub code scanning)
+
+use std::cell::RefCell;
+use std::io::{self, IsTerminal, Stdout, Write};
+use std::str::FromStr;
+
+use quick_xml::escape::escape;
+use serde_json::{json, Value as JsonValue};
+
+use crate::error::{Error, Result};
+use crate::validators::ValidationResult;
+
+/// Rule identifier used for SARIF results
+const SARIF_RULE_ID: &str = "xsd-validation";
+
+/// Trait for validation output formatters
+pub trait SchemaReporter {
+    /// Report the validation result of a single file
+    fn report_result(&self, file: &str, result: &ValidationResult);
+
+    /// Report the totals once all files have been validated
+    fn report_summary(&self, total: usize, valid: usize, invalid: usize);
+}
+
+/// Reporter type enumeration for selecting an output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReporterType {
+    /// Human readable terminal output
+    #[default]
+    Terminal,
+    /// Newline-delimited JSON
+    Json,
+    /// JUnit XML
+    JUnit,
+    /// SARIF 2.1.0 JSON
+    Sarif,
+}
+
+impl FromStr for ReporterType {
+    type Err = Error;
+
+    /// Parse from a reporter name
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "terminal" => Ok(Self::Terminal),
+            "json" => Ok(Self::Json),
+            "junit" => Ok(Self::JUnit),
+            "sarif" => Ok(Self::Sarif),
+            _ => Err(Error::Value(format!(
+                "Unknown reporter: '{}'. Use: terminal, json, junit, sarif",
+                s
+            ))),
+        }
+    }
+}
+
+/// Create a reporter writing to stdout
+pub fn create_reporter(reporter_type: ReporterType) -> Box<dyn SchemaReporter> {
+    match reporter_type {
+        ReporterType::Terminal => Box::new(TerminalReporter::new()),
+        ReporterType::Json => Box::new(JsonReporter::new()),
+        ReporterType::JUnit => Box::new(JUnitReporter::new()),
+        ReporterType::Sarif => Box::new(SarifReporter::new()),
+    }
+}
+
+// =============================================================================
+// Terminal
+// =============================================================================
+
+/// Human readable reporter with optional ANSI colors
+#[derive(Debug)]
+pub struct TerminalReporter<W: Write = Stdout> {
+    out: RefCell<W>,
+    color: bool,
+}
+
+impl TerminalReporter<Stdout> {
+    /// Create a reporter writing to stdout, colored when stdout is a terminal
+    pub fn new() -> Self {
+        let out = io::stdout();
+        let color = out.is_terminal();
+        Self {
+            out: RefCell::new(out),
+            color,
+        }
+    }
+}
+
+impl Default for TerminalReporter<Stdout> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Write> TerminalReporter<W> {
+    /// Create an uncolored reporter writing to the given writer
+    pub fn with_writer(out: W) -> Self {
+        Self {
+            out: RefCell::new(out),
+            color: false,
+        }
+    }
+
+    /// Enable or disable ANSI colors
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Consume the reporter and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.out.into_inner()
+    }
+
+    fn paint(&self, text: &str, code: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+impl<W: Write> SchemaReporter for TerminalReporter<W> {
+    fn report_result(&self, file: &str, result: &ValidationResult) {
+        let mut out = self.out.borrow_mut();
+        if result.valid {
+            let _ = writeln!(out, "{} {}: Document is valid", self.paint("✓", "32"), file);
+        } else {
+            let _ = writeln!(out, "{} {}: Document is invalid", self.paint("✗", "31"), file);
+            let _ = writeln!(out);
+            let _ = writeln!(out, "Errors:");
+            for error in &result.errors {
+                match (error.source_line, error.source_column) {
+                    (Some(line), Some(column)) => {
+                        let _ = writeln!(out, "  - {} (line {}, column {})", error.message, line, column);
+                    }
+                    _ => {
+                        let _ = writeln!(out, "  - {}", error.message);
+                    }
+                }
+            }
+        }
+        for warning in &result.warnings {
+            let _ = writeln!(out, "  {} {}", self.paint("warning:", "33"), warning);
+        }
+    }
+
+    fn report_summary(&self, total: usize, valid: usize, invalid: usize) {
+        let mut out = self.out.borrow_mut();
+        let _ = writeln!(
+            out,
+            "\nValidated {} file(s): {} valid, {} invalid",
+            total, valid, invalid
+        );
+    }
+}
+
+// =============================================================================
+// JSON
+// =============================================================================
+
+/// Newline-delimited JSON reporter
+///
+/// Writes one object per validated file and a final summary object.
+#[derive(Debug)]
+pub struct JsonReporter<W: Write = Stdout> {
+    out: RefCell<W>,
+}
+
+impl JsonReporter<Stdout> {
+    /// Create a reporter writing to stdout
+    pub fn new() -> Self {
+        Self::with_writer(io::stdout())
+    }
+}
+
+impl Default for JsonReporter<Stdout> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Write> JsonReporter<W> {
+    /// Create a reporter writing to the given writer
+    pub fn with_writer(out: W) -> Self {
+        Self {
+            out: RefCell::new(out),
+        }
+    }
+
+    /// Consume the reporter and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.out.into_inner()
+    }
+}
+
+impl<W: Write> SchemaReporter for JsonReporter<W> {
+    fn report_result(&self, file: &str, result: &ValidationResult) {
+        let line = json!({
+            "file": file,
+            "valid": result.valid,
+            "errors": result.error_messages(),
+            "warnings": result.warnings,
+        });
+        let _ = writeln!(self.out.borrow_mut(), "{}", line);
+    }
+
+    fn report_summary(&self, total: usize, valid: usize, invalid: usize) {
+        let line = json!({
+            "summary": {
+                "total": total,
+                "valid": valid,
+                "invalid": invalid,
+            }
+        });
+        let _ = writeln!(self.out.borrow_mut(), "{}", line);
+    }
+}
+
+// =============================================================================
+// JUnit
+// =============================================================================
+
+/// JUnit XML reporter
+///
+/// Each file becomes a test case; invalid files are reported as failures.
+/// Output is written when the summary is reported.
+#[derive(Debug)]
+pub struct JUnitReporter<W: Write = Stdout> {
+    out: RefCell<W>,
+    cases: RefCell<Vec<(String, ValidationResult)>>,
+}
+
+impl JUnitReporter<Stdout> {
+    /// Create a reporter writing to stdout
+    pub fn new() -> Self {
+        Self::with_writer(io::stdout())
+    }
+}
+
+impl Default for JUnitReporter<Stdout> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Write> JUnitReporter<W> {
+    /// Create a reporter writing to the given writer
+    pub fn with_writer(out: W) -> Self {
+        Self {
+            out: RefCell::new(out),
+            cases: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Consume the reporter and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.out.into_inner()
+    }
+}
+
+impl<W: Write> SchemaReporter for JUnitReporter<W> {
+    fn report_result(&self, file: &str, result: &ValidationResult) {
+        self.cases.borrow_mut().push((file.to_string(), result.clone()));
+    }
+
+    fn report_summary(&self, total: usize, _valid: usize, invalid: usize) {
+        let mut out = self.out.borrow_mut();
+        let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        let _ = writeln!(
+            out,
+            r#"<testsuites name="xmlschema" tests="{}" failures="{}">"#,
+            total, invalid
+        );
+        let _ = writeln!(
+            out,
+            r#"  <testsuite name="validate" tests="{}" failures="{}">"#,
+            total, invalid
+        );
+        for (file, result) in self.cases.borrow().iter() {
+            let name = escape(file.as_str());
+            if result.valid {
+                let _ = writeln!(out, r#"    <testcase classname="xmlschema.validate" name="{}"/>"#, name);
+            } else {
+                let _ = writeln!(out, r#"    <testcase classname="xmlschema.validate" name="{}">"#, name);
+                let _ = writeln!(
+                    out,
+                    r#"      <failure message="{} validation error(s)">{}</failure>"#,
+                    result.errors.len(),
+                    escape(result.error_messages().join("\n").as_str())
+                );
+                let _ = writeln!(out, "    </testcase>");
+            }
+        }
+        let _ = writeln!(out, "  </testsuite>");
+        let _ = writeln!(out, "</testsuites>");
+    }
+}
+
+// =============================================================================
+// SARIF
+// =============================================================================
+
+/// SARIF 2.1.0 reporter
+///
+/// Each validation error becomes a SARIF result located in the validated
+/// file. Output is written when the summary is reported.
+#[derive(Debug)]
+pub struct SarifReporter<W: Write = Stdout> {
+    out: RefCell<W>,
+    results: RefCell<Vec<JsonValue>>,
+}
+
+impl SarifReporter<Stdout> {
+    /// Create a reporter writing to stdout
+    pub fn new() -> Self {
+        Self::with_writer(io::stdout())
+    }
+}
+
+impl Default for SarifReporter<Stdout> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Write> SarifReporter<W> {
+    /// Create a reporter writing to the given writer
+    pub fn with_writer(out: W) -> Self {
+        Self {
+            out: RefCell::new(out),
+            results: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Consume the reporter and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.out.into_inner()
+    }
+}
+
+impl<W: Write> SchemaReporter for SarifReporter<W> {
+    fn report_result(&self, file: &str, result: &ValidationResult) {
+        let mut results = self.results.borrow_mut();
+        for error in &result.errors {
+            results.push(sarif_result(file, &error.message, "error"));
+        }
+        for warning in &result.warnings {
+            results.push(sarif_result(file, warning, "warning"));
+        }
+    }
+
+    fn report_summary(&self, _total: usize, _valid: usize, _invalid: usize) {
+        let log = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "xmlschema-rs",
+                        "version": crate::VERSION,
+                        "informationUri": "https://github.com/ParapluOU/xmlschema-rs",
+                        "rules": [{
+                            "id": SARIF_RULE_ID,
+                            "shortDescription": { "text": "XML Schema validation error" },
+                        }],
+                    }
+                },
+                "results": *self.results.borrow(),
+            }],
+        });
+        let mut out = self.out.borrow_mut();
+        let _ = writeln!(
+            out,
+            "{}",
+            serde_json::to_string_pretty(&log).unwrap_or_default()
+        );
+    }
+}
+
+fn sarif_result(file: &str, message: &str, level: &str) -> JsonValue {
+    json!({
+        "ruleId": SARIF_RULE_ID,
+        "level": level,
+        "message": { "text": message },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": file }
+            }
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid_result() -> ValidationResult {
+        ValidationResult::invalid(vec!["Value does not match pattern '^\\d{13}$'".to_string()])
+    }
+
+    fn output(bytes: Vec<u8>) -> String {
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_reporter_type_from_str() {
+        assert_eq!("terminal".parse::<ReporterType>().unwrap(), ReporterType::Terminal);
+        assert_eq!("JSON".parse::<ReporterType>().unwrap(), ReporterType::Json);
+        assert_eq!("junit".parse::<ReporterType>().unwrap(), ReporterType::JUnit);
+        assert_eq!("sarif".parse::<ReporterType>().unwrap(), ReporterType::Sarif);
+        assert!("html".parse::<ReporterType>().is_err());
+    }
+
+    #[test]
+    fn test_terminal_reporter() {
+        let reporter = TerminalReporter::with_writer(Vec::new());
+        reporter.report_result("good.xml", &ValidationResult::valid());
+        reporter.report_result("bad.xml", &invalid_result());
+        reporter.report_summary(2, 1, 1);
+
+        let text = output(reporter.into_inner());
+        assert!(text.contains("✓ good.xml: Document is valid"));
+        assert!(text.contains("✗ bad.xml: Document is invalid"));
+        assert!(text.contains("  - Value does not match pattern"));
+        assert!(text.contains("Validated 2 file(s): 1 valid, 1 invalid"));
+        assert!(!text.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_terminal_reporter_color() {
+        let reporter = TerminalReporter::with_writer(Vec::new()).with_color(true);
+        reporter.report_result("good.xml", &ValidationResult::valid());
+        assert!(output(reporter.into_inner()).contains("\x1b[32m✓\x1b[0m"));
+    }
+
+    #[test]
+    fn test_json_reporter() {
+        let reporter = JsonReporter::with_writer(Vec::new());
+        reporter.report_result("good.xml", &ValidationResult::valid());
+        reporter.report_result("bad.xml", &invalid_result());
+        reporter.report_summary(2, 1, 1);
+
+        let text = output(reporter.into_inner());
+        let lines: Vec<JsonValue> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["file"], "good.xml");
+        assert_eq!(lines[0]["valid"], true);
+        assert_eq!(lines[1]["valid"], false);
+        assert_eq!(lines[1]["errors"].as_array().unwrap().len(), 1);
+        assert_eq!(lines[2]["summary"]["invalid"], 1);
+    }
+
+    #[test]
+    fn test_junit_reporter() {
+        let reporter = JUnitReporter::with_writer(Vec::new());
+        reporter.report_result("good.xml", &ValidationResult::valid());
+        reporter.report_result("bad & ugly.xml", &invalid_result());
+        reporter.report_summary(2, 1, 1);
+
+        let text = output(reporter.into_inner());
+        assert!(text.contains(r#"<testsuites name="xmlschema" tests="2" failures="1">"#));
+        assert!(text.contains(r#"<testcase classname="xmlschema.validate" name="good.xml"/>"#));
+        assert!(text.contains(r#"name="bad &amp; ugly.xml""#));
+        assert!(text.contains(r#"<failure message="1 validation error(s)">"#));
+
+        // The output must be well-formed XML
+        assert!(roxmltree::Document::parse(&text).is_ok());
+    }
+
+    #[test]
+    fn test_sarif_reporter() {
+        let reporter = SarifReporter::with_writer(Vec::new());
+        reporter.report_result("good.xml", &ValidationResult::valid());
+        reporter.report_result("bad.xml", &invalid_result());
+        reporter.report_summary(2, 1, 1);
+
+        let log: JsonValue = serde_json::from_slice(&reporter.into_inner()).unwrap();
+        assert_eq!(log["version"], "2.1.0");
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], SARIF_RULE_ID);
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "bad.xml"
+        );
+    }
+}
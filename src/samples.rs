@@ -0,0 +1,879 @@
+//! Minimal sample documents
+//!
+//! This module builds the smallest document that is valid for a global
+//! element, as a starting point for hand-written instances, for
+//! documentation and for test data. Unless enumeration values are
+//! randomized, the output is deterministic:
+//! - only required elements and attributes are included (see
+//!   [`SampleGeneratorOptions::use_all_optional`]), and choices take their
+//!   first branch;
+//! - values are the fixed or default value when declared, else the first
+//!   enumeration value, else a string built from the pattern facets, else
+//!   a placeholder such as `0` for numeric types and `example` for
+//!   strings, adjusted to the length and range facets;
+//! - choices avoid branches reentering a type that is being expanded, and
+//!   an element whose type was already reentered once is written empty,
+//!   which breaks cycles in recursive content models after one level.
+//!
+//! Candidate values are checked against their type, so a placeholder that
+//! doesn't match a pattern is only used when nothing else is valid.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use quick_xml::escape::escape;
+
+use crate::error::{Error, Result};
+use crate::namespaces::QName;
+use crate::validators::base::AttributeValidator;
+use crate::validators::facets::NumericBound;
+use crate::validators::{
+    ComplexContent, GlobalType, GroupParticle, ModelType, Occurs, SimpleType, SimpleTypeVariety,
+    XsdComplexType, XsdElement, XsdGroup, XsdSchema,
+};
+
+/// Placeholder for string values
+const PLACEHOLDER: &str = "example";
+
+/// Characters tried for pattern character classes after their own members
+const FALLBACK_CHARS: &str = "aA0 -_.:x";
+
+/// Options for generating minimal sample documents
+///
+/// See [`XsdSchema::sample_document_with_options`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampleGeneratorOptions {
+    /// Also emit optional elements and attributes (once each)
+    pub use_all_optional: bool,
+    /// Maximum element nesting depth; optional content is left out at the
+    /// limit, which ends recursion in self-containing content models
+    pub depth_limit: usize,
+    /// Pick a random enumeration value instead of the first one
+    pub randomize_enums: bool,
+}
+
+impl Default for SampleGeneratorOptions {
+    fn default() -> Self {
+        Self {
+            use_all_optional: false,
+            depth_limit: 8,
+            randomize_enums: false,
+        }
+    }
+}
+
+impl XsdSchema {
+    /// Build a minimal valid document for a global element
+    ///
+    /// The document is serialized without XML declaration, with unprefixed
+    /// element names and a default namespace declaration on the root.
+    /// Returns an error if `root_element` is not a global element.
+    pub fn sample_document(&self, root_element: &QName) -> Result<String> {
+        self.sample_document_with_options(root_element, &SampleGeneratorOptions::default())
+    }
+
+    /// Build a sample document for a global element with explicit options
+    ///
+    /// With the default options this is [`XsdSchema::sample_document`].
+    pub fn sample_document_with_options(&self, root_element: &QName, options: &SampleGeneratorOptions) -> Result<String> {
+        let root = self.lookup_element(root_element).ok_or_else(|| {
+            Error::Value(format!(
+                "No global element declaration found for '{}'",
+                root_element.local_name
+            ))
+        })?;
+
+        let mut writer = SampleWriter {
+            schema: self,
+            options,
+            random: RandomState::new(),
+            picks: 0,
+            expanding: Vec::new(),
+            ids: Vec::new(),
+            output: String::new(),
+        };
+        writer.element(root, None, 0, false)?;
+        Ok(writer.output)
+    }
+}
+
+/// Walks content models writing their minimal serialization
+struct SampleWriter<'a> {
+    schema: &'a XsdSchema,
+    options: &'a SampleGeneratorOptions,
+    /// Source of randomized enumeration picks
+    random: RandomState,
+    /// Number of randomized enumeration picks so far
+    picks: u64,
+    /// Types being expanded, named by the type or by the element for anonymous types
+    expanding: Vec<QName>,
+    /// Values written for ID attributes and elements
+    ids: Vec<String>,
+    output: String,
+}
+
+impl SampleWriter<'_> {
+    /// Write an element, `parent_ns` being the default namespace in scope
+    ///
+    /// Optional occurrences are bounded by the depth limit, so only
+    /// required ones are written empty when they reenter a type.
+    fn element(&mut self, element: &XsdElement, parent_ns: Option<&str>, depth: usize, optional: bool) -> Result<()> {
+        let tag = element.name.local_name.as_str();
+        let ns = element.name.namespace.as_deref();
+        self.output.push('<');
+        self.output.push_str(tag);
+        if ns != parent_ns {
+            self.output.push_str(&format!(" xmlns=\"{}\"", escape(ns.unwrap_or(""))));
+        }
+
+        let fixed_or_default = element.fixed.as_deref().or(element.default.as_deref());
+        let content = match element.effective_type(self.schema) {
+            Some(GlobalType::Simple(simple_type)) => {
+                let value = self.value(fixed_or_default, simple_type.as_ref());
+                Some(escape(value.as_str()).into_owned())
+            }
+            Some(GlobalType::Complex(complex_type)) => {
+                self.attributes(&complex_type);
+                let key = complex_type.name.clone().unwrap_or_else(|| element.name.clone());
+                if !optional && self.expanding.iter().filter(|expanding| **expanding == key).count() > 1 {
+                    None
+                } else {
+                    self.expanding.push(key);
+                    let content = self.complex_content(&complex_type, fixed_or_default, ns, depth);
+                    self.expanding.pop();
+                    content?
+                }
+            }
+            None => fixed_or_default.map(|value| escape(value).into_owned()),
+        };
+
+        match content.filter(|content| !content.is_empty()) {
+            Some(content) => {
+                self.output.push('>');
+                self.output.push_str(&content);
+                self.output.push_str("</");
+                self.output.push_str(tag);
+                self.output.push('>');
+            }
+            None => self.output.push_str("/>"),
+        }
+        Ok(())
+    }
+
+    /// Serialize the content of a complex type
+    ///
+    /// Child elements are written into a separate buffer, so that the start
+    /// tag can still be closed as an empty element.
+    fn complex_content(
+        &mut self,
+        complex_type: &XsdComplexType,
+        fixed_or_default: Option<&str>,
+        ns: Option<&str>,
+        depth: usize,
+    ) -> Result<Option<String>> {
+        match &complex_type.content {
+            ComplexContent::Simple(simple_type) => {
+                let value = self.value(fixed_or_default, simple_type.as_ref());
+                Ok(Some(escape(value.as_str()).into_owned()))
+            }
+            ComplexContent::Group(group) => {
+                let start_tag = std::mem::take(&mut self.output);
+                let result = self.group(group, ns, depth, false);
+                let children = std::mem::replace(&mut self.output, start_tag);
+                result.map(|_| Some(children))
+            }
+        }
+    }
+
+    /// Write the attributes of a complex type, sorted by name
+    ///
+    /// Optional attributes are only written with `use_all_optional`.
+    fn attributes(&mut self, complex_type: &XsdComplexType) {
+        let schema = self.schema;
+        let all_optional = self.options.use_all_optional;
+        let mut attributes: Vec<_> = complex_type
+            .attributes
+            .iter_attributes()
+            .filter(|attr| (all_optional || attr.is_required()) && !attr.is_prohibited())
+            .collect();
+        attributes.sort_by(|a, b| a.name().local_name.cmp(&b.name().local_name));
+
+        let mut prefixes = 0;
+        for attr in attributes {
+            let fixed_or_default = attr.fixed_value().or(attr.default_value());
+            let value = match attr.effective_type(schema) {
+                Some(simple_type) => self.value(fixed_or_default, simple_type.as_ref()),
+                None => fixed_or_default.unwrap_or(PLACEHOLDER).to_string(),
+            };
+
+            let name = attr.name();
+            self.output.push(' ');
+            // Qualified attributes get a prefix declared on the element
+            if let Some(ns) = &name.namespace {
+                prefixes += 1;
+                self.output.push_str(&format!("xmlns:a{}=\"{}\" a{}:", prefixes, escape(ns.as_str()), prefixes));
+            }
+            self.output.push_str(&format!("{}=\"{}\"", name.local_name, escape(value.as_str())));
+        }
+    }
+
+    /// Write the particles of a model group, `depth` being that of its element
+    ///
+    /// `optional` is set within optional occurrences of an enclosing group.
+    fn group(&mut self, group: &XsdGroup, ns: Option<&str>, depth: usize, optional: bool) -> Result<()> {
+        // Unresolved group references are looked up in the schema
+        if group.particles.is_empty() {
+            if let Some(referenced) = group.group_ref.as_ref().and_then(|name| self.schema.lookup_group(name)) {
+                let referenced = referenced.clone();
+                for index in 0..self.repetitions(group.occurs, depth) {
+                    self.group(&referenced, ns, depth, optional || index >= group.occurs.min)?;
+                }
+                return Ok(());
+            }
+        }
+
+        let particles: Vec<&GroupParticle> = match group.model {
+            // Prefer the first branch that doesn't reenter an expanding type
+            ModelType::Choice => group
+                .particles
+                .iter()
+                .find(|particle| !self.reenters(particle))
+                .or(group.particles.first())
+                .into_iter()
+                .collect(),
+            ModelType::Sequence | ModelType::All => group.particles.iter().collect(),
+        };
+
+        for particle in particles {
+            let occurs = particle.occurs();
+            for index in 0..self.repetitions(occurs, depth) {
+                let optional = optional || index >= occurs.min;
+                match particle {
+                    GroupParticle::Element(ep) => {
+                        let decl = ep
+                            .element_decl
+                            .clone()
+                            .or_else(|| ep.element_ref.as_ref().and_then(|r| self.schema.lookup_element(r).cloned()))
+                            .or_else(|| self.schema.lookup_element(&ep.name).cloned())
+                            .ok_or_else(|| {
+                                Error::Value(format!("No declaration found for element '{}'", ep.name.local_name))
+                            })?;
+                        self.element(&decl, ns, depth + 1, optional)?;
+                    }
+                    GroupParticle::Group(nested) => self.group(nested, ns, depth, optional)?,
+                    // Wildcard content is left out
+                    GroupParticle::Any(_) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of times to write a particle
+    ///
+    /// Optional particles are written once with `use_all_optional`, while
+    /// their elements stay below the depth limit.
+    fn repetitions(&self, occurs: Occurs, depth: usize) -> u32 {
+        if occurs.min > 0 || !self.options.use_all_optional || depth + 1 >= self.options.depth_limit {
+            occurs.min
+        } else {
+            occurs.max.unwrap_or(1).min(1)
+        }
+    }
+
+    /// Check whether a particle is an element of a type being expanded
+    fn reenters(&self, particle: &GroupParticle) -> bool {
+        let GroupParticle::Element(ep) = particle else {
+            return false;
+        };
+        let decl = ep
+            .element_decl
+            .clone()
+            .or_else(|| ep.element_ref.as_ref().and_then(|r| self.schema.lookup_element(r).cloned()));
+        let Some(decl) = decl else {
+            return false;
+        };
+        match decl.effective_type(self.schema) {
+            Some(GlobalType::Complex(complex_type)) => {
+                let key = complex_type.name.clone().unwrap_or_else(|| decl.name.clone());
+                self.expanding.contains(&key)
+            }
+            _ => false,
+        }
+    }
+
+    /// Pick the value of an element or attribute
+    ///
+    /// The fixed or default value is used as is; otherwise the first
+    /// candidate accepted by the type, or the first candidate if none is.
+    fn value(&mut self, fixed_or_default: Option<&str>, simple_type: &dyn SimpleType) -> String {
+        if let Some(value) = fixed_or_default {
+            return value.to_string();
+        }
+        let mut candidates = self.candidates(simple_type);
+        let enumerated = simple_type.effective_facets().enumeration.is_some();
+        if self.options.randomize_enums && enumerated && !candidates.is_empty() {
+            let mut hasher = self.random.build_hasher();
+            hasher.write_u64(self.picks);
+            self.picks += 1;
+            let index = (hasher.finish() % candidates.len() as u64) as usize;
+            candidates.rotate_left(index);
+        }
+        let value = candidates
+            .iter()
+            .find(|candidate| simple_type.validate_value(candidate).is_ok())
+            .or(candidates.first())
+            .cloned()
+            .unwrap_or_default();
+        if simple_type.primitive_name() == Some("ID") {
+            self.ids.push(value.clone());
+        }
+        value
+    }
+
+    /// Candidate values of a simple type, most preferred first
+    fn candidates(&self, simple_type: &dyn SimpleType) -> Vec<String> {
+        let facets = simple_type.effective_facets();
+        if let Some(enumeration) = facets.enumeration.as_ref().filter(|e| !e.values.is_empty()) {
+            return enumeration.values.clone();
+        }
+
+        match simple_type.variety() {
+            SimpleTypeVariety::List => {
+                let item = simple_type
+                    .item_type()
+                    .map(|item_type| self.item_value(item_type.as_ref()))
+                    .unwrap_or_else(|| PLACEHOLDER.to_string());
+                // List lengths count items
+                let items = facets
+                    .length
+                    .as_ref()
+                    .map(|f| f.value)
+                    .or(facets.min_length.as_ref().map(|f| f.value))
+                    .unwrap_or(1)
+                    .max(1);
+                vec![vec![item; items].join(" ")]
+            }
+            SimpleTypeVariety::Union => simple_type
+                .member_types()
+                .iter()
+                .map(|member| self.item_value(member.as_ref()))
+                .collect(),
+            SimpleTypeVariety::Atomic => {
+                let primitive = simple_type.primitive_name().unwrap_or("string");
+                let mut candidates: Vec<String> = facets
+                    .patterns
+                    .iter()
+                    .filter_map(|facet| facet.source.as_deref().and_then(PatternSampler::sample))
+                    .collect();
+                if is_numeric(primitive) {
+                    candidates.push("0".to_string());
+                    if let Some(min) = &facets.min_inclusive {
+                        candidates.push(min.value.to_string());
+                    }
+                    if let Some(NumericBound::Integer(min)) = facets.min_exclusive.as_ref().map(|f| &f.value) {
+                        candidates.push((min + 1).to_string());
+                    }
+                    if let Some(max) = &facets.max_inclusive {
+                        candidates.push(max.value.to_string());
+                    }
+                    candidates.extend(["1", "-1"].map(String::from));
+                } else if let Some(value) = primitive_placeholder(primitive) {
+                    candidates.push(value.to_string());
+                } else if primitive == "ID" {
+                    candidates.push(format!("id{}", self.ids.len() + 1));
+                } else if primitive == "IDREF" {
+                    candidates.push(self.ids.first().cloned().unwrap_or_else(|| "id1".to_string()));
+                } else {
+                    let length = facets.length.as_ref().map(|f| f.value);
+                    let min = length.or(facets.min_length.as_ref().map(|f| f.value)).unwrap_or(0);
+                    let max = length.or(facets.max_length.as_ref().map(|f| f.value)).unwrap_or(usize::MAX);
+                    candidates.push(fit_length(PLACEHOLDER, min, max));
+                }
+                candidates
+            }
+        }
+    }
+
+    /// Value of a list item or union member type
+    fn item_value(&self, simple_type: &dyn SimpleType) -> String {
+        let candidates = self.candidates(simple_type);
+        candidates
+            .iter()
+            .find(|candidate| simple_type.validate_value(candidate).is_ok())
+            .or(candidates.first())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Check whether a built-in type is numeric
+fn is_numeric(primitive: &str) -> bool {
+    matches!(
+        primitive,
+        "decimal"
+            | "float"
+            | "double"
+            | "integer"
+            | "nonNegativeInteger"
+            | "positiveInteger"
+            | "nonPositiveInteger"
+            | "negativeInteger"
+            | "long"
+            | "int"
+            | "short"
+            | "byte"
+            | "unsignedLong"
+            | "unsignedInt"
+            | "unsignedShort"
+            | "unsignedByte"
+    )
+}
+
+/// Placeholder values of the built-in types with a fixed lexical form
+fn primitive_placeholder(primitive: &str) -> Option<&'static str> {
+    let value = match primitive {
+        "boolean" => "false",
+        "date" => "2000-01-01",
+        "dateTime" | "dateTimeStamp" => "2000-01-01T00:00:00Z",
+        "time" => "00:00:00",
+        "gYear" => "2000",
+        "gYearMonth" => "2000-01",
+        "gMonth" => "--01",
+        "gDay" => "---01",
+        "gMonthDay" => "--01-01",
+        "duration" | "dayTimeDuration" => "PT0S",
+        "yearMonthDuration" => "P0M",
+        "hexBinary" => "00",
+        "base64Binary" => "AA==",
+        "anyURI" => "http://example.com",
+        "language" => "en",
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Truncate or pad a value to a length range
+fn fit_length(value: &str, min: usize, max: usize) -> String {
+    let mut fitted: String = value.chars().take(max).collect();
+    while fitted.chars().count() < min {
+        fitted.push('x');
+    }
+    fitted
+}
+
+/// Builds the shortest string matching an XSD regular expression
+///
+/// Branches take their first alternative, quantifiers their minimum and
+/// character classes their first member, so `[A-Z]{2}-\d{3}` gives
+/// `AA-000`. Class escapes and properties are approximated, which is why
+/// callers check the result against the type.
+struct PatternSampler {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl PatternSampler {
+    /// Sample a pattern, or `None` if it can't be parsed
+    fn sample(pattern: &str) -> Option<String> {
+        let mut sampler = Self {
+            chars: pattern.chars().collect(),
+            pos: 0,
+        };
+        let value = sampler.reg_exp()?;
+        (sampler.pos == sampler.chars.len()).then_some(value)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    /// `regExp ::= branch ( '|' branch )*`
+    fn reg_exp(&mut self) -> Option<String> {
+        let first = self.branch()?;
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            self.branch()?;
+        }
+        Some(first)
+    }
+
+    /// `branch ::= piece*`
+    fn branch(&mut self) -> Option<String> {
+        let mut value = String::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            let count = self.quantifier()?;
+            for _ in 0..count {
+                value.push_str(&atom);
+            }
+        }
+        Some(value)
+    }
+
+    fn atom(&mut self) -> Option<String> {
+        match self.next()? {
+            '(' => {
+                let value = self.reg_exp()?;
+                (self.next()? == ')').then_some(value)
+            }
+            '[' => self.char_class()?.sample().map(String::from),
+            '\\' => {
+                let class = CharClass {
+                    negated: false,
+                    items: vec![self.escape()?],
+                    subtraction: None,
+                };
+                class.sample().map(String::from)
+            }
+            '.' => Some("x".to_string()),
+            '?' | '*' | '+' | '{' | '}' | ']' => None,
+            c => Some(c.to_string()),
+        }
+    }
+
+    /// Minimum count of a quantifier, 1 without quantifier
+    fn quantifier(&mut self) -> Option<usize> {
+        match self.peek() {
+            Some('?') | Some('*') => {
+                self.pos += 1;
+                Some(0)
+            }
+            Some('+') => {
+                self.pos += 1;
+                Some(1)
+            }
+            Some('{') => {
+                self.pos += 1;
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c != '}') {
+                    self.pos += 1;
+                }
+                let bounds: String = self.chars[start..self.pos].iter().collect();
+                self.next()?;
+                bounds.split(',').next()?.trim().parse().ok()
+            }
+            _ => Some(1),
+        }
+    }
+
+    /// Parse an escape, after the backslash
+    fn escape(&mut self) -> Option<ClassItem> {
+        let c = self.next()?;
+        let item = match c {
+            'n' => ClassItem::Range('\n', '\n'),
+            'r' => ClassItem::Range('\r', '\r'),
+            't' => ClassItem::Range('\t', '\t'),
+            'd' | 's' | 'w' | 'i' | 'c' | 'D' | 'S' | 'W' | 'I' | 'C' => ClassItem::Escape {
+                kind: c.to_ascii_lowercase(),
+                property: String::new(),
+                negated: c.is_ascii_uppercase(),
+            },
+            'p' | 'P' => {
+                if self.next()? != '{' {
+                    return None;
+                }
+                let mut property = String::new();
+                loop {
+                    match self.next()? {
+                        '}' => break,
+                        c => property.push(c),
+                    }
+                }
+                ClassItem::Escape {
+                    kind: 'p',
+                    property,
+                    negated: c == 'P',
+                }
+            }
+            c => ClassItem::Range(c, c),
+        };
+        Some(item)
+    }
+
+    /// Parse a character class expression, after the opening bracket
+    fn char_class(&mut self) -> Option<CharClass> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.pos += 1;
+        }
+        let mut items = Vec::new();
+        let mut subtraction = None;
+        loop {
+            match self.next()? {
+                ']' => break,
+                '-' if self.peek() == Some('[') => {
+                    self.pos += 1;
+                    subtraction = Some(Box::new(self.char_class()?));
+                    if self.next()? != ']' {
+                        return None;
+                    }
+                    break;
+                }
+                '\\' => items.push(self.escape()?),
+                c => {
+                    // A '-' before the closing bracket or a subtraction is a literal
+                    let is_range = self.peek() == Some('-')
+                        && !matches!(self.chars.get(self.pos + 1), Some(']') | Some('[') | None);
+                    if is_range {
+                        self.pos += 1;
+                        let end = match self.next()? {
+                            '\\' => match self.escape()? {
+                                ClassItem::Range(end, _) => end,
+                                ClassItem::Escape { .. } => return None,
+                            },
+                            end => end,
+                        };
+                        items.push(ClassItem::Range(c, end));
+                    } else {
+                        items.push(ClassItem::Range(c, c));
+                    }
+                }
+            }
+        }
+        Some(CharClass {
+            negated,
+            items,
+            subtraction,
+        })
+    }
+}
+
+/// A character class of a pattern
+struct CharClass {
+    negated: bool,
+    items: Vec<ClassItem>,
+    subtraction: Option<Box<CharClass>>,
+}
+
+impl CharClass {
+    fn matches(&self, c: char) -> bool {
+        let included = self.items.iter().any(|item| item.matches(c)) != self.negated;
+        included && !self.subtraction.as_ref().is_some_and(|subtraction| subtraction.matches(c))
+    }
+
+    /// The first member of the class, trying its own members first
+    fn sample(&self) -> Option<char> {
+        self.items
+            .iter()
+            .flat_map(ClassItem::representatives)
+            .chain(FALLBACK_CHARS.chars())
+            .find(|&c| self.matches(c))
+    }
+}
+
+/// A member of a character class
+enum ClassItem {
+    /// A character range, single characters being one-character ranges
+    Range(char, char),
+    /// A class escape such as `\d` or `\p{Lu}`, `kind` being the lowercase letter
+    Escape { kind: char, property: String, negated: bool },
+}
+
+impl ClassItem {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Self::Range(start, end) => (*start..=*end).contains(&c),
+            Self::Escape { kind, property, negated } => {
+                let matched = match *kind {
+                    'd' => c.is_numeric(),
+                    's' => matches!(c, ' ' | '\t' | '\n' | '\r'),
+                    'w' => c.is_alphanumeric(),
+                    'i' => c.is_alphabetic() || c == '_' || c == ':',
+                    'c' => c.is_alphanumeric() || matches!(c, '-' | '.' | '_' | ':'),
+                    _ => property_matches(property, c),
+                };
+                matched != *negated
+            }
+        }
+    }
+
+    /// Characters of the item to try, in order
+    fn representatives(&self) -> Vec<char> {
+        match self {
+            Self::Range(start, end) => (*start..=*end).take(64).collect(),
+            Self::Escape { negated: true, .. } => Vec::new(),
+            Self::Escape { kind, .. } => match *kind {
+                'd' => vec!['0'],
+                's' => vec![' '],
+                'p' => vec!['a', 'A', '0', ' ', '.'],
+                _ => vec!['a'],
+            },
+        }
+    }
+}
+
+/// Approximate match of a Unicode category or block escape
+///
+/// Blocks are not checked; the sampled value is validated anyway.
+fn property_matches(property: &str, c: char) -> bool {
+    match property {
+        "Lu" => c.is_uppercase(),
+        "Ll" => c.is_lowercase(),
+        "Nd" => c.is_ascii_digit() || c.is_numeric(),
+        _ => match property.chars().next() {
+            Some('L') => c.is_alphabetic(),
+            Some('N') => c.is_numeric(),
+            Some('Z') => c.is_whitespace(),
+            Some('P') => c.is_ascii_punctuation(),
+            Some('C') => c.is_control(),
+            _ => true,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_root() {
+        let schema = XsdSchema::new();
+        assert!(schema.sample_document(&QName::local("missing")).is_err());
+    }
+
+    #[test]
+    fn test_book_sample() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           xmlns="http://example.com/books"
+           targetNamespace="http://example.com/books"
+           elementFormDefault="qualified">
+  <xs:simpleType name="genreType">
+    <xs:restriction base="xs:string">
+      <xs:enumeration value="fiction"/>
+      <xs:enumeration value="non-fiction"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="isbnType">
+    <xs:restriction base="xs:string">
+      <xs:length value="13"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:element name="book">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="title" type="xs:string"/>
+        <xs:element name="author" type="xs:string" maxOccurs="unbounded"/>
+        <xs:element name="isbn" type="isbnType"/>
+        <xs:element name="pages" type="xs:positiveInteger"/>
+        <xs:element name="published" type="xs:date"/>
+        <xs:element name="subtitle" type="xs:string" minOccurs="0"/>
+        <xs:element name="format" type="xs:string" default="paperback"/>
+      </xs:sequence>
+      <xs:attribute name="genre" type="genreType" use="required"/>
+      <xs:attribute name="available" type="xs:boolean" use="required"/>
+      <xs:attribute name="lang" type="xs:language"/>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+
+        let xml = schema
+            .sample_document(&QName::namespaced("http://example.com/books", "book"))
+            .unwrap();
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<book xmlns="http://example.com/books" available="false" genre="fiction">"#,
+                "<title>example</title><author>example</author><isbn>examplexxxxxx</isbn>",
+                "<pages>1</pages><published>2000-01-01</published><format>paperback</format></book>"
+            )
+        );
+        assert!(schema.is_valid_string(&xml));
+    }
+
+    #[test]
+    fn test_recursive_sample() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="tree" type="nodeType"/>
+  <xs:complexType name="nodeType">
+    <xs:sequence>
+      <xs:element name="label" type="xs:string"/>
+      <xs:choice>
+        <xs:element name="children">
+          <xs:complexType>
+            <xs:sequence>
+              <xs:element name="node" type="nodeType" maxOccurs="unbounded"/>
+            </xs:sequence>
+          </xs:complexType>
+        </xs:element>
+        <xs:element name="leaf" type="xs:integer"/>
+      </xs:choice>
+    </xs:sequence>
+    <xs:attribute name="id" type="xs:ID" use="required"/>
+  </xs:complexType>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+
+        let xml = schema.sample_document(&QName::local("tree")).unwrap();
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<tree id="id1"><label>example</label><children>"#,
+                r#"<node id="id2"><label>example</label><leaf>0</leaf></node>"#,
+                "</children></tree>"
+            )
+        );
+        assert!(schema.is_valid_string(&xml));
+    }
+
+    #[test]
+    fn test_pattern_sample() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="codeType">
+    <xs:restriction base="xs:string">
+      <xs:pattern value="[A-Z]{2}-\d{3}"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:element name="item">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="code" type="codeType"/>
+        <xs:element name="sku">
+          <xs:simpleType>
+            <xs:restriction base="xs:string">
+              <xs:pattern value="(SKU|ID)_[b-z-[aeiou]]+[0-9]?"/>
+              <xs:minLength value="4"/>
+            </xs:restriction>
+          </xs:simpleType>
+        </xs:element>
+      </xs:sequence>
+      <xs:attribute name="ref" type="codeType" use="required"/>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+
+        let xml = schema.sample_document(&QName::local("item")).unwrap();
+        assert_eq!(xml, r#"<item ref="AA-000"><code>AA-000</code><sku>SKU_b</sku></item>"#);
+        assert!(schema.is_valid_string(&xml));
+    }
+
+    #[test]
+    fn test_pattern_sampler() {
+        assert_eq!(PatternSampler::sample(r"\d{13}").as_deref(), Some("0000000000000"));
+        assert_eq!(PatternSampler::sample(r"[^a-z]\s\p{Lu}+").as_deref(), Some("A A"));
+        assert_eq!(PatternSampler::sample("a|bc").as_deref(), Some("a"));
+        assert_eq!(PatternSampler::sample("x*y?").as_deref(), Some(""));
+        assert_eq!(PatternSampler::sample("(ab"), None);
+    }
+
+    #[test]
+    fn test_fit_length() {
+        assert_eq!(fit_length("example", 0, usize::MAX), "example");
+        assert_eq!(fit_length("example", 0, 3), "exa");
+        assert_eq!(fit_length("example", 9, 12), "examplexx");
+    }
+}
@@ -0,0 +1,422 @@
+//! Document size estimation
+//!
+//! This module estimates the serialized size of documents that are valid
+//! for a global element, which is useful for buffer pre-allocation and
+//! capacity planning. The estimate walks the effective content model and
+//! sums the sizes of tags, attributes and values:
+//! - the minimum leaves out optional elements and attributes and uses the
+//!   shortest values allowed by the types;
+//! - the maximum includes everything once, repeats unbounded particles
+//!   [`SizeEstimateOptions::unbounded_occurs`] times and uses the longest
+//!   values allowed by the types.
+//!
+//! Documents are assumed to be serialized like the generated samples:
+//! unprefixed names, a default namespace declaration on the root, no XML
+//! declaration and no whitespace between elements. Wildcard content and
+//! character escaping are not accounted for.
+
+use std::collections::HashMap;
+
+use crate::namespaces::QName;
+use crate::validators::base::AttributeValidator;
+use crate::validators::facets::NumericBound;
+use crate::validators::{
+    ComplexContent, GlobalType, GroupParticle, ModelType, SimpleType, SimpleTypeVariety, XsdComplexType,
+    XsdElement, XsdGroup, XsdSchema,
+};
+
+/// Options for document size estimation
+///
+/// See [`XsdSchema::estimated_document_size_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeEstimateOptions {
+    /// Number of occurrences assumed for unbounded particles and list items
+    pub unbounded_occurs: usize,
+    /// Maximum length assumed for values of types without a length limit
+    pub max_value_length: usize,
+    /// Maximum element nesting depth; deeper elements only count their
+    /// tags, which ends recursion in self-containing content models
+    pub depth_limit: usize,
+}
+
+impl Default for SizeEstimateOptions {
+    fn default() -> Self {
+        Self {
+            unbounded_occurs: 10,
+            max_value_length: 64,
+            depth_limit: 8,
+        }
+    }
+}
+
+/// Minimum and maximum size in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Bounds {
+    min: usize,
+    max: usize,
+}
+
+impl Bounds {
+    const ZERO: Bounds = Bounds { min: 0, max: 0 };
+
+    fn new(min: usize, max: usize) -> Self {
+        Self { min, max }
+    }
+
+    fn exact(size: usize) -> Self {
+        Self::new(size, size)
+    }
+
+    /// Both of two parts, in sequence
+    fn plus(self, other: Bounds) -> Self {
+        Self::new(self.min.saturating_add(other.min), self.max.saturating_add(other.max))
+    }
+
+    /// One of two alternatives
+    fn either(self, other: Bounds) -> Self {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    /// Repeated between `min` and `max` times
+    fn times(self, min: usize, max: usize) -> Self {
+        Self::new(self.min.saturating_mul(min), self.max.saturating_mul(max))
+    }
+}
+
+impl XsdSchema {
+    /// Estimate the size of a valid document for a global element
+    ///
+    /// Returns `(min_bytes, max_bytes)` computed with the default
+    /// [`SizeEstimateOptions`], or `(0, 0)` if `root_qname` is not a
+    /// global element. See [`XsdSchema::estimated_document_size_with`].
+    pub fn estimated_document_size(
+        &self,
+        root_qname: &QName,
+        sample_values: Option<&HashMap<QName, String>>,
+    ) -> (usize, usize) {
+        self.estimated_document_size_with(root_qname, sample_values, &SizeEstimateOptions::default())
+    }
+
+    /// Estimate the size of a valid document for a global element
+    ///
+    /// `sample_values` maps element and attribute names to representative
+    /// values, whose length is used for both bounds instead of the length
+    /// allowed by the type. Fixed values are used the same way.
+    pub fn estimated_document_size_with(
+        &self,
+        root_qname: &QName,
+        sample_values: Option<&HashMap<QName, String>>,
+        options: &SizeEstimateOptions,
+    ) -> (usize, usize) {
+        let Some(root) = self.lookup_element(root_qname) else {
+            return (0, 0);
+        };
+        let estimator = SizeEstimator {
+            schema: self,
+            sample_values,
+            options,
+        };
+
+        let mut size = estimator.element(root, 0);
+        if let Some(ns) = &root.name.namespace {
+            // ` xmlns="..."`
+            size = size.plus(Bounds::exact(ns.len() + 9));
+        }
+        (size.min, size.max)
+    }
+}
+
+/// Walks content models summing the size of their serialization
+struct SizeEstimator<'a> {
+    schema: &'a XsdSchema,
+    sample_values: Option<&'a HashMap<QName, String>>,
+    options: &'a SizeEstimateOptions,
+}
+
+impl SizeEstimator<'_> {
+    /// Size of an element, including its tags
+    fn element(&self, element: &XsdElement, depth: usize) -> Bounds {
+        let name_len = element.name.local_name.len();
+        // `<name/>`
+        let empty_tag = name_len + 3;
+        // `<name>` and `</name>`
+        let tags = 2 * name_len + 5;
+        if depth > self.options.depth_limit {
+            return Bounds::exact(empty_tag);
+        }
+
+        let (attributes, content) = match element.effective_type(self.schema) {
+            Some(GlobalType::Simple(simple_type)) => {
+                let value = self.value(&element.name, element.fixed.as_deref(), Some(simple_type.as_ref()));
+                (Bounds::ZERO, value)
+            }
+            Some(GlobalType::Complex(complex_type)) => {
+                let content = match &complex_type.content {
+                    ComplexContent::Simple(simple_type) => {
+                        self.value(&element.name, element.fixed.as_deref(), Some(simple_type.as_ref()))
+                    }
+                    ComplexContent::Group(group) => self.group(group, depth),
+                };
+                (self.attributes(&complex_type), content)
+            }
+            None => (Bounds::ZERO, self.value(&element.name, element.fixed.as_deref(), None)),
+        };
+
+        let min = if content.min == 0 { empty_tag } else { tags + content.min };
+        Bounds::new(min, tags.saturating_add(content.max)).plus(attributes)
+    }
+
+    /// Size of the attributes of a complex type
+    ///
+    /// Optional attributes only count towards the maximum.
+    fn attributes(&self, complex_type: &XsdComplexType) -> Bounds {
+        let mut size = Bounds::ZERO;
+        for attr in complex_type.attributes.iter_attributes() {
+            if attr.is_prohibited() {
+                continue;
+            }
+            let simple_type = attr.effective_type(self.schema).map(|st| st.as_ref() as &dyn SimpleType);
+            // ` name="value"`
+            let attr_size = Bounds::exact(attr.name().local_name.len() + 4)
+                .plus(self.value(attr.name(), attr.fixed_value(), simple_type));
+            size = if attr.is_required() {
+                size.plus(attr_size)
+            } else {
+                size.plus(Bounds::new(0, attr_size.max))
+            };
+        }
+        size
+    }
+
+    /// Size of the particles of a model group
+    fn group(&self, group: &XsdGroup, depth: usize) -> Bounds {
+        // Unresolved group references are looked up in the schema
+        if group.particles.is_empty() {
+            if let Some(referenced) = group.group_ref.as_ref().and_then(|name| self.schema.lookup_group(name)) {
+                return self
+                    .group(referenced, depth)
+                    .times(group.occurs.min as usize, self.max_occurs(group.occurs.max));
+            }
+        }
+
+        let mut size: Option<Bounds> = None;
+        for particle in &group.particles {
+            let occurs = particle.occurs();
+            let particle_size = match particle {
+                GroupParticle::Element(ep) => {
+                    let decl = ep
+                        .element_decl
+                        .clone()
+                        .or_else(|| ep.element_ref.as_ref().and_then(|r| self.schema.lookup_element(r).cloned()))
+                        .or_else(|| self.schema.lookup_element(&ep.name).cloned());
+                    match decl {
+                        Some(decl) => self.element(&decl, depth + 1),
+                        None => Bounds::ZERO,
+                    }
+                }
+                GroupParticle::Group(nested) => self.group(nested, depth),
+                // Wildcard content is not accounted for
+                GroupParticle::Any(_) => Bounds::ZERO,
+            }
+            .times(occurs.min as usize, self.max_occurs(occurs.max));
+
+            size = Some(match (size, group.model) {
+                (None, _) => particle_size,
+                (Some(size), ModelType::Choice) => size.either(particle_size),
+                (Some(size), ModelType::Sequence | ModelType::All) => size.plus(particle_size),
+            });
+        }
+        size.unwrap_or(Bounds::ZERO)
+    }
+
+    fn max_occurs(&self, max: Option<u32>) -> usize {
+        max.map_or(self.options.unbounded_occurs, |max| max as usize)
+    }
+
+    /// Size of the value of an element or attribute
+    fn value(&self, name: &QName, fixed: Option<&str>, simple_type: Option<&dyn SimpleType>) -> Bounds {
+        if let Some(sample) = self.sample_values.and_then(|samples| samples.get(name)) {
+            return Bounds::exact(sample.len());
+        }
+        if let Some(fixed) = fixed {
+            return Bounds::exact(fixed.len());
+        }
+        match simple_type {
+            Some(simple_type) => self.simple_type(simple_type),
+            None => Bounds::new(0, self.options.max_value_length),
+        }
+    }
+
+    /// Size of the values allowed by a simple type
+    fn simple_type(&self, simple_type: &dyn SimpleType) -> Bounds {
+        let facets = simple_type.effective_facets();
+        if let Some(enumeration) = facets.enumeration.as_ref().filter(|e| !e.values.is_empty()) {
+            let lengths = enumeration.values.iter().map(String::len);
+            return Bounds::new(lengths.clone().min().unwrap_or(0), lengths.max().unwrap_or(0));
+        }
+
+        let length = match (&facets.length, &facets.min_length, &facets.max_length) {
+            (Some(length), _, _) => Some((length.value, length.value)),
+            (None, None, None) => None,
+            (None, min, max) => {
+                let min = min.as_ref().map_or(0, |f| f.value);
+                Some((min, max.as_ref().map_or(min.max(self.options.max_value_length), |f| f.value)))
+            }
+        };
+
+        match simple_type.variety() {
+            SimpleTypeVariety::List => {
+                let item = match simple_type.item_type() {
+                    Some(item_type) => self.simple_type(item_type.as_ref()),
+                    None => Bounds::new(0, self.options.max_value_length),
+                };
+                // List lengths count items, separated by single spaces
+                let (min_items, max_items) = length.unwrap_or((0, self.options.unbounded_occurs));
+                Bounds::new(
+                    item.min.saturating_mul(min_items) + min_items.saturating_sub(1),
+                    item.max.saturating_mul(max_items) + max_items.saturating_sub(1),
+                )
+            }
+            SimpleTypeVariety::Union => simple_type
+                .member_types()
+                .iter()
+                .map(|member| self.simple_type(member.as_ref()))
+                .reduce(Bounds::either)
+                .unwrap_or(Bounds::new(0, self.options.max_value_length)),
+            SimpleTypeVariety::Atomic => {
+                let primitive = simple_type.primitive_name().unwrap_or("string");
+                match (primitive, length) {
+                    // Lengths of binary types count octets
+                    ("hexBinary", Some((min, max))) => Bounds::new(min * 2, max.saturating_mul(2)),
+                    ("base64Binary", Some((min, max))) => {
+                        Bounds::new(min.div_ceil(3) * 4, max.div_ceil(3).saturating_mul(4))
+                    }
+                    (_, Some((min, max))) => Bounds::new(min, max),
+                    (_, None) => {
+                        let min = facets.min_inclusive.map(|f| f.value);
+                        let max = facets.max_inclusive.map(|f| f.value);
+                        numeric_digits(&min, &max)
+                            .or_else(|| primitive_length(primitive))
+                            .map_or(Bounds::new(0, self.options.max_value_length), |(min, max)| {
+                                Bounds::new(min, max)
+                            })
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Lengths of the integers in an inclusive range, if both bounds are integers
+fn numeric_digits(min: &Option<NumericBound>, max: &Option<NumericBound>) -> Option<(usize, usize)> {
+    match (min, max) {
+        (Some(NumericBound::Integer(lo)), Some(NumericBound::Integer(hi))) if lo <= hi => {
+            let shortest = if *lo <= 0 && *hi >= 0 {
+                1
+            } else {
+                lo.to_string().len().min(hi.to_string().len())
+            };
+            Some((shortest, lo.to_string().len().max(hi.to_string().len())))
+        }
+        _ => None,
+    }
+}
+
+/// Lexical lengths of the built-in primitive and integer types
+///
+/// Returns None for string-like types, whose length is unlimited.
+fn primitive_length(primitive: &str) -> Option<(usize, usize)> {
+    let length = match primitive {
+        "boolean" => (1, 5),
+        "byte" => (1, 4),
+        "unsignedByte" => (1, 3),
+        "short" => (1, 6),
+        "unsignedShort" => (1, 5),
+        "int" => (1, 11),
+        "unsignedInt" => (1, 10),
+        "long" => (1, 20),
+        "unsignedLong" => (1, 20),
+        "integer" | "nonNegativeInteger" | "positiveInteger" | "nonPositiveInteger" => (1, 20),
+        "negativeInteger" => (2, 20),
+        "decimal" => (1, 32),
+        "float" | "double" => (1, 24),
+        "date" => (10, 16),
+        "dateTime" | "dateTimeStamp" => (19, 35),
+        "time" => (8, 24),
+        "gYear" => (4, 10),
+        "gYearMonth" => (7, 13),
+        "gMonth" => (4, 10),
+        "gDay" => (5, 11),
+        "gMonthDay" => (7, 13),
+        "duration" | "dayTimeDuration" | "yearMonthDuration" => (3, 32),
+        "language" => (1, 35),
+        _ => return None,
+    };
+    Some(length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn estimate(xsd: &str, root: &str) -> (usize, usize) {
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        schema.estimated_document_size(&QName::local(root), None)
+    }
+
+    #[test]
+    fn test_unknown_root() {
+        let schema = XsdSchema::new();
+        assert_eq!(schema.estimated_document_size(&QName::local("missing"), None), (0, 0));
+    }
+
+    #[test]
+    fn test_choice_and_enumeration() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="sizeType">
+    <xs:restriction base="xs:string">
+      <xs:enumeration value="S"/>
+      <xs:enumeration value="XXL"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:element name="r">
+    <xs:complexType>
+      <xs:choice>
+        <xs:element name="a" type="xs:boolean"/>
+        <xs:element name="bb" type="sizeType"/>
+      </xs:choice>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+
+        // <r></r> around <a>1</a> (8 bytes) or <bb>XXL</bb> (12 bytes)
+        assert_eq!(estimate(xsd, "r"), (7 + 8, 7 + 12));
+    }
+
+    #[test]
+    fn test_recursion_is_cut_off() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="node" type="nodeType"/>
+  <xs:complexType name="nodeType">
+    <xs:sequence>
+      <xs:element ref="node" minOccurs="0" maxOccurs="unbounded"/>
+    </xs:sequence>
+  </xs:complexType>
+</xs:schema>"#;
+
+        let (min, max) = estimate(xsd, "node");
+        assert_eq!(min, "<node/>".len());
+        assert!(max > min && max < usize::MAX);
+    }
+
+    #[test]
+    fn test_numeric_digits() {
+        let int = |v| Some(NumericBound::Integer(v));
+        assert_eq!(numeric_digits(&int(1), &int(999)), Some((1, 3)));
+        assert_eq!(numeric_digits(&int(-50), &int(5)), Some((1, 3)));
+        assert_eq!(numeric_digits(&int(100), &int(200)), Some((3, 3)));
+        assert_eq!(numeric_digits(&None, &int(5)), None);
+    }
+}
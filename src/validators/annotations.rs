@@ -0,0 +1,120 @@
+//! Schema annotations
+//!
+//! This module represents the `xs:annotation` elements of schema components,
+//! with the text of their `xs:documentation` and `xs:appinfo` children.
+
+use crate::documents::Element;
+
+/// Local name of the xs:annotation element
+const ANNOTATION: &str = "annotation";
+/// Local name of the xs:documentation element
+const DOCUMENTATION: &str = "documentation";
+/// Local name of the xs:appinfo element
+const APPINFO: &str = "appinfo";
+
+/// Annotation of a schema component
+///
+/// Holds the text of each `xs:documentation` and `xs:appinfo` element of the
+/// component's `xs:annotation`s, in document order. The text of nested
+/// elements, such as XHTML markup in a documentation, is included.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XsdAnnotation {
+    /// Text of the xs:documentation elements
+    pub documentation: Vec<String>,
+    /// Text of the xs:appinfo elements
+    pub app_info: Vec<String>,
+}
+
+impl XsdAnnotation {
+    /// Parse an xs:annotation element
+    pub fn parse(annotation: &Element) -> Self {
+        let mut parsed = Self::default();
+        parsed.extend_from(annotation);
+        parsed
+    }
+
+    /// Parse the xs:annotation children of a schema component element
+    ///
+    /// The annotations of the component are merged. Returns `None` when the
+    /// element has no annotation.
+    pub fn from_component(elem: &Element) -> Option<Self> {
+        let mut annotations = elem
+            .children
+            .iter()
+            .filter(|child| child.local_name() == ANNOTATION)
+            .peekable();
+        annotations.peek()?;
+
+        let mut parsed = Self::default();
+        for annotation in annotations {
+            parsed.extend_from(annotation);
+        }
+        Some(parsed)
+    }
+
+    /// Add the documentation and appinfo of an xs:annotation element
+    fn extend_from(&mut self, annotation: &Element) {
+        for child in &annotation.children {
+            match child.local_name() {
+                DOCUMENTATION => self.documentation.push(element_text(child)),
+                APPINFO => self.app_info.push(element_text(child)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Check whether the annotation has no documentation and no appinfo
+    pub fn is_empty(&self) -> bool {
+        self.documentation.is_empty() && self.app_info.is_empty()
+    }
+}
+
+/// Get the text of an element and its descendants, trimmed
+fn element_text(elem: &Element) -> String {
+    fn collect<'a>(elem: &'a Element, parts: &mut Vec<&'a str>) {
+        if let Some(text) = elem.text.as_deref() {
+            parts.push(text.trim());
+        }
+        for child in &elem.children {
+            collect(child, parts);
+        }
+    }
+
+    let mut parts = Vec::new();
+    collect(elem, &mut parts);
+    parts.retain(|part| !part.is_empty());
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documents::Document;
+
+    #[test]
+    fn test_annotation_from_component() {
+        let doc = Document::from_string(
+            r#"<xs:element xmlns:xs="http://www.w3.org/2001/XMLSchema" name="e">
+  <xs:annotation>
+    <xs:documentation xml:lang="en">  A note.  </xs:documentation>
+    <xs:appinfo><tool version="2">generated</tool></xs:appinfo>
+  </xs:annotation>
+  <xs:annotation>
+    <xs:documentation>Second</xs:documentation>
+  </xs:annotation>
+</xs:element>"#,
+        )
+        .unwrap();
+
+        let annotation = XsdAnnotation::from_component(doc.root().unwrap()).unwrap();
+        assert_eq!(annotation.documentation, vec!["A note.", "Second"]);
+        assert_eq!(annotation.app_info, vec!["generated"]);
+        assert!(!annotation.is_empty());
+    }
+
+    #[test]
+    fn test_no_annotation() {
+        let doc = Document::from_string(r#"<element name="e"><complexType/></element>"#).unwrap();
+        assert_eq!(XsdAnnotation::from_component(doc.root().unwrap()), None);
+    }
+}
@@ -3,7 +3,10 @@
 //! This module handles xs:assert constraints for XSD 1.1.
 //! Assertions allow additional constraints via XPath expressions.
 
-use crate::error::{ParseError, Result};
+use crate::documents::Element;
+use crate::error::{Error, ParseError, Result};
+use crate::namespaces::QName;
+use crate::xpath::{XPathContext, XPathExpression, XPathResult};
 use super::base::{ValidationStatus, Validator};
 
 /// XSD 1.1 Assert constraint
@@ -26,8 +29,10 @@ pub struct XsdAssert {
     pub id: Option<String>,
     /// XPath default namespace
     pub xpath_default_namespace: Option<XPathDefaultNamespace>,
-    /// The parsed XPath token (placeholder - would need XPath parser)
+    /// Whether the test expression has been compiled
     xpath_compiled: bool,
+    /// The compiled test expression, if it is valid
+    expression: Option<XPathExpression>,
     /// Building errors
     errors: Vec<ParseError>,
     /// Whether the assertion has been built
@@ -67,6 +72,7 @@ impl XsdAssert {
             id: None,
             xpath_default_namespace: None,
             xpath_compiled: false,
+            expression: None,
             errors: Vec::new(),
             built: false,
         }
@@ -99,6 +105,11 @@ impl XsdAssert {
         self.xpath_compiled
     }
 
+    /// Get the compiled test expression, if it is valid
+    pub fn expression(&self) -> Option<&XPathExpression> {
+        self.expression.as_ref()
+    }
+
     /// Add a parse error
     pub fn add_error(&mut self, error: ParseError) {
         self.errors.push(error);
@@ -124,34 +135,35 @@ impl XsdAssert {
             // In Python this triggers XMLSchemaAssertPathWarning
         }
 
-        // Mark as compiled (actual XPath compilation would happen here)
+        match XPathExpression::parse(&self.test) {
+            Ok(expression) => self.expression = Some(expression),
+            Err(e) => self.add_error(ParseError::new(format!("invalid assertion test '{}': {}", self.test, e))),
+        }
         self.xpath_compiled = true;
         Ok(())
     }
 
     /// Evaluate the assertion against a value
     ///
-    /// Note: This is a placeholder. Real implementation would require:
-    /// - An XPath 2.0+ evaluator (for XSD 1.1)
-    /// - Integration with schema-bound parser
-    /// - Variable binding support ($value)
-    pub fn evaluate(&self, _value: &str) -> Result<bool> {
-        if !self.xpath_compiled {
-            return Err(crate::error::Error::Parse(
-                ParseError::new("assertion not compiled")
-            ));
-        }
+    /// The value is bound to `$value` and is the text of the context node.
+    pub fn evaluate(&self, value: &str) -> Result<bool> {
+        let mut element = Element::new(QName::local("value"));
+        element.set_text(value.to_string());
+        let context = XPathContext::new().with_variable("value", XPathResult::String(value.to_string()));
+        self.evaluate_element(&element, &context)
+    }
 
-        // Placeholder: would need actual XPath evaluation
-        // For now, return true for 'true()' and false for 'false()'
-        match self.test.trim() {
-            "true()" => Ok(true),
-            "false()" => Ok(false),
-            _ => {
-                // Cannot evaluate complex expressions without XPath engine
-                Ok(true) // Optimistic default
-            }
+    /// Evaluate the assertion with an element as context node
+    pub fn evaluate_element(&self, element: &Element, context: &XPathContext) -> Result<bool> {
+        if !self.xpath_compiled {
+            return Err(Error::Parse(ParseError::new("assertion not compiled")));
         }
+        let Some(expression) = &self.expression else {
+            return Err(Error::Parse(ParseError::new(format!("invalid assertion test '{}'", self.test))));
+        };
+        expression
+            .evaluate_boolean(element, context)
+            .map_err(|e| Error::Parse(ParseError::new(format!("cannot evaluate assertion '{}': {}", self.test, e))))
     }
 }
 
@@ -316,6 +328,29 @@ mod tests {
         assert_eq!(assertion.evaluate("any").unwrap(), false);
     }
 
+    #[test]
+    fn test_assertion_evaluate_value() {
+        let mut assertion = XsdAssert::new("$value >= 0 and string-length(.) <= 3");
+        assertion.build().unwrap();
+
+        assert!(assertion.evaluate("42").unwrap());
+        assert!(!assertion.evaluate("-1").unwrap());
+        assert!(!assertion.evaluate("1000").unwrap());
+    }
+
+    #[test]
+    fn test_assertion_evaluate_element() {
+        let doc = crate::documents::Document::from_string(r#"<range min="1" max="5"/>"#).unwrap();
+        let mut assertion = XsdAssert::new("@min <= @max");
+        assertion.build().unwrap();
+        assert!(assertion.evaluate_element(doc.root().unwrap(), &XPathContext::new()).unwrap());
+
+        let mut invalid = XsdAssert::new("@min <=");
+        invalid.build().unwrap();
+        assert!(invalid.has_errors());
+        assert!(invalid.evaluate_element(doc.root().unwrap(), &XPathContext::new()).is_err());
+    }
+
     #[test]
     fn test_assertion_list() {
         let mut list = AssertionList::new();
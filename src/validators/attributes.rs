@@ -8,10 +8,14 @@ use crate::namespaces::QName;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use super::annotations::XsdAnnotation;
 use super::base::{
     AttributeValidator, TypeValidator, ValidationMode, ValidationStatus, Validator,
 };
 use super::builtins::XsdValue;
+use super::facets::WhiteSpace;
+use super::helpers::normalize_attribute_value;
+use super::schemas::XsdSchema;
 use super::simple_types::SimpleType;
 use super::wildcards::XsdAnyAttribute;
 
@@ -124,6 +128,8 @@ pub struct XsdAttribute {
     inheritable: bool,
     /// Reference to another attribute (if this is a ref)
     reference: Option<Arc<XsdAttribute>>,
+    /// Annotation of the declaration
+    pub annotation: Option<XsdAnnotation>,
     /// Build errors
     errors: Vec<ParseError>,
     /// Whether fully built
@@ -143,6 +149,7 @@ impl XsdAttribute {
             fixed: None,
             inheritable: false,
             reference: None,
+            annotation: None,
             errors: Vec::new(),
             built: false,
         }
@@ -160,6 +167,7 @@ impl XsdAttribute {
             fixed: None,
             inheritable: false,
             reference: None,
+            annotation: None,
             errors: Vec::new(),
             built: false,
         }
@@ -242,11 +250,26 @@ impl XsdAttribute {
         self.form == AttributeForm::Qualified
     }
 
+    /// Check if required
+    pub fn is_required(&self) -> bool {
+        self.use_mode == AttributeUse::Required
+    }
+
     /// Check if optional
     pub fn is_optional(&self) -> bool {
         self.use_mode == AttributeUse::Optional
     }
 
+    /// Check if this attribute has a default value
+    pub fn has_default(&self) -> bool {
+        self.default.is_some()
+    }
+
+    /// Check if this attribute has a fixed value
+    pub fn has_fixed(&self) -> bool {
+        self.fixed.is_some()
+    }
+
     /// Check if prohibited
     pub fn is_prohibited(&self) -> bool {
         self.use_mode == AttributeUse::Prohibited
@@ -267,6 +290,24 @@ impl XsdAttribute {
         self.fixed.as_deref().or(self.default.as_deref())
     }
 
+    /// Get the effective simple type of this attribute
+    ///
+    /// Uses the resolved type when available, then the referenced global
+    /// attribute, and finally looks up `type_name` in the schema. Returns
+    /// `None` for untyped attributes.
+    pub fn effective_type<'a>(&'a self, schema: &'a XsdSchema) -> Option<&'a Arc<dyn SimpleType + Send + Sync>> {
+        if let Some(ref attr_type) = self.attr_type {
+            return Some(attr_type);
+        }
+        if let Some(ref reference) = self.reference {
+            if let Some(attr_type) = reference.effective_type(schema) {
+                return Some(attr_type);
+            }
+        }
+        let type_name = self.type_name.as_ref()?;
+        schema.lookup_simple_type(type_name)
+    }
+
     /// Validate an attribute value
     pub fn validate_value(&self, value: Option<&str>) -> Result<XsdValue> {
         // If value is None, check for default/fixed
@@ -289,9 +330,14 @@ impl XsdAttribute {
             }
         };
 
+        // Values are compared and validated once normalized according to the whiteSpace of the type
+        let white_space = self.attr_type.as_ref().map_or(WhiteSpace::Preserve, |attr_type| attr_type.white_space());
+        let actual_value = normalize_attribute_value(actual_value, white_space);
+        let actual_value = actual_value.as_ref();
+
         // If fixed, check that value matches
         if let Some(ref fixed) = self.fixed {
-            if value.is_some() && actual_value != fixed {
+            if value.is_some() && actual_value != normalize_attribute_value(fixed, white_space) {
                 return Err(crate::error::Error::Validation(
                     ValidationError::new(format!(
                         "Attribute '{}' has fixed value '{}'",
@@ -405,6 +451,8 @@ pub struct XsdAttributeGroup {
     any_attribute: Option<Arc<XsdAnyAttribute>>,
     /// Back-reference to original attribute group when this is a redefinition (xs:redefine)
     pub redefine: Option<Arc<XsdAttributeGroup>>,
+    /// Annotation of the definition
+    pub annotation: Option<XsdAnnotation>,
     /// Build errors
     errors: Vec<ParseError>,
     /// Whether fully built
@@ -421,6 +469,7 @@ impl XsdAttributeGroup {
             pending_group_refs: Vec::new(),
             any_attribute: None,
             redefine: None,
+            annotation: None,
             errors: Vec::new(),
             built: false,
         }
@@ -435,6 +484,7 @@ impl XsdAttributeGroup {
             pending_group_refs: Vec::new(),
             any_attribute: None,
             redefine: None,
+            annotation: None,
             errors: Vec::new(),
             built: false,
         }
@@ -718,6 +768,37 @@ mod tests {
         assert!(attr.set_fixed("fixed".to_string()).is_err());
     }
 
+    #[test]
+    fn test_attribute_use_accessors() {
+        let mut attr = XsdAttribute::new(QName::local("flag"));
+        assert!(attr.is_optional());
+        assert!(!attr.is_required());
+        assert!(!attr.is_prohibited());
+
+        attr.set_use(AttributeUse::Required);
+        assert!(attr.is_required());
+        assert!(!attr.is_optional());
+
+        attr.set_use(AttributeUse::Prohibited);
+        assert!(attr.is_prohibited());
+        assert!(!attr.is_required());
+    }
+
+    #[test]
+    fn test_attribute_has_default_and_fixed() {
+        let mut with_default = XsdAttribute::new(QName::local("status"));
+        assert!(!with_default.has_default());
+        assert!(!with_default.has_fixed());
+        with_default.set_default("active".to_string()).unwrap();
+        assert!(with_default.has_default());
+        assert!(!with_default.has_fixed());
+
+        let mut with_fixed = XsdAttribute::new(QName::local("version"));
+        with_fixed.set_fixed("1.0".to_string()).unwrap();
+        assert!(with_fixed.has_fixed());
+        assert!(!with_fixed.has_default());
+    }
+
     #[test]
     fn test_attribute_validate_value() {
         let name = QName::local("amount");
@@ -923,4 +1004,39 @@ mod tests {
         let plain_attr = XsdAttribute::new(QName::local("attr3"));
         assert!(plain_attr.value_constraint().is_none());
     }
+
+    #[test]
+    fn test_attribute_effective_type_forward_reference() {
+        let schema = XsdSchema::from_string(
+            r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:attribute name="size" type="sizeType"/>
+  <xs:simpleType name="sizeType">
+    <xs:restriction base="xs:integer"/>
+  </xs:simpleType>
+</xs:schema>"#,
+        )
+        .unwrap();
+
+        // Declaration with only a type name, as left by a forward reference
+        let mut attr = XsdAttribute::new(QName::local("size"));
+        attr.type_name = Some(QName::local("sizeType"));
+        assert!(attr.simple_type().is_none());
+        let effective = attr.effective_type(&schema).unwrap();
+        assert!(effective.validate_value("42").is_ok());
+        assert!(effective.validate_value("big").is_err());
+
+        // A reference resolves through the referenced attribute
+        let mut reference = XsdAttribute::new(QName::local("size"));
+        reference.set_reference(Arc::new(attr));
+        assert!(reference.effective_type(&schema).is_some());
+
+        // A resolved type takes precedence
+        let mut typed = XsdAttribute::new(QName::local("label"));
+        typed.type_name = Some(QName::local("missingType"));
+        typed.set_type(Arc::new(XsdAtomicType::new(XSD_STRING).unwrap()));
+        assert!(typed.effective_type(&schema).is_some());
+
+        assert!(XsdAttribute::new(QName::local("untyped")).effective_type(&schema).is_none());
+    }
 }
@@ -6,7 +6,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::error::{ParseError, Result};
+use crate::error::{Error, ParseError, Result};
 use crate::namespaces::QName;
 
 use super::base::ValidationMode;
@@ -18,7 +18,8 @@ use super::groups::{ModelType, XsdGroup};
 use super::wildcards::{NamespaceConstraint, ProcessContents, XsdAnyElement, XsdAnyAttribute};
 use super::identities::{XsdIdentity, XsdSelector, XsdField};
 use super::particles::Occurs;
-use super::globals::{XsdGlobals, XsdNotation};
+use super::globals::{GlobalType, XsdGlobals, XsdNotation};
+use super::schemas::XsdSchema;
 use super::builtins::XSD_NAMESPACE;
 
 /// XSD version
@@ -794,6 +795,16 @@ impl<T, B> StagedMap<T, B> {
         self.store.insert(qname, built);
     }
 
+    /// Get a staged item
+    pub fn staged(&self, qname: &QName) -> Option<&StagedItem<T>> {
+        self.staging.get(qname)
+    }
+
+    /// Get a mutable staged item
+    pub fn staged_mut(&mut self, qname: &QName) -> Option<&mut StagedItem<T>> {
+        self.staging.get_mut(qname)
+    }
+
     /// Remove a staged item
     pub fn remove_staged(&mut self, qname: &QName) -> Option<StagedItem<T>> {
         self.staging.remove(qname)
@@ -839,10 +850,14 @@ impl<T, B> StagedMap<T, B> {
 /// Schema build context
 ///
 /// Tracks the state during schema building, including circular reference detection.
+/// Global complex types are staged before the build and resolved on demand by
+/// [`BuildContext::resolve_type`], base types first.
 #[derive(Debug)]
 pub struct BuildContext {
     /// Components currently being built (for circular reference detection)
     building_stack: Vec<QName>,
+    /// Build state of the staged global types
+    types: StagedMap<(), ()>,
     /// Errors encountered during building
     errors: Vec<ParseError>,
     /// XSD version
@@ -854,6 +869,7 @@ impl BuildContext {
     pub fn new(version: XsdVersion) -> Self {
         Self {
             building_stack: Vec::new(),
+            types: StagedMap::new(),
             errors: Vec::new(),
             version,
         }
@@ -862,17 +878,76 @@ impl BuildContext {
     /// Push a component onto the building stack
     pub fn push(&mut self, qname: QName) -> Result<()> {
         if self.building_stack.contains(&qname) {
-            return Err(crate::error::Error::Parse(
-                ParseError::new(format!(
-                    "Circular reference detected for '{}'",
-                    qname.to_string()
-                ))
-            ));
+            return Err(Self::circular_reference(&qname));
         }
         self.building_stack.push(qname);
         Ok(())
     }
 
+    fn circular_reference(qname: &QName) -> Error {
+        Error::Parse(ParseError::new(format!(
+            "Circular reference detected for '{}'",
+            qname.to_string()
+        )))
+    }
+
+    /// Stage a global type for resolution
+    pub fn stage_type(&mut self, qname: QName) {
+        self.types.stage(qname, ());
+    }
+
+    /// Get the build state of a staged type
+    pub fn type_state(&self, qname: &QName) -> Option<&StagedItem<()>> {
+        self.types.staged(qname)
+    }
+
+    /// Resolve a global type of the schema, resolving its base type first
+    ///
+    /// A staged complex type derived from another global complex type gets
+    /// the content and attributes of its base merged in. Types that are not
+    /// staged, or already resolved, are returned as they are. A type whose
+    /// base chain leads back to itself is an error; the types on the cycle
+    /// are left unmerged.
+    pub fn resolve_type<'s>(&mut self, qname: &QName, schema: &'s mut XsdSchema) -> Result<&'s GlobalType> {
+        match self.types.staged(qname) {
+            Some(item) if item.is_building() => return Err(Self::circular_reference(qname)),
+            Some(item) if item.is_pending() => {
+                self.push(qname.clone())?;
+                if let Some(item) = self.types.staged_mut(qname) {
+                    item.mark_building();
+                }
+
+                let result = match schema.maps.global_maps.types.get(qname) {
+                    Some(GlobalType::Complex(ct)) => match ct.base_type.clone() {
+                        // A redefinition refers to the type it replaces, which isn't staged
+                        Some(base) if &base != qname && self.types.is_staged(&base) => {
+                            self.resolve_type(&base, schema).map(|_| ())
+                        }
+                        _ => Ok(()),
+                    },
+                    _ => Ok(()),
+                };
+                if result.is_ok() {
+                    schema.resolve_complex_type_derivation(qname);
+                }
+
+                self.pop();
+                if let Some(item) = self.types.staged_mut(qname) {
+                    item.mark_built();
+                }
+                result?;
+            }
+            _ => {}
+        }
+
+        schema
+            .maps
+            .global_maps
+            .types
+            .get(qname)
+            .ok_or_else(|| Error::Parse(ParseError::new(format!("Unknown type '{}'", qname.to_string()))))
+    }
+
     /// Pop a component from the building stack
     pub fn pop(&mut self) {
         self.building_stack.pop();
@@ -1256,4 +1331,58 @@ mod tests {
         builder.clear_errors();
         assert!(builder.errors().is_empty());
     }
+
+    #[test]
+    fn test_build_context_resolve_type() {
+        let mut schema = XsdSchema::new();
+        schema.register_builtins().unwrap();
+        let builders = XsdBuilders::new();
+
+        let base = builders.build_complex_type_sequence("baseType");
+        let mut derived = builders.build_complex_type_empty("derivedType");
+        derived.base_type = Some(QName::local("baseType"));
+        derived.derivation = Some(super::super::complex_types::DerivationMethod::Extension);
+        schema.maps.register_complex_type(QName::local("baseType"), Arc::new(base));
+        schema.maps.register_complex_type(QName::local("derivedType"), Arc::new(derived));
+
+        let mut ctx = BuildContext::default();
+        ctx.stage_type(QName::local("baseType"));
+        ctx.stage_type(QName::local("derivedType"));
+        assert!(ctx.type_state(&QName::local("derivedType")).unwrap().is_pending());
+
+        // Resolving the derived type resolves its base first
+        let resolved = ctx.resolve_type(&QName::local("derivedType"), &mut schema).unwrap();
+        assert!(resolved.is_complex());
+        assert!(ctx.type_state(&QName::local("baseType")).unwrap().is_built());
+        assert!(ctx.type_state(&QName::local("derivedType")).unwrap().is_built());
+        assert_eq!(ctx.depth(), 0);
+
+        // Types that aren't staged are looked up as they are
+        assert!(ctx.resolve_type(&schema.builders.xsd_qname("string"), &mut schema).is_ok());
+        assert!(ctx.resolve_type(&QName::local("missingType"), &mut schema).is_err());
+    }
+
+    #[test]
+    fn test_build_context_resolve_type_cycle() {
+        let mut schema = XsdSchema::new();
+        let builders = XsdBuilders::new();
+
+        for (name, base) in [("aType", "bType"), ("bType", "aType")] {
+            let mut ct = builders.build_complex_type_empty(name);
+            ct.base_type = Some(QName::local(base));
+            ct.derivation = Some(super::super::complex_types::DerivationMethod::Extension);
+            schema.maps.register_complex_type(QName::local(name), Arc::new(ct));
+        }
+
+        let mut ctx = BuildContext::default();
+        ctx.stage_type(QName::local("aType"));
+        ctx.stage_type(QName::local("bType"));
+
+        let err = ctx.resolve_type(&QName::local("aType"), &mut schema).unwrap_err();
+        assert!(err.to_string().contains("Circular reference detected for 'aType'"));
+        // Both types are settled, so the cycle is not reported again
+        assert!(ctx.type_state(&QName::local("bType")).unwrap().is_built());
+        assert!(ctx.resolve_type(&QName::local("bType"), &mut schema).is_ok());
+        assert_eq!(ctx.depth(), 0);
+    }
 }
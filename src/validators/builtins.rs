@@ -4,6 +4,7 @@
 //! These types form the foundation of XSD validation.
 
 use crate::error::{Error, Result, ValidationError};
+use crate::namespaces::QName;
 use crate::validators::facets::WhiteSpace;
 use crate::validators::helpers::{
     base64_binary_validator, boolean_to_rust, byte_validator, decimal_validator,
@@ -415,6 +416,23 @@ impl BuiltinType {
     }
 }
 
+// =============================================================================
+// Lexical Patterns
+// =============================================================================
+
+lazy_static::lazy_static! {
+    static ref LANGUAGE_REGEX: regex::Regex = regex::Regex::new(r"^[a-zA-Z]{1,8}(-[a-zA-Z0-9]{1,8})*$").unwrap();
+    static ref DURATION_REGEX: regex::Regex = regex::Regex::new(r"^-?P(\d+Y)?(\d+M)?(\d+D)?(T(\d+H)?(\d+M)?(\d+(\.\d+)?S)?)?$").unwrap();
+    static ref DATETIME_REGEX: regex::Regex = regex::Regex::new(r"^-?\d{4,}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})?$").unwrap();
+    static ref DATE_REGEX: regex::Regex = regex::Regex::new(r"^-?\d{4,}-\d{2}-\d{2}(Z|[+-]\d{2}:\d{2})?$").unwrap();
+    static ref TIME_REGEX: regex::Regex = regex::Regex::new(r"^\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})?$").unwrap();
+    static ref GYEAR_REGEX: regex::Regex = regex::Regex::new(r"^-?\d{4,}(Z|[+-]\d{2}:\d{2})?$").unwrap();
+    static ref GYEAR_MONTH_REGEX: regex::Regex = regex::Regex::new(r"^-?\d{4,}-\d{2}(Z|[+-]\d{2}:\d{2})?$").unwrap();
+    static ref GMONTH_REGEX: regex::Regex = regex::Regex::new(r"^--\d{2}(Z|[+-]\d{2}:\d{2})?$").unwrap();
+    static ref GDAY_REGEX: regex::Regex = regex::Regex::new(r"^---\d{2}(Z|[+-]\d{2}:\d{2})?$").unwrap();
+    static ref GMONTH_DAY_REGEX: regex::Regex = regex::Regex::new(r"^--\d{2}-\d{2}(Z|[+-]\d{2}:\d{2})?$").unwrap();
+}
+
 // =============================================================================
 // Validator Functions
 // =============================================================================
@@ -445,8 +463,7 @@ fn validate_token(value: &str) -> Result<XsdValue> {
 
 fn validate_language(value: &str) -> Result<XsdValue> {
     // Language code pattern: [a-zA-Z]{1,8}(-[a-zA-Z0-9]{1,8})*
-    let re = regex::Regex::new(r"^[a-zA-Z]{1,8}(-[a-zA-Z0-9]{1,8})*$").unwrap();
-    if !re.is_match(value) {
+    if !LANGUAGE_REGEX.is_match(value) {
         return Err(Error::Validation(ValidationError::new(
             "invalid language code format",
         )));
@@ -672,10 +689,7 @@ fn validate_qname(value: &str) -> Result<XsdValue> {
 
 fn validate_duration(value: &str) -> Result<XsdValue> {
     // ISO 8601 duration: P[n]Y[n]M[n]DT[n]H[n]M[n]S
-    let re = regex::Regex::new(
-        r"^-?P(\d+Y)?(\d+M)?(\d+D)?(T(\d+H)?(\d+M)?(\d+(\.\d+)?S)?)?$"
-    ).unwrap();
-    if !re.is_match(value) || value == "P" || value == "-P" {
+    if !DURATION_REGEX.is_match(value) || value == "P" || value == "-P" {
         return Err(Error::Validation(ValidationError::new(
             "invalid duration format",
         )));
@@ -685,10 +699,7 @@ fn validate_duration(value: &str) -> Result<XsdValue> {
 
 fn validate_datetime(value: &str) -> Result<XsdValue> {
     // Basic dateTime validation: YYYY-MM-DDThh:mm:ss[.sss][Z|(+|-)hh:mm]
-    let re = regex::Regex::new(
-        r"^-?\d{4,}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})?$"
-    ).unwrap();
-    if !re.is_match(value) {
+    if !DATETIME_REGEX.is_match(value) {
         return Err(Error::Validation(ValidationError::new(
             "invalid dateTime format",
         )));
@@ -698,10 +709,7 @@ fn validate_datetime(value: &str) -> Result<XsdValue> {
 
 fn validate_date(value: &str) -> Result<XsdValue> {
     // Basic date validation: YYYY-MM-DD[Z|(+|-)hh:mm]
-    let re = regex::Regex::new(
-        r"^-?\d{4,}-\d{2}-\d{2}(Z|[+-]\d{2}:\d{2})?$"
-    ).unwrap();
-    if !re.is_match(value) {
+    if !DATE_REGEX.is_match(value) {
         return Err(Error::Validation(ValidationError::new(
             "invalid date format",
         )));
@@ -711,10 +719,7 @@ fn validate_date(value: &str) -> Result<XsdValue> {
 
 fn validate_time(value: &str) -> Result<XsdValue> {
     // Basic time validation: hh:mm:ss[.sss][Z|(+|-)hh:mm]
-    let re = regex::Regex::new(
-        r"^\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})?$"
-    ).unwrap();
-    if !re.is_match(value) {
+    if !TIME_REGEX.is_match(value) {
         return Err(Error::Validation(ValidationError::new(
             "invalid time format",
         )));
@@ -723,8 +728,7 @@ fn validate_time(value: &str) -> Result<XsdValue> {
 }
 
 fn validate_gyear(value: &str) -> Result<XsdValue> {
-    let re = regex::Regex::new(r"^-?\d{4,}(Z|[+-]\d{2}:\d{2})?$").unwrap();
-    if !re.is_match(value) {
+    if !GYEAR_REGEX.is_match(value) {
         return Err(Error::Validation(ValidationError::new(
             "invalid gYear format",
         )));
@@ -733,8 +737,7 @@ fn validate_gyear(value: &str) -> Result<XsdValue> {
 }
 
 fn validate_gyear_month(value: &str) -> Result<XsdValue> {
-    let re = regex::Regex::new(r"^-?\d{4,}-\d{2}(Z|[+-]\d{2}:\d{2})?$").unwrap();
-    if !re.is_match(value) {
+    if !GYEAR_MONTH_REGEX.is_match(value) {
         return Err(Error::Validation(ValidationError::new(
             "invalid gYearMonth format",
         )));
@@ -743,8 +746,7 @@ fn validate_gyear_month(value: &str) -> Result<XsdValue> {
 }
 
 fn validate_gmonth(value: &str) -> Result<XsdValue> {
-    let re = regex::Regex::new(r"^--\d{2}(Z|[+-]\d{2}:\d{2})?$").unwrap();
-    if !re.is_match(value) {
+    if !GMONTH_REGEX.is_match(value) {
         return Err(Error::Validation(ValidationError::new(
             "invalid gMonth format",
         )));
@@ -753,8 +755,7 @@ fn validate_gmonth(value: &str) -> Result<XsdValue> {
 }
 
 fn validate_gday(value: &str) -> Result<XsdValue> {
-    let re = regex::Regex::new(r"^---\d{2}(Z|[+-]\d{2}:\d{2})?$").unwrap();
-    if !re.is_match(value) {
+    if !GDAY_REGEX.is_match(value) {
         return Err(Error::Validation(ValidationError::new(
             "invalid gDay format",
         )));
@@ -763,8 +764,7 @@ fn validate_gday(value: &str) -> Result<XsdValue> {
 }
 
 fn validate_gmonth_day(value: &str) -> Result<XsdValue> {
-    let re = regex::Regex::new(r"^--\d{2}-\d{2}(Z|[+-]\d{2}:\d{2})?$").unwrap();
-    if !re.is_match(value) {
+    if !GMONTH_DAY_REGEX.is_match(value) {
         return Err(Error::Validation(ValidationError::new(
             "invalid gMonthDay format",
         )));
@@ -1077,6 +1077,14 @@ lazy_static::lazy_static! {
             admitted_facets: &STRING_FACETS,
             validator: validate_qname,
         },
+        BuiltinType {
+            name: XSD_NOTATION,
+            category: TypeCategory::Primitive,
+            base_type: Some(XSD_ANY_SIMPLE_TYPE),
+            white_space: WhiteSpace::Collapse,
+            admitted_facets: &STRING_FACETS,
+            validator: validate_qname,
+        },
 
         // Date/time types
         BuiltinType {
@@ -1159,6 +1167,24 @@ pub fn get_builtin_type(name: &str) -> Option<&'static BuiltinType> {
     BUILTIN_TYPES.iter().find(|t| t.name == name)
 }
 
+lazy_static::lazy_static! {
+    /// Qualified names of the built-in types, by local name
+    static ref BUILTIN_TYPE_QNAMES: Vec<QName> = BUILTIN_TYPES
+        .iter()
+        .map(|t| QName::namespaced(XSD_NAMESPACE, t.name))
+        .collect();
+}
+
+/// Get the qualified name of a built-in type by local name
+pub fn builtin_type_qname(name: &str) -> Option<&'static QName> {
+    BUILTIN_TYPE_QNAMES.iter().find(|qname| qname.local_name == name)
+}
+
+/// Check whether a type name is in the XSD namespace
+pub(crate) fn is_builtin_qname(qname: &QName) -> bool {
+    qname.namespace.as_deref() == Some(XSD_NAMESPACE)
+}
+
 /// Validate a value against a built-in type by name
 pub fn validate_builtin(type_name: &str, value: &str) -> Result<XsdValue> {
     match get_builtin_type(type_name) {
@@ -1288,6 +1314,8 @@ mod tests {
         assert!(validate_builtin(XSD_QNAME, "localName").is_ok());
         assert!(validate_builtin(XSD_QNAME, "prefix:localName").is_ok());
         assert!(validate_builtin(XSD_QNAME, "").is_err());
+        assert!(validate_builtin(XSD_NOTATION, "m:jpeg").is_ok());
+        assert!(validate_builtin(XSD_NOTATION, "1jpeg").is_err());
     }
 
     #[test]
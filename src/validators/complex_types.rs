@@ -8,11 +8,17 @@
 
 use std::sync::Arc;
 
-use crate::error::ParseError;
+use crate::documents::Element;
+use crate::error::{Error, ParseError, Result};
 use crate::namespaces::QName;
-
-use super::attributes::XsdAttributeGroup;
-use super::groups::{ModelType, XsdGroup};
+use crate::xpath::{XPathContext, XPathExpression};
+
+use super::annotations::XsdAnnotation;
+use super::assertions::AssertionList;
+use super::attributes::{AttributeUse, XsdAttribute, XsdAttributeGroup};
+use super::elements::{ElementScope, XsdElement};
+use super::groups::{ElementParticle, GroupParticle, ModelType, XsdGroup};
+use super::particles::Occurs;
 use super::simple_types::SimpleType;
 use super::wildcards::XsdAnyElement;
 
@@ -159,6 +165,49 @@ impl Default for XsdOpenContent {
     }
 }
 
+/// Type alternative of an element declaration (XSD 1.1)
+///
+/// Represents an xs:alternative child of xs:element; the first alternative
+/// whose test is true on the element's attributes selects its type.
+#[derive(Debug, Clone)]
+pub struct XsdAlternative {
+    /// The XPath test expression (None for the default alternative)
+    pub test: Option<String>,
+    /// The type selected by this alternative
+    pub type_ref: QName,
+    /// The compiled test expression, if it is valid
+    expression: Option<XPathExpression>,
+}
+
+impl XsdAlternative {
+    /// Create a new type alternative, compiling its test expression
+    pub fn new(test: Option<String>, type_ref: QName) -> Self {
+        let expression = test.as_deref().and_then(|test| XPathExpression::parse(test).ok());
+        Self {
+            test,
+            type_ref,
+            expression,
+        }
+    }
+
+    /// Check if the test expression could not be compiled
+    pub fn is_invalid(&self) -> bool {
+        self.test.is_some() && self.expression.is_none()
+    }
+
+    /// Check if this alternative applies to an element
+    ///
+    /// An alternative without test always applies; a test that is invalid or
+    /// cannot be evaluated never does.
+    pub fn matches(&self, element: &Element, context: &XPathContext) -> bool {
+        match (&self.test, &self.expression) {
+            (None, _) => true,
+            (Some(_), Some(expression)) => expression.evaluate_boolean(element, context).unwrap_or(false),
+            (Some(_), None) => false,
+        }
+    }
+}
+
 /// Block/final derivation flags
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct DerivationFlags {
@@ -221,6 +270,10 @@ pub struct XsdComplexType {
     /// Whether this is a mixed content type
     pub mixed: bool,
 
+    /// Whether `mixed` was given explicitly rather than defaulted, so that
+    /// derivation only inherits the base type's `mixed` when it was not
+    pub(crate) mixed_explicit: bool,
+
     /// Whether this type is abstract
     pub abstract_type: bool,
 
@@ -233,9 +286,17 @@ pub struct XsdComplexType {
     /// Open content (XSD 1.1)
     pub open_content: Option<XsdOpenContent>,
 
+    /// Assertions, including the ones inherited from the base type (XSD 1.1)
+    pub assertions: AssertionList,
+
     /// Back-reference to original type when this is a redefinition (xs:redefine)
     pub redefine: Option<Arc<XsdComplexType>>,
 
+    /// Text of the xs:documentation annotations
+    pub documentation: Option<String>,
+    /// Annotation of the definition
+    pub annotation: Option<XsdAnnotation>,
+
     /// Parse errors
     errors: Vec<ParseError>,
 }
@@ -250,11 +311,15 @@ impl XsdComplexType {
             base_type: None,
             derivation: None,
             mixed: false,
+            mixed_explicit: false,
             abstract_type: false,
             block: DerivationFlags::default(),
             final_deriv: DerivationFlags::default(),
             open_content: None,
+            assertions: AssertionList::new(),
             redefine: None,
+            documentation: None,
+            annotation: None,
             errors: Vec::new(),
         }
     }
@@ -268,11 +333,15 @@ impl XsdComplexType {
             base_type: None,
             derivation: None,
             mixed: false,
+            mixed_explicit: false,
             abstract_type: false,
             block: DerivationFlags::default(),
             final_deriv: DerivationFlags::default(),
             open_content: None,
+            assertions: AssertionList::new(),
             redefine: None,
+            documentation: None,
+            annotation: None,
             errors: Vec::new(),
         }
     }
@@ -286,11 +355,15 @@ impl XsdComplexType {
             base_type: None,
             derivation: None,
             mixed: false,
+            mixed_explicit: false,
             abstract_type: false,
             block: DerivationFlags::default(),
             final_deriv: DerivationFlags::default(),
             open_content: None,
+            assertions: AssertionList::new(),
             redefine: None,
+            documentation: None,
+            annotation: None,
             errors: Vec::new(),
         }
     }
@@ -328,6 +401,16 @@ impl XsdComplexType {
         true
     }
 
+    /// Check if this type is abstract
+    pub fn is_abstract(&self) -> bool {
+        self.abstract_type
+    }
+
+    /// Check if this type allows mixed content
+    pub fn is_mixed(&self) -> bool {
+        self.mixed
+    }
+
     /// Check if content is empty
     pub fn is_empty(&self) -> bool {
         if let Some(ref open) = self.open_content {
@@ -408,6 +491,7 @@ impl XsdComplexType {
     /// Set mixed content mode
     pub fn set_mixed(&mut self, mixed: bool) {
         self.mixed = mixed;
+        self.mixed_explicit = true;
         if let ComplexContent::Group(ref mut group) = self.content {
             // Need to get mutable access - clone and modify
             let mut new_group = (**group).clone();
@@ -450,11 +534,12 @@ pub struct ComplexTypeBuilder {
     attributes: XsdAttributeGroup,
     base_type: Option<QName>,
     derivation: Option<DerivationMethod>,
-    mixed: bool,
+    mixed: Option<bool>,
     abstract_type: bool,
     block: DerivationFlags,
     final_deriv: DerivationFlags,
     open_content: Option<XsdOpenContent>,
+    sequence: Vec<GroupParticle>,
 }
 
 impl ComplexTypeBuilder {
@@ -466,11 +551,12 @@ impl ComplexTypeBuilder {
             attributes: XsdAttributeGroup::anonymous(),
             base_type: None,
             derivation: None,
-            mixed: false,
+            mixed: None,
             abstract_type: false,
             block: DerivationFlags::default(),
             final_deriv: DerivationFlags::default(),
             open_content: None,
+            sequence: Vec::new(),
         }
     }
 
@@ -480,6 +566,11 @@ impl ComplexTypeBuilder {
         self
     }
 
+    /// Set the type name (alias of [`ComplexTypeBuilder::name`])
+    pub fn set_name(self, name: QName) -> Self {
+        self.name(name)
+    }
+
     /// Set the content as a model group
     pub fn content_group(mut self, group: Arc<XsdGroup>) -> Self {
         self.content = Some(ComplexContent::Group(group));
@@ -501,7 +592,7 @@ impl ComplexTypeBuilder {
 
     /// Set mixed mode
     pub fn mixed(mut self, mixed: bool) -> Self {
-        self.mixed = mixed;
+        self.mixed = Some(mixed);
         self
     }
 
@@ -524,16 +615,75 @@ impl ComplexTypeBuilder {
     }
 
     /// Add an attribute
-    pub fn attribute(mut self, attr: Arc<super::attributes::XsdAttribute>) -> Self {
+    pub fn attribute(mut self, attr: Arc<XsdAttribute>) -> Self {
         let _ = self.attributes.add_attribute(attr);
         self
     }
 
+    /// Append a local element to the type's sequence content
+    ///
+    /// The element type is recorded by name only; it is resolved when the
+    /// type is built through [`XsdSchema::derive_extension`](super::XsdSchema::derive_extension).
+    pub fn add_sequence_element(mut self, name: QName, type_qname: QName, occurs: Occurs) -> Self {
+        let mut element = XsdElement::any_type(name.clone());
+        element.type_name = Some(type_qname);
+        element.occurs = occurs;
+        element.scope = ElementScope::Local;
+        self.sequence.push(GroupParticle::Element(Arc::new(
+            ElementParticle::with_decl(name, occurs, Arc::new(element)),
+        )));
+        self
+    }
+
+    /// Add an attribute declared by name and type reference
+    ///
+    /// Like [`ComplexTypeBuilder::add_sequence_element`], the attribute type
+    /// is resolved later against a schema.
+    pub fn add_attribute(mut self, name: QName, type_qname: QName, use_mode: AttributeUse) -> Self {
+        let mut attr = XsdAttribute::new(name);
+        attr.type_name = Some(type_qname);
+        attr.set_use(use_mode);
+        let _ = self.attributes.add_attribute(Arc::new(attr));
+        self
+    }
+
+    /// Build the complex type, checking that its content is consistent
+    ///
+    /// Returns an error if elements were added with
+    /// [`ComplexTypeBuilder::add_sequence_element`] to a type with simple
+    /// content, which [`ComplexTypeBuilder::build`] would leave out.
+    pub fn try_build(self) -> Result<XsdComplexType> {
+        if matches!(self.content, Some(ComplexContent::Simple(_))) && !self.sequence.is_empty() {
+            return Err(Error::Type(format!(
+                "complex type '{}' has simple content and cannot have sequence elements",
+                self.name.as_ref().map_or_else(|| "(anonymous)".to_string(), |name| name.to_string())
+            )));
+        }
+        Ok(self.build())
+    }
+
     /// Build the complex type
+    ///
+    /// Elements added with [`ComplexTypeBuilder::add_sequence_element`] form
+    /// a sequence that follows any explicitly set model group. They are left
+    /// out of types with simple content; see [`ComplexTypeBuilder::try_build`].
     pub fn build(self) -> XsdComplexType {
-        let content = self.content.unwrap_or_else(|| {
-            ComplexContent::Group(Arc::new(XsdGroup::new(ModelType::Sequence)))
-        });
+        let content = match (self.content, self.sequence.is_empty()) {
+            (Some(content), true) => content,
+            (None, true) => ComplexContent::Group(Arc::new(XsdGroup::new(ModelType::Sequence))),
+            (Some(ComplexContent::Group(group)), false) => {
+                let mut wrapper = XsdGroup::new(ModelType::Sequence);
+                wrapper.add_particle(GroupParticle::Group(group));
+                wrapper.particles.extend(self.sequence);
+                ComplexContent::Group(Arc::new(wrapper))
+            }
+            (Some(simple @ ComplexContent::Simple(_)), false) => simple,
+            (None, false) => {
+                let mut group = XsdGroup::new(ModelType::Sequence);
+                group.particles = self.sequence;
+                ComplexContent::Group(Arc::new(group))
+            }
+        };
 
         XsdComplexType {
             name: self.name,
@@ -541,12 +691,16 @@ impl ComplexTypeBuilder {
             attributes: self.attributes,
             base_type: self.base_type,
             derivation: self.derivation,
-            mixed: self.mixed,
+            mixed: self.mixed.unwrap_or(false),
+            mixed_explicit: self.mixed.is_some(),
             abstract_type: self.abstract_type,
             block: self.block,
             final_deriv: self.final_deriv,
             open_content: self.open_content,
+            assertions: AssertionList::new(),
             redefine: None,
+            documentation: None,
+            annotation: None,
             errors: Vec::new(),
         }
     }
@@ -608,6 +762,23 @@ mod tests {
         assert_eq!(ct.content_type_label(), ContentTypeLabel::Mixed);
     }
 
+    #[test]
+    fn test_complex_type_flag_accessors() {
+        let ct = XsdComplexType::empty(Some(QName::local("Base")));
+        assert!(!ct.is_abstract());
+        assert!(!ct.is_mixed());
+        assert!(ct.is_empty());
+
+        let mut group = XsdGroup::new(ModelType::Sequence);
+        group.add_element(QName::local("child"), Occurs::once());
+        let mut ct = XsdComplexType::new(Some(QName::local("Derived")), Arc::new(group));
+        ct.abstract_type = true;
+        ct.mixed = true;
+        assert!(ct.is_abstract());
+        assert!(ct.is_mixed());
+        assert!(!ct.is_empty());
+    }
+
     #[test]
     fn test_complex_type_with_simple_content() {
         let simple = XsdAtomicType::new(XSD_STRING).unwrap();
@@ -661,6 +832,18 @@ mod tests {
         assert!(!ct.abstract_type);
     }
 
+    #[test]
+    fn test_complex_type_builder_simple_content_with_elements() {
+        let string_type = Arc::new(XsdAtomicType::new(XSD_STRING).unwrap());
+        let result = ComplexTypeBuilder::new()
+            .name(QName::local("priceType"))
+            .content_simple(string_type)
+            .add_sequence_element(QName::local("currency"), QName::local("string"), Occurs::once())
+            .try_build();
+
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_base_type() {
         let group = Arc::new(XsdGroup::new(ModelType::Sequence));
@@ -0,0 +1,523 @@
+//! Decoding documents to JSON values, and encoding them back
+//!
+//! The shape of the JSON values is given by a converter, as in
+//! `xml2json`; the schema gives the types of the values. Text and
+//! attributes of boolean and numeric types are decoded to JSON booleans and
+//! numbers instead of strings, and the namespace of each encoded element is
+//! taken from its declaration.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::{Number, Value as JsonValue};
+
+use crate::converters::{create_converter, ContentItem, ConverterType, ElementData, JsonConverter};
+use crate::documents::{Document, Element};
+use crate::error::{Error, Result, ValidationError};
+use crate::namespaces::QName;
+
+use super::attributes::XsdAttribute;
+use super::builtins::{XsdValue, XSD_NAMESPACE};
+use super::complex_types::{ContentTypeLabel, XsdComplexType};
+use super::document_validation::find_child_declaration;
+use super::elements::XsdElement;
+use super::facets::WhiteSpace;
+use super::globals::GlobalType;
+use super::groups::{ElementParticle, GroupParticle, XsdGroup};
+use super::helpers::normalize_attribute_value;
+use super::schemas::{ValidationResult, XsdSchema, XML_NAMESPACE};
+use super::simple_types::SimpleType;
+
+impl XsdSchema {
+    /// Decode an XML document to a JSON value
+    ///
+    /// The document is validated first; an invalid document is an error.
+    /// Values of boolean and numeric types become JSON booleans and numbers,
+    /// whitespace between the children of element-only content is dropped
+    /// and missing attributes with a default value are added.
+    pub fn decode_to_value(&self, xml: &str, converter: ConverterType) -> Result<JsonValue> {
+        let doc = Document::from_string(xml)?;
+        check_valid(self.validate(&doc))?;
+        let root = doc
+            .root
+            .as_ref()
+            .ok_or_else(|| Error::Decode("Document has no root element".to_string()))?;
+
+        let decl = self.check_root_element(root).ok().cloned();
+        let converter = create_converter(converter);
+        Ok(self.decode_element(root, decl.as_ref(), converter.as_ref(), 0))
+    }
+
+    /// Encode a JSON value to an XML document with the given root element
+    ///
+    /// This is the reverse of [`decode_to_value`](Self::decode_to_value)
+    /// with the same converter. The encoded document is validated; an
+    /// invalid document is an error.
+    pub fn encode_from_value(&self, value: &JsonValue, root_element: &QName, converter: ConverterType) -> Result<String> {
+        let decl = self
+            .lookup_element(root_element)
+            .ok_or_else(|| Error::Key(format!("Unknown root element: {}", root_element.to_string())))?;
+
+        let mut prefixes = Prefixes::new(self);
+        let converter = create_converter(converter);
+        let mut root = self.encode_element(
+            value,
+            root_element.clone(),
+            Some(decl),
+            converter.as_ref(),
+            0,
+            &mut prefixes,
+        );
+        for (uri, prefix) in prefixes.declared {
+            root.namespaces.add_prefix(prefix, uri);
+        }
+
+        let mut doc = Document::new();
+        doc.root = Some(root);
+        let xml = doc.to_xml_string(false);
+        check_valid(self.validate_string(&xml))?;
+        Ok(xml)
+    }
+
+    fn decode_element(
+        &self,
+        elem: &Element,
+        decl: Option<&Arc<XsdElement>>,
+        converter: &dyn JsonConverter,
+        level: usize,
+    ) -> JsonValue {
+        let (simple_type, complex_type) = match decl.and_then(|decl| decl.effective_type(self)) {
+            Some(GlobalType::Simple(simple_type)) => (Some(simple_type), None),
+            Some(GlobalType::Complex(complex_type)) => (complex_type.simple_type().cloned(), Some(complex_type)),
+            None => (None, None),
+        };
+
+        let tag = match &elem.prefix {
+            Some(prefix) => format!("{}:{}", prefix, elem.local_name()),
+            None => elem.local_name().to_string(),
+        };
+        let mut data = ElementData::new(tag);
+        if let Some(uri) = elem.namespaces.get_default_namespace() {
+            data = data.with_xmlns("", uri);
+        }
+        for (prefix, uri) in elem.namespaces.iter() {
+            data = data.with_xmlns(prefix, uri);
+        }
+
+        // Typed values are decoded as placeholders, replaced once the converter is done
+        let mut typed = Vec::new();
+        let mut typed_text = |text: &str, simple_type: Option<&Arc<dyn SimpleType + Send + Sync>>| {
+            match simple_type.and_then(|simple_type| typed_value(simple_type.as_ref(), text)) {
+                Some(value) => {
+                    let placeholder = format!("\u{0}{}", typed.len());
+                    typed.push((placeholder.clone(), value));
+                    placeholder
+                }
+                None => text.to_string(),
+            }
+        };
+
+        let element_only = complex_type.as_ref().is_some_and(|complex_type| {
+            matches!(
+                complex_type.content_type_label(),
+                ContentTypeLabel::ElementOnly | ContentTypeLabel::Empty
+            )
+        });
+        if let Some(text) = &elem.text {
+            if !(element_only && text.trim().is_empty()) {
+                data.text = Some(typed_text(text, simple_type.as_ref()));
+            }
+        }
+
+        for (qname, value) in &elem.attributes {
+            let name = match elem.attribute_prefixes.get(qname) {
+                Some(prefix) => format!("{}:{}", prefix, qname.local_name),
+                None => qname.local_name.clone(),
+            };
+            let attribute = complex_type.as_deref().and_then(|ct| attribute_declaration(ct, qname));
+            let attr_type = attribute.and_then(|attribute| attribute.effective_type(self));
+            let white_space = attr_type.map_or(WhiteSpace::Preserve, |attr_type| attr_type.white_space());
+            let value = normalize_attribute_value(value, white_space);
+            data.attributes.insert(name, typed_text(&value, attr_type));
+        }
+        if let Some(complex_type) = &complex_type {
+            for attribute in complex_type.attributes.iter_attributes() {
+                let name = &attribute.name().local_name;
+                if elem.get_attribute(name).is_some() || attribute.is_prohibited() {
+                    continue;
+                }
+                if let Some(default) = attribute.value_constraint() {
+                    let value = typed_text(default, attribute.effective_type(self));
+                    data.attributes.insert(name.clone(), value);
+                }
+            }
+        }
+
+        for child in &elem.children {
+            let child_decl = find_child_declaration(self, child, complex_type.as_deref());
+            let value = self.decode_element(child, child_decl.as_ref(), converter, level + 1);
+            let tag = match &child.prefix {
+                Some(prefix) => format!("{}:{}", prefix, child.local_name()),
+                None => child.local_name().to_string(),
+            };
+            data = data.with_child(tag, value);
+        }
+
+        let mut value = converter.decode(&data, level);
+        if !typed.is_empty() {
+            replace_placeholders(&mut value, &typed);
+        }
+        value
+    }
+
+    fn encode_element(
+        &self,
+        value: &JsonValue,
+        name: QName,
+        decl: Option<&Arc<XsdElement>>,
+        converter: &dyn JsonConverter,
+        level: usize,
+        prefixes: &mut Prefixes,
+    ) -> Element {
+        let data = converter.encode(value, &name.local_name, level);
+        let complex_type = match decl.and_then(|decl| decl.effective_type(self)) {
+            Some(GlobalType::Complex(complex_type)) => Some(complex_type),
+            _ => None,
+        };
+
+        // Namespace declarations come first, for the prefixes of the names
+        let mut attributes: Vec<(&String, &String)> = data.attributes.iter().collect();
+        attributes.sort();
+        for (attr_name, uri) in &attributes {
+            if let Some(prefix) = attr_name.strip_prefix("xmlns:") {
+                prefixes.prefer(prefix, uri);
+            }
+        }
+
+        let mut elem = Element::new(name);
+        elem.prefix = elem.qname.namespace.clone().map(|uri| prefixes.prefix(&uri));
+        elem.text = data.text.clone();
+        for (attr_name, attr_value) in attributes {
+            if *attr_name == "xmlns" || attr_name.starts_with("xmlns:") {
+                continue;
+            }
+            let qname = match attr_name.split_once(':') {
+                Some((prefix, local_name)) => QName::new(prefixes.namespace(prefix), local_name),
+                None => QName::local(attr_name.as_str()),
+            };
+            if let Some(uri) = &qname.namespace {
+                elem.attribute_prefixes.insert(qname.clone(), prefixes.prefix(uri));
+            }
+            elem.attributes.insert(qname, attr_value.clone());
+        }
+
+        // JSON objects don't keep the order of the children: use the content model's
+        let mut content: Vec<&ContentItem> = data.content.iter().collect();
+        if let Some(group) = complex_type.as_deref().and_then(XsdComplexType::model_group) {
+            let mut names = Vec::new();
+            particle_names(group, &mut names);
+            content.sort_by_key(|item| match item {
+                ContentItem::Element(child_name, _) => {
+                    let local_name = child_name.rsplit(':').next().unwrap_or(child_name);
+                    names.iter().position(|name| *name == local_name).unwrap_or(names.len())
+                }
+                ContentItem::CData(..) => names.len(),
+            });
+        }
+
+        for item in content {
+            match item {
+                ContentItem::Element(child_name, child_value) => {
+                    let (qname, child_decl) =
+                        self.child_element(child_name, complex_type.as_deref(), elem.namespace(), prefixes);
+                    let child = self.encode_element(
+                        child_value,
+                        qname,
+                        child_decl.as_ref(),
+                        converter,
+                        level + 1,
+                        prefixes,
+                    );
+                    elem.children.push(child);
+                }
+                ContentItem::CData(_, text) => elem.text.get_or_insert_with(String::new).push_str(text),
+            }
+        }
+        elem
+    }
+
+    /// The name and declaration of a child element named in a JSON value
+    ///
+    /// An unprefixed name is looked up in the content model of the parent,
+    /// which tells whether it is qualified, then among the global elements.
+    fn child_element(
+        &self,
+        name: &str,
+        parent_type: Option<&XsdComplexType>,
+        parent_namespace: Option<&str>,
+        prefixes: &Prefixes,
+    ) -> (QName, Option<Arc<XsdElement>>) {
+        if let Some((prefix, local_name)) = name.split_once(':') {
+            let qname = QName::new(prefixes.namespace(prefix), local_name);
+            let decl = self.lookup_element(&qname).cloned();
+            return (qname, decl);
+        }
+        let particle = parent_type
+            .and_then(XsdComplexType::model_group)
+            .and_then(|group| find_particle(group, name));
+        if let Some(particle) = particle {
+            let decl = particle
+                .element()
+                .cloned()
+                .or_else(|| self.lookup_element(&particle.name).cloned());
+            return (particle.name.clone(), decl);
+        }
+        let qname = QName::new(parent_namespace.or(self.target_namespace.as_deref()), name);
+        let decl = self.lookup_element(&qname).cloned();
+        (qname, decl)
+    }
+}
+
+/// Turn an invalid validation result into an error
+fn check_valid(result: ValidationResult) -> Result<()> {
+    if result.valid {
+        return Ok(());
+    }
+    Err(Error::Validation(
+        ValidationError::new(result.errors.first().map(|e| e.message.clone()).unwrap_or_default())
+            .with_reason(format!("{} validation error(s)", result.errors.len())),
+    ))
+}
+
+/// The JSON value of a boolean or numeric value, `None` for other types
+pub(crate) fn typed_value(simple_type: &dyn SimpleType, text: &str) -> Option<JsonValue> {
+    match simple_type.validate_value(text).ok()? {
+        XsdValue::Boolean(value) => Some(JsonValue::Bool(value)),
+        XsdValue::Integer(value) => Some(JsonValue::Number(value.into())),
+        XsdValue::Float(value) | XsdValue::Double(value) => Number::from_f64(value).map(JsonValue::Number),
+        XsdValue::Decimal(value) => value.to_string().parse().ok().map(JsonValue::Number),
+        _ => None,
+    }
+}
+
+fn replace_placeholders(value: &mut JsonValue, typed: &[(String, JsonValue)]) {
+    match value {
+        JsonValue::String(text) => {
+            if let Some((_, typed_value)) = typed.iter().find(|(placeholder, _)| placeholder == text) {
+                *value = typed_value.clone();
+            }
+        }
+        JsonValue::Array(items) => items.iter_mut().for_each(|item| replace_placeholders(item, typed)),
+        JsonValue::Object(map) => map.values_mut().for_each(|item| replace_placeholders(item, typed)),
+        _ => {}
+    }
+}
+
+/// The declaration of an attribute of a complex type, by local name
+fn attribute_declaration<'a>(complex_type: &'a XsdComplexType, qname: &QName) -> Option<&'a Arc<XsdAttribute>> {
+    complex_type
+        .attributes
+        .iter_attributes()
+        .find(|attribute| attribute.name().local_name == qname.local_name)
+}
+
+/// The element particle of a model group with a local name, searching nested groups
+fn find_particle<'a>(group: &'a XsdGroup, local_name: &str) -> Option<&'a ElementParticle> {
+    group.particles.iter().find_map(|particle| match particle {
+        GroupParticle::Element(particle) if particle.name.local_name == local_name => Some(particle.as_ref()),
+        GroupParticle::Group(nested) => find_particle(nested, local_name),
+        _ => None,
+    })
+}
+
+/// The local names of the element particles of a model group, in definition order
+fn particle_names<'a>(group: &'a XsdGroup, names: &mut Vec<&'a str>) {
+    for particle in &group.particles {
+        match particle {
+            GroupParticle::Element(particle) => names.push(&particle.name.local_name),
+            GroupParticle::Group(nested) => particle_names(nested, names),
+            GroupParticle::Any(_) => {}
+        }
+    }
+}
+
+/// Prefixes of the namespaces of an encoded document
+///
+/// Every namespace is bound to a prefix declared on the document element,
+/// so that unqualified elements are never in a default namespace.
+struct Prefixes {
+    /// Prefixes by namespace, from the JSON value and the schema
+    preferred: HashMap<String, String>,
+    /// Namespaces by prefix, from the JSON value and the schema
+    namespaces: HashMap<String, String>,
+    /// Prefixes used in the document, by namespace
+    declared: Vec<(String, String)>,
+}
+
+impl Prefixes {
+    fn new(schema: &XsdSchema) -> Self {
+        let mut prefixes = Self {
+            preferred: HashMap::new(),
+            namespaces: HashMap::new(),
+            declared: Vec::new(),
+        };
+        let mut declarations: Vec<_> = schema.source.namespaces.iter().collect();
+        declarations.sort();
+        for (prefix, uri) in declarations {
+            if uri != XSD_NAMESPACE {
+                prefixes.prefer(prefix, uri);
+            }
+        }
+        prefixes
+    }
+
+    /// Bind a prefix to a namespace, taking precedence over the earlier bindings
+    fn prefer(&mut self, prefix: &str, uri: &str) {
+        if prefix.is_empty() || prefix == "xml" {
+            return;
+        }
+        self.preferred.insert(uri.to_string(), prefix.to_string());
+        self.namespaces.insert(prefix.to_string(), uri.to_string());
+    }
+
+    /// The namespace bound to a prefix
+    fn namespace(&self, prefix: &str) -> Option<String> {
+        match prefix {
+            "xml" => Some(XML_NAMESPACE.to_string()),
+            _ => self.namespaces.get(prefix).cloned(),
+        }
+    }
+
+    /// The prefix of a namespace, declaring it on first use
+    fn prefix(&mut self, uri: &str) -> String {
+        if uri == XML_NAMESPACE {
+            return "xml".to_string();
+        }
+        if let Some((_, prefix)) = self.declared.iter().find(|(declared, _)| declared == uri) {
+            return prefix.clone();
+        }
+        let prefix = match self.preferred.get(uri) {
+            Some(prefix) if !self.declared.iter().any(|(_, declared)| declared == prefix) => prefix.clone(),
+            _ => (0..)
+                .map(|n| format!("ns{}", n))
+                .find(|prefix| !self.namespaces.contains_key(prefix))
+                .unwrap_or_default(),
+        };
+        self.declared.push((uri.to_string(), prefix.clone()));
+        prefix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BOOK_NS: &str = "http://example.com/book";
+
+    fn book_schema() -> XsdSchema {
+        XsdSchema::from_string(include_str!("../../examples/data/book.xsd")).unwrap()
+    }
+
+    #[test]
+    fn test_decode_book() {
+        let schema = book_schema();
+        let xml = include_str!("../../tests/fixtures/book.xml").replace("isbn=", r#"edition="2" isbn="#);
+        let value = schema.decode_to_value(&xml, ConverterType::Default).unwrap();
+
+        assert_eq!(value["@isbn"], json!("978-0-13-468599-1"));
+        assert_eq!(value["@edition"], json!(2));
+        assert_eq!(value["pages"], json!(552));
+        assert_eq!(value["published"], json!("2019-08-12"));
+        assert_eq!(value["author"][0]["firstName"], json!("Steve"));
+        assert_eq!(value["author"][1]["lastName"], json!("Nichols"));
+        assert!(value.get("$").is_none());
+
+        // A missing attribute with a default value is added
+        let value = schema
+            .decode_to_value(include_str!("../../tests/fixtures/book.xml"), ConverterType::Default)
+            .unwrap();
+        assert_eq!(value["@edition"], json!(1));
+    }
+
+    #[test]
+    fn test_decode_typed_values() {
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="settings">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="enabled" type="xs:boolean"/>
+        <xs:element name="retries" type="xs:integer"/>
+        <xs:element name="ratio" type="xs:double"/>
+        <xs:element name="label" type="xs:string"/>
+      </xs:sequence>
+      <xs:attribute name="strict" type="xs:boolean"/>
+      <xs:attribute name="code" type="xs:token"/>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let xml = r#"<settings strict="1"><enabled>true</enabled><retries>3</retries><ratio>0.5</ratio><label>42</label></settings>"#;
+
+        let value = schema.decode_to_value(xml, ConverterType::Default).unwrap();
+        assert_eq!(
+            value,
+            json!({"@strict": true, "enabled": true, "retries": 3, "ratio": 0.5, "label": "42"})
+        );
+
+        // Attribute values are stored normalized
+        let xml_with_code = xml.replace("strict=", r#"code="  a  b  " strict="#);
+        let value = schema.decode_to_value(&xml_with_code, ConverterType::Default).unwrap();
+        assert_eq!(value["@code"], json!("a b"));
+
+        let value = schema.decode_to_value(xml, ConverterType::Parker).unwrap();
+        assert_eq!(value, json!({"enabled": true, "retries": 3, "ratio": 0.5, "label": "42"}));
+
+        let xml = schema.encode_from_value(
+            &json!({"enabled": false, "retries": 7, "ratio": 1.5, "label": "x"}),
+            &QName::local("settings"),
+            ConverterType::Default,
+        );
+        assert_eq!(
+            xml.unwrap(),
+            "<settings><enabled>false</enabled><retries>7</retries><ratio>1.5</ratio><label>x</label></settings>"
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_document() {
+        let schema = book_schema();
+        let xml = include_str!("../../tests/fixtures/book_invalid.xml");
+        assert!(schema.decode_to_value(xml, ConverterType::Default).is_err());
+    }
+
+    #[test]
+    fn test_encode_book() {
+        let schema = book_schema();
+        let book = QName::namespaced(BOOK_NS, "book");
+        let value = schema
+            .decode_to_value(include_str!("../../tests/fixtures/book.xml"), ConverterType::Default)
+            .unwrap();
+
+        let xml = schema.encode_from_value(&value, &book, ConverterType::Default).unwrap();
+        assert!(xml.starts_with(r#"<book:book xmlns:book="http://example.com/book" edition="1" isbn="#));
+        assert!(xml.contains("<book:pages>552</book:pages>"));
+
+        // The names of the encoded elements are prefixed
+        let decoded = schema.decode_to_value(&xml, ConverterType::Default).unwrap();
+        assert_eq!(decoded["@xmlns:book"], json!(BOOK_NS));
+        assert_eq!(decoded["@edition"], json!(1));
+        assert_eq!(decoded["book:pages"], json!(552));
+        assert_eq!(decoded["book:author"][1]["book:lastName"], json!("Nichols"));
+
+        // The encoded document must be valid
+        let mut invalid = value.clone();
+        invalid["pages"] = json!(-1);
+        assert!(schema.encode_from_value(&invalid, &book, ConverterType::Default).is_err());
+        let unknown = QName::namespaced(BOOK_NS, "magazine");
+        assert!(matches!(
+            schema.encode_from_value(&value, &unknown, ConverterType::Default),
+            Err(Error::Key(_))
+        ));
+    }
+}
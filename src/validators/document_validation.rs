@@ -4,19 +4,27 @@
 //! It provides the core logic for validating XML documents, elements,
 //! attributes, and content models.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::documents::{Document, Element};
 use crate::error::Result;
 use crate::namespaces::QName;
+use crate::xpath::{XPathContext, XPathResult};
 
 use super::base::AttributeValidator;
-use super::complex_types::{ComplexContent, ContentTypeLabel, XsdComplexType};
+use super::builtins::{XSD_ID, XSD_IDREF, XSD_IDREFS, XSD_NOTATION};
+use super::complex_types::{ComplexContent, ContentTypeLabel, DerivationFlags, DerivationMethod, XsdComplexType};
 use super::elements::{ElementType, XsdElement};
-use super::groups::GroupParticle;
+use super::exceptions::ValidationError;
+use super::facets::WhiteSpace;
+use super::globals::GlobalType;
+use super::groups::{GroupParticle, ModelType, XsdGroup};
+use super::helpers::normalize_attribute_value;
+use super::identities::{FieldTuple, FieldValue, IdentityCounter, XsdIdentity};
 use super::models::ModelVisitor;
 use super::schemas::{XsdSchema, XSI_NAMESPACE};
-use super::simple_types::SimpleType;
+use super::simple_types::{list_item_type, SimpleType, SimpleTypeVariety};
 use super::validation::ValidationContext;
 
 /// Validate an XML document against the schema
@@ -32,23 +40,23 @@ pub fn validate_document(
         }
     };
 
-    // Find the root element declaration
-    let root_qname = resolve_element_qname(root, schema);
-
-    let element_decl = match schema.lookup_element(&root_qname) {
-        Some(decl) => Arc::clone(decl),
-        None => {
-            return context.validation_error(
-                format!("Unknown root element: {}", root.local_name()),
-                Some(format!("No global element declaration found for '{}:{}'",
-                    root_qname.namespace.as_deref().unwrap_or(""),
-                    root_qname.local_name)),
-            );
+    // Check the root element against the global declarations first
+    let element_decl = match schema.check_document_element(doc) {
+        Ok(decl) => Arc::clone(decl),
+        Err(error) => {
+            let mut error = ValidationError::from(error).with_error_code("cvc-elt.1.a");
+            error.element_tag = Some(root.local_name().to_string());
+            return context.report(error);
         }
     };
 
-    // Validate the root element
-    validate_element(schema, root, &element_decl, context)
+    // Validate the root element, then resolve the IDREFs against all the IDs
+    let outcome = validate_element(schema, root, &element_decl, context).and_then(|()| context.check_idrefs());
+    match outcome {
+        // Reaching the error limit ends validation with the errors collected
+        Err(_) if context.is_stopped() => Ok(()),
+        outcome => outcome,
+    }
 }
 
 /// Validate an XML element against its declaration
@@ -57,6 +65,60 @@ pub fn validate_element(
     elem: &Element,
     decl: &XsdElement,
     context: &mut ValidationContext,
+) -> Result<()> {
+    context.element_path.push(elem.local_name().to_string());
+    context.element_locations.push(elem.source_line.zip(elem.source_column));
+    let outer_namespaces = enter_namespace_scope(elem, context);
+    let result = validate_element_at_path(schema, elem, decl, context);
+    if let Some(namespaces) = outer_namespaces {
+        context.namespaces = namespaces;
+    }
+    context.element_locations.pop();
+    context.element_path.pop();
+    result
+}
+
+/// A local element declaration bound to the final definition of its named type
+///
+/// Local declarations may capture a named type before its derivation was
+/// resolved, so the type registered in the schema takes precedence.
+fn resolved_local_decl(schema: &XsdSchema, decl: &XsdElement) -> XsdElement {
+    let mut resolved = decl.clone();
+    if let ElementType::Complex(ct) = &decl.element_type {
+        if let Some(GlobalType::Complex(global)) = ct.name.as_ref().and_then(|name| schema.lookup_type(name)) {
+            if !Arc::ptr_eq(ct, global) {
+                resolved.element_type = ElementType::Complex(Arc::clone(global));
+            }
+        }
+    }
+    resolved
+}
+
+/// Bring the namespace declarations of an element into the context's scope
+///
+/// The default namespace is kept under the empty prefix. Returns the outer
+/// scope to restore, if the element declares any namespace.
+pub(crate) fn enter_namespace_scope(elem: &Element, context: &mut ValidationContext) -> Option<HashMap<String, String>> {
+    let default_namespace = elem.namespaces.get_default_namespace();
+    if default_namespace.is_none() && elem.namespaces.iter().next().is_none() {
+        return None;
+    }
+    let outer = context.namespaces.clone();
+    context
+        .namespaces
+        .extend(elem.namespaces.iter().map(|(prefix, uri)| (prefix.to_string(), uri.to_string())));
+    if let Some(uri) = default_namespace {
+        context.namespaces.insert(String::new(), uri.to_string());
+    }
+    Some(outer)
+}
+
+/// Validate an element whose name is the last step of the context path
+fn validate_element_at_path(
+    schema: &XsdSchema,
+    elem: &Element,
+    decl: &XsdElement,
+    context: &mut ValidationContext,
 ) -> Result<()> {
     context.current_element = Some(elem.local_name().to_string());
     context.enter_level();
@@ -67,48 +129,264 @@ pub fn validate_element(
         return Ok(()); // Skip validation at this depth
     }
 
-    // Check for xsi:nil
-    if let Some(nil_value) = get_xsi_attribute(elem, "nil") {
-        if nil_value == "true" {
-            if !decl.nillable {
-                context.validation_error(
-                    format!("Element '{}' is not nillable", elem.local_name()),
-                    Some("xsi:nil='true' used on non-nillable element".to_string()),
-                )?;
-            } else {
-                // Nilled element should be empty
-                if !elem.children.is_empty() || elem.text.is_some() {
-                    context.validation_error(
-                        format!("Nilled element '{}' must be empty", elem.local_name()),
-                        None,
-                    )?;
-                }
-                context.exit_level();
-                return Ok(());
-            }
-        }
+    // Content and attributes of a nilled element are not validated
+    if is_nilled(elem, decl, context)? {
+        check_nilled_content(elem, decl, context)?;
+        context.exit_level();
+        return Ok(());
     }
 
     // Validate based on element type
-    match &decl.element_type {
-        ElementType::Simple(simple_type) => {
-            validate_simple_element(elem, simple_type.as_ref(), decl, context)?;
+    match instance_type(schema, elem, decl, context)? {
+        Some(GlobalType::Simple(simple_type)) => {
+            validate_simple_element(schema, elem, simple_type.as_ref(), decl, context)?;
         }
-        ElementType::Complex(complex_type) => {
-            validate_complex_element(schema, elem, complex_type, decl, context)?;
+        Some(GlobalType::Complex(complex_type)) => {
+            validate_complex_element(schema, elem, &complex_type, decl, context)?;
         }
-        ElementType::Any => {
+        None => {
             // Any type allows any content - skip validation
         }
     }
 
+    if context.check_identities && !decl.identities.is_empty() {
+        validate_identities(elem, decl, context)?;
+    }
+
     context.exit_level();
     context.current_element = None;
     Ok(())
 }
 
+/// Check the `xsi:nil` attribute of an element
+///
+/// Returns whether the element is nilled. An invalid value, or a nilled
+/// element whose declaration isn't nillable, is reported and gives `false`.
+pub(crate) fn is_nilled(elem: &Element, decl: &XsdElement, context: &mut ValidationContext) -> Result<bool> {
+    let Some(nil_value) = get_xsi_attribute(elem, "nil", context) else {
+        return Ok(false);
+    };
+    let nilled = match nil_value.trim() {
+        "true" | "1" => true,
+        "false" | "0" => false,
+        _ => {
+            context.report(
+                ValidationError::new(format!(
+                    "Invalid xsi:nil value '{}' on element '{}'",
+                    nil_value,
+                    elem.local_name()
+                ))
+                .with_reason("xsi:nil must be a boolean")
+                .with_schema_component(decl.name.to_string())
+                .with_error_code("cvc-datatype-valid.1.2.1"),
+            )?;
+            false
+        }
+    };
+    if nilled && !decl.nillable {
+        context.report(
+            ValidationError::new(format!("Element '{}' is not nillable", elem.local_name()))
+                .with_reason("xsi:nil='true' used on non-nillable element")
+                .with_schema_component(decl.name.to_string())
+                .with_error_code("cvc-elt.3.1"),
+        )?;
+        return Ok(false);
+    }
+    Ok(nilled)
+}
+
+/// Check that a nilled element is empty and has no fixed value
+pub(crate) fn check_nilled_content(elem: &Element, decl: &XsdElement, context: &mut ValidationContext) -> Result<()> {
+    if !elem.children.is_empty() || elem.text.is_some() {
+        context.report(
+            ValidationError::new(format!("Nilled element '{}' must be empty", elem.local_name()))
+                .with_schema_component(decl.name.to_string())
+                .with_error_code("cvc-elt.3.2.1"),
+        )?;
+    }
+    if decl.fixed.is_some() {
+        context.report(
+            ValidationError::new(format!(
+                "Element '{}' has a fixed value and cannot be nilled",
+                elem.local_name()
+            ))
+            .with_schema_component(decl.name.to_string())
+            .with_error_code("cvc-elt.3.2.2"),
+        )?;
+    }
+    Ok(())
+}
+
+/// Get the type an element is validated against
+///
+/// That is the type named by the element's `xsi:type` attribute, if it is
+/// validly derived from the selected type, or else the selected type: the
+/// type of the first matching type alternative, or the declared type. An
+/// invalid `xsi:type` is reported and ignored. `None` stands for
+/// `xs:anyType`, and for an abstract element or type, which is reported
+/// instead.
+pub(crate) fn instance_type(
+    schema: &XsdSchema,
+    elem: &Element,
+    decl: &XsdElement,
+    context: &mut ValidationContext,
+) -> Result<Option<GlobalType>> {
+    // Only the members of the substitution group of an abstract element may appear
+    if decl.abstract_element {
+        context.report(
+            ValidationError::new(format!("Element '{}' is abstract", elem.local_name()))
+                .with_reason("a member of its substitution group is required")
+                .with_schema_component(decl.name.to_string())
+                .with_error_code("cvc-elt.2"),
+        )?;
+        return Ok(None);
+    }
+
+    let declared = match selected_alternative_type(schema, elem, decl, context) {
+        Some(selected) => Some(selected),
+        None => decl.effective_type(schema),
+    };
+    let mut instance = declared.clone();
+
+    if let Some(value) = get_xsi_attribute(elem, "type", context) {
+        let xsi_type = resolve_instance_qname(value.trim(), context)
+            .and_then(|qname| schema.lookup_type(&qname))
+            .cloned();
+        match (xsi_type, &declared) {
+            (None, _) => {
+                context.report(
+                    ValidationError::new(format!(
+                        "xsi:type '{}' of element '{}' does not name a type of the schema",
+                        value,
+                        elem.local_name()
+                    ))
+                    .with_schema_component(decl.name.to_string())
+                    .with_error_code("cvc-elt.4.2"),
+                )?;
+            }
+            (Some(xsi_type), Some(base)) => match check_type_substitution(schema, &xsi_type, base, decl.block) {
+                Ok(()) => instance = Some(xsi_type),
+                Err(reason) => {
+                    context.report(
+                        ValidationError::new(format!(
+                            "xsi:type '{}' is not a valid substitute for the type of element '{}'",
+                            value,
+                            elem.local_name()
+                        ))
+                        .with_reason(reason)
+                        .with_schema_component(decl.name.to_string())
+                        .with_error_code("cvc-elt.4.3"),
+                    )?;
+                }
+            },
+            // Any type derives from xs:anyType
+            (Some(xsi_type), None) => instance = Some(xsi_type),
+        }
+    }
+
+    if let Some(GlobalType::Complex(complex_type)) = &instance {
+        if complex_type.is_abstract() {
+            let component = complex_type
+                .name
+                .as_ref()
+                .map_or_else(|| decl.name.to_string(), QName::to_string);
+            context.report(
+                ValidationError::new(format!(
+                    "Element '{}' has abstract type '{}'",
+                    elem.local_name(),
+                    component
+                ))
+                .with_reason("an xsi:type naming a non-abstract derived type is required")
+                .with_schema_component(component)
+                .with_error_code("cvc-type.2"),
+            )?;
+            return Ok(None);
+        }
+    }
+
+    Ok(instance)
+}
+
+/// Get the type selected by the type alternatives of an element declaration
+///
+/// The tests are evaluated in order on the attributes of the element; the
+/// first alternative that matches and names a known type selects it.
+fn selected_alternative_type(
+    schema: &XsdSchema,
+    elem: &Element,
+    decl: &XsdElement,
+    context: &ValidationContext,
+) -> Option<GlobalType> {
+    if decl.alternatives.is_empty() {
+        return None;
+    }
+    // Tests only see the attributes of the element, not its content
+    let mut attributes_only = Element::new(elem.qname.clone());
+    attributes_only.attributes = elem.attributes.clone();
+    attributes_only.namespaces = elem.namespaces.clone();
+    let xpath_context = XPathContext::new().with_namespaces(context.namespaces.clone());
+
+    let alternative = decl
+        .alternatives
+        .iter()
+        .find(|alternative| alternative.matches(&attributes_only, &xpath_context))?;
+    schema.lookup_type(&alternative.type_ref).cloned()
+}
+
+/// Check that a type may be used in place of a base type through `xsi:type`
+///
+/// The type must be derived from the base type, and none of the derivation
+/// steps may be blocked by the element declaration or the base type.
+/// Returns the reason of the failure otherwise.
+fn check_type_substitution(
+    schema: &XsdSchema,
+    derived: &GlobalType,
+    base: &GlobalType,
+    block: DerivationFlags,
+) -> std::result::Result<(), String> {
+    let mut blocked = block;
+    if let GlobalType::Complex(base_type) = base {
+        blocked.restriction |= base_type.block.restriction;
+        blocked.extension |= base_type.block.extension;
+    }
+    let base_name = base.name().map_or_else(|| "anonymous type".to_string(), QName::to_string);
+
+    let mut methods = Vec::new();
+    let mut visited: Vec<QName> = Vec::new();
+    let mut current = derived.clone();
+    while !current.is_same(base) {
+        if let Some(name) = current.name() {
+            if visited.contains(name) {
+                return Err(format!("circular derivation of type '{}'", name.to_string()));
+            }
+            visited.push(name.clone());
+        }
+        let (next_name, method) = match &current {
+            GlobalType::Complex(complex_type) => (
+                complex_type.base_type.clone(),
+                complex_type.derivation.unwrap_or(DerivationMethod::Restriction),
+            ),
+            GlobalType::Simple(simple_type) => (
+                SimpleType::base_type(simple_type.as_ref()).and_then(|base_type| base_type.name()).cloned(),
+                DerivationMethod::Restriction,
+            ),
+        };
+        let Some(next) = next_name.and_then(|name| schema.lookup_type(&name).cloned()) else {
+            return Err(format!("the type is not derived from '{}'", base_name));
+        };
+        methods.push(method);
+        current = next;
+    }
+
+    match methods.into_iter().find(|method| blocked.is_blocked(*method)) {
+        Some(method) => Err(format!("derivation by {} from '{}' is blocked", method, base_name)),
+        None => Ok(()),
+    }
+}
+
 /// Validate an element with simple type content
-fn validate_simple_element(
+pub(crate) fn validate_simple_element(
+    schema: &XsdSchema,
     elem: &Element,
     simple_type: &(dyn SimpleType + Send + Sync),
     decl: &XsdElement,
@@ -116,9 +394,13 @@ fn validate_simple_element(
 ) -> Result<()> {
     // Simple elements should not have child elements
     if !elem.children.is_empty() {
-        context.validation_error(
-            format!("Element '{}' has simple type but contains child elements", elem.local_name()),
-            None,
+        context.report(
+            ValidationError::new(format!(
+                "Element '{}' has simple type but contains child elements",
+                elem.local_name()
+            ))
+            .with_schema_component(type_component(simple_type, decl))
+            .with_error_code("cvc-type.3.1.2"),
         )?;
     }
 
@@ -128,33 +410,109 @@ fn validate_simple_element(
     // Check for fixed value
     if let Some(ref fixed) = decl.fixed {
         if text != fixed {
-            context.validation_error(
-                format!(
+            context.report(
+                ValidationError::new(format!(
                     "Element '{}' has fixed value '{}' but contains '{}'",
                     elem.local_name(),
                     fixed,
                     text
-                ),
-                None,
+                ))
+                .with_schema_component(decl.name.to_string())
+                .with_error_code("cvc-elt.5.2.2.2.2"),
             )?;
         }
     }
 
     // Validate the text content against the simple type
     if let Err(e) = simple_type.validate_value(text) {
-        context.validation_error(
-            format!(
-                "Invalid value for element '{}': {}",
+        context.report(
+            ValidationError::new(format!(
+                "Invalid value for element '{}': {} (expected {})",
                 elem.local_name(),
-                text
-            ),
-            Some(e.to_string()),
+                text,
+                simple_type.facet_summary()
+            ))
+            .with_reason(e.to_string())
+            .with_schema_component(type_component(simple_type, decl))
+            .with_error_code("cvc-type.3.1.3"),
         )?;
+    } else {
+        validate_notation_value(schema, elem, simple_type, text, context)?;
+        let path = context.current_path();
+        track_id_values(simple_type, elem.local_name(), text, path, context)?;
+    }
+
+    Ok(())
+}
+
+/// Check that an xs:NOTATION value names a notation declared in the schema
+///
+/// The value is resolved with the namespace declarations of the element;
+/// since ancestor declarations are not kept on the element, a value whose
+/// prefix cannot be resolved is matched by local name in the target namespace.
+fn validate_notation_value(
+    schema: &XsdSchema,
+    elem: &Element,
+    simple_type: &dyn SimpleType,
+    value: &str,
+    context: &mut ValidationContext,
+) -> Result<()> {
+    if simple_type.primitive_name() != Some(XSD_NOTATION) {
+        return Ok(());
+    }
+
+    let local_name = value.rsplit(':').next().unwrap_or(value);
+    let declared = elem
+        .namespaces
+        .resolve(value)
+        .ok()
+        .and_then(|qname| schema.lookup_notation(&qname))
+        .is_some()
+        || schema.has_notation(local_name);
+    if !declared {
+        let mut error = ValidationError::new(format!("Notation '{}' is not declared in the schema", value))
+            .with_error_code("cvc-datatype-valid.1.2.1");
+        if let Some(name) = simple_type.name() {
+            error = error.with_schema_component(name.to_string());
+        }
+        context.report(error)?;
     }
 
     Ok(())
 }
 
+/// Register the xs:ID value or record the xs:IDREF values of a node
+///
+/// Types derived from xs:ID, xs:IDREF or xs:IDREFS, and lists of xs:IDREF
+/// items, are tracked; the IDREFs are resolved at the end of the document.
+fn track_id_values(
+    simple_type: &dyn SimpleType,
+    name: &str,
+    value: &str,
+    path: String,
+    context: &mut ValidationContext,
+) -> Result<()> {
+    let primitive = match simple_type.variety() {
+        SimpleTypeVariety::List => match list_item_type(simple_type).and_then(|item| item.primitive_name()) {
+            Some(XSD_IDREF) => Some(XSD_IDREFS),
+            _ => None,
+        },
+        _ => simple_type.primitive_name(),
+    };
+
+    match primitive {
+        Some(XSD_ID) => context.register_id_value(name, value.trim(), path)?,
+        Some(XSD_IDREF) => context.register_idref(name, value.trim(), path),
+        Some(XSD_IDREFS) => {
+            for idref in value.split_whitespace() {
+                context.register_idref(name, idref, path.clone());
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 /// Validate an element with complex type content
 fn validate_complex_element(
     schema: &XsdSchema,
@@ -163,41 +521,99 @@ fn validate_complex_element(
     decl: &XsdElement,
     context: &mut ValidationContext,
 ) -> Result<()> {
+    let component = complex_type
+        .name
+        .as_ref()
+        .map_or_else(|| decl.name.to_string(), QName::to_string);
+
     // Validate attributes
-    validate_attributes(elem, complex_type, context)?;
+    validate_attributes(schema, elem, complex_type, context)?;
 
     // Validate content based on content type
     match complex_type.content_type_label() {
         ContentTypeLabel::Empty => {
-            validate_empty_content(elem, context)?;
+            validate_empty_content(elem, &component, context)?;
         }
         ContentTypeLabel::Simple => {
             if let Some(simple_type) = complex_type.simple_type() {
-                validate_simple_content(elem, simple_type.as_ref(), decl, context)?;
+                validate_simple_content(schema, elem, simple_type.as_ref(), decl, &component, context)?;
             }
         }
         ContentTypeLabel::Mixed | ContentTypeLabel::ElementOnly => {
-            validate_element_content(schema, elem, complex_type, context)?;
+            validate_element_content(schema, elem, complex_type, &component, context)?;
         }
     }
 
+    if !complex_type.assertions.is_empty() {
+        validate_assertions(elem, complex_type, &component, context)?;
+    }
+
+    Ok(())
+}
+
+/// Evaluate the assertions of a complex type with the element as context node
+///
+/// For simple content the text of the element is bound to `$value`.
+/// Assertions whose test could not be compiled were reported with the schema
+/// and are skipped.
+fn validate_assertions(
+    elem: &Element,
+    complex_type: &XsdComplexType,
+    component: &str,
+    context: &mut ValidationContext,
+) -> Result<()> {
+    let mut xpath_context = XPathContext::new().with_namespaces(context.namespaces.clone());
+    if complex_type.has_simple_content() {
+        let value = elem.text.clone().unwrap_or_default();
+        xpath_context = xpath_context.with_variable("value", XPathResult::String(value));
+    }
+
+    for assertion in complex_type.assertions.iter().filter(|assertion| assertion.expression().is_some()) {
+        match assertion.evaluate_element(elem, &xpath_context) {
+            Ok(true) => {}
+            Ok(false) => context.report(
+                ValidationError::new(format!(
+                    "Assertion '{}' failed for element '{}'",
+                    assertion.test(),
+                    elem.local_name()
+                ))
+                .with_schema_component(component)
+                .with_error_code("cvc-assertion"),
+            )?,
+            Err(e) => context.report(
+                ValidationError::new(format!(
+                    "Cannot evaluate assertion '{}' for element '{}'",
+                    assertion.test(),
+                    elem.local_name()
+                ))
+                .with_reason(e.to_string())
+                .with_schema_component(component)
+                .with_error_code("cvc-assertion"),
+            )?,
+        }
+    }
     Ok(())
 }
 
 /// Validate empty content (no text, no children)
-fn validate_empty_content(elem: &Element, context: &mut ValidationContext) -> Result<()> {
+pub(crate) fn validate_empty_content(elem: &Element, component: &str, context: &mut ValidationContext) -> Result<()> {
     if !elem.children.is_empty() {
-        context.validation_error(
-            format!("Element '{}' should be empty but contains child elements", elem.local_name()),
-            None,
+        context.report(
+            ValidationError::new(format!(
+                "Element '{}' should be empty but contains child elements",
+                elem.local_name()
+            ))
+            .with_schema_component(component)
+            .with_error_code("cvc-complex-type.2.1"),
         )?;
     }
 
     if let Some(ref text) = elem.text {
         if !text.trim().is_empty() {
-            context.validation_error(
-                format!("Element '{}' should be empty but contains text", elem.local_name()),
-                None,
+            context.report(
+                ValidationError::new(format!("Element '{}' should be empty but contains text", elem.local_name()))
+                    .with_schema_component(component)
+                    .with_error_code("cvc-complex-type.2.1"),
             )?;
         }
     }
@@ -206,17 +622,23 @@ fn validate_empty_content(elem: &Element, context: &mut ValidationContext) -> Re
 }
 
 /// Validate simple content (text only, with possible attributes)
-fn validate_simple_content(
+pub(crate) fn validate_simple_content(
+    schema: &XsdSchema,
     elem: &Element,
     simple_type: &(dyn SimpleType + Send + Sync),
     decl: &XsdElement,
+    component: &str,
     context: &mut ValidationContext,
 ) -> Result<()> {
     // Should not have child elements
     if !elem.children.is_empty() {
-        context.validation_error(
-            format!("Element '{}' has simple content but contains child elements", elem.local_name()),
-            None,
+        context.report(
+            ValidationError::new(format!(
+                "Element '{}' has simple content but contains child elements",
+                elem.local_name()
+            ))
+            .with_schema_component(component)
+            .with_error_code("cvc-complex-type.2.2"),
         )?;
     }
 
@@ -225,28 +647,36 @@ fn validate_simple_content(
     // Check for fixed value
     if let Some(ref fixed) = decl.fixed {
         if text != fixed {
-            context.validation_error(
-                format!(
+            context.report(
+                ValidationError::new(format!(
                     "Element '{}' has fixed value '{}' but contains '{}'",
                     elem.local_name(),
                     fixed,
                     text
-                ),
-                None,
+                ))
+                .with_schema_component(decl.name.to_string())
+                .with_error_code("cvc-elt.5.2.2.2.2"),
             )?;
         }
     }
 
     // Validate the text content
     if let Err(e) = simple_type.validate_value(text) {
-        context.validation_error(
-            format!(
-                "Invalid simple content for element '{}': {}",
+        context.report(
+            ValidationError::new(format!(
+                "Invalid simple content for element '{}': {} (expected {})",
                 elem.local_name(),
-                text
-            ),
-            Some(e.to_string()),
+                text,
+                simple_type.facet_summary()
+            ))
+            .with_reason(e.to_string())
+            .with_schema_component(component)
+            .with_error_code("cvc-complex-type.2.2"),
         )?;
+    } else {
+        validate_notation_value(schema, elem, simple_type, text, context)?;
+        let path = context.current_path();
+        track_id_values(simple_type, elem.local_name(), text, path, context)?;
     }
 
     Ok(())
@@ -257,22 +687,12 @@ fn validate_element_content(
     schema: &XsdSchema,
     elem: &Element,
     complex_type: &Arc<XsdComplexType>,
+    component: &str,
     context: &mut ValidationContext,
 ) -> Result<()> {
     // Check for text content in element-only mode
     if complex_type.content_type_label() == ContentTypeLabel::ElementOnly {
-        if let Some(ref text) = elem.text {
-            if !text.trim().is_empty() {
-                context.validation_error(
-                    format!(
-                        "Element '{}' has element-only content but contains text: '{}'",
-                        elem.local_name(),
-                        text.trim()
-                    ),
-                    None,
-                )?;
-            }
-        }
+        check_element_only_text(elem, component, context)?;
     }
 
     // Get the model group
@@ -286,17 +706,12 @@ fn validate_element_content(
 
     // If group is empty and no children, that's valid
     if model_group.is_empty() {
-        if !elem.children.is_empty() {
-            context.validation_error(
-                format!(
-                    "Element '{}' should have no child elements but contains {}",
-                    elem.local_name(),
-                    elem.children.len()
-                ),
-                None,
-            )?;
-        }
-        return Ok(());
+        return check_no_children(elem, elem.children.len(), component, context);
+    }
+
+    // Children of an all group may appear in any order
+    if model_group.model == ModelType::All {
+        return validate_all_content(schema, elem, &model_group, component, context);
     }
 
     // Create model visitor and validate children
@@ -305,10 +720,21 @@ fn validate_element_content(
     for child in &elem.children {
         let child_name = child.local_name();
 
-        // Try to match the child element
-        let matched = visitor.match_element(child_name);
+        // Try to match the child element, or a head it substitutes for
+        let heads = schema.substitution_heads(&resolve_element_qname(child, schema));
+        let matched = visitor.match_element_with_heads(child_name, &heads);
 
         if matched.is_some() {
+            // A local declaration of the matched particle takes precedence
+            let local_decl = match visitor.current_element() {
+                Some(GroupParticle::Element(particle))
+                    if particle.element_ref.is_none() && particle.name.local_name == child_name =>
+                {
+                    particle.element().map(|decl| resolved_local_decl(schema, decl))
+                }
+                _ => None,
+            };
+
             // Advance the visitor (with match)
             visitor.advance(true);
 
@@ -316,26 +742,27 @@ fn validate_element_content(
             let child_qname = resolve_element_qname(child, schema);
 
             // Look up the element declaration
-            if let Some(child_decl) = schema.lookup_element(&child_qname) {
+            if let Some(child_decl) = local_decl {
+                validate_element(schema, child, &child_decl, context)?;
+            } else if let Some(child_decl) = schema.lookup_element(&child_qname) {
                 validate_element(schema, child, child_decl, context)?;
             } else {
                 // Try to find in the content model's particles
                 if let Some(child_decl) = find_element_in_visitor(&visitor, &child_qname) {
                     validate_element(schema, child, &child_decl, context)?;
+                } else {
+                    // Unknown elements are often allowed by wildcards - don't error here
+                    context.skipped.push(child_qname.to_string());
                 }
-                // Unknown elements are often allowed by wildcards - don't error here
             }
         } else {
-            // No match - check if it's allowed by wildcards or report error
-            let expected = visitor.expected();
-            context.validation_error(
-                format!(
-                    "Unexpected child element '{}' in '{}'",
-                    child_name,
-                    elem.local_name()
-                ),
-                Some(format!("Expected one of: {:?}", expected)),
-            )?;
+            // No match - report the elements tried, such as all the alternatives of a choice
+            let expected = match visitor.tried() {
+                [] => visitor.expected(),
+                tried => tried.to_vec(),
+            };
+            let error = unexpected_child_error(elem, child, &expected, component, context);
+            context.report(error)?;
 
             // Try to advance anyway for error recovery
             visitor.advance(false);
@@ -343,17 +770,68 @@ fn validate_element_content(
     }
 
     // Check that model is complete (all required elements present)
+    report_missing_elements(&mut visitor, elem, component, context)
+}
+
+/// Check that an element with element-only content has no text
+pub(crate) fn check_element_only_text(elem: &Element, component: &str, context: &mut ValidationContext) -> Result<()> {
+    if let Some(ref text) = elem.text {
+        if !text.trim().is_empty() {
+            context.report(
+                ValidationError::new(format!(
+                    "Element '{}' has element-only content but contains text: '{}'",
+                    elem.local_name(),
+                    text.trim()
+                ))
+                .with_schema_component(component)
+                .with_error_code("cvc-complex-type.2.3"),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Check that an element whose model group is empty has no children
+pub(crate) fn check_no_children(
+    elem: &Element,
+    children: usize,
+    component: &str,
+    context: &mut ValidationContext,
+) -> Result<()> {
+    if children > 0 {
+        context.report(
+            ValidationError::new(format!(
+                "Element '{}' should have no child elements but contains {}",
+                elem.local_name(),
+                children
+            ))
+            .with_schema_component(component)
+            .with_error_code("cvc-complex-type.2.4"),
+        )?;
+    }
+    Ok(())
+}
+
+/// Report the required elements of a content model not matched by the children
+pub(crate) fn report_missing_elements(
+    visitor: &mut ModelVisitor,
+    elem: &Element,
+    component: &str,
+    context: &mut ValidationContext,
+) -> Result<()> {
     let remaining_errors = visitor.stop();
     for (particle, _count, expected) in remaining_errors {
         if let GroupParticle::Element(missing_elem) = particle {
             if missing_elem.occurs.min > 0 {
-                context.validation_error(
-                    format!(
+                context.report(
+                    ValidationError::new(format!(
                         "Missing required element '{}' in '{}'",
                         missing_elem.name.local_name,
                         elem.local_name()
-                    ),
-                    Some(format!("Expected: {:?}", expected)),
+                    ))
+                    .with_reason(format!("Expected: {:?}", expected))
+                    .with_schema_component(component)
+                    .with_error_code("cvc-complex-type.2.4"),
                 )?;
             }
         }
@@ -362,8 +840,159 @@ fn validate_element_content(
     Ok(())
 }
 
+/// Validate the children of an element against an xs:all group
+///
+/// Each particle of the group may match at most its maxOccurs children, in
+/// any order; required particles not matched by any child are reported once
+/// all the children have been seen. An optional group may match no children.
+fn validate_all_content(
+    schema: &XsdSchema,
+    elem: &Element,
+    group: &XsdGroup,
+    component: &str,
+    context: &mut ValidationContext,
+) -> Result<()> {
+    let mut counts = vec![0u32; group.particles.len()];
+
+    for child in &elem.children {
+        let Some(index) = match_all_particle(schema, group, &mut counts, elem, child, component, context)? else {
+            continue;
+        };
+        if let GroupParticle::Element(particle) = &group.particles[index] {
+            let child_qname = resolve_element_qname(child, schema);
+            // A member of a substitution group has its own declaration
+            let particle_decl = particle.element().filter(|_| particle.name == child_qname);
+            if let Some(child_decl) = particle_decl.or_else(|| schema.lookup_element(&child_qname)) {
+                validate_element(schema, child, child_decl, context)?;
+            } else {
+                context.skipped.push(child_qname.to_string());
+            }
+        } else if let GroupParticle::Any(_) = &group.particles[index] {
+            context.skipped.push(resolve_element_qname(child, schema).to_string());
+        }
+    }
+
+    check_all_complete(group, &counts, !elem.children.is_empty(), elem, component, context)
+}
+
+/// Match a child element with a particle of an xs:all group
+///
+/// Returns the index of the matched particle, whose count is incremented.
+/// A child matching no particle, or a particle already matched maxOccurs
+/// times, is reported and gives `None`.
+pub(crate) fn match_all_particle(
+    schema: &XsdSchema,
+    group: &XsdGroup,
+    counts: &mut [u32],
+    elem: &Element,
+    child: &Element,
+    component: &str,
+    context: &mut ValidationContext,
+) -> Result<Option<usize>> {
+    let child_name = child.local_name();
+    let child_qname = resolve_element_qname(child, schema);
+    let heads = schema.substitution_heads(&child_qname);
+    let matched = group.particles.iter().position(|particle| match particle {
+        GroupParticle::Element(particle) => particle.matches_qname(&child_qname, &heads),
+        GroupParticle::Any(any) => any.matches_tag(child_name),
+        GroupParticle::Group(_) => false,
+    });
+
+    let Some(index) = matched else {
+        let expected: Vec<QName> = group
+            .particles
+            .iter()
+            .filter_map(|particle| match particle {
+                GroupParticle::Element(particle) => Some(particle.name.clone()),
+                _ => None,
+            })
+            .collect();
+        let error = unexpected_child_error(elem, child, &expected, component, context);
+        context.report(error)?;
+        return Ok(None);
+    };
+
+    let occurs = group.particles[index].occurs();
+    if occurs.is_over(counts[index]) {
+        let mut error = ValidationError::new(format!(
+            "Element '{}' occurs more than allowed in the all group of '{}'",
+            child_name,
+            elem.local_name()
+        ))
+        .with_reason(format!("Maximum occurrences: {}", occurs.max.unwrap_or(0)))
+        .with_path(format!("{}/{}", context.current_path(), child_name))
+        .with_schema_component(component)
+        .with_error_code("cvc-complex-type.2.4");
+        if let (Some(line), Some(column)) = (child.source_line, child.source_column) {
+            error = error.with_source_line(line as usize).with_source_column(column as usize);
+        }
+        context.report(error)?;
+        return Ok(None);
+    }
+    counts[index] += 1;
+    Ok(Some(index))
+}
+
+/// Report the required particles of an xs:all group matched by no child
+pub(crate) fn check_all_complete(
+    group: &XsdGroup,
+    counts: &[u32],
+    has_children: bool,
+    elem: &Element,
+    component: &str,
+    context: &mut ValidationContext,
+) -> Result<()> {
+    // An empty optional group doesn't require any of its particles
+    if !has_children && group.occurs.min == 0 {
+        return Ok(());
+    }
+    for (particle, &count) in group.particles.iter().zip(counts) {
+        if let GroupParticle::Element(missing_elem) = particle {
+            if missing_elem.occurs.is_missing(count) {
+                context.report(
+                    ValidationError::new(format!(
+                        "Missing required element '{}' in '{}'",
+                        missing_elem.name.local_name,
+                        elem.local_name()
+                    ))
+                    .with_reason("Elements of an all group may appear in any order")
+                    .with_schema_component(component)
+                    .with_error_code("cvc-complex-type.2.4"),
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Build the error for a child element not allowed by the content model
+pub(crate) fn unexpected_child_error(
+    elem: &Element,
+    child: &Element,
+    expected: &[QName],
+    component: &str,
+    context: &ValidationContext,
+) -> ValidationError {
+    let child_name = child.local_name();
+    let mut error = ValidationError::new(format!(
+        "Unexpected child element '{}' in '{}'",
+        child_name,
+        elem.local_name()
+    ))
+    .with_reason(format!("Expected one of: {:?}", expected))
+    .with_path(format!("{}/{}", context.current_path(), child_name))
+    .with_schema_component(component)
+    .with_error_code("cvc-complex-type.2.4");
+    if let (Some(line), Some(column)) = (child.source_line, child.source_column) {
+        error = error.with_source_line(line as usize).with_source_column(column as usize);
+    }
+    error
+}
+
 /// Validate element attributes
-fn validate_attributes(
+pub(crate) fn validate_attributes(
+    schema: &XsdSchema,
     elem: &Element,
     complex_type: &Arc<XsdComplexType>,
     context: &mut ValidationContext,
@@ -384,49 +1013,72 @@ fn validate_attributes(
 
         // Check use mode
         if attr_decl.is_required() && value.is_none() {
-            context.validation_error(
-                format!(
+            context.report(
+                ValidationError::new(format!(
                     "Missing required attribute '{}' on element '{}'",
                     attr_name_str,
                     elem.local_name()
-                ),
-                None,
+                ))
+                .with_schema_component(attr_name.to_string())
+                .with_error_code("cvc-complex-type.4"),
             )?;
         }
 
+        let attr_path = format!("{}/@{}", context.current_path(), attr_name_str);
         if attr_decl.is_prohibited() && value.is_some() {
-            context.validation_error(
-                format!(
+            context.report(
+                ValidationError::new(format!(
                     "Prohibited attribute '{}' present on element '{}'",
                     attr_name_str,
                     elem.local_name()
-                ),
-                None,
+                ))
+                .with_path(attr_path.clone())
+                .with_schema_component(attr_name.to_string())
+                .with_error_code("cvc-complex-type.3.2.1"),
             )?;
         }
 
-        // Validate attribute value if present
+        // Validate attribute value if present, normalized according to the whiteSpace of its type
         if let Some(val) = value {
+            let simple_type = attr_decl.effective_type(schema);
+            let white_space = simple_type.map_or(WhiteSpace::Preserve, |simple_type| simple_type.white_space());
+            let val = normalize_attribute_value(val, white_space);
+            let val = val.as_ref();
+
             // Check for fixed value
             if let Some(fixed) = attr_decl.fixed_value() {
-                if val != fixed {
-                    context.validation_error(
-                        format!(
+                if val != normalize_attribute_value(fixed, white_space) {
+                    context.report(
+                        ValidationError::new(format!(
                             "Attribute '{}' has fixed value '{}' but contains '{}'",
                             attr_name_str, fixed, val
-                        ),
-                        None,
+                        ))
+                        .with_path(attr_path.clone())
+                        .with_schema_component(attr_name.to_string())
+                        .with_error_code("cvc-au"),
                     )?;
                 }
             }
 
             // Validate against type
-            if let Some(simple_type) = attr_decl.simple_type() {
+            if let Some(simple_type) = simple_type {
                 if let Err(e) = simple_type.validate_value(val) {
-                    context.validation_error(
-                        format!("Invalid value for attribute '{}': {}", attr_name_str, val),
-                        Some(e.to_string()),
+                    let component = simple_type.name().unwrap_or(attr_name).to_string();
+                    context.report(
+                        ValidationError::new(format!(
+                            "Invalid value for attribute '{}': {} (expected {})",
+                            attr_name_str,
+                            val,
+                            simple_type.facet_summary()
+                        ))
+                        .with_reason(e.to_string())
+                        .with_path(attr_path)
+                        .with_schema_component(component)
+                        .with_error_code("cvc-attribute.3"),
                     )?;
+                } else {
+                    validate_notation_value(schema, elem, simple_type.as_ref(), val, context)?;
+                    track_id_values(simple_type.as_ref(), attr_name_str, val, attr_path, context)?;
                 }
             }
         }
@@ -437,7 +1089,7 @@ fn validate_attributes(
         let attr_name = &attr_qname.local_name;
 
         // Skip xsi: namespace attributes
-        if attr_qname.namespace.as_deref() == Some(XSI_NAMESPACE) {
+        if is_xsi_attribute(elem, attr_qname, context) {
             continue;
         }
 
@@ -449,27 +1101,177 @@ fn validate_attributes(
         if !validated_attrs.contains(attr_name) {
             // For now, just warn about unknown attributes in strict mode
             // A full implementation would check anyAttribute wildcards
-            context.validation_error(
-                format!(
+            context.report(
+                ValidationError::new(format!(
                     "Unknown attribute '{}' on element '{}'",
                     attr_name,
                     elem.local_name()
-                ),
-                None,
+                ))
+                .with_path(format!("{}/@{}", context.current_path(), attr_name))
+                .with_error_code("cvc-complex-type.3.2.1"),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the identity constraints declared on an element within its scope
+///
+/// xs:unique and xs:key tuples are counted first, so that an xs:keyref can
+/// refer to a constraint of the same element. A referred constraint declared
+/// on another element is not in scope and is reported as unresolved.
+fn validate_identities(elem: &Element, decl: &XsdElement, context: &mut ValidationContext) -> Result<()> {
+    let mut counters: HashMap<QName, IdentityCounter> = HashMap::new();
+
+    for identity in decl.identities.iter().filter(|identity| !identity.is_keyref()) {
+        let mut counter = IdentityCounter::new(Arc::clone(identity));
+        for (selected, fields) in select_identity_tuples(identity, elem, context)? {
+            if fields.iter().any(FieldValue::is_null) {
+                if identity.is_key() {
+                    let message = format!(
+                        "Missing field value for key '{}' on element '{}'",
+                        identity.name.local_name,
+                        selected.local_name()
+                    );
+                    let error = identity_error(identity, selected, context, message)
+                        .with_error_code("cvc-identity-constraint.4.2.1");
+                    context.report(error)?;
+                }
+                continue;
+            }
+            if let Err(e) = counter.increase(fields) {
+                let (label, code) = if identity.is_key() {
+                    ("key", "cvc-identity-constraint.4.2.2")
+                } else {
+                    ("unique constraint", "cvc-identity-constraint.4.1")
+                };
+                let message = format!(
+                    "Duplicate value for {} '{}' on element '{}'",
+                    label,
+                    identity.name.local_name,
+                    selected.local_name()
+                );
+                let error = identity_error(identity, selected, context, message)
+                    .with_reason(e.to_string())
+                    .with_error_code(code);
+                context.report(error)?;
+            }
+        }
+        counters.insert(identity.name.clone(), counter);
+    }
+
+    for identity in decl.identities.iter().filter(|identity| identity.is_keyref()) {
+        let Some(refer) = identity.refer.as_ref() else {
+            continue;
+        };
+        let Some(refer_counter) = counters.get(refer) else {
+            context.report(
+                ValidationError::new(format!(
+                    "Keyref '{}' refers to '{}', which is not in scope",
+                    identity.name.local_name, refer.local_name
+                ))
+                .with_schema_component(identity.name.to_string())
+                .with_error_code("cvc-identity-constraint.4.3"),
             )?;
+            continue;
+        };
+        for (selected, fields) in select_identity_tuples(identity, elem, context)? {
+            if fields.iter().any(FieldValue::is_null) || refer_counter.contains(&fields) {
+                continue;
+            }
+            let message = format!(
+                "Keyref '{}' value {:?} on element '{}' not found in '{}'",
+                identity.name.local_name,
+                fields,
+                selected.local_name(),
+                refer.local_name
+            );
+            let error =
+                identity_error(identity, selected, context, message).with_error_code("cvc-identity-constraint.4.3");
+            context.report(error)?;
         }
     }
 
     Ok(())
 }
 
+/// Evaluate an identity constraint into the field tuples of the selected elements
+///
+/// Elements whose fields cannot be evaluated are reported and left out.
+fn select_identity_tuples<'a>(
+    identity: &XsdIdentity,
+    scope: &'a Element,
+    context: &mut ValidationContext,
+) -> Result<Vec<(&'a Element, FieldTuple)>> {
+    let rows = match identity.field_tuples(scope) {
+        Ok(rows) => rows,
+        Err(e) => {
+            context.report(
+                ValidationError::new(format!(
+                    "Cannot evaluate identity constraint '{}'",
+                    identity.name.local_name
+                ))
+                .with_reason(e.to_string())
+                .with_schema_component(identity.name.to_string())
+                .with_error_code("cvc-identity-constraint.3"),
+            )?;
+            return Ok(Vec::new());
+        }
+    };
+
+    let mut tuples = Vec::new();
+    for (selected, fields) in rows {
+        match fields {
+            Ok(fields) => tuples.push((selected, fields)),
+            Err(e) => {
+                let message = format!(
+                    "Invalid field value for '{}' on element '{}'",
+                    identity.name.local_name,
+                    selected.local_name()
+                );
+                let error = identity_error(identity, selected, context, message)
+                    .with_reason(e.to_string())
+                    .with_error_code("cvc-identity-constraint.3");
+                context.report(error)?;
+            }
+        }
+    }
+    Ok(tuples)
+}
+
+/// Error on an element selected by an identity constraint
+///
+/// The path is the one of the scope element followed by the selector.
+fn identity_error(
+    identity: &XsdIdentity,
+    selected: &Element,
+    context: &ValidationContext,
+    message: String,
+) -> ValidationError {
+    let mut error = ValidationError::new(message)
+        .with_path(format!("{}/{}", context.current_path(), identity.selector.xpath))
+        .with_schema_component(identity.name.to_string());
+    if let (Some(line), Some(column)) = (selected.source_line, selected.source_column) {
+        error = error.with_source_line(line as usize).with_source_column(column as usize);
+    }
+    error
+}
+
+/// Name of the type of a simple element, or of the element for anonymous types
+fn type_component(simple_type: &dyn SimpleType, decl: &XsdElement) -> String {
+    simple_type.name().unwrap_or(&decl.name).to_string()
+}
+
 /// Helper to resolve element QName from an XML element
-fn resolve_element_qname(elem: &Element, schema: &XsdSchema) -> QName {
+pub(crate) fn resolve_element_qname(elem: &Element, schema: &XsdSchema) -> QName {
     let local_name = elem.local_name();
 
     // Check for namespace on element
     if let Some(ns) = elem.namespace() {
         QName::namespaced(ns, local_name)
+    } else if let Some(ns) = elem.prefix.as_deref().and_then(|prefix| elem.namespaces.get_namespace(prefix)) {
+        QName::namespaced(ns, local_name)
     } else if let Some(default_ns) = elem.namespaces.get_default_namespace() {
         QName::namespaced(default_ns, local_name)
     } else if let Some(target_ns) = &schema.target_namespace {
@@ -480,19 +1282,41 @@ fn resolve_element_qname(elem: &Element, schema: &XsdSchema) -> QName {
 }
 
 /// Get xsi: attribute value
-fn get_xsi_attribute<'a>(elem: &'a Element, local_name: &str) -> Option<&'a str> {
-    for (qname, value) in &elem.attributes {
-        if qname.local_name == local_name {
-            if let Some(ref ns) = qname.namespace {
-                if ns == XSI_NAMESPACE {
-                    return Some(value);
-                }
-            }
-        }
+pub(crate) fn get_xsi_attribute<'a>(elem: &'a Element, local_name: &str, context: &ValidationContext) -> Option<&'a str> {
+    elem.attributes
+        .iter()
+        .find(|(qname, _)| qname.local_name == local_name && is_xsi_attribute(elem, qname, context))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Check whether an instance attribute is in the XSI namespace
+///
+/// Attributes are parsed without their namespace, so the prefix they were
+/// written with is resolved with the namespaces in scope. Unprefixed
+/// attributes are in no namespace.
+fn is_xsi_attribute(elem: &Element, name: &QName, context: &ValidationContext) -> bool {
+    match name.namespace.as_deref() {
+        Some(namespace) => namespace == XSI_NAMESPACE,
+        None => elem
+            .attribute_prefixes
+            .get(name)
+            .and_then(|prefix| context.namespaces.get(prefix))
+            .is_some_and(|namespace| namespace == XSI_NAMESPACE),
     }
+}
 
-    // Also check without namespace (common in documents)
-    elem.get_attribute(local_name)
+/// Resolve a QName value of an instance document with the namespaces in scope
+fn resolve_instance_qname(value: &str, context: &ValidationContext) -> Option<QName> {
+    match value.split_once(':') {
+        Some((prefix, local_name)) => context
+            .namespaces
+            .get(prefix)
+            .map(|namespace| QName::namespaced(namespace, local_name)),
+        None => Some(match context.namespaces.get("").filter(|namespace| !namespace.is_empty()) {
+            Some(namespace) => QName::namespaced(namespace, value),
+            None => QName::local(value),
+        }),
+    }
 }
 
 /// Find element declaration in the current content model
@@ -500,6 +1324,23 @@ fn find_element_in_visitor(visitor: &ModelVisitor, qname: &QName) -> Option<Arc<
     visitor.find_element_decl(qname)
 }
 
+/// Find the declaration of a child element, as element content validation does
+///
+/// Global element declarations take precedence over the local declarations
+/// of the parent type's content model.
+pub(crate) fn find_child_declaration(
+    schema: &XsdSchema,
+    child: &Element,
+    parent_type: Option<&XsdComplexType>,
+) -> Option<Arc<XsdElement>> {
+    let child_qname = resolve_element_qname(child, schema);
+    if let Some(decl) = schema.lookup_element(&child_qname) {
+        return Some(Arc::clone(decl));
+    }
+    let group = parent_type?.model_group()?;
+    ModelVisitor::new(Arc::clone(group)).find_element_decl(&child_qname)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -559,4 +1400,98 @@ mod tests {
 
         assert!(context.has_errors());
     }
+
+    #[test]
+    fn test_validate_max_errors() {
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="values">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="value" type="xs:int" maxOccurs="unbounded"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let xml = format!("<values>{}</values>", "<value>x</value>".repeat(100));
+        let doc = Document::from_string(&xml).unwrap();
+
+        let mut context = ValidationContext::new().with_mode(ValidationMode::Lax);
+        assert!(validate_document(&schema, &doc, &mut context).is_ok());
+        assert!(context.error_count() >= 100);
+        assert!(!context.is_stopped());
+
+        let mut context = ValidationContext::new().with_mode(ValidationMode::Lax).with_max_errors(5);
+        assert!(validate_document(&schema, &doc, &mut context).is_ok());
+        assert_eq!(context.error_count(), 5);
+        assert!(context.is_stopped());
+
+        // Reporting after the limit doesn't collect the error
+        assert!(context.validation_error("late error", None).is_err());
+        assert_eq!(context.error_count(), 5);
+    }
+
+    #[test]
+    fn test_validate_simple_content_extension() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="quantity">
+    <xs:complexType>
+      <xs:simpleContent>
+        <xs:extension base="xs:integer">
+          <xs:attribute name="unit" type="xs:string" use="required"/>
+        </xs:extension>
+      </xs:simpleContent>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+
+        assert!(schema.is_valid_string(r#"<quantity unit="kg">42</quantity>"#));
+        assert!(schema.is_valid_string(r#"<quantity unit="kg">4<!-- split -->2</quantity>"#));
+        assert!(schema.is_valid_string(r#"<quantity unit="kg">4<![CDATA[2]]></quantity>"#));
+
+        let result = schema.validate_string(r#"<quantity unit="kg">many</quantity>"#);
+        assert!(result.errors.iter().any(|e| e.error_code.as_deref() == Some("cvc-complex-type.2.2")));
+
+        let result = schema.validate_string(r#"<quantity unit="kg"><value>42</value></quantity>"#);
+        assert!(result.errors.iter().any(|e| e.message.contains("has simple content but contains child elements")));
+
+        assert!(!schema.is_valid_string(r#"<quantity>42</quantity>"#));
+    }
+
+    #[test]
+    fn test_validate_named_simple_content_types() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="price" type="discountType"/>
+  <xs:complexType name="discountType">
+    <xs:simpleContent>
+      <xs:extension base="priceType">
+        <xs:attribute name="rate" type="xs:decimal"/>
+      </xs:extension>
+    </xs:simpleContent>
+  </xs:complexType>
+  <xs:complexType name="priceType">
+    <xs:simpleContent>
+      <xs:extension base="amountType">
+        <xs:attribute name="currency" type="xs:string" use="required"/>
+      </xs:extension>
+    </xs:simpleContent>
+  </xs:complexType>
+  <xs:simpleType name="amountType">
+    <xs:restriction base="xs:decimal">
+      <xs:minInclusive value="0"/>
+    </xs:restriction>
+  </xs:simpleType>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+
+        // The simple base type is declared after the complex types
+        assert!(schema.is_valid_string(r#"<price currency="EUR">9.99</price>"#));
+        assert!(schema.is_valid_string(r#"<price currency="EUR" rate="0.1">9.99</price>"#));
+        assert!(!schema.is_valid_string(r#"<price currency="EUR">-1</price>"#));
+        assert!(!schema.is_valid_string(r#"<price currency="EUR">free</price>"#));
+        assert!(!schema.is_valid_string(r#"<price>9.99</price>"#));
+    }
 }
@@ -11,10 +11,14 @@ use std::sync::Arc;
 use crate::error::ParseError;
 use crate::namespaces::QName;
 
+use super::annotations::XsdAnnotation;
 use super::attributes::XsdAttributeGroup;
-use super::complex_types::{DerivationFlags, XsdComplexType};
+use super::complex_types::{DerivationFlags, XsdAlternative, XsdComplexType};
+use super::globals::GlobalType;
 use super::groups::XsdGroup;
+use super::identities::XsdIdentity;
 use super::particles::{Occurs, Particle};
+use super::schemas::XsdSchema;
 use super::simple_types::SimpleType;
 use super::wildcards::XsdAnyElement;
 
@@ -153,6 +157,17 @@ pub struct XsdElement {
     /// Whether this element is qualified
     pub qualified: bool,
 
+    /// Identity constraints (xs:unique, xs:key, xs:keyref)
+    pub identities: Vec<Arc<XsdIdentity>>,
+
+    /// Type alternatives (xs:alternative, XSD 1.1)
+    pub alternatives: Vec<XsdAlternative>,
+
+    /// Text of the xs:documentation annotations
+    pub documentation: Option<String>,
+    /// Annotation of the declaration
+    pub annotation: Option<XsdAnnotation>,
+
     /// Parse errors
     errors: Vec<ParseError>,
 }
@@ -177,6 +192,10 @@ impl XsdElement {
             type_name: None,
             target_namespace: None,
             qualified: false,
+            identities: Vec::new(),
+            alternatives: Vec::new(),
+            documentation: None,
+            annotation: None,
             errors: Vec::new(),
         }
     }
@@ -215,6 +234,10 @@ impl XsdElement {
             type_name: None,
             target_namespace: None,
             qualified: false,
+            identities: Vec::new(),
+            alternatives: Vec::new(),
+            documentation: None,
+            annotation: None,
             errors: Vec::new(),
         }
     }
@@ -274,6 +297,35 @@ impl XsdElement {
         self.fixed.as_deref().or(self.default.as_deref())
     }
 
+    /// Get the effective type of this element
+    ///
+    /// Uses the resolved `element_type` when available, then falls back to
+    /// looking up `type_name` (or the referenced global element) in the schema,
+    /// so callers get a type even if a forward reference was not resolved
+    /// in place. Returns `None` for untyped elements (`xs:anyType` content).
+    ///
+    /// An owned `GlobalType` is returned because anonymous types live in the
+    /// declaration itself rather than in the schema; cloning it only clones
+    /// an `Arc`.
+    pub fn effective_type(&self, schema: &XsdSchema) -> Option<GlobalType> {
+        match &self.element_type {
+            ElementType::Simple(st) => return Some(GlobalType::Simple(Arc::clone(st))),
+            ElementType::Complex(ct) => return Some(GlobalType::Complex(Arc::clone(ct))),
+            ElementType::Any => {}
+        }
+
+        if let Some(ref type_name) = self.type_name {
+            return schema.lookup_type(type_name).cloned();
+        }
+
+        let ref_name = self.ref_element.as_ref()?;
+        let target = schema.lookup_element(ref_name)?;
+        if std::ptr::eq(target.as_ref(), self) {
+            return None;
+        }
+        target.effective_type(schema)
+    }
+
     /// Check if this is a reference to another element
     pub fn is_reference(&self) -> bool {
         self.ref_element.is_some()
@@ -289,6 +341,26 @@ impl XsdElement {
         self.scope == ElementScope::Local
     }
 
+    /// Check if this element is abstract
+    pub fn is_abstract(&self) -> bool {
+        self.abstract_element
+    }
+
+    /// Check if this element is nillable
+    pub fn is_nillable(&self) -> bool {
+        self.nillable
+    }
+
+    /// Check if this element has a default value
+    pub fn has_default(&self) -> bool {
+        self.default.is_some()
+    }
+
+    /// Check if this element has a fixed value
+    pub fn has_fixed(&self) -> bool {
+        self.fixed.is_some()
+    }
+
     /// Set occurrence constraints
     pub fn with_occurs(mut self, occurs: Occurs) -> Self {
         self.occurs = occurs;
@@ -600,6 +672,10 @@ impl XsdElementBuilder {
             type_name: self.type_name,
             target_namespace: self.target_namespace,
             qualified: self.qualified,
+            identities: Vec::new(),
+            alternatives: Vec::new(),
+            documentation: None,
+            annotation: None,
             errors: Vec::new(),
         })
     }
@@ -702,6 +778,32 @@ mod tests {
         assert_eq!(with_both.value_constraint(), Some("fixed"));
     }
 
+    #[test]
+    fn test_element_flag_accessors() {
+        let plain = XsdElement::any_type(QName::local("elem"));
+        assert!(!plain.is_abstract());
+        assert!(!plain.is_nillable());
+        assert!(!plain.has_default());
+        assert!(!plain.has_fixed());
+
+        let elem = XsdElementBuilder::new()
+            .name(QName::local("head"))
+            .abstract_element(true)
+            .nillable(true)
+            .build()
+            .unwrap();
+        assert!(elem.is_abstract());
+        assert!(elem.is_nillable());
+
+        let with_default = plain.clone().with_default("x".to_string());
+        assert!(with_default.has_default());
+        assert!(!with_default.has_fixed());
+
+        let with_fixed = plain.with_fixed("y".to_string());
+        assert!(with_fixed.has_fixed());
+        assert!(!with_fixed.has_default());
+    }
+
     #[test]
     fn test_element_overlap() {
         let elem1 = XsdElement::any_type(QName::local("item"));
@@ -741,4 +843,60 @@ mod tests {
         assert_eq!(ElementForm::from_str("unqualified"), Some(ElementForm::Unqualified));
         assert_eq!(ElementForm::from_str("invalid"), None);
     }
+
+    const FORWARD_REF_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="order" type="orderType"/>
+  <xs:element name="code" type="codeType"/>
+  <xs:complexType name="orderType">
+    <xs:sequence>
+      <xs:element ref="code"/>
+    </xs:sequence>
+  </xs:complexType>
+  <xs:simpleType name="codeType">
+    <xs:restriction base="xs:string"/>
+  </xs:simpleType>
+</xs:schema>"#;
+
+    #[test]
+    fn test_effective_type_forward_reference() {
+        let schema = XsdSchema::from_string(FORWARD_REF_XSD).unwrap();
+
+        let order = schema.lookup_element(&QName::local("order")).unwrap();
+        assert!(order.effective_type(&schema).unwrap().is_complex());
+
+        let code = schema.lookup_element(&QName::local("code")).unwrap();
+        assert!(code.effective_type(&schema).unwrap().is_simple());
+    }
+
+    #[test]
+    fn test_effective_type_unresolved_declaration() {
+        let schema = XsdSchema::from_string(FORWARD_REF_XSD).unwrap();
+
+        // A declaration whose type was never resolved in place
+        let mut elem = XsdElement::any_type(QName::local("other"));
+        elem.type_name = Some(QName::local("orderType"));
+        assert!(matches!(elem.element_type, ElementType::Any));
+        let effective = elem.effective_type(&schema).unwrap();
+        assert_eq!(effective.name(), Some(&QName::local("orderType")));
+
+        // A reference resolves through the referenced global element
+        let reference = XsdElement::reference(QName::local("code"), Occurs::once());
+        assert!(reference.effective_type(&schema).unwrap().is_simple());
+
+        // Untyped and unknown types have no effective type
+        assert!(XsdElement::any_type(QName::local("any")).effective_type(&schema).is_none());
+        let mut unknown = XsdElement::any_type(QName::local("unknown"));
+        unknown.type_name = Some(QName::local("missingType"));
+        assert!(unknown.effective_type(&schema).is_none());
+    }
+
+    #[test]
+    fn test_effective_type_prefers_element_type() {
+        let schema = XsdSchema::new();
+        let string_type = Arc::new(XsdAtomicType::new(XSD_STRING).unwrap());
+        let mut elem = XsdElement::simple(QName::local("name"), string_type);
+        elem.type_name = Some(QName::local("missingType"));
+        assert!(elem.effective_type(&schema).unwrap().is_simple());
+    }
 }
@@ -18,21 +18,30 @@ pub trait XsdValidatorError: std::error::Error + fmt::Debug {
 
     /// Get the source line number (if available)
     fn source_line(&self) -> Option<usize>;
+
+    /// Get the source column number (if available)
+    fn source_column(&self) -> Option<usize>;
 }
 
 /// Validation error when XML data doesn't conform to the schema
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ValidationError {
     /// The error message
-    message: String,
+    pub message: String,
     /// The reason for the validation failure
     pub reason: Option<String>,
-    /// The XPath to the element that failed validation
-    path: Option<String>,
+    /// The XPath to the element or attribute that failed validation
+    pub path: Option<String>,
+    /// The QName of the failing schema component (element, type or attribute)
+    pub schema_component: Option<String>,
+    /// The code of the violated validation rule, e.g. `cvc-type.3.1.3`
+    pub error_code: Option<String>,
     /// The schema URL
     schema_url: Option<String>,
-    /// The source line number
-    source_line: Option<usize>,
+    /// The line of the failing element in the instance document (1-based)
+    pub source_line: Option<usize>,
+    /// The column of the failing element in the instance document (1-based)
+    pub source_column: Option<usize>,
     /// The tag of the element
     pub element_tag: Option<String>,
     /// The expected type or element
@@ -48,8 +57,11 @@ impl ValidationError {
             message: message.into(),
             reason: None,
             path: None,
+            schema_component: None,
+            error_code: None,
             schema_url: None,
             source_line: None,
+            source_column: None,
             element_tag: None,
             expected: None,
             actual: None,
@@ -68,6 +80,18 @@ impl ValidationError {
         self
     }
 
+    /// Set the failing schema component
+    pub fn with_schema_component(mut self, component: impl Into<String>) -> Self {
+        self.schema_component = Some(component.into());
+        self
+    }
+
+    /// Set the code of the violated validation rule
+    pub fn with_error_code(mut self, code: impl Into<String>) -> Self {
+        self.error_code = Some(code.into());
+        self
+    }
+
     /// Set the schema URL
     pub fn with_schema_url(mut self, url: impl Into<String>) -> Self {
         self.schema_url = Some(url.into());
@@ -80,6 +104,12 @@ impl ValidationError {
         self
     }
 
+    /// Set the source column
+    pub fn with_source_column(mut self, column: usize) -> Self {
+        self.source_column = Some(column);
+        self
+    }
+
     /// Set the element tag
     pub fn with_element(mut self, tag: impl Into<String>) -> Self {
         self.element_tag = Some(tag.into());
@@ -108,6 +138,12 @@ impl fmt::Display for ValidationError {
         if let Some(ref path) = self.path {
             write!(f, "\nPath: {}", path)?;
         }
+        if let Some(line) = self.source_line {
+            match self.source_column {
+                Some(column) => write!(f, "\nLocation: line {}, column {}", line, column)?,
+                None => write!(f, "\nLocation: line {}", line)?,
+            }
+        }
         Ok(())
     }
 }
@@ -130,6 +166,10 @@ impl XsdValidatorError for ValidationError {
     fn source_line(&self) -> Option<usize> {
         self.source_line
     }
+
+    fn source_column(&self) -> Option<usize> {
+        self.source_column
+    }
 }
 
 /// Error when a child element fails validation
@@ -518,6 +558,18 @@ impl From<ParseError> for ValidationError {
     }
 }
 
+/// Convert from the crate's validation error, raised in strict mode
+impl From<crate::error::ValidationError> for ValidationError {
+    fn from(err: crate::error::ValidationError) -> Self {
+        Self {
+            reason: err.reason.filter(|reason| !reason.is_empty()),
+            path: err.path,
+            schema_component: err.schema_component,
+            ..ValidationError::new(err.message)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -527,11 +579,34 @@ mod tests {
         let error = ValidationError::new("Invalid element")
             .with_reason("element 'foo' not expected")
             .with_path("/root/child")
-            .with_source_line(42);
+            .with_source_line(42)
+            .with_source_column(7);
 
         assert_eq!(error.message(), "Invalid element");
         assert_eq!(error.path(), Some("/root/child"));
         assert_eq!(error.source_line(), Some(42));
+        assert_eq!(error.source_column(), Some(7));
+        assert!(error.to_string().ends_with("\nLocation: line 42, column 7"));
+    }
+
+    #[test]
+    fn test_validation_error_structured_fields() {
+        let error = ValidationError::new("Invalid value")
+            .with_path("/root/@id")
+            .with_schema_component("{http://example.com}idType")
+            .with_error_code("cvc-attribute.3");
+
+        assert_eq!(error.message, "Invalid value");
+        assert_eq!(error.path.as_deref(), Some("/root/@id"));
+        assert_eq!(error.schema_component.as_deref(), Some("{http://example.com}idType"));
+        assert_eq!(error.error_code.as_deref(), Some("cvc-attribute.3"));
+
+        let raised = crate::error::ValidationError::new("Invalid value")
+            .with_path("/root/@id")
+            .with_reason("");
+        let converted = ValidationError::from(raised);
+        assert_eq!(converted.path.as_deref(), Some("/root/@id"));
+        assert_eq!(converted.reason, None);
     }
 
     #[test]
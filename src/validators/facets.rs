@@ -3,9 +3,11 @@
 //! This module implements XSD facets that constrain simple types.
 
 use crate::error::{Result, ValidationError};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
 use regex::Regex;
 use rust_decimal::Decimal;
 use std::fmt;
+use std::str::FromStr;
 
 /// White space handling modes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -32,6 +34,15 @@ impl WhiteSpace {
         }
     }
 
+    /// Get the mode as a string
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WhiteSpace::Preserve => "preserve",
+            WhiteSpace::Replace => "replace",
+            WhiteSpace::Collapse => "collapse",
+        }
+    }
+
     /// Normalize a string according to this white space mode
     pub fn normalize(&self, s: &str) -> String {
         match self {
@@ -193,6 +204,8 @@ impl MaxLengthFacet {
 pub struct PatternFacet {
     /// Regular expression pattern
     pub pattern: String,
+    /// The `xs:pattern` value the regex was translated from
+    pub source: Option<String>,
     /// Compiled regex
     regex: Regex,
 }
@@ -206,10 +219,17 @@ impl PatternFacet {
 
         Ok(Self {
             pattern: pattern.to_string(),
+            source: None,
             regex,
         })
     }
 
+    /// Record the `xs:pattern` value the regex was translated from
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
     /// Validate a value against this pattern
     pub fn validate(&self, value: &str) -> Result<()> {
         if self.regex.is_match(value) {
@@ -258,6 +278,8 @@ pub enum NumericBound {
     Decimal(Decimal),
     /// Float bound
     Float(f64),
+    /// Date, time or dateTime bound, in its lexical form
+    Temporal(String),
 }
 
 impl NumericBound {
@@ -268,6 +290,8 @@ impl NumericBound {
             NumericBound::Decimal(bound) => {
                 Decimal::from(value).cmp(bound)
             }
+            // Temporal bounds don't order numbers
+            NumericBound::Temporal(_) => std::cmp::Ordering::Equal,
             NumericBound::Float(bound) => {
                 let val_f64 = value as f64;
                 if val_f64 < *bound {
@@ -288,6 +312,7 @@ impl NumericBound {
                 value.cmp(&Decimal::from(*bound))
             }
             NumericBound::Decimal(bound) => value.cmp(bound),
+            NumericBound::Temporal(_) => std::cmp::Ordering::Equal,
             NumericBound::Float(bound) => {
                 let val_f64 = value.to_string().parse::<f64>().unwrap_or(0.0);
                 if val_f64 < *bound {
@@ -302,12 +327,84 @@ impl NumericBound {
     }
 }
 
+impl NumericBound {
+    /// Create a temporal bound from a date, time or dateTime value
+    ///
+    /// Returns `None` if the value is not one of those.
+    pub fn temporal(value: &str) -> Option<Self> {
+        let value = value.trim();
+        temporal_key(value).map(|_| NumericBound::Temporal(value.to_string()))
+    }
+
+    /// Compare with a value in its lexical form
+    ///
+    /// Numbers compare numerically and temporal values on the time line.
+    /// Returns `None` if the value cannot be compared with the bound, which
+    /// is then left to the validation of the base type.
+    pub fn compare_lexical(&self, value: &str) -> Option<std::cmp::Ordering> {
+        let value = value.trim();
+        match self {
+            NumericBound::Integer(_) | NumericBound::Decimal(_) => {
+                if let Ok(int) = value.parse::<i64>() {
+                    Some(self.compare_int(int))
+                } else if let Ok(decimal) = value.parse::<Decimal>() {
+                    Some(self.compare_decimal(&decimal))
+                } else {
+                    value.parse::<f64>().ok()?.partial_cmp(&self.to_string().parse::<f64>().ok()?)
+                }
+            }
+            NumericBound::Float(bound) => value.parse::<f64>().ok()?.partial_cmp(bound),
+            NumericBound::Temporal(bound) => Some(temporal_key(value)?.cmp(&temporal_key(bound)?)),
+        }
+    }
+}
+
+/// Place a date, time or dateTime value on the time line
+///
+/// Values are normalized to UTC, values without a timezone being taken as
+/// UTC, and times are placed on the reference date 1972-12-31.
+fn temporal_key(value: &str) -> Option<NaiveDateTime> {
+    let (local, offset_minutes) = split_timezone(value.trim())?;
+    let datetime = if let Ok(datetime) = NaiveDateTime::parse_from_str(local, "%Y-%m-%dT%H:%M:%S%.f") {
+        datetime
+    } else if let Ok(date) = NaiveDate::parse_from_str(local, "%Y-%m-%d") {
+        date.and_hms_opt(0, 0, 0)?
+    } else if let Ok(time) = NaiveTime::parse_from_str(local, "%H:%M:%S%.f") {
+        NaiveDate::from_ymd_opt(1972, 12, 31)?.and_time(time)
+    } else {
+        return None;
+    };
+    datetime.checked_sub_signed(TimeDelta::try_minutes(offset_minutes)?)
+}
+
+/// Split a temporal value into its local part and its timezone offset in minutes
+fn split_timezone(value: &str) -> Option<(&str, i64)> {
+    if let Some(local) = value.strip_suffix('Z') {
+        return Some((local, 0));
+    }
+    if value.len() > 6 && value.is_char_boundary(value.len() - 6) {
+        let (local, zone) = value.split_at(value.len() - 6);
+        if let [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2] = zone.as_bytes() {
+            let digits = [h1, h2, m1, m2];
+            if !digits.iter().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            let hours = i64::from((h1 - b'0') * 10 + (h2 - b'0'));
+            let minutes = i64::from((m1 - b'0') * 10 + (m2 - b'0'));
+            let offset = hours * 60 + minutes;
+            return Some((local, if *sign == b'-' { -offset } else { offset }));
+        }
+    }
+    Some((value, 0))
+}
+
 impl fmt::Display for NumericBound {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             NumericBound::Integer(v) => write!(f, "{}", v),
             NumericBound::Decimal(v) => write!(f, "{}", v),
             NumericBound::Float(v) => write!(f, "{}", v),
+            NumericBound::Temporal(v) => write!(f, "{}", v),
         }
     }
 }
@@ -359,6 +456,20 @@ impl MinInclusiveFacet {
             _ => Ok(()),
         }
     }
+
+    /// Validate a value in its lexical form
+    ///
+    /// Values that cannot be compared with the bound are left to the base type.
+    pub fn validate_lexical(&self, value: &str) -> Result<()> {
+        use std::cmp::Ordering;
+        match self.value.compare_lexical(value) {
+            Some(Ordering::Less) => Err(crate::error::Error::Validation(
+                ValidationError::new(format!("Value must be >= {}", self.value))
+                    .with_reason(format!("Value: {}", value.trim())),
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Maximum inclusive bound facet
@@ -389,6 +500,20 @@ impl MaxInclusiveFacet {
             _ => Ok(()),
         }
     }
+
+    /// Validate a value in its lexical form
+    ///
+    /// Values that cannot be compared with the bound are left to the base type.
+    pub fn validate_lexical(&self, value: &str) -> Result<()> {
+        use std::cmp::Ordering;
+        match self.value.compare_lexical(value) {
+            Some(Ordering::Greater) => Err(crate::error::Error::Validation(
+                ValidationError::new(format!("Value must be <= {}", self.value))
+                    .with_reason(format!("Value: {}", value.trim())),
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Minimum exclusive bound facet
@@ -438,6 +563,20 @@ impl MinExclusiveFacet {
             )),
         }
     }
+
+    /// Validate a value in its lexical form
+    ///
+    /// Values that cannot be compared with the bound are left to the base type.
+    pub fn validate_lexical(&self, value: &str) -> Result<()> {
+        use std::cmp::Ordering;
+        match self.value.compare_lexical(value) {
+            Some(Ordering::Less | Ordering::Equal) => Err(crate::error::Error::Validation(
+                ValidationError::new(format!("Value must be > {}", self.value))
+                    .with_reason(format!("Value: {}", value.trim())),
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Maximum exclusive bound facet
@@ -487,6 +626,20 @@ impl MaxExclusiveFacet {
             )),
         }
     }
+
+    /// Validate a value in its lexical form
+    ///
+    /// Values that cannot be compared with the bound are left to the base type.
+    pub fn validate_lexical(&self, value: &str) -> Result<()> {
+        use std::cmp::Ordering;
+        match self.value.compare_lexical(value) {
+            Some(Ordering::Greater | Ordering::Equal) => Err(crate::error::Error::Validation(
+                ValidationError::new(format!("Value must be < {}", self.value))
+                    .with_reason(format!("Value: {}", value.trim())),
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Total digits facet - constrains the maximum number of decimal digits
@@ -541,6 +694,25 @@ impl TotalDigitsFacet {
             Ok(())
         }
     }
+
+    /// Validate a value in its lexical form
+    ///
+    /// Leading zeros of the integer part and trailing zeros of the fraction
+    /// are not significant. Values that are not decimal numerals are left to
+    /// the base type.
+    pub fn validate_lexical(&self, value: &str) -> Result<()> {
+        let Some((total, _)) = decimal_digits(value) else {
+            return Ok(());
+        };
+        if total > self.value {
+            Err(crate::error::Error::Validation(
+                ValidationError::new(format!("Value exceeds totalDigits limit of {}", self.value))
+                    .with_reason(format!("Value {} has {} significant digits", value.trim(), total)),
+            ))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 /// Fraction digits facet - constrains the maximum number of decimal places
@@ -569,6 +741,122 @@ impl FractionDigitsFacet {
             Ok(())
         }
     }
+
+    /// Validate a value in its lexical form
+    ///
+    /// Trailing zeros of the fraction are not significant. Values that are
+    /// not decimal numerals are left to the base type.
+    pub fn validate_lexical(&self, value: &str) -> Result<()> {
+        let Some((_, fraction)) = decimal_digits(value) else {
+            return Ok(());
+        };
+        if fraction > self.value {
+            Err(crate::error::Error::Validation(
+                ValidationError::new(format!("Value exceeds fractionDigits limit of {}", self.value))
+                    .with_reason(format!("Value {} has {} fractional digits", value.trim(), fraction)),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Count the significant digits of a decimal numeral
+///
+/// Returns the total and fractional digit counts, after stripping the sign,
+/// the decimal point, leading zeros and trailing fractional zeros, or `None`
+/// if the value is not a decimal numeral.
+fn decimal_digits(value: &str) -> Option<(u32, u32)> {
+    let value = value.trim();
+    let unsigned = value.strip_prefix(['+', '-']).unwrap_or(value);
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (integer.is_empty() && fraction.is_empty()) || !is_digits(integer) || !is_digits(fraction) {
+        return None;
+    }
+
+    let integer = integer.trim_start_matches('0');
+    let fraction = fraction.trim_end_matches('0');
+    let fraction_digits = fraction.len() as u32;
+    let total_digits = if integer.is_empty() {
+        // Leading zeros of a fraction below one are not significant either
+        fraction.trim_start_matches('0').len() as u32
+    } else {
+        integer.len() as u32 + fraction_digits
+    };
+    Some((total_digits, fraction_digits))
+}
+
+/// Explicit timezone facet (XSD 1.1) - constrains the timezone of temporal values
+///
+/// Neither flag set means the timezone is optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExplicitTimezoneFacet {
+    /// Values must have a timezone
+    pub required: bool,
+    /// Values must not have a timezone
+    pub prohibited: bool,
+}
+
+impl ExplicitTimezoneFacet {
+    /// Validate a temporal value in its lexical form
+    pub fn validate(&self, value: &str) -> Result<()> {
+        let has_timezone = Self::has_timezone(value);
+        if self.required && !has_timezone {
+            Err(crate::error::Error::Validation(
+                ValidationError::new("Value must have a timezone")
+                    .with_reason("explicitTimezone facet is 'required'"),
+            ))
+        } else if self.prohibited && has_timezone {
+            Err(crate::error::Error::Validation(
+                ValidationError::new("Value must not have a timezone")
+                    .with_reason("explicitTimezone facet is 'prohibited'"),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check whether a lexical temporal value ends with a timezone (`Z` or `±hh:mm`)
+    fn has_timezone(value: &str) -> bool {
+        if value.ends_with('Z') {
+            return true;
+        }
+        match value.as_bytes() {
+            [.., sign, h1, h2, b':', m1, m2] => {
+                matches!(sign, b'+' | b'-') && [h1, h2, m1, m2].iter().all(|b| b.is_ascii_digit())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for ExplicitTimezoneFacet {
+    type Err = crate::error::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "required" => Ok(Self { required: true, prohibited: false }),
+            "prohibited" => Ok(Self { required: false, prohibited: true }),
+            "optional" => Ok(Self::default()),
+            _ => Err(crate::error::Error::Value(format!(
+                "Invalid explicitTimezone value: '{}'. Must be 'required', 'prohibited', or 'optional'",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ExplicitTimezoneFacet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.required {
+            write!(f, "required")
+        } else if self.prohibited {
+            write!(f, "prohibited")
+        } else {
+            write!(f, "optional")
+        }
+    }
 }
 
 // TODO: Implement more facets:
@@ -604,6 +892,30 @@ mod tests {
         assert!(WhiteSpace::Collapse.validate("hello world").is_ok());
     }
 
+    #[test]
+    fn test_explicit_timezone_facet() {
+        let required: ExplicitTimezoneFacet = "required".parse().unwrap();
+        assert!(required.validate("2024-01-15T10:30:00Z").is_ok());
+        assert!(required.validate("2024-01-15T10:30:00+05:30").is_ok());
+        assert!(required.validate("2024-01-15-08:00").is_ok());
+        assert!(required.validate("2024-01-15T10:30:00").is_err());
+        assert!(required.validate("2024-01-15").is_err());
+
+        let prohibited: ExplicitTimezoneFacet = "prohibited".parse().unwrap();
+        assert!(prohibited.validate("2024-01-15").is_ok());
+        assert!(prohibited.validate("10:30:00").is_ok());
+        assert!(prohibited.validate("10:30:00Z").is_err());
+        assert!(prohibited.validate("--12-25-05:00").is_err());
+
+        let optional: ExplicitTimezoneFacet = "optional".parse().unwrap();
+        assert_eq!(optional, ExplicitTimezoneFacet::default());
+        assert!(optional.validate("2024-01-15T10:30:00").is_ok());
+        assert!(optional.validate("2024-01-15T10:30:00Z").is_ok());
+
+        assert!("sometimes".parse::<ExplicitTimezoneFacet>().is_err());
+        assert_eq!(prohibited.to_string(), "prohibited");
+    }
+
     #[test]
     fn test_length_facet() {
         let facet = LengthFacet::new(5);
@@ -704,6 +1016,39 @@ mod tests {
         assert!(facet.validate_int(101).is_err());
     }
 
+    #[test]
+    fn test_bound_facets_lexical() {
+        let min_inclusive = MinInclusiveFacet::new_int(10);
+        let min_exclusive = MinExclusiveFacet::new_int(10);
+        assert!(min_inclusive.validate_lexical("10").is_ok());
+        assert!(min_exclusive.validate_lexical("10").is_err());
+        assert!(min_exclusive.validate_lexical("10.5").is_ok());
+        assert!(min_exclusive.validate_lexical("1.1E1").is_ok());
+        assert!(min_inclusive.validate_lexical("9.99").is_err());
+
+        let max_inclusive = MaxInclusiveFacet { value: NumericBound::Decimal(Decimal::new(25, 1)) };
+        let max_exclusive = MaxExclusiveFacet::new_decimal(Decimal::new(25, 1));
+        assert!(max_inclusive.validate_lexical("2.5").is_ok());
+        assert!(max_exclusive.validate_lexical("2.50").is_err());
+        assert!(max_exclusive.validate_lexical("2.49").is_ok());
+        assert!(max_exclusive.validate_lexical("abc").is_ok()); // left to the base type
+
+        let date = |value: &str| NumericBound::Temporal(value.to_string());
+        let min_date = MinExclusiveFacet { value: date("2024-01-01") };
+        assert!(min_date.validate_lexical("2024-01-01").is_err());
+        assert!(min_date.validate_lexical("2024-01-02").is_ok());
+        assert!(MinInclusiveFacet { value: date("2024-01-01") }.validate_lexical("2024-01-01").is_ok());
+
+        let max_time = MaxExclusiveFacet { value: date("12:00:00Z") };
+        assert!(max_time.validate_lexical("11:59:59.5").is_ok());
+        assert!(max_time.validate_lexical("13:30:00+01:00").is_err());
+        assert!(max_time.validate_lexical("12:30:00+01:00").is_ok());
+
+        let max_datetime = MaxInclusiveFacet { value: date("2024-06-30T23:59:59") };
+        assert!(max_datetime.validate_lexical("2024-06-30T23:59:59").is_ok());
+        assert!(max_datetime.validate_lexical("2024-07-01T00:00:00").is_err());
+    }
+
     #[test]
     fn test_total_digits_facet() {
         let facet = TotalDigitsFacet::new(5);
@@ -720,6 +1065,25 @@ mod tests {
         assert!(facet.validate_decimal(&Decimal::new(123456, 0)).is_err()); // 123456 (6 digits)
     }
 
+    #[test]
+    fn test_digits_facets_lexical() {
+        let total = TotalDigitsFacet::new(4);
+        assert!(total.validate_lexical("1234").is_ok());
+        assert!(total.validate_lexical("-12.34").is_ok());
+        assert!(total.validate_lexical("0012.3400").is_ok());
+        assert!(total.validate_lexical("0.0001").is_ok());
+        assert!(total.validate_lexical("12345").is_err());
+        assert!(total.validate_lexical("+123.45").is_err());
+        assert!(total.validate_lexical("abc").is_ok()); // left to the base type
+
+        let fraction = FractionDigitsFacet::new(2);
+        assert!(fraction.validate_lexical("1.25").is_ok());
+        assert!(fraction.validate_lexical("1.2500").is_ok());
+        assert!(fraction.validate_lexical("100").is_ok());
+        assert!(fraction.validate_lexical(".125").is_err());
+        assert!(fraction.validate_lexical("-0.001").is_err());
+    }
+
     #[test]
     fn test_fraction_digits_facet() {
         let facet = FractionDigitsFacet::new(2);
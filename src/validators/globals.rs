@@ -3,10 +3,15 @@
 //! This module provides the XsdGlobals mediator class that manages global
 //! declarations (types, elements, attributes, groups, notations) and provides
 //! lookup functionality across schemas.
+//!
+//! The maps keep components in insertion order, so iterating over them is
+//! deterministic across runs and platforms.
 
-use std::collections::HashMap;
+use std::cmp::Ordering;
 use std::sync::Arc;
 
+use indexmap::IndexMap;
+
 use crate::error::{ParseError, Result};
 use crate::namespaces::QName;
 
@@ -19,21 +24,26 @@ use super::groups::XsdGroup;
 use super::identities::XsdIdentity;
 
 /// Type map - maps QNames to global types
-pub type TypeMap = HashMap<QName, GlobalType>;
+pub type TypeMap = IndexMap<QName, GlobalType>;
 /// Notation map - maps QNames to notation declarations
-pub type NotationMap = HashMap<QName, XsdNotation>;
+pub type NotationMap = IndexMap<QName, XsdNotation>;
 /// Attribute map - maps QNames to global attribute declarations
-pub type AttributeMap = HashMap<QName, Arc<XsdAttribute>>;
+pub type AttributeMap = IndexMap<QName, Arc<XsdAttribute>>;
 /// Attribute group map - maps QNames to attribute group definitions
-pub type AttributeGroupMap = HashMap<QName, Arc<XsdAttributeGroup>>;
+pub type AttributeGroupMap = IndexMap<QName, Arc<XsdAttributeGroup>>;
 /// Element map - maps QNames to global element declarations
-pub type ElementMap = HashMap<QName, Arc<XsdElement>>;
+pub type ElementMap = IndexMap<QName, Arc<XsdElement>>;
 /// Group map - maps QNames to model group definitions
-pub type GroupMap = HashMap<QName, Arc<XsdGroup>>;
+pub type GroupMap = IndexMap<QName, Arc<XsdGroup>>;
 /// Identity map - maps QNames to identity constraints
-pub type IdentityMap = HashMap<QName, Arc<XsdIdentity>>;
+pub type IdentityMap = IndexMap<QName, Arc<XsdIdentity>>;
 /// Substitution group map - maps head element QNames to substitute elements
-pub type SubstitutionGroupMap = HashMap<QName, Vec<Arc<XsdElement>>>;
+pub type SubstitutionGroupMap = IndexMap<QName, Vec<Arc<XsdElement>>>;
+
+/// Order QNames by namespace, then local name
+pub(crate) fn qname_order(a: &QName, b: &QName) -> Ordering {
+    (&a.namespace, &a.local_name).cmp(&(&b.namespace, &b.local_name))
+}
 
 /// XSD Notation declaration
 #[derive(Debug, Clone)]
@@ -82,6 +92,16 @@ impl XsdNotation {
         self.target_namespace = Some(ns.into());
         self
     }
+
+    /// Get the public identifier
+    pub fn public(&self) -> Option<&str> {
+        self.public.as_deref()
+    }
+
+    /// Get the system identifier
+    pub fn system(&self) -> Option<&str> {
+        self.system.as_deref()
+    }
 }
 
 impl Validator for XsdNotation {
@@ -169,6 +189,24 @@ impl GlobalType {
             GlobalType::Complex(t) => Some(t),
         }
     }
+
+    /// Check if two values refer to the same type
+    ///
+    /// Named types compare by name, anonymous types by identity.
+    pub fn is_same(&self, other: &GlobalType) -> bool {
+        match (self.name(), other.name()) {
+            (Some(a), Some(b)) => return a == b,
+            (None, None) => {}
+            _ => return false,
+        }
+        match (self, other) {
+            (GlobalType::Simple(a), GlobalType::Simple(b)) => {
+                std::ptr::addr_eq(Arc::as_ptr(a), Arc::as_ptr(b))
+            }
+            (GlobalType::Complex(a), GlobalType::Complex(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
 }
 
 /// Collection of global maps for XSD components
@@ -224,6 +262,17 @@ impl GlobalMaps {
             + self.groups.len()
     }
 
+    /// Sort all maps by QName: namespace first (no namespace before any
+    /// namespace), then local name
+    pub fn sort(&mut self) {
+        self.types.sort_by(|a, _, b, _| qname_order(a, b));
+        self.notations.sort_by(|a, _, b, _| qname_order(a, b));
+        self.attributes.sort_by(|a, _, b, _| qname_order(a, b));
+        self.attribute_groups.sort_by(|a, _, b, _| qname_order(a, b));
+        self.elements.sort_by(|a, _, b, _| qname_order(a, b));
+        self.groups.sort_by(|a, _, b, _| qname_order(a, b));
+    }
+
     /// Add a simple type
     pub fn add_simple_type(&mut self, name: QName, typ: Arc<dyn SimpleType + Send + Sync>) {
         self.types.insert(name, GlobalType::Simple(typ));
@@ -311,13 +360,20 @@ impl XsdGlobals {
             mode: ValidationMode::Strict,
             target_namespace: None,
             global_maps: GlobalMaps::new(),
-            substitution_groups: HashMap::new(),
-            identities: HashMap::new(),
+            substitution_groups: IndexMap::new(),
+            identities: IndexMap::new(),
             errors: Vec::new(),
             built: false,
         }
     }
 
+    /// Sort the global maps, substitution groups and identities by QName
+    pub fn sort(&mut self) {
+        self.global_maps.sort();
+        self.substitution_groups.sort_by(|a, _, b, _| qname_order(a, b));
+        self.identities.sort_by(|a, _, b, _| qname_order(a, b));
+    }
+
     /// Set the target namespace
     pub fn with_target_namespace(mut self, ns: impl Into<String>) -> Self {
         self.target_namespace = Some(ns.into());
@@ -11,6 +11,7 @@ use crate::error::ParseError;
 use crate::namespaces::QName;
 use std::sync::Arc;
 
+use super::annotations::XsdAnnotation;
 use super::elements::XsdElement;
 use super::particles::{Occurs, OccursCalculator, Particle};
 use super::wildcards::XsdAnyElement;
@@ -146,6 +147,22 @@ impl ElementParticle {
     pub fn element(&self) -> Option<&Arc<XsdElement>> {
         self.element_decl.as_ref()
     }
+
+    /// Check if an element tag matches this particle
+    ///
+    /// A particle referencing a global element also matches the members of
+    /// its substitution group, whose chain of `heads` includes that element.
+    pub fn matches(&self, tag: &str, heads: &[QName]) -> bool {
+        self.name.local_name == tag || self.element_ref.as_ref().is_some_and(|name| heads.contains(name))
+    }
+
+    /// Check if an element name matches this particle
+    ///
+    /// Like [`ElementParticle::matches`], but the namespace of the name must
+    /// match too.
+    pub fn matches_qname(&self, qname: &QName, heads: &[QName]) -> bool {
+        self.name == *qname || self.element_ref.as_ref().is_some_and(|name| heads.contains(name))
+    }
 }
 
 impl Particle for ElementParticle {
@@ -171,6 +188,8 @@ pub struct XsdGroup {
     pub group_ref: Option<QName>,
     /// Back-reference to original group when this is a redefinition (xs:redefine)
     pub redefine: Option<Arc<XsdGroup>>,
+    /// Annotation of the definition
+    pub annotation: Option<XsdAnnotation>,
     /// Parse errors
     errors: Vec<ParseError>,
 }
@@ -186,6 +205,7 @@ impl XsdGroup {
             mixed: false,
             group_ref: None,
             redefine: None,
+            annotation: None,
             errors: Vec::new(),
         }
     }
@@ -200,6 +220,7 @@ impl XsdGroup {
             mixed: false,
             group_ref: None,
             redefine: None,
+            annotation: None,
             errors: Vec::new(),
         }
     }
@@ -214,6 +235,7 @@ impl XsdGroup {
             mixed: false,
             group_ref: Some(ref_name),
             redefine: None,
+            annotation: None,
             errors: Vec::new(),
         }
     }
@@ -439,12 +461,12 @@ impl XsdGroup {
 
             match self.model {
                 ModelType::Sequence | ModelType::All => {
-                    calc.add(particle_occurs);
+                    calc.add_occurs(particle_occurs);
                 }
                 ModelType::Choice => {
                     // For choice: first particle initializes, rest use max_with
                     if first {
-                        calc.add(particle_occurs);
+                        calc.add_occurs(particle_occurs);
                         first = false;
                     } else {
                         calc.max_with(particle_occurs);
@@ -453,7 +475,7 @@ impl XsdGroup {
             }
         }
 
-        calc.multiply(self.occurs);
+        calc.multiply_occurs(self.occurs);
         calc
     }
 
@@ -501,6 +523,7 @@ mod tests {
         let group = XsdGroup::new(ModelType::Sequence);
         assert_eq!(group.model, ModelType::Sequence);
         assert!(group.particles.is_empty());
+        assert!(group.is_empty());
         assert_eq!(group.occurs, Occurs::once());
     }
 
@@ -519,6 +542,7 @@ mod tests {
         group.add_element(QName::local("third"), Occurs::zero_or_more());
 
         assert_eq!(group.len(), 3);
+        assert!(!group.is_empty());
     }
 
     #[test]
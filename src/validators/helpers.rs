@@ -4,8 +4,10 @@
 //! including type validators and conversion functions.
 
 use crate::error::{Error, Result, ValidationError};
+use crate::validators::facets::WhiteSpace;
 use base64::Engine;
 use rust_decimal::Decimal;
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 /// XSD final attribute values
@@ -293,6 +295,31 @@ pub fn int_to_rust(value: &str) -> Result<i64> {
     })
 }
 
+// =============================================================================
+// White Space Normalization
+// =============================================================================
+
+/// Normalize an attribute value according to the whiteSpace facet of its type
+///
+/// `preserve` keeps the value, `replace` turns tabs, carriage returns and
+/// line feeds into spaces and `collapse` also trims the value and reduces
+/// runs of spaces to one. The value is borrowed when already normalized.
+pub fn normalize_attribute_value(value: &str, white_space: WhiteSpace) -> Cow<'_, str> {
+    let has_control = value.contains(['\t', '\n', '\r']);
+    let normalized = match white_space {
+        WhiteSpace::Preserve => true,
+        WhiteSpace::Replace => !has_control,
+        WhiteSpace::Collapse => {
+            !has_control && !value.starts_with(' ') && !value.ends_with(' ') && !value.contains("  ")
+        }
+    };
+    if normalized {
+        Cow::Borrowed(value)
+    } else {
+        Cow::Owned(white_space.normalize(value))
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -426,4 +453,17 @@ mod tests {
         assert!(error_type_validator(&"anything").is_err());
         assert!(error_type_validator(&123).is_err());
     }
+
+    #[test]
+    fn test_normalize_attribute_value() {
+        let value = "  foo \t bar\n";
+        assert_eq!(normalize_attribute_value(value, WhiteSpace::Preserve), value);
+        assert_eq!(normalize_attribute_value(value, WhiteSpace::Replace), "  foo   bar ");
+        assert_eq!(normalize_attribute_value(value, WhiteSpace::Collapse), "foo bar");
+        assert_eq!(normalize_attribute_value("  foo  bar  ", WhiteSpace::Collapse), "foo bar");
+
+        assert!(matches!(normalize_attribute_value("foo bar", WhiteSpace::Collapse), Cow::Borrowed(_)));
+        assert!(matches!(normalize_attribute_value(" foo", WhiteSpace::Replace), Cow::Borrowed(_)));
+        assert!(matches!(normalize_attribute_value(" foo", WhiteSpace::Collapse), Cow::Owned(_)));
+    }
 }
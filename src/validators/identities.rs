@@ -10,10 +10,13 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::error::{ParseError, Result};
+use crate::documents::Element;
+use crate::error::{Error, ParseError, Result};
 use crate::namespaces::QName;
+use crate::xpath::{SelectedNode, XPathContext, XPathEvaluator, XPathNodeType};
 
 use super::base::{ValidationStatus, Validator};
+use super::elements::XsdElement;
 
 /// Type for identity field values.
 /// In XPath terms, this is the typed or untyped atomic value.
@@ -123,6 +126,50 @@ impl XsdSelector {
     pub fn errors(&self) -> &[ParseError] {
         &self.errors
     }
+
+    /// Select the elements subject to the constraint within a scope element
+    ///
+    /// Alternatives separated by `|` are selected in turn.
+    pub fn select<'a>(&self, scope: &'a Element) -> Result<Vec<&'a Element>> {
+        let evaluator = XPathEvaluator::for_element(scope);
+        let mut selected = Vec::new();
+        for path in self.xpath.split('|') {
+            let nodes = evaluator
+                .select(path)
+                .map_err(|e| ParseError::new(format!("invalid selector '{}': {}", self.xpath, e)))?;
+            selected.extend(nodes.into_iter().filter_map(|node| match node {
+                SelectedNode::Element(element) => Some(element),
+                _ => None,
+            }));
+        }
+        Ok(selected)
+    }
+
+    /// Get the names of the element declarations selected within a complex type
+    ///
+    /// The selector is evaluated on the components of the context's schema
+    /// proxy; unprefixed names are in the selector's XPath default namespace.
+    pub fn select_declarations(&self, context: &XPathContext, type_name: &QName) -> Result<Vec<QName>> {
+        let mut context = context.clone();
+        if let Some(ns) = &self.xpath_default_namespace {
+            context.schema = context.schema.map(|schema| schema.with_default_namespace(ns.clone()));
+        }
+        let mut selected = Vec::new();
+        for path in self.xpath.split('|') {
+            let result = context
+                .evaluate_schema_path(path, type_name)
+                .map_err(|e| ParseError::new(format!("invalid selector '{}': {}", self.xpath, e)))?;
+            selected.extend(
+                result
+                    .as_nodes()
+                    .into_iter()
+                    .flatten()
+                    .filter(|node| node.node_type == XPathNodeType::Element)
+                    .map(|node| QName::new(node.namespace.clone(), node.name.clone())),
+            );
+        }
+        Ok(selected)
+    }
 }
 
 /// XPath field selector for identity constraints.
@@ -175,6 +222,32 @@ impl XsdField {
     pub fn errors(&self) -> &[ParseError] {
         &self.errors
     }
+
+    /// Get the value of the field for an element selected by the selector
+    ///
+    /// Returns [`FieldValue::Null`] if the field selects no node, and an
+    /// error if it selects more than one.
+    pub fn value(&self, element: &Element) -> Result<FieldValue> {
+        let evaluator = XPathEvaluator::for_element(element);
+        let mut nodes = Vec::new();
+        for path in self.xpath.split('|') {
+            nodes.extend(
+                evaluator
+                    .select(path)
+                    .map_err(|e| ParseError::new(format!("invalid field '{}': {}", self.xpath, e)))?,
+            );
+        }
+        match nodes.as_slice() {
+            [] => Ok(FieldValue::Null),
+            [node] => Ok(FieldValue::String(node.string_value().trim().to_string())),
+            _ => Err(ParseError::new(format!(
+                "field '{}' selects {} nodes, at most one is allowed",
+                self.xpath,
+                nodes.len()
+            ))
+            .into()),
+        }
+    }
 }
 
 /// Type of identity constraint
@@ -282,6 +355,23 @@ impl XsdIdentity {
         matches!(self.kind, IdentityConstraintKind::Keyref)
     }
 
+    /// Evaluate the constraint within a scope element
+    ///
+    /// Returns the elements selected by the selector, each with the tuple
+    /// of its field values or the error raised evaluating a field.
+    pub fn field_tuples<'a>(&self, scope: &'a Element) -> Result<Vec<(&'a Element, Result<FieldTuple>)>> {
+        let rows = self
+            .selector
+            .select(scope)?
+            .into_iter()
+            .map(|element| {
+                let fields: Result<FieldTuple> = self.fields.iter().map(|field| field.value(element)).collect();
+                (element, fields)
+            })
+            .collect();
+        Ok(rows)
+    }
+
     /// Validate the identity constraint
     pub fn validate(&mut self) -> bool {
         let mut valid = true;
@@ -321,6 +411,21 @@ impl XsdIdentity {
         valid
     }
 
+    /// Attach this constraint to an element declaration
+    ///
+    /// Identity constraint names must be unique within an element.
+    pub fn add_to_element(self, element: &mut XsdElement) -> Result<()> {
+        if element.identities.iter().any(|identity| identity.name == self.name) {
+            return Err(Error::Parse(ParseError::new(format!(
+                "duplicate identity constraint '{}' on element '{}'",
+                self.name.to_string(),
+                element.name.to_string()
+            ))));
+        }
+        element.identities.push(Arc::new(self));
+        Ok(())
+    }
+
     /// Add a parse error
     pub fn add_error(&mut self, error: ParseError) {
         self.errors.push(error);
@@ -653,6 +758,18 @@ pub struct IdentityBuilder {
 }
 
 impl IdentityBuilder {
+    /// Create a builder for a named constraint of the given kind
+    pub fn new(kind: IdentityConstraintKind, name: QName) -> Self {
+        Self {
+            name: Some(name),
+            kind,
+            selector: None,
+            fields: Vec::new(),
+            refer: None,
+            target_namespace: None,
+        }
+    }
+
     /// Create a builder for a unique constraint
     pub fn unique() -> Self {
         Self {
@@ -701,12 +818,22 @@ impl IdentityBuilder {
         self
     }
 
+    /// Set the selector (alias of [`IdentityBuilder::selector`])
+    pub fn with_selector(self, xpath: &str) -> Self {
+        self.selector(xpath)
+    }
+
     /// Add a field
     pub fn field(mut self, xpath: impl Into<String>) -> Self {
         self.fields.push(XsdField::new(xpath));
         self
     }
 
+    /// Add a field (alias of [`IdentityBuilder::field`])
+    pub fn with_field(self, xpath: &str) -> Self {
+        self.field(xpath)
+    }
+
     /// Set the refer attribute (for keyref)
     pub fn refer(mut self, refer: QName) -> Self {
         self.refer = Some(refer);
@@ -720,7 +847,10 @@ impl IdentityBuilder {
     }
 
     /// Build the identity constraint
-    pub fn build(self) -> std::result::Result<XsdIdentity, ParseError> {
+    ///
+    /// The constraint is validated, so empty selector or field expressions
+    /// are rejected as well as missing components.
+    pub fn build(self) -> Result<XsdIdentity> {
         let name = self.name.ok_or_else(|| ParseError::new(
             "identity constraint must have a name",
         ))?;
@@ -732,13 +862,13 @@ impl IdentityBuilder {
         if self.fields.is_empty() {
             return Err(ParseError::new(
                 "identity constraint must have at least one field",
-            ));
+            ).into());
         }
 
         if self.kind == IdentityConstraintKind::Keyref && self.refer.is_none() {
             return Err(ParseError::new(
                 "keyref must have a 'refer' attribute",
-            ));
+            ).into());
         }
 
         let mut identity = XsdIdentity::new(name, self.kind, selector);
@@ -746,6 +876,13 @@ impl IdentityBuilder {
         identity.refer = self.refer;
         identity.target_namespace = self.target_namespace;
 
+        if !identity.validate() {
+            let error = identity.errors.first().cloned().unwrap_or_else(|| {
+                ParseError::new("invalid identity constraint")
+            });
+            return Err(error.into());
+        }
+
         Ok(identity)
     }
 }
@@ -754,6 +891,44 @@ impl IdentityBuilder {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_selector_select_declarations() {
+        use crate::validators::XsdSchema;
+        use crate::xpath::SchemaProxy;
+
+        let schema = XsdSchema::from_string(
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:b="urn:books" targetNamespace="urn:books">
+  <xs:complexType name="library">
+    <xs:sequence>
+      <xs:element name="book" type="b:book" maxOccurs="unbounded"/>
+    </xs:sequence>
+  </xs:complexType>
+  <xs:complexType name="book">
+    <xs:sequence>
+      <xs:element name="isbn" type="xs:string"/>
+    </xs:sequence>
+  </xs:complexType>
+</xs:schema>"#,
+        )
+        .unwrap();
+        let context = XPathContext::new().with_schema(SchemaProxy::from_schema(Arc::new(schema)));
+        let library = QName::namespaced("urn:books", "library");
+        let book = QName::namespaced("urn:books", "book");
+
+        let selector = XsdSelector::new("b:book");
+        assert_eq!(selector.select_declarations(&context, &library).unwrap(), [book.clone()]);
+
+        // Unprefixed names are resolved with the XPath default namespace
+        let selector = XsdSelector::with_default_namespace("book", "urn:books");
+        assert_eq!(selector.select_declarations(&context, &library).unwrap(), [book.clone()]);
+        let selector = XsdSelector::with_default_namespace("book", "urn:other");
+        assert!(selector.select_declarations(&context, &library).unwrap().is_empty());
+
+        let selector = XsdSelector::new("b:book/b:isbn | b:book");
+        let isbn = QName::namespaced("urn:books", "isbn");
+        assert_eq!(selector.select_declarations(&context, &library).unwrap(), [isbn, book]);
+    }
+
     #[test]
     fn test_field_value_creation() {
         let s = FieldValue::from("test");
@@ -788,6 +963,36 @@ mod tests {
         assert_eq!(field.xpath, "@id");
     }
 
+    #[test]
+    fn test_identity_field_tuples() {
+        let doc = crate::documents::Document::from_string(
+            r#"<lib><book id="1"><isbn> 111 </isbn></book><shelf><book id="2"/></shelf><dvd id="3"/></lib>"#,
+        )
+        .unwrap();
+        let identity = IdentityBuilder::key()
+            .name(QName::local("bookKey"))
+            .with_selector(".//book|dvd")
+            .with_field("@id")
+            .with_field("isbn")
+            .build()
+            .unwrap();
+
+        let rows = identity.field_tuples(doc.root().unwrap()).unwrap();
+        let tuples: Vec<_> = rows.into_iter().map(|(_, fields)| fields.unwrap()).collect();
+        assert_eq!(
+            tuples,
+            vec![
+                vec![FieldValue::from("1"), FieldValue::from("111")],
+                vec![FieldValue::from("2"), FieldValue::Null],
+                vec![FieldValue::from("3"), FieldValue::Null],
+            ]
+        );
+
+        // A field must not select more than one node
+        let field = XsdField::new("*");
+        assert!(field.value(doc.root().unwrap()).is_err());
+    }
+
     #[test]
     fn test_identity_unique_creation() {
         let identity = XsdIdentity::unique(
@@ -1024,4 +1229,115 @@ mod tests {
         identity.errors.clear();
         assert!(identity.validate());
     }
+
+    #[test]
+    fn test_builder_new_with_selector_and_fields() {
+        let identity = IdentityBuilder::new(IdentityConstraintKind::Unique, QName::local("pairUnique"))
+            .with_selector("item")
+            .with_field("@a")
+            .with_field("@b")
+            .build()
+            .unwrap();
+
+        assert!(identity.is_unique());
+        assert_eq!(identity.name.local_name, "pairUnique");
+        assert_eq!(identity.fields.len(), 2);
+
+        // Empty XPath expressions are rejected
+        let result = IdentityBuilder::new(IdentityConstraintKind::Key, QName::local("k"))
+            .with_selector("")
+            .with_field("@id")
+            .build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_add_to_element() {
+        let mut element = XsdElement::any_type(QName::local("catalog"));
+        let key = || {
+            IdentityBuilder::new(IdentityConstraintKind::Key, QName::local("productKey"))
+                .with_selector("product")
+                .with_field("@id")
+                .build()
+                .unwrap()
+        };
+
+        key().add_to_element(&mut element).unwrap();
+        assert_eq!(element.identities.len(), 1);
+
+        // Names must be unique within the element
+        assert!(key().add_to_element(&mut element).is_err());
+        assert_eq!(element.identities.len(), 1);
+    }
+
+    #[test]
+    fn test_builder_matches_parsed_constraints() {
+        use crate::validators::XsdSchema;
+
+        let ns = "http://example.com/catalog";
+        let schema = XsdSchema::from_string(r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           xmlns:c="http://example.com/catalog"
+           targetNamespace="http://example.com/catalog">
+  <xs:element name="catalog">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="product" type="xs:string" maxOccurs="unbounded"/>
+        <xs:element name="order" type="xs:string" maxOccurs="unbounded"/>
+      </xs:sequence>
+    </xs:complexType>
+    <xs:key name="productKey">
+      <xs:selector xpath="c:product"/>
+      <xs:field xpath="@id"/>
+    </xs:key>
+    <xs:keyref name="orderProductRef" refer="c:productKey">
+      <xs:selector xpath="c:order"/>
+      <xs:field xpath="@product"/>
+    </xs:keyref>
+    <xs:unique name="orderUnique">
+      <xs:selector xpath="c:order"/>
+      <xs:field xpath="@number"/>
+      <xs:field xpath="@line"/>
+    </xs:unique>
+  </xs:element>
+</xs:schema>"#).unwrap();
+
+        let catalog = schema.lookup_element(&QName::namespaced(ns, "catalog")).unwrap();
+
+        let expected = vec![
+            IdentityBuilder::new(IdentityConstraintKind::Key, QName::namespaced(ns, "productKey"))
+                .with_selector("c:product")
+                .with_field("@id")
+                .target_namespace(ns)
+                .build()
+                .unwrap(),
+            IdentityBuilder::new(IdentityConstraintKind::Keyref, QName::namespaced(ns, "orderProductRef"))
+                .with_selector("c:order")
+                .with_field("@product")
+                .refer(QName::namespaced(ns, "productKey"))
+                .target_namespace(ns)
+                .build()
+                .unwrap(),
+            IdentityBuilder::new(IdentityConstraintKind::Unique, QName::namespaced(ns, "orderUnique"))
+                .with_selector("c:order")
+                .with_field("@number")
+                .with_field("@line")
+                .target_namespace(ns)
+                .build()
+                .unwrap(),
+        ];
+
+        assert_eq!(catalog.identities.len(), expected.len());
+        for (parsed, built) in catalog.identities.iter().zip(&expected) {
+            assert_eq!(parsed.name, built.name);
+            assert_eq!(parsed.kind, built.kind);
+            assert_eq!(parsed.selector.xpath, built.selector.xpath);
+            let parsed_fields: Vec<_> = parsed.fields.iter().map(|f| &f.xpath).collect();
+            let built_fields: Vec<_> = built.fields.iter().map(|f| &f.xpath).collect();
+            assert_eq!(parsed_fields, built_fields);
+            assert_eq!(parsed.refer, built.refer);
+            assert_eq!(parsed.target_namespace, built.target_namespace);
+        }
+        assert!(catalog.errors().is_empty());
+    }
 }
@@ -0,0 +1,90 @@
+//! XSD meta-schema validation
+//!
+//! Schema documents are themselves XML documents described by the W3C
+//! `XMLSchema.xsd` meta-schema. The XSD 1.0 meta-schema is embedded in the
+//! crate, with the `xml.xsd` schema it imports, and loaded once on first use.
+
+use std::sync::OnceLock;
+
+use super::schemas::{ValidationResult, XsdSchema};
+
+/// The W3C XSD 1.0 meta-schema
+const XSD_1_0_META_SCHEMA: &[u8] = include_bytes!("schemas/XSD_1.0/XMLSchema.xsd");
+
+/// The schema for the XML namespace, imported by the meta-schema
+const XML_NAMESPACE_SCHEMA: &[u8] = include_bytes!("schemas/XML/xml.xsd");
+
+/// Location of the XML namespace schema in the meta-schema's import
+const XML_NAMESPACE_SCHEMA_LOCATION: &str = "http://www.w3.org/2001/xml.xsd";
+
+static META_SCHEMA: OnceLock<XsdSchema> = OnceLock::new();
+
+/// The XSD 1.0 meta-schema, built on first use
+fn meta_schema() -> &'static XsdSchema {
+    META_SCHEMA.get_or_init(|| {
+        let meta_schema = std::str::from_utf8(XSD_1_0_META_SCHEMA).expect("embedded meta-schema is UTF-8");
+        let xml_schema = std::str::from_utf8(XML_NAMESPACE_SCHEMA).expect("embedded xml.xsd is UTF-8");
+        XsdSchema::from_sources(&[
+            (meta_schema, "XMLSchema.xsd"),
+            (xml_schema, XML_NAMESPACE_SCHEMA_LOCATION),
+        ])
+        .expect("embedded meta-schema is a valid schema")
+    })
+}
+
+impl XsdSchema {
+    /// Validate an XSD document against the XSD 1.0 meta-schema
+    ///
+    /// Only the structure of the schema document is checked: a document that
+    /// passes may still fail to build, for instance because of a reference to
+    /// an undefined type.
+    pub fn validate_schema_source(xml: &str) -> ValidationResult {
+        meta_schema().validate_string(xml)
+    }
+
+    /// Check this schema against the XSD 1.0 meta-schema
+    ///
+    /// The schema is serialized with [`to_xsd_string`](Self::to_xsd_string)
+    /// and the resulting document is validated.
+    pub fn is_valid_xsd(&self) -> bool {
+        self.to_xsd_string()
+            .map(|xsd| Self::validate_schema_source(&xsd).valid)
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::namespaces::QName;
+    use crate::validators::builtins::XSD_NAMESPACE;
+
+    #[test]
+    fn test_meta_schema_loads() {
+        let schema_element = QName::namespaced(XSD_NAMESPACE, "schema");
+        assert!(meta_schema().lookup_element(&schema_element).is_some());
+    }
+
+    #[test]
+    fn test_invalid_schema_source() {
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element nam="item"/>
+</xs:schema>"#;
+        let result = XsdSchema::validate_schema_source(xsd);
+        assert!(!result.valid);
+        assert!(result.error_messages().iter().any(|msg| msg.contains("'name'")));
+    }
+
+    #[test]
+    fn test_unknown_schema_component() {
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:elements name="item"/>
+</xs:schema>"#;
+        assert!(!XsdSchema::validate_schema_source(xsd).valid);
+    }
+
+    #[test]
+    fn test_not_well_formed_schema_source() {
+        assert!(!XsdSchema::validate_schema_source("<xs:schema").valid);
+    }
+}
@@ -6,9 +6,11 @@
 pub mod base;
 pub mod helpers;         // Helper utilities ✅
 pub mod particles;       // Particle components ✅
+pub mod annotations;     // Schema annotations ✅
 
 // Wave 5: Type system
 pub mod facets;       // Facet validators ✅
+pub mod regex;        // XSD regular expressions ✅
 pub mod builtins;     // Built-in types ✅
 pub mod simple_types; // Simple type validators ✅
 pub mod attributes;   // Attribute validators ✅
@@ -35,24 +37,29 @@ pub mod validation;      // Validation context and traits ✅
 
 // Wave 10: XSD Document Parsing
 pub mod parsing;         // XSD document parser ✅
+pub mod meta_schema;     // XSD meta-schema validation ✅
 
 // Wave 11: Document Validation
 pub mod document_validation;  // XML document validation ✅
+pub mod streaming;            // Streaming document validation ✅
+pub mod decoding;             // JSON decoding and encoding ✅
 
 // Re-exports
 pub use base::{
     AttributeValidator, ElementValidator, TypeValidator, ValidationMode, ValidationStatus,
     ValidityStatus, Validator, XsdValidator,
 };
+pub use annotations::XsdAnnotation;
 pub use facets::{
-    EnumerationFacet, LengthFacet, MaxInclusiveFacet, MaxLengthFacet, MinInclusiveFacet,
+    EnumerationFacet, ExplicitTimezoneFacet, LengthFacet, MaxInclusiveFacet, MaxLengthFacet, MinInclusiveFacet,
     MinLengthFacet, NumericBound, PatternFacet, WhiteSpace,
 };
+pub use self::regex::xsd_pattern_to_rust;
 pub use helpers::{
     base64_binary_validator, boolean_to_rust, byte_validator, decimal_validator,
     float_to_rust, hex_binary_validator, int_to_rust, int_validator, long_validator,
     negative_int_validator, non_negative_int_validator, non_positive_int_validator,
-    positive_int_validator, qname_validator, rust_to_boolean, rust_to_float, rust_to_int,
+    normalize_attribute_value, positive_int_validator, qname_validator, rust_to_boolean, rust_to_float, rust_to_int,
     short_validator, unsigned_byte_validator, unsigned_int_validator, unsigned_long_validator,
     unsigned_short_validator, XSD_BOOLEAN_MAP,
 };
@@ -76,8 +83,8 @@ pub use builtins::{
     XSD_EXPLICIT_TIMEZONE,
 };
 pub use simple_types::{
-    FacetSet, SimpleType, SimpleTypeVariety, XsdAtomicType, XsdListType,
-    XsdRestrictedType, XsdUnionType,
+    FacetSet, PlaceholderType, SimpleType, SimpleTypeVariety, XsdAtomicType,
+    XsdListType, XsdRestrictedType, XsdUnionType,
 };
 pub use attributes::{
     AttributeForm, AttributeScope, AttributeUse, XsdAttribute, XsdAttributeGroup,
@@ -92,12 +99,13 @@ pub use groups::{
     ElementParticle, GroupParticle, ModelType, XsdGroup,
 };
 pub use models::{
-    AdvanceYield, ContentItem, ContentKey, InterleavedModelVisitor, ModelVisitor,
-    SuffixedModelVisitor, check_model, distinguishable_paths, sort_content,
+    AdvanceYield, AmbiguityReport, AmbiguityViolation, ContentItem, ContentKey,
+    InterleavedModelVisitor, ModelVisitor, SuffixedModelVisitor, UpaError,
+    check_model, check_unique_particle_attribution, check_upa, distinguishable_paths, sort_content,
 };
 pub use complex_types::{
     ComplexContent, ComplexTypeBuilder, ContentTypeLabel, DerivationFlags,
-    DerivationMethod, OpenContentMode, XsdComplexType, XsdOpenContent,
+    DerivationMethod, OpenContentMode, XsdAlternative, XsdComplexType, XsdOpenContent,
 };
 pub use elements::{
     ElementForm, ElementScope, ElementType, XsdElement, XsdElementBuilder,
@@ -114,8 +122,9 @@ pub use builders::{
     BuildContext, StagedItem, StagedMap, XsdBuilders, XsdVersion,
 };
 pub use schemas::{
-    DerivationDefault, FormDefault, NamespaceView, SchemaCollection, SchemaImport,
-    SchemaInclude, SchemaSource, ValidationResult, XsdSchema,
+    AttributeSummary, DerivationDefault, ElementSummary, FormDefault, LaxValidationResult, LintKind, LintWarning, NamespaceUsageError, NamespaceUsageKind, NamespaceView,
+    SchemaCollection, SchemaImport, SchemaLintReport,
+    OptionalContent, RequiredContent, SchemaInclude, SchemaSource, TypeExplanation, TypeKind, ValidationResult, XsdSchema,
     XML_NAMESPACE, XSI_NAMESPACE, VC_NAMESPACE,
 };
 pub use assertions::{
@@ -127,10 +136,11 @@ pub use exceptions::{
     XsdValidatorError,
 };
 pub use validation::{
-    DecimalTypePreference, DecodeContext, EncodeContext, ValidationContext,
+    CustomValidator, DecimalTypePreference, DecodeContext, EncodeContext, IdrefUse, ValidationContext,
     ValidationOutcome, XmlDecoder, XmlEncoder, XmlValidator,
 };
 pub use document_validation::validate_document;
+pub use streaming::{StreamingValidator, ValidationEvent};
 
 /// Type alias for backward compatibility
 pub type Schema = XsdSchema;
@@ -14,7 +14,7 @@ use crate::error::{ParseError, Result};
 use crate::namespaces::QName;
 
 use super::elements::XsdElement;
-use super::groups::{GroupParticle, XsdGroup};
+use super::groups::{GroupParticle, ModelType, XsdGroup};
 use super::particles::{OccursCounter, Particle};
 use super::wildcards::XsdAnyElement;
 
@@ -62,6 +62,9 @@ pub struct ModelVisitor {
 
     /// Whether current group has a match
     matched: bool,
+
+    /// Element names tried by the last match
+    tried: Vec<QName>,
 }
 
 impl ModelVisitor {
@@ -76,6 +79,7 @@ impl ModelVisitor {
             group,
             position: 0,
             matched: false,
+            tried: Vec::new(),
         };
         visitor.start();
         visitor
@@ -89,6 +93,7 @@ impl ModelVisitor {
         self.group = Arc::clone(&self.root);
         self.position = 0;
         self.matched = false;
+        self.tried.clear();
     }
 
     /// Restart the visitor
@@ -98,46 +103,145 @@ impl ModelVisitor {
     }
 
     /// Initialize by finding the first element
+    ///
+    /// A choice group that has matched an element is left after its
+    /// matched branch, instead of moving on to the other alternatives.
+    /// A group that has matched is repeated while under its maxOccurs.
     fn start(&mut self) {
-        while self.position < self.group.len() {
-            if let Some(particle) = self.group.particles.get(self.position) {
-                match particle {
-                    GroupParticle::Group(inner_group) => {
-                        // Push current state and descend into group
-                        self.groups_stack.push((
-                            Arc::clone(&self.group),
-                            self.position + 1,
-                            self.matched,
-                        ));
-                        self.group = Arc::clone(inner_group);
-                        self.position = 0;
-                        self.matched = false;
+        loop {
+            let choice_done = self.group.model == ModelType::Choice && self.matched;
+            match self.group.particles.get(self.position) {
+                Some(GroupParticle::Group(inner_group)) if !choice_done => {
+                    // Push current state and descend into group
+                    self.groups_stack.push((
+                        Arc::clone(&self.group),
+                        self.position + 1,
+                        self.matched,
+                    ));
+                    self.group = Arc::clone(inner_group);
+                    self.position = 0;
+                    self.matched = false;
+                }
+                Some(GroupParticle::Element(_) | GroupParticle::Any(_)) if !choice_done => {
+                    self.element_index = Some(self.position);
+                    return;
+                }
+                _ => {
+                    if self.matched {
+                        let count = self.inc_occurs(group_id(&self.group));
+                        if !self.group.occurs.is_over(count) {
+                            // Start another occurrence of the group
+                            self.reset_occurs(&Arc::clone(&self.group));
+                            self.position = 0;
+                            self.matched = false;
+                            continue;
+                        }
                     }
-                    GroupParticle::Element(_) | GroupParticle::Any(_) => {
-                        self.element_index = Some(self.position);
+                    // End of current group, pop from stack
+                    if !self.pop_group() {
+                        // End of model
                         return;
                     }
                 }
-            } else {
-                // End of current group, pop from stack
-                if let Some((parent, pos, matched)) = self.groups_stack.pop() {
-                    self.group = parent;
-                    self.position = pos;
-                    self.matched = matched;
-                } else {
-                    // End of model
-                    return;
-                }
             }
         }
+    }
+
+    /// Return to the parent group, which has matched if the child group has
+    ///
+    /// A child group with completed occurrences has matched, even if its
+    /// current occurrence hasn't.
+    fn pop_group(&mut self) -> bool {
+        let completed = self.get_occurs(group_id(&self.group)) > 0;
+        match self.groups_stack.pop() {
+            Some((parent, pos, matched)) => {
+                self.group = parent;
+                self.position = pos;
+                self.matched = matched || self.matched || completed;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget the occurrences counted inside a group, before it repeats
+    fn reset_occurs(&mut self, group: &XsdGroup) {
+        for particle in &group.particles {
+            self.occurs.remove(&particle_id(particle));
+            if let GroupParticle::Group(nested) = particle {
+                self.reset_occurs(nested);
+            }
+        }
+    }
 
-        // Try popping from stack if we've exhausted current group
-        if let Some((parent, pos, matched)) = self.groups_stack.pop() {
-            self.group = parent;
-            self.position = pos;
-            self.matched = matched;
+    /// Leave the groups of the current branch that haven't matched yet
+    ///
+    /// Unmatched groups are left up to the nearest one that already has
+    /// the occurrences it requires, which is then left too.
+    fn leave_group(&mut self) -> bool {
+        if self.matched {
+            return false;
+        }
+        if !self.group.occurs.is_missing(self.get_occurs(group_id(&self.group))) {
+            self.pop_group();
+            self.element_index = None;
             self.start();
+            return true;
+        }
+        for (depth, (parent, _, parent_matched)) in self.groups_stack.iter().enumerate().rev() {
+            if *parent_matched {
+                return false;
+            }
+            if !parent.occurs.is_missing(self.get_occurs(group_id(parent))) {
+                self.groups_stack.truncate(depth + 1);
+                self.pop_group();
+                self.pop_group();
+                self.element_index = None;
+                self.start();
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Move to the next alternative of the current choice group
+    ///
+    /// The last alternative of a required choice can't be passed, since the
+    /// choice would then be left without any match.
+    fn next_alternative(&mut self) -> bool {
+        if self.position + 1 >= self.group.len() && self.group.occurs.is_missing(self.get_occurs(group_id(&self.group))) {
+            return false;
+        }
+        self.position += 1;
+        self.element_index = None;
+        self.start();
+        true
+    }
+
+    /// Give up the current branch of a choice, if none of it matched yet
+    ///
+    /// Unmatched groups are left up to the nearest enclosing choice that
+    /// hasn't matched either, which then moves to its next alternative.
+    fn abandon_branch(&mut self) -> bool {
+        if self.matched {
+            return false;
+        }
+        for (depth, (parent, next, parent_matched)) in self.groups_stack.iter().enumerate().rev() {
+            if *parent_matched {
+                return false;
+            }
+            if parent.model == ModelType::Choice {
+                if *next >= parent.len() && parent.occurs.is_missing(self.get_occurs(group_id(parent))) {
+                    return false;
+                }
+                self.groups_stack.truncate(depth + 1);
+                self.pop_group();
+                self.element_index = None;
+                self.start();
+                return true;
+            }
         }
+        false
     }
 
     /// Get the current element particle, if any
@@ -176,63 +280,84 @@ impl ModelVisitor {
         expected
     }
 
+    /// Get the element names tried by the last call to `match_element`
+    pub fn tried(&self) -> &[QName] {
+        &self.tried
+    }
+
     /// Match an element by tag name
     ///
     /// If the current particle doesn't match but has satisfied its minOccurs,
-    /// this will advance past it to try subsequent particles.
+    /// this will advance past it to try subsequent particles. Alternatives of
+    /// a choice are tried in definition order until one of them matches.
     pub fn match_element(&mut self, tag: &str) -> Option<QName> {
+        self.match_element_with_heads(tag, &[])
+    }
+
+    /// Match an element by tag name, or as a member of a substitution group
+    ///
+    /// `heads` are the heads of the substitution groups the element belongs
+    /// to: a particle referencing any of them matches the element too.
+    pub fn match_element_with_heads(&mut self, tag: &str, heads: &[QName]) -> Option<QName> {
+        self.tried.clear();
+
         // Try to match at current position, advancing past satisfied particles if needed
-        loop {
-            if let Some(particle) = self.current_element() {
-                // Try to match current particle
-                let matched = match particle {
-                    GroupParticle::Element(elem) => {
-                        if elem.name.local_name == tag {
-                            Some(elem.name.clone())
-                        } else {
-                            None
-                        }
-                    }
-                    GroupParticle::Any(any) => {
-                        if any.matches_tag(tag) {
-                            Some(QName::local(tag))
-                        } else {
-                            None
-                        }
+        while let Some(particle) = self.current_element().cloned() {
+            // Try to match current particle
+            let matched = match &particle {
+                GroupParticle::Element(elem) => {
+                    self.tried.push(elem.name.clone());
+                    if elem.matches(tag, heads) {
+                        Some(elem.name.clone())
+                    } else {
+                        None
                     }
-                    GroupParticle::Group(_) => None,
-                };
-
-                if matched.is_some() {
-                    return matched;
                 }
-
-                // Current particle didn't match - check if we can skip it
-                // (i.e., its minOccurs has been satisfied)
-                if let Some(idx) = self.element_index {
-                    let occurs = self.get_occurs(idx);
-                    let can_skip = match particle {
-                        GroupParticle::Element(elem) => !elem.occurs.is_missing(occurs),
-                        GroupParticle::Any(any) => !any.occurs().is_missing(occurs),
-                        GroupParticle::Group(group) => !group.occurs.is_missing(occurs),
-                    };
-
-                    if can_skip {
-                        // Advance to next particle and try again
-                        self.position += 1;
-                        self.element_index = None;
-                        self.start();
-                        continue;
+                GroupParticle::Any(any) => {
+                    if any.matches_tag(tag) {
+                        Some(QName::local(tag))
+                    } else {
+                        None
                     }
                 }
+                GroupParticle::Group(_) => None,
+            };
 
-                // Can't skip current particle - no match
-                return None;
-            } else {
-                // No current element (model ended)
-                return None;
+            if matched.is_some() {
+                return matched;
+            }
+
+            // Current particle didn't match - check if we can skip it
+            // (i.e., its minOccurs has been satisfied)
+            let occurs = self.get_occurs(particle_id(&particle));
+            let can_skip = match &particle {
+                GroupParticle::Element(elem) => !elem.occurs.is_missing(occurs),
+                GroupParticle::Any(any) => !any.occurs().is_missing(occurs),
+                GroupParticle::Group(group) => !group.occurs.is_missing(occurs),
+            };
+
+            if can_skip {
+                // Advance to next particle and try again
+                self.position += 1;
+                self.element_index = None;
+                self.start();
+                continue;
             }
+
+            // A choice can still try its other alternatives
+            if self.group.model == ModelType::Choice && !self.matched && self.next_alternative() {
+                continue;
+            }
+            if self.abandon_branch() || self.leave_group() {
+                continue;
+            }
+
+            // Can't skip current particle - no match
+            return None;
         }
+
+        // No current element (model ended)
+        None
     }
 
     /// Advance the visitor, optionally with a match
@@ -246,17 +371,13 @@ impl ModelVisitor {
         }
 
         if matched {
-            if let Some(idx) = self.element_index {
-                let id = idx; // Use index as ID
-                self.inc_occurs(id);
+            if let Some(particle) = self.current_element().cloned() {
+                let occurs = self.inc_occurs(particle_id(&particle));
                 self.matched = true;
 
                 // Check if we need to stay on current element (not over max)
-                if let Some(particle) = self.current_element() {
-                    let occurs = self.get_occurs(id);
-                    if !particle.is_over(occurs) {
-                        return errors;
-                    }
+                if !particle.is_over(occurs) {
+                    return errors;
                 }
             }
         }
@@ -325,6 +446,23 @@ impl ModelVisitor {
     }
 }
 
+/// Identify a particle for occurrence counting
+///
+/// Particles are shared through `Arc`s, so the address of the particle is
+/// unique within a content model, unlike its index in its group.
+fn particle_id(particle: &GroupParticle) -> usize {
+    match particle {
+        GroupParticle::Element(elem) => Arc::as_ptr(elem) as *const () as usize,
+        GroupParticle::Any(any) => Arc::as_ptr(any) as *const () as usize,
+        GroupParticle::Group(group) => Arc::as_ptr(group) as *const () as usize,
+    }
+}
+
+/// Identify a group for occurrence counting, as its particle would be
+fn group_id(group: &Arc<XsdGroup>) -> usize {
+    Arc::as_ptr(group) as *const () as usize
+}
+
 impl GroupParticle {
     /// Check if this particle's occurs are over the maximum
     fn is_over(&self, count: u32) -> bool {
@@ -423,6 +561,295 @@ pub fn check_model(group: &XsdGroup) -> Result<()> {
     Ok(())
 }
 
+/// Report of Unique Particle Attribution (UPA) violations in a schema
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AmbiguityReport {
+    /// Ambiguous content models found
+    pub violations: Vec<AmbiguityViolation>,
+}
+
+impl AmbiguityReport {
+    /// Check if no ambiguity was found
+    pub fn is_empty(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// An element name that can be matched by more than one particle
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmbiguityViolation {
+    /// Complex type with the ambiguous content model (the element name for
+    /// anonymous types)
+    pub type_qname: QName,
+    /// Element name matched by more than one particle
+    pub ambiguous_element: QName,
+    /// Model paths of the competing particles, e.g. `sequence/choice[2]/a`
+    pub competing_particles: Vec<String>,
+}
+
+impl std::fmt::Display for AmbiguityViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Content model of '{}' violates Unique Particle Attribution: element '{}' matches {}",
+            self.type_qname.to_string(),
+            self.ambiguous_element.to_string(),
+            self.competing_particles.join(" and ")
+        )
+    }
+}
+
+/// An element name of a content model that can be matched by more than one particle
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpaError {
+    /// Element name matched by more than one particle
+    pub ambiguous_element: QName,
+    /// Model paths of the competing particles, e.g. `sequence/choice[2]/a`
+    pub competing_particles: Vec<String>,
+}
+
+impl std::fmt::Display for UpaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "element '{}' matches {}",
+            self.ambiguous_element.to_string(),
+            self.competing_particles.join(" and ")
+        )
+    }
+}
+
+impl std::error::Error for UpaError {}
+
+/// A leaf particle of a content model
+struct Position<'a> {
+    particle: &'a GroupParticle,
+    path: String,
+}
+
+/// First and last positions of a model fragment
+struct Fragment {
+    first: Vec<usize>,
+    last: Vec<usize>,
+    nullable: bool,
+}
+
+impl Fragment {
+    fn empty() -> Self {
+        Self { first: Vec::new(), last: Vec::new(), nullable: true }
+    }
+}
+
+/// Positions and follow sets of a content model (its Glushkov automaton)
+struct ParticleAutomaton<'a> {
+    positions: Vec<Position<'a>>,
+    follow: Vec<Vec<usize>>,
+}
+
+impl<'a> ParticleAutomaton<'a> {
+    fn add_follow(&mut self, from: &[usize], to: &[usize]) {
+        for &position in from {
+            for &next in to {
+                if !self.follow[position].contains(&next) {
+                    self.follow[position].push(next);
+                }
+            }
+        }
+    }
+
+    /// Build the fragment of a particle, applying its occurrence constraints
+    fn particle(&mut self, particle: &'a GroupParticle, path: String, depth: usize) -> Fragment {
+        let occurs = match particle {
+            GroupParticle::Element(ep) => ep.occurs,
+            GroupParticle::Any(any) => any.occurs(),
+            GroupParticle::Group(group) => group.occurs,
+        };
+        if occurs.max == Some(0) {
+            return Fragment::empty();
+        }
+
+        let mut fragment = match particle {
+            GroupParticle::Group(group) => self.group(group, path, depth + 1),
+            _ => {
+                self.positions.push(Position { particle, path });
+                self.follow.push(Vec::new());
+                let position = self.positions.len() - 1;
+                Fragment { first: vec![position], last: vec![position], nullable: false }
+            }
+        };
+
+        // A fixed number of repetitions is resolved by counting, so only
+        // variable repetitions can compete with what follows
+        if occurs.max.is_none_or(|max| max > 1 && max > occurs.min) {
+            let (last, first) = (fragment.last.clone(), fragment.first.clone());
+            self.add_follow(&last, &first);
+        }
+        fragment.nullable |= occurs.min == 0;
+        fragment
+    }
+
+    /// Build the fragment of a model group
+    fn group(&mut self, group: &'a XsdGroup, path: String, depth: usize) -> Fragment {
+        if depth > 100 {
+            return Fragment::empty();
+        }
+
+        // Steps are numbered among siblings of the same name, as in XPath
+        let steps: Vec<String> = group
+            .particles
+            .iter()
+            .map(|particle| match particle {
+                GroupParticle::Element(ep) => ep.name.local_name.clone(),
+                GroupParticle::Any(_) => "any".to_string(),
+                GroupParticle::Group(nested) => model_name(nested.model).to_string(),
+            })
+            .collect();
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut children = Vec::new();
+        for (particle, step) in group.particles.iter().zip(&steps) {
+            let count = counts.entry(step.as_str()).or_insert(0);
+            *count += 1;
+            let step = if steps.iter().filter(|s| *s == step).count() > 1 {
+                format!("{}[{}]", step, count)
+            } else {
+                step.clone()
+            };
+            children.push(self.particle(particle, format!("{}/{}", path, step), depth));
+        }
+
+        match group.model {
+            ModelType::Sequence => {
+                let mut fragment = Fragment::empty();
+                for child in children {
+                    self.add_follow(&fragment.last, &child.first);
+                    if fragment.nullable {
+                        fragment.first.extend(&child.first);
+                    }
+                    if child.nullable {
+                        fragment.last.extend(child.last);
+                    } else {
+                        fragment.last = child.last;
+                    }
+                    fragment.nullable &= child.nullable;
+                }
+                fragment
+            }
+            ModelType::Choice => {
+                let mut fragment = Fragment { first: Vec::new(), last: Vec::new(), nullable: children.is_empty() };
+                for child in children {
+                    fragment.first.extend(child.first);
+                    fragment.last.extend(child.last);
+                    fragment.nullable |= child.nullable;
+                }
+                fragment
+            }
+            ModelType::All => {
+                // Any particle of an all group can follow any other one
+                for (i, child) in children.iter().enumerate() {
+                    for (j, other) in children.iter().enumerate() {
+                        if i != j {
+                            self.add_follow(&child.last, &other.first);
+                        }
+                    }
+                }
+                let mut fragment = Fragment::empty();
+                for child in children {
+                    fragment.first.extend(child.first);
+                    fragment.last.extend(child.last);
+                    fragment.nullable &= child.nullable;
+                }
+                fragment
+            }
+        }
+    }
+
+    /// Get the element name matched by both positions, if any
+    fn competing_name(&self, position1: usize, position2: usize) -> Option<QName> {
+        match (self.positions[position1].particle, self.positions[position2].particle) {
+            (GroupParticle::Element(e1), GroupParticle::Element(e2)) if e1.name == e2.name => {
+                Some(e1.name.clone())
+            }
+            (GroupParticle::Element(e), GroupParticle::Any(any))
+            | (GroupParticle::Any(any), GroupParticle::Element(e))
+                if any.is_matching(&e.name.to_string(), None) =>
+            {
+                Some(e.name.clone())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Get the tag name of a model group
+fn model_name(model: ModelType) -> &'static str {
+    match model {
+        ModelType::Sequence => "sequence",
+        ModelType::Choice => "choice",
+        ModelType::All => "all",
+    }
+}
+
+/// Find Unique Particle Attribution violations in a content model
+///
+/// Builds the particle automaton of the model: each element and wildcard
+/// particle is a position, and the positions that can match the first
+/// element or follow another one (through sequences, choices, optional
+/// and repeated particles) are computed. The model is ambiguous when one
+/// of these sets holds two positions that can match the same element
+/// name, such as two choice branches starting with the same element or an
+/// optional element followed by an element of the same name. Wildcards are
+/// compared with element particles but not with each other.
+pub fn check_unique_particle_attribution(type_qname: &QName, group: &XsdGroup) -> Vec<AmbiguityViolation> {
+    find_upa_errors(group)
+        .into_iter()
+        .map(|error| AmbiguityViolation {
+            type_qname: type_qname.clone(),
+            ambiguous_element: error.ambiguous_element,
+            competing_particles: error.competing_particles,
+        })
+        .collect()
+}
+
+/// Check that a content model satisfies Unique Particle Attribution
+///
+/// Returns the first ambiguity found, see
+/// [`check_unique_particle_attribution`] for how the model is checked.
+pub fn check_upa(group: &XsdGroup) -> std::result::Result<(), UpaError> {
+    match find_upa_errors(group).into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Find the ambiguous element names of a content model, with their competing particles
+fn find_upa_errors(group: &XsdGroup) -> Vec<UpaError> {
+    let mut automaton = ParticleAutomaton { positions: Vec::new(), follow: Vec::new() };
+    let root = automaton.group(group, model_name(group.model).to_string(), 0);
+
+    let mut errors: Vec<UpaError> = Vec::new();
+    let states = std::iter::once(&root.first).chain(automaton.follow.iter());
+    for state in states {
+        for (i, &position1) in state.iter().enumerate() {
+            for &position2 in &state[i + 1..] {
+                let Some(name) = automaton.competing_name(position1, position2) else {
+                    continue;
+                };
+                let mut competing = vec![
+                    automaton.positions[position1].path.clone(),
+                    automaton.positions[position2].path.clone(),
+                ];
+                competing.sort();
+
+                let error = UpaError { ambiguous_element: name, competing_particles: competing };
+                if !errors.contains(&error) {
+                    errors.push(error);
+                }
+            }
+        }
+    }
+    errors
+}
+
 /// Interleaved model visitor for openContent models.
 ///
 /// Handles XSD 1.1 openContent with interleave mode.
@@ -664,6 +1091,79 @@ mod tests {
         assert!(matched_a || matched_b);
     }
 
+    #[test]
+    fn test_model_visitor_choice_branches() {
+        let mut branch = XsdGroup::new(ModelType::Sequence);
+        branch.particles.push(make_element("b"));
+        branch.particles.push(make_element("c"));
+        let mut choice = XsdGroup::new(ModelType::Choice);
+        choice.particles.push(make_element("a"));
+        choice.particles.push(GroupParticle::Group(Arc::new(branch)));
+        let mut group = XsdGroup::new(ModelType::Sequence);
+        group.particles.push(GroupParticle::Group(Arc::new(choice)));
+        group.particles.push(make_element("d"));
+        let group = Arc::new(group);
+
+        // The second branch is chosen, then the choice is left
+        let mut visitor = ModelVisitor::new(Arc::clone(&group));
+        for tag in ["b", "c", "d"] {
+            assert!(visitor.match_element(tag).is_some(), "{}", tag);
+            visitor.advance(true);
+        }
+        assert!(visitor.is_ended());
+
+        // Once a branch has matched, the other ones are not available
+        let mut visitor = ModelVisitor::new(Arc::clone(&group));
+        assert!(visitor.match_element("a").is_some());
+        visitor.advance(true);
+        assert!(visitor.match_element("b").is_none());
+
+        // A required choice can't be passed over
+        let mut visitor = ModelVisitor::new(group);
+        assert!(visitor.match_element("d").is_none());
+        let tried: Vec<&str> = visitor.tried().iter().map(|name| name.local_name.as_str()).collect();
+        assert_eq!(tried, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_model_visitor_repeated_groups() {
+        let mut choice = XsdGroup::new(ModelType::Choice);
+        choice.occurs = Occurs::new(0, None);
+        choice.particles.push(make_element("a"));
+        choice.particles.push(make_element("b"));
+        let mut pair = XsdGroup::new(ModelType::Sequence);
+        pair.occurs = Occurs::new(1, Some(2));
+        pair.particles.push(make_element("c"));
+        pair.particles.push(make_element("d"));
+        let mut group = XsdGroup::new(ModelType::Sequence);
+        group.particles.push(GroupParticle::Group(Arc::new(choice)));
+        group.particles.push(GroupParticle::Group(Arc::new(pair)));
+        group.particles.push(make_element("e"));
+        let group = Arc::new(group);
+
+        let mut visitor = ModelVisitor::new(Arc::clone(&group));
+        for tag in ["a", "b", "a", "c", "d", "c", "d", "e"] {
+            assert!(visitor.match_element(tag).is_some(), "{}", tag);
+            visitor.advance(true);
+        }
+        assert!(visitor.is_ended());
+
+        // A repeated group is left once it has its required occurrences
+        let mut visitor = ModelVisitor::new(Arc::clone(&group));
+        for tag in ["c", "d", "e"] {
+            assert!(visitor.match_element(tag).is_some(), "{}", tag);
+            visitor.advance(true);
+        }
+
+        // But not beyond its maxOccurs
+        let mut visitor = ModelVisitor::new(group);
+        for tag in ["c", "d", "c", "d"] {
+            assert!(visitor.match_element(tag).is_some(), "{}", tag);
+            visitor.advance(true);
+        }
+        assert!(visitor.match_element("c").is_none());
+    }
+
     #[test]
     fn test_model_visitor_restart() {
         let mut group = XsdGroup::new(ModelType::Sequence);
@@ -696,6 +1196,123 @@ mod tests {
         assert!(check_model(&group).is_ok());
     }
 
+    fn make_occurs(name: &str, min: u32, max: Option<u32>) -> GroupParticle {
+        GroupParticle::Element(Arc::new(ElementParticle::new(
+            QName::local(name),
+            Occurs { min, max },
+        )))
+    }
+
+    fn ambiguous_names(group: &XsdGroup) -> Vec<String> {
+        check_unique_particle_attribution(&QName::local("test"), group)
+            .into_iter()
+            .map(|v| v.ambiguous_element.local_name)
+            .collect()
+    }
+
+    #[test]
+    fn test_upa_deterministic_models() {
+        let mut group = XsdGroup::new(ModelType::Sequence);
+        group.particles.push(make_element("a"));
+        group.particles.push(make_occurs("b", 0, None));
+        group.particles.push(make_element("c"));
+        assert!(ambiguous_names(&group).is_empty());
+
+        // A fixed count is resolved by counting occurrences
+        let mut group = XsdGroup::new(ModelType::Sequence);
+        group.particles.push(make_occurs("a", 2, Some(2)));
+        group.particles.push(make_element("a"));
+        assert!(ambiguous_names(&group).is_empty());
+
+        let mut group = XsdGroup::new(ModelType::Choice);
+        group.particles.push(make_element("a"));
+        group.particles.push(make_element("b"));
+        assert!(ambiguous_names(&group).is_empty());
+    }
+
+    #[test]
+    fn test_upa_choice_with_same_first_element() {
+        // (a, b) | (a, c)
+        let mut first = XsdGroup::new(ModelType::Sequence);
+        first.particles.push(make_element("a"));
+        first.particles.push(make_element("b"));
+        let mut second = XsdGroup::new(ModelType::Sequence);
+        second.particles.push(make_element("a"));
+        second.particles.push(make_element("c"));
+        let mut group = XsdGroup::new(ModelType::Choice);
+        group.particles.push(GroupParticle::Group(Arc::new(first)));
+        group.particles.push(GroupParticle::Group(Arc::new(second)));
+
+        let violations = check_unique_particle_attribution(&QName::local("pairType"), &group);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].type_qname, QName::local("pairType"));
+        assert_eq!(violations[0].ambiguous_element, QName::local("a"));
+        assert_eq!(
+            violations[0].competing_particles,
+            vec!["choice/sequence[1]/a".to_string(), "choice/sequence[2]/a".to_string()]
+        );
+        assert!(violations[0].to_string().contains("element 'a' matches"));
+    }
+
+    #[test]
+    fn test_upa_optional_and_repeated_particles() {
+        // a?, a
+        let mut group = XsdGroup::new(ModelType::Sequence);
+        group.particles.push(make_occurs("a", 0, Some(1)));
+        group.particles.push(make_element("a"));
+        assert_eq!(ambiguous_names(&group), vec!["a"]);
+
+        // a+, a
+        let mut group = XsdGroup::new(ModelType::Sequence);
+        group.particles.push(make_occurs("a", 1, None));
+        group.particles.push(make_element("a"));
+        assert_eq!(ambiguous_names(&group), vec!["a"]);
+
+        // (a, b?)+ followed by b: after a, b may belong to either particle
+        let mut inner = XsdGroup::new(ModelType::Sequence);
+        inner.particles.push(make_element("a"));
+        inner.particles.push(make_occurs("b", 0, Some(1)));
+        inner.occurs = Occurs { min: 1, max: None };
+        let mut group = XsdGroup::new(ModelType::Sequence);
+        group.particles.push(GroupParticle::Group(Arc::new(inner)));
+        group.particles.push(make_element("b"));
+        assert_eq!(ambiguous_names(&group), vec!["b"]);
+    }
+
+    #[test]
+    fn test_upa_wildcard_competing_with_element() {
+        // any?, a
+        let mut group = XsdGroup::new(ModelType::Sequence);
+        group.particles.push(GroupParticle::Any(Arc::new(XsdAnyElement::with_settings(
+            crate::validators::wildcards::NamespaceConstraint::Any,
+            crate::validators::wildcards::ProcessContents::Lax,
+            Occurs { min: 0, max: Some(1) },
+            None,
+        ))));
+        group.particles.push(make_element("a"));
+
+        let violations = check_unique_particle_attribution(&QName::local("test"), &group);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].competing_particles, vec!["sequence/a".to_string(), "sequence/any".to_string()]);
+    }
+
+    #[test]
+    fn test_check_upa() {
+        let mut group = XsdGroup::new(ModelType::Sequence);
+        group.particles.push(make_element("a"));
+        group.particles.push(make_occurs("b", 0, None));
+        assert_eq!(check_upa(&group), Ok(()));
+
+        // a?, a?: the first a may belong to either particle
+        let mut group = XsdGroup::new(ModelType::Sequence);
+        group.particles.push(make_occurs("a", 0, Some(1)));
+        group.particles.push(make_occurs("a", 0, Some(1)));
+        let error = check_upa(&group).unwrap_err();
+        assert_eq!(error.ambiguous_element, QName::local("a"));
+        assert_eq!(error.competing_particles, vec!["sequence/a[1]".to_string(), "sequence/a[2]".to_string()]);
+        assert_eq!(error.to_string(), "element 'a' matches sequence/a[1] and sequence/a[2]");
+    }
+
     #[test]
     fn test_content_key() {
         let index_key = ContentKey::Index(0);
@@ -3,26 +3,43 @@
 //! This module provides parsing of XSD schema documents into XsdSchema structures.
 
 use std::collections::VecDeque;
+use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use super::annotations::XsdAnnotation;
+use super::assertions::{AssertionList, XPathDefaultNamespace, XsdAssert};
 use super::attributes::{AttributeUse, XsdAttribute, XsdAttributeGroup};
 use super::base::Validator;
 use super::builders::XsdVersion;
-use super::complex_types::{ComplexContent, DerivationMethod, XsdComplexType};
+use super::complex_types::{
+    ComplexContent, DerivationFlags, DerivationMethod, XsdAlternative, XsdComplexType,
+};
 use super::elements::{ElementType, XsdElement};
 use super::globals::GlobalType;
 use super::groups::{ElementParticle, GroupParticle, ModelType, XsdGroup};
+use super::identities::{IdentityBuilder, IdentityConstraintKind};
 use super::particles::Occurs;
-use super::schemas::{DerivationDefault, FormDefault, RedefinedComponent, SchemaRedefine, XsdSchema};
+use super::schemas::{
+    DerivationDefault, FormDefault, RedefinedComponent, SchemaInclude, SchemaRedefine, XsdSchema,
+};
 use super::simple_types::{XsdAtomicType, XsdListType, XsdRestrictedType, XsdUnionType};
 use super::builtins::XSD_NAMESPACE;
+use super::facets::{
+    ExplicitTimezoneFacet, MaxExclusiveFacet, MaxInclusiveFacet, MinExclusiveFacet, MinInclusiveFacet, NumericBound,
+    PatternFacet, WhiteSpace,
+};
+use super::regex::xsd_pattern_to_rust;
 use super::wildcards::{NamespaceConstraint, ProcessContents, XsdAnyAttribute, XsdAnyElement};
 
 use crate::catalog::XmlCatalog;
 use crate::documents::{Document, Element};
+use crate::dtd::DoctypeWarning;
 use crate::error::{Error, ParseError, Result};
-use crate::loaders::Loader;
+use crate::loaders::{
+    is_http_location, normalize_uri, read_schema_resource, HttpCache, InMemoryLoader, Loader, ResourceLoader,
+    SchemaCache,
+};
 use crate::locations::Location;
 use crate::namespaces::QName;
 
@@ -32,6 +49,30 @@ struct PendingSchemaWork {
     path: PathBuf,
     /// Parent namespace (for chameleon include handling)
     parent_namespace: Option<String>,
+    /// Location as written in the including schema (schemaLocation)
+    location: String,
+    /// Redefinitions to apply once the schema is loaded (xs:redefine only)
+    redefine: Option<PendingRedefine>,
+}
+
+impl PendingSchemaWork {
+    /// Work item for the primary schema
+    fn root(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            parent_namespace: None,
+            location: path.to_string_lossy().to_string(),
+            redefine: None,
+        }
+    }
+}
+
+/// Redefinitions of an xs:redefine, waiting for the redefined schema
+struct PendingRedefine {
+    /// Empty schema with the parsing context of the redefining schema
+    context: XsdSchema,
+    /// Components to be redefined
+    redefinitions: Vec<RedefinedComponent>,
 }
 
 /// XSD element local names
@@ -47,6 +88,7 @@ mod xsd_elements {
     pub const CHOICE: &str = "choice";
     pub const ALL: &str = "all";
     pub const ANNOTATION: &str = "annotation";
+    pub const DOCUMENTATION: &str = "documentation";
     pub const IMPORT: &str = "import";
     pub const INCLUDE: &str = "include";
     pub const REDEFINE: &str = "redefine";
@@ -59,12 +101,27 @@ mod xsd_elements {
     pub const ANY: &str = "any";
     pub const ANY_ATTRIBUTE: &str = "anyAttribute";
     pub const NOTATION: &str = "notation";
+    pub const UNIQUE: &str = "unique";
+    pub const KEY: &str = "key";
+    pub const KEYREF: &str = "keyref";
+    pub const SELECTOR: &str = "selector";
+    pub const FIELD: &str = "field";
+    pub const ASSERT: &str = "assert";
+    pub const ALTERNATIVE: &str = "alternative";
     // Facets
     pub const PATTERN: &str = "pattern";
     pub const ENUMERATION: &str = "enumeration";
     pub const MIN_LENGTH: &str = "minLength";
     pub const MAX_LENGTH: &str = "maxLength";
     pub const LENGTH: &str = "length";
+    pub const EXPLICIT_TIMEZONE: &str = "explicitTimezone";
+    pub const MIN_INCLUSIVE: &str = "minInclusive";
+    pub const MAX_INCLUSIVE: &str = "maxInclusive";
+    pub const MIN_EXCLUSIVE: &str = "minExclusive";
+    pub const MAX_EXCLUSIVE: &str = "maxExclusive";
+    pub const TOTAL_DIGITS: &str = "totalDigits";
+    pub const FRACTION_DIGITS: &str = "fractionDigits";
+    pub const WHITE_SPACE: &str = "whiteSpace";
 }
 
 /// XSD attribute names
@@ -84,6 +141,7 @@ mod xsd_attrs {
     pub const VALUE: &str = "value";
     pub const MIXED: &str = "mixed";
     pub const ABSTRACT: &str = "abstract";
+    pub const BLOCK: &str = "block";
     pub const SUBSTITUTION_GROUP: &str = "substitutionGroup";
     pub const NAMESPACE: &str = "namespace";
     pub const SCHEMA_LOCATION: &str = "schemaLocation";
@@ -94,6 +152,28 @@ mod xsd_attrs {
     pub const MIN_OCCURS: &str = "minOccurs";
     pub const MAX_OCCURS: &str = "maxOccurs";
     pub const USE: &str = "use";
+    pub const XPATH: &str = "xpath";
+    pub const REFER: &str = "refer";
+    pub const TEST: &str = "test";
+    pub const ID: &str = "id";
+    pub const XPATH_DEFAULT_NAMESPACE: &str = "xpathDefaultNamespace";
+}
+
+/// Create a built-in simple type from its internal name
+///
+/// xs:IDREFS, xs:NMTOKENS and xs:ENTITIES are non-empty lists of their item
+/// type; the other built-in types are atomic.
+fn builtin_simple_type(builtin_name: &str) -> Option<Arc<dyn super::simple_types::SimpleType + Send + Sync>> {
+    use super::builtins::{XSD_ENTITIES, XSD_ENTITY, XSD_IDREF, XSD_IDREFS, XSD_NMTOKEN, XSD_NMTOKENS};
+    let item_name = match builtin_name {
+        XSD_IDREFS => XSD_IDREF,
+        XSD_NMTOKENS => XSD_NMTOKEN,
+        XSD_ENTITIES => XSD_ENTITY,
+        _ => return XsdAtomicType::new(builtin_name).ok().map(|atomic| Arc::new(atomic) as _),
+    };
+    let item_type = Arc::new(XsdAtomicType::new(item_name).ok()?);
+    let list = XsdListType::with_name(item_type, QName::namespaced(XSD_NAMESPACE, builtin_name)).with_min_length(1);
+    Some(Arc::new(list))
 }
 
 /// Map XSD built-in type local name to the internal constant
@@ -150,6 +230,18 @@ fn resolve_builtin_name(local_name: &str) -> Option<&'static str> {
     }
 }
 
+/// Collect the errors recorded while loading a schema and its imports
+fn collect_load_errors(schema: &XsdSchema, errors: &mut Vec<ParseError>) {
+    errors.extend(schema.errors.iter().cloned());
+    let mut imports: Vec<_> = schema.imports.values().collect();
+    imports.sort_by(|a, b| a.namespace.cmp(&b.namespace));
+    for import in imports {
+        if let Some(imported) = &import.schema {
+            collect_load_errors(imported, errors);
+        }
+    }
+}
+
 /// XSD namespace
 const XSD_NS: &str = "http://www.w3.org/2001/XMLSchema";
 
@@ -160,17 +252,74 @@ impl XsdSchema {
         Self::from_document(&doc)
     }
 
+    /// Parse an XSD schema from a string, tolerating unresolved type references
+    ///
+    /// References to types that can't be resolved, for instance because an
+    /// import couldn't be loaded, are replaced with placeholders: `xs:anyType`
+    /// for elements and a permissive [`PlaceholderType`] for attributes. The
+    /// rest of the schema validates as usual.
+    ///
+    /// Returns the schema along with the load errors and a warning for each
+    /// placeholder. A document that can't be parsed as a schema gives an
+    /// empty schema and the parse error.
+    ///
+    /// [`PlaceholderType`]: crate::validators::PlaceholderType
+    pub fn from_string_lenient(xml: &str) -> (Self, Vec<ParseError>) {
+        let schema = Document::from_string(xml).and_then(|doc| Self::from_document(&doc));
+        let mut schema = match schema {
+            Ok(schema) => schema,
+            Err(Error::Parse(error)) => return (XsdSchema::new(), vec![error]),
+            Err(error) => return (XsdSchema::new(), vec![ParseError::new(error.to_string())]),
+        };
+
+        let mut errors = Vec::new();
+        collect_load_errors(&schema, &mut errors);
+        errors.extend(schema.replace_unresolved_types().into_iter().map(ParseError::new));
+        (schema, errors)
+    }
+
     /// Parse an XSD schema from bytes
     pub fn from_bytes(xml: &[u8]) -> Result<Self> {
         let doc = Document::parse(xml)?;
         Self::from_document(&doc)
     }
 
+    /// Parse an XSD schema from a reader
+    ///
+    /// The schema is parsed as it is read, through a buffer, so a schema
+    /// received over the network or extracted from an archive doesn't have
+    /// to be loaded in memory first. Relative includes and imports are
+    /// resolved from the working directory, as for [`from_string`](Self::from_string).
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        let doc = Document::from_reader(BufReader::new(reader))?;
+        Self::from_document(&doc)
+    }
+
+    /// Parse an XSD schema file, validating it against its DOCTYPE first
+    ///
+    /// Legacy schemas may declare the XSD meta-schema DTD; problems found by
+    /// DTD validation are returned as warnings alongside the parsed schema.
+    pub fn from_dtd_with_validation(path: impl AsRef<Path>) -> Result<(Self, Vec<DoctypeWarning>)> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| Error::Resource(format!("Failed to read schema '{}': {}", path.display(), e)))?;
+        let (_, warnings) = Document::from_string_validating_doctype(&content)?;
+        Ok((Self::from_file(path)?, warnings))
+    }
+
     /// Parse an XSD schema from a file path
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
         Self::from_file_with_catalog(path, None::<&Path>)
     }
 
+    /// Parse an XSD schema from a file path, or get it from a cache
+    ///
+    /// The schema is parsed again if the file was modified since it was
+    /// cached. See [`SchemaCache`].
+    pub fn from_file_cached(path: impl AsRef<Path>, cache: &SchemaCache) -> Result<Arc<Self>> {
+        cache.get_or_load(path)
+    }
+
     /// Parse an XSD schema from a file path with an XML catalog for URN resolution
     ///
     /// The catalog is used to resolve URN-based schema locations (like those in DITA 1.3)
@@ -219,28 +368,32 @@ impl XsdSchema {
 
         // Shared set to track loaded files (prevents circular includes)
         let loaded_paths = Arc::new(std::sync::Mutex::new(std::collections::HashSet::new()));
+        // Shared cache of the schemas downloaded from HTTP locations
+        let http_cache = HttpCache::default();
 
         // Queue of schemas to process (iterative worklist algorithm)
         let mut pending: VecDeque<PendingSchemaWork> = VecDeque::new();
 
         // Start with the root schema
-        pending.push_back(PendingSchemaWork {
-            path: path.to_path_buf(),
-            parent_namespace: None,
-        });
+        pending.push_back(PendingSchemaWork::root(path));
 
         // The root schema we'll return
         let mut root_schema: Option<XsdSchema> = None;
 
         // Process schemas iteratively
         while let Some(work) = pending.pop_front() {
-            // Check if already loaded
-            if let Ok(canonical) = work.path.canonicalize() {
+            // Check if already loaded; URLs are compared after normalization
+            let loaded_key = if is_http_location(&work.path.to_string_lossy()) {
+                Some(PathBuf::from(normalize_uri(&work.path.to_string_lossy())))
+            } else {
+                work.path.canonicalize().ok()
+            };
+            if let Some(key) = loaded_key {
                 let mut loaded = loaded_paths.lock().unwrap();
-                if loaded.contains(&canonical) {
+                if loaded.contains(&key) {
                     continue; // Already processed
                 }
-                loaded.insert(canonical);
+                loaded.insert(key);
             }
 
             // Load and parse this schema (without recursively processing includes)
@@ -249,6 +402,7 @@ impl XsdSchema {
                 work.parent_namespace.as_deref(),
                 catalog.clone(),
                 loaded_paths.clone(),
+                http_cache.clone(),
             );
 
             let schema = match schema_result {
@@ -263,39 +417,13 @@ impl XsdSchema {
                 }
             };
 
-            // Collect pending includes from this schema
-            for include_location in &schema.pending_include_locations {
-                let resolved_path = resolve_schema_location(
-                    include_location,
-                    schema.source.base_url.as_deref(),
-                    schema.source.catalog.as_ref().map(|c| c.as_ref()),
-                );
-                pending.push_back(PendingSchemaWork {
-                    path: resolved_path,
-                    parent_namespace: schema.target_namespace.clone(),
-                });
-            }
-
-            // Collect pending redefines
-            for redefine_location in &schema.pending_redefine_locations {
-                let resolved_path = resolve_schema_location(
-                    redefine_location,
-                    schema.source.base_url.as_deref(),
-                    schema.source.catalog.as_ref().map(|c| c.as_ref()),
-                );
-                pending.push_back(PendingSchemaWork {
-                    path: resolved_path,
-                    parent_namespace: schema.target_namespace.clone(),
-                });
-            }
-
-            if root_schema.is_none() {
-                root_schema = Some(schema);
-            } else {
+            // Collect pending includes and redefines from this schema
+            pending.extend(pending_schema_work(&schema));
+
+            match root_schema {
+                None => root_schema = Some(schema),
                 // Merge globals from this schema into the root
-                if let Some(ref mut root) = root_schema {
-                    root.maps.global_maps.merge(&schema.maps.global_maps);
-                }
+                Some(ref mut root) => merge_schema_work(root, work, schema)?,
             }
         }
 
@@ -329,6 +457,238 @@ impl XsdSchema {
 
         Ok(schema)
     }
+
+    /// Parse an XSD schema from in-memory `(xml_content, base_uri)` pairs
+    ///
+    /// The first entry is the primary schema. The remaining entries are only
+    /// loaded when an include, redefine or import refers to them: each
+    /// `schemaLocation` is resolved against the base URI of the referencing
+    /// schema and matched against the provided base URIs. No file I/O is done.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let schema = XsdSchema::from_sources(&[
+    ///     (MAIN_XSD, "schemas/main.xsd"),
+    ///     (TYPES_XSD, "schemas/types.xsd"),
+    /// ])?;
+    /// ```
+    pub fn from_sources(sources: &[(&str, &str)]) -> Result<Self> {
+        let (_, primary_uri) = sources.first().ok_or_else(|| {
+            Error::Parse(ParseError::new("No schema sources provided"))
+        })?;
+
+        let loader = sources
+            .iter()
+            .fold(InMemoryLoader::new(), |loader, (content, base_uri)| {
+                loader.with_resource(base_uri, content)
+            });
+        Self::from_loader(Arc::new(loader), primary_uri)
+    }
+
+    /// Parse the schema at `primary_uri`, loading it and the schemas it
+    /// includes, redefines or imports through `loader`
+    fn from_loader(loader: Arc<dyn ResourceLoader>, primary_uri: &str) -> Result<Self> {
+        // Shared set to track loaded URIs (prevents circular includes)
+        let loaded_paths = Arc::new(std::sync::Mutex::new(std::collections::HashSet::new()));
+        let mut pending: VecDeque<PendingSchemaWork> = VecDeque::new();
+        pending.push_back(PendingSchemaWork::root(Path::new(primary_uri)));
+        let mut root_schema: Option<XsdSchema> = None;
+
+        while let Some(work) = pending.pop_front() {
+            let uri = normalize_uri(&work.path.to_string_lossy());
+            if !loaded_paths.lock().unwrap().insert(PathBuf::from(&uri)) {
+                continue;
+            }
+
+            let schema_result = loader.load_resource(&uri).and_then(|content| {
+                parse_schema_content_no_includes(
+                    &work.path,
+                    &content,
+                    work.parent_namespace.as_deref(),
+                    None,
+                    loaded_paths.clone(),
+                    Some(loader.clone()),
+                    HttpCache::default(),
+                )
+            });
+
+            let schema = match schema_result {
+                Ok(s) => s,
+                Err(e) => {
+                    // For the root schema, propagate the error
+                    if root_schema.is_none() {
+                        return Err(e);
+                    }
+                    // For includes, skip and continue
+                    continue;
+                }
+            };
+
+            pending.extend(pending_schema_work(&schema));
+
+            match root_schema {
+                None => root_schema = Some(schema),
+                Some(ref mut root) => merge_schema_work(root, work, schema)?,
+            }
+        }
+
+        let mut schema = root_schema.ok_or_else(|| {
+            Error::Parse(ParseError::new("Failed to parse any schema"))
+        })?;
+
+        // Build the schema
+        schema.build()?;
+
+        Ok(schema)
+    }
+}
+
+#[cfg(feature = "http")]
+impl XsdSchema {
+    /// Parse an XSD schema published at an HTTP or HTTPS URL
+    ///
+    /// The schema must be served as `application/xml` or `text/xml`.
+    /// Relative includes, redefines and imports are resolved against the
+    /// URL and downloaded too; every document is downloaded once.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let schema = XsdSchema::from_url("https://www.w3.org/2007/schema-for-xslt20.xsd")?;
+    /// ```
+    pub fn from_url(url: &str) -> Result<Self> {
+        if !is_http_location(url) {
+            return Err(Error::Resource(format!("'{}' is not an HTTP or HTTPS URL", url)));
+        }
+        let loader = crate::loaders::HttpLoader::new();
+        loader.load_xml_bytes(url)?;
+        Self::from_loader(Arc::new(loader), url)
+    }
+}
+
+#[cfg(feature = "zip")]
+impl XsdSchema {
+    /// Parse an XSD schema from an entry of a ZIP archive, such as a JAR
+    ///
+    /// Includes, redefines and imports are resolved against the other
+    /// entries of the archive, like the sources of [`XsdSchema::from_sources`],
+    /// so relative locations are read from the archive rather than the file
+    /// system. Entries that are not UTF-8 text are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let archive = std::fs::File::open("schemas.zip")?;
+    /// let schema = XsdSchema::from_zip(archive, "xsd/main.xsd")?;
+    /// ```
+    pub fn from_zip<R: Read + std::io::Seek>(reader: R, entry: &str) -> Result<Self> {
+        let mut archive = zip::ZipArchive::new(reader)
+            .map_err(|e| Error::Resource(format!("Failed to open ZIP archive: {}", e)))?;
+
+        let mut loader = InMemoryLoader::new();
+        for index in 0..archive.len() {
+            let mut file = archive
+                .by_index(index)
+                .map_err(|e| Error::Resource(format!("Failed to read ZIP archive: {}", e)))?;
+            if file.is_dir() {
+                continue;
+            }
+            let mut content = String::new();
+            if file.read_to_string(&mut content).is_ok() {
+                loader = loader.with_resource(file.name(), &content);
+            }
+        }
+        Self::from_loader(Arc::new(loader), entry)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl XsdSchema {
+    /// Parse an XSD schema from a file path asynchronously
+    ///
+    /// Schema files are read with `tokio::fs`. Includes and redefines are
+    /// loaded breadth-first, with every file of the same depth read
+    /// concurrently, and merged in the same order as [`XsdSchema::from_file`].
+    /// Each file is parsed on the blocking thread pool, which also loads the
+    /// schemas it imports, so imports never block the calling task and the
+    /// files of a depth are parsed concurrently. A schema that can't be read
+    /// or parsed, including an included one, is an error.
+    ///
+    /// The built schema is immutable, so it can be wrapped in an `Arc` and
+    /// shared between tasks.
+    pub async fn from_file_async(path: &Path) -> Result<Self> {
+        let loaded_paths = Arc::new(std::sync::Mutex::new(std::collections::HashSet::new()));
+        let http_cache = HttpCache::default();
+        let mut wave = vec![PendingSchemaWork::root(path)];
+        let mut root_schema: Option<XsdSchema> = None;
+
+        while !wave.is_empty() {
+            // Skip files that were already loaded (prevents circular includes)
+            let mut work_items = Vec::with_capacity(wave.len());
+            for work in wave.drain(..) {
+                if let Ok(canonical) = tokio::fs::canonicalize(&work.path).await {
+                    if !loaded_paths.lock().unwrap().insert(canonical) {
+                        continue;
+                    }
+                }
+                work_items.push(work);
+            }
+
+            let contents = futures::future::join_all(
+                work_items.iter().map(|work| tokio::fs::read_to_string(&work.path)),
+            )
+            .await;
+
+            // Imports are loaded while parsing, with blocking reads
+            let parsed = futures::future::join_all(work_items.iter().zip(contents).map(|(work, content)| {
+                let path = work.path.clone();
+                let parent_namespace = work.parent_namespace.clone();
+                let loaded_paths = loaded_paths.clone();
+                let http_cache = http_cache.clone();
+                async move {
+                    let content = content.map_err(|e| {
+                        Error::Resource(format!("Failed to read schema '{}': {}", path.display(), e))
+                    })?;
+                    tokio::task::spawn_blocking(move || {
+                        parse_schema_content_no_includes(
+                            &path,
+                            &content,
+                            parent_namespace.as_deref(),
+                            None,
+                            loaded_paths,
+                            None,
+                            http_cache,
+                        )
+                    })
+                    .await
+                    .map_err(|e| Error::Parse(ParseError::new(format!("Schema parser task failed: {}", e))))?
+                }
+            }))
+            .await;
+
+            let mut next_wave = Vec::new();
+            for (work, schema) in work_items.into_iter().zip(parsed) {
+                let schema = schema?;
+                next_wave.extend(pending_schema_work(&schema));
+
+                match root_schema {
+                    None => root_schema = Some(schema),
+                    Some(ref mut root) => merge_schema_work(root, work, schema)?,
+                }
+            }
+            wave = next_wave;
+        }
+
+        let mut schema = root_schema.ok_or_else(|| {
+            Error::Parse(ParseError::new("Failed to parse any schema"))
+        })?;
+
+        // Build the schema
+        schema.build()?;
+
+        Ok(schema)
+    }
 }
 
 /// Load and parse a schema file without recursively processing includes.
@@ -345,14 +705,32 @@ fn parse_schema_no_includes(
     parent_namespace: Option<&str>,
     catalog: Option<Arc<XmlCatalog>>,
     loaded_paths: Arc<std::sync::Mutex<std::collections::HashSet<PathBuf>>>,
+    http_cache: HttpCache,
 ) -> Result<XsdSchema> {
-    // Read the file
-    let content = std::fs::read_to_string(path).map_err(|e| {
+    // Read the file, or download it from an HTTP location
+    let content = read_schema_resource(path, &http_cache).map_err(|e| {
         Error::Resource(format!("Failed to read schema '{}': {}", path.display(), e))
     })?;
 
+    parse_schema_content_no_includes(path, &content, parent_namespace, catalog, loaded_paths, None, http_cache)
+}
+
+/// Parse already loaded schema content without processing includes.
+///
+/// Shared by the file, asynchronous and in-memory loaders; `path` is only used
+/// as the source location for resolving relative include locations. Imports
+/// are loaded through `resource_loader` when one is given.
+fn parse_schema_content_no_includes(
+    path: &Path,
+    content: &str,
+    parent_namespace: Option<&str>,
+    catalog: Option<Arc<XmlCatalog>>,
+    loaded_paths: Arc<std::sync::Mutex<std::collections::HashSet<PathBuf>>>,
+    resource_loader: Option<Arc<dyn ResourceLoader>>,
+    http_cache: HttpCache,
+) -> Result<XsdSchema> {
     // Parse as document
-    let doc = Document::from_string(&content)?;
+    let doc = Document::from_string(content)?;
     let root = doc.root().ok_or_else(|| Error::Parse(ParseError::new("Empty document")))?;
 
     // Verify this is a schema element
@@ -369,6 +747,8 @@ fn parse_schema_no_includes(
     schema.source.base_url = path.parent().map(|p| p.to_string_lossy().to_string());
     schema.source.catalog = catalog;
     schema.source.loaded_paths = loaded_paths;
+    schema.source.resource_loader = resource_loader;
+    schema.source.http_cache = http_cache;
 
     // Parse the schema element (this collects include locations but doesn't load them)
     parse_schema_element(&mut schema, root)?;
@@ -397,6 +777,179 @@ fn parse_schema_no_includes(
     Ok(schema)
 }
 
+/// Collect the includes and redefines of a parsed schema as pending work items
+fn pending_schema_work(schema: &XsdSchema) -> Vec<PendingSchemaWork> {
+    let work = |location: &String, redefine: Option<PendingRedefine>| PendingSchemaWork {
+        path: resolve_schema_location(
+            location,
+            schema.source.base_url.as_deref(),
+            schema.source.catalog.as_ref().map(|c| c.as_ref()),
+        ),
+        parent_namespace: schema.target_namespace.clone(),
+        location: location.clone(),
+        redefine,
+    };
+
+    let includes = schema.pending_include_locations.iter().map(|location| work(location, None));
+    let redefines = schema.pending_redefine_locations.iter().map(|location| {
+        let redefine = PendingRedefine {
+            context: redefinition_context(schema),
+            redefinitions: schema.pending_redefinitions.get(location).cloned().unwrap_or_default(),
+        };
+        work(location, Some(redefine))
+    });
+    includes.chain(redefines).collect()
+}
+
+/// Merge an included or redefined schema into the root schema
+///
+/// The schema is recorded in the root's `includes` or `redefines`. The
+/// components of a redefined schema are replaced by their redefinitions.
+fn merge_schema_work(root: &mut XsdSchema, work: PendingSchemaWork, schema: XsdSchema) -> Result<()> {
+    root.maps.global_maps.merge(&schema.maps.global_maps);
+
+    match work.redefine {
+        Some(redefine) => {
+            apply_redefinitions(root, &redefine.context, &redefine.redefinitions, &work.location)?;
+            root.redefines.push(SchemaRedefine {
+                location: work.location,
+                schema: Arc::new(schema),
+                redefinitions: redefine.redefinitions,
+            });
+        }
+        None => root.includes.push(SchemaInclude {
+            location: work.location,
+            schema: Arc::new(schema),
+        }),
+    }
+    Ok(())
+}
+
+/// Create an empty schema with the parsing context of `schema`
+///
+/// Redefinitions are parsed with the target namespace, namespace
+/// declarations and defaults of the schema containing the xs:redefine.
+fn redefinition_context(schema: &XsdSchema) -> XsdSchema {
+    let mut context = XsdSchema::with_version(schema.version);
+    context.set_target_namespace(schema.target_namespace.clone());
+    context.source.namespaces = schema.source.namespaces.clone();
+    context.element_form_default = schema.element_form_default;
+    context.attribute_form_default = schema.attribute_form_default;
+    context.block_default = schema.block_default.clone();
+    context.final_default = schema.final_default.clone();
+    context
+}
+
+/// Replace components of the root schema with their redefinitions
+///
+/// Each redefinition is parsed in the context of the redefining schema and
+/// keeps the component it replaces as its `redefine` back-reference, which
+/// resolves the self-references when the schema is built. Fails if a
+/// redefined component doesn't exist in the redefined schema, or if a
+/// redefined type doesn't derive from itself.
+fn apply_redefinitions(
+    root: &mut XsdSchema,
+    context: &XsdSchema,
+    redefinitions: &[RedefinedComponent],
+    location: &str,
+) -> Result<()> {
+    let missing = |kind: &str, qname: &QName| {
+        Error::Parse(ParseError::new(format!(
+            "Redefined {} '{}' is not defined in '{}'",
+            kind,
+            qname.to_string(),
+            location
+        )))
+    };
+
+    for component in redefinitions {
+        let mut scratch = redefinition_context(context);
+        match component {
+            RedefinedComponent::SimpleType { qname, elem } => {
+                let Some(GlobalType::Simple(original)) = root.maps.global_maps.types.get(qname) else {
+                    return Err(missing("simple type", qname));
+                };
+                check_self_derivation(context, elem, qname, "simple type")?;
+                // The restriction base is looked up while parsing
+                scratch
+                    .maps
+                    .global_maps
+                    .types
+                    .insert(qname.clone(), GlobalType::Simple(Arc::clone(original)));
+                parse_simple_type(&mut scratch, elem)?;
+                if let Some(redefined) = scratch.maps.global_maps.types.shift_remove(qname) {
+                    root.maps.global_maps.types.insert(qname.clone(), redefined);
+                }
+            }
+            RedefinedComponent::ComplexType { qname, elem } => {
+                let Some(GlobalType::Complex(original)) = root.maps.global_maps.types.get(qname) else {
+                    return Err(missing("complex type", qname));
+                };
+                let original = Arc::clone(original);
+                check_self_derivation(context, elem, qname, "complex type")?;
+                parse_complex_type(&mut scratch, elem)?;
+                if let Some(GlobalType::Complex(redefined)) = scratch.maps.global_maps.types.shift_remove(qname) {
+                    let mut redefined = (*redefined).clone();
+                    redefined.redefine = Some(original);
+                    root.maps.global_maps.types.insert(qname.clone(), GlobalType::Complex(Arc::new(redefined)));
+                }
+            }
+            RedefinedComponent::Group { qname, elem } => {
+                let original = root.maps.global_maps.groups.get(qname).cloned().ok_or_else(|| missing("group", qname))?;
+                parse_group(&mut scratch, elem)?;
+                if let Some(redefined) = scratch.maps.global_maps.groups.shift_remove(qname) {
+                    let mut redefined = (*redefined).clone();
+                    redefined.redefine = Some(original);
+                    root.maps.global_maps.groups.insert(qname.clone(), Arc::new(redefined));
+                }
+            }
+            RedefinedComponent::AttributeGroup { qname, elem } => {
+                let original = root
+                    .maps
+                    .global_maps
+                    .attribute_groups
+                    .get(qname)
+                    .cloned()
+                    .ok_or_else(|| missing("attribute group", qname))?;
+                parse_attribute_group(&mut scratch, elem)?;
+                if let Some(redefined) = scratch.maps.global_maps.attribute_groups.shift_remove(qname) {
+                    let mut redefined = (*redefined).clone();
+                    redefined.redefine = Some(original);
+                    root.maps.global_maps.attribute_groups.insert(qname.clone(), Arc::new(redefined));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that a redefined type derives from the type it redefines
+fn check_self_derivation(context: &XsdSchema, elem: &Element, qname: &QName, kind: &str) -> Result<()> {
+    // Simple types restrict directly, complex types within their content
+    let derivation = elem
+        .children
+        .iter()
+        .flat_map(|child| match child.local_name() {
+            xsd_elements::COMPLEX_CONTENT | xsd_elements::SIMPLE_CONTENT => child.children.iter().collect(),
+            _ => vec![child],
+        })
+        .find(|child| matches!(child.local_name(), xsd_elements::RESTRICTION | xsd_elements::EXTENSION));
+
+    let base = derivation.and_then(|d| d.get_attribute(xsd_attrs::BASE)).map(|base| {
+        let (ns, local) = context.resolve_qname(base);
+        QName::new(ns.map(|s| s.to_string()), local)
+    });
+    if base.as_ref() == Some(qname) {
+        Ok(())
+    } else {
+        Err(Error::Parse(ParseError::new(format!(
+            "Redefined {} '{}' must derive from itself",
+            kind,
+            qname.to_string()
+        ))))
+    }
+}
+
 /// Parse the xs:schema root element
 fn parse_schema_element(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
     // Copy namespace declarations from the schema element
@@ -405,6 +958,9 @@ fn parse_schema_element(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
             schema.add_namespace(prefix, namespace);
         }
     }
+    if let Some(namespace) = elem.namespaces.get_default_namespace() {
+        schema.add_namespace("", namespace);
+    }
 
     // Parse schema attributes
     if let Some(ns) = elem.get_attribute(xsd_attrs::TARGET_NAMESPACE) {
@@ -465,7 +1021,10 @@ fn parse_schema_child(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
         xsd_elements::IMPORT => parse_import(schema, elem),
         xsd_elements::INCLUDE => parse_include(schema, elem),
         xsd_elements::NOTATION => parse_notation(schema, elem),
-        xsd_elements::ANNOTATION => Ok(()), // Skip annotations
+        xsd_elements::ANNOTATION => {
+            schema.annotations.push(XsdAnnotation::parse(elem));
+            Ok(())
+        }
         xsd_elements::REDEFINE => parse_redefine(schema, elem),
         _ => {
             schema.parse_error(ParseError::new(format!(
@@ -493,8 +1052,8 @@ fn parse_global_element(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
 
         // First try built-in simple types
         if let Some(builtin_name) = resolve_builtin_name(&type_local) {
-            if let Ok(simple_type) = XsdAtomicType::new(builtin_name) {
-                ElementType::Simple(Arc::new(simple_type))
+            if let Some(simple_type) = builtin_simple_type(builtin_name) {
+                ElementType::Simple(simple_type)
             } else {
                 ElementType::Any
             }
@@ -554,6 +1113,7 @@ fn parse_global_element(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
     if let Some(abstract_) = elem.get_attribute(xsd_attrs::ABSTRACT) {
         xsd_element.abstract_element = abstract_ == "true";
     }
+    xsd_element.block = parse_block(schema, elem);
 
     if let Some(default) = elem.get_attribute(xsd_attrs::DEFAULT) {
         xsd_element.default = Some(default.to_string());
@@ -568,6 +1128,11 @@ fn parse_global_element(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
         xsd_element.substitution_group = Some(QName::new(sg_ns.map(|s| s.to_string()), sg_local));
     }
 
+    parse_identity_constraints(schema, elem, &mut xsd_element);
+    parse_alternatives(schema, elem, &mut xsd_element);
+    xsd_element.documentation = parse_documentation(elem);
+    xsd_element.annotation = XsdAnnotation::from_component(elem);
+
     schema.maps.global_maps.elements.insert(qname, Arc::new(xsd_element));
 
     Ok(())
@@ -601,8 +1166,9 @@ fn parse_inline_complex_type(schema: &XsdSchema, elem: &Element) -> Option<XsdCo
     let mut complex_type = XsdComplexType::new(None, Arc::new(group));
 
     // Parse mixed attribute
-    if let Some(mixed) = elem.get_attribute(xsd_attrs::MIXED) {
-        complex_type.mixed = mixed == "true";
+    if let Some(mixed) = parse_mixed(elem) {
+        complex_type.mixed = mixed;
+        complex_type.mixed_explicit = true;
     }
 
     // Parse attributes
@@ -614,11 +1180,6 @@ fn parse_inline_complex_type(schema: &XsdSchema, elem: &Element) -> Option<XsdCo
         if child.local_name() == xsd_elements::COMPLEX_CONTENT
             || child.local_name() == xsd_elements::SIMPLE_CONTENT
         {
-            // Check for mixed attribute on complexContent
-            if let Some(mixed) = child.get_attribute(xsd_attrs::MIXED) {
-                complex_type.mixed = mixed == "true";
-            }
-
             for grandchild in &child.children {
                 match grandchild.local_name() {
                     xsd_elements::RESTRICTION => {
@@ -630,8 +1191,7 @@ fn parse_inline_complex_type(schema: &XsdSchema, elem: &Element) -> Option<XsdCo
                         // Parse restriction's content model (replaces base content)
                         let restriction_model = find_content_model_element(grandchild);
                         if let Some(model_elem) = restriction_model {
-                            let mut restriction_group = XsdGroup::new(ModelType::from_tag(model_elem.local_name()).unwrap_or(ModelType::Sequence));
-                            parse_content_model(schema, model_elem, &mut restriction_group);
+                            let restriction_group = parse_derivation_content_model(schema, model_elem);
                             complex_type.content = ComplexContent::Group(Arc::new(restriction_group));
                         }
                         parse_attributes(schema, grandchild, &mut attr_group);
@@ -645,8 +1205,7 @@ fn parse_inline_complex_type(schema: &XsdSchema, elem: &Element) -> Option<XsdCo
                         // Parse extension's content model (will be appended to base content)
                         let extension_model = find_content_model_element(grandchild);
                         if let Some(model_elem) = extension_model {
-                            let mut extension_group = XsdGroup::new(ModelType::from_tag(model_elem.local_name()).unwrap_or(ModelType::Sequence));
-                            parse_content_model(schema, model_elem, &mut extension_group);
+                            let extension_group = parse_derivation_content_model(schema, model_elem);
                             complex_type.content = ComplexContent::Group(Arc::new(extension_group));
                         }
                         parse_attributes(schema, grandchild, &mut attr_group);
@@ -657,11 +1216,39 @@ fn parse_inline_complex_type(schema: &XsdSchema, elem: &Element) -> Option<XsdCo
         }
     }
 
+    if let Some(simple_type) = parse_simple_content(schema, elem) {
+        complex_type.content = ComplexContent::Simple(simple_type);
+    }
+
     complex_type.attributes = attr_group;
+    complex_type.assertions = parse_assertions(elem);
 
     Some(complex_type)
 }
 
+/// Parse the content type of a complex type with an xs:simpleContent extension
+///
+/// The base type is a built-in type or a simple type parsed before the
+/// complex type. Returns `None` otherwise, e.g. for a complex base type
+/// with simple content, which is inherited when the derivations are resolved.
+fn parse_simple_content(
+    schema: &XsdSchema,
+    elem: &Element,
+) -> Option<Arc<dyn super::simple_types::SimpleType + Send + Sync>> {
+    let content = elem.children.iter().find(|child| child.local_name() == xsd_elements::SIMPLE_CONTENT)?;
+    let extension = content.children.iter().find(|child| child.local_name() == xsd_elements::EXTENSION)?;
+    let (base_ns, base_local) = schema.resolve_qname(extension.get_attribute(xsd_attrs::BASE)?);
+
+    let base_qname = QName::new(base_ns.map(|s| s.to_string()), base_local);
+    if let Some(simple_type) = schema.maps.lookup_simple_type(&base_qname) {
+        return Some(Arc::clone(simple_type));
+    }
+    match base_ns {
+        Some(XSD_NAMESPACE) | None => builtin_simple_type(resolve_builtin_name(base_local)?),
+        Some(_) => None,
+    }
+}
+
 /// Parse an inline (anonymous) simple type
 fn parse_inline_simple_type(schema: &XsdSchema, elem: &Element) -> Option<XsdAtomicType> {
     // Look for restriction
@@ -684,10 +1271,18 @@ fn parse_inline_simple_type(schema: &XsdSchema, elem: &Element) -> Option<XsdAto
 
             // Parse facets
             let mut enumeration: Vec<String> = Vec::new();
-            let mut patterns: Vec<String> = Vec::new();
+            let mut patterns: Vec<(String, String)> = Vec::new();
             let mut min_length: Option<usize> = None;
             let mut max_length: Option<usize> = None;
             let mut length: Option<usize> = None;
+            let mut explicit_timezone: Option<ExplicitTimezoneFacet> = None;
+            let mut total_digits: Option<u32> = None;
+            let mut fraction_digits: Option<u32> = None;
+            let mut min_inclusive: Option<NumericBound> = None;
+            let mut max_inclusive: Option<NumericBound> = None;
+            let mut min_exclusive: Option<NumericBound> = None;
+            let mut max_exclusive: Option<NumericBound> = None;
+            let mut white_space: Option<WhiteSpace> = None;
 
             for facet_child in &child.children {
                 match facet_child.local_name() {
@@ -698,7 +1293,7 @@ fn parse_inline_simple_type(schema: &XsdSchema, elem: &Element) -> Option<XsdAto
                     }
                     xsd_elements::PATTERN => {
                         if let Some(value) = facet_child.get_attribute(xsd_attrs::VALUE) {
-                            patterns.push(format!("^{}$", value));
+                            patterns.push((value.to_string(), anchored_pattern(value)));
                         }
                     }
                     xsd_elements::MIN_LENGTH => {
@@ -716,6 +1311,38 @@ fn parse_inline_simple_type(schema: &XsdSchema, elem: &Element) -> Option<XsdAto
                             length = value.parse().ok();
                         }
                     }
+                    xsd_elements::EXPLICIT_TIMEZONE => {
+                        if let Some(value) = facet_child.get_attribute(xsd_attrs::VALUE) {
+                            explicit_timezone = value.parse().ok();
+                        }
+                    }
+                    xsd_elements::TOTAL_DIGITS => {
+                        if let Some(value) = facet_child.get_attribute(xsd_attrs::VALUE) {
+                            total_digits = value.trim().parse().ok();
+                        }
+                    }
+                    xsd_elements::FRACTION_DIGITS => {
+                        if let Some(value) = facet_child.get_attribute(xsd_attrs::VALUE) {
+                            fraction_digits = value.trim().parse().ok();
+                        }
+                    }
+                    xsd_elements::MIN_INCLUSIVE => {
+                        min_inclusive = facet_child.get_attribute(xsd_attrs::VALUE).and_then(parse_numeric_bound);
+                    }
+                    xsd_elements::MAX_INCLUSIVE => {
+                        max_inclusive = facet_child.get_attribute(xsd_attrs::VALUE).and_then(parse_numeric_bound);
+                    }
+                    xsd_elements::MIN_EXCLUSIVE => {
+                        min_exclusive = facet_child.get_attribute(xsd_attrs::VALUE).and_then(parse_numeric_bound);
+                    }
+                    xsd_elements::MAX_EXCLUSIVE => {
+                        max_exclusive = facet_child.get_attribute(xsd_attrs::VALUE).and_then(parse_numeric_bound);
+                    }
+                    xsd_elements::WHITE_SPACE => {
+                        if let Some(value) = facet_child.get_attribute(xsd_attrs::VALUE) {
+                            white_space = WhiteSpace::from_str(value).ok();
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -724,8 +1351,8 @@ fn parse_inline_simple_type(schema: &XsdSchema, elem: &Element) -> Option<XsdAto
             if !enumeration.is_empty() {
                 atomic = atomic.with_enumeration(enumeration);
             }
-            if let Some(pattern) = patterns.into_iter().find(|p| regex::Regex::new(p).is_ok()) {
-                atomic = atomic.with_pattern(&pattern).expect("pattern already validated");
+            if let Some(facet) = first_valid_pattern(patterns) {
+                atomic = atomic.with_pattern_facet(facet);
             }
             if let Some(len) = min_length {
                 atomic = atomic.with_min_length(len);
@@ -736,6 +1363,30 @@ fn parse_inline_simple_type(schema: &XsdSchema, elem: &Element) -> Option<XsdAto
             if let Some(len) = length {
                 atomic = atomic.with_length(len);
             }
+            if let Some(facet) = explicit_timezone {
+                atomic = atomic.with_explicit_timezone(facet);
+            }
+            if let Some(digits) = total_digits {
+                atomic = atomic.with_total_digits(digits);
+            }
+            if let Some(digits) = fraction_digits {
+                atomic = atomic.with_fraction_digits(digits);
+            }
+            if let Some(value) = min_inclusive {
+                atomic = atomic.with_min_inclusive(MinInclusiveFacet { value });
+            }
+            if let Some(value) = max_inclusive {
+                atomic = atomic.with_max_inclusive(MaxInclusiveFacet { value });
+            }
+            if let Some(value) = min_exclusive {
+                atomic = atomic.with_min_exclusive(MinExclusiveFacet { value });
+            }
+            if let Some(value) = max_exclusive {
+                atomic = atomic.with_max_exclusive(MaxExclusiveFacet { value });
+            }
+            if let Some(white_space) = white_space {
+                atomic = atomic.with_white_space(white_space);
+            }
 
             return Some(atomic);
         }
@@ -780,14 +1431,16 @@ fn parse_complex_type(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
     let mut complex_type = XsdComplexType::new(Some(qname.clone()), Arc::new(group));
 
     // Parse mixed attribute
-    if let Some(mixed) = elem.get_attribute(xsd_attrs::MIXED) {
-        complex_type.mixed = mixed == "true";
+    if let Some(mixed) = parse_mixed(elem) {
+        complex_type.mixed = mixed;
+        complex_type.mixed_explicit = true;
     }
 
     // Parse abstract attribute
     if let Some(abstract_) = elem.get_attribute(xsd_attrs::ABSTRACT) {
         complex_type.abstract_type = abstract_ == "true";
     }
+    complex_type.block = parse_block(schema, elem);
 
     // Parse attributes directly on the complexType
     let mut attr_group = XsdAttributeGroup::anonymous();
@@ -824,14 +1477,80 @@ fn parse_complex_type(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
         }
     }
 
+    if let Some(simple_type) = parse_simple_content(schema, elem) {
+        complex_type.content = ComplexContent::Simple(simple_type);
+    }
+
     // Set the attributes on the complex type
     complex_type.attributes = attr_group;
+    complex_type.assertions = parse_assertions(elem);
+    complex_type.documentation = parse_documentation(elem);
+    complex_type.annotation = XsdAnnotation::from_component(elem);
 
     schema.maps.global_maps.types.insert(qname, GlobalType::Complex(Arc::new(complex_type)));
 
     Ok(())
 }
 
+/// Parse the xs:assert children of a complex type and of its derivation
+///
+/// Tests that are not valid expressions are kept with their build errors.
+fn parse_assertions(elem: &Element) -> AssertionList {
+    let derivations = elem
+        .children
+        .iter()
+        .filter(|child| matches!(child.local_name(), xsd_elements::COMPLEX_CONTENT | xsd_elements::SIMPLE_CONTENT))
+        .flat_map(|content| content.children.iter())
+        .filter(|child| matches!(child.local_name(), xsd_elements::RESTRICTION | xsd_elements::EXTENSION));
+
+    let mut assertions = AssertionList::new();
+    for parent in std::iter::once(elem).chain(derivations) {
+        for assert in parent.children.iter().filter(|child| child.local_name() == xsd_elements::ASSERT) {
+            let mut assertion = XsdAssert::new(assert.get_attribute(xsd_attrs::TEST).unwrap_or_default());
+            if let Some(id) = assert.get_attribute(xsd_attrs::ID) {
+                assertion = assertion.with_id(id);
+            }
+            if let Some(ns) = assert.get_attribute(xsd_attrs::XPATH_DEFAULT_NAMESPACE) {
+                if let Ok(ns) = XPathDefaultNamespace::from_str(ns) {
+                    assertion = assertion.with_xpath_default_namespace(ns);
+                }
+            }
+            if assertion.build().is_ok() {
+                assertions.add(assertion);
+            }
+        }
+    }
+    assertions
+}
+
+/// Parse the effective `mixed` attribute of a complex type
+///
+/// `xs:complexContent/@mixed` takes precedence over `xs:complexType/@mixed`.
+/// Returns `None` if neither is given, so the value can be inherited from
+/// the base type.
+fn parse_mixed(elem: &Element) -> Option<bool> {
+    elem.children
+        .iter()
+        .find(|child| child.local_name() == xsd_elements::COMPLEX_CONTENT)
+        .and_then(|content| content.get_attribute(xsd_attrs::MIXED))
+        .or_else(|| elem.get_attribute(xsd_attrs::MIXED))
+        .map(|mixed| mixed == "true" || mixed == "1")
+}
+
+/// Parse the `block` attribute of an element or complex type
+///
+/// Falls back to the extension and restriction flags of the schema's
+/// `blockDefault` when the attribute is absent.
+fn parse_block(schema: &XsdSchema, elem: &Element) -> DerivationFlags {
+    match elem.get_attribute(xsd_attrs::BLOCK) {
+        Some(block) => DerivationFlags::from_attr(block),
+        None => DerivationFlags {
+            restriction: schema.block_default.restriction,
+            extension: schema.block_default.extension,
+        },
+    }
+}
+
 /// Parse a global simple type definition
 fn parse_simple_type(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
     let name = elem.get_attribute(xsd_attrs::NAME).ok_or_else(|| {
@@ -906,10 +1625,18 @@ fn parse_simple_restriction(schema: &mut XsdSchema, elem: &Element, qname: &QNam
 
     // Collect all facets first
     let mut enumeration: Vec<String> = Vec::new();
-    let mut patterns: Vec<String> = Vec::new();
+    let mut patterns: Vec<(String, String)> = Vec::new();
     let mut min_length: Option<usize> = None;
     let mut max_length: Option<usize> = None;
     let mut length: Option<usize> = None;
+    let mut explicit_timezone: Option<ExplicitTimezoneFacet> = None;
+    let mut min_inclusive: Option<NumericBound> = None;
+    let mut max_inclusive: Option<NumericBound> = None;
+    let mut min_exclusive: Option<NumericBound> = None;
+    let mut max_exclusive: Option<NumericBound> = None;
+    let mut total_digits: Option<u32> = None;
+    let mut fraction_digits: Option<u32> = None;
+    let mut white_space: Option<WhiteSpace> = None;
 
     for child in &elem.children {
         match child.local_name() {
@@ -920,8 +1647,7 @@ fn parse_simple_restriction(schema: &mut XsdSchema, elem: &Element, qname: &QNam
             }
             xsd_elements::PATTERN => {
                 if let Some(value) = child.get_attribute(xsd_attrs::VALUE) {
-                    // Convert XSD regex to Rust regex
-                    patterns.push(format!("^{}$", value));
+                    patterns.push((value.to_string(), anchored_pattern(value)));
                 }
             }
             xsd_elements::MIN_LENGTH => {
@@ -939,20 +1665,60 @@ fn parse_simple_restriction(schema: &mut XsdSchema, elem: &Element, qname: &QNam
                     length = value.parse().ok();
                 }
             }
+            xsd_elements::EXPLICIT_TIMEZONE => {
+                if let Some(value) = child.get_attribute(xsd_attrs::VALUE) {
+                    explicit_timezone = value.parse().ok();
+                }
+            }
+            xsd_elements::MIN_INCLUSIVE => {
+                min_inclusive = child.get_attribute(xsd_attrs::VALUE).and_then(parse_numeric_bound);
+            }
+            xsd_elements::MAX_INCLUSIVE => {
+                max_inclusive = child.get_attribute(xsd_attrs::VALUE).and_then(parse_numeric_bound);
+            }
+            xsd_elements::MIN_EXCLUSIVE => {
+                min_exclusive = child.get_attribute(xsd_attrs::VALUE).and_then(parse_numeric_bound);
+            }
+            xsd_elements::MAX_EXCLUSIVE => {
+                max_exclusive = child.get_attribute(xsd_attrs::VALUE).and_then(parse_numeric_bound);
+            }
+            xsd_elements::WHITE_SPACE => {
+                white_space = child.get_attribute(xsd_attrs::VALUE).and_then(|value| WhiteSpace::from_str(value).ok());
+            }
+            xsd_elements::TOTAL_DIGITS => {
+                total_digits = child.get_attribute(xsd_attrs::VALUE).and_then(|value| value.trim().parse().ok());
+            }
+            xsd_elements::FRACTION_DIGITS => {
+                fraction_digits = child.get_attribute(xsd_attrs::VALUE).and_then(|value| value.trim().parse().ok());
+            }
             _ => {}
         }
     }
 
+    // A restriction of the type itself is a redefinition (xs:redefine)
+    let redefine = base_attr
+        .map(|base| {
+            let (base_ns, base_local) = schema.resolve_qname(base);
+            QName::new(base_ns.map(|s| s.to_string()), base_local)
+        })
+        .filter(|base_qname| base_qname == qname)
+        .map(|_| Arc::clone(&base_type));
+
     // Create the restricted type with the base type reference
     let mut restricted = XsdRestrictedType::with_name(base_type, qname.clone());
+    restricted.redefine = redefine;
 
     // Apply facets
     if !enumeration.is_empty() {
         restricted = restricted.with_enumeration(enumeration);
     }
-    // Apply first valid pattern (pre-validate to avoid move issues)
-    if let Some(pattern) = patterns.into_iter().find(|p| regex::Regex::new(p).is_ok()) {
-        restricted = restricted.with_pattern(&pattern).expect("pattern already validated");
+    if !patterns.is_empty() {
+        let anchored = patterns.iter().map(|(_, pattern)| pattern.clone()).collect();
+        schema.pattern_sources.insert(qname.clone(), anchored);
+    }
+    // Apply first valid pattern
+    if let Some(facet) = first_valid_pattern(patterns) {
+        restricted = restricted.with_pattern_facet(facet);
     }
     if let Some(len) = min_length {
         restricted = restricted.with_min_length(len);
@@ -963,12 +1729,74 @@ fn parse_simple_restriction(schema: &mut XsdSchema, elem: &Element, qname: &QNam
     if let Some(len) = length {
         restricted = restricted.with_length(len);
     }
-
-    schema.maps.global_maps.types.insert(qname.clone(), GlobalType::Simple(Arc::new(restricted)));
-
+    if let Some(facet) = explicit_timezone {
+        restricted = restricted.with_explicit_timezone(facet);
+    }
+    if let Some(value) = min_inclusive {
+        restricted = restricted.with_min_inclusive(MinInclusiveFacet { value });
+    }
+    if let Some(value) = max_inclusive {
+        restricted = restricted.with_max_inclusive(MaxInclusiveFacet { value });
+    }
+    if let Some(value) = min_exclusive {
+        restricted = restricted.with_min_exclusive(MinExclusiveFacet { value });
+    }
+    if let Some(value) = max_exclusive {
+        restricted = restricted.with_max_exclusive(MaxExclusiveFacet { value });
+    }
+    if let Some(white_space) = white_space {
+        restricted = restricted.with_white_space(white_space);
+    }
+    if let Some(digits) = total_digits {
+        restricted = restricted.with_total_digits(digits);
+    }
+    if let Some(digits) = fraction_digits {
+        restricted = restricted.with_fraction_digits(digits);
+    }
+
+    schema.maps.global_maps.types.insert(qname.clone(), GlobalType::Simple(Arc::new(restricted)));
+
     Ok(())
 }
 
+/// Translate an `xs:pattern` value into an anchored Rust regex
+///
+/// Patterns that aren't valid XSD regular expressions are kept as written,
+/// so that compiling them reports the error (see `precompile_patterns`).
+fn anchored_pattern(value: &str) -> String {
+    match xsd_pattern_to_rust(value) {
+        Ok(translated) => format!("^{}$", translated),
+        Err(_) => format!("^{}$", value),
+    }
+}
+
+/// Compile the first valid pattern of a restriction
+///
+/// Each pattern is given as its `xs:pattern` value and its anchored
+/// translation; the facet keeps the value as its source.
+fn first_valid_pattern(patterns: Vec<(String, String)>) -> Option<PatternFacet> {
+    patterns
+        .into_iter()
+        .find_map(|(value, pattern)| PatternFacet::new(&pattern).ok().map(|facet| facet.with_source(value)))
+}
+
+/// Parse the value of a range facet
+///
+/// Numbers and date, time or dateTime values are supported; bounds of other
+/// ordered types, like durations, are ignored.
+fn parse_numeric_bound(value: &str) -> Option<NumericBound> {
+    let value = value.trim();
+    if let Ok(int) = value.parse::<i64>() {
+        Some(NumericBound::Integer(int))
+    } else if let Ok(decimal) = value.parse::<rust_decimal::Decimal>() {
+        Some(NumericBound::Decimal(decimal))
+    } else if let Ok(float) = value.parse::<f64>() {
+        Some(NumericBound::Float(float))
+    } else {
+        NumericBound::temporal(value)
+    }
+}
+
 /// Parse a simple type list
 fn parse_simple_list(schema: &mut XsdSchema, elem: &Element, qname: &QName, _name: &str) -> Result<()> {
     // Get the itemType attribute
@@ -1022,7 +1850,7 @@ fn parse_simple_union(schema: &mut XsdSchema, elem: &Element, qname: &QName, _na
     let member_types_attr = elem.get_attribute(xsd_attrs::MEMBER_TYPES);
 
     // Resolve member types
-    let member_types: Vec<Arc<dyn super::simple_types::SimpleType + Send + Sync>> = if let Some(types_str) = member_types_attr {
+    let mut member_types: Vec<Arc<dyn super::simple_types::SimpleType + Send + Sync>> = if let Some(types_str) = member_types_attr {
         types_str.split_whitespace()
             .map(|type_name| {
                 let (member_ns, member_local) = schema.resolve_qname(type_name);
@@ -1052,11 +1880,16 @@ fn parse_simple_union(schema: &mut XsdSchema, elem: &Element, qname: &QName, _na
             })
             .collect::<Result<Vec<_>>>()?
     } else {
-        // No memberTypes attribute - could have inline simpleType children
-        // For now, create an empty union (this should be rare in practice)
         vec![]
     };
 
+    // Inline simpleType children are members after the memberTypes
+    for child in elem.children.iter().filter(|c| c.local_name() == xsd_elements::SIMPLE_TYPE) {
+        if let Some(member_type) = parse_inline_simple_type(schema, child) {
+            member_types.push(Arc::new(member_type));
+        }
+    }
+
     // Create the union type
     let union_type = XsdUnionType::with_name(member_types, qname.clone());
 
@@ -1072,7 +1905,8 @@ fn parse_global_attribute(schema: &mut XsdSchema, elem: &Element) -> Result<()>
     })?;
 
     let qname = make_qname(schema, name);
-    let attr = XsdAttribute::new(qname.clone());
+    let mut attr = XsdAttribute::new(qname.clone());
+    parse_attribute_properties(schema, elem, &mut attr);
 
     schema.maps.global_maps.attributes.insert(qname, Arc::new(attr));
 
@@ -1091,6 +1925,7 @@ fn parse_attribute_group(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
 
     // Parse the attribute group content (attributes and attribute group references)
     parse_attributes(schema, elem, &mut attr_group);
+    attr_group.annotation = XsdAnnotation::from_component(elem);
 
     schema.maps.global_maps.attribute_groups.insert(qname, Arc::new(attr_group));
 
@@ -1126,6 +1961,7 @@ fn parse_group(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
     if let Some(content_elem) = model_elem {
         parse_content_model(schema, content_elem, &mut group);
     }
+    group.annotation = XsdAnnotation::from_component(elem);
 
     schema.maps.global_maps.groups.insert(qname, Arc::new(group));
 
@@ -1163,7 +1999,28 @@ fn parse_import(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
         if let Some(ref ns) = namespace {
             let resolved_path = resolve_schema_location(loc, schema.base_url(), schema.catalog());
 
-            match load_imported_schema(&resolved_path, Some(ns), schema.source.catalog.clone()) {
+            let imported = match schema.source.resource_loader.clone() {
+                Some(loader) => loader
+                    .load_resource(&resolved_path.to_string_lossy())
+                    .and_then(|content| {
+                        parse_imported_schema_content(
+                            &resolved_path,
+                            &content,
+                            Some(ns),
+                            schema.source.catalog.clone(),
+                            Some(loader),
+                            schema.source.http_cache.clone(),
+                        )
+                    }),
+                None => load_imported_schema(
+                    &resolved_path,
+                    Some(ns),
+                    schema.source.catalog.clone(),
+                    schema.source.http_cache.clone(),
+                ),
+            };
+
+            match imported {
                 Ok(imported_schema) => {
                     // Update the import record with the loaded schema
                     if let Some(import) = schema.imports.get_mut(ns) {
@@ -1190,14 +2047,27 @@ fn load_imported_schema(
     path: &Path,
     expected_namespace: Option<&str>,
     catalog: Option<Arc<XmlCatalog>>,
+    http_cache: HttpCache,
 ) -> Result<XsdSchema> {
-    // Read the file
-    let content = std::fs::read_to_string(path).map_err(|e| {
+    // Read the file, or download it from an HTTP location
+    let content = read_schema_resource(path, &http_cache).map_err(|e| {
         Error::Resource(format!("Failed to read imported schema '{}': {}", path.display(), e))
     })?;
 
+    parse_imported_schema_content(path, &content, expected_namespace, catalog, None, http_cache)
+}
+
+/// Parse and build already loaded imported schema content
+fn parse_imported_schema_content(
+    path: &Path,
+    content: &str,
+    expected_namespace: Option<&str>,
+    catalog: Option<Arc<XmlCatalog>>,
+    resource_loader: Option<Arc<dyn ResourceLoader>>,
+    http_cache: HttpCache,
+) -> Result<XsdSchema> {
     // Parse as document
-    let doc = Document::from_string(&content)?;
+    let doc = Document::from_string(content)?;
     let root = doc.root().ok_or_else(|| Error::Parse(ParseError::new("Empty imported document")))?;
 
     // Verify this is a schema element
@@ -1213,6 +2083,8 @@ fn load_imported_schema(
     imported_schema.source.url = Some(path.to_string_lossy().to_string());
     imported_schema.source.base_url = path.parent().map(|p| p.to_string_lossy().to_string());
     imported_schema.source.catalog = catalog;
+    imported_schema.source.resource_loader = resource_loader;
+    imported_schema.source.http_cache = http_cache;
 
     // Parse the schema element
     parse_schema_element(&mut imported_schema, root)?;
@@ -1263,9 +2135,9 @@ fn parse_include(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
 /// from the included schema. The redefined components must derive from
 /// themselves (for types) or contain a self-reference (for groups).
 ///
-/// This only collects the redefine location. The actual loading is done
-/// iteratively by `parse_file_internal` to avoid stack overflow.
-/// Redefinition elements (children of xs:redefine) are processed inline.
+/// This only collects the redefine location and the redefined components.
+/// The actual loading is done iteratively by `parse_file_internal` to avoid
+/// stack overflow, which then applies the redefinitions.
 fn parse_redefine(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
     let location = match elem.get_attribute(xsd_attrs::SCHEMA_LOCATION) {
         Some(loc) => loc,
@@ -1280,15 +2152,35 @@ fn parse_redefine(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
         schema.pending_redefine_locations.push(location.to_string());
     }
 
-    // NOTE: Redefinition children (simpleType, complexType, group, attributeGroup
-    // within xs:redefine) would normally be processed here. However, since we're
-    // deferring the schema loading, the redefinitions cannot be fully processed
-    // until after all schemas are loaded. For now, we simply include the schema
-    // and don't support actual redefinitions (the redefinition elements are ignored).
-    //
-    // TODO: To properly support xs:redefine, we would need to:
-    // 1. Store the redefinition Element data
-    // 2. Process redefinitions after all schemas are merged in parse_file_internal
+    // Redefinitions are applied once the redefined schema has been merged
+    let mut redefinitions = Vec::new();
+    for child in &elem.children {
+        let local = child.local_name();
+        if local == xsd_elements::ANNOTATION {
+            continue;
+        }
+        let Some(name) = child.get_attribute(xsd_attrs::NAME) else {
+            schema.parse_error(ParseError::new(format!("Redefined {} missing 'name' attribute", local)));
+            continue;
+        };
+        let qname = make_qname(schema, name);
+        let elem = child.clone();
+        redefinitions.push(match local {
+            xsd_elements::SIMPLE_TYPE => RedefinedComponent::SimpleType { qname, elem },
+            xsd_elements::COMPLEX_TYPE => RedefinedComponent::ComplexType { qname, elem },
+            xsd_elements::GROUP => RedefinedComponent::Group { qname, elem },
+            xsd_elements::ATTRIBUTE_GROUP => RedefinedComponent::AttributeGroup { qname, elem },
+            _ => {
+                schema.parse_error(ParseError::new(format!("Unexpected xs:{} in xs:redefine", local)));
+                continue;
+            }
+        });
+    }
+    schema
+        .pending_redefinitions
+        .entry(location.to_string())
+        .or_default()
+        .extend(redefinitions);
 
     Ok(())
 }
@@ -1297,10 +2189,10 @@ fn parse_redefine(schema: &mut XsdSchema, elem: &Element) -> Result<()> {
 ///
 /// Resolution order:
 /// 1. Check XML catalog for URN/system ID mapping
-/// 2. If location is absolute, use it directly
+/// 2. If location is absolute or an HTTP URL, use it directly
 /// 3. Resolve relative to base_url if available
 /// 4. Use the location as-is
-fn resolve_schema_location(
+pub(crate) fn resolve_schema_location(
     location: &str,
     base_url: Option<&str>,
     catalog: Option<&XmlCatalog>,
@@ -1315,7 +2207,7 @@ fn resolve_schema_location(
     let location_path = Path::new(location);
 
     // If location is absolute, use it directly
-    if location_path.is_absolute() {
+    if location_path.is_absolute() || is_http_location(location) {
         return location_path.to_path_buf();
     }
 
@@ -1389,7 +2281,7 @@ fn chameleon_renamespace(schema: &mut XsdSchema, target_ns: &str) {
     schema.target_namespace = Some(target_ns.to_string());
 
     // Re-namespace elements
-    let elements: Vec<_> = schema.maps.global_maps.elements.drain().collect();
+    let elements: Vec<_> = schema.maps.global_maps.elements.drain(..).collect();
     for (mut qname, elem) in elements {
         qname.namespace = Some(target_ns.to_string());
         // Also update the element's name
@@ -1399,35 +2291,35 @@ fn chameleon_renamespace(schema: &mut XsdSchema, target_ns: &str) {
     }
 
     // Re-namespace types
-    let types: Vec<_> = schema.maps.global_maps.types.drain().collect();
+    let types: Vec<_> = schema.maps.global_maps.types.drain(..).collect();
     for (mut qname, typ) in types {
         qname.namespace = Some(target_ns.to_string());
         schema.maps.global_maps.types.insert(qname, typ);
     }
 
     // Re-namespace groups
-    let groups: Vec<_> = schema.maps.global_maps.groups.drain().collect();
+    let groups: Vec<_> = schema.maps.global_maps.groups.drain(..).collect();
     for (mut qname, group) in groups {
         qname.namespace = Some(target_ns.to_string());
         schema.maps.global_maps.groups.insert(qname, group);
     }
 
     // Re-namespace attributes
-    let attrs: Vec<_> = schema.maps.global_maps.attributes.drain().collect();
+    let attrs: Vec<_> = schema.maps.global_maps.attributes.drain(..).collect();
     for (mut qname, attr) in attrs {
         qname.namespace = Some(target_ns.to_string());
         schema.maps.global_maps.attributes.insert(qname, attr);
     }
 
     // Re-namespace attribute groups
-    let attr_groups: Vec<_> = schema.maps.global_maps.attribute_groups.drain().collect();
+    let attr_groups: Vec<_> = schema.maps.global_maps.attribute_groups.drain(..).collect();
     for (mut qname, group) in attr_groups {
         qname.namespace = Some(target_ns.to_string());
         schema.maps.global_maps.attribute_groups.insert(qname, group);
     }
 
     // Re-namespace notations
-    let notations: Vec<_> = schema.maps.global_maps.notations.drain().collect();
+    let notations: Vec<_> = schema.maps.global_maps.notations.drain(..).collect();
     for (mut qname, notation) in notations {
         qname.namespace = Some(target_ns.to_string());
         schema.maps.global_maps.notations.insert(qname, notation);
@@ -1505,6 +2397,22 @@ fn find_content_model_element(elem: &Element) -> Option<&Element> {
     None
 }
 
+/// Parse the content model of a derivation, which may be a group reference
+fn parse_derivation_content_model(schema: &XsdSchema, model_elem: &Element) -> XsdGroup {
+    let mut group = XsdGroup::new(ModelType::from_tag(model_elem.local_name()).unwrap_or(ModelType::Sequence));
+    if model_elem.local_name() == xsd_elements::GROUP {
+        if let Some(ref_str) = model_elem.get_attribute(xsd_attrs::REF) {
+            let (ref_ns, ref_local) = schema.resolve_qname(ref_str);
+            let ref_qname = QName::new(ref_ns.map(|s| s.to_string()), ref_local);
+            let ref_group = XsdGroup::reference(ref_qname, parse_occurs_option(model_elem));
+            group.particles.push(GroupParticle::Group(Arc::new(ref_group)));
+        }
+    } else {
+        parse_content_model(schema, model_elem, &mut group);
+    }
+    group
+}
+
 /// Parse a content model (sequence, choice, or all) and populate the group with particles
 fn parse_content_model(schema: &XsdSchema, model_elem: &Element, group: &mut XsdGroup) {
     for child in &model_elem.children {
@@ -1574,8 +2482,8 @@ fn parse_element_particle(schema: &XsdSchema, elem: &Element) -> Option<ElementP
 
             // First try built-in simple types
             if let Some(builtin_name) = resolve_builtin_name(&type_local) {
-                if let Ok(simple_type) = XsdAtomicType::new(builtin_name) {
-                    ElementType::Simple(Arc::new(simple_type))
+                if let Some(simple_type) = builtin_simple_type(builtin_name) {
+                    ElementType::Simple(simple_type)
                 } else {
                     ElementType::Any
                 }
@@ -1621,6 +2529,7 @@ fn parse_element_particle(schema: &XsdSchema, elem: &Element) -> Option<ElementP
         if let Some(nillable) = elem.get_attribute(xsd_attrs::NILLABLE) {
             xsd_element.nillable = nillable == "true";
         }
+        xsd_element.block = parse_block(schema, elem);
 
         // Parse default/fixed values
         if let Some(default) = elem.get_attribute(xsd_attrs::DEFAULT) {
@@ -1630,12 +2539,113 @@ fn parse_element_particle(schema: &XsdSchema, elem: &Element) -> Option<ElementP
             xsd_element.fixed = Some(fixed.to_string());
         }
 
+        parse_identity_constraints(schema, elem, &mut xsd_element);
+        parse_alternatives(schema, elem, &mut xsd_element);
+        xsd_element.documentation = parse_documentation(elem);
+        xsd_element.annotation = XsdAnnotation::from_component(elem);
+
         return Some(ElementParticle::with_decl(qname, occurs, Arc::new(xsd_element)));
     }
 
     None
 }
 
+/// Parse the text of the xs:documentation children of a component's xs:annotation
+///
+/// White space is collapsed; several documentation elements are separated by
+/// a blank line.
+fn parse_documentation(elem: &Element) -> Option<String> {
+    let paragraphs: Vec<String> = elem
+        .children
+        .iter()
+        .filter(|child| child.local_name() == xsd_elements::ANNOTATION)
+        .flat_map(|annotation| &annotation.children)
+        .filter(|child| child.local_name() == xsd_elements::DOCUMENTATION)
+        .filter_map(|documentation| documentation.text.as_deref())
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|text| !text.is_empty())
+        .collect();
+    if paragraphs.is_empty() {
+        None
+    } else {
+        Some(paragraphs.join("\n\n"))
+    }
+}
+
+/// Parse the type alternatives (xs:alternative) of an element (XSD 1.1)
+///
+/// Alternatives without a `type` attribute or with an invalid test are
+/// recorded as errors on the element declaration.
+fn parse_alternatives(schema: &XsdSchema, elem: &Element, xsd_element: &mut XsdElement) {
+    for child in &elem.children {
+        if child.local_name() != xsd_elements::ALTERNATIVE {
+            continue;
+        }
+        let Some(type_str) = child.get_attribute(xsd_attrs::TYPE) else {
+            xsd_element.add_error(ParseError::new("xs:alternative missing 'type' attribute"));
+            continue;
+        };
+        let (type_ns, type_local) = schema.resolve_qname(type_str);
+        let type_ref = QName::new(type_ns.map(|s| s.to_string()), type_local);
+        let test = child.get_attribute(xsd_attrs::TEST).map(|s| s.to_string());
+
+        let alternative = XsdAlternative::new(test, type_ref);
+        if alternative.is_invalid() {
+            xsd_element.add_error(ParseError::new(format!(
+                "invalid alternative test '{}'",
+                alternative.test.as_deref().unwrap_or_default()
+            )));
+        }
+        xsd_element.alternatives.push(alternative);
+    }
+}
+
+/// Parse the identity constraints (xs:unique, xs:key, xs:keyref) of an element
+///
+/// Constraints are built with [`IdentityBuilder`]; invalid ones are recorded
+/// as errors on the element declaration.
+fn parse_identity_constraints(schema: &XsdSchema, elem: &Element, xsd_element: &mut XsdElement) {
+    for child in &elem.children {
+        let kind = match child.local_name() {
+            xsd_elements::UNIQUE => IdentityConstraintKind::Unique,
+            xsd_elements::KEY => IdentityConstraintKind::Key,
+            xsd_elements::KEYREF => IdentityConstraintKind::Keyref,
+            _ => continue,
+        };
+        let Some(name) = child.get_attribute(xsd_attrs::NAME) else {
+            xsd_element.add_error(ParseError::new(format!(
+                "xs:{} missing 'name' attribute",
+                child.local_name()
+            )));
+            continue;
+        };
+
+        let mut builder = IdentityBuilder::new(kind, make_qname(schema, name));
+        if let Some(ns) = &schema.target_namespace {
+            builder = builder.target_namespace(ns.clone());
+        }
+        if let Some(refer) = child.get_attribute(xsd_attrs::REFER) {
+            let (refer_ns, refer_local) = schema.resolve_qname(refer);
+            builder = builder.refer(QName::new(refer_ns.map(|s| s.to_string()), refer_local));
+        }
+        for component in &child.children {
+            let xpath = component.get_attribute(xsd_attrs::XPATH).unwrap_or("");
+            match component.local_name() {
+                xsd_elements::SELECTOR => builder = builder.with_selector(xpath),
+                xsd_elements::FIELD => builder = builder.with_field(xpath),
+                _ => {}
+            }
+        }
+
+        let result = builder
+            .build()
+            .and_then(|identity| identity.add_to_element(xsd_element));
+        if let Err(e) = result {
+            xsd_element.add_error(ParseError::new(e.to_string()));
+        }
+    }
+}
+
 /// Parse an xs:any element wildcard
 fn parse_any_element(schema: &XsdSchema, elem: &Element) -> Option<XsdAnyElement> {
     let occurs = parse_occurs_option(elem);
@@ -1719,6 +2729,7 @@ fn parse_attribute_decl(schema: &XsdSchema, elem: &Element) -> Option<XsdAttribu
         let ref_qname = QName::new(ref_ns.map(|s| s.to_string()), ref_local);
         // Create attribute with ref name
         let mut attr = XsdAttribute::new(ref_qname);
+        attr.annotation = XsdAnnotation::from_component(elem);
 
         // Parse use attribute
         if let Some(use_str) = elem.get_attribute(xsd_attrs::USE) {
@@ -1735,15 +2746,21 @@ fn parse_attribute_decl(schema: &XsdSchema, elem: &Element) -> Option<XsdAttribu
     let qname = QName::local(name); // Local attributes typically don't use target namespace
 
     let mut attr = XsdAttribute::new(qname);
+    parse_attribute_properties(schema, elem, &mut attr);
 
+    Some(attr)
+}
+
+/// Parse the type, use and value constraint of an attribute declaration
+fn parse_attribute_properties(schema: &XsdSchema, elem: &Element, attr: &mut XsdAttribute) {
     // Parse type reference
     if let Some(type_str) = elem.get_attribute(xsd_attrs::TYPE) {
         let (type_ns, type_local) = schema.resolve_qname(type_str);
 
         // First try built-in types
         if let Some(builtin_name) = resolve_builtin_name(&type_local) {
-            if let Ok(simple_type) = XsdAtomicType::new(builtin_name) {
-                attr.set_type(Arc::new(simple_type));
+            if let Some(simple_type) = builtin_simple_type(builtin_name) {
+                attr.set_type(simple_type);
             }
         } else {
             // Look up user-defined simple type
@@ -1757,6 +2774,11 @@ fn parse_attribute_decl(schema: &XsdSchema, elem: &Element) -> Option<XsdAttribu
                 attr.type_name = Some(type_qname);
             }
         }
+    } else if let Some(child) = elem.children.iter().find(|child| child.local_name() == xsd_elements::SIMPLE_TYPE) {
+        // Anonymous simple type
+        if let Some(simple_type) = parse_inline_simple_type(schema, child) {
+            attr.set_type(Arc::new(simple_type));
+        }
     }
 
     // Parse use attribute
@@ -1773,8 +2795,7 @@ fn parse_attribute_decl(schema: &XsdSchema, elem: &Element) -> Option<XsdAttribu
     if let Some(fixed) = elem.get_attribute(xsd_attrs::FIXED) {
         let _ = attr.set_fixed(fixed.to_string());
     }
-
-    Some(attr)
+    attr.annotation = XsdAnnotation::from_component(elem);
 }
 
 /// Find the content model element (sequence/choice/all) in a complex type or derivation
@@ -1893,6 +2914,143 @@ mod tests {
         assert!(schema.lookup_element(&book_qname).is_some());
     }
 
+    #[test]
+    fn test_explain_book_type() {
+        use crate::validators::TypeKind;
+
+        let schema = XsdSchema::from_string(BOOK_XSD).expect("Failed to parse book schema");
+        let book_type = QName::new(Some("http://example.com/book".to_string()), "bookType");
+        let explanation = schema.explain_type(&book_type).expect("bookType should be explained");
+
+        assert_eq!(explanation.kind, TypeKind::Complex);
+        assert_eq!(explanation.derivation_chain.first(), Some(&book_type));
+
+        let content: Vec<_> = explanation.allowed_content.iter().map(|e| e.name.local_name.as_str()).collect();
+        assert_eq!(content, ["title", "author", "year", "isbn"]);
+        let types: Vec<_> = explanation
+            .allowed_content
+            .iter()
+            .map(|e| e.type_name.as_ref().map(|qname| qname.local_name.as_str()))
+            .collect();
+        assert_eq!(types[0], Some("string"));
+        assert_eq!(types[2], Some("gYear"));
+
+        let attributes: Vec<_> = explanation.attributes.iter().map(|a| a.name.local_name.as_str()).collect();
+        assert!(attributes.contains(&"id"));
+        assert!(attributes.contains(&"category"));
+        assert!(explanation.attributes.iter().all(|a| !a.required));
+        let id = explanation.attributes.iter().find(|a| a.name.local_name == "id").unwrap();
+        assert_eq!(id.type_name.as_ref().map(|qname| qname.local_name.as_str()), Some("ID"));
+
+        let isbn_type = QName::new(Some("http://example.com/book".to_string()), "isbnType");
+        let explanation = schema.explain_type(&isbn_type).expect("isbnType should be explained");
+        assert_eq!(explanation.kind, TypeKind::Simple);
+        assert_eq!(explanation.base_type.map(|qname| qname.local_name), Some("string".to_string()));
+        assert!(explanation.facets.iter().any(|facet| facet.contains("13")));
+
+        assert!(schema.explain_type(&QName::local("missingType")).is_none());
+    }
+
+    #[test]
+    fn test_parse_annotations() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:annotation><xs:documentation>Order schema</xs:documentation></xs:annotation>
+  <xs:element name="order" type="orderType">
+    <xs:annotation>
+      <xs:documentation>A purchase order.</xs:documentation>
+      <xs:appinfo>generated</xs:appinfo>
+    </xs:annotation>
+  </xs:element>
+  <xs:complexType name="orderType">
+    <xs:annotation><xs:documentation>Content of an order</xs:documentation></xs:annotation>
+    <xs:sequence>
+      <xs:element name="item" type="xs:string">
+        <xs:annotation><xs:documentation>An ordered item</xs:documentation></xs:annotation>
+      </xs:element>
+    </xs:sequence>
+    <xs:attribute name="id" type="xs:string">
+      <xs:annotation><xs:documentation>Order number</xs:documentation></xs:annotation>
+    </xs:attribute>
+  </xs:complexType>
+  <xs:attribute name="currency" type="xs:string">
+    <xs:annotation><xs:documentation>ISO 4217 code</xs:documentation></xs:annotation>
+  </xs:attribute>
+  <xs:attributeGroup name="audit">
+    <xs:annotation><xs:documentation>Audit attributes</xs:documentation></xs:annotation>
+    <xs:attribute name="by" type="xs:string"/>
+  </xs:attributeGroup>
+  <xs:group name="lines">
+    <xs:annotation><xs:documentation>Order lines</xs:documentation></xs:annotation>
+    <xs:sequence><xs:element name="line" type="xs:string"/></xs:sequence>
+  </xs:group>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).expect("Failed to parse schema");
+
+        assert_eq!(schema.annotations.len(), 1);
+        assert_eq!(schema.annotations[0].documentation, vec!["Order schema"]);
+
+        let order = schema.lookup_element(&QName::local("order")).unwrap();
+        let annotation = order.annotation.as_ref().unwrap();
+        assert_eq!(annotation.documentation, vec!["A purchase order."]);
+        assert_eq!(annotation.app_info, vec!["generated"]);
+
+        let Some(GlobalType::Complex(order_type)) = schema.lookup_type(&QName::local("orderType")) else {
+            panic!("orderType is not a complex type");
+        };
+        let id = order_type.attributes.get_attribute(&QName::local("id")).unwrap();
+        assert_eq!(id.annotation.as_ref().unwrap().documentation, vec!["Order number"]);
+        let item = order_type.model_group().unwrap().particles.iter().find_map(|particle| match particle {
+            GroupParticle::Element(particle) => particle.element().cloned(),
+            _ => None,
+        });
+        assert_eq!(item.unwrap().annotation.as_ref().unwrap().documentation, vec!["An ordered item"]);
+
+        // The first annotated component of a name
+        let documentation = |name: &str| schema.documentation_for(&QName::local(name)).map(<[String]>::to_vec);
+        assert_eq!(documentation("order"), Some(vec!["A purchase order.".to_string()]));
+        assert_eq!(documentation("orderType"), Some(vec!["Content of an order".to_string()]));
+        assert_eq!(documentation("currency"), Some(vec!["ISO 4217 code".to_string()]));
+        assert_eq!(documentation("audit"), Some(vec!["Audit attributes".to_string()]));
+        assert_eq!(documentation("lines"), Some(vec!["Order lines".to_string()]));
+        assert_eq!(documentation("missing"), None);
+    }
+
+    #[test]
+    fn test_parse_schema_from_reader() {
+        use std::io::{Cursor, Write};
+
+        // Bytes
+        let schema = XsdSchema::from_bytes(BOOK_XSD.as_bytes()).expect("Failed to parse bytes");
+        assert_eq!(schema.element_count(), 1);
+
+        // In-memory cursor
+        let schema = XsdSchema::from_reader(Cursor::new(BOOK_XSD.as_bytes())).expect("Failed to parse cursor");
+        assert_eq!(schema.target_namespace.as_deref(), Some("http://example.com/book"));
+        assert_eq!(schema.element_count(), 1);
+
+        // File opened as a reader
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/comparison/schemas/book.xsd");
+        let file = std::fs::File::open(&path).expect("Failed to open book.xsd");
+        let schema = XsdSchema::from_reader(file).expect("Failed to parse file");
+        let expected = XsdSchema::from_file(&path).expect("Failed to parse book.xsd");
+        assert_eq!(schema.element_count(), expected.element_count());
+        assert_eq!(schema.type_count(), expected.type_count());
+
+        // Gzip-compressed schema
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(BOOK_XSD.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let schema = XsdSchema::from_reader(flate2::read::GzDecoder::new(compressed.as_slice()))
+            .expect("Failed to parse gzip stream");
+        assert_eq!(schema.element_count(), 1);
+        assert!(schema.lookup_type(&QName::namespaced("http://example.com/book", "ratingType")).is_some());
+
+        // Read errors and malformed content are errors
+        assert!(XsdSchema::from_reader(flate2::read::GzDecoder::new(BOOK_XSD.as_bytes())).is_err());
+        assert!(XsdSchema::from_reader(Cursor::new(b"<xs:schema></xs:element>".to_vec())).is_err());
+    }
+
     #[test]
     fn test_parse_empty_document_fails() {
         let result = XsdSchema::from_string("");
@@ -1925,6 +3083,274 @@ mod tests {
         assert!(isbn_type.is_some(), "isbnType should exist");
     }
 
+    #[test]
+    fn test_schema_sources_pass_meta_validation() {
+        for xsd in [SIMPLE_XSD, BOOK_XSD] {
+            let result = XsdSchema::validate_schema_source(xsd);
+            assert!(result.valid, "{:?}", result.error_messages());
+        }
+    }
+
+    #[test]
+    fn test_parsed_schemas_are_valid_xsd() {
+        let simple = XsdSchema::from_string(SIMPLE_XSD).expect("Failed to parse schema");
+        assert!(simple.is_valid_xsd());
+        let book = XsdSchema::from_string(BOOK_XSD).expect("Failed to parse book schema");
+        assert!(book.is_valid_xsd());
+    }
+
+    #[test]
+    fn test_book_schema_xsd_round_trip() {
+        let schema = XsdSchema::from_string(BOOK_XSD).expect("Failed to parse book schema");
+        let xsd = schema.to_xsd_string().expect("Failed to serialize book schema");
+        let reparsed = XsdSchema::from_string(&xsd).expect("Failed to parse serialized schema");
+
+        assert_eq!(reparsed.target_namespace, schema.target_namespace);
+        assert!(reparsed.element_form_default.is_qualified());
+        assert_eq!(reparsed.element_count(), schema.element_count());
+        assert_eq!(reparsed.type_count(), schema.type_count());
+        assert!(xsd.contains(r#"<xs:pattern value="\d{13}"/>"#));
+
+        let book = |isbn: &str, category: &str| {
+            format!(
+                r#"<book xmlns="http://example.com/book" id="b1" category="{}"><title>T</title><author>A</author><year>2020</year><isbn>{}</isbn></book>"#,
+                category, isbn
+            )
+        };
+        for (document, valid) in [
+            (book("9781234567897", "fiction"), true),
+            (book("978123456789", "fiction"), false),
+            (book("9781234567897", "poetry"), false),
+        ] {
+            assert_eq!(schema.is_valid_string(&document), valid, "{}", document);
+            assert_eq!(reparsed.is_valid_string(&document), valid, "{}", document);
+        }
+    }
+
+    #[test]
+    fn test_parse_xsd_regex_patterns() {
+        let xsd = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="ncName">
+        <xs:restriction base="xs:string">
+            <xs:pattern value="[\i-[:]][\c-[:]]*"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="answer">
+        <xs:restriction base="xs:string">
+            <xs:pattern value="yes|no"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:element name="code">
+        <xs:simpleType>
+            <xs:restriction base="xs:string">
+                <xs:pattern value="\p{Lu}{2}\s\d+"/>
+            </xs:restriction>
+        </xs:simpleType>
+    </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).expect("Failed to parse schema");
+
+        let Some(GlobalType::Simple(nc_name)) = schema.lookup_type(&QName::local("ncName")) else {
+            panic!("ncName should be a simple type");
+        };
+        assert!(nc_name.validate_value("élément-1").is_ok());
+        assert!(nc_name.validate_value("xs:element").is_err());
+        assert!(nc_name.validate_value("1element").is_err());
+
+        let Some(GlobalType::Simple(answer)) = schema.lookup_type(&QName::local("answer")) else {
+            panic!("answer should be a simple type");
+        };
+        assert!(answer.validate_value("no").is_ok());
+        assert!(answer.validate_value("yesno").is_err());
+
+        let code = |value: &str| schema.is_valid(&Document::from_string(&format!("<code>{}</code>", value)).unwrap());
+        assert!(code("AB 12"));
+        assert!(!code("Ab 12"));
+        assert!(!code("AB\u{A0}12"));
+    }
+
+    #[test]
+    fn test_parse_explicit_timezone_facet() {
+        let xsd = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="zonedDateTime">
+        <xs:restriction base="xs:dateTime">
+            <xs:explicitTimezone value="required"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="localDate">
+        <xs:restriction base="xs:date">
+            <xs:explicitTimezone value="prohibited"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="anyTime">
+        <xs:restriction base="xs:time">
+            <xs:explicitTimezone value="optional"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:element name="event">
+        <xs:complexType>
+            <xs:sequence>
+                <xs:element name="start" type="zonedDateTime"/>
+                <xs:element name="day" type="localDate"/>
+                <xs:element name="at" type="anyTime"/>
+                <xs:element name="until">
+                    <xs:simpleType>
+                        <xs:restriction base="xs:date">
+                            <xs:explicitTimezone value="required"/>
+                        </xs:restriction>
+                    </xs:simpleType>
+                </xs:element>
+            </xs:sequence>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).expect("Failed to parse schema");
+
+        let simple = |name: &str| match schema.lookup_type(&QName::local(name)) {
+            Some(GlobalType::Simple(st)) => Arc::clone(st),
+            _ => panic!("{} should be a simple type", name),
+        };
+        let zoned = simple("zonedDateTime");
+        assert!(zoned.facet_summary().contains("explicitTimezone=required"));
+        assert!(zoned.validate_value("2024-01-15T10:30:00Z").is_ok());
+        assert!(zoned.validate_value("2024-01-15T10:30:00+01:00").is_ok());
+        assert!(zoned.validate_value("2024-01-15T10:30:00").is_err());
+
+        let local = simple("localDate");
+        assert!(local.validate_value("2024-01-15").is_ok());
+        assert!(local.validate_value("2024-01-15Z").is_err());
+        assert!(local.validate_value("2024-01-15-05:00").is_err());
+
+        let any = simple("anyTime");
+        assert!(any.validate_value("10:30:00").is_ok());
+        assert!(any.validate_value("10:30:00Z").is_ok());
+
+        let event = |start: &str, day: &str, until: &str| {
+            let xml = format!(
+                "<event><start>{}</start><day>{}</day><at>12:00:00</at><until>{}</until></event>",
+                start, day, until
+            );
+            schema.is_valid(&Document::from_string(&xml).unwrap())
+        };
+        assert!(event("2024-01-15T10:30:00Z", "2024-01-15", "2024-02-01Z"));
+        assert!(!event("2024-01-15T10:30:00", "2024-01-15", "2024-02-01Z"));
+        assert!(!event("2024-01-15T10:30:00Z", "2024-01-15Z", "2024-02-01Z"));
+        assert!(!event("2024-01-15T10:30:00Z", "2024-01-15", "2024-02-01"));
+        assert!(schema.errors().is_empty());
+
+        // The facet only applies to date/time types
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="zonedName">
+        <xs:restriction base="xs:string">
+            <xs:explicitTimezone value="required"/>
+        </xs:restriction>
+    </xs:simpleType>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).expect("Failed to parse schema");
+        assert_eq!(schema.errors().len(), 1);
+        assert!(schema.errors()[0].message.contains("'zonedName' only applies to date and time types"));
+    }
+
+    #[test]
+    fn test_parse_digits_facets() {
+        let xsd = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="price">
+        <xs:restriction base="xs:decimal">
+            <xs:totalDigits value="4"/>
+            <xs:fractionDigits value="2"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:element name="amount">
+        <xs:simpleType>
+            <xs:restriction base="xs:decimal">
+                <xs:totalDigits value="4"/>
+            </xs:restriction>
+        </xs:simpleType>
+    </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).expect("Failed to parse schema");
+
+        let Some(GlobalType::Simple(price)) = schema.lookup_type(&QName::local("price")) else {
+            panic!("price should be a simple type");
+        };
+        assert!(price.facet_summary().contains("totalDigits=4"));
+        assert!(price.validate_value("12.34").is_ok());
+        assert!(price.validate_value("-99.9").is_ok());
+        assert!(price.validate_value("12.340").is_ok());
+        assert!(price.validate_value("123.45").is_err());
+        assert!(price.validate_value("1.234").is_err());
+        assert!(price.validate_value("12345").is_err());
+
+        let amount = |value: &str| schema.is_valid(&Document::from_string(&format!("<amount>{}</amount>", value)).unwrap());
+        assert!(amount("9999"));
+        assert!(amount("0.125"));
+        assert!(!amount("10000"));
+        assert!(!amount("1.2345"));
+    }
+
+    #[test]
+    fn test_parse_range_facets() {
+        let xsd = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="percentInclusive">
+        <xs:restriction base="xs:decimal">
+            <xs:minInclusive value="0"/>
+            <xs:maxInclusive value="100"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="percentExclusive">
+        <xs:restriction base="xs:decimal">
+            <xs:minExclusive value="0"/>
+            <xs:maxExclusive value="100"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="after2000">
+        <xs:restriction base="xs:date">
+            <xs:minExclusive value="2000-01-01"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:element name="deadline">
+        <xs:simpleType>
+            <xs:restriction base="xs:dateTime">
+                <xs:maxExclusive value="2030-01-01T00:00:00Z"/>
+            </xs:restriction>
+        </xs:simpleType>
+    </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).expect("Failed to parse schema");
+
+        let simple = |name: &str| match schema.lookup_type(&QName::local(name)) {
+            Some(GlobalType::Simple(st)) => Arc::clone(st),
+            _ => panic!("{} should be a simple type", name),
+        };
+
+        // Boundary values pass inclusive facets but fail exclusive ones
+        let inclusive = simple("percentInclusive");
+        let exclusive = simple("percentExclusive");
+        for boundary in ["0", "100", "100.0"] {
+            assert!(inclusive.validate_value(boundary).is_ok(), "{}", boundary);
+            assert!(exclusive.validate_value(boundary).is_err(), "{}", boundary);
+        }
+        assert!(exclusive.validate_value("0.01").is_ok());
+        assert!(exclusive.validate_value("99.99").is_ok());
+        assert!(inclusive.validate_value("100.01").is_err());
+        assert!(exclusive.facet_summary().contains("> 0 and < 100"));
+
+        let after2000 = simple("after2000");
+        assert!(after2000.validate_value("2000-01-01").is_err());
+        assert!(after2000.validate_value("1999-12-31").is_err());
+        assert!(after2000.validate_value("2000-01-02").is_ok());
+
+        let deadline = |value: &str| schema.is_valid(&Document::from_string(&format!("<deadline>{}</deadline>", value)).unwrap());
+        assert!(deadline("2029-12-31T23:59:59Z"));
+        assert!(!deadline("2030-01-01T00:00:00Z"));
+        assert!(!deadline("2030-01-01T01:00:00+01:00"));
+        assert!(deadline("2030-01-01T00:30:00+01:00"));
+    }
+
     #[test]
     fn test_version_detection() {
         let xsd11 = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -2081,6 +3507,177 @@ mod tests {
         std::fs::remove_dir_all(&temp_dir).ok();
     }
 
+    /// Parse a schema redefining components of `BASE_REDEFINE_XSD`
+    fn parse_redefine(main_xsd: &str) -> Result<XsdSchema> {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("base.xsd"), BASE_REDEFINE_XSD).unwrap();
+        std::fs::write(dir.path().join("main.xsd"), main_xsd).unwrap();
+        XsdSchema::from_file(dir.path().join("main.xsd"))
+    }
+
+    const BASE_REDEFINE_XSD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="codeType">
+        <xs:restriction base="xs:string">
+            <xs:pattern value="[A-Z]+"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:complexType name="personType">
+        <xs:sequence>
+            <xs:element name="name" type="xs:string"/>
+        </xs:sequence>
+    </xs:complexType>
+    <xs:group name="contactGroup">
+        <xs:sequence>
+            <xs:element name="phone" type="xs:string"/>
+        </xs:sequence>
+    </xs:group>
+    <xs:attributeGroup name="commonAttrs">
+        <xs:attribute name="id" type="xs:string"/>
+    </xs:attributeGroup>
+</xs:schema>"#;
+
+    #[test]
+    fn test_redefine_types() {
+        let schema = parse_redefine(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:redefine schemaLocation="base.xsd">
+        <xs:simpleType name="codeType">
+            <xs:restriction base="codeType">
+                <xs:maxLength value="3"/>
+            </xs:restriction>
+        </xs:simpleType>
+        <xs:complexType name="personType">
+            <xs:complexContent>
+                <xs:extension base="personType">
+                    <xs:sequence>
+                        <xs:element name="email" type="xs:string"/>
+                    </xs:sequence>
+                </xs:extension>
+            </xs:complexContent>
+        </xs:complexType>
+    </xs:redefine>
+    <xs:element name="person" type="personType"/>
+    <xs:element name="code" type="codeType"/>
+</xs:schema>"#,
+        )
+        .expect("Failed to parse schema with redefine");
+
+        assert!(schema.includes.is_empty());
+        assert_eq!(schema.redefines.len(), 1);
+        assert_eq!(schema.redefines[0].location, "base.xsd");
+        assert_eq!(schema.redefines[0].redefinitions.len(), 2);
+
+        // The redefined type extends the original content
+        match schema.lookup_type(&QName::local("personType")) {
+            Some(GlobalType::Complex(ct)) => assert!(ct.redefine.is_some()),
+            other => panic!("Expected complex personType, got {:?}", other.map(|t| t.is_complex())),
+        }
+        assert!(schema.is_valid_string("<person><name>Ann</name><email>ann@example.com</email></person>"));
+        assert!(!schema.is_valid_string("<person><name>Ann</name><phone>555</phone></person>"));
+
+        // The redefined simple type keeps the facets of the original
+        assert!(schema.is_valid_string("<code>ABC</code>"));
+        assert!(!schema.is_valid_string("<code>ABCD</code>"));
+        assert!(!schema.is_valid_string("<code>abc</code>"));
+    }
+
+    #[test]
+    fn test_redefine_type_without_self_derivation() {
+        let result = parse_redefine(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:redefine schemaLocation="base.xsd">
+        <xs:complexType name="personType">
+            <xs:sequence>
+                <xs:element name="email" type="xs:string"/>
+            </xs:sequence>
+        </xs:complexType>
+    </xs:redefine>
+</xs:schema>"#,
+        );
+
+        let err = result.expect_err("Redefinition without self-derivation should fail");
+        assert!(err.to_string().contains("'personType' must derive from itself"), "{}", err);
+    }
+
+    #[test]
+    fn test_redefine_missing_component() {
+        let result = parse_redefine(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:redefine schemaLocation="base.xsd">
+        <xs:group name="undefinedGroup">
+            <xs:sequence>
+                <xs:group ref="undefinedGroup"/>
+            </xs:sequence>
+        </xs:group>
+    </xs:redefine>
+</xs:schema>"#,
+        );
+
+        let err = result.expect_err("Redefinition of an undefined group should fail");
+        assert!(err.to_string().contains("group 'undefinedGroup' is not defined in 'base.xsd'"), "{}", err);
+    }
+
+    #[test]
+    fn test_redefine_group() {
+        let schema = parse_redefine(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:redefine schemaLocation="base.xsd">
+        <xs:group name="contactGroup">
+            <xs:sequence>
+                <xs:group ref="contactGroup"/>
+                <xs:element name="email" type="xs:string" minOccurs="0"/>
+            </xs:sequence>
+        </xs:group>
+    </xs:redefine>
+    <xs:complexType name="contactType">
+        <xs:group ref="contactGroup"/>
+    </xs:complexType>
+    <xs:element name="contact" type="contactType"/>
+</xs:schema>"#,
+        )
+        .expect("Failed to parse schema with redefine");
+
+        let group = &schema.maps.global_maps.groups[&QName::local("contactGroup")];
+        assert!(group.redefine.is_some());
+
+        assert!(schema.is_valid_string("<contact><phone>555</phone><email>a@example.com</email></contact>"));
+        assert!(schema.is_valid_string("<contact><phone>555</phone></contact>"));
+        assert!(!schema.is_valid_string("<contact><email>a@example.com</email><phone>555</phone></contact>"));
+    }
+
+    #[test]
+    fn test_redefine_attribute_group() {
+        let schema = parse_redefine(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:redefine schemaLocation="base.xsd">
+        <xs:attributeGroup name="commonAttrs">
+            <xs:attributeGroup ref="commonAttrs"/>
+            <xs:attribute name="lang" type="xs:language"/>
+        </xs:attributeGroup>
+    </xs:redefine>
+    <xs:complexType name="noteType">
+        <xs:attributeGroup ref="commonAttrs"/>
+    </xs:complexType>
+    <xs:element name="note" type="noteType"/>
+</xs:schema>"#,
+        )
+        .expect("Failed to parse schema with redefine");
+
+        let group = &schema.maps.global_maps.attribute_groups[&QName::local("commonAttrs")];
+        assert!(group.redefine.is_some());
+        assert!(group.get_attribute(&QName::local("id")).is_some());
+        assert!(group.get_attribute(&QName::local("lang")).is_some());
+
+        assert!(schema.is_valid_string(r#"<note id="n1" lang="en"/>"#));
+        assert!(!schema.is_valid_string(r#"<note other="x"/>"#));
+    }
+
     #[test]
     fn test_parse_import() {
         // Test parsing a schema with xs:import
@@ -2151,6 +3748,177 @@ mod tests {
         std::fs::remove_dir_all(&temp_dir).ok();
     }
 
+    #[test]
+    fn test_from_sources_with_includes_and_imports() {
+        let main_xsd = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           targetNamespace="http://example.com/test"
+           xmlns:tns="http://example.com/test"
+           xmlns:addr="http://example.com/address"
+           elementFormDefault="qualified">
+    <xs:include schemaLocation="types/person.xsd"/>
+    <xs:import namespace="http://example.com/address" schemaLocation="./address.xsd"/>
+    <xs:element name="root" type="tns:personType"/>
+</xs:schema>"#;
+
+        let person_xsd = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           targetNamespace="http://example.com/test"
+           xmlns:tns="http://example.com/test"
+           elementFormDefault="qualified">
+    <xs:include schemaLocation="../common/ids.xsd"/>
+    <xs:complexType name="personType">
+        <xs:sequence>
+            <xs:element name="name" type="xs:string"/>
+            <xs:element name="id" type="tns:idType"/>
+        </xs:sequence>
+    </xs:complexType>
+</xs:schema>"#;
+
+        // No targetNamespace: chameleon-included into the parent's namespace
+        let ids_xsd = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="idType">
+        <xs:restriction base="xs:string">
+            <xs:pattern value="[A-Z]{2}[0-9]{4}"/>
+        </xs:restriction>
+    </xs:simpleType>
+</xs:schema>"#;
+
+        let address_xsd = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           targetNamespace="http://example.com/address">
+    <xs:element name="address" type="xs:string"/>
+</xs:schema>"#;
+
+        let schema = XsdSchema::from_sources(&[
+            (main_xsd, "schemas/main.xsd"),
+            (address_xsd, "schemas/address.xsd"),
+            (person_xsd, "schemas/types/person.xsd"),
+            (ids_xsd, "schemas/common/ids.xsd"),
+        ])
+        .expect("Failed to load schema from sources");
+
+        let ns = Some("http://example.com/test".to_string());
+        assert!(schema.lookup_type(&QName::new(ns.clone(), "personType")).is_some());
+        assert!(schema.lookup_type(&QName::new(ns, "idType")).is_some());
+
+        let import = schema.get_import("http://example.com/address").expect("Import should exist");
+        assert!(import.schema.is_some(), "Imported schema should be loaded from memory");
+        let address_qname = QName::new(Some("http://example.com/address".to_string()), "address");
+        assert!(schema.lookup_element(&address_qname).is_some());
+
+        let valid = Document::from_string(
+            r#"<root xmlns="http://example.com/test"><name>Ann</name><id>AB1234</id></root>"#,
+        )
+        .unwrap();
+        assert!(schema.is_valid(&valid));
+
+        let invalid = Document::from_string(
+            r#"<root xmlns="http://example.com/test"><name>Ann</name><id>1234</id></root>"#,
+        )
+        .unwrap();
+        assert!(!schema.is_valid(&invalid));
+    }
+
+    #[test]
+    fn test_from_sources_errors() {
+        assert!(XsdSchema::from_sources(&[]).is_err());
+        assert!(XsdSchema::from_sources(&[("<notASchema/>", "main.xsd")]).is_err());
+
+        // A missing include is skipped like a missing file
+        let main_xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:include schemaLocation="missing.xsd"/>
+    <xs:element name="root" type="xs:string"/>
+</xs:schema>"#;
+        let schema = XsdSchema::from_sources(&[(main_xsd, "main.xsd")]).unwrap();
+        assert_eq!(schema.element_count(), 1);
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn test_from_zip_with_include() {
+        use std::io::{Cursor, Write};
+
+        let main_xsd = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:include schemaLocation="common/types.xsd"/>
+    <xs:element name="code" type="codeType"/>
+</xs:schema>"#;
+        let types_xsd = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="codeType">
+        <xs:restriction base="xs:string">
+            <xs:pattern value="[A-Z]{3}"/>
+        </xs:restriction>
+    </xs:simpleType>
+</xs:schema>"#;
+
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, content) in [("xsd/main.xsd", main_xsd), ("xsd/common/types.xsd", types_xsd)] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        let archive = writer.finish().unwrap().into_inner();
+
+        let schema = XsdSchema::from_zip(Cursor::new(archive.clone()), "xsd/main.xsd").unwrap();
+        assert!(schema.lookup_type(&QName::local("codeType")).is_some());
+        assert!(schema.is_valid_string("<code>ABC</code>"));
+        assert!(!schema.is_valid_string("<code>abc</code>"));
+
+        assert!(XsdSchema::from_zip(Cursor::new(archive), "xsd/missing.xsd").is_err());
+        assert!(XsdSchema::from_zip(Cursor::new(b"not a zip".to_vec()), "main.xsd").is_err());
+    }
+
+    #[test]
+    fn test_from_dtd_with_validation() {
+        let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+
+        let (schema, warnings) = XsdSchema::from_dtd_with_validation(fixtures.join("doctype_schema.xsd")).unwrap();
+        assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+        assert_eq!(schema.element_count(), 1);
+
+        // DTD problems are reported as warnings, the schema is still parsed
+        let (schema, warnings) =
+            XsdSchema::from_dtd_with_validation(fixtures.join("doctype_schema_invalid.xsd")).unwrap();
+        let messages: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Attribute 'maxOcurs' is not declared for element 'element'",
+                "Attribute 'use' has value 'sometimes', expected one of prohibited|optional|required",
+            ]
+        );
+        assert_eq!(schema.element_count(), 1);
+    }
+
+    #[test]
+    fn test_validating_doctype_external_dtd() {
+        // Schemas without a DOCTYPE are not checked
+        let (_, warnings) = Document::from_string_validating_doctype(
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" bogus="1"/>"#,
+        )
+        .unwrap();
+        assert!(warnings.is_empty());
+
+        // An unknown external DTD is reported and the internal subset is still used
+        let xml = r#"<!DOCTYPE xs:schema SYSTEM "custom.dtd" [
+    <!ELEMENT xs:schema EMPTY>
+    <!ATTLIST xs:schema version CDATA #REQUIRED>
+]>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"/>"#;
+        let (_, warnings) = Document::from_string_validating_doctype(xml).unwrap();
+        let messages: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "External DTD 'custom.dtd' could not be loaded; only the internal subset is validated",
+                "Required attribute 'version' is missing on element 'schema'",
+            ]
+        );
+    }
+
     #[test]
     fn test_complex_content_extension() {
         // Test parsing and resolving complex content extension
@@ -2225,6 +3993,122 @@ mod tests {
         }
     }
 
+    const MIXED_DERIVATION_XSD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           targetNamespace="http://example.com/mixed"
+           xmlns:tns="http://example.com/mixed"
+           elementFormDefault="qualified">
+    <xs:complexType name="mixedBase" mixed="true">
+        <xs:sequence>
+            <xs:element name="b" type="xs:string" minOccurs="0"/>
+        </xs:sequence>
+    </xs:complexType>
+
+    <xs:complexType name="plainBase">
+        <xs:sequence>
+            <xs:element name="b" type="xs:string" minOccurs="0"/>
+        </xs:sequence>
+    </xs:complexType>
+
+    <!-- Mixed base, derived types without their own mixed attribute -->
+    <xs:complexType name="inheritsMixed">
+        <xs:complexContent>
+            <xs:extension base="tns:mixedBase">
+                <xs:sequence>
+                    <xs:element name="i" type="xs:string" minOccurs="0"/>
+                </xs:sequence>
+            </xs:extension>
+        </xs:complexContent>
+    </xs:complexType>
+    <xs:complexType name="inheritsMixedTwice">
+        <xs:complexContent>
+            <xs:extension base="tns:inheritsMixed"/>
+        </xs:complexContent>
+    </xs:complexType>
+
+    <!-- Mixed base, explicitly restricted to element-only content -->
+    <xs:complexType name="elementOnlyRestriction">
+        <xs:complexContent mixed="false">
+            <xs:restriction base="tns:mixedBase">
+                <xs:sequence>
+                    <xs:element name="b" type="xs:string" minOccurs="0"/>
+                </xs:sequence>
+            </xs:restriction>
+        </xs:complexContent>
+    </xs:complexType>
+
+    <!-- Element-only base, derived type made mixed -->
+    <xs:complexType name="mixedExtension">
+        <xs:complexContent mixed="true">
+            <xs:extension base="tns:plainBase"/>
+        </xs:complexContent>
+    </xs:complexType>
+
+    <!-- complexContent/@mixed takes precedence over complexType/@mixed -->
+    <xs:complexType name="contentOverridesType" mixed="true">
+        <xs:complexContent mixed="false">
+            <xs:extension base="tns:plainBase"/>
+        </xs:complexContent>
+    </xs:complexType>
+
+    <xs:element name="inherits" type="tns:inheritsMixedTwice"/>
+    <xs:element name="restricted" type="tns:elementOnlyRestriction"/>
+    <xs:element name="extended" type="tns:mixedExtension"/>
+    <xs:element name="anonymous">
+        <xs:complexType mixed="false">
+            <xs:complexContent mixed="true">
+                <xs:extension base="tns:plainBase"/>
+            </xs:complexContent>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>"#;
+
+    #[test]
+    fn test_complex_content_mixed_precedence() {
+        let schema = XsdSchema::from_string(MIXED_DERIVATION_XSD).unwrap();
+        let is_mixed = |name: &str| match schema
+            .lookup_type(&QName::new(Some("http://example.com/mixed".to_string()), name))
+        {
+            Some(GlobalType::Complex(ct)) => ct.mixed,
+            _ => panic!("{} should be a complex type", name),
+        };
+
+        assert!(is_mixed("mixedExtension"));
+        assert!(!is_mixed("contentOverridesType"));
+
+        let doc = Document::from_string(
+            r#"<anonymous xmlns="http://example.com/mixed">text <b>bold</b> more</anonymous>"#,
+        )
+        .unwrap();
+        assert!(schema.is_valid(&doc), "complexContent/@mixed should override complexType/@mixed");
+    }
+
+    #[test]
+    fn test_mixed_inheritance_through_derivation() {
+        let schema = XsdSchema::from_string(MIXED_DERIVATION_XSD).unwrap();
+        let is_mixed = |name: &str| match schema
+            .lookup_type(&QName::new(Some("http://example.com/mixed".to_string()), name))
+        {
+            Some(GlobalType::Complex(ct)) => ct.mixed,
+            _ => panic!("{} should be a complex type", name),
+        };
+
+        // Mixed base with non-mixed derived types
+        assert!(is_mixed("inheritsMixed"));
+        assert!(is_mixed("inheritsMixedTwice"));
+        assert!(!is_mixed("elementOnlyRestriction"));
+
+        // Non-mixed base with a mixed derived type
+        assert!(!is_mixed("plainBase"));
+        assert!(is_mixed("mixedExtension"));
+
+        let validate = |xml: &str| schema.is_valid(&Document::from_string(xml).unwrap());
+        assert!(validate(r#"<inherits xmlns="http://example.com/mixed">a <b>b</b> c <i>i</i></inherits>"#));
+        assert!(!validate(r#"<restricted xmlns="http://example.com/mixed">a <b>b</b></restricted>"#));
+        assert!(validate(r#"<restricted xmlns="http://example.com/mixed"><b>b</b></restricted>"#));
+        assert!(validate(r#"<extended xmlns="http://example.com/mixed">a <b>b</b></extended>"#));
+    }
+
     #[test]
     fn test_complex_content_restriction() {
         // Test parsing complex content restriction
@@ -2559,4 +4443,404 @@ mod tests {
             panic!("personType should be a complex type");
         }
     }
+
+    #[cfg(feature = "tokio")]
+    fn write_async_include_set(dir: &Path) {
+        let header = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           targetNamespace="http://example.com/test"
+           xmlns:tns="http://example.com/test"
+           elementFormDefault="qualified">"#;
+
+        std::fs::write(
+            dir.join("main.xsd"),
+            format!(
+                r#"{header}
+    <xs:include schemaLocation="a.xsd"/>
+    <xs:include schemaLocation="b.xsd"/>
+    <xs:element name="root" type="tns:rootType"/>
+</xs:schema>"#
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("a.xsd"),
+            format!(
+                r#"{header}
+    <xs:include schemaLocation="c.xsd"/>
+    <xs:complexType name="rootType">
+        <xs:sequence>
+            <xs:element name="name" type="xs:string"/>
+            <xs:element name="count" type="tns:countType"/>
+        </xs:sequence>
+    </xs:complexType>
+</xs:schema>"#
+            ),
+        )
+        .unwrap();
+        // b.xsd includes main.xsd back (circular)
+        std::fs::write(
+            dir.join("b.xsd"),
+            format!(
+                r#"{header}
+    <xs:include schemaLocation="main.xsd"/>
+    <xs:element name="other" type="xs:string"/>
+</xs:schema>"#
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("c.xsd"),
+            format!(
+                r#"{header}
+    <xs:simpleType name="countType">
+        <xs:restriction base="xs:integer"/>
+    </xs:simpleType>
+</xs:schema>"#
+            ),
+        )
+        .unwrap();
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_from_file_async_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        write_async_include_set(dir.path());
+        let main_path = dir.path().join("main.xsd");
+
+        let schema = XsdSchema::from_file_async(&main_path).await.expect("Failed to parse main.xsd");
+        let sync_schema = XsdSchema::from_file(&main_path).unwrap();
+
+        assert_eq!(schema.element_count(), 2);
+        assert_eq!(schema.element_count(), sync_schema.element_count());
+        assert_eq!(schema.type_count(), sync_schema.type_count());
+
+        let ns = "http://example.com/test";
+        assert!(schema.lookup_type(&QName::namespaced(ns, "rootType")).is_some());
+        assert!(schema.lookup_type(&QName::namespaced(ns, "countType")).is_some());
+
+        assert!(schema.is_valid_string(
+            r#"<root xmlns="http://example.com/test"><name>x</name><count>3</count></root>"#
+        ));
+        assert!(!schema.is_valid_string(
+            r#"<root xmlns="http://example.com/test"><name>x</name><count>three</count></root>"#
+        ));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_from_file_async_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = XsdSchema::from_file_async(&dir.path().join("missing.xsd")).await;
+        assert!(matches!(result, Err(Error::Resource(_))));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_from_file_async_imports_and_broken_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("main.xsd"),
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           xmlns:u="http://example.com/units" targetNamespace="http://example.com/main">
+    <xs:import namespace="http://example.com/units" schemaLocation="units.xsd"/>
+    <xs:element name="length" type="u:meters"/>
+</xs:schema>"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("units.xsd"),
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="http://example.com/units">
+    <xs:simpleType name="meters">
+        <xs:restriction base="xs:decimal"/>
+    </xs:simpleType>
+</xs:schema>"#,
+        )
+        .unwrap();
+        let schema = XsdSchema::from_file_async(&dir.path().join("main.xsd")).await.unwrap();
+        assert!(schema.lookup_type(&QName::namespaced("http://example.com/units", "meters")).is_some());
+
+        std::fs::write(
+            dir.path().join("broken.xsd"),
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:include schemaLocation="missing.xsd"/>
+</xs:schema>"#,
+        )
+        .unwrap();
+        assert!(XsdSchema::from_file_async(&dir.path().join("broken.xsd")).await.is_err());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_validate_file_async_shared_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        write_async_include_set(dir.path());
+        let schema = Arc::new(XsdSchema::from_file_async(&dir.path().join("main.xsd")).await.unwrap());
+
+        let valid_path = dir.path().join("valid.xml");
+        let invalid_path = dir.path().join("invalid.xml");
+        std::fs::write(
+            &valid_path,
+            r#"<root xmlns="http://example.com/test"><name>x</name><count>3</count></root>"#,
+        )
+        .unwrap();
+        std::fs::write(
+            &invalid_path,
+            r#"<root xmlns="http://example.com/test"><name>x</name><count>three</count></root>"#,
+        )
+        .unwrap();
+
+        let tasks: Vec<_> = [valid_path, invalid_path, dir.path().join("missing.xml")]
+            .into_iter()
+            .map(|path| {
+                let schema = Arc::clone(&schema);
+                tokio::spawn(async move { schema.validate_file_async(&path).await.valid })
+            })
+            .collect();
+
+        let mut results = Vec::new();
+        for task in tasks {
+            results.push(task.await.unwrap());
+        }
+        assert_eq!(results, vec![true, false, false]);
+    }
+
+    const DERIVATION_CHAIN_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="report" type="finalType"/>
+  <xs:complexType name="finalType">
+    <xs:complexContent>
+      <xs:extension base="middleType">
+        <xs:sequence>
+          <xs:element name="summary" type="xs:string"/>
+        </xs:sequence>
+        <xs:attribute name="status" type="xs:string"/>
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:complexType name="middleType">
+    <xs:complexContent>
+      <xs:extension base="baseType">
+        <xs:sequence>
+          <xs:group ref="detailsGroup"/>
+        </xs:sequence>
+        <xs:attribute name="version" type="xs:integer"/>
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:group name="detailsGroup">
+    <xs:sequence>
+      <xs:element name="author" type="xs:string"/>
+      <xs:element name="date" type="xs:date"/>
+    </xs:sequence>
+  </xs:group>
+  <xs:complexType name="baseType">
+    <xs:sequence>
+      <xs:element name="title" type="xs:string"/>
+    </xs:sequence>
+    <xs:attribute name="id" type="xs:ID"/>
+  </xs:complexType>
+</xs:schema>"#;
+
+    /// Element names of a model group, in model order
+    fn content_element_names(group: &XsdGroup, names: &mut Vec<String>) {
+        for particle in &group.particles {
+            match particle {
+                GroupParticle::Element(ep) => names.push(ep.name.local_name.clone()),
+                GroupParticle::Group(nested) => content_element_names(nested, names),
+                GroupParticle::Any(_) => names.push("*".to_string()),
+            }
+        }
+    }
+
+    /// Describe the user-defined types of a schema, in map order
+    fn schema_fingerprint(schema: &XsdSchema) -> Vec<String> {
+        schema
+            .types()
+            .filter(|(qname, _)| qname.namespace.as_deref() != Some(XSD_NAMESPACE))
+            .map(|(qname, global_type)| match global_type {
+                GlobalType::Complex(ct) => {
+                    let mut elements = Vec::new();
+                    if let Some(group) = ct.model_group() {
+                        content_element_names(group, &mut elements);
+                    }
+                    let mut attributes: Vec<String> =
+                        ct.attributes.iter_attributes().map(|a| a.name().local_name.clone()).collect();
+                    attributes.sort();
+                    format!("{}: ({}) [{}]", qname.local_name, elements.join(", "), attributes.join(", "))
+                }
+                GlobalType::Simple(_) => qname.local_name.clone(),
+            })
+            .collect()
+    }
+
+    fn build_ordered_from_string(xsd: &str) -> XsdSchema {
+        let doc = Document::from_string(xsd).unwrap();
+        let mut schema = XsdSchema::new();
+        parse_schema_element(&mut schema, doc.root().unwrap()).unwrap();
+        schema.build_ordered().unwrap();
+        schema
+    }
+
+    #[test]
+    fn test_build_ordered_is_deterministic() {
+        let expected = vec![
+            "baseType: (title) [id]".to_string(),
+            "finalType: (title, author, date, summary) [id, status, version]".to_string(),
+            "middleType: (title, author, date) [id, version]".to_string(),
+        ];
+
+        for _ in 0..100 {
+            let schema = build_ordered_from_string(DERIVATION_CHAIN_XSD);
+            assert_eq!(schema_fingerprint(&schema), expected);
+        }
+
+        // Building in declaration order resolves the derivation chain the same way
+        let schema = XsdSchema::from_string(DERIVATION_CHAIN_XSD).unwrap();
+        let mut fingerprint = schema_fingerprint(&schema);
+        fingerprint.sort();
+        assert_eq!(fingerprint, expected);
+
+        let doc = Document::from_string(
+            "<report id=\"r1\" version=\"2\"><title>T</title><author>A</author><date>2024-01-01</date><summary>S</summary></report>",
+        )
+        .unwrap();
+        assert!(schema.is_valid(&doc), "{:?}", schema.validate(&doc).errors);
+    }
+
+    const BROKEN_IMPORT_XSD: &str = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           xmlns:ext="http://example.com/ext"
+           targetNamespace="http://example.com/main"
+           elementFormDefault="qualified">
+    <xs:import namespace="http://example.com/ext" schemaLocation="missing-ext.xsd"/>
+    <xs:complexType name="derivedType">
+        <xs:complexContent>
+            <xs:extension base="ext:baseType">
+                <xs:sequence>
+                    <xs:element name="extra" type="xs:string"/>
+                </xs:sequence>
+            </xs:extension>
+        </xs:complexContent>
+    </xs:complexType>
+    <xs:element name="root">
+        <xs:complexType>
+            <xs:sequence>
+                <xs:element name="name" type="xs:string"/>
+                <xs:element name="count" type="xs:int"/>
+                <xs:element name="payload" type="ext:payloadType"/>
+            </xs:sequence>
+            <xs:attribute name="code" type="ext:codeType"/>
+        </xs:complexType>
+    </xs:element>
+    <xs:element name="other" type="ext:otherType"/>
+</xs:schema>"#;
+
+    #[test]
+    fn test_from_string_lenient_missing_import() {
+        use crate::validators::PlaceholderType;
+
+        let (schema, errors) = XsdSchema::from_string_lenient(BROKEN_IMPORT_XSD);
+        let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages.len(), 5, "{:?}", messages);
+        assert!(messages[0].contains("missing-ext.xsd"));
+        assert!(messages.contains(&"unknown base type '{http://example.com/ext}baseType' of complex type '{http://example.com/main}derivedType'"));
+        assert!(messages.contains(&"unknown type '{http://example.com/ext}otherType' of element '{http://example.com/main}other' replaced with xs:anyType"));
+        assert!(messages.iter().any(|m| m.starts_with("unknown type '{http://example.com/ext}payloadType' of element 'payload'")));
+        assert!(messages.iter().any(|m| m.starts_with("unknown type '{http://example.com/ext}codeType' of attribute 'code'")));
+        assert!(schema.warnings.iter().any(|w| w.contains("otherType")));
+
+        // Elements of unknown types are xs:anyType placeholders
+        let other = schema.lookup_element(&QName::namespaced("http://example.com/main", "other")).unwrap();
+        match &other.element_type {
+            ElementType::Complex(ct) => assert_eq!(ct.name.as_ref().map(|n| n.local_name.as_str()), Some("anyType")),
+            _ => panic!("expected an xs:anyType placeholder"),
+        }
+
+        // Attributes of unknown types keep the original type name
+        let root = schema.lookup_element(&QName::namespaced("http://example.com/main", "root")).unwrap();
+        let ElementType::Complex(root_type) = &root.element_type else {
+            panic!("expected a complex type");
+        };
+        let code = root_type.attributes.iter_attributes().find(|a| a.name().local_name == "code").unwrap();
+        let code_type = code.simple_type().unwrap();
+        assert_eq!(code_type.name(), Some(&QName::namespaced("http://example.com/ext", "codeType")));
+        assert!(code_type.validate_value("anything").is_ok());
+        assert_eq!(PlaceholderType::new(QName::local("t")).original_name(), &QName::local("t"));
+
+        // Resolved declarations still validate
+        assert!(schema.is_valid_string(
+            r#"<root xmlns="http://example.com/main" code="x"><name>a</name><count>1</count><payload><any/>text</payload></root>"#
+        ));
+        assert!(!schema.is_valid_string(
+            r#"<root xmlns="http://example.com/main"><name>a</name><count>one</count><payload/></root>"#
+        ));
+        assert!(!schema.is_valid_string(
+            r#"<root xmlns="http://example.com/main"><count>1</count><payload/></root>"#
+        ));
+        assert!(schema.is_valid_string(r#"<other xmlns="http://example.com/main"><any>text</any></other>"#));
+    }
+
+    #[test]
+    fn test_from_string_lenient_broken_import_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let middle_xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           xmlns:ext="http://example.com/ext"
+           targetNamespace="http://example.com/middle"
+           elementFormDefault="qualified">
+    <xs:import namespace="http://example.com/ext" schemaLocation="missing-ext.xsd"/>
+    <xs:complexType name="itemType">
+        <xs:sequence>
+            <xs:element name="label" type="xs:string"/>
+            <xs:element name="leaf" type="ext:leafType"/>
+        </xs:sequence>
+    </xs:complexType>
+</xs:schema>"#;
+        let middle_path = dir.path().join("middle.xsd");
+        std::fs::write(&middle_path, middle_xsd).unwrap();
+
+        let main_xsd = format!(
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           xmlns:mid="http://example.com/middle"
+           targetNamespace="http://example.com/main"
+           elementFormDefault="qualified">
+    <xs:import namespace="http://example.com/middle" schemaLocation="{}"/>
+    <xs:element name="root">
+        <xs:complexType>
+            <xs:sequence>
+                <xs:element name="item" type="mid:itemType"/>
+                <xs:element name="total" type="xs:int"/>
+            </xs:sequence>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>"#,
+            middle_path.display()
+        );
+
+        let (schema, errors) = XsdSchema::from_string_lenient(&main_xsd);
+        let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages.len(), 2, "{:?}", messages);
+        assert!(messages[0].contains("missing-ext.xsd"));
+        assert!(messages[1].starts_with("unknown type '{http://example.com/ext}leafType' of element 'leaf'"));
+
+        let mid = "http://example.com/middle";
+        let valid = format!(
+            r#"<root xmlns="http://example.com/main"><item><label xmlns="{mid}">a</label><leaf xmlns="{mid}"><x/>y</leaf></item><total>3</total></root>"#
+        );
+        assert!(schema.is_valid_string(&valid), "{:?}", schema.validate_string(&valid).errors);
+        assert!(!schema.is_valid_string(&format!(
+            r#"<root xmlns="http://example.com/main"><item><leaf xmlns="{mid}"/></item><total>3</total></root>"#
+        )));
+        assert!(!schema.is_valid_string(&format!(
+            r#"<root xmlns="http://example.com/main"><item><label xmlns="{mid}">a</label><leaf xmlns="{mid}"/></item><total>x</total></root>"#
+        )));
+    }
+
+    #[test]
+    fn test_from_string_lenient_invalid_document() {
+        let (schema, errors) = XsdSchema::from_string_lenient("<xs:element/>");
+        assert_eq!(schema.element_count(), 0);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("Expected xs:schema root element"));
+    }
 }
@@ -104,6 +104,17 @@ impl Occurs {
         }
     }
 
+    /// The range of occurrences, as (min, max) with None for unbounded
+    ///
+    /// None if the particle cannot occur, either because its maximum is zero
+    /// or because it is lower than its minimum.
+    pub fn total_range(&self) -> Option<(u32, Option<u32>)> {
+        match self.max {
+            Some(max) if max == 0 || max < self.min => None,
+            max => Some((self.min, max)),
+        }
+    }
+
     /// Check if this particle has valid occurs restriction compared to another
     pub fn has_occurs_restriction(&self, other: &Occurs) -> bool {
         // Self must have >= min_occurs than other
@@ -285,24 +296,13 @@ impl OccursCalculator {
     }
 
     /// Add another particle's occurs (for sequence)
-    pub fn add(&mut self, other: Occurs) {
-        self.min_occurs += other.min;
-        match (self.max_occurs, other.max) {
-            (Some(a), Some(b)) => self.max_occurs = Some(a + b),
-            _ => self.max_occurs = None,
-        }
+    pub fn add_occurs(&mut self, other: Occurs) {
+        self.set(Self::add(self.occurs(), other));
     }
 
     /// Multiply by another particle's occurs (for nested groups)
-    pub fn multiply(&mut self, other: Occurs) {
-        self.min_occurs *= other.min;
-        match (self.max_occurs, other.max) {
-            (None, Some(0)) => self.max_occurs = Some(0),
-            (Some(0), _) => self.max_occurs = Some(0),
-            (Some(_), None) => self.max_occurs = None,
-            (None, _) => {}
-            (Some(a), Some(b)) => self.max_occurs = Some(a * b),
-        }
+    pub fn multiply_occurs(&mut self, other: Occurs) {
+        self.set(Self::multiply(self.occurs(), other));
     }
 
     /// Subtract another particle's occurs
@@ -323,12 +323,48 @@ impl OccursCalculator {
 
     /// Take the max of this and another (for choice)
     pub fn max_with(&mut self, other: Occurs) {
-        // For choice: min is the min of all branches, max is unbounded if any is
-        self.min_occurs = self.min_occurs.min(other.min);
-        match (self.max_occurs, other.max) {
-            (None, _) | (_, None) => self.max_occurs = None,
-            (Some(a), Some(b)) => self.max_occurs = Some(a.max(b)),
-        }
+        self.set(Self::union(self.occurs(), other));
+    }
+
+    fn set(&mut self, occurs: Occurs) {
+        self.min_occurs = occurs.min;
+        self.max_occurs = occurs.max;
+    }
+
+    /// The occurs of a sequence of two particles
+    ///
+    /// Both bounds are summed; the maximum is unbounded if either one is.
+    pub fn add(a: Occurs, b: Occurs) -> Occurs {
+        let max = match (a.max, b.max) {
+            (Some(a), Some(b)) => Some(a.saturating_add(b)),
+            _ => None,
+        };
+        Occurs::new(a.min.saturating_add(b.min), max)
+    }
+
+    /// The occurs of a particle repeated within a group with the given occurs
+    ///
+    /// Both bounds are multiplied. A maximum of zero on either side makes the
+    /// product empty, otherwise the maximum is unbounded if either one is.
+    pub fn multiply(a: Occurs, b: Occurs) -> Occurs {
+        let max = match (a.max, b.max) {
+            (Some(0), _) | (_, Some(0)) => Some(0),
+            (Some(a), Some(b)) => Some(a.saturating_mul(b)),
+            _ => None,
+        };
+        Occurs::new(a.min.saturating_mul(b.min), max)
+    }
+
+    /// The occurs of a choice between two particles
+    ///
+    /// The broadest range covering both: the lower minimum and the higher
+    /// maximum, unbounded if either one is.
+    pub fn union(a: Occurs, b: Occurs) -> Occurs {
+        let max = match (a.max, b.max) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            _ => None,
+        };
+        Occurs::new(a.min.min(b.min), max)
     }
 }
 
@@ -468,15 +504,15 @@ mod tests {
     #[test]
     fn test_occurs_calculator_add() {
         let mut calc = OccursCalculator::new();
-        calc.add(Occurs::new(1, Some(2)));
+        calc.add_occurs(Occurs::new(1, Some(2)));
         assert_eq!(calc.min_occurs, 1);
         assert_eq!(calc.max_occurs, Some(2));
 
-        calc.add(Occurs::new(2, Some(3)));
+        calc.add_occurs(Occurs::new(2, Some(3)));
         assert_eq!(calc.min_occurs, 3);
         assert_eq!(calc.max_occurs, Some(5));
 
-        calc.add(Occurs::new(1, None)); // unbounded
+        calc.add_occurs(Occurs::new(1, None)); // unbounded
         assert_eq!(calc.min_occurs, 4);
         assert_eq!(calc.max_occurs, None);
     }
@@ -484,9 +520,9 @@ mod tests {
     #[test]
     fn test_occurs_calculator_multiply() {
         let mut calc = OccursCalculator::new();
-        calc.add(Occurs::new(2, Some(3)));
+        calc.add_occurs(Occurs::new(2, Some(3)));
 
-        calc.multiply(Occurs::new(2, Some(4)));
+        calc.multiply_occurs(Occurs::new(2, Some(4)));
         assert_eq!(calc.min_occurs, 4);
         assert_eq!(calc.max_occurs, Some(12));
     }
@@ -494,11 +530,34 @@ mod tests {
     #[test]
     fn test_occurs_calculator_max_with() {
         let mut calc = OccursCalculator::new();
-        calc.add(Occurs::new(2, Some(3)));
+        calc.add_occurs(Occurs::new(2, Some(3)));
 
         // For choice, take min of mins and max of maxes
         calc.max_with(Occurs::new(1, Some(5)));
         assert_eq!(calc.min_occurs, 1); // min(2, 1)
         assert_eq!(calc.max_occurs, Some(5)); // max(3, 5)
     }
+
+    #[test]
+    fn test_occurs_arithmetic() {
+        let occurs = |min, max| Occurs::new(min, max);
+
+        assert_eq!(OccursCalculator::multiply(occurs(1, Some(2)), occurs(0, Some(3))), occurs(0, Some(6)));
+        assert_eq!(OccursCalculator::multiply(occurs(2, None), occurs(1, Some(3))), occurs(2, None));
+        assert_eq!(OccursCalculator::multiply(occurs(0, None), occurs(0, Some(0))), occurs(0, Some(0)));
+
+        assert_eq!(OccursCalculator::add(occurs(1, Some(1)), occurs(0, Some(2))), occurs(1, Some(3)));
+        assert_eq!(OccursCalculator::add(occurs(1, Some(1)), occurs(1, None)), occurs(2, None));
+
+        assert_eq!(OccursCalculator::union(occurs(1, Some(2)), occurs(2, Some(4))), occurs(1, Some(4)));
+        assert_eq!(OccursCalculator::union(occurs(0, Some(1)), occurs(1, None)), occurs(0, None));
+    }
+
+    #[test]
+    fn test_occurs_total_range() {
+        assert_eq!(Occurs::once().total_range(), Some((1, Some(1))));
+        assert_eq!(Occurs::zero_or_more().total_range(), Some((0, None)));
+        assert_eq!(Occurs::empty().total_range(), None);
+        assert_eq!(Occurs::new(3, Some(2)).total_range(), None);
+    }
 }
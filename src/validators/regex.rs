@@ -0,0 +1,423 @@
+//! XSD regular expressions
+//!
+//! Translates the regular expression dialect of the `xs:pattern` facet
+//! (XSD 1.0 Part 2, Appendix F) into the syntax of the `regex` crate.
+//!
+//! The dialects differ in a few places:
+//! - `\i`, `\I`, `\c` and `\C` match XML name characters
+//! - `\s` only matches XML whitespace and `\w` is defined by exclusion of
+//!   punctuation, separator and other characters
+//! - `.` matches anything except `\n` and `\r`
+//! - `^` and `$` are ordinary characters, patterns are implicitly anchored
+//! - character class subtraction is written `[a-z-[aeiou]]`
+//! - `\p{IsBlock}` selects a Unicode block rather than a property
+//! - there are no groups with `(?`, such as lookarounds or inline flags
+
+use crate::error::{Error, Result};
+
+/// XML `NameStartChar` ranges, without the enclosing brackets
+const NAME_START_CHARS: &str = concat!(
+    r"A-Z_a-z:\x{C0}-\x{D6}\x{D8}-\x{F6}\x{F8}-\x{2FF}\x{370}-\x{37D}\x{37F}-\x{1FFF}",
+    r"\x{200C}-\x{200D}\x{2070}-\x{218F}\x{2C00}-\x{2FEF}\x{3001}-\x{D7FF}\x{F900}-\x{FDCF}",
+    r"\x{FDF0}-\x{FFFD}\x{10000}-\x{EFFFF}",
+);
+
+/// XML `NameChar` ranges in addition to the `NameStartChar` ones
+const NAME_CHARS: &str = r"\-.0-9\x{B7}\x{300}-\x{36F}\x{203F}-\x{2040}";
+
+/// XML whitespace, matched by `\s`
+const XML_WHITESPACE: &str = r" \t\n\r";
+
+/// Characters excluded from `\w`
+const NON_WORD_CATEGORIES: &str = r"\p{P}\p{Z}\p{C}";
+
+/// Unicode blocks supported by `\p{IsBlock}`, with their code point ranges
+const UNICODE_BLOCKS: &[(&str, u32, u32)] = &[
+    ("BasicLatin", 0x0000, 0x007F),
+    ("Latin-1Supplement", 0x0080, 0x00FF),
+    ("LatinExtended-A", 0x0100, 0x017F),
+    ("LatinExtended-B", 0x0180, 0x024F),
+    ("IPAExtensions", 0x0250, 0x02AF),
+    ("SpacingModifierLetters", 0x02B0, 0x02FF),
+    ("CombiningDiacriticalMarks", 0x0300, 0x036F),
+    ("Greek", 0x0370, 0x03FF),
+    ("GreekandCoptic", 0x0370, 0x03FF),
+    ("Cyrillic", 0x0400, 0x04FF),
+    ("Armenian", 0x0530, 0x058F),
+    ("Hebrew", 0x0590, 0x05FF),
+    ("Arabic", 0x0600, 0x06FF),
+    ("Devanagari", 0x0900, 0x097F),
+    ("Thai", 0x0E00, 0x0E7F),
+    ("Georgian", 0x10A0, 0x10FF),
+    ("HangulJamo", 0x1100, 0x11FF),
+    ("LatinExtendedAdditional", 0x1E00, 0x1EFF),
+    ("GreekExtended", 0x1F00, 0x1FFF),
+    ("GeneralPunctuation", 0x2000, 0x206F),
+    ("SuperscriptsandSubscripts", 0x2070, 0x209F),
+    ("CurrencySymbols", 0x20A0, 0x20CF),
+    ("LetterlikeSymbols", 0x2100, 0x214F),
+    ("NumberForms", 0x2150, 0x218F),
+    ("Arrows", 0x2190, 0x21FF),
+    ("MathematicalOperators", 0x2200, 0x22FF),
+    ("BoxDrawing", 0x2500, 0x257F),
+    ("GeometricShapes", 0x25A0, 0x25FF),
+    ("MiscellaneousSymbols", 0x2600, 0x26FF),
+    ("CJKSymbolsandPunctuation", 0x3000, 0x303F),
+    ("Hiragana", 0x3040, 0x309F),
+    ("Katakana", 0x30A0, 0x30FF),
+    ("CJKUnifiedIdeographs", 0x4E00, 0x9FFF),
+    ("HangulSyllables", 0xAC00, 0xD7AF),
+    ("PrivateUse", 0xE000, 0xF8FF),
+    ("AlphabeticPresentationForms", 0xFB00, 0xFB4F),
+    ("HalfwidthandFullwidthForms", 0xFF00, 0xFFEF),
+    ("Specials", 0xFFF0, 0xFFFF),
+];
+
+/// Translate an XSD pattern into an equivalent `regex` crate expression
+///
+/// The result is not anchored; wrap it in `^…$` to match whole values the
+/// way the pattern facet does. Top-level alternatives are grouped so that
+/// anchoring applies to all of them.
+pub fn xsd_pattern_to_rust(pattern: &str) -> Result<String> {
+    let mut translator = Translator { chars: pattern.chars().collect(), pos: 0, pattern };
+    let mut output = String::with_capacity(pattern.len());
+    let mut depth = 0usize;
+    let mut top_level_branch = false;
+
+    while let Some(c) = translator.next() {
+        match c {
+            '\\' => output.push_str(&translator.escape()?),
+            '[' => output.push_str(&translator.class()?),
+            ']' => return Err(translator.error("unmatched ']'")),
+            '.' => output.push_str(r"[^\n\r]"),
+            '^' | '$' => {
+                output.push('\\');
+                output.push(c);
+            }
+            '(' => {
+                if translator.peek() == Some('?') {
+                    return Err(translator.error("groups starting with '(?' are not allowed"));
+                }
+                depth += 1;
+                output.push(c);
+            }
+            ')' => {
+                depth = depth.checked_sub(1).ok_or_else(|| translator.error("unmatched ')'"))?;
+                output.push(c);
+            }
+            '|' => {
+                top_level_branch |= depth == 0;
+                output.push(c);
+            }
+            _ => output.push(c),
+        }
+    }
+
+    if depth > 0 {
+        return Err(translator.error("unclosed group"));
+    }
+    if top_level_branch {
+        output = format!("(?:{})", output);
+    }
+    Ok(output)
+}
+
+/// Cursor over the characters of a pattern being translated
+struct Translator<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    pattern: &'a str,
+}
+
+impl Translator<'_> {
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.get(self.pos).copied();
+        self.pos += 1;
+        c
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::Value(format!("Invalid XSD pattern '{}': {}", self.pattern, message))
+    }
+
+    /// Translate the escape following a backslash
+    ///
+    /// Multi-character escapes translate to a bracketed class, which is also
+    /// valid as a nested class inside a character class expression.
+    fn escape(&mut self) -> Result<String> {
+        let c = self.next().ok_or_else(|| self.error("trailing backslash"))?;
+        let translated = match c {
+            'i' => format!("[{}]", NAME_START_CHARS),
+            'I' => format!("[^{}]", NAME_START_CHARS),
+            'c' => format!("[{}{}]", NAME_START_CHARS, NAME_CHARS),
+            'C' => format!("[^{}{}]", NAME_START_CHARS, NAME_CHARS),
+            's' => format!("[{}]", XML_WHITESPACE),
+            'S' => format!("[^{}]", XML_WHITESPACE),
+            'w' => format!("[^{}]", NON_WORD_CATEGORIES),
+            'W' => format!("[{}]", NON_WORD_CATEGORIES),
+            // Digits are Unicode decimal digits in both dialects
+            'd' | 'D' => format!("\\{}", c),
+            'p' | 'P' => self.property(c == 'P')?,
+            'n' | 'r' | 't' => format!("\\{}", c),
+            '\\' | '|' | '.' | '-' | '^' | '?' | '*' | '+' | '{' | '}' | '(' | ')' | '[' | ']' => {
+                format!("\\{}", c)
+            }
+            _ => return Err(self.error(&format!("unknown escape '\\{}'", c))),
+        };
+        Ok(translated)
+    }
+
+    /// Translate a `\p{…}` or `\P{…}` escape
+    ///
+    /// General categories are delegated to the Unicode support of the
+    /// `regex` crate; `Is` block names are expanded into code point ranges.
+    fn property(&mut self, negated: bool) -> Result<String> {
+        if self.next() != Some('{') {
+            return Err(self.error("expected '{' after \\p"));
+        }
+        let mut name = String::new();
+        loop {
+            match self.next() {
+                Some('}') => break,
+                Some(c) => name.push(c),
+                None => return Err(self.error("unterminated property escape")),
+            }
+        }
+
+        if let Some(block) = name.strip_prefix("Is") {
+            let (_, start, end) = UNICODE_BLOCKS
+                .iter()
+                .find(|(name, _, _)| *name == block)
+                .ok_or_else(|| self.error(&format!("unsupported Unicode block '{}'", block)))?;
+            let negation = if negated { "^" } else { "" };
+            return Ok(format!("[{}\\x{{{:X}}}-\\x{{{:X}}}]", negation, start, end));
+        }
+
+        let valid = matches!(
+            name.as_str(),
+            "L" | "Lu" | "Ll" | "Lt" | "Lm" | "Lo"
+                | "M" | "Mn" | "Mc" | "Me"
+                | "N" | "Nd" | "Nl" | "No"
+                | "P" | "Pc" | "Pd" | "Ps" | "Pe" | "Pi" | "Pf" | "Po"
+                | "Z" | "Zs" | "Zl" | "Zp"
+                | "S" | "Sm" | "Sc" | "Sk" | "So"
+                | "C" | "Cc" | "Cf" | "Co" | "Cn"
+        );
+        if !valid {
+            return Err(self.error(&format!("unknown Unicode category '{}'", name)));
+        }
+        Ok(format!("\\{}{{{}}}", if negated { 'P' } else { 'p' }, name))
+    }
+
+    /// Translate a character class expression, after its opening bracket
+    fn class(&mut self) -> Result<String> {
+        let mut output = String::from("[");
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.pos += 1;
+            output.push('^');
+        }
+
+        let mut empty = true;
+        loop {
+            let c = self.next().ok_or_else(|| self.error("unterminated character class"))?;
+            match c {
+                ']' if !empty => break,
+                ']' => return Err(self.error("empty character class")),
+                '\\' => output.push_str(&self.escape()?),
+                '-' if self.peek() == Some('[') => {
+                    // Subtraction must be the last part of the class
+                    self.pos += 1;
+                    let subtracted = self.class()?;
+                    if self.next() != Some(']') {
+                        return Err(self.error("character class subtraction must end the class"));
+                    }
+                    // The regex crate negates the whole expression, XSD only the base class
+                    if negated {
+                        output = format!("[{}]", output);
+                    }
+                    output.push_str("--");
+                    output.push_str(&subtracted);
+                    break;
+                }
+                '[' => return Err(self.error("unescaped '[' in character class")),
+                // Operators and nesting of the regex crate are literal in XSD classes
+                '&' | '~' => {
+                    output.push('\\');
+                    output.push(c);
+                }
+                _ => output.push(c),
+            }
+            empty = false;
+        }
+
+        output.push(']');
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(pattern: &str) -> ::regex::Regex {
+        let translated = xsd_pattern_to_rust(pattern).unwrap();
+        ::regex::Regex::new(&format!("^{}$", translated))
+            .unwrap_or_else(|e| panic!("'{}' translated to invalid '{}': {}", pattern, translated, e))
+    }
+
+    fn matches(pattern: &str, value: &str) -> bool {
+        compile(pattern).is_match(value)
+    }
+
+    #[test]
+    fn test_plain_patterns_unchanged() {
+        assert_eq!(xsd_pattern_to_rust(r"\d{3}-[A-Z]{2}").unwrap(), r"\d{3}-[A-Z]{2}");
+        assert_eq!(xsd_pattern_to_rust("[a-z]+(ab)?").unwrap(), "[a-z]+(ab)?");
+        assert!(matches(r"\d{3}-[A-Z]{2}", "123-AB"));
+        assert!(!matches(r"\d{3}-[A-Z]{2}", "12-AB"));
+    }
+
+    #[test]
+    fn test_name_start_char_escape() {
+        for value in ["a", "Z", "_", ":", "é", "Ω", "中"] {
+            assert!(matches(r"\i", value), "{}", value);
+            assert!(!matches(r"\I", value), "{}", value);
+        }
+        for value in ["1", "-", ".", "\u{B7}", " ", "#"] {
+            assert!(!matches(r"\i", value), "{}", value);
+            assert!(matches(r"\I", value), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_name_char_escape() {
+        for value in ["a", "_", ":", "1", "-", ".", "\u{B7}", "\u{301}", "é"] {
+            assert!(matches(r"\c", value), "{}", value);
+            assert!(!matches(r"\C", value), "{}", value);
+        }
+        for value in [" ", "#", "@", "/"] {
+            assert!(!matches(r"\c", value), "{}", value);
+            assert!(matches(r"\C", value), "{}", value);
+        }
+        assert!(matches(r"\i\c*", "xs:element-1.2"));
+        assert!(!matches(r"\i\c*", "1element"));
+    }
+
+    #[test]
+    fn test_whitespace_escape() {
+        for value in [" ", "\t", "\n", "\r"] {
+            assert!(matches(r"\s", value), "{:?}", value);
+            assert!(!matches(r"\S", value), "{:?}", value);
+        }
+        // Unicode spaces aren't XML whitespace
+        for value in ["\u{A0}", "\u{2003}", "a"] {
+            assert!(!matches(r"\s", value), "{:?}", value);
+            assert!(matches(r"\S", value), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn test_word_escape() {
+        for value in ["a", "Z", "é", "5", "+", "$"] {
+            assert!(matches(r"\w", value), "{}", value);
+            assert!(!matches(r"\W", value), "{}", value);
+        }
+        for value in [".", ",", "-", " ", "\u{A0}", "\u{7}"] {
+            assert!(!matches(r"\w", value), "{:?}", value);
+            assert!(matches(r"\W", value), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn test_digit_escape() {
+        for value in ["0", "9", "\u{661}"] {
+            assert!(matches(r"\d", value), "{}", value);
+            assert!(!matches(r"\D", value), "{}", value);
+        }
+        assert!(!matches(r"\d", "a"));
+        assert!(matches(r"\D", "a"));
+    }
+
+    #[test]
+    fn test_category_escapes() {
+        assert!(matches(r"\p{Lu}+", "ABC"));
+        assert!(!matches(r"\p{Lu}+", "AbC"));
+        assert!(matches(r"\P{Lu}+", "abc"));
+        assert!(matches(r"\p{L}\p{Nd}", "é7"));
+        assert!(matches(r"\p{Sc}", "€"));
+        assert!(matches(r"[\p{Ll}\p{Nd}]+", "abc123"));
+        assert!(xsd_pattern_to_rust(r"\p{Foo}").is_err());
+        assert!(xsd_pattern_to_rust(r"\p{Lu").is_err());
+        assert!(xsd_pattern_to_rust(r"\pL").is_err());
+    }
+
+    #[test]
+    fn test_block_escapes() {
+        assert!(matches(r"\p{IsBasicLatin}+", "Hello"));
+        assert!(!matches(r"\p{IsBasicLatin}+", "Héllo"));
+        assert!(matches(r"\P{IsBasicLatin}", "é"));
+        assert!(matches(r"\p{IsGreek}+", "αβγ"));
+        assert!(matches(r"[\p{IsBasicLatin}\p{IsCyrillic}]+", "abcжзи"));
+        assert!(xsd_pattern_to_rust(r"\p{IsKlingon}").is_err());
+    }
+
+    #[test]
+    fn test_dot_excludes_line_breaks() {
+        assert!(matches(".", "a"));
+        assert!(matches(".", "\u{2028}"));
+        assert!(!matches(".", "\n"));
+        assert!(!matches(".", "\r"));
+        assert!(matches("[.]", "."));
+        assert!(!matches("[.]", "a"));
+    }
+
+    #[test]
+    fn test_anchors_are_literal() {
+        assert!(matches("^a$", "^a$"));
+        assert!(!matches("^a$", "a"));
+        assert!(matches(r"\^", "^"));
+        assert!(matches("[^^]", "a"));
+        assert!(!matches("[^^]", "^"));
+    }
+
+    #[test]
+    fn test_top_level_alternatives_are_grouped() {
+        assert_eq!(xsd_pattern_to_rust("a|b").unwrap(), "(?:a|b)");
+        assert_eq!(xsd_pattern_to_rust("(a|b)c").unwrap(), "(a|b)c");
+        assert!(matches("yes|no", "no"));
+        assert!(!matches("yes|no", "yesno"));
+        assert!(!matches("yes|no", "nope"));
+    }
+
+    #[test]
+    fn test_class_subtraction() {
+        assert!(matches("[a-z-[aeiou]]+", "xyz"));
+        assert!(!matches("[a-z-[aeiou]]+", "xaz"));
+        assert!(matches(r"[\i-[:]][\c-[:]]*", "ncname"));
+        assert!(!matches(r"[\i-[:]][\c-[:]]*", "xs:ncname"));
+        assert!(matches("[^a-z-[0-9]]+", "ABC"));
+        assert!(!matches("[^a-z-[0-9]]", "5"));
+        assert!(!matches("[^a-z-[0-9]]", "q"));
+        assert!(xsd_pattern_to_rust("[a-z-[aeiou]x]").is_err());
+    }
+
+    #[test]
+    fn test_class_literals() {
+        assert!(matches("[&~]+", "&~"));
+        assert!(matches("[a-]", "-"));
+        assert!(matches(r"[\-\[\]]+", "-[]"));
+        assert!(matches(r"[\i\d]+", "a1"));
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        for pattern in ["[a-z", "a]", "(a", "a)", "[]", r"a\", r"\x41", "(?=a)", "(?i)a", "[a[b]]"] {
+            assert!(xsd_pattern_to_rust(pattern).is_err(), "{}", pattern);
+        }
+    }
+}
@@ -5,26 +5,47 @@
 //!
 //! Based on xmlschema/validators/schemas.py
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
+use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
+use regex::Regex;
+
 use crate::catalog::XmlCatalog;
-use super::attributes::{XsdAttribute, XsdAttributeGroup};
+use super::annotations::XsdAnnotation;
+use super::assertions::AssertionList;
+use super::attributes::{AttributeUse, XsdAttribute, XsdAttributeGroup};
 use super::base::{ValidationMode, ValidationStatus, Validator};
-use super::builders::{XsdBuilders, XsdVersion};
-use super::document_validation::validate_document;
-use super::elements::XsdElement;
-use super::exceptions::XsdValidatorError;
-use super::globals::{XsdGlobals, XsdNotation};
-use super::groups::XsdGroup;
-use super::simple_types::SimpleType;
-use super::validation::ValidationContext;
-
-use crate::documents::Document;
-use crate::error::{ParseError, Result};
+use super::builders::{BuildContext, XsdBuilders, XsdVersion};
+use super::builtins::{
+    builtin_type_qname, get_builtin_type, TypeCategory, XsdValue, XSD_ANY_ATOMIC_TYPE, XSD_ANY_SIMPLE_TYPE,
+    XSD_ANY_TYPE, XSD_NAMESPACE,
+};
+use super::complex_types::{ComplexContent, ComplexTypeBuilder, DerivationMethod, XsdComplexType};
+use super::document_validation::{
+    find_child_declaration, resolve_element_qname, validate_document, validate_element,
+};
+use super::elements::{ElementType, XsdElement};
+use super::exceptions;
+use super::facets::{NumericBound, WhiteSpace};
+use super::globals::{qname_order, XsdGlobals, XsdNotation};
+use super::helpers::normalize_attribute_value;
+use super::groups::{ElementParticle, GroupParticle, ModelType, XsdGroup};
+use super::models::{check_unique_particle_attribution, check_upa, AmbiguityReport};
+use super::parsing::resolve_schema_location;
+use super::particles::Occurs;
+use super::simple_types::{PlaceholderType, SimpleType, SimpleTypeVariety, XsdAtomicType, XsdUnionType};
+use super::streaming::{StreamingValidator, ValidationEvent};
+use super::validation::{CustomValidator, ValidationContext};
+use super::wildcards::XsdAnyAttribute;
+
+use crate::documents::{Document, Element};
+use crate::error::{Error, ParseError, Result, ValidationError};
+use crate::loaders::{HttpCache, ResourceLoader};
 use crate::namespaces::QName;
+use crate::xpath::XPathEvaluator;
 
 // Re-export from builtins for local use
 use super::globals::GlobalType;
@@ -38,6 +59,9 @@ pub const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
 /// Versioning namespace for XSD 1.1
 pub const VC_NAMESPACE: &str = "http://www.w3.org/2007/XMLSchema-versioning";
 
+/// Depth to which `content_model_summary` expands nested model groups
+pub const CONTENT_MODEL_SUMMARY_DEPTH: usize = 8;
+
 /// Form default for elements and attributes
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum FormDefault {
@@ -140,6 +164,12 @@ pub struct SchemaSource {
     /// Tracks loaded schema paths to prevent circular includes.
     /// Shared across all schemas in an include chain via Arc<Mutex<>>.
     pub loaded_paths: Arc<Mutex<HashSet<PathBuf>>>,
+    /// Loader serving included and imported schemas instead of the file system
+    pub resource_loader: Option<Arc<dyn ResourceLoader>>,
+    /// Schemas downloaded from HTTP locations, shared by the schemas loaded together
+    pub http_cache: HttpCache,
+    /// Prefixes registered by the user for namespace URIs (URI -> prefix)
+    pub preferred_prefixes: HashMap<String, String>,
 }
 
 impl Default for SchemaSource {
@@ -150,6 +180,9 @@ impl Default for SchemaSource {
             namespaces: HashMap::new(),
             catalog: None,
             loaded_paths: Arc::new(Mutex::new(HashSet::new())),
+            resource_loader: None,
+            http_cache: HttpCache::default(),
+            preferred_prefixes: HashMap::new(),
         }
     }
 }
@@ -259,16 +292,28 @@ pub struct XsdSchema {
     pub includes: Vec<SchemaInclude>,
     /// Redefined schemas (xs:redefine)
     pub redefines: Vec<SchemaRedefine>,
+    /// Annotations at the top level of the schema
+    pub annotations: Vec<XsdAnnotation>,
     /// Parse errors
     pub errors: Vec<ParseError>,
+    /// Non-fatal problems found while building the schema
+    pub warnings: Vec<String>,
     /// Whether the schema has been built
     built: bool,
+    /// Reverse index of attribute and type uses, built with the schema
+    usage_index: Option<UsageIndex>,
+    /// Source `xs:pattern` values of the global simple types, anchored
+    pub(crate) pattern_sources: HashMap<QName, Vec<String>>,
+    /// Patterns compiled by `precompile_patterns`, by simple type name
+    compiled_patterns: HashMap<QName, Vec<Regex>>,
     /// Pending include locations (for iterative processing)
     /// These are collected during initial parsing and processed later
     /// to avoid deep recursion that can cause stack overflow.
     pub pending_include_locations: Vec<String>,
     /// Pending redefine locations (for iterative processing)
     pub pending_redefine_locations: Vec<String>,
+    /// Components redefined by the pending redefines, by location
+    pub pending_redefinitions: HashMap<String, Vec<RedefinedComponent>>,
 }
 
 impl Default for XsdSchema {
@@ -295,10 +340,16 @@ impl XsdSchema {
             imports: HashMap::new(),
             includes: Vec::new(),
             redefines: Vec::new(),
+            annotations: Vec::new(),
             errors: Vec::new(),
+            warnings: Vec::new(),
             built: false,
+            usage_index: None,
+            pattern_sources: HashMap::new(),
+            compiled_patterns: HashMap::new(),
             pending_include_locations: Vec::new(),
             pending_redefine_locations: Vec::new(),
+            pending_redefinitions: HashMap::new(),
         }
     }
 
@@ -376,6 +427,20 @@ impl XsdSchema {
         self.builders.register_builtins(&mut self.maps)
     }
 
+    /// Build the schema after sorting all global maps by QName
+    ///
+    /// Components are then resolved and iterated in name order, so the
+    /// built schema does not depend on the order of declarations in the
+    /// source documents. A schema that is already built only has its maps
+    /// sorted.
+    pub fn build_ordered(&mut self) -> Result<()> {
+        if self.maps.global_maps.types.is_empty() {
+            self.register_builtins()?;
+        }
+        self.maps.sort();
+        self.build()
+    }
+
     /// Look up a global type by QName
     ///
     /// First searches local types, then searches in imported schemas.
@@ -460,6 +525,177 @@ impl XsdSchema {
         None
     }
 
+    /// Describe the constraints of a type in a human-readable form
+    ///
+    /// Simple types and complex types with simple content are described by
+    /// their effective facets, e.g. `xs:integer between 1..100` or
+    /// `enumeration of: fiction, non-fiction, reference`. Built-in types
+    /// are described by their name.
+    pub fn facet_summary(&self, type_qname: &QName) -> String {
+        match self.lookup_type(type_qname) {
+            Some(GlobalType::Simple(simple_type)) => simple_type.facet_summary(),
+            Some(GlobalType::Complex(complex_type)) => match complex_type.simple_type() {
+                Some(simple_type) => simple_type.facet_summary(),
+                None => format!("complex type {}", type_qname.to_string()),
+            },
+            None => {
+                let builtin = type_qname.namespace.as_deref() == Some(XSD_NAMESPACE);
+                match XsdAtomicType::new(&type_qname.local_name) {
+                    Ok(atomic) if builtin => atomic.describe_facets(),
+                    _ => format!("unknown type {}", type_qname.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Describe the content model of a type in a BNF-like form
+    ///
+    /// Sequences are written `(title, author+, year?)`, choices
+    /// `(fiction | nonfiction)` and all groups `{a, b, c}`, with `?`, `*`,
+    /// `+` or `{min,max}` after the particles that do not occur exactly
+    /// once; wildcards are written `any`. Mixed content is prefixed with
+    /// `mixed` and empty content is `EMPTY`. Simple types and complex types
+    /// with simple content are described by [`facet_summary`](Self::facet_summary).
+    /// Nested groups are expanded up to [`CONTENT_MODEL_SUMMARY_DEPTH`] levels.
+    pub fn content_model_summary(&self, qname: &QName) -> String {
+        self.content_model_summary_with_depth(qname, CONTENT_MODEL_SUMMARY_DEPTH)
+    }
+
+    /// Describe the content model of a type, expanding nested groups up to `max_depth` levels
+    ///
+    /// Deeper groups are written `(...)`.
+    pub fn content_model_summary_with_depth(&self, qname: &QName, max_depth: usize) -> String {
+        let Some(GlobalType::Complex(complex_type)) = self.lookup_type(qname) else {
+            return self.facet_summary(qname);
+        };
+        if complex_type.simple_type().is_some() {
+            return self.facet_summary(qname);
+        }
+        let model = match complex_type.model_group() {
+            Some(group) if !group.particles.is_empty() => {
+                format!("{}{}", summarize_group(group, 0, max_depth), occurs_suffix(group.occurs))
+            }
+            _ if complex_type.mixed => "()".to_string(),
+            _ => return "EMPTY".to_string(),
+        };
+        if complex_type.mixed {
+            format!("mixed {}", model)
+        } else {
+            model
+        }
+    }
+
+    /// Explain what a type accepts
+    ///
+    /// The explanation gathers the derivation chain of the type, the child
+    /// elements of its content model, its attributes, the facets in effect
+    /// and its documentation. Built-in types are explained by their facets.
+    /// Returns `None` if the type is unknown.
+    pub fn explain_type(&self, qname: &QName) -> Option<TypeExplanation> {
+        let builtin = qname.namespace.as_deref() == Some(XSD_NAMESPACE);
+        let derivation_chain = self.derivation_chain(qname);
+        let mut explanation = TypeExplanation {
+            name: qname.clone(),
+            kind: if builtin { TypeKind::Builtin } else { TypeKind::Simple },
+            base_type: derivation_chain.get(1).cloned(),
+            derivation_chain,
+            allowed_content: Vec::new(),
+            attributes: Vec::new(),
+            facets: Vec::new(),
+            documentation: Vec::new(),
+        };
+
+        match self.lookup_type(qname) {
+            Some(GlobalType::Simple(simple_type)) => explanation.facets = facet_descriptions(simple_type.as_ref()),
+            Some(GlobalType::Complex(complex_type)) => {
+                if !builtin {
+                    explanation.kind = TypeKind::Complex;
+                }
+                if let Some(simple_type) = complex_type.simple_type() {
+                    explanation.facets = facet_descriptions(simple_type.as_ref());
+                }
+                if let Some(group) = complex_type.model_group() {
+                    for (name, occurs) in self.element_occurs(group) {
+                        let element = self.find_element_particle(group, &name).and_then(|particle| {
+                            particle
+                                .element()
+                                .or_else(|| particle.element_ref.as_ref().and_then(|name| self.lookup_element(name)))
+                        });
+                        let type_name = element.and_then(|element| self.element_type_qname(element));
+                        explanation.allowed_content.push(ElementSummary { name, type_name, occurs });
+                    }
+                }
+
+                let mut attributes: Vec<_> = complex_type
+                    .attributes
+                    .iter_attributes()
+                    .filter(|attribute| !attribute.is_prohibited())
+                    .collect();
+                attributes.sort_by(|a, b| qname_order(a.name(), b.name()));
+                explanation.attributes = attributes
+                    .into_iter()
+                    .map(|attribute| AttributeSummary {
+                        name: attribute.name().clone(),
+                        type_name: attribute.type_name.clone().or_else(|| {
+                            attribute
+                                .effective_type(self)
+                                .and_then(|simple_type| simple_type_qname(simple_type.as_ref()))
+                        }),
+                        required: attribute.use_mode() == AttributeUse::Required,
+                    })
+                    .collect();
+
+                if let Some(annotation) = &complex_type.annotation {
+                    explanation.documentation = annotation.documentation.clone();
+                }
+            }
+            None if builtin => {
+                let atomic = XsdAtomicType::new(&qname.local_name).ok()?;
+                explanation.facets = facet_descriptions(&atomic);
+            }
+            None => return None,
+        }
+        Some(explanation)
+    }
+
+    /// Names of a type and of its named base types, up to the root of the derivation
+    ///
+    /// Anonymous simple base types are skipped; a built-in simple base type
+    /// ends the chain.
+    fn derivation_chain(&self, qname: &QName) -> Vec<QName> {
+        let mut chain = vec![qname.clone()];
+        let mut current = qname.clone();
+        loop {
+            let base = match self.lookup_type(&current) {
+                Some(GlobalType::Complex(complex_type)) => complex_type.base_type.clone(),
+                Some(GlobalType::Simple(simple_type)) => {
+                    let mut base = SimpleType::base_type(simple_type.as_ref());
+                    let mut name = None;
+                    while let Some(ty) = base {
+                        name = ty.name().cloned().or_else(|| match SimpleType::base_type(ty) {
+                            None => ty.primitive_name().map(|primitive| QName::namespaced(XSD_NAMESPACE, primitive)),
+                            Some(_) => None,
+                        });
+                        if name.is_some() {
+                            break;
+                        }
+                        base = SimpleType::base_type(ty);
+                    }
+                    name
+                }
+                None => None,
+            };
+            match base {
+                Some(base) if !chain.contains(&base) => {
+                    chain.push(base.clone());
+                    current = base;
+                }
+                _ => break,
+            }
+        }
+        chain
+    }
+
     /// Look up a global group by QName
     ///
     /// First searches local groups, then searches in imported schemas.
@@ -507,6 +743,186 @@ impl XsdSchema {
         self.maps.lookup_notation(qname)
     }
 
+    /// Get the xs:documentation texts of a global component
+    ///
+    /// Elements, complex types, attributes, attribute groups and model groups
+    /// are searched in this order; the first one with an annotation gives the
+    /// texts. Returns `None` if no component of the name is annotated.
+    pub fn documentation_for(&self, qname: &QName) -> Option<&[String]> {
+        let complex_type = match self.lookup_type(qname) {
+            Some(GlobalType::Complex(complex_type)) => Some(complex_type),
+            _ => None,
+        };
+        [
+            self.lookup_element(qname).and_then(|element| element.annotation.as_ref()),
+            complex_type.and_then(|complex_type| complex_type.annotation.as_ref()),
+            self.lookup_attribute(qname).and_then(|attribute| attribute.annotation.as_ref()),
+            self.lookup_attribute_group(qname).and_then(|group| group.annotation.as_ref()),
+            self.lookup_group(qname).and_then(|group| group.annotation.as_ref()),
+        ]
+        .into_iter()
+        .flatten()
+        .next()
+        .map(|annotation| annotation.documentation.as_slice())
+    }
+
+    /// Check if a notation is declared
+    ///
+    /// The name may be prefixed with a prefix declared in the schema;
+    /// unprefixed names are looked up in the target namespace.
+    pub fn has_notation(&self, name: &str) -> bool {
+        let (namespace, local_name) = self.resolve_qname(name);
+        self.lookup_notation(&QName::new(namespace.map(|s| s.to_string()), local_name))
+            .is_some()
+    }
+
+    /// Derive a new complex type by extension of a global complex type
+    ///
+    /// The builder passed to `build` is already configured with the base
+    /// type and the extension derivation method. Element and attribute
+    /// types added by name are resolved against this schema (built-in XSD
+    /// types are always available), and the resulting type has the base
+    /// content followed by the extension content, as well as the base
+    /// attributes that the extension does not redeclare.
+    pub fn derive_extension<F>(&self, base_qname: &QName, build: F) -> Result<XsdComplexType>
+    where
+        F: FnOnce(ComplexTypeBuilder) -> ComplexTypeBuilder,
+    {
+        use super::base::AttributeValidator;
+
+        let base_ct = match self.lookup_type(base_qname) {
+            Some(GlobalType::Complex(ct)) => Arc::clone(ct),
+            Some(GlobalType::Simple(_)) => {
+                return Err(Error::Type(format!(
+                    "'{}' is a simple type and cannot be extended with complex content",
+                    base_qname.to_string()
+                )))
+            }
+            None => {
+                return Err(Error::Type(format!("unknown base type '{}'", base_qname.to_string())))
+            }
+        };
+        if base_ct.is_derivation_final(DerivationMethod::Extension) {
+            return Err(Error::Type(format!(
+                "base type '{}' is final for extension",
+                base_qname.to_string()
+            )));
+        }
+
+        let mut derived = build(ComplexTypeBuilder::new())
+            .base(base_qname.clone(), DerivationMethod::Extension)
+            .try_build()?;
+
+        // Resolve the named types of the builder-added declarations
+        if let ComplexContent::Group(ref group) = derived.content {
+            derived.content = ComplexContent::Group(Arc::new(self.resolve_group_type_names(group)?));
+        }
+        let mut attributes = XsdAttributeGroup::anonymous();
+        for attr in derived.attributes.iter_attributes() {
+            let attr = match (&attr.type_name, attr.simple_type()) {
+                (Some(type_name), None) => {
+                    let mut resolved = XsdAttribute::new(attr.name().clone());
+                    resolved.type_name = Some(type_name.clone());
+                    resolved.set_type(self.resolve_simple_type_name(type_name)?);
+                    resolved.set_use(attr.use_mode());
+                    if let Some(default) = attr.default() {
+                        resolved.set_default(default.to_string())?;
+                    }
+                    if let Some(fixed) = attr.fixed_value() {
+                        resolved.set_fixed(fixed.to_string())?;
+                    }
+                    Arc::new(resolved)
+                }
+                _ => Arc::clone(attr),
+            };
+            attributes.add_attribute(attr)?;
+        }
+
+        // Merge with the base type, as resolve_complex_type_derivations does
+        match (&base_ct.content, &derived.content) {
+            (ComplexContent::Group(base_group), ComplexContent::Group(ext_group)) => {
+                if base_group.is_empty() {
+                    // Extension content stays as-is
+                } else if ext_group.is_empty() {
+                    derived.content = ComplexContent::Group(Arc::clone(base_group));
+                } else {
+                    let mut wrapper = XsdGroup::new(ModelType::Sequence);
+                    wrapper.particles.push(GroupParticle::Group(Arc::clone(base_group)));
+                    wrapper.particles.push(GroupParticle::Group(Arc::clone(ext_group)));
+                    derived.content = ComplexContent::Group(Arc::new(wrapper));
+                }
+            }
+            (ComplexContent::Simple(simple), ComplexContent::Group(ext_group)) if ext_group.is_empty() => {
+                derived.content = ComplexContent::Simple(Arc::clone(simple));
+            }
+            (ComplexContent::Simple(_), ComplexContent::Group(_)) => {
+                return Err(Error::Type(format!(
+                    "cannot extend simple content type '{}' with element content",
+                    base_qname.to_string()
+                )));
+            }
+            _ => {}
+        }
+        if !derived.mixed_explicit {
+            derived.mixed = base_ct.mixed;
+        }
+        for attr in base_ct.attributes.iter_attributes() {
+            if attributes.get_attribute(attr.name()).is_none() {
+                attributes.add_attribute(Arc::clone(attr))?;
+            }
+        }
+        if let Some(any) =
+            Self::merge_attribute_wildcards(&derived.attributes, &base_ct.attributes, DerivationMethod::Extension)
+        {
+            attributes.set_any_attribute(any);
+        }
+        derived.attributes = attributes;
+
+        Ok(derived)
+    }
+
+    /// Resolve a simple type reference, falling back to XSD built-in types
+    fn resolve_simple_type_name(&self, type_name: &QName) -> Result<Arc<dyn SimpleType + Send + Sync>> {
+        if let Some(st) = self.lookup_simple_type(type_name) {
+            return Ok(Arc::clone(st));
+        }
+        if type_name.namespace.as_deref() == Some(XSD_NAMESPACE) {
+            return Ok(Arc::new(XsdAtomicType::with_name(&type_name.local_name, type_name.clone())?));
+        }
+        Err(Error::Type(format!("unknown simple type '{}'", type_name.to_string())))
+    }
+
+    /// Resolve the `type_name` of local element declarations in a model group
+    fn resolve_group_type_names(&self, group: &XsdGroup) -> Result<XsdGroup> {
+        let mut resolved = group.clone();
+        for particle in resolved.particles.iter_mut() {
+            match particle {
+                GroupParticle::Element(ep) => {
+                    let Some(decl) = ep.element_decl.as_ref() else { continue };
+                    let Some(type_name) = decl.type_name.as_ref() else { continue };
+                    if !matches!(decl.element_type, ElementType::Any) {
+                        continue;
+                    }
+                    let element_type = match self.lookup_type(type_name) {
+                        Some(GlobalType::Complex(ct)) => ElementType::Complex(Arc::clone(ct)),
+                        Some(GlobalType::Simple(st)) => ElementType::Simple(Arc::clone(st)),
+                        None => ElementType::Simple(self.resolve_simple_type_name(type_name)?),
+                    };
+                    let mut new_decl = (**decl).clone();
+                    new_decl.element_type = element_type;
+                    let mut new_ep = (**ep).clone();
+                    new_ep.element_decl = Some(Arc::new(new_decl));
+                    *ep = Arc::new(new_ep);
+                }
+                GroupParticle::Group(nested) => {
+                    *nested = Arc::new(self.resolve_group_type_names(nested)?);
+                }
+                GroupParticle::Any(_) => {}
+            }
+        }
+        Ok(resolved)
+    }
+
     /// Get the number of global elements
     pub fn element_count(&self) -> usize {
         self.maps.global_maps.elements.len()
@@ -517,6 +933,229 @@ impl XsdSchema {
         self.maps.global_maps.types.len()
     }
 
+    /// Check the content models for Unique Particle Attribution violations
+    ///
+    /// All named complex types and the anonymous complex types of global and
+    /// local elements are checked; anonymous types are reported under the
+    /// name of their element. XSD 1.0 requires deterministic content models,
+    /// so every violation makes a schema non-conformant; `build()` records
+    /// them as warnings only.
+    pub fn check_ambiguity(&self) -> AmbiguityReport {
+        let violations = self
+            .complex_types_with_anonymous()
+            .into_iter()
+            .filter_map(|(qname, ct)| Some(check_unique_particle_attribution(&qname, ct.model_group()?)))
+            .flatten()
+            .collect();
+        AmbiguityReport { violations }
+    }
+
+    /// Named complex types and the anonymous complex types of elements
+    ///
+    /// Anonymous types are named after their element. Sorted by name.
+    fn complex_types_with_anonymous(&self) -> Vec<(QName, &XsdComplexType)> {
+        let mut complex_types: Vec<(QName, &XsdComplexType)> = Vec::new();
+        let mut seen = HashSet::new();
+        for (qname, global_type) in self.types() {
+            if let GlobalType::Complex(ct) = global_type {
+                complex_types.push((qname.clone(), ct));
+                if let Some(group) = ct.model_group() {
+                    Self::collect_anonymous_types(group, &mut seen, &mut complex_types);
+                }
+            }
+        }
+        for element in self.maps.global_maps.elements.values() {
+            if let ElementType::Complex(ct) = &element.element_type {
+                if ct.name.is_none() && seen.insert(Arc::as_ptr(ct)) {
+                    complex_types.push((element.name.clone(), ct));
+                    if let Some(group) = ct.model_group() {
+                        Self::collect_anonymous_types(group, &mut seen, &mut complex_types);
+                    }
+                }
+            }
+        }
+        complex_types.sort_by_key(|(qname, _)| qname.to_string());
+        complex_types
+    }
+
+    /// Collect the anonymous complex types of the local elements of a model group
+    fn collect_anonymous_types<'a>(
+        group: &'a XsdGroup,
+        seen: &mut HashSet<*const XsdComplexType>,
+        complex_types: &mut Vec<(QName, &'a XsdComplexType)>,
+    ) {
+        for particle in &group.particles {
+            match particle {
+                GroupParticle::Element(ep) => {
+                    let Some(decl) = ep.element_decl.as_ref() else { continue };
+                    if let ElementType::Complex(ct) = &decl.element_type {
+                        if ct.name.is_none() && seen.insert(Arc::as_ptr(ct)) {
+                            complex_types.push((decl.name.clone(), ct));
+                            if let Some(nested) = ct.model_group() {
+                                Self::collect_anonymous_types(nested, seen, complex_types);
+                            }
+                        }
+                    }
+                }
+                GroupParticle::Group(nested) => Self::collect_anonymous_types(nested, seen, complex_types),
+                GroupParticle::Any(_) => {}
+            }
+        }
+    }
+
+    /// Compile the `xs:pattern` facets of the global simple types
+    ///
+    /// Valid patterns are cached and available through `compiled_patterns`.
+    /// Returns the type name, pattern and regex error of every pattern that
+    /// fails to compile, sorted by type name; such patterns are otherwise
+    /// dropped silently while parsing.
+    pub fn precompile_patterns(&mut self) -> Vec<(QName, String, regex::Error)> {
+        let mut names: Vec<&QName> = self.pattern_sources.keys().collect();
+        names.sort_by(|a, b| qname_order(a, b));
+
+        let mut compiled = HashMap::new();
+        let mut failures = Vec::new();
+        for name in names {
+            let mut regexes = Vec::new();
+            for pattern in &self.pattern_sources[name] {
+                match Regex::new(pattern) {
+                    Ok(regex) => regexes.push(regex),
+                    Err(e) => failures.push((name.clone(), pattern.clone(), e)),
+                }
+            }
+            compiled.insert(name.clone(), regexes);
+        }
+        self.compiled_patterns = compiled;
+        failures
+    }
+
+    /// Get the compiled patterns of a global simple type
+    ///
+    /// Empty until `precompile_patterns` has been called.
+    pub fn compiled_patterns(&self, type_name: &QName) -> Option<&[Regex]> {
+        self.compiled_patterns.get(type_name).map(Vec::as_slice)
+    }
+
+    /// Check the facets of the global simple types for redundant constraints
+    ///
+    /// Reports range facets that allow a single value and `length` facets
+    /// combined with `minLength` or `maxLength`.
+    pub fn check_redundant_constraints(&self) -> Vec<LintWarning> {
+        let mut simple_types: Vec<_> = self
+            .types()
+            .filter_map(|(qname, global_type)| match global_type {
+                GlobalType::Simple(st) if qname.namespace.as_deref() != Some(XSD_NAMESPACE) => Some((qname, st)),
+                _ => None,
+            })
+            .collect();
+        simple_types.sort_by(|a, b| qname_order(a.0, b.0));
+
+        let mut warnings = Vec::new();
+        for (qname, st) in simple_types {
+            let facets = st.facets();
+            if let (Some(min), Some(max)) = (&facets.min_inclusive, &facets.max_inclusive) {
+                if same_bound(&min.value, &max.value) {
+                    warnings.push(LintWarning::new(
+                        LintKind::SingleValueRange,
+                        qname.clone(),
+                        format!(
+                            "Type '{}' has minInclusive equal to maxInclusive ({}), so only one value is valid",
+                            qname.to_string(),
+                            min.value
+                        ),
+                    ));
+                }
+            }
+            if facets.length.is_some() && (facets.min_length.is_some() || facets.max_length.is_some()) {
+                warnings.push(LintWarning::new(
+                    LintKind::RedundantLengthFacets,
+                    qname.clone(),
+                    format!("Type '{}' has a length facet with redundant minLength/maxLength", qname.to_string()),
+                ));
+            }
+        }
+        warnings
+    }
+
+    /// Check the schema for likely design mistakes
+    ///
+    /// Besides [`check_redundant_constraints`](Self::check_redundant_constraints),
+    /// reports abstract complex types without concrete subtypes, global
+    /// elements not referenced by any content model and attribute groups
+    /// with a single attribute. The warnings don't affect validation.
+    pub fn lint(&self) -> SchemaLintReport {
+        let mut warnings = self.check_redundant_constraints();
+        let complex_types = self.complex_types_with_anonymous();
+
+        // Abstract types need a concrete type derived from them
+        let mut derived = HashSet::new();
+        for (_, ct) in complex_types.iter().filter(|(_, ct)| !ct.abstract_type) {
+            let mut base = ct.base_type.clone();
+            while let Some(base_name) = base {
+                if !derived.insert(base_name.clone()) {
+                    break;
+                }
+                base = match self.lookup_type(&base_name) {
+                    Some(GlobalType::Complex(base_type)) => base_type.base_type.clone(),
+                    _ => None,
+                };
+            }
+        }
+        for (qname, ct) in &complex_types {
+            if ct.abstract_type && ct.name.is_some() && !derived.contains(qname) {
+                warnings.push(LintWarning::new(
+                    LintKind::UnreachableAbstractType,
+                    qname.clone(),
+                    format!("Abstract type '{}' has no concrete subtypes", qname.to_string()),
+                ));
+            }
+        }
+
+        // Global elements are used through references and substitution groups
+        let mut referenced = HashSet::new();
+        for (_, ct) in &complex_types {
+            if let Some(group) = ct.model_group() {
+                collect_element_refs(group, &mut referenced);
+            }
+        }
+        for (_, group) in self.groups() {
+            collect_element_refs(group, &mut referenced);
+        }
+        let mut elements: Vec<_> = self
+            .elements()
+            .filter(|(qname, element)| !referenced.contains(*qname) && element.substitution_group.is_none())
+            .map(|(qname, _)| qname)
+            .collect();
+        elements.sort_by(|a, b| qname_order(a, b));
+        for qname in elements {
+            warnings.push(LintWarning::new(
+                LintKind::UnusedElement,
+                qname.clone(),
+                format!("Global element '{}' is not referenced in any content model", qname.to_string()),
+            ));
+        }
+
+        let mut attribute_groups: Vec<_> = self
+            .attribute_groups()
+            .filter(|(_, group)| group.len() == 1 && !group.has_any_attribute() && !group.has_pending_refs())
+            .collect();
+        attribute_groups.sort_by(|a, b| qname_order(a.0, b.0));
+        for (qname, group) in attribute_groups {
+            let attribute = group.iter_attributes().next().map(|attr| attr.name().to_string()).unwrap_or_default();
+            warnings.push(LintWarning::new(
+                LintKind::SingleAttributeGroup,
+                qname.clone(),
+                format!(
+                    "Attribute group '{}' only has attribute '{}' and could be replaced with it",
+                    qname.to_string(),
+                    attribute
+                ),
+            ));
+        }
+
+        SchemaLintReport { warnings }
+    }
+
     /// Iterate over global element names
     pub fn element_names(&self) -> impl Iterator<Item = &QName> {
         self.maps.global_maps.elements.keys()
@@ -532,6 +1171,69 @@ impl XsdSchema {
         self.maps.global_maps.elements.iter()
     }
 
+    /// Group the global elements by the head of their substitution group
+    ///
+    /// Each head maps to its direct members, in declaration order; members of
+    /// a member are listed under that member.
+    pub fn collect_substitution_groups(&self) -> HashMap<QName, Vec<Arc<XsdElement>>> {
+        let mut groups: HashMap<QName, Vec<Arc<XsdElement>>> = HashMap::new();
+        for (_, element) in self.elements() {
+            if let Some(head) = &element.substitution_group {
+                groups.entry(head.clone()).or_default().push(Arc::clone(element));
+            }
+        }
+        groups
+    }
+
+    /// Get the members of the substitution group of a head element
+    ///
+    /// Members are transitive: an element whose head is itself a member is
+    /// included, after the direct members.
+    pub fn substitution_group_members(&self, head: &QName) -> Vec<Arc<XsdElement>> {
+        let groups = self.collect_substitution_groups();
+        let mut members: Vec<Arc<XsdElement>> = Vec::new();
+        let mut visited = HashSet::from([head.clone()]);
+        let mut pending = vec![head.clone()];
+        while !pending.is_empty() {
+            let mut next = Vec::new();
+            for name in &pending {
+                for member in groups.get(name).into_iter().flatten() {
+                    if visited.insert(member.name.clone()) {
+                        next.push(member.name.clone());
+                        members.push(Arc::clone(member));
+                    }
+                }
+            }
+            pending = next;
+        }
+        members
+    }
+
+    /// The heads an element can substitute for, nearest first
+    ///
+    /// Abstract elements and elements without a global declaration can't
+    /// appear in place of a head and give no heads.
+    pub(crate) fn substitution_heads(&self, qname: &QName) -> Vec<QName> {
+        let mut heads = Vec::new();
+        let Some(mut element) = self.lookup_element(qname) else {
+            return heads;
+        };
+        if element.abstract_element {
+            return heads;
+        }
+        while let Some(head) = &element.substitution_group {
+            if *head == *qname || heads.contains(head) {
+                break;
+            }
+            heads.push(head.clone());
+            match self.lookup_element(head) {
+                Some(head_element) => element = head_element,
+                None => break,
+            }
+        }
+        heads
+    }
+
     /// Iterate over global types
     pub fn types(&self) -> impl Iterator<Item = (&QName, &GlobalType)> {
         self.maps.global_maps.types.iter()
@@ -571,15 +1273,45 @@ impl XsdSchema {
 
     /// Create a QName with the target namespace prefix
     pub fn create_qname(&self, local_name: &str) -> String {
-        if let Some(ns) = &self.target_namespace {
-            // Find prefix for namespace
-            for (prefix, namespace) in &self.source.namespaces {
-                if namespace == ns && !prefix.is_empty() {
-                    return format!("{}:{}", prefix, local_name);
-                }
-            }
+        match self.target_namespace.as_deref().and_then(|ns| self.preferred_prefix_for(ns)) {
+            Some(prefix) if !prefix.is_empty() => format!("{}:{}", prefix, local_name),
+            _ => local_name.to_string(),
+        }
+    }
+
+    /// Get the namespace declarations of the schema document's root element
+    ///
+    /// The map is keyed by prefix; the default namespace has an empty prefix.
+    pub fn namespace_declarations(&self) -> &HashMap<String, String> {
+        &self.source.namespaces
+    }
+
+    /// Get the prefix the schema uses for a namespace URI
+    ///
+    /// A prefix registered with [`XsdSchema::add_preferred_prefix`] wins.
+    /// Otherwise the schema document's declarations are searched, preferring
+    /// a non-empty prefix over the default namespace; when several prefixes
+    /// are bound to the URI the alphabetically first one is returned.
+    pub fn preferred_prefix_for(&self, namespace: &str) -> Option<&str> {
+        if let Some(prefix) = self.source.preferred_prefixes.get(namespace) {
+            return Some(prefix);
         }
-        local_name.to_string()
+        self.source
+            .namespaces
+            .iter()
+            .filter(|(_, uri)| *uri == namespace)
+            .map(|(prefix, _)| prefix.as_str())
+            .min_by_key(|prefix| (prefix.is_empty(), *prefix))
+    }
+
+    /// Register the prefix to use for a namespace URI
+    ///
+    /// This only affects [`XsdSchema::preferred_prefix_for`] and names built
+    /// from it; prefixes in the schema document keep resolving as declared.
+    pub fn add_preferred_prefix(&mut self, prefix: &str, namespace: &str) {
+        self.source
+            .preferred_prefixes
+            .insert(namespace.to_string(), prefix.to_string());
     }
 
     /// Resolve element form for a local element
@@ -612,6 +1344,42 @@ impl XsdSchema {
         self.imports.get(namespace)
     }
 
+    /// Get the loaded schema for a namespace
+    ///
+    /// Returns this schema if `ns` is its target namespace, otherwise searches
+    /// the imported schemas and, transitively, their imports.
+    pub fn schema_for_namespace(&self, ns: &str) -> Option<&XsdSchema> {
+        self.all_loaded_schemas()
+            .into_iter()
+            .find(|schema| schema.target_namespace.as_deref() == Some(ns))
+    }
+
+    /// Get all transitively loaded schemas
+    ///
+    /// The list starts with this schema, followed by its imported schemas
+    /// (depth-first, imports ordered by namespace). Each schema appears once.
+    pub fn all_loaded_schemas(&self) -> Vec<&XsdSchema> {
+        let mut schemas = vec![self];
+        let mut index = 0;
+        while index < schemas.len() {
+            let current = schemas[index];
+            let mut imports: Vec<_> = current.imports.values().collect();
+            imports.sort_by(|a, b| a.namespace.cmp(&b.namespace));
+            let mut position = index + 1;
+            for import in imports {
+                if let Some(ref imported) = import.schema {
+                    let imported = imported.as_ref();
+                    if !schemas.iter().any(|s| std::ptr::eq(*s, imported)) {
+                        schemas.insert(position, imported);
+                        position += 1;
+                    }
+                }
+            }
+            index += 1;
+        }
+        schemas
+    }
+
     /// Add an import record
     pub fn add_import(&mut self, namespace: String, location: Option<String>) {
         self.imports.insert(namespace.clone(), SchemaImport {
@@ -625,1433 +1393,6117 @@ impl XsdSchema {
     // Document Validation API
     // =========================================================================
 
-    /// Validate an XML document against this schema
+    /// Check if a local name is declared as a global element
+    pub fn is_valid_name_for_element(&self, local_name: &str) -> bool {
+        self.element_names().any(|qname| qname.local_name == local_name)
+    }
+
+    /// Get the element names that can appear as children of the element at `path`
     ///
-    /// Returns a ValidationResult containing validation status and any errors.
+    /// `path` is a slash-separated list of element names starting at the
+    /// document root, e.g. `/book/author`; prefixes and positional predicates
+    /// (`author[2]`) are ignored. The result is the union of all elements of
+    /// the effective content model, in declaration order. Wildcards are not
+    /// expanded. Returns an empty list if the path does not match the schema
+    /// or the element has simple or empty content.
+    pub fn valid_elements_at(&self, path: &str) -> Vec<&QName> {
+        let mut names = Vec::new();
+        if let Some(group) = self
+            .element_at_path(path)
+            .and_then(|decl| self.declared_complex_type(decl))
+            .and_then(|ct| ct.model_group())
+        {
+            self.collect_group_element_names(group, &mut names);
+        }
+        names
+    }
+
+    /// Check if `element_name` can appear as a child of the element at `path`
+    pub fn is_valid_element_at(&self, path: &str, element_name: &QName) -> bool {
+        self.valid_elements_at(path).into_iter().any(|qname| {
+            qname.local_name == element_name.local_name
+                && (element_name.namespace.is_none() || qname.namespace == element_name.namespace)
+        })
+    }
+
+    /// Get the type of the element at the end of a path of element names
     ///
-    /// # Example
+    /// The first name is a global element and each following name a child of
+    /// the previous element, e.g. `&["book", "author", "email"]`; names are
+    /// matched by local name. Repeated elements yield their item type. When a
+    /// name matches declarations of different simple types, as in the branches
+    /// of a choice, the result is the union of those types. Returns `None` for
+    /// unknown or untyped elements, elements only allowed by a wildcard, and
+    /// names matching different complex types.
     ///
-    /// ```ignore
-    /// let schema = XsdSchema::parse_file("schema.xsd")?;
-    /// let doc = Document::from_string("<root>...</root>")?;
-    /// let result = schema.validate(&doc);
-    /// if !result.valid {
-    ///     for error in &result.errors {
-    ///         eprintln!("Validation error: {}", error);
-    ///     }
-    /// }
-    /// ```
-    pub fn validate(&self, doc: &Document) -> ValidationResult {
-        self.validate_with_mode(doc, self.validation)
+    /// An owned `GlobalType` is returned because anonymous types live in the
+    /// element declarations rather than in the schema, as for
+    /// [`XsdElement::effective_type`].
+    pub fn type_at_element_path(&self, element_names: &[&str]) -> Option<GlobalType> {
+        let (root_name, steps) = element_names.split_first()?;
+        let root = self
+            .lookup_element(&QName::new(self.target_namespace.clone(), *root_name))
+            .or_else(|| self.elements().find(|(qname, _)| qname.local_name == *root_name).map(|(_, e)| e))?;
+
+        let mut current = root.effective_type(self)?;
+        for step in steps {
+            let group = current.as_complex()?.model_group()?;
+            let mut types = Vec::new();
+            self.collect_group_element_types(group, step, &mut types);
+
+            current = match types.len() {
+                0 => return None,
+                1 => types.pop()?,
+                _ => {
+                    let members = types
+                        .iter()
+                        .map(|t| t.as_simple().cloned())
+                        .collect::<Option<Vec<_>>>()?;
+                    GlobalType::Simple(Arc::new(XsdUnionType::new(members)))
+                }
+            };
+        }
+        Some(current)
     }
 
-    /// Validate an XML document with a specific validation mode
-    pub fn validate_with_mode(&self, doc: &Document, mode: ValidationMode) -> ValidationResult {
-        let mut context = ValidationContext::new().with_mode(mode);
+    /// Validate a single attribute value for an element of the given type
+    ///
+    /// The attribute is looked up in the attribute declarations of the
+    /// complex type `element_type`, falling back to the global attribute
+    /// declarations for qualified names. `None` stands for an absent
+    /// attribute: the fixed or default value is used, and a required
+    /// attribute is an error. Returns the typed value on success.
+    pub fn validate_attribute_value(
+        &self,
+        element_type: &QName,
+        attr_name: &QName,
+        value: Option<&str>,
+    ) -> Result<XsdValue> {
+        use super::base::AttributeValidator;
 
-        match validate_document(self, doc, &mut context) {
-            Ok(()) => {
-                if context.has_errors() {
-                    ValidationResult::invalid(
-                        context.errors.iter().map(|e| e.message().to_string()).collect(),
-                    )
-                } else {
-                    ValidationResult::valid()
-                }
+        let complex_type = match self.lookup_type(element_type) {
+            Some(GlobalType::Complex(ct)) => ct,
+            Some(GlobalType::Simple(_)) => {
+                return Err(Error::Validation(
+                    ValidationError::new(format!(
+                        "Attribute '{}' is not allowed on elements of simple type '{}'",
+                        attr_name.to_string(),
+                        element_type.to_string()
+                    ))
+                    .with_reason("Simple types have no attributes"),
+                ))
             }
-            Err(e) => {
-                let mut errors: Vec<String> = context.errors.iter().map(|e| e.message().to_string()).collect();
-                errors.push(e.to_string());
-                ValidationResult::invalid(errors)
+            None => {
+                return Err(Error::Validation(
+                    ValidationError::new(format!("Unknown type: {}", element_type.to_string()))
+                        .with_reason("No global type declaration found"),
+                ))
+            }
+        };
+
+        let global = attr_name.namespace.as_ref().and_then(|_| self.lookup_attribute(attr_name));
+        let local = complex_type
+            .attributes
+            .get_attribute(attr_name)
+            .or_else(|| complex_type.attributes.get_attribute(&QName::local(&attr_name.local_name)));
+        let decl = local.or(global).ok_or_else(|| {
+            Error::Validation(
+                ValidationError::new(format!(
+                    "Attribute '{}' is not declared for type '{}'",
+                    attr_name.to_string(),
+                    element_type.to_string()
+                ))
+                .with_reason("No local or global attribute declaration found"),
+            )
+        })?;
+
+        if decl.is_prohibited() {
+            return Err(Error::Validation(
+                ValidationError::new(format!("Attribute '{}' is prohibited", attr_name.to_string()))
+                    .with_reason(format!("Prohibited by type '{}'", element_type.to_string())),
+            ));
+        }
+
+        // A reference may carry the use only, the rest comes from the global declaration
+        let fixed = decl.fixed_value().or_else(|| global.and_then(|g| g.fixed_value()));
+        let default = decl.default().or_else(|| global.and_then(|g| g.default()));
+        let attr_type = decl
+            .effective_type(self)
+            .or_else(|| global.and_then(|g| g.effective_type(self)));
+        let white_space = attr_type.map_or(WhiteSpace::Preserve, |attr_type| attr_type.white_space());
+        let value = value.map(|value| normalize_attribute_value(value, white_space));
+
+        let actual = match value.as_deref() {
+            Some(value) => {
+                if let Some(fixed) = fixed {
+                    if value != normalize_attribute_value(fixed, white_space) {
+                        return Err(Error::Validation(
+                            ValidationError::new(format!(
+                                "Attribute '{}' has fixed value '{}'",
+                                attr_name.to_string(),
+                                fixed
+                            ))
+                            .with_reason(format!("Provided value: '{}'", value)),
+                        ));
+                    }
+                }
+                value
             }
+            None => match fixed.or(default) {
+                Some(constraint) => constraint,
+                None if decl.is_required() => {
+                    return Err(Error::Validation(
+                        ValidationError::new(format!("Attribute '{}' is required", attr_name.to_string()))
+                            .with_reason("No value provided for required attribute"),
+                    ))
+                }
+                None => return Ok(XsdValue::Null),
+            },
+        };
+
+        match attr_type {
+            Some(attr_type) => attr_type.validate_value(actual),
+            // No type means anySimpleType - accept any value
+            None => Ok(XsdValue::String(actual.to_string())),
         }
     }
 
-    /// Check if an XML document is valid against this schema
-    ///
-    /// This is a convenience method that returns a boolean.
-    /// Use `validate()` if you need detailed error information.
-    ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// let schema = XsdSchema::parse_file("schema.xsd")?;
-    /// let doc = Document::from_string("<root>...</root>")?;
-    /// if schema.is_valid(&doc) {
-    ///     println!("Document is valid!");
-    /// }
-    /// ```
-    pub fn is_valid(&self, doc: &Document) -> bool {
-        self.validate(doc).valid
-    }
-
-    /// Validate an XML string against this schema
-    ///
-    /// Parses the XML string and validates it.
-    pub fn validate_string(&self, xml: &str) -> ValidationResult {
-        match Document::from_string(xml) {
-            Ok(doc) => self.validate(&doc),
-            Err(e) => ValidationResult::invalid(vec![format!("Failed to parse XML: {}", e)]),
-        }
-    }
+    /// Find the element declaration for a slash-separated element path
+    fn element_at_path(&self, path: &str) -> Option<&XsdElement> {
+        let mut steps = path.split('/').filter(|step| !step.is_empty()).map(|step| {
+            let step = step.split('[').next().unwrap_or(step);
+            step.rsplit(':').next().unwrap_or(step)
+        });
 
-    /// Check if an XML string is valid against this schema
-    pub fn is_valid_string(&self, xml: &str) -> bool {
-        self.validate_string(xml).valid
-    }
+        let root_name = steps.next()?;
+        let mut decl: &XsdElement = self
+            .lookup_element(&QName::new(self.target_namespace.clone(), root_name))
+            .or_else(|| self.elements().find(|(qname, _)| qname.local_name == root_name).map(|(_, e)| e))?;
 
-    /// Validate an XML file against this schema
-    ///
-    /// Reads and parses the file, then validates it.
-    pub fn validate_file(&self, path: &Path) -> ValidationResult {
-        match std::fs::read_to_string(path) {
-            Ok(content) => self.validate_string(&content),
-            Err(e) => ValidationResult::invalid(vec![format!(
-                "Failed to read file '{}': {}",
-                path.display(),
-                e
-            )]),
+        for step in steps {
+            let group = self.declared_complex_type(decl)?.model_group()?;
+            decl = self.find_group_element(group, step)?;
         }
+        Some(decl)
     }
 
-    /// Check if an XML file is valid against this schema
-    pub fn is_valid_file(&self, path: &Path) -> bool {
-        self.validate_file(path).valid
-    }
-
-    /// Get all validation errors for an XML document
-    ///
-    /// This always runs in lax mode to collect all errors.
-    pub fn iter_errors(&self, doc: &Document) -> Vec<String> {
-        let result = self.validate_with_mode(doc, ValidationMode::Lax);
-        result.errors
-    }
-
-    /// Resolve type references in global elements
-    ///
-    /// This is called during the build phase to resolve forward type references.
-    fn resolve_element_types(&mut self) {
-        use super::elements::ElementType;
-
-        // Collect elements that need type resolution
-        let elements_to_update: Vec<_> = self.maps.global_maps.elements.iter()
-            .filter_map(|(qname, elem)| {
-                // Check if element has a type_name that needs resolution
-                if let Some(ref type_name) = elem.type_name {
-                    // Only resolve if current type is Any (unresolved)
-                    if matches!(elem.element_type, ElementType::Any) {
-                        if let Some(global_type) = self.maps.global_maps.types.get(type_name) {
-                            let resolved_type = match global_type {
-                                GlobalType::Complex(ct) => ElementType::Complex(Arc::clone(ct)),
-                                GlobalType::Simple(st) => ElementType::Simple(Arc::clone(st)),
-                            };
-                            return Some((qname.clone(), elem.as_ref().clone(), resolved_type));
-                        }
-                    }
+    /// Get the complex type of an element declaration, resolving by name if needed
+    fn declared_complex_type<'a>(&'a self, decl: &'a XsdElement) -> Option<&'a XsdComplexType> {
+        if let Some(ref_name) = decl.ref_element.as_ref() {
+            if let Some(target) = self.lookup_element(ref_name) {
+                if !std::ptr::eq(target.as_ref(), decl) {
+                    return self.declared_complex_type(target);
                 }
-                None
-            })
-            .collect();
-
-        // Update elements with resolved types
-        for (qname, mut elem, resolved_type) in elements_to_update {
-            elem.element_type = resolved_type;
-            self.maps.global_maps.elements.insert(qname, Arc::new(elem));
+            }
+        }
+        match &decl.element_type {
+            ElementType::Complex(ct) => Some(ct),
+            ElementType::Simple(_) => None,
+            ElementType::Any => match self.lookup_type(decl.type_name.as_ref()?)? {
+                GlobalType::Complex(ct) => Some(ct),
+                GlobalType::Simple(_) => None,
+            },
         }
     }
 
-    /// Resolve element types in complex type content models
-    ///
-    /// This handles forward references where a local element references a type
-    /// that's defined later in the schema.
-    fn resolve_element_particle_types(&mut self) {
-        use super::complex_types::ComplexContent;
-        use super::elements::ElementType;
-        use super::groups::{ElementParticle, GroupParticle, XsdGroup};
-
-        // Collect complex types that need element type resolution
-        let types_to_update: Vec<_> = self.maps.global_maps.types.iter()
-            .filter_map(|(qname, global_type)| {
-                if let GlobalType::Complex(ct) = global_type {
-                    if let ComplexContent::Group(group) = &ct.content {
-                        if Self::has_unresolved_element_types(group) {
-                            return Some((qname.clone(), Arc::clone(ct)));
-                        }
+    /// Find the declaration of a child element by local name in a model group
+    fn find_group_element<'a>(&'a self, group: &'a XsdGroup, local_name: &str) -> Option<&'a XsdElement> {
+        for particle in &group.particles {
+            match particle {
+                GroupParticle::Element(ep) if ep.name.local_name == local_name => {
+                    if let Some(decl) = ep.element_decl.as_deref() {
+                        return Some(decl);
+                    }
+                    let ref_name = ep.element_ref.as_ref().unwrap_or(&ep.name);
+                    if let Some(decl) = self.lookup_element(ref_name) {
+                        return Some(decl);
                     }
                 }
-                None
-            })
-            .collect();
-
-        // Resolve element types in each complex type
-        for (qname, ct) in types_to_update {
-            if let ComplexContent::Group(group) = &ct.content {
-                if let Some(resolved_group) = self.resolve_element_types_in_group(group) {
-                    let mut new_ct = (*ct).clone();
-                    new_ct.content = ComplexContent::Group(Arc::new(resolved_group));
-                    self.maps.global_maps.types.insert(qname, GlobalType::Complex(Arc::new(new_ct)));
+                GroupParticle::Group(nested) => {
+                    if let Some(decl) = self.find_group_element(self.resolve_group_ref(nested), local_name) {
+                        return Some(decl);
+                    }
                 }
+                _ => {}
             }
         }
+        None
     }
 
-    /// Check if a group has any unresolved element types
-    fn has_unresolved_element_types(group: &super::groups::XsdGroup) -> bool {
-        use super::elements::ElementType;
-        use super::groups::GroupParticle;
-
+    /// Collect the names of all elements allowed by a model group
+    fn collect_group_element_names<'a>(&'a self, group: &'a XsdGroup, names: &mut Vec<&'a QName>) {
         for particle in &group.particles {
             match particle {
                 GroupParticle::Element(ep) => {
-                    if let Some(elem) = ep.element() {
-                        if matches!(elem.element_type, ElementType::Any) && elem.type_name.is_some() {
-                            return true;
-                        }
+                    if ep.occurs.max != Some(0) && !names.contains(&&ep.name) {
+                        names.push(&ep.name);
                     }
                 }
                 GroupParticle::Group(nested) => {
-                    if Self::has_unresolved_element_types(nested) {
-                        return true;
+                    if nested.occurs.max != Some(0) {
+                        self.collect_group_element_names(self.resolve_group_ref(nested), names);
                     }
                 }
                 GroupParticle::Any(_) => {}
             }
         }
-        false
     }
 
-    /// Resolve element types in a group recursively
-    fn resolve_element_types_in_group(&self, group: &super::groups::XsdGroup) -> Option<super::groups::XsdGroup> {
-        use super::elements::ElementType;
-        use super::groups::{ElementParticle, GroupParticle, XsdGroup};
-
-        let mut new_particles = Vec::new();
-        let mut changed = false;
-
+    /// Collect the distinct types of the elements named `local_name` in a model group
+    fn collect_group_element_types(&self, group: &XsdGroup, local_name: &str, types: &mut Vec<GlobalType>) {
         for particle in &group.particles {
             match particle {
-                GroupParticle::Element(ep) => {
-                    if let Some(elem) = ep.element() {
-                        if matches!(elem.element_type, ElementType::Any) {
-                            // Try to resolve the type
-                            if let Some(ref type_name) = elem.type_name {
-                                if let Some(global_type) = self.maps.global_maps.types.get(type_name) {
-                                    let resolved_type = match global_type {
-                                        GlobalType::Complex(ct) => ElementType::Complex(Arc::clone(ct)),
-                                        GlobalType::Simple(st) => ElementType::Simple(Arc::clone(st)),
-                                    };
-                                    // Create updated element with resolved type
-                                    // elem is Arc<XsdElement>, we need to clone the inner value
-                                    let mut new_elem = elem.as_ref().clone();
-                                    new_elem.element_type = resolved_type;
-                                    let new_particle = ElementParticle::with_decl(
-                                        ep.name.clone(),
-                                        ep.occurs,
-                                        Arc::new(new_elem),
-                                    );
-                                    new_particles.push(GroupParticle::Element(Arc::new(new_particle)));
-                                    changed = true;
-                                    continue;
-                                }
-                            }
+                GroupParticle::Element(ep) if ep.name.local_name == local_name && ep.occurs.max != Some(0) => {
+                    let decl = match ep.element_decl.as_ref() {
+                        Some(decl) => Some(decl),
+                        None => self.lookup_element(ep.element_ref.as_ref().unwrap_or(&ep.name)),
+                    };
+                    if let Some(element_type) = decl.and_then(|decl| decl.effective_type(self)) {
+                        if !types.iter().any(|t| t.is_same(&element_type)) {
+                            types.push(element_type);
                         }
                     }
-                    new_particles.push(particle.clone());
-                }
-                GroupParticle::Group(nested) => {
-                    if let Some(resolved_nested) = self.resolve_element_types_in_group(nested) {
-                        new_particles.push(GroupParticle::Group(Arc::new(resolved_nested)));
-                        changed = true;
-                    } else {
-                        new_particles.push(particle.clone());
-                    }
                 }
-                GroupParticle::Any(_) => {
-                    new_particles.push(particle.clone());
+                GroupParticle::Group(nested) if nested.occurs.max != Some(0) => {
+                    self.collect_group_element_types(self.resolve_group_ref(nested), local_name, types);
                 }
+                _ => {}
             }
         }
+    }
 
-        if changed {
-            // Create a new group with the same model and resolved particles
-            let mut new_group = if let Some(ref name) = group.name {
-                XsdGroup::named(name.clone(), group.model)
-            } else {
-                XsdGroup::new(group.model)
-            };
-            new_group.particles = new_particles;
-            Some(new_group)
-        } else {
-            None
+    /// Get the referenced global group for an unresolved group reference
+    fn resolve_group_ref<'a>(&'a self, group: &'a XsdGroup) -> &'a XsdGroup {
+        match group.group_ref.as_ref() {
+            Some(ref_name) if group.particles.is_empty() => {
+                self.lookup_group(ref_name).map(|g| g.as_ref()).unwrap_or(group)
+            }
+            _ => group,
         }
     }
 
-    /// Resolve attribute types in complex types (forward references)
+    /// Check that the document root matches a global element declaration
     ///
-    /// This handles cases where attributes reference simple types that are defined
-    /// later in the schema.
-    fn resolve_attribute_types(&mut self) {
-        use super::attributes::XsdAttribute;
-        use super::base::AttributeValidator;
+    /// The root element name is resolved to a QName, whose namespace must be
+    /// the target namespace or an imported namespace, and looked up among the
+    /// global elements. Failures are reported as an "Unknown root element"
+    /// error, distinct from content model errors, so callers can tell a
+    /// document for another schema from an invalid document.
+    pub fn check_document_element(&self, doc: &Document) -> std::result::Result<&Arc<XsdElement>, ValidationError> {
+        let root = doc
+            .root
+            .as_ref()
+            .ok_or_else(|| ValidationError::new("Document has no root element"))?;
+        self.check_root_element(root)
+    }
 
-        // Collect complex types that have unresolved attribute types
-        let types_to_update: Vec<_> = self.maps.global_maps.types.iter()
-            .filter_map(|(qname, global_type)| {
-                if let GlobalType::Complex(ct) = global_type {
-                    // Check if any attribute has an unresolved type_name
-                    let has_unresolved = ct.attributes.iter_attributes()
-                        .any(|attr| attr.type_name.is_some() && attr.simple_type().is_none());
-                    if has_unresolved {
-                        return Some((qname.clone(), Arc::clone(ct)));
-                    }
-                }
-                None
-            })
-            .collect();
+    /// Check that a root element matches a global element declaration
+    ///
+    /// This is [`check_document_element`](Self::check_document_element) for
+    /// an element read without its document, as streaming validation does.
+    pub fn check_root_element(&self, root: &Element) -> std::result::Result<&Arc<XsdElement>, ValidationError> {
+        let root_qname = resolve_element_qname(root, self);
+        let unknown = || {
+            ValidationError::new(format!("Unknown root element: {}", root.local_name()))
+                .with_path(format!("/{}", root.local_name()))
+        };
 
-        // Resolve attribute types
-        for (qname, ct) in types_to_update {
-            let mut new_ct = (*ct).clone();
+        let namespace = root_qname.namespace.as_deref();
+        let known_namespace = namespace == self.target_namespace.as_deref()
+            || namespace.is_some_and(|ns| self.has_import(ns));
+        if !known_namespace {
+            return Err(unknown().with_reason(format!(
+                "Namespace '{}' is neither the target namespace nor an imported namespace",
+                namespace.unwrap_or("")
+            )));
+        }
 
-            // Collect attributes to update (to avoid borrow issues)
-            let attrs_to_update: Vec<_> = new_ct.attributes.iter_attributes()
-                .filter_map(|attr| {
-                    if let Some(ref type_name) = attr.type_name {
-                        if attr.simple_type().is_none() {
-                            if let Some(global_type) = self.maps.global_maps.types.get(type_name) {
-                                if let GlobalType::Simple(st) = global_type {
-                                    // Create updated attribute with resolved type
-                                    let mut new_attr = XsdAttribute::new(attr.name().clone());
-                                    new_attr.set_type(Arc::clone(st));
-                                    new_attr.set_use(attr.use_mode());
-                                    if let Some(default) = attr.default() {
-                                        let _ = new_attr.set_default(default.to_string());
-                                    }
-                                    if let Some(fixed) = attr.fixed_value() {
-                                        let _ = new_attr.set_fixed(fixed.to_string());
-                                    }
-                                    return Some(Arc::new(new_attr));
-                                }
-                            }
-                        }
-                    }
-                    None
-                })
-                .collect();
+        self.lookup_element(&root_qname).ok_or_else(|| {
+            unknown().with_reason(format!(
+                "No global element declaration found for '{}:{}'",
+                namespace.unwrap_or(""),
+                root_qname.local_name
+            ))
+        })
+    }
 
-            // Apply updates
-            if !attrs_to_update.is_empty() {
-                for new_attr in attrs_to_update {
-                    new_ct.attributes.set_attribute(new_attr);
-                }
-                self.maps.global_maps.types.insert(qname, GlobalType::Complex(Arc::new(new_ct)));
-            }
-        }
+    /// Validate an XML document against this schema
+    ///
+    /// Returns a ValidationResult containing validation status and any errors.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let schema = XsdSchema::parse_file("schema.xsd")?;
+    /// let doc = Document::from_string("<root>...</root>")?;
+    /// let result = schema.validate(&doc);
+    /// if !result.valid {
+    ///     for error in &result.errors {
+    ///         eprintln!("Validation error: {}", error);
+    ///     }
+    /// }
+    /// ```
+    pub fn validate(&self, doc: &Document) -> ValidationResult {
+        self.validate_with_mode(doc, self.validation)
     }
 
-    /// Refresh global element types with the fully resolved versions
+    /// Validate an XML document against a schema shared between threads
     ///
-    /// After all type resolution is complete, update global elements that reference
-    /// named complex types to use the fully resolved type from global_maps.types.
-    /// This ensures element's inline type copies have the resolved attributes and children.
-    fn refresh_element_types(&mut self) {
-        use super::elements::ElementType;
+    /// `XsdSchema` is `Send + Sync`, so a schema wrapped in an `Arc` can be
+    /// used by many threads at once without being cloned.
+    pub fn validate_arc(self: &Arc<Self>, doc: &Document) -> ValidationResult {
+        self.validate(doc)
+    }
 
-        // Collect elements that need their type refreshed
-        let elements_to_update: Vec<_> = self.maps.global_maps.elements.iter()
-            .filter_map(|(elem_qname, elem)| {
-                if let ElementType::Complex(ct) = &elem.element_type {
-                    // If the complex type has a name, look up the fresh version
-                    if let Some(ref type_name) = ct.name {
-                        if let Some(GlobalType::Complex(fresh_ct)) = self.maps.global_maps.types.get(type_name) {
-                            let mut new_elem = (**elem).clone();
-                            new_elem.element_type = ElementType::Complex(Arc::clone(fresh_ct));
-                            return Some((elem_qname.clone(), Arc::new(new_elem)));
-                        }
-                    }
-                }
-                None
-            })
-            .collect();
+    /// Get another handle to a shared schema, for a new thread
+    pub fn clone_schema(self: &Arc<Self>) -> Arc<Self> {
+        Arc::clone(self)
+    }
 
-        // Apply updates
-        for (qname, new_elem) in elements_to_update {
-            self.maps.global_maps.elements.insert(qname, new_elem);
+    /// Validate an XML document with a specific validation mode
+    pub fn validate_with_mode(&self, doc: &Document, mode: ValidationMode) -> ValidationResult {
+        let namespace_usage = self.validate_xml_namespace_usage(doc);
+        let mut context = ValidationContext::new().with_mode(mode);
+        let outcome = validate_document(self, doc, &mut context);
+        let mut result = Self::validation_result(&context, outcome);
+        for usage_error in namespace_usage {
+            result.add_warning(usage_error.to_string());
         }
+        result
     }
 
-    /// Resolve group references (xs:group ref="...")
+    /// Validate an XML document read from a reader, without building its tree
     ///
-    /// This walks through all complex types and resolves group references to their actual content.
-    fn resolve_group_references(&mut self) {
-        use super::complex_types::ComplexContent;
-        use super::groups::GroupParticle;
+    /// The document is validated as it is read, by a [`StreamingValidator`],
+    /// so large documents can be validated in a memory that depends on their
+    /// depth only. Identity constraints are not checked, and malformed XML is
+    /// reported as an error of the result.
+    pub fn validate_reader<R: Read>(&self, reader: R) -> ValidationResult {
+        self.validate_reader_with_mode(reader, self.validation)
+    }
 
-        // Collect complex types that need group reference resolution
-        let types_to_update: Vec<_> = self.maps.global_maps.types.iter()
-            .filter_map(|(qname, global_type)| {
-                if let GlobalType::Complex(ct) = global_type {
-                    if let ComplexContent::Group(group) = &ct.content {
-                        if Self::has_group_refs(group) {
-                            return Some((qname.clone(), Arc::clone(ct)));
-                        }
-                    }
-                }
-                None
-            })
+    /// Validate an XML document read from a reader with a specific validation mode
+    pub fn validate_reader_with_mode<R: Read>(&self, reader: R, mode: ValidationMode) -> ValidationResult {
+        let mut context = ValidationContext::new().with_mode(mode);
+        let outcome = StreamingValidator::new(self).validate_stream(BufReader::new(reader), &mut context);
+        Self::validation_result(&context, outcome)
+    }
+
+    /// Check the namespace usage of an instance document
+    ///
+    /// This reports common interoperability problems that don't make the
+    /// document invalid:
+    /// - elements in a namespace that is neither the target namespace, the
+    ///   XML or XSI namespace, nor an imported namespace;
+    /// - prefixes used in QName-typed attribute values, or in element tags,
+    ///   that are not declared in the element's scope;
+    /// - `xmlns` declarations that rebind a prefix of an outer declaration to
+    ///   a different namespace.
+    ///
+    /// The problems are returned separately from the validity errors;
+    /// [`validate_with_mode`](Self::validate_with_mode) reports them as warnings.
+    pub fn validate_xml_namespace_usage(&self, doc: &Document) -> Vec<NamespaceUsageError> {
+        let mut usage_errors = Vec::new();
+        if let Some(root) = &doc.root {
+            let mut scope: HashMap<String, String> = doc
+                .namespaces
+                .iter()
+                .map(|(prefix, uri)| (prefix.to_string(), uri.to_string()))
+                .collect();
+            if let Some(default_ns) = doc.namespaces.get_default_namespace() {
+                scope.insert(String::new(), default_ns.to_string());
+            }
+            let decl = self.check_document_element(doc).ok().cloned();
+            self.check_namespace_usage(root, decl.as_deref(), &scope, "", &mut usage_errors);
+        }
+        usage_errors
+    }
+
+    fn check_namespace_usage(
+        &self,
+        elem: &Element,
+        decl: Option<&XsdElement>,
+        outer_scope: &HashMap<String, String>,
+        parent_path: &str,
+        usage_errors: &mut Vec<NamespaceUsageError>,
+    ) {
+        let path = format!("{}/{}", parent_path, elem.local_name());
+
+        // Bring the element's own declarations into scope, default namespace under ""
+        let mut declarations: Vec<(String, String)> = elem
+            .namespaces
+            .iter()
+            .map(|(prefix, uri)| (prefix.to_string(), uri.to_string()))
             .collect();
+        if let Some(default_ns) = elem.namespaces.get_default_namespace() {
+            declarations.push((String::new(), default_ns.to_string()));
+        }
+        declarations.sort();
+        let mut scope = outer_scope.clone();
+        for (prefix, uri) in declarations {
+            if let Some(outer_uri) = outer_scope.get(&prefix).filter(|outer_uri| **outer_uri != uri) {
+                let declared = if prefix.is_empty() {
+                    "The default namespace".to_string()
+                } else {
+                    format!("Prefix '{}'", prefix)
+                };
+                usage_errors.push(NamespaceUsageError::new(
+                    NamespaceUsageKind::ShadowedDeclaration,
+                    format!("{} is rebound from '{}' to '{}'", declared, outer_uri, uri),
+                    &path,
+                ));
+            }
+            scope.insert(prefix, uri);
+        }
 
-        // Resolve group references in each type
-        for (qname, ct) in types_to_update {
-            if let ComplexContent::Group(group) = &ct.content {
-                if let Some(resolved_group) = self.resolve_group_ref_recursive(group) {
-                    let mut new_ct = (*ct).clone();
-                    new_ct.content = ComplexContent::Group(Arc::new(resolved_group));
-                    self.maps.global_maps.types.insert(qname, GlobalType::Complex(Arc::new(new_ct)));
+        // The element's own namespace
+        let namespace = match (elem.namespace(), &elem.prefix) {
+            (Some(ns), _) => Some(ns.to_string()),
+            (None, Some(prefix)) => match Self::namespace_in_scope(&scope, prefix) {
+                Some(ns) => Some(ns),
+                None => {
+                    usage_errors.push(NamespaceUsageError::new(
+                        NamespaceUsageKind::UndeclaredPrefix,
+                        format!("Prefix '{}' of element '{}' is not declared", prefix, elem.local_name()),
+                        &path,
+                    ));
+                    None
                 }
+            },
+            // An empty default namespace declaration undeclares it
+            (None, None) => scope.get("").filter(|ns| !ns.is_empty()).cloned(),
+        };
+        if let Some(ns) = namespace.filter(|ns| !self.is_known_instance_namespace(ns)) {
+            usage_errors.push(NamespaceUsageError::new(
+                NamespaceUsageKind::UnknownNamespace,
+                format!("Element '{}' is in namespace '{}', which the schema doesn't import", elem.local_name(), ns),
+                &path,
+            ));
+        }
+
+        // Prefixes in QName-typed attribute values
+        let mut attributes: Vec<(&QName, &String)> = elem.attributes.iter().collect();
+        attributes.sort_by(|a, b| qname_order(a.0, b.0));
+        for (attr_qname, value) in attributes {
+            if !self.is_qname_attribute(attr_qname, decl, &scope) {
+                continue;
+            }
+            let Some((prefix, _)) = value.trim().split_once(':') else {
+                continue;
+            };
+            if Self::namespace_in_scope(&scope, prefix).is_none() {
+                usage_errors.push(NamespaceUsageError::new(
+                    NamespaceUsageKind::UndeclaredPrefix,
+                    format!(
+                        "Prefix '{}' in the value '{}' of attribute '{}' is not declared",
+                        prefix,
+                        value.trim(),
+                        attr_qname.local_name
+                    ),
+                    &path,
+                ));
             }
         }
+
+        let parent_type = match decl.map(|decl| &decl.element_type) {
+            Some(ElementType::Complex(ct)) => Some(ct.as_ref()),
+            _ => None,
+        };
+        for child in &elem.children {
+            let child_decl = find_child_declaration(self, child, parent_type);
+            self.check_namespace_usage(child, child_decl.as_deref(), &scope, &path, usage_errors);
+        }
     }
 
-    /// Check if a group or its children contain group references
-    fn has_group_refs(group: &super::groups::XsdGroup) -> bool {
-        use super::groups::GroupParticle;
+    /// Look up the namespace bound to a prefix, the `xml` prefix being always bound
+    fn namespace_in_scope(scope: &HashMap<String, String>, prefix: &str) -> Option<String> {
+        match prefix {
+            "xml" => Some(XML_NAMESPACE.to_string()),
+            _ => scope.get(prefix).filter(|ns| !ns.is_empty()).cloned(),
+        }
+    }
 
-        if group.group_ref.is_some() {
+    /// Check whether instance elements may be in a namespace
+    fn is_known_instance_namespace(&self, namespace: &str) -> bool {
+        namespace == XML_NAMESPACE
+            || namespace == XSI_NAMESPACE
+            || self.target_namespace.as_deref() == Some(namespace)
+            || self.imports.contains_key(namespace)
+    }
+
+    /// Check whether an attribute of an instance element has a QName value
+    ///
+    /// That is `xsi:type`, when the XSI namespace is in scope, and attributes
+    /// whose declaration in the element's type is derived from `xs:QName`.
+    fn is_qname_attribute(&self, attr_qname: &QName, decl: Option<&XsdElement>, scope: &HashMap<String, String>) -> bool {
+        if attr_qname.local_name == "type" && scope.values().any(|ns| ns == XSI_NAMESPACE) {
             return true;
         }
-        for particle in &group.particles {
-            if let GroupParticle::Group(nested) = particle {
-                if Self::has_group_refs(nested) {
-                    return true;
-                }
+        let Some(ElementType::Complex(ct)) = decl.map(|decl| &decl.element_type) else {
+            return false;
+        };
+        ct.attributes
+            .iter_attributes()
+            .find(|attr| attr.name().local_name == attr_qname.local_name)
+            .and_then(|attr| attr.simple_type())
+            .and_then(|simple_type| simple_type.primitive_name())
+            .is_some_and(|name| name == "QName")
+    }
+
+    /// Validate an XML fragment against a named type
+    ///
+    /// Unlike [`validate`](Self::validate), the element does not have to
+    /// match a global element declaration: its attributes and content are
+    /// validated directly against the named simple or complex type. This is
+    /// useful for fragments whose type is known from context, such as the
+    /// body of a REST request.
+    pub fn validate_partial(&self, element: &Element, type_qname: &QName) -> ValidationResult {
+        let element_type = match self.lookup_type(type_qname) {
+            Some(GlobalType::Simple(simple_type)) => ElementType::Simple(Arc::clone(simple_type)),
+            Some(GlobalType::Complex(complex_type)) => ElementType::Complex(Arc::clone(complex_type)),
+            None => {
+                return ValidationResult::invalid(vec![format!(
+                    "Unknown type '{}'",
+                    type_qname.to_string()
+                )])
             }
-        }
-        false
+        };
+        let decl = XsdElement::new(element.qname.clone(), element_type);
+
+        let mut context = ValidationContext::new().with_mode(self.validation);
+        let outcome = validate_element(self, element, &decl, &mut context);
+        Self::validation_result(&context, outcome)
     }
 
-    /// Recursively resolve group references in a group
-    fn resolve_group_ref_recursive(&self, group: &super::groups::XsdGroup) -> Option<super::groups::XsdGroup> {
-        use std::collections::HashSet;
-        let mut visited = HashSet::new();
-        // Pass context about the current group being processed for self-reference detection
-        self.resolve_group_ref_with_context(group, group.name.as_ref(), group.redefine.as_ref(), &mut visited)
+    /// Collect the errors of a finished validation run into a result
+    fn validation_result(context: &ValidationContext, outcome: Result<()>) -> ValidationResult {
+        let mut errors = context.errors.clone();
+        match outcome {
+            Ok(()) if errors.is_empty() => ValidationResult::valid(),
+            Ok(()) => ValidationResult::with_errors(errors),
+            Err(Error::Validation(e)) => {
+                errors.push(context.raised.clone().unwrap_or_else(|| e.into()));
+                ValidationResult::with_errors(errors)
+            }
+            Err(e) => {
+                errors.push(exceptions::ValidationError::new(e.to_string()));
+                ValidationResult::with_errors(errors)
+            }
+        }
     }
 
-    /// Recursively resolve group references with context about the owning group
-    fn resolve_group_ref_with_context(
+    /// Validate an XML document, then apply custom validation rules
+    ///
+    /// Schema validation runs first; each custom validator is then called
+    /// for every element of the document, in document order, with the
+    /// declared type of the element when the schema knows it. Messages from
+    /// the custom validators are appended to the schema validation errors.
+    pub fn validate_with_extensions(
         &self,
-        group: &super::groups::XsdGroup,
-        owner_name: Option<&QName>,
-        owner_redefine: Option<&Arc<super::groups::XsdGroup>>,
-        visited: &mut std::collections::HashSet<QName>,
-    ) -> Option<super::groups::XsdGroup> {
-        use super::groups::{GroupParticle, XsdGroup};
+        doc: &Document,
+        extensions: &[Box<dyn CustomValidator>],
+    ) -> ValidationResult {
+        let mut result = self.validate(doc);
+
+        if let (Some(root), false) = (&doc.root, extensions.is_empty()) {
+            let mut context = ValidationContext::new().with_mode(self.validation);
+            let decl = self.check_document_element(doc).ok().cloned();
+            let mut errors = Vec::new();
+            let path = format!("/{}", root.local_name());
+            self.apply_custom_validators(root, decl.as_deref(), &path, extensions, &mut context, &mut errors);
+
+            if !errors.is_empty() {
+                result.valid = false;
+                result.errors.extend(errors);
+            }
+        }
+        result
+    }
 
-        // If this group is a reference, resolve it
-        if let Some(ref ref_name) = group.group_ref {
-            // IMPORTANT: Check for self-referential redefinitions BEFORE checking visited set
-            // This allows xs:redefine patterns where a group references itself
-            // In that case, we substitute the original (pre-redefine) content
-            if let (Some(owner), Some(original)) = (owner_name, owner_redefine) {
-                if ref_name == owner {
-                    // Self-reference in redefinition - use the original
-                    let mut resolved = (**original).clone();
-                    resolved.occurs = group.occurs;
-                    resolved.group_ref = None;
-                    // Recursively resolve any nested references in the original
-                    // (but not as a self-reference since we're now in the original)
-                    // Don't add to visited - the original is different from the redefined version
-                    if let Some(nested_resolved) = self.resolve_group_ref_with_context(&resolved, None, None, visited) {
-                        return Some(nested_resolved);
-                    }
-                    return Some(resolved);
+    /// Run custom validators on an element and its descendants
+    fn apply_custom_validators(
+        &self,
+        element: &Element,
+        decl: Option<&XsdElement>,
+        path: &str,
+        extensions: &[Box<dyn CustomValidator>],
+        context: &mut ValidationContext,
+        errors: &mut Vec<exceptions::ValidationError>,
+    ) {
+        let schema_type = decl.and_then(|decl| decl.effective_type(self));
+        for extension in extensions {
+            for message in extension.validate_element(element, schema_type.as_ref(), context) {
+                let mut error = exceptions::ValidationError::new(message).with_path(path);
+                if let Some(decl) = decl {
+                    error = error.with_schema_component(decl.name.to_string());
                 }
+                errors.push(error);
             }
+        }
 
-            // Check if we've already visited this group to prevent infinite recursion
-            // (but only for non-self-references)
-            if visited.contains(ref_name) {
-                // Already visited - just return the group as-is without further resolution
-                let mut resolved = group.clone();
-                resolved.group_ref = None;
-                return Some(resolved);
-            }
+        let parent_type = schema_type.as_ref().and_then(|t| t.as_complex()).map(|ct| ct.as_ref());
+        context.enter_level();
+        for child in &element.children {
+            let child_decl = find_child_declaration(self, child, parent_type);
+            let child_path = format!("{}/{}", path, child.local_name());
+            self.apply_custom_validators(child, child_decl.as_deref(), &child_path, extensions, context, errors);
+        }
+        context.exit_level();
+    }
 
-            // Mark as visited before recursing
-            visited.insert(ref_name.clone());
+    /// Add the default values the schema declares but a document omits
+    ///
+    /// This is the default value augmentation of the post-schema-validation
+    /// infoset. Every element with a known declaration gets the attributes
+    /// of its type that have a default or fixed value but are absent, and an
+    /// element declared with a default or fixed value that is present but
+    /// empty gets that value as its text. Absent elements are not added, as
+    /// XSD only applies element defaults to empty elements.
+    ///
+    /// The document should be validated first. Returns the number of
+    /// values added.
+    pub fn apply_defaults(&self, doc: &mut Document) -> usize {
+        let decl = self.check_document_element(doc).ok().cloned();
+        match (doc.root_mut(), decl) {
+            (Some(root), Some(decl)) => self.apply_element_defaults(root, &decl),
+            _ => 0,
+        }
+    }
 
-            // Otherwise look up in global maps
-            if let Some(referenced_group) = self.maps.global_maps.groups.get(ref_name) {
-                // Create a new group with the referenced group's content
-                // Dereference twice: &Arc<XsdGroup> -> Arc<XsdGroup> -> XsdGroup
-                let mut resolved = (**referenced_group).clone();
-                resolved.occurs = group.occurs;
-                resolved.group_ref = None;
-                // Recursively resolve any nested references
-                // IMPORTANT: Switch to the referenced group's context, not the outer context
-                // This prevents infinite recursion when the referenced group itself has a redefine
-                let ref_owner = referenced_group.name.as_ref();
-                let ref_redefine = referenced_group.redefine.as_ref();
-                if let Some(nested_resolved) = self.resolve_group_ref_with_context(&resolved, ref_owner, ref_redefine, visited) {
-                    return Some(nested_resolved);
+    /// Add default values to an element and its descendants
+    fn apply_element_defaults(&self, element: &mut Element, decl: &XsdElement) -> usize {
+        use super::base::AttributeValidator;
+
+        let mut count = 0;
+        let schema_type = decl.effective_type(self);
+        let complex_type = schema_type.as_ref().and_then(|t| t.as_complex());
+
+        if let Some(ct) = complex_type {
+            for attr in ct.attributes.iter_attributes() {
+                if attr.is_prohibited() || element.get_attribute(&attr.name().local_name).is_some() {
+                    continue;
+                }
+                if let Some(value) = attr.fixed_value().or(attr.default()) {
+                    element.attributes.insert(attr.name().clone(), value.to_string());
+                    count += 1;
                 }
-                return Some(resolved);
             }
-            // Reference not found - return None
-            return None;
         }
 
-        // Not a reference - resolve any nested group references
-        let mut modified = false;
-        let mut new_particles = Vec::new();
+        let is_nil = element.attributes.iter().any(|(name, value)| {
+            name.namespace.as_deref() == Some(XSI_NAMESPACE)
+                && name.local_name == "nil"
+                && (value == "true" || value == "1")
+        });
+        if element.text.is_none() && element.children.is_empty() && !is_nil {
+            if let Some(value) = decl.fixed.as_ref().or(decl.default.as_ref()) {
+                element.text = Some(value.clone());
+                count += 1;
+            }
+        }
 
-        for particle in &group.particles {
-            match particle {
-                GroupParticle::Group(nested) => {
-                    if let Some(resolved_nested) = self.resolve_group_ref_with_context(nested, owner_name, owner_redefine, visited) {
-                        new_particles.push(GroupParticle::Group(Arc::new(resolved_nested)));
-                        modified = true;
-                    } else {
-                        new_particles.push(particle.clone());
-                    }
-                }
-                _ => new_particles.push(particle.clone()),
+        let parent_type = complex_type.map(|ct| ct.as_ref());
+        for child in &mut element.children {
+            if let Some(child_decl) = find_child_declaration(self, child, parent_type) {
+                count += self.apply_element_defaults(child, &child_decl);
             }
         }
+        count
+    }
 
-        if modified {
-            let mut new_group = group.clone();
-            new_group.particles = new_particles;
-            Some(new_group)
-        } else {
-            None
+    /// Find the global complex types that use an attribute
+    ///
+    /// A type uses an attribute when the attribute is among its attribute
+    /// uses, whether declared directly, through an attribute group or
+    /// inherited from its base type. Prohibited attributes are not uses.
+    /// Once the schema is built the result comes from a reverse index, which
+    /// reflects the schema as it was when built.
+    pub fn find_types_using_attribute(&self, attr_qname: &QName) -> Vec<&QName> {
+        match &self.usage_index {
+            Some(index) => index.types_by_attribute.get(attr_qname).map(|types| types.iter().collect()).unwrap_or_default(),
+            None => self
+                .attribute_uses()
+                .into_iter()
+                .filter(|(attr, _)| *attr == attr_qname)
+                .map(|(_, type_qname)| type_qname)
+                .collect(),
         }
     }
 
-    /// Resolve complex type derivations (extension/restriction)
+    /// Get the names of the global complex types that use an attribute
     ///
-    /// This is called during the build phase to merge base type content with derived types.
-    fn resolve_complex_type_derivations(&mut self) {
-        use super::complex_types::{ComplexContent, DerivationMethod};
-        use super::groups::{GroupParticle, ModelType, XsdGroup};
+    /// See [`find_types_using_attribute`](Self::find_types_using_attribute).
+    pub fn get_attribute_use_context(&self, attr_qname: &QName) -> Vec<QName> {
+        self.find_types_using_attribute(attr_qname).into_iter().cloned().collect()
+    }
 
-        // Collect types that need derivation resolution
-        let types_to_update: Vec<_> = self.maps.global_maps.types.iter()
-            .filter_map(|(qname, global_type)| {
-                if let GlobalType::Complex(ct) = global_type {
-                    if let Some(ref base_type_name) = ct.base_type {
-                        if let Some(derivation) = ct.derivation {
-                            // For self-referential redefinitions, use the original from redefine field
-                            if base_type_name == qname && ct.redefine.is_some() {
-                                let original = ct.redefine.as_ref().unwrap();
-                                return Some((qname.clone(), Arc::clone(ct), Arc::clone(original), derivation));
-                            }
-                            // Otherwise look up base type in global maps
-                            if let Some(GlobalType::Complex(base_ct)) = self.maps.global_maps.types.get(base_type_name) {
-                                return Some((qname.clone(), Arc::clone(ct), Arc::clone(base_ct), derivation));
-                            }
-                        }
-                    }
-                }
-                None
+    /// Find the element declarations that have a named type
+    ///
+    /// Both global elements and the local elements of global components are
+    /// considered; a name is listed once even if several local declarations
+    /// share it. Like [`find_types_using_attribute`](Self::find_types_using_attribute),
+    /// the result comes from a reverse index once the schema is built.
+    pub fn find_elements_using_type(&self, type_qname: &QName) -> Vec<&QName> {
+        match &self.usage_index {
+            Some(index) => index.elements_by_type.get(type_qname).map(|elements| elements.iter().collect()).unwrap_or_default(),
+            None => self
+                .type_uses()
+                .into_iter()
+                .filter(|(used_type, _)| *used_type == type_qname)
+                .map(|(_, element)| element)
+                .collect(),
+        }
+    }
+
+    /// Find the global elements whose type is a given type
+    ///
+    /// An element matches when its type is the given type or an alias of it,
+    /// i.e. a global simple type restricting it without facets. Elements of a
+    /// substitution group declared without a type have the type of their
+    /// head. Unlike [`find_elements_using_type`](Self::find_elements_using_type)
+    /// local elements are not considered.
+    pub fn find_elements_by_type(&self, type_qname: &QName) -> Vec<QName> {
+        let aliases = self.type_aliases(type_qname);
+        let mut names: Vec<QName> = self
+            .elements()
+            .filter(|(_, element)| {
+                self.element_type_qname(element)
+                    .is_some_and(|type_name| aliases.contains(&type_name))
             })
+            .map(|(name, _)| name.clone())
             .collect();
+        names.sort_by(qname_order);
+        names
+    }
 
-        // Resolve each derived type
-        for (qname, derived_ct, base_ct, derivation) in types_to_update {
-            let mut new_ct = (*derived_ct).clone();
+    /// Find the global attributes whose type is a given type
+    ///
+    /// Aliases of the type match as in [`find_elements_by_type`](Self::find_elements_by_type).
+    pub fn find_attributes_by_type(&self, type_qname: &QName) -> Vec<QName> {
+        let aliases = self.type_aliases(type_qname);
+        let mut names: Vec<QName> = self
+            .attributes()
+            .filter(|(_, attribute)| {
+                let type_name = attribute
+                    .type_name
+                    .as_ref()
+                    .or_else(|| attribute.simple_type().and_then(|st| st.name()));
+                type_name.is_some_and(|type_name| aliases.contains(type_name))
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort_by(qname_order);
+        names
+    }
 
-            match derivation {
-                DerivationMethod::Extension => {
-                    // For extension: create a sequence containing base content + extension content
-                    if let (ComplexContent::Group(base_group), ComplexContent::Group(ext_group)) =
-                        (&base_ct.content, &derived_ct.content)
-                    {
-                        // If base type is empty, just use extension's content
-                        if base_group.is_empty() {
-                            // Extension content stays as-is
-                        } else if ext_group.is_empty() {
-                            // No new content, just inherit base
-                            new_ct.content = ComplexContent::Group(Arc::clone(base_group));
-                        } else {
-                            // Both have content - create wrapper sequence
-                            let mut wrapper = XsdGroup::new(ModelType::Sequence);
-                            wrapper.particles.push(GroupParticle::Group(Arc::clone(base_group)));
-                            wrapper.particles.push(GroupParticle::Group(Arc::clone(ext_group)));
-                            new_ct.content = ComplexContent::Group(Arc::new(wrapper));
-                        }
-                    }
+    /// Get a type and the global simple types restricting it without facets,
+    /// directly or through other aliases
+    fn type_aliases(&self, type_qname: &QName) -> HashSet<QName> {
+        let mut aliases = HashSet::from([type_qname.clone()]);
+        loop {
+            let found: Vec<QName> = self
+                .types()
+                .filter(|(name, global_type)| {
+                    !aliases.contains(*name)
+                        && matches!(global_type, GlobalType::Simple(st) if is_alias_type(st.as_ref()))
+                        && self.base_type_name(name).is_some_and(|base| aliases.contains(&base))
+                })
+                .map(|(name, _)| name.clone())
+                .collect();
+            if found.is_empty() {
+                return aliases;
+            }
+            aliases.extend(found);
+        }
+    }
 
-                    // Inherit mixed from base if not explicitly set
-                    if !new_ct.mixed && base_ct.mixed {
-                        new_ct.mixed = base_ct.mixed;
-                    }
-
-                    // Inherit attributes from base type
-                    for attr in base_ct.attributes.iter_attributes() {
-                        // Only add if not already defined (extension can override)
-                        if new_ct.attributes.get_attribute(attr.name()).is_none() {
-                            let _ = new_ct.attributes.add_attribute(Arc::clone(attr));
-                        }
-                    }
-                }
-                DerivationMethod::Restriction => {
-                    // For restriction: the derived content model already replaces base
-                    // If derived content is empty, inherit from base
-                    if let ComplexContent::Group(ref ext_group) = new_ct.content {
-                        if ext_group.is_empty() {
-                            if let ComplexContent::Group(base_group) = &base_ct.content {
-                                new_ct.content = ComplexContent::Group(Arc::clone(base_group));
-                            }
-                        }
-                    }
-
-                    // Inherit mixed from base if not explicitly set
-                    if !new_ct.mixed {
-                        new_ct.mixed = base_ct.mixed;
-                    }
-
-                    // For restriction, inherit base attributes (derived type can narrow them)
-                    for attr in base_ct.attributes.iter_attributes() {
-                        if new_ct.attributes.get_attribute(attr.name()).is_none() {
-                            let _ = new_ct.attributes.add_attribute(Arc::clone(attr));
-                        }
+    /// Get the name of the type of a global element, following the heads
+    /// of the substitution group of an element declared without a type
+    fn element_type_qname<'s>(&'s self, element: &'s XsdElement) -> Option<QName> {
+        let mut element = element;
+        let mut visited = HashSet::new();
+        loop {
+            match (&element.type_name, &element.element_type) {
+                (Some(type_name), _) => return Some(type_name.clone()),
+                // Built-in types are attached without a type name
+                (None, ElementType::Simple(st)) => return simple_type_qname(st.as_ref()),
+                (None, ElementType::Complex(ct)) => return ct.name.clone(),
+                (None, ElementType::Any) => {
+                    let head = element.substitution_group.as_ref()?;
+                    if !visited.insert(head.clone()) {
+                        return None;
                     }
+                    element = self.lookup_element(head)?.as_ref();
                 }
             }
+        }
+    }
 
-            // Update the type in the global map
-            self.maps.global_maps.types.insert(qname, GlobalType::Complex(Arc::new(new_ct)));
+    /// Get the ancestors of a type, from its base type up to `xs:anyType`
+    ///
+    /// Complex types follow their base type, `xs:anyType` when they have
+    /// none; simple types follow their base type up to `xs:anySimpleType`.
+    /// Built-in atomic types go through `xs:anyAtomicType`. An unknown type
+    /// has no ancestors.
+    pub fn type_ancestors(&self, qname: &QName) -> Vec<QName> {
+        let mut ancestors: Vec<QName> = Vec::new();
+        let mut current = qname.clone();
+        while let Some(base) = self.base_type_name(&current) {
+            if base == *qname || ancestors.contains(&base) {
+                break;
+            }
+            ancestors.push(base.clone());
+            current = base;
         }
+        ancestors
     }
 
-    /// Resolve derivations for inline complex types in elements
+    /// Check if a type is derived from another, directly or indirectly
     ///
-    /// Elements can have inline anonymous complex types that use extension/restriction.
-    /// These need to be resolved separately since they're not in global_maps.types.
-    fn resolve_inline_element_type_derivations(&mut self) {
-        use super::complex_types::{ComplexContent, DerivationMethod};
-        use super::elements::ElementType;
-        use super::groups::{GroupParticle, ModelType, XsdGroup};
+    /// A type is considered derived from itself.
+    pub fn is_derived_from(&self, child: &QName, ancestor: &QName) -> bool {
+        child == ancestor || self.type_ancestors(child).contains(ancestor)
+    }
 
-        // Collect elements that have inline complex types needing derivation resolution
-        let elements_to_update: Vec<_> = self.maps.global_maps.elements.iter()
-            .filter_map(|(elem_qname, elem)| {
-                if let ElementType::Complex(ct) = &elem.element_type {
-                    // Only process inline types (anonymous - no name)
-                    // Named types are handled in resolve_complex_type_derivations
-                    if ct.name.is_none() {
-                        if let Some(ref base_type_name) = ct.base_type {
-                            if let Some(derivation) = ct.derivation {
-                                // Look up base type in global maps
-                                if let Some(GlobalType::Complex(base_ct)) = self.maps.global_maps.types.get(base_type_name) {
-                                    return Some((elem_qname.clone(), Arc::clone(ct), Arc::clone(base_ct), derivation));
-                                }
-                            }
-                        }
-                    }
+    /// Get the name of the base type of a type
+    fn base_type_name(&self, qname: &QName) -> Option<QName> {
+        let xsd_type = |name: &str| QName::namespaced(XSD_NAMESPACE, name);
+        if qname.namespace.as_deref() == Some(XSD_NAMESPACE) {
+            if qname.local_name == XSD_ANY_ATOMIC_TYPE {
+                return Some(xsd_type(XSD_ANY_SIMPLE_TYPE));
+            }
+            if let Some(builtin) = get_builtin_type(&qname.local_name) {
+                if builtin.category == TypeCategory::Primitive {
+                    return Some(xsd_type(XSD_ANY_ATOMIC_TYPE));
                 }
-                None
-            })
-            .collect();
-
-        // Resolve each element's inline type
-        for (elem_qname, derived_ct, base_ct, derivation) in elements_to_update {
-            let mut new_ct = (*derived_ct).clone();
-
-            match derivation {
-                DerivationMethod::Extension => {
-                    // For extension: inherit base content
-                    if let (ComplexContent::Group(base_group), ComplexContent::Group(ext_group)) =
-                        (&base_ct.content, &derived_ct.content)
-                    {
-                        if base_group.is_empty() {
-                            // Extension content stays as-is
-                        } else if ext_group.is_empty() {
-                            // No new content, just inherit base
-                            new_ct.content = ComplexContent::Group(Arc::clone(base_group));
-                        } else {
-                            // Both have content - create wrapper sequence
-                            let mut wrapper = XsdGroup::new(ModelType::Sequence);
-                            wrapper.particles.push(GroupParticle::Group(Arc::clone(base_group)));
-                            wrapper.particles.push(GroupParticle::Group(Arc::clone(ext_group)));
-                            new_ct.content = ComplexContent::Group(Arc::new(wrapper));
-                        }
-                    }
+                return builtin.base_type.map(xsd_type);
+            }
+        }
+        match self.lookup_type(qname)? {
+            GlobalType::Complex(complex_type) => {
+                Some(complex_type.base_type.clone().unwrap_or_else(|| xsd_type(XSD_ANY_TYPE)))
+            }
+            GlobalType::Simple(simple_type) => Some(
+                SimpleType::base_type(simple_type.as_ref())
+                    .and_then(|base_type| {
+                        // Built-in types are only known by their local name
+                        base_type.name().cloned().or_else(|| base_type.primitive_name().map(xsd_type))
+                    })
+                    .unwrap_or_else(|| xsd_type(XSD_ANY_SIMPLE_TYPE)),
+            ),
+        }
+    }
 
-                    // Inherit mixed from base if not explicitly set
-                    if !new_ct.mixed && base_ct.mixed {
-                        new_ct.mixed = base_ct.mixed;
-                    }
+    /// Collect (attribute, type) pairs for the attribute uses of the global complex types
+    fn attribute_uses(&self) -> Vec<(&QName, &QName)> {
+        let mut uses = Vec::new();
+        for (type_qname, global_type) in self.maps.global_maps.types.iter() {
+            let GlobalType::Complex(ct) = global_type else {
+                continue;
+            };
+            for attr in ct.attributes.iter_attributes() {
+                if !attr.is_prohibited() {
+                    uses.push((attr.name(), type_qname));
+                }
+            }
+        }
+        uses.sort_by(|a, b| qname_order(a.1, b.1).then_with(|| qname_order(a.0, b.0)));
+        uses.dedup();
+        uses
+    }
 
-                    // Inherit attributes from base type
-                    for attr in base_ct.attributes.iter_attributes() {
-                        if new_ct.attributes.get_attribute(attr.name()).is_none() {
-                            let _ = new_ct.attributes.add_attribute(Arc::clone(attr));
-                        }
-                    }
+    /// Collect (type, element) pairs for the element declarations with a named type
+    fn type_uses(&self) -> Vec<(&QName, &QName)> {
+        let mut uses = Vec::new();
+        for element in self.maps.global_maps.elements.values() {
+            self.collect_type_uses(element, 0, &mut uses);
+        }
+        for global_type in self.maps.global_maps.types.values() {
+            if let GlobalType::Complex(ct) = global_type {
+                if let Some(group) = ct.model_group() {
+                    self.collect_group_type_uses(group, 0, &mut uses);
                 }
-                DerivationMethod::Restriction => {
-                    // For restriction: the derived content model already replaces base
-                    if let ComplexContent::Group(ref ext_group) = new_ct.content {
-                        if ext_group.is_empty() {
-                            if let ComplexContent::Group(base_group) = &base_ct.content {
-                                new_ct.content = ComplexContent::Group(Arc::clone(base_group));
-                            }
-                        }
-                    }
+            }
+        }
+        uses.sort_by(|a, b| qname_order(a.1, b.1).then_with(|| qname_order(a.0, b.0)));
+        uses.dedup();
+        uses
+    }
 
-                    // Inherit mixed from base if not explicitly set
-                    if !new_ct.mixed {
-                        new_ct.mixed = base_ct.mixed;
+    fn collect_type_uses<'s>(&'s self, element: &'s XsdElement, depth: usize, uses: &mut Vec<(&'s QName, &'s QName)>) {
+        match (&element.type_name, &element.element_type) {
+            (Some(type_name), _) => uses.push((type_name, &element.name)),
+            // Built-in types are attached without a type name
+            (None, ElementType::Simple(st)) => {
+                let type_name = match (st.name(), st.is_builtin()) {
+                    (Some(type_name), _) => Some(type_name),
+                    (None, true) => st.primitive_name().and_then(builtin_type_qname),
+                    (None, false) => None,
+                };
+                if let Some(type_name) = type_name {
+                    uses.push((type_name, &element.name));
+                }
+            }
+            (None, ElementType::Complex(ct)) => match &ct.name {
+                Some(type_name) => uses.push((type_name, &element.name)),
+                // Anonymous types are walked for their local elements
+                None => {
+                    if let Some(group) = ct.model_group() {
+                        self.collect_group_type_uses(group, depth + 1, uses);
                     }
+                }
+            },
+            (None, ElementType::Any) => {}
+        }
+    }
 
-                    // For restriction, inherit base attributes
-                    for attr in base_ct.attributes.iter_attributes() {
-                        if new_ct.attributes.get_attribute(attr.name()).is_none() {
-                            let _ = new_ct.attributes.add_attribute(Arc::clone(attr));
-                        }
+    fn collect_group_type_uses<'s>(&'s self, group: &'s XsdGroup, depth: usize, uses: &mut Vec<(&'s QName, &'s QName)>) {
+        const MAX_GROUP_DEPTH: usize = 32;
+        if depth > MAX_GROUP_DEPTH {
+            return;
+        }
+        for particle in &group.particles {
+            match particle {
+                GroupParticle::Element(ep) if ep.element_ref.is_none() => {
+                    if let Some(element) = &ep.element_decl {
+                        self.collect_type_uses(element, depth + 1, uses);
                     }
                 }
+                GroupParticle::Group(nested) => self.collect_group_type_uses(nested, depth + 1, uses),
+                _ => {}
             }
+        }
+    }
 
-            // Update the element with the resolved type
-            if let Some(elem) = self.maps.global_maps.elements.get(&elem_qname) {
-                let mut new_elem = (**elem).clone();
-                new_elem.element_type = ElementType::Complex(Arc::new(new_ct));
-                self.maps.global_maps.elements.insert(elem_qname, Arc::new(new_elem));
+    /// Build the reverse index of attribute and type uses
+    fn build_usage_index(&self) -> UsageIndex {
+        let mut index = UsageIndex::default();
+        for (attr, type_qname) in self.attribute_uses() {
+            index.types_by_attribute.entry(attr.clone()).or_default().push(type_qname.clone());
+        }
+        for (type_qname, element) in self.type_uses() {
+            let elements = index.elements_by_type.entry(type_qname.clone()).or_default();
+            if !elements.contains(element) {
+                elements.push(element.clone());
             }
         }
+        index
     }
 
-    /// Resolve attribute group references
+    /// Get the required child elements and attributes of a global element
     ///
-    /// This resolves pending attribute group references in:
-    /// 1. Global attribute groups that reference other attribute groups
-    /// 2. Complex types whose attribute groups reference other attribute groups
+    /// A child element is required when its effective `minOccurs` is greater
+    /// than zero: occurrence bounds are multiplied through nested groups,
+    /// and a choice between several branches makes each branch optional.
+    /// Returns `None` if the element is not declared.
+    pub fn required_content(&self, qname: &QName) -> Option<RequiredContent> {
+        let element = self.lookup_element(qname)?;
+        Some(self.element_content_parts(element).0)
+    }
+
+    /// Get the optional child elements and attributes of a global element
     ///
-    /// The resolution runs in a loop until no more changes are made, ensuring
-    /// all transitive references are resolved regardless of HashMap iteration order.
-    fn resolve_attribute_group_references(&mut self) {
-        // First, resolve references in global attribute groups
-        // Loop until no more changes are made (handles transitive references and non-deterministic iteration)
-        let max_iterations = 100; // Prevent infinite loops
-        for iteration in 0..max_iterations {
-            let groups_to_update: Vec<_> = self.maps.global_maps.attribute_groups.iter()
-                .filter_map(|(qname, group)| {
-                    if group.has_pending_refs() {
-                        Some((qname.clone(), (**group).clone()))
-                    } else {
-                        None
-                    }
-                })
-                .collect();
+    /// This is the complement of [`XsdSchema::required_content`]; prohibited
+    /// attributes are left out. Returns `None` if the element is not
+    /// declared.
+    pub fn optional_content(&self, qname: &QName) -> Option<OptionalContent> {
+        let element = self.lookup_element(qname)?;
+        Some(self.element_content_parts(element).1)
+    }
 
-            if groups_to_update.is_empty() {
-                break;
-            }
+    /// Split the content of an element declaration into required and optional parts
+    fn element_content_parts(&self, element: &XsdElement) -> (RequiredContent, OptionalContent) {
+        match element.effective_type(self) {
+            Some(GlobalType::Complex(ct)) => self.content_parts(&ct),
+            _ => Default::default(),
+        }
+    }
 
-            if iteration == max_iterations - 1 {
-                eprintln!("Warning: Attribute group resolution reached max iterations");
+    /// Split the content of a complex type into required and optional parts
+    pub(crate) fn content_parts(&self, complex_type: &XsdComplexType) -> (RequiredContent, OptionalContent) {
+        let mut required = RequiredContent::default();
+        let mut optional = OptionalContent::default();
+
+        let mut attributes: Vec<_> = complex_type.attributes.iter_attributes().collect();
+        attributes.sort_by(|a, b| qname_order(a.name(), b.name()));
+        for attr in attributes {
+            let entry = (attr.name().clone(), attr.use_mode());
+            match attr.use_mode() {
+                AttributeUse::Required => required.required_attributes.push(entry),
+                AttributeUse::Optional => optional.optional_attributes.push(entry),
+                AttributeUse::Prohibited => {}
             }
+        }
 
-            for (qname, mut group) in groups_to_update {
-                // Get the redefine reference for self-reference detection
-                let redefine_ref = group.redefine.clone();
-
-                for ref_qname in group.pending_group_refs().to_vec() {
-                    // For self-referential redefinitions, use the original from redefine field
-                    if ref_qname == qname {
-                        if let Some(ref original) = redefine_ref {
-                            // Self-reference in redefinition - use the original
-                            for attr in original.iter_attributes() {
-                                let _ = group.add_attribute(Arc::clone(attr));
-                            }
-                            continue;
-                        }
-                    }
-                    // Otherwise look up in global maps
-                    if let Some(referenced_group) = self.maps.global_maps.attribute_groups.get(&ref_qname) {
-                        // Only add attributes if the referenced group has been fully resolved
-                        // (no pending refs), otherwise we'll get them in a later iteration
-                        if !referenced_group.has_pending_refs() {
-                            for attr in referenced_group.iter_attributes() {
-                                let _ = group.add_attribute(Arc::clone(attr));
-                            }
-                        }
-                    }
+        if let ComplexContent::Group(group) = &complex_type.content {
+            for (name, occurs) in self.element_occurs(group) {
+                if occurs.min > 0 {
+                    required.required_elements.push((name, occurs));
+                } else {
+                    optional.optional_elements.push((name, occurs));
                 }
+            }
+        }
 
-                // Only clear pending refs if all referenced groups have been resolved
-                let all_resolved = group.pending_group_refs().iter().all(|ref_qname| {
-                    if *ref_qname == qname {
-                        return true; // Self-reference is always handled
-                    }
-                    self.maps.global_maps.attribute_groups
-                        .get(ref_qname)
-                        .map_or(true, |g| !g.has_pending_refs())
-                });
+        (required, optional)
+    }
 
-                if all_resolved {
-                    group.clear_pending_refs();
-                }
+    /// Get the effective occurrence bounds of the elements of a model group, in document order
+    ///
+    /// Elements in a choice are optional; see [`required_content`](Self::required_content).
+    pub(crate) fn element_occurs(&self, group: &XsdGroup) -> Vec<(QName, Occurs)> {
+        let mut elements = Vec::new();
+        self.collect_element_occurs(group, group.occurs, 0, &mut elements);
+        elements
+    }
 
-                self.maps.global_maps.attribute_groups.insert(qname, Arc::new(group));
-            }
+    /// Find the first particle of a named element in a model group
+    ///
+    /// Nested groups and group references are searched depth first.
+    pub(crate) fn find_element_particle<'a>(&'a self, group: &'a XsdGroup, name: &QName) -> Option<&'a ElementParticle> {
+        self.find_element_particle_at(group, name, 0)
+    }
+
+    fn find_element_particle_at<'a>(&'a self, group: &'a XsdGroup, name: &QName, depth: usize) -> Option<&'a ElementParticle> {
+        const MAX_GROUP_DEPTH: usize = 32;
+        if depth > MAX_GROUP_DEPTH {
+            return None;
+        }
+        if group.particles.is_empty() {
+            let referenced = group.group_ref.as_ref().and_then(|group_ref| self.lookup_group(group_ref))?;
+            return self.find_element_particle_at(referenced, name, depth + 1);
         }
+        group.particles.iter().find_map(|particle| match particle {
+            GroupParticle::Element(ep) if &ep.name == name => Some(ep.as_ref()),
+            GroupParticle::Group(nested) => self.find_element_particle_at(nested, name, depth + 1),
+            _ => None,
+        })
+    }
 
-        // Then, resolve references in complex types' attribute groups
-        let types_to_update: Vec<_> = self.maps.global_maps.types.iter()
-            .filter_map(|(qname, global_type)| {
-                if let GlobalType::Complex(ct) = global_type {
-                    if ct.attributes.has_pending_refs() {
-                        return Some((qname.clone(), Arc::clone(ct)));
-                    }
-                }
-                None
-            })
-            .collect();
+    /// Collect the effective occurrence bounds of the elements of a model group
+    ///
+    /// `group_occurs` is the effective occurrence of the group itself.
+    /// Elements appearing in several places have their bounds added up.
+    fn collect_element_occurs(&self, group: &XsdGroup, group_occurs: Occurs, depth: usize, out: &mut Vec<(QName, Occurs)>) {
+        const MAX_GROUP_DEPTH: usize = 32;
+        if depth > MAX_GROUP_DEPTH {
+            return;
+        }
 
-        for (qname, ct) in types_to_update {
-            let mut new_ct = (*ct).clone();
+        if group.particles.is_empty() {
+            if let Some(referenced) = group.group_ref.as_ref().and_then(|name| self.lookup_group(name)) {
+                self.collect_element_occurs(referenced, group_occurs, depth + 1, out);
+            }
+            return;
+        }
 
-            for ref_qname in new_ct.attributes.pending_group_refs().to_vec() {
-                if let Some(referenced_group) = self.maps.global_maps.attribute_groups.get(&ref_qname) {
-                    // Add the referenced attributes to this complex type
-                    for attr in referenced_group.iter_attributes() {
-                        let _ = new_ct.attributes.add_attribute(Arc::clone(attr));
+        let is_choice = group.model == ModelType::Choice && group.particles.len() > 1;
+        for particle in &group.particles {
+            let occurs = particle.occurs();
+            let min = if is_choice { 0 } else { group_occurs.min.saturating_mul(occurs.min) };
+            let max = group_occurs.max.zip(occurs.max).map(|(a, b)| a.saturating_mul(b));
+            let particle_occurs = Occurs::new(min, max);
+            match particle {
+                GroupParticle::Element(ep) => match out.iter_mut().find(|(name, _)| *name == ep.name) {
+                    Some((_, total)) => {
+                        total.min = total.min.saturating_add(particle_occurs.min);
+                        total.max = total.max.zip(particle_occurs.max).map(|(a, b)| a.saturating_add(b));
                     }
+                    None => out.push((ep.name.clone(), particle_occurs)),
+                },
+                GroupParticle::Group(nested) => {
+                    self.collect_element_occurs(nested, particle_occurs, depth + 1, out);
                 }
+                GroupParticle::Any(_) => {}
             }
-            new_ct.attributes.clear_pending_refs();
-
-            self.maps.global_maps.types.insert(qname, GlobalType::Complex(Arc::new(new_ct)));
         }
     }
 
-    /// Validate that redefined components have proper self-references
+    /// Check if an XML document is valid against this schema
     ///
-    /// According to XSD spec:
-    /// - Redefined complex types must derive from themselves
-    /// - Redefined groups must contain exactly one self-reference
-    /// - Redefined attribute groups must contain exactly one self-reference
-    fn validate_redefinitions(&mut self) {
-        // Collect errors first to avoid borrow issues
-        let mut errors = Vec::new();
-
-        // Validate complex types with redefine have self as base type
-        for (qname, global_type) in &self.maps.global_maps.types {
-            if let GlobalType::Complex(ct) = global_type {
-                if ct.redefine.is_some() {
-                    // Check that base_type matches the type's own name
-                    if let Some(ref base) = ct.base_type {
-                        if base != qname {
-                            errors.push(ParseError::new(format!(
-                                "Redefined complex type '{:?}' must derive from itself, but derives from '{:?}'",
-                                qname, base
-                            )));
-                        }
-                    } else {
-                        errors.push(ParseError::new(format!(
-                            "Redefined complex type '{:?}' must derive from itself",
-                            qname
-                        )));
-                    }
-                }
-            }
+    /// This is a convenience method that returns a boolean.
+    /// Use `validate()` if you need detailed error information.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let schema = XsdSchema::parse_file("schema.xsd")?;
+    /// let doc = Document::from_string("<root>...</root>")?;
+    /// if schema.is_valid(&doc) {
+    ///     println!("Document is valid!");
+    /// }
+    /// ```
+    pub fn is_valid(&self, doc: &Document) -> bool {
+        self.validate(doc).valid
+    }
+
+    /// Validate an XML string against this schema
+    ///
+    /// Parses the XML string and validates it.
+    pub fn validate_string(&self, xml: &str) -> ValidationResult {
+        match Document::from_string(xml) {
+            Ok(doc) => self.validate(&doc),
+            Err(e) => ValidationResult::invalid(vec![format!("Failed to parse XML: {}", e)]),
         }
+    }
 
-        // Validate groups with redefine (self-reference already resolved at this point)
-        // Groups that were redefined should have had their self-reference resolved to original content
-        // Just warn if a redefined group appears empty (might indicate missing self-ref)
-        for (qname, group) in &self.maps.global_maps.groups {
-            if group.redefine.is_some() {
-                if group.particles.is_empty() {
-                    errors.push(ParseError::new(format!(
-                        "Redefined group '{:?}' has no content - may be missing self-reference",
-                        qname
-                    )));
+    /// Check if an XML string is valid against this schema
+    pub fn is_valid_string(&self, xml: &str) -> bool {
+        self.validate_string(xml).valid
+    }
+
+    /// Validate an XML string in lax mode, listing the skipped elements
+    ///
+    /// Errors are collected instead of stopping validation. Elements allowed
+    /// by the content model, typically through a wildcard, but without a
+    /// declaration are not validated; their names are listed in
+    /// [`LaxValidationResult::skipped`] rather than reported as errors.
+    pub fn validate_lax(&self, xml: &str) -> LaxValidationResult {
+        let doc = match Document::from_string(xml) {
+            Ok(doc) => doc,
+            Err(e) => {
+                return LaxValidationResult {
+                    result: ValidationResult::invalid(vec![format!("Failed to parse XML: {}", e)]),
+                    skipped: Vec::new(),
                 }
             }
+        };
+        let mut context = ValidationContext::new().with_mode(ValidationMode::Lax);
+        let outcome = validate_document(self, &doc, &mut context);
+        LaxValidationResult {
+            result: Self::validation_result(&context, outcome),
+            skipped: std::mem::take(&mut context.skipped),
         }
+    }
 
-        // Validate attribute groups with redefine
-        for (qname, attr_group) in &self.maps.global_maps.attribute_groups {
-            if attr_group.redefine.is_some() {
-                if attr_group.len() == 0 {
-                    errors.push(ParseError::new(format!(
-                        "Redefined attribute group '{:?}' has no attributes - may be missing self-reference",
-                        qname
-                    )));
-                }
+    /// Check if an XML string is valid against this schema in lax mode
+    ///
+    /// See [`validate_lax`](Self::validate_lax).
+    pub fn is_lax_valid(&self, xml: &str) -> bool {
+        self.validate_lax(xml).result.valid
+    }
+
+    /// Validate an XML document, using its schema location hints when needed
+    ///
+    /// When the root element is neither declared by this schema nor in its
+    /// target namespace, the schema hinted for the root's namespace by
+    /// `xsi:schemaLocation` or `xsi:noNamespaceSchemaLocation` is loaded and
+    /// the document is validated against it. Relative locations are resolved
+    /// against the base URL of this schema. Without a matching hint, or if
+    /// the hinted schema cannot be loaded, the document is validated against
+    /// this schema; a failed load is reported as a warning.
+    pub fn validate_with_hints(&self, doc: &Document) -> ValidationResult {
+        let Some(root) = doc.root() else {
+            return self.validate(doc);
+        };
+        // Element names are parsed unresolved, so look up the default namespace
+        let root_namespace = root.namespace().or(root.namespaces.get_default_namespace());
+        if self.lookup_element(&root.qname).is_some() || root_namespace == self.target_namespace.as_deref() {
+            return self.validate(doc);
+        }
+
+        let hints = doc.schema_location_hints();
+        let Some((_, location)) = hints.iter().find(|(namespace, _)| namespace.as_deref() == root_namespace) else {
+            return self.validate(doc);
+        };
+        let path = resolve_schema_location(location, self.base_url(), self.catalog());
+        match XsdSchema::from_file(&path) {
+            Ok(hinted) => hinted.validate(doc),
+            Err(e) => {
+                let mut result = self.validate(doc);
+                result.add_warning(format!("Failed to load the schema hinted at '{}': {}", location, e));
+                result
             }
         }
+    }
 
-        // Add collected errors
-        for error in errors {
-            self.parse_error(error);
+    /// Validate an XML string, using its schema location hints when needed
+    ///
+    /// See [`XsdSchema::validate_with_hints`].
+    pub fn validate_string_with_hints(&self, xml: &str) -> ValidationResult {
+        match Document::from_string(xml) {
+            Ok(doc) => self.validate_with_hints(&doc),
+            Err(e) => ValidationResult::invalid(vec![format!("Failed to parse XML: {}", e)]),
         }
     }
-}
 
-impl Validator for XsdSchema {
-    fn is_built(&self) -> bool {
-        self.built
+    /// Validate an XML file against this schema
+    ///
+    /// Reads and parses the file, then validates it.
+    pub fn validate_file(&self, path: &Path) -> ValidationResult {
+        match std::fs::read_to_string(path) {
+            Ok(content) => self.validate_string(&content),
+            Err(e) => ValidationResult::invalid(vec![format!(
+                "Failed to read file '{}': {}",
+                path.display(),
+                e
+            )]),
+        }
     }
 
-    fn build(&mut self) -> Result<()> {
-        if self.built {
-            return Ok(());
+    /// Check if an XML file is valid against this schema
+    pub fn is_valid_file(&self, path: &Path) -> bool {
+        self.validate_file(path).valid
+    }
+
+    /// Validate XML files in parallel and merge their results
+    ///
+    /// Each file is validated with [`validate_file`](Self::validate_file) on
+    /// the rayon thread pool. The messages of the errors and warnings are
+    /// prefixed with the path of their file, and the results are merged in
+    /// the order of the paths.
+    #[cfg(feature = "rayon")]
+    pub fn validate_all<'p, I: IntoIterator<Item = &'p Path>>(&self, paths: I) -> ValidationResult {
+        use rayon::prelude::*;
+
+        let paths: Vec<&Path> = paths.into_iter().collect();
+        let results: Vec<ValidationResult> = paths
+            .par_iter()
+            .map(|path| {
+                let mut result = self.validate_file(path);
+                for error in &mut result.errors {
+                    error.message = format!("{}: {}", path.display(), error.message);
+                }
+                for warning in &mut result.warnings {
+                    *warning = format!("{}: {}", path.display(), warning);
+                }
+                result
+            })
+            .collect();
+
+        let mut merged = ValidationResult::valid();
+        for result in results {
+            merged.merge(result);
         }
+        merged
+    }
 
-        // Register built-in types if not already done
-        if self.maps.global_maps.types.is_empty() {
-            self.register_builtins()?;
+    /// Validate an XML file against this schema, reading it asynchronously
+    ///
+    /// The file is read with `tokio::fs`; validation itself runs on the
+    /// calling task.
+    #[cfg(feature = "tokio")]
+    pub async fn validate_file_async(&self, path: &Path) -> ValidationResult {
+        match tokio::fs::read_to_string(path).await {
+            Ok(content) => self.validate_string(&content),
+            Err(e) => ValidationResult::invalid(vec![format!(
+                "Failed to read file '{}': {}",
+                path.display(),
+                e
+            )]),
         }
+    }
 
-        // Resolve complex type derivations (extension/restriction)
-        // This merges base type content into derived types
-        self.resolve_complex_type_derivations();
+    /// Get all validation errors for an XML document
+    ///
+    /// This always runs in lax mode to collect all errors.
+    pub fn iter_errors(&self, doc: &Document) -> Vec<exceptions::ValidationError> {
+        let result = self.validate_with_mode(doc, ValidationMode::Lax);
+        result.errors
+    }
 
-        // Resolve group references in complex types
-        // This resolves <xs:group ref="..."/> to their actual content
-        self.resolve_group_references();
+    /// Iterate over the validation errors of an XML document as they are found
+    ///
+    /// Unlike [`iter_errors`](Self::iter_errors), the document is validated
+    /// lazily: its elements are fed to a lax [`StreamingValidator`] in
+    /// document order, a few at a time, and the errors found are buffered
+    /// until they are consumed. Stopping the iteration stops the validation.
+    /// As with [`validate_reader`](Self::validate_reader), identity
+    /// constraints are not checked.
+    pub fn iter_errors_structured<'a>(
+        &'a self,
+        doc: &'a Document,
+    ) -> impl Iterator<Item = exceptions::ValidationError> + 'a {
+        let mut validator = StreamingValidator::new(self);
+        let mut buffer: VecDeque<exceptions::ValidationError> = VecDeque::new();
+        let mut root = doc.root.as_ref();
+        // Open elements, with the index of their next child
+        let mut stack: Vec<(&Element, usize)> = Vec::new();
+        let mut finished = false;
+
+        std::iter::from_fn(move || loop {
+            if let Some(error) = buffer.pop_front() {
+                return Some(error);
+            }
+            if finished {
+                return None;
+            }
+            let events = if let Some(elem) = root.take() {
+                stack.push((elem, 0));
+                validator.start_tree_element(elem)
+            } else {
+                match stack.last_mut() {
+                    Some((elem, next)) if *next < elem.children.len() => {
+                        let child = &elem.children[*next];
+                        *next += 1;
+                        stack.push((child, 0));
+                        validator.start_tree_element(child)
+                    }
+                    Some((elem, _)) => {
+                        let events = validator.end_tree_element(elem);
+                        stack.pop();
+                        events
+                    }
+                    None => {
+                        finished = true;
+                        validator.finish()
+                    }
+                }
+            };
+            buffer.extend(events.into_iter().filter_map(|event| match event {
+                ValidationEvent::Error(error) => Some(*error),
+                ValidationEvent::Validated { .. } => None,
+            }));
+        })
+    }
 
-        // Resolve derivations for inline element types AFTER group resolution
-        // This ensures the base types have fully resolved content
-        self.resolve_inline_element_type_derivations();
+    /// Validate a document and extract values from it with XPath expressions
+    ///
+    /// `projection` is a list of `(output_key, xpath_expression)` pairs,
+    /// e.g. `[("isbn", "/book/isbn/text()")]`. The document is validated
+    /// first; an invalid document is an error. Each expression is then
+    /// evaluated with [`XPathEvaluator`] and its string values stored under
+    /// the output key: `null` when nothing matches, a string for a single
+    /// match and an array of strings otherwise.
+    pub fn validate_and_project(&self, doc: &Document, projection: &[(&str, &str)]) -> Result<serde_json::Value> {
+        let result = self.validate(doc);
+        if !result.valid {
+            return Err(Error::Validation(
+                ValidationError::new(result.errors.first().map(|e| e.message.clone()).unwrap_or_default())
+                    .with_reason(format!("{} validation error(s)", result.errors.len())),
+            ));
+        }
 
-        // Resolve attribute group references
-        self.resolve_attribute_group_references();
+        let evaluator = XPathEvaluator::new(doc)
+            .ok_or_else(|| Error::Validation(ValidationError::new("Document has no root element")))?;
+        let mut output = serde_json::Map::new();
+        for (key, expression) in projection {
+            let nodes = evaluator.select(expression).map_err(|e| {
+                Error::Value(format!("Invalid XPath expression '{}' for '{}': {}", expression, key, e))
+            })?;
+            let mut values: Vec<serde_json::Value> =
+                nodes.iter().map(|node| serde_json::Value::String(node.string_value())).collect();
+            let value = match values.len() {
+                0 => serde_json::Value::Null,
+                1 => values.remove(0),
+                _ => serde_json::Value::Array(values),
+            };
+            output.insert(key.to_string(), value);
+        }
 
-        // Resolve type references in global elements
-        self.resolve_element_types();
+        Ok(serde_json::Value::Object(output))
+    }
 
-        // Resolve element types in complex type content models (forward references)
-        self.resolve_element_particle_types();
+    /// Rewrite the simple-typed values of a document in canonical form
+    ///
+    /// The document is validated first; an invalid document is an error.
+    /// Element text and attribute values with a known simple type are then
+    /// replaced by their canonical lexical representation (see
+    /// [`SimpleType::canonical_value`]), e.g. `1` becomes `true` for
+    /// `xs:boolean` and `+42` becomes `42` for `xs:integer`.
+    pub fn canonicalize(&self, doc: &Document) -> Result<Document> {
+        let result = self.validate(doc);
+        if !result.valid {
+            return Err(Error::Validation(
+                ValidationError::new(result.errors.first().map(|e| e.message.clone()).unwrap_or_default())
+                    .with_reason(format!("{} validation error(s)", result.errors.len())),
+            ));
+        }
 
-        // Resolve attribute types in complex types (forward references)
-        self.resolve_attribute_types();
+        let mut root = doc.root.clone();
+        if let Some(ref mut root) = root {
+            let decl = self.check_document_element(doc).map_err(Error::Validation)?;
+            self.canonicalize_element(root, decl)?;
+        }
 
-        // Refresh global element types with the fully resolved versions from global_maps.types
-        self.refresh_element_types();
+        let mut canonical = Document::new();
+        canonical.root = root;
+        canonical.namespaces = doc.namespaces.clone();
+        Ok(canonical)
+    }
 
-        // Validate redefinitions have proper self-references
-        self.validate_redefinitions();
+    /// Canonicalize the values of an element and its descendants
+    fn canonicalize_element(&self, elem: &mut Element, decl: &XsdElement) -> Result<()> {
+        let (simple_type, complex_type) = match decl.effective_type(self) {
+            Some(GlobalType::Simple(st)) => (Some(st), None),
+            Some(GlobalType::Complex(ct)) => (ct.simple_type().cloned(), Some(ct)),
+            None => (None, None),
+        };
 
-        // Mark as built
-        self.built = true;
-        Ok(())
-    }
+        if let (Some(simple_type), Some(text)) = (&simple_type, &elem.text) {
+            elem.text = Some(simple_type.canonical_value(text)?);
+        }
 
-    fn validation_attempted(&self) -> ValidationStatus {
-        if self.built {
-            ValidationStatus::Full
-        } else {
-            ValidationStatus::None
+        if let Some(ref ct) = complex_type {
+            for (qname, value) in elem.attributes.iter_mut() {
+                let attr_type = ct
+                    .attributes
+                    .get_attribute(qname)
+                    .or_else(|| ct.attributes.get_attribute(&QName::local(&qname.local_name)))
+                    .or_else(|| self.lookup_attribute(qname))
+                    .and_then(|attr| attr.effective_type(self));
+                if let Some(attr_type) = attr_type {
+                    *value = attr_type.canonical_value(value)?;
+                }
+            }
         }
-    }
 
-    fn has_errors(&self) -> bool {
-        !self.errors.is_empty()
-    }
+        for child in elem.children.iter_mut() {
+            if let Some(child_decl) = find_child_declaration(self, child, complex_type.as_deref()) {
+                self.canonicalize_element(child, &child_decl)?;
+            }
+        }
 
-    fn errors(&self) -> Vec<ParseError> {
-        self.errors.clone()
+        Ok(())
     }
-}
 
-/// A namespace view provides access to global declarations within a specific namespace
-#[derive(Debug)]
-pub struct NamespaceView<'a> {
-    schema: &'a XsdSchema,
-    namespace: Option<&'a str>,
-}
+    /// Resolve type references in global elements
+    ///
+    /// This is called during the build phase to resolve forward type references.
+    fn resolve_element_types(&mut self) {
+        use super::elements::ElementType;
 
-impl<'a> NamespaceView<'a> {
-    /// Create a new namespace view
-    pub fn new(schema: &'a XsdSchema, namespace: Option<&'a str>) -> Self {
-        Self { schema, namespace }
-    }
+        // Collect elements that need type resolution
+        let elements_to_update: Vec<_> = self.maps.global_maps.elements.iter()
+            .filter_map(|(qname, elem)| {
+                // Check if element has a type_name that needs resolution
+                if let Some(ref type_name) = elem.type_name {
+                    // Only resolve if current type is Any (unresolved)
+                    if matches!(elem.element_type, ElementType::Any) {
+                        if let Some(global_type) = self.maps.global_maps.types.get(type_name) {
+                            let resolved_type = match global_type {
+                                GlobalType::Complex(ct) => ElementType::Complex(Arc::clone(ct)),
+                                GlobalType::Simple(st) => ElementType::Simple(Arc::clone(st)),
+                            };
+                            return Some((qname.clone(), elem.as_ref().clone(), resolved_type));
+                        }
+                    }
+                }
+                None
+            })
+            .collect();
 
-    /// Check if a QName matches this namespace
-    fn matches_namespace(&self, qname: &QName) -> bool {
-        qname.namespace.as_ref().map(|n| n.as_str()) == self.namespace
+        // Update elements with resolved types
+        for (qname, mut elem, resolved_type) in elements_to_update {
+            elem.element_type = resolved_type;
+            self.maps.global_maps.elements.insert(qname, Arc::new(elem));
+        }
     }
 
-    /// Get elements in this namespace
-    pub fn elements(&self) -> impl Iterator<Item = (&QName, &Arc<XsdElement>)> + '_ {
-        self.schema.elements().filter(|(name, _)| self.matches_namespace(name))
-    }
+    /// Resolve element types in complex type content models
+    ///
+    /// This handles forward references where a local element references a type
+    /// that's defined later in the schema.
+    fn resolve_element_particle_types(&mut self) {
+        use super::complex_types::ComplexContent;
+        use super::elements::ElementType;
+        use super::groups::{ElementParticle, GroupParticle, XsdGroup};
+
+        // Collect complex types that need element type resolution
+        let types_to_update: Vec<_> = self.maps.global_maps.types.iter()
+            .filter_map(|(qname, global_type)| {
+                if let GlobalType::Complex(ct) = global_type {
+                    if let ComplexContent::Group(group) = &ct.content {
+                        if Self::has_unresolved_element_types(group) {
+                            return Some((qname.clone(), Arc::clone(ct)));
+                        }
+                    }
+                }
+                None
+            })
+            .collect();
+
+        // Resolve element types in each complex type
+        for (qname, ct) in types_to_update {
+            if let ComplexContent::Group(group) = &ct.content {
+                if let Some(resolved_group) = self.resolve_element_types_in_group(group) {
+                    let mut new_ct = (*ct).clone();
+                    new_ct.content = ComplexContent::Group(Arc::new(resolved_group));
+                    self.maps.global_maps.types.insert(qname, GlobalType::Complex(Arc::new(new_ct)));
+                }
+            }
+        }
+    }
+
+    /// Check if a group has any unresolved element types
+    fn has_unresolved_element_types(group: &super::groups::XsdGroup) -> bool {
+        use super::elements::ElementType;
+        use super::groups::GroupParticle;
+
+        for particle in &group.particles {
+            match particle {
+                GroupParticle::Element(ep) => {
+                    if let Some(elem) = ep.element() {
+                        if matches!(elem.element_type, ElementType::Any) && elem.type_name.is_some() {
+                            return true;
+                        }
+                    }
+                }
+                GroupParticle::Group(nested) => {
+                    if Self::has_unresolved_element_types(nested) {
+                        return true;
+                    }
+                }
+                GroupParticle::Any(_) => {}
+            }
+        }
+        false
+    }
+
+    /// Resolve element types in a group recursively
+    fn resolve_element_types_in_group(&self, group: &super::groups::XsdGroup) -> Option<super::groups::XsdGroup> {
+        use super::elements::ElementType;
+        use super::groups::{ElementParticle, GroupParticle};
+
+        let mut new_particles = Vec::new();
+        let mut changed = false;
+
+        for particle in &group.particles {
+            match particle {
+                GroupParticle::Element(ep) => {
+                    if let Some(elem) = ep.element() {
+                        if matches!(elem.element_type, ElementType::Any) {
+                            // Try to resolve the type
+                            if let Some(ref type_name) = elem.type_name {
+                                if let Some(global_type) = self.maps.global_maps.types.get(type_name) {
+                                    let resolved_type = match global_type {
+                                        GlobalType::Complex(ct) => ElementType::Complex(Arc::clone(ct)),
+                                        GlobalType::Simple(st) => ElementType::Simple(Arc::clone(st)),
+                                    };
+                                    // Create updated element with resolved type
+                                    // elem is Arc<XsdElement>, we need to clone the inner value
+                                    let mut new_elem = elem.as_ref().clone();
+                                    new_elem.element_type = resolved_type;
+                                    let new_particle = ElementParticle::with_decl(
+                                        ep.name.clone(),
+                                        ep.occurs,
+                                        Arc::new(new_elem),
+                                    );
+                                    new_particles.push(GroupParticle::Element(Arc::new(new_particle)));
+                                    changed = true;
+                                    continue;
+                                }
+                            }
+                        }
+                    }
+                    new_particles.push(particle.clone());
+                }
+                GroupParticle::Group(nested) => {
+                    if let Some(resolved_nested) = self.resolve_element_types_in_group(nested) {
+                        new_particles.push(GroupParticle::Group(Arc::new(resolved_nested)));
+                        changed = true;
+                    } else {
+                        new_particles.push(particle.clone());
+                    }
+                }
+                GroupParticle::Any(_) => {
+                    new_particles.push(particle.clone());
+                }
+            }
+        }
+
+        if changed {
+            // Keep the occurs and the rest of the group, with resolved particles
+            let mut new_group = group.clone();
+            new_group.particles = new_particles;
+            Some(new_group)
+        } else {
+            None
+        }
+    }
+
+    /// Resolve attribute types in complex types (forward references)
+    ///
+    /// This handles cases where attributes reference simple types that are defined
+    /// later in the schema.
+    fn resolve_attribute_types(&mut self) {
+        // Collect complex types that have unresolved attribute types
+        let types_to_update: Vec<_> = self.maps.global_maps.types.iter()
+            .filter_map(|(qname, global_type)| {
+                if let GlobalType::Complex(ct) = global_type {
+                    // Check if any attribute has an unresolved type_name
+                    let has_unresolved = ct.attributes.iter_attributes()
+                        .any(|attr| attr.type_name.is_some() && attr.simple_type().is_none());
+                    if has_unresolved {
+                        return Some((qname.clone(), Arc::clone(ct)));
+                    }
+                }
+                None
+            })
+            .collect();
+
+        // Resolve attribute types
+        for (qname, ct) in types_to_update {
+            let mut new_ct = (*ct).clone();
+
+            // Collect attributes to update (to avoid borrow issues)
+            let attrs_to_update: Vec<_> = new_ct.attributes.iter_attributes()
+                .filter_map(|attr| {
+                    if let Some(ref type_name) = attr.type_name {
+                        if attr.simple_type().is_none() {
+                            if let Some(global_type) = self.maps.global_maps.types.get(type_name) {
+                                if let GlobalType::Simple(st) = global_type {
+                                    // Create updated attribute with resolved type
+                                    return Some(Arc::new(retyped_attribute(attr, Arc::clone(st))));
+                                }
+                            }
+                        }
+                    }
+                    None
+                })
+                .collect();
+
+            // Apply updates
+            if !attrs_to_update.is_empty() {
+                for new_attr in attrs_to_update {
+                    new_ct.attributes.set_attribute(new_attr);
+                }
+                self.maps.global_maps.types.insert(qname, GlobalType::Complex(Arc::new(new_ct)));
+            }
+        }
+    }
+
+    /// Replace type references that can't be resolved with placeholders
+    ///
+    /// Elements of an unknown type get an `xs:anyType` placeholder and
+    /// attributes a permissive [`PlaceholderType`], so that the rest of the
+    /// schema stays usable. Unknown base types are only reported. Returns a
+    /// warning for each replaced reference.
+    pub(crate) fn replace_unresolved_types(&mut self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        let mut namespaces: Vec<_> = self.imports.keys().cloned().collect();
+        namespaces.sort();
+        for namespace in namespaces {
+            let import = self.imports.get_mut(&namespace).and_then(|import| import.schema.as_mut());
+            if let Some(imported) = import.and_then(Arc::get_mut) {
+                warnings.extend(imported.replace_unresolved_types());
+            }
+        }
+
+        let mut type_names: Vec<_> = self.maps.global_maps.types.keys().cloned().collect();
+        type_names.sort_by(qname_order);
+        for qname in type_names {
+            if let Some(GlobalType::Complex(ct)) = self.maps.global_maps.types.get(&qname) {
+                let ct = Arc::clone(ct);
+                let context = format!("complex type '{}'", qname.to_string());
+                if let Some(new_ct) = self.placeholder_complex_type(&ct, &context, &mut warnings) {
+                    self.maps.global_maps.types.insert(qname, GlobalType::Complex(Arc::new(new_ct)));
+                }
+            }
+        }
+
+        let mut element_names: Vec<_> = self.maps.global_maps.elements.keys().cloned().collect();
+        element_names.sort_by(qname_order);
+        for qname in element_names {
+            let elem = Arc::clone(&self.maps.global_maps.elements[&qname]);
+            let context = format!("element '{}'", qname.to_string());
+            if let Some(new_elem) = self.placeholder_element(&elem, &context, &mut warnings) {
+                self.maps.global_maps.elements.insert(qname, Arc::new(new_elem));
+            }
+        }
+
+        self.warnings.extend(warnings.iter().cloned());
+        warnings
+    }
+
+    /// Check whether a type name refers to a global or built-in type
+    fn is_known_type(&self, type_name: &QName) -> bool {
+        self.lookup_type(type_name).is_some()
+            || (type_name.namespace.as_deref() == Some(XSD_NAMESPACE)
+                && builtin_type_qname(&type_name.local_name).is_some())
+    }
+
+    /// Element with a placeholder for an unknown type, if anything was replaced
+    fn placeholder_element(&self, elem: &XsdElement, context: &str, warnings: &mut Vec<String>) -> Option<XsdElement> {
+        match (&elem.type_name, &elem.element_type) {
+            (Some(type_name), ElementType::Any) if !self.is_known_type(type_name) => {
+                warnings.push(format!(
+                    "unknown type '{}' of {} replaced with xs:anyType",
+                    type_name.to_string(),
+                    context
+                ));
+                let mut any_type = self.builders.build_any_type();
+                let (_, any_attribute) = self.builders.build_any_attribute_group();
+                any_type.attributes.set_any_attribute(Arc::new(any_attribute));
+                let mut new_elem = elem.clone();
+                new_elem.element_type = ElementType::Complex(Arc::new(any_type));
+                Some(new_elem)
+            }
+            // Anonymous types are walked for their local declarations
+            (None, ElementType::Complex(ct)) if ct.name.is_none() => {
+                let new_ct = self.placeholder_complex_type(ct, context, warnings)?;
+                let mut new_elem = elem.clone();
+                new_elem.element_type = ElementType::Complex(Arc::new(new_ct));
+                Some(new_elem)
+            }
+            _ => None,
+        }
+    }
+
+    /// Complex type with placeholders for unknown types, if anything was replaced
+    fn placeholder_complex_type(
+        &self,
+        ct: &XsdComplexType,
+        context: &str,
+        warnings: &mut Vec<String>,
+    ) -> Option<XsdComplexType> {
+        if let Some(base) = &ct.base_type {
+            if !self.is_known_type(base) {
+                warnings.push(format!("unknown base type '{}' of {}", base.to_string(), context));
+            }
+        }
+
+        let mut new_ct = None;
+        if let ComplexContent::Group(group) = &ct.content {
+            if let Some(new_group) = self.placeholder_group(group, context, warnings) {
+                new_ct.get_or_insert_with(|| ct.clone()).content = ComplexContent::Group(Arc::new(new_group));
+            }
+        }
+
+        let attrs_to_update: Vec<_> = ct
+            .attributes
+            .iter_attributes()
+            .filter_map(|attr| {
+                let type_name = attr.type_name.as_ref()?;
+                if attr.simple_type().is_some() || self.is_known_type(type_name) {
+                    return None;
+                }
+                warnings.push(format!(
+                    "unknown type '{}' of attribute '{}' in {} replaced with a placeholder",
+                    type_name.to_string(),
+                    attr.name().local_name,
+                    context
+                ));
+                let mut new_attr = retyped_attribute(attr, Arc::new(PlaceholderType::new(type_name.clone())));
+                new_attr.type_name = Some(type_name.clone());
+                Some(Arc::new(new_attr))
+            })
+            .collect();
+        if !attrs_to_update.is_empty() {
+            let new_ct = new_ct.get_or_insert_with(|| ct.clone());
+            for new_attr in attrs_to_update {
+                new_ct.attributes.set_attribute(new_attr);
+            }
+        }
+        new_ct
+    }
+
+    /// Model group with placeholders for unknown element types, if anything was replaced
+    fn placeholder_group(&self, group: &XsdGroup, context: &str, warnings: &mut Vec<String>) -> Option<XsdGroup> {
+        use super::groups::ElementParticle;
+
+        let mut changed = false;
+        let particles = group
+            .particles
+            .iter()
+            .map(|particle| match particle {
+                GroupParticle::Element(ep) => {
+                    let new_elem = ep.element().and_then(|elem| {
+                        let context = format!("element '{}' in {}", ep.name.local_name, context);
+                        self.placeholder_element(elem, &context, warnings)
+                    });
+                    match new_elem {
+                        Some(new_elem) => {
+                            changed = true;
+                            let new_particle = ElementParticle::with_decl(ep.name.clone(), ep.occurs, Arc::new(new_elem));
+                            GroupParticle::Element(Arc::new(new_particle))
+                        }
+                        None => particle.clone(),
+                    }
+                }
+                GroupParticle::Group(nested) => match self.placeholder_group(nested, context, warnings) {
+                    Some(new_nested) => {
+                        changed = true;
+                        GroupParticle::Group(Arc::new(new_nested))
+                    }
+                    None => particle.clone(),
+                },
+                GroupParticle::Any(_) => particle.clone(),
+            })
+            .collect();
+
+        if changed {
+            let mut new_group = group.clone();
+            new_group.particles = particles;
+            Some(new_group)
+        } else {
+            None
+        }
+    }
+
+    /// Refresh global element types with the fully resolved versions
+    ///
+    /// After all type resolution is complete, update global elements that reference
+    /// named complex types to use the fully resolved type from global_maps.types.
+    /// This ensures element's inline type copies have the resolved attributes and children.
+    fn refresh_element_types(&mut self) {
+        use super::elements::ElementType;
+
+        // Collect elements that need their type refreshed
+        let elements_to_update: Vec<_> = self.maps.global_maps.elements.iter()
+            .filter_map(|(elem_qname, elem)| {
+                if let ElementType::Complex(ct) = &elem.element_type {
+                    // If the complex type has a name, look up the fresh version
+                    if let Some(ref type_name) = ct.name {
+                        if let Some(GlobalType::Complex(fresh_ct)) = self.maps.global_maps.types.get(type_name) {
+                            let mut new_elem = (**elem).clone();
+                            new_elem.element_type = ElementType::Complex(Arc::clone(fresh_ct));
+                            return Some((elem_qname.clone(), Arc::new(new_elem)));
+                        }
+                    }
+                }
+                None
+            })
+            .collect();
+
+        // Apply updates
+        for (qname, new_elem) in elements_to_update {
+            self.maps.global_maps.elements.insert(qname, new_elem);
+        }
+    }
+
+    /// Resolve group references (xs:group ref="...")
+    ///
+    /// This walks through all complex types and resolves group references to their actual content.
+    fn resolve_group_references(&mut self) {
+        use super::complex_types::ComplexContent;
+        use super::groups::GroupParticle;
+
+        // Collect complex types that need group reference resolution, bases first
+        let types_to_update: Vec<_> = self
+            .complex_types_in_derivation_order()
+            .into_iter()
+            .filter_map(|qname| match self.maps.global_maps.types.get(&qname) {
+                Some(GlobalType::Complex(ct)) => match &ct.content {
+                    ComplexContent::Group(group) if Self::has_group_refs(group) => {
+                        let ct = Arc::clone(ct);
+                        Some((qname, ct))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+
+        // Resolve group references in each type
+        for (qname, ct) in types_to_update {
+            if let ComplexContent::Group(group) = &ct.content {
+                if let Some(resolved_group) = self.resolve_group_ref_recursive(group) {
+                    let mut new_ct = (*ct).clone();
+                    new_ct.content = ComplexContent::Group(Arc::new(resolved_group));
+                    self.maps.global_maps.types.insert(qname, GlobalType::Complex(Arc::new(new_ct)));
+                }
+            }
+        }
+    }
+
+    /// Get the names of the global complex types, each after its base type
+    ///
+    /// Types are visited in QName order and each one is preceded by its
+    /// chain of base types, so the order only depends on the type names.
+    fn complex_types_in_derivation_order(&self) -> Vec<QName> {
+        let mut names: Vec<&QName> = self
+            .maps
+            .global_maps
+            .types
+            .iter()
+            .filter(|(_, global_type)| global_type.is_complex())
+            .map(|(qname, _)| qname)
+            .collect();
+        names.sort_by(|a, b| qname_order(a, b));
+
+        let mut ordered = Vec::with_capacity(names.len());
+        let mut visited = HashSet::new();
+        for qname in names {
+            // Walk up the base chain, then add the chain from the top down
+            let mut chain = Vec::new();
+            let mut current = Some(qname);
+            while let Some(name) = current {
+                if !visited.insert(name.clone()) {
+                    break;
+                }
+                chain.push(name.clone());
+                current = match self.maps.global_maps.types.get(name) {
+                    Some(GlobalType::Complex(ct)) => ct
+                        .base_type
+                        .as_ref()
+                        .filter(|base| self.maps.global_maps.types.get(*base).is_some_and(|t| t.is_complex())),
+                    _ => None,
+                };
+            }
+            ordered.extend(chain.into_iter().rev());
+        }
+        ordered
+    }
+
+    /// Check if a group or its children contain group references
+    fn has_group_refs(group: &super::groups::XsdGroup) -> bool {
+        use super::groups::GroupParticle;
+
+        if group.group_ref.is_some() {
+            return true;
+        }
+        for particle in &group.particles {
+            if let GroupParticle::Group(nested) = particle {
+                if Self::has_group_refs(nested) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Recursively resolve group references in a group
+    fn resolve_group_ref_recursive(&self, group: &super::groups::XsdGroup) -> Option<super::groups::XsdGroup> {
+        use std::collections::HashSet;
+        let mut visited = HashSet::new();
+        // Pass context about the current group being processed for self-reference detection
+        self.resolve_group_ref_with_context(group, group.name.as_ref(), group.redefine.as_ref(), &mut visited)
+    }
+
+    /// Recursively resolve group references with context about the owning group
+    fn resolve_group_ref_with_context(
+        &self,
+        group: &super::groups::XsdGroup,
+        owner_name: Option<&QName>,
+        owner_redefine: Option<&Arc<super::groups::XsdGroup>>,
+        visited: &mut std::collections::HashSet<QName>,
+    ) -> Option<super::groups::XsdGroup> {
+        use super::groups::{GroupParticle, XsdGroup};
+
+        // If this group is a reference, resolve it
+        if let Some(ref ref_name) = group.group_ref {
+            // IMPORTANT: Check for self-referential redefinitions BEFORE checking visited set
+            // This allows xs:redefine patterns where a group references itself
+            // In that case, we substitute the original (pre-redefine) content
+            if let (Some(owner), Some(original)) = (owner_name, owner_redefine) {
+                if ref_name == owner {
+                    // Self-reference in redefinition - use the original
+                    let mut resolved = (**original).clone();
+                    resolved.occurs = group.occurs;
+                    resolved.group_ref = None;
+                    // Recursively resolve any nested references in the original
+                    // (but not as a self-reference since we're now in the original)
+                    // Don't add to visited - the original is different from the redefined version
+                    if let Some(nested_resolved) = self.resolve_group_ref_with_context(&resolved, None, None, visited) {
+                        return Some(nested_resolved);
+                    }
+                    return Some(resolved);
+                }
+            }
+
+            // Check if we've already visited this group to prevent infinite recursion
+            // (but only for non-self-references)
+            if visited.contains(ref_name) {
+                // Already visited - just return the group as-is without further resolution
+                let mut resolved = group.clone();
+                resolved.group_ref = None;
+                return Some(resolved);
+            }
+
+            // Mark as visited before recursing
+            visited.insert(ref_name.clone());
+
+            // Otherwise look up in global maps
+            if let Some(referenced_group) = self.maps.global_maps.groups.get(ref_name) {
+                // Create a new group with the referenced group's content
+                // Dereference twice: &Arc<XsdGroup> -> Arc<XsdGroup> -> XsdGroup
+                let mut resolved = (**referenced_group).clone();
+                resolved.occurs = group.occurs;
+                resolved.group_ref = None;
+                // Recursively resolve any nested references
+                // IMPORTANT: Switch to the referenced group's context, not the outer context
+                // This prevents infinite recursion when the referenced group itself has a redefine
+                let ref_owner = referenced_group.name.as_ref();
+                let ref_redefine = referenced_group.redefine.as_ref();
+                if let Some(nested_resolved) = self.resolve_group_ref_with_context(&resolved, ref_owner, ref_redefine, visited) {
+                    return Some(nested_resolved);
+                }
+                return Some(resolved);
+            }
+            // Reference not found - return None
+            return None;
+        }
+
+        // Not a reference - resolve any nested group references
+        let mut modified = false;
+        let mut new_particles = Vec::new();
+
+        for particle in &group.particles {
+            match particle {
+                GroupParticle::Group(nested) => {
+                    if let Some(resolved_nested) = self.resolve_group_ref_with_context(nested, owner_name, owner_redefine, visited) {
+                        new_particles.push(GroupParticle::Group(Arc::new(resolved_nested)));
+                        modified = true;
+                    } else {
+                        new_particles.push(particle.clone());
+                    }
+                }
+                _ => new_particles.push(particle.clone()),
+            }
+        }
+
+        if modified {
+            let mut new_group = group.clone();
+            new_group.particles = new_particles;
+            new_group.annotation = group.annotation.clone();
+            Some(new_group)
+        } else {
+            None
+        }
+    }
+
+    /// Resolve complex type derivations (extension/restriction)
+    ///
+    /// This is called during the build phase to merge base type content with derived types.
+    /// A [`BuildContext`] resolves every global complex type after its base, so that
+    /// derivation chains see the fully resolved content of every ancestor; circular
+    /// derivations are recorded as parse errors.
+    fn resolve_complex_type_derivations(&mut self) {
+        let mut names: Vec<QName> = self
+            .maps
+            .global_maps
+            .types
+            .iter()
+            .filter(|(_, global_type)| global_type.is_complex())
+            .map(|(qname, _)| qname.clone())
+            .collect();
+        names.sort_by(qname_order);
+
+        let mut context = BuildContext::new(self.version);
+        for qname in &names {
+            context.stage_type(qname.clone());
+        }
+        for qname in &names {
+            if let Err(err) = context.resolve_type(qname, self) {
+                context.add_error(match err {
+                    Error::Parse(error) => error,
+                    other => ParseError::new(other.to_string()),
+                });
+            }
+        }
+        self.errors.extend(context.errors().iter().cloned());
+    }
+
+    /// Merge the content and attributes of a complex type's base type into it
+    ///
+    /// The base type is looked up in the global maps as it is, so it should be
+    /// resolved first; see [`BuildContext::resolve_type`].
+    pub(crate) fn resolve_complex_type_derivation(&mut self, qname: &QName) {
+        use super::complex_types::{ComplexContent, DerivationMethod};
+        use super::groups::{GroupParticle, ModelType, XsdGroup};
+
+        let Some(GlobalType::Complex(derived_ct)) = self.maps.global_maps.types.get(qname) else {
+            return;
+        };
+        let derived_ct = Arc::clone(derived_ct);
+        let (Some(base_type_name), Some(derivation)) = (derived_ct.base_type.as_ref(), derived_ct.derivation) else {
+            return;
+        };
+        // A simpleContent extension of a simple type declared after the complex type
+        if let Some(GlobalType::Simple(simple_type)) = self.maps.global_maps.types.get(base_type_name) {
+            if derived_ct.simple_type().is_none() {
+                let mut new_ct = (*derived_ct).clone();
+                new_ct.content = ComplexContent::Simple(Arc::clone(simple_type));
+                self.maps.global_maps.types.insert(qname.clone(), GlobalType::Complex(Arc::new(new_ct)));
+            }
+            return;
+        }
+        let base_ct = match (&derived_ct.redefine, self.maps.global_maps.types.get(base_type_name)) {
+            // For self-referential redefinitions, use the original from redefine field
+            (Some(original), _) if base_type_name == qname => Arc::clone(original),
+            // Otherwise look up base type in global maps
+            (_, Some(GlobalType::Complex(base_ct))) => Arc::clone(base_ct),
+            _ => return,
+        };
+
+        let mut new_ct = (*derived_ct).clone();
+
+        match derivation {
+            DerivationMethod::Extension => {
+                // For extension: create a sequence containing base content + extension content
+                if let (ComplexContent::Group(base_group), ComplexContent::Group(ext_group)) =
+                    (&base_ct.content, &derived_ct.content)
+                {
+                    // If base type is empty, just use extension's content
+                    if base_group.is_empty() {
+                        // Extension content stays as-is
+                    } else if ext_group.is_empty() {
+                        // No new content, just inherit base
+                        new_ct.content = ComplexContent::Group(Arc::clone(base_group));
+                    } else {
+                        // Both have content - create wrapper sequence
+                        let mut wrapper = XsdGroup::new(ModelType::Sequence);
+                        wrapper.particles.push(GroupParticle::Group(Arc::clone(base_group)));
+                        wrapper.particles.push(GroupParticle::Group(Arc::clone(ext_group)));
+                        new_ct.content = ComplexContent::Group(Arc::new(wrapper));
+                    }
+                }
+
+                // Inherit mixed from base if not explicitly set
+                if !new_ct.mixed_explicit {
+                    new_ct.mixed = base_ct.mixed;
+                }
+
+                // Inherit attributes from base type
+                for attr in base_ct.attributes.iter_attributes() {
+                    // Only add if not already defined (extension can override)
+                    if new_ct.attributes.get_attribute(attr.name()).is_none() {
+                        let _ = new_ct.attributes.add_attribute(Arc::clone(attr));
+                    }
+                }
+            }
+            DerivationMethod::Restriction => {
+                // For restriction: the derived content model replaces base, and
+                // a restriction without a content model has empty content
+
+                // Inherit mixed from base if not explicitly set
+                if !new_ct.mixed_explicit {
+                    new_ct.mixed = base_ct.mixed;
+                }
+
+                // For restriction, inherit base attributes (derived type can narrow them)
+                for attr in base_ct.attributes.iter_attributes() {
+                    if new_ct.attributes.get_attribute(attr.name()).is_none() {
+                        let _ = new_ct.attributes.add_attribute(Arc::clone(attr));
+                    }
+                }
+            }
+        }
+
+        Self::inherit_simple_content(&mut new_ct, &base_ct);
+        if let Some(any) = Self::merge_attribute_wildcards(&derived_ct.attributes, &base_ct.attributes, derivation) {
+            new_ct.attributes.set_any_attribute(any);
+        }
+        new_ct.assertions = Self::merge_assertions(&derived_ct.assertions, &base_ct.assertions);
+
+        // Update the type in the global map
+        self.maps.global_maps.types.insert(qname.clone(), GlobalType::Complex(Arc::new(new_ct)));
+    }
+
+    /// Give a derived type without content of its own the simple content of its base type
+    fn inherit_simple_content(derived: &mut XsdComplexType, base: &XsdComplexType) {
+        if let (ComplexContent::Simple(simple_type), ComplexContent::Group(group)) = (&base.content, &derived.content) {
+            if group.is_empty() {
+                derived.content = ComplexContent::Simple(Arc::clone(simple_type));
+            }
+        }
+    }
+
+    /// Merge the attribute wildcards of a derived type and its base type
+    ///
+    /// An extension allows the union of both wildcards, a restriction only
+    /// their intersection. A wildcard present on one side only is kept for
+    /// extension; a restriction without its own wildcard has none.
+    fn merge_attribute_wildcards(
+        derived: &XsdAttributeGroup,
+        base: &XsdAttributeGroup,
+        derivation: DerivationMethod,
+    ) -> Option<Arc<XsdAnyAttribute>> {
+        match (derived.any_attribute(), base.any_attribute(), derivation) {
+            (Some(own), Some(inherited), DerivationMethod::Extension) => Some(Arc::new(XsdAnyAttribute {
+                wildcard: own.wildcard.union(&inherited.wildcard),
+            })),
+            (Some(own), Some(inherited), DerivationMethod::Restriction) => Some(Arc::new(XsdAnyAttribute {
+                wildcard: own.wildcard.intersect(&inherited.wildcard),
+            })),
+            (None, Some(inherited), DerivationMethod::Extension) => Some(Arc::clone(inherited)),
+            (own, _, _) => own.cloned(),
+        }
+    }
+
+    /// Merge the assertions of a derived type and its base type
+    ///
+    /// Both extension and restriction keep the base type's assertions, which
+    /// come first. An assertion repeated by the derived type is kept once.
+    fn merge_assertions(derived: &AssertionList, base: &AssertionList) -> AssertionList {
+        let mut assertions = base.clone();
+        for assertion in derived.iter() {
+            if !base.iter().any(|inherited| inherited.test() == assertion.test()) {
+                assertions.add(assertion.clone());
+            }
+        }
+        assertions
+    }
+
+    /// Resolve derivations for inline complex types in elements
+    ///
+    /// Elements can have inline anonymous complex types that use extension/restriction.
+    /// These need to be resolved separately since they're not in global_maps.types.
+    fn resolve_inline_element_type_derivations(&mut self) {
+        use super::complex_types::{ComplexContent, DerivationMethod};
+        use super::elements::ElementType;
+        use super::groups::{GroupParticle, ModelType, XsdGroup};
+
+        // Collect elements that have inline complex types needing derivation resolution
+        let elements_to_update: Vec<_> = self.maps.global_maps.elements.iter()
+            .filter_map(|(elem_qname, elem)| {
+                if let ElementType::Complex(ct) = &elem.element_type {
+                    // Only process inline types (anonymous - no name)
+                    // Named types are handled in resolve_complex_type_derivations
+                    if ct.name.is_none() {
+                        if let Some(ref base_type_name) = ct.base_type {
+                            if let Some(derivation) = ct.derivation {
+                                // Look up base type in global maps
+                                if let Some(GlobalType::Complex(base_ct)) = self.maps.global_maps.types.get(base_type_name) {
+                                    return Some((elem_qname.clone(), Arc::clone(ct), Arc::clone(base_ct), derivation));
+                                }
+                            }
+                        }
+                    }
+                }
+                None
+            })
+            .collect();
+
+        // Resolve each element's inline type
+        for (elem_qname, derived_ct, base_ct, derivation) in elements_to_update {
+            let mut new_ct = (*derived_ct).clone();
+
+            match derivation {
+                DerivationMethod::Extension => {
+                    // For extension: inherit base content
+                    if let (ComplexContent::Group(base_group), ComplexContent::Group(ext_group)) =
+                        (&base_ct.content, &derived_ct.content)
+                    {
+                        if base_group.is_empty() {
+                            // Extension content stays as-is
+                        } else if ext_group.is_empty() {
+                            // No new content, just inherit base
+                            new_ct.content = ComplexContent::Group(Arc::clone(base_group));
+                        } else {
+                            // Both have content - create wrapper sequence
+                            let mut wrapper = XsdGroup::new(ModelType::Sequence);
+                            wrapper.particles.push(GroupParticle::Group(Arc::clone(base_group)));
+                            wrapper.particles.push(GroupParticle::Group(Arc::clone(ext_group)));
+                            new_ct.content = ComplexContent::Group(Arc::new(wrapper));
+                        }
+                    }
+
+                    // Inherit mixed from base if not explicitly set
+                    if !new_ct.mixed_explicit {
+                        new_ct.mixed = base_ct.mixed;
+                    }
+
+                    // Inherit attributes from base type
+                    for attr in base_ct.attributes.iter_attributes() {
+                        if new_ct.attributes.get_attribute(attr.name()).is_none() {
+                            let _ = new_ct.attributes.add_attribute(Arc::clone(attr));
+                        }
+                    }
+                }
+                DerivationMethod::Restriction => {
+                    // For restriction: the derived content model replaces base, and
+                    // a restriction without a content model has empty content
+
+                    // Inherit mixed from base if not explicitly set
+                    if !new_ct.mixed_explicit {
+                        new_ct.mixed = base_ct.mixed;
+                    }
+
+                    // For restriction, inherit base attributes
+                    for attr in base_ct.attributes.iter_attributes() {
+                        if new_ct.attributes.get_attribute(attr.name()).is_none() {
+                            let _ = new_ct.attributes.add_attribute(Arc::clone(attr));
+                        }
+                    }
+                }
+            }
+
+            Self::inherit_simple_content(&mut new_ct, &base_ct);
+            if let Some(any) = Self::merge_attribute_wildcards(&derived_ct.attributes, &base_ct.attributes, derivation) {
+                new_ct.attributes.set_any_attribute(any);
+            }
+            new_ct.assertions = Self::merge_assertions(&derived_ct.assertions, &base_ct.assertions);
+
+            // Resolve the group references of the inherited and own content
+            if let ComplexContent::Group(group) = &new_ct.content {
+                if let Some(resolved_group) = self.resolve_group_ref_recursive(group) {
+                    new_ct.content = ComplexContent::Group(Arc::new(resolved_group));
+                }
+            }
+
+            // Update the element with the resolved type
+            if let Some(elem) = self.maps.global_maps.elements.get(&elem_qname) {
+                let mut new_elem = (**elem).clone();
+                new_elem.element_type = ElementType::Complex(Arc::new(new_ct));
+                self.maps.global_maps.elements.insert(elem_qname, Arc::new(new_elem));
+            }
+        }
+    }
+
+    /// Resolve attribute group references
+    ///
+    /// This resolves pending attribute group references in:
+    /// 1. Global attribute groups that reference other attribute groups
+    /// 2. Complex types whose attribute groups reference other attribute groups
+    ///
+    /// The resolution runs in a loop until no more changes are made, ensuring
+    /// all transitive references are resolved regardless of HashMap iteration order.
+    fn resolve_attribute_group_references(&mut self) {
+        // First, resolve references in global attribute groups
+        // Loop until no more changes are made (handles transitive references and non-deterministic iteration)
+        let max_iterations = 100; // Prevent infinite loops
+        for iteration in 0..max_iterations {
+            let groups_to_update: Vec<_> = self.maps.global_maps.attribute_groups.iter()
+                .filter_map(|(qname, group)| {
+                    if group.has_pending_refs() {
+                        Some((qname.clone(), (**group).clone()))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+
+            if groups_to_update.is_empty() {
+                break;
+            }
+
+            if iteration == max_iterations - 1 {
+                eprintln!("Warning: Attribute group resolution reached max iterations");
+            }
+
+            for (qname, mut group) in groups_to_update {
+                // Get the redefine reference for self-reference detection
+                let redefine_ref = group.redefine.clone();
+
+                for ref_qname in group.pending_group_refs().to_vec() {
+                    // For self-referential redefinitions, use the original from redefine field
+                    if ref_qname == qname {
+                        if let Some(ref original) = redefine_ref {
+                            // Self-reference in redefinition - use the original
+                            for attr in original.iter_attributes() {
+                                let _ = group.add_attribute(Arc::clone(attr));
+                            }
+                            continue;
+                        }
+                    }
+                    // Otherwise look up in global maps
+                    if let Some(referenced_group) = self.maps.global_maps.attribute_groups.get(&ref_qname) {
+                        // Only add attributes if the referenced group has been fully resolved
+                        // (no pending refs), otherwise we'll get them in a later iteration
+                        if !referenced_group.has_pending_refs() {
+                            for attr in referenced_group.iter_attributes() {
+                                let _ = group.add_attribute(Arc::clone(attr));
+                            }
+                        }
+                    }
+                }
+
+                // Only clear pending refs if all referenced groups have been resolved
+                let all_resolved = group.pending_group_refs().iter().all(|ref_qname| {
+                    if *ref_qname == qname {
+                        return true; // Self-reference is always handled
+                    }
+                    self.maps.global_maps.attribute_groups
+                        .get(ref_qname)
+                        .map_or(true, |g| !g.has_pending_refs())
+                });
+
+                if all_resolved {
+                    group.clear_pending_refs();
+                }
+
+                self.maps.global_maps.attribute_groups.insert(qname, Arc::new(group));
+            }
+        }
+
+        // Then, resolve references in complex types' attribute groups
+        let types_to_update: Vec<_> = self.maps.global_maps.types.iter()
+            .filter_map(|(qname, global_type)| {
+                if let GlobalType::Complex(ct) = global_type {
+                    if ct.attributes.has_pending_refs() {
+                        return Some((qname.clone(), Arc::clone(ct)));
+                    }
+                }
+                None
+            })
+            .collect();
+
+        for (qname, ct) in types_to_update {
+            let mut new_ct = (*ct).clone();
+            self.expand_attribute_group_refs(&mut new_ct.attributes);
+            self.maps.global_maps.types.insert(qname, GlobalType::Complex(Arc::new(new_ct)));
+        }
+
+        // Finally, resolve references in the anonymous types of global elements
+        let elements_to_update: Vec<_> = self.maps.global_maps.elements.iter()
+            .filter_map(|(qname, elem)| match &elem.element_type {
+                ElementType::Complex(ct) if ct.name.is_none() && ct.attributes.has_pending_refs() => {
+                    Some((qname.clone(), Arc::clone(elem), Arc::clone(ct)))
+                }
+                _ => None,
+            })
+            .collect();
+
+        for (qname, elem, ct) in elements_to_update {
+            let mut new_ct = (*ct).clone();
+            self.expand_attribute_group_refs(&mut new_ct.attributes);
+            let mut new_elem = (*elem).clone();
+            new_elem.element_type = ElementType::Complex(Arc::new(new_ct));
+            self.maps.global_maps.elements.insert(qname, Arc::new(new_elem));
+        }
+    }
+
+    /// Add the attributes of the referenced attribute groups to a complex type's attributes
+    fn expand_attribute_group_refs(&self, attributes: &mut XsdAttributeGroup) {
+        for ref_qname in attributes.pending_group_refs().to_vec() {
+            if let Some(referenced_group) = self.maps.global_maps.attribute_groups.get(&ref_qname) {
+                for attr in referenced_group.iter_attributes() {
+                    let _ = attributes.add_attribute(Arc::clone(attr));
+                }
+            }
+        }
+        attributes.clear_pending_refs();
+    }
+
+    /// Validate that redefined components have proper self-references
+    ///
+    /// According to XSD spec:
+    /// - Redefined complex types must derive from themselves
+    /// - Redefined groups must contain exactly one self-reference
+    /// - Redefined attribute groups must contain exactly one self-reference
+    fn validate_redefinitions(&mut self) {
+        // Collect errors first to avoid borrow issues
+        let mut errors = Vec::new();
+
+        // Validate complex types with redefine have self as base type
+        for (qname, global_type) in &self.maps.global_maps.types {
+            if let GlobalType::Complex(ct) = global_type {
+                if ct.redefine.is_some() {
+                    // Check that base_type matches the type's own name
+                    if let Some(ref base) = ct.base_type {
+                        if base != qname {
+                            errors.push(ParseError::new(format!(
+                                "Redefined complex type '{:?}' must derive from itself, but derives from '{:?}'",
+                                qname, base
+                            )));
+                        }
+                    } else {
+                        errors.push(ParseError::new(format!(
+                            "Redefined complex type '{:?}' must derive from itself",
+                            qname
+                        )));
+                    }
+                }
+            }
+        }
+
+        // Validate groups with redefine (self-reference already resolved at this point)
+        // Groups that were redefined should have had their self-reference resolved to original content
+        // Just warn if a redefined group appears empty (might indicate missing self-ref)
+        for (qname, group) in &self.maps.global_maps.groups {
+            if group.redefine.is_some() {
+                if group.particles.is_empty() {
+                    errors.push(ParseError::new(format!(
+                        "Redefined group '{:?}' has no content - may be missing self-reference",
+                        qname
+                    )));
+                }
+            }
+        }
+
+        // Validate attribute groups with redefine
+        for (qname, attr_group) in &self.maps.global_maps.attribute_groups {
+            if attr_group.redefine.is_some() {
+                if attr_group.len() == 0 {
+                    errors.push(ParseError::new(format!(
+                        "Redefined attribute group '{:?}' has no attributes - may be missing self-reference",
+                        qname
+                    )));
+                }
+            }
+        }
+
+        // Add collected errors
+        for error in errors {
+            self.parse_error(error);
+        }
+    }
+
+    /// Check the structural constraints of the schema components
+    ///
+    /// Runs over the global components and the local elements and anonymous
+    /// types they contain, and returns an error for each violation of:
+    /// - an element with both a `default` and a `fixed` value (src-element.1)
+    /// - an element value constraint that is not valid for the element's
+    ///   simple type (e-props-correct.2)
+    /// - an empty enumeration facet, or an enumeration value that is not
+    ///   valid for the base type (enumeration-valid-restriction)
+    /// - an explicitTimezone facet on a type that is not a date/time type
+    /// - a complex type restriction declaring an element its base type's
+    ///   content model does not allow (derivation-ok-restriction.5)
+    ///
+    /// Called by `build`, which records the errors on the schema.
+    pub fn validate_schema_constraints(&self) -> Vec<ParseError> {
+        let mut messages = Vec::new();
+
+        let mut elements: Vec<&XsdElement> = self.maps.global_maps.elements.values().map(Arc::as_ref).collect();
+        for (_, ct) in self.complex_types_with_anonymous() {
+            if let Some(group) = ct.model_group() {
+                Self::collect_local_elements(group, &mut elements);
+            }
+        }
+        for group in self.maps.global_maps.groups.values() {
+            Self::collect_local_elements(group, &mut elements);
+        }
+        for element in elements {
+            Self::check_value_constraint(element, &mut messages);
+        }
+
+        for (qname, global_type) in self.types() {
+            match global_type {
+                GlobalType::Simple(st) => {
+                    Self::check_enumeration(qname, st.as_ref(), &mut messages);
+                    Self::check_explicit_timezone(qname, st.as_ref(), &mut messages);
+                }
+                GlobalType::Complex(ct) => self.check_content_restriction(qname, ct, &mut messages),
+            }
+        }
+
+        // Local declarations shared through groups are reached more than once
+        messages.sort();
+        messages.dedup();
+        messages.into_iter().map(ParseError::new).collect()
+    }
+
+    /// Collect the local element declarations of a model group
+    fn collect_local_elements<'a>(group: &'a XsdGroup, elements: &mut Vec<&'a XsdElement>) {
+        for particle in &group.particles {
+            match particle {
+                GroupParticle::Element(ep) if ep.element_ref.is_none() => {
+                    if let Some(decl) = ep.element_decl.as_ref() {
+                        elements.push(decl);
+                    }
+                }
+                GroupParticle::Group(nested) => Self::collect_local_elements(nested, elements),
+                _ => {}
+            }
+        }
+    }
+
+    fn check_value_constraint(element: &XsdElement, messages: &mut Vec<String>) {
+        if element.default.is_some() && element.fixed.is_some() {
+            messages.push(format!(
+                "Element '{}' cannot have both a default and a fixed value",
+                element.name.local_name
+            ));
+        }
+        let simple_type = match &element.element_type {
+            ElementType::Simple(st) => Some(st),
+            ElementType::Complex(ct) => ct.simple_type(),
+            ElementType::Any => None,
+        };
+        let Some(simple_type) = simple_type else { return };
+        for (kind, value) in [("default", &element.default), ("fixed", &element.fixed)] {
+            let Some(value) = value else { continue };
+            if let Err(e) = simple_type.validate_value(value) {
+                messages.push(format!(
+                    "The {} value '{}' of element '{}' is not valid for its type: {}",
+                    kind, value, element.name.local_name, e
+                ));
+            }
+        }
+    }
+
+    fn check_enumeration(qname: &QName, simple_type: &dyn SimpleType, messages: &mut Vec<String>) {
+        let Some(enumeration) = &simple_type.facets().enumeration else { return };
+        if enumeration.values.is_empty() {
+            messages.push(format!("Enumeration facet of type '{}' has no values", qname.local_name));
+            return;
+        }
+        let Some(base_type) = SimpleType::base_type(simple_type) else { return };
+        for value in &enumeration.values {
+            if base_type.validate_value(value).is_err() {
+                messages.push(format!(
+                    "Enumeration value '{}' of type '{}' is not valid for its base type",
+                    value, qname.local_name
+                ));
+            }
+        }
+    }
+
+    /// Check that an explicitTimezone facet restricts a date/time type
+    fn check_explicit_timezone(qname: &QName, simple_type: &dyn SimpleType, messages: &mut Vec<String>) {
+        const TEMPORAL_TYPES: [&str; 9] =
+            ["dateTime", "dateTimeStamp", "time", "date", "gYearMonth", "gYear", "gMonthDay", "gDay", "gMonth"];
+        if simple_type.facets().explicit_timezone.is_none() {
+            return;
+        }
+        if !simple_type.primitive_name().is_some_and(|name| TEMPORAL_TYPES.contains(&name)) {
+            messages.push(format!(
+                "explicitTimezone facet of type '{}' only applies to date and time types",
+                qname.local_name
+            ));
+        }
+    }
+
+    /// Check that the elements of a restricted content model are allowed by the base type
+    ///
+    /// Only the element names are compared: an element is allowed when the
+    /// base content model declares it, or one of its substitution group heads,
+    /// or has an element wildcard. Bases with a missing ancestor are skipped.
+    fn check_content_restriction(&self, qname: &QName, ct: &XsdComplexType, messages: &mut Vec<String>) {
+        if ct.derivation != Some(DerivationMethod::Restriction) || ct.redefine.is_some() {
+            return;
+        }
+        let Some(base_name) = ct.base_type.as_ref().filter(|base| *base != qname) else { return };
+        let Some(GlobalType::Complex(base)) = self.maps.global_maps.types.get(base_name) else { return };
+        // The base content is incomplete when one of its ancestors is missing
+        if !self.is_derived_from(base_name, &QName::namespaced(XSD_NAMESPACE, XSD_ANY_TYPE)) {
+            return;
+        }
+        let (Some(group), Some(base_group)) = (ct.model_group(), base.model_group()) else { return };
+
+        let mut base_names = HashSet::new();
+        if !Self::collect_element_names(base_group, &mut base_names) {
+            return;
+        }
+        let mut names = HashSet::new();
+        Self::collect_element_names(group, &mut names);
+        let mut names: Vec<&QName> = names.into_iter().collect();
+        names.sort_by(|a, b| qname_order(a, b));
+        for name in names {
+            let allowed = base_names.contains(name)
+                || self.substitution_heads(name).iter().any(|head| base_names.contains(head));
+            if !allowed {
+                messages.push(format!(
+                    "Element '{}' of type '{}' is not allowed by the content model of its base type '{}'",
+                    name.local_name, qname.local_name, base_name.local_name
+                ));
+            }
+        }
+    }
+
+    /// Collect the element names of a model group, returning false if it has an element wildcard
+    fn collect_element_names<'a>(group: &'a XsdGroup, names: &mut HashSet<&'a QName>) -> bool {
+        let mut closed = true;
+        for particle in &group.particles {
+            match particle {
+                GroupParticle::Element(ep) => {
+                    names.insert(&ep.name);
+                }
+                GroupParticle::Group(nested) => closed &= Self::collect_element_names(nested, names),
+                GroupParticle::Any(_) => closed = false,
+            }
+        }
+        closed
+    }
+}
+
+impl Validator for XsdSchema {
+    fn is_built(&self) -> bool {
+        self.built
+    }
+
+    fn build(&mut self) -> Result<()> {
+        if self.built {
+            return Ok(());
+        }
+
+        // Register built-in types if not already done
+        if self.maps.global_maps.types.is_empty() {
+            self.register_builtins()?;
+        }
+
+        // Resolve attribute group references before derivations, so that
+        // derived types inherit the attributes of their base's groups
+        self.resolve_attribute_group_references();
+
+        // Resolve complex type derivations (extension/restriction)
+        // This merges base type content into derived types
+        self.resolve_complex_type_derivations();
+
+        // Resolve group references in complex types
+        // This resolves <xs:group ref="..."/> to their actual content
+        self.resolve_group_references();
+
+        // Resolve derivations for inline element types AFTER group resolution
+        // This ensures the base types have fully resolved content
+        self.resolve_inline_element_type_derivations();
+
+        // Resolve type references in global elements
+        self.resolve_element_types();
+
+        // Resolve element types in complex type content models (forward references)
+        self.resolve_element_particle_types();
+
+        // Resolve attribute types in complex types (forward references)
+        self.resolve_attribute_types();
+
+        // Refresh global element types with the fully resolved versions from global_maps.types
+        self.refresh_element_types();
+
+        // Validate redefinitions have proper self-references
+        self.validate_redefinitions();
+
+        // Check the structural constraints of the resolved components
+        for error in self.validate_schema_constraints() {
+            self.parse_error(error);
+        }
+
+        // Report ambiguous content models without rejecting the schema
+        self.warnings = self
+            .check_ambiguity()
+            .violations
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect();
+
+        // XSD 1.0 requires deterministic content models
+        if !self.is_xsd11() {
+            let errors: Vec<ParseError> = self
+                .complex_types_with_anonymous()
+                .into_iter()
+                .filter_map(|(qname, ct)| {
+                    let error = check_upa(ct.model_group()?).err()?;
+                    Some(ParseError::new(format!(
+                        "Content model of '{}' violates Unique Particle Attribution: {}",
+                        qname.to_string(), error
+                    )))
+                })
+                .collect();
+            for error in errors {
+                self.parse_error(error);
+            }
+        }
+
+        // Index attribute and type uses for reverse lookups
+        self.usage_index = Some(self.build_usage_index());
+
+        // Mark as built
+        self.built = true;
+        Ok(())
+    }
+
+    fn validation_attempted(&self) -> ValidationStatus {
+        if self.built {
+            ValidationStatus::Full
+        } else {
+            ValidationStatus::None
+        }
+    }
+
+    fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    fn errors(&self) -> Vec<ParseError> {
+        self.errors.clone()
+    }
+}
+
+/// A namespace view provides access to global declarations within a specific namespace
+#[derive(Debug)]
+pub struct NamespaceView<'a> {
+    schema: &'a XsdSchema,
+    namespace: Option<&'a str>,
+}
+
+impl<'a> NamespaceView<'a> {
+    /// Create a new namespace view
+    pub fn new(schema: &'a XsdSchema, namespace: Option<&'a str>) -> Self {
+        Self { schema, namespace }
+    }
+
+    /// Check if a QName matches this namespace
+    fn matches_namespace(&self, qname: &QName) -> bool {
+        qname.namespace.as_ref().map(|n| n.as_str()) == self.namespace
+    }
+
+    /// Schemas that may declare components of this namespace
+    ///
+    /// These are the schema of the view and the loaded schemas whose target
+    /// namespace is the namespace of the view, such as imported schemas.
+    fn sources(&self) -> Vec<&'a XsdSchema> {
+        let root = self.schema;
+        root.all_loaded_schemas()
+            .into_iter()
+            .filter(|schema| std::ptr::eq(*schema, root) || schema.target_namespace.as_deref() == self.namespace)
+            .collect()
+    }
+
+    /// Get elements in this namespace
+    pub fn elements(&self) -> impl Iterator<Item = (&QName, &Arc<XsdElement>)> + '_ {
+        let mut seen = HashSet::new();
+        self.sources()
+            .into_iter()
+            .flat_map(|schema| schema.elements())
+            .filter(move |(name, _)| self.matches_namespace(name) && seen.insert(*name))
+    }
+
+    /// Get types in this namespace
+    pub fn types(&self) -> impl Iterator<Item = (&QName, &GlobalType)> + '_ {
+        let mut seen = HashSet::new();
+        self.sources()
+            .into_iter()
+            .flat_map(|schema| schema.types())
+            .filter(move |(name, _)| self.matches_namespace(name) && seen.insert(*name))
+    }
+
+    /// Get attributes in this namespace
+    pub fn attributes(&self) -> impl Iterator<Item = (&QName, &Arc<XsdAttribute>)> + '_ {
+        let mut seen = HashSet::new();
+        self.sources()
+            .into_iter()
+            .flat_map(|schema| schema.attributes())
+            .filter(move |(name, _)| self.matches_namespace(name) && seen.insert(*name))
+    }
+
+    /// Get groups in this namespace
+    pub fn groups(&self) -> impl Iterator<Item = (&QName, &Arc<XsdGroup>)> + '_ {
+        let mut seen = HashSet::new();
+        self.sources()
+            .into_iter()
+            .flat_map(|schema| schema.groups())
+            .filter(move |(name, _)| self.matches_namespace(name) && seen.insert(*name))
+    }
+
+    /// Get attribute groups in this namespace
+    pub fn attribute_groups(&self) -> impl Iterator<Item = (&QName, &Arc<XsdAttributeGroup>)> + '_ {
+        let mut seen = HashSet::new();
+        self.sources()
+            .into_iter()
+            .flat_map(|schema| schema.attribute_groups())
+            .filter(move |(name, _)| self.matches_namespace(name) && seen.insert(*name))
+    }
+
+    /// Validate a document whose root element is declared in this namespace
+    ///
+    /// The root element must match a global element of the view, and is
+    /// validated against that declaration only. This checks fragments of a
+    /// foreign namespace, such as MathML embedded in a DITA topic, with the
+    /// components imported for that namespace.
+    pub fn validate_all(&self, doc: &Document) -> ValidationResult {
+        let Some(root) = &doc.root else {
+            return ValidationResult::invalid(vec!["Document has no root element".to_string()]);
+        };
+        let root_qname = resolve_element_qname(root, self.schema);
+        // Prefer the schemas of the namespace, so that the declaration is
+        // validated against the schema it comes from
+        let sources = self.sources();
+        let declaring = sources
+            .iter()
+            .filter(|schema| schema.target_namespace.as_deref() == self.namespace)
+            .chain(sources.iter())
+            .find_map(|&schema| {
+                schema
+                    .elements()
+                    .find(|(name, _)| self.matches_namespace(name) && **name == root_qname)
+                    .map(|(_, decl)| (schema, decl))
+            });
+        let Some((schema, decl)) = declaring else {
+            return ValidationResult::invalid(vec![format!(
+                "No global element '{}' in namespace '{}'",
+                root_qname.local_name,
+                self.namespace.unwrap_or("")
+            )]);
+        };
+
+        let mut context = ValidationContext::new().with_mode(self.schema.validation);
+        let outcome = validate_element(schema, root, decl, &mut context).and_then(|()| context.check_idrefs());
+        XsdSchema::validation_result(&context, outcome)
+    }
+}
+
+/// Content that every instance of an element must have
+///
+/// See [`XsdSchema::required_content`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequiredContent {
+    /// Child elements with an effective `minOccurs > 0`, with their
+    /// effective occurrence bounds
+    pub required_elements: Vec<(QName, Occurs)>,
+    /// Attributes declared with `use="required"`
+    pub required_attributes: Vec<(QName, AttributeUse)>,
+}
+
+/// What a type accepts, for introspection
+///
+/// See [`XsdSchema::explain_type`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeExplanation {
+    /// Name of the type
+    pub name: QName,
+    /// Whether the type is a simple, complex or built-in type
+    pub kind: TypeKind,
+    /// Name of the type this type is derived from
+    pub base_type: Option<QName>,
+    /// Names of the type and of its named base types, up to the root
+    /// of the derivation
+    pub derivation_chain: Vec<QName>,
+    /// Child elements of the content model, with their effective
+    /// occurrence bounds
+    pub allowed_content: Vec<ElementSummary>,
+    /// Attributes of the type, prohibited ones left out
+    pub attributes: Vec<AttributeSummary>,
+    /// Facets in effect, including the inherited ones, e.g. `length=13`
+    pub facets: Vec<String>,
+    /// xs:documentation texts of the type
+    pub documentation: Vec<String>,
+}
+
+/// Kind of an explained type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeKind {
+    /// Simple type defined in a schema
+    Simple,
+    /// Complex type defined in a schema
+    Complex,
+    /// Built-in XSD type
+    Builtin,
+}
+
+impl fmt::Display for TypeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeKind::Simple => write!(f, "simple"),
+            TypeKind::Complex => write!(f, "complex"),
+            TypeKind::Builtin => write!(f, "builtin"),
+        }
+    }
+}
+
+/// A child element accepted by a type, see [`TypeExplanation`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementSummary {
+    /// Name of the element
+    pub name: QName,
+    /// Name of the element's type, `None` for anonymous types
+    pub type_name: Option<QName>,
+    /// Effective occurrence bounds in the content model
+    pub occurs: Occurs,
+}
+
+/// An attribute accepted by a type, see [`TypeExplanation`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeSummary {
+    /// Name of the attribute
+    pub name: QName,
+    /// Name of the attribute's type, `None` for anonymous types
+    pub type_name: Option<QName>,
+    /// Whether the attribute is declared with `use="required"`
+    pub required: bool,
+}
+
+/// Content that instances of an element may leave out
+///
+/// See [`XsdSchema::optional_content`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OptionalContent {
+    /// Child elements with an effective `minOccurs = 0`, with their
+    /// effective occurrence bounds
+    pub optional_elements: Vec<(QName, Occurs)>,
+    /// Attributes declared with `use="optional"`
+    pub optional_attributes: Vec<(QName, AttributeUse)>,
+}
+
+/// Schema validation result
+#[derive(Debug, Clone)]
+pub struct ValidationResult {
+    /// Whether validation succeeded
+    pub valid: bool,
+    /// Validation errors
+    pub errors: Vec<exceptions::ValidationError>,
+    /// Validation warnings
+    pub warnings: Vec<String>,
+}
+
+impl ValidationResult {
+    /// Create a valid result
+    pub fn valid() -> Self {
+        Self {
+            valid: true,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Create an invalid result with error messages
+    pub fn invalid(errors: Vec<String>) -> Self {
+        Self::with_errors(errors.into_iter().map(exceptions::ValidationError::new).collect())
+    }
+
+    /// Create an invalid result with structured errors
+    pub fn with_errors(errors: Vec<exceptions::ValidationError>) -> Self {
+        Self {
+            valid: false,
+            errors,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Get the messages of the errors
+    pub fn error_messages(&self) -> Vec<&str> {
+        self.errors.iter().map(|e| e.message.as_str()).collect()
+    }
+
+    /// Add a warning
+    pub fn add_warning(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    /// Add an error
+    pub fn add_error(&mut self, error: exceptions::ValidationError) {
+        self.valid = false;
+        self.errors.push(error);
+    }
+
+    /// Merge the errors and warnings of another result into this one
+    ///
+    /// The merged result is valid only if both results were.
+    pub fn merge(&mut self, other: ValidationResult) {
+        self.valid &= other.valid;
+        self.errors.extend(other.errors);
+        self.warnings.extend(other.warnings);
+    }
+}
+
+/// Result of a lax validation, see [`XsdSchema::validate_lax`]
+#[derive(Debug, Clone)]
+pub struct LaxValidationResult {
+    /// Errors and warnings of the validation
+    pub result: ValidationResult,
+    /// Names of the elements whose validation was skipped, in document order
+    pub skipped: Vec<String>,
+}
+
+/// Reverse index of the uses of attributes and types, see [`XsdSchema::find_types_using_attribute`]
+#[derive(Debug, Clone, Default)]
+struct UsageIndex {
+    /// Global complex types using each attribute
+    types_by_attribute: HashMap<QName, Vec<QName>>,
+    /// Element declarations of each named type
+    elements_by_type: HashMap<QName, Vec<QName>>,
+}
+
+/// Kind of namespace usage problem in an instance document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamespaceUsageKind {
+    /// An element is in a namespace the schema neither targets nor imports
+    UnknownNamespace,
+    /// A prefix is used without being declared in scope
+    UndeclaredPrefix,
+    /// A namespace declaration rebinds a prefix of an outer declaration
+    ShadowedDeclaration,
+}
+
+/// A namespace usage problem found by [`XsdSchema::validate_xml_namespace_usage`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceUsageError {
+    /// Kind of problem
+    pub kind: NamespaceUsageKind,
+    /// Description of the problem
+    pub message: String,
+    /// Path of the element where the problem was found
+    pub path: String,
+}
+
+impl NamespaceUsageError {
+    /// Create a namespace usage error
+    pub fn new(kind: NamespaceUsageKind, message: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            path: path.into(),
+        }
+    }
+}
+
+impl fmt::Display for NamespaceUsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at {})", self.message, self.path)
+    }
+}
+
+/// Kind of schema lint warning, see [`XsdSchema::lint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    /// `minInclusive` equals `maxInclusive`, so only one value is valid
+    SingleValueRange,
+    /// `length` is combined with `minLength` or `maxLength`
+    RedundantLengthFacets,
+    /// An abstract type has no concrete subtypes
+    UnreachableAbstractType,
+    /// A global element isn't referenced in any content model
+    UnusedElement,
+    /// An attribute group only has one attribute
+    SingleAttributeGroup,
+}
+
+/// A schema quality warning found by [`XsdSchema::lint`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintWarning {
+    /// Kind of warning
+    pub kind: LintKind,
+    /// Name of the schema component the warning is about
+    pub component: QName,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl LintWarning {
+    /// Create a lint warning
+    pub fn new(kind: LintKind, component: QName, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            component,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Result of [`XsdSchema::lint`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaLintReport {
+    /// Warnings found, grouped by kind
+    pub warnings: Vec<LintWarning>,
+}
+
+impl SchemaLintReport {
+    /// Check if no warning was found
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    /// Iterate over the warnings of a kind
+    pub fn of_kind(&self, kind: LintKind) -> impl Iterator<Item = &LintWarning> {
+        self.warnings.iter().filter(move |warning| warning.kind == kind)
+    }
+}
+
+/// Schema collection for managing multiple schemas
+///
+/// This is useful for handling imports and includes across namespaces.
+#[derive(Debug, Default)]
+pub struct SchemaCollection {
+    /// Schemas indexed by target namespace
+    pub schemas: HashMap<Option<String>, Vec<Arc<XsdSchema>>>,
+    /// Primary schema (first loaded)
+    pub primary: Option<Arc<XsdSchema>>,
+}
+
+impl SchemaCollection {
+    /// Create a new empty collection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a schema to the collection
+    pub fn add(&mut self, schema: Arc<XsdSchema>) {
+        let namespace = schema.target_namespace.clone();
+        if self.primary.is_none() {
+            self.primary = Some(Arc::clone(&schema));
+        }
+        self.schemas.entry(namespace).or_default().push(schema);
+    }
+
+    /// Get schemas for a namespace
+    pub fn get(&self, namespace: Option<&str>) -> Option<&Vec<Arc<XsdSchema>>> {
+        self.schemas.get(&namespace.map(|s| s.to_string()))
+    }
+
+    /// Get the primary schema
+    pub fn primary(&self) -> Option<&Arc<XsdSchema>> {
+        self.primary.as_ref()
+    }
+
+    /// Check if a namespace is loaded
+    pub fn has_namespace(&self, namespace: Option<&str>) -> bool {
+        self.schemas.contains_key(&namespace.map(|s| s.to_string()))
+    }
+
+    /// Get all loaded namespaces
+    pub fn namespaces(&self) -> impl Iterator<Item = Option<&String>> {
+        self.schemas.keys().map(|k| k.as_ref())
+    }
+
+    /// Get count of schemas
+    pub fn len(&self) -> usize {
+        self.schemas.values().map(|v| v.len()).sum()
+    }
+
+    /// Check if collection is empty
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+}
+
+/// Collect the global elements referenced in a model group
+fn collect_element_refs(group: &XsdGroup, referenced: &mut HashSet<QName>) {
+    for particle in &group.particles {
+        match particle {
+            GroupParticle::Element(ep) => {
+                if let Some(element_ref) = &ep.element_ref {
+                    referenced.insert(element_ref.clone());
+                }
+            }
+            GroupParticle::Group(nested) => collect_element_refs(nested, referenced),
+            GroupParticle::Any(_) => {}
+        }
+    }
+}
+
+/// Check whether a simple type restricts its base type without facets
+///
+/// A restricted type starts with the white space of its base type, which
+/// doesn't count as a facet of its own.
+fn is_alias_type(simple_type: &dyn SimpleType) -> bool {
+    let Some(base_type) = SimpleType::base_type(simple_type) else {
+        return false;
+    };
+    let mut facets = simple_type.facets().clone();
+    if facets.white_space == Some(base_type.white_space()) {
+        facets.white_space = None;
+    }
+    simple_type.variety() == SimpleTypeVariety::Atomic && facets.is_empty()
+}
+
+/// Name of a simple type, using the primitive name for anonymous built-in types
+fn simple_type_qname(simple_type: &dyn SimpleType) -> Option<QName> {
+    match (simple_type.name(), simple_type.is_builtin()) {
+        (Some(type_name), _) => Some(type_name.clone()),
+        (None, true) => simple_type.primitive_name().and_then(builtin_type_qname).cloned(),
+        (None, false) => None,
+    }
+}
+
+/// Describe the facets in effect for a simple type, e.g. `length=13`
+fn facet_descriptions(simple_type: &dyn SimpleType) -> Vec<String> {
+    let facets = simple_type.effective_facets();
+    let mut descriptions = Vec::new();
+    if let Some(enumeration) = &facets.enumeration {
+        descriptions.push(format!("enumeration of: {}", enumeration.values.join(", ")));
+    }
+    descriptions.extend(facets.describe_range());
+    descriptions.extend(facets.describe_constraints());
+    descriptions
+}
+
+/// Write a model group in the form of `XsdSchema::content_model_summary`
+fn summarize_group(group: &XsdGroup, depth: usize, max_depth: usize) -> String {
+    let items: Vec<String> = group
+        .particles
+        .iter()
+        .map(|particle| {
+            let item = match particle {
+                GroupParticle::Element(ep) => ep.name.local_name.clone(),
+                GroupParticle::Any(_) => "any".to_string(),
+                GroupParticle::Group(nested) if depth < max_depth => summarize_group(nested, depth + 1, max_depth),
+                GroupParticle::Group(_) => "(...)".to_string(),
+            };
+            format!("{}{}", item, occurs_suffix(particle.occurs()))
+        })
+        .collect();
+    match group.model {
+        ModelType::Sequence => format!("({})", items.join(", ")),
+        ModelType::Choice => format!("({})", items.join(" | ")),
+        ModelType::All => format!("{{{}}}", items.join(", ")),
+    }
+}
+
+/// The suffix of a particle in a content model summary, empty when it occurs once
+fn occurs_suffix(occurs: Occurs) -> String {
+    match (occurs.min, occurs.max) {
+        (1, Some(1)) => String::new(),
+        (0, Some(1)) => "?".to_string(),
+        (0, None) => "*".to_string(),
+        (1, None) => "+".to_string(),
+        (min, Some(max)) => format!("{{{},{}}}", min, max),
+        (min, None) => format!("{{{},}}", min),
+    }
+}
+
+/// Check whether two facet bounds have the same value
+fn same_bound(a: &NumericBound, b: &NumericBound) -> bool {
+    if let NumericBound::Temporal(value) = a {
+        return b.compare_lexical(value).is_some_and(|ordering| ordering.is_eq());
+    }
+    match b {
+        NumericBound::Integer(value) => a.compare_int(*value).is_eq(),
+        NumericBound::Decimal(value) => a.compare_decimal(value).is_eq(),
+        NumericBound::Float(value) => a.to_string().parse::<f64>().is_ok_and(|bound| bound == *value),
+        NumericBound::Temporal(value) => a.compare_lexical(value).is_some_and(|ordering| ordering.is_eq()),
+    }
+}
+
+/// Copy of an attribute declaration with another simple type
+fn retyped_attribute(attr: &XsdAttribute, simple_type: Arc<dyn SimpleType + Send + Sync>) -> XsdAttribute {
+    use super::base::AttributeValidator;
+
+    let mut new_attr = XsdAttribute::new(attr.name().clone());
+    new_attr.annotation = attr.annotation.clone();
+    new_attr.set_type(simple_type);
+    new_attr.set_use(attr.use_mode());
+    if let Some(default) = attr.default() {
+        let _ = new_attr.set_default(default.to_string());
+    }
+    if let Some(fixed) = attr.fixed_value() {
+        let _ = new_attr.set_fixed(fixed.to_string());
+    }
+    new_attr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::base::ValidityStatus;
+    use super::super::builtins::XSD_NAMESPACE;
+
+    #[test]
+    fn test_schema_creation() {
+        let schema = XsdSchema::new();
+        assert_eq!(schema.version, XsdVersion::Xsd10);
+        assert!(schema.target_namespace.is_none());
+        assert!(!schema.is_built());
+    }
+
+    #[test]
+    fn test_schema_with_version() {
+        let schema = XsdSchema::with_version(XsdVersion::Xsd11);
+        assert_eq!(schema.version, XsdVersion::Xsd11);
+        assert!(schema.is_xsd11());
+        assert_eq!(schema.xsd_version(), "1.1");
+    }
+
+    #[test]
+    fn test_schema_with_namespace() {
+        let schema = XsdSchema::with_namespace("http://example.com/test");
+        assert_eq!(schema.target_namespace.as_deref(), Some("http://example.com/test"));
+    }
+
+    #[test]
+    fn test_schema_with_version_and_namespace() {
+        let schema = XsdSchema::with_version_and_namespace(
+            XsdVersion::Xsd11,
+            "http://example.com/test",
+        );
+        assert_eq!(schema.version, XsdVersion::Xsd11);
+        assert_eq!(schema.target_namespace.as_deref(), Some("http://example.com/test"));
+    }
+
+    #[test]
+    fn test_form_default_parse() {
+        assert_eq!(FormDefault::from_str("qualified"), Some(FormDefault::Qualified));
+        assert_eq!(FormDefault::from_str("unqualified"), Some(FormDefault::Unqualified));
+        assert_eq!(FormDefault::from_str("invalid"), None);
+    }
+
+    #[test]
+    fn test_form_default_is_qualified() {
+        assert!(FormDefault::Qualified.is_qualified());
+        assert!(!FormDefault::Unqualified.is_qualified());
+    }
+
+    #[test]
+    fn test_derivation_default_parse() {
+        let dd = DerivationDefault::parse("#all");
+        assert!(dd.extension);
+        assert!(dd.restriction);
+        assert!(dd.substitution);
+
+        let dd = DerivationDefault::parse("extension restriction");
+        assert!(dd.extension);
+        assert!(dd.restriction);
+        assert!(!dd.substitution);
+
+        let dd = DerivationDefault::parse("");
+        assert!(dd.is_empty());
+    }
+
+    #[test]
+    fn test_schema_build() {
+        let mut schema = XsdSchema::new();
+        assert!(!schema.is_built());
+        assert!(schema.build().is_ok());
+        assert!(schema.is_built());
+    }
+
+    #[test]
+    fn test_schema_namespaces() {
+        let mut schema = XsdSchema::new();
+        schema.add_namespace("xs", XSD_NAMESPACE);
+        schema.add_namespace("tns", "http://example.com/test");
+
+        assert_eq!(schema.get_namespace("xs"), Some(XSD_NAMESPACE));
+        assert_eq!(schema.get_namespace("tns"), Some("http://example.com/test"));
+        assert_eq!(schema.get_namespace("unknown"), None);
+    }
+
+    #[test]
+    fn test_namespace_declarations() {
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           xmlns="http://example.com/test"
+           xmlns:tns="http://example.com/test"
+           xmlns:a="http://example.com/test"
+           targetNamespace="http://example.com/test">
+    <xs:element name="root" type="xs:string"/>
+</xs:schema>"#;
+        let mut schema = XsdSchema::from_string(xsd).unwrap();
+
+        let declarations = schema.namespace_declarations();
+        assert_eq!(declarations.get("xs").map(String::as_str), Some(XSD_NAMESPACE));
+        assert_eq!(declarations.get("").map(String::as_str), Some("http://example.com/test"));
+        assert_eq!(declarations.get("tns").map(String::as_str), Some("http://example.com/test"));
+
+        assert_eq!(schema.preferred_prefix_for(XSD_NAMESPACE), Some("xs"));
+        assert_eq!(schema.preferred_prefix_for("http://example.com/test"), Some("a"));
+        assert_eq!(schema.preferred_prefix_for("http://example.com/other"), None);
+        assert_eq!(schema.create_qname("root"), "a:root");
+
+        schema.add_preferred_prefix("tns", "http://example.com/test");
+        schema.add_preferred_prefix("o", "http://example.com/other");
+        assert_eq!(schema.preferred_prefix_for("http://example.com/test"), Some("tns"));
+        assert_eq!(schema.preferred_prefix_for("http://example.com/other"), Some("o"));
+        assert_eq!(schema.create_qname("root"), "tns:root");
+        // Registered prefixes do not change the schema's own bindings
+        assert_eq!(schema.get_namespace("o"), None);
+    }
+
+    #[test]
+    fn test_resolve_qname() {
+        let mut schema = XsdSchema::new();
+        schema.target_namespace = Some("http://example.com/default".to_string());
+        schema.add_namespace("tns", "http://example.com/test");
+        schema.add_namespace("xs", XSD_NAMESPACE);
+
+        // Prefixed QName
+        let (ns, local) = schema.resolve_qname("tns:element");
+        assert_eq!(ns, Some("http://example.com/test"));
+        assert_eq!(local, "element");
+
+        // Unprefixed QName uses target namespace
+        let (ns, local) = schema.resolve_qname("element");
+        assert_eq!(ns, Some("http://example.com/default"));
+        assert_eq!(local, "element");
+    }
+
+    #[test]
+    fn test_schema_imports() {
+        let mut schema = XsdSchema::new();
+
+        schema.add_import(
+            "http://example.com/imported".to_string(),
+            Some("imported.xsd".to_string()),
+        );
+
+        assert!(schema.has_import("http://example.com/imported"));
+        assert!(!schema.has_import("http://example.com/unknown"));
+
+        let import = schema.get_import("http://example.com/imported").unwrap();
+        assert_eq!(import.namespace, "http://example.com/imported");
+        assert_eq!(import.location.as_deref(), Some("imported.xsd"));
+    }
+
+    #[test]
+    fn test_resolve_form() {
+        let mut schema = XsdSchema::new();
+        schema.element_form_default = FormDefault::Qualified;
+        schema.attribute_form_default = FormDefault::Unqualified;
+
+        // Default forms
+        assert!(schema.resolve_element_form(None).is_qualified());
+        assert!(!schema.resolve_attribute_form(None).is_qualified());
+
+        // Explicit forms override defaults
+        assert!(!schema.resolve_element_form(Some(FormDefault::Unqualified)).is_qualified());
+        assert!(schema.resolve_attribute_form(Some(FormDefault::Qualified)).is_qualified());
+    }
+
+    #[test]
+    fn test_validation_result() {
+        let valid = ValidationResult::valid();
+        assert!(valid.valid);
+        assert!(valid.errors.is_empty());
+
+        let invalid = ValidationResult::invalid(vec!["test error".to_string()]);
+        assert!(!invalid.valid);
+        assert_eq!(invalid.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_validation_result_modifications() {
+        let mut result = ValidationResult::valid();
+        assert!(result.valid);
+
+        result.add_warning("Warning!".to_string());
+        assert!(result.valid);
+        assert_eq!(result.warnings.len(), 1);
+
+        result.add_error(exceptions::ValidationError::new("Error!"));
+        assert!(!result.valid);
+        assert_eq!(result.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_schema_collection() {
+        let mut collection = SchemaCollection::new();
+        assert!(collection.is_empty());
+        assert_eq!(collection.len(), 0);
+
+        let schema1 = Arc::new(XsdSchema::with_namespace("http://example.com/a"));
+        let schema2 = Arc::new(XsdSchema::with_namespace("http://example.com/b"));
+        let schema3 = Arc::new(XsdSchema::with_namespace("http://example.com/a"));
+
+        collection.add(Arc::clone(&schema1));
+        assert_eq!(collection.len(), 1);
+        assert!(collection.primary().is_some());
+
+        collection.add(Arc::clone(&schema2));
+        collection.add(Arc::clone(&schema3));
+        assert_eq!(collection.len(), 3);
+
+        assert!(collection.has_namespace(Some("http://example.com/a")));
+        assert!(collection.has_namespace(Some("http://example.com/b")));
+        assert!(!collection.has_namespace(Some("http://example.com/c")));
+
+        let schemas_a = collection.get(Some("http://example.com/a")).unwrap();
+        assert_eq!(schemas_a.len(), 2);
+    }
+
+    #[test]
+    fn test_namespace_view() {
+        let mut schema = XsdSchema::new();
+        schema.set_target_namespace(Some("http://example.com/test".to_string()));
+        schema.add_namespace("tns", "http://example.com/test");
+
+        let view = NamespaceView::new(&schema, Some("http://example.com/test"));
+        // View should filter correctly (empty since no elements registered)
+        assert_eq!(view.elements().count(), 0);
+    }
+
+    #[test]
+    fn test_namespace_view_validate_all() {
+        let main_xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           xmlns:m="http://example.com/math"
+           targetNamespace="http://example.com/doc" elementFormDefault="qualified">
+    <xs:import namespace="http://example.com/math" schemaLocation="math.xsd"/>
+    <xs:element name="doc">
+        <xs:complexType>
+            <xs:sequence>
+                <xs:element name="title" type="xs:string"/>
+                <xs:element ref="m:formula" maxOccurs="unbounded"/>
+            </xs:sequence>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>"#;
+        let math_xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           targetNamespace="http://example.com/math" elementFormDefault="qualified">
+    <xs:element name="formula">
+        <xs:complexType>
+            <xs:sequence>
+                <xs:element name="n" type="xs:integer" maxOccurs="unbounded"/>
+            </xs:sequence>
+            <xs:attribute name="op" type="xs:string" use="required"/>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_sources(&[(main_xsd, "main.xsd"), (math_xsd, "math.xsd")]).unwrap();
+        let math = NamespaceView::new(&schema, Some("http://example.com/math"));
+        assert_eq!(math.elements().count(), 1);
+
+        let doc = Document::from_string(
+            r#"<doc xmlns="http://example.com/doc"><title>Sums</title><formula xmlns="http://example.com/math" op="add"><n>1</n><n>2</n></formula><formula xmlns="http://example.com/math"><n>x</n></formula></doc>"#,
+        )
+        .unwrap();
+        let fragment = |index: usize| {
+            let mut fragment = Document::new();
+            fragment.root = doc.root.as_ref().map(|root| root.children[index + 1].clone());
+            fragment
+        };
+
+        assert!(math.validate_all(&fragment(0)).valid);
+        assert!(!math.validate_all(&fragment(1)).valid);
+
+        // Elements of other namespaces aren't in the view
+        assert!(!math.validate_all(&doc).valid);
+        // The invalid formula makes the whole document invalid
+        let doc_view = NamespaceView::new(&schema, Some("http://example.com/doc"));
+        assert!(!doc_view.validate_all(&doc).valid);
+        let valid_doc = Document::from_string(
+            r#"<doc xmlns="http://example.com/doc"><title>Sums</title><formula xmlns="http://example.com/math" op="add"><n>1</n></formula></doc>"#,
+        )
+        .unwrap();
+        assert!(doc_view.validate_all(&valid_doc).valid);
+    }
+
+    #[test]
+    fn test_validator_trait() {
+        let schema = XsdSchema::new();
+        assert_eq!(schema.validation, ValidationMode::Strict);
+        assert!(!schema.has_errors());
+        assert!(schema.errors().is_empty());
+        assert_eq!(schema.validation_attempted(), ValidationStatus::None);
+    }
+
+    #[test]
+    fn test_validation_status() {
+        let schema = XsdSchema::new();
+        assert_eq!(schema.validation_attempted(), ValidationStatus::None);
+        assert_eq!(schema.validity(ValidationMode::Strict), ValidityStatus::NotKnown);
+
+        let mut schema = XsdSchema::new();
+        schema.build().unwrap();
+        assert_eq!(schema.validation_attempted(), ValidationStatus::Full);
+        assert_eq!(schema.validity(ValidationMode::Strict), ValidityStatus::Valid);
+
+        let mut schema = XsdSchema::new();
+        schema.parse_error(ParseError::new("test error"));
+        assert_eq!(schema.validity(ValidationMode::Strict), ValidityStatus::Invalid);
+    }
+
+    #[test]
+    fn test_schema_source() {
+        let source = SchemaSource {
+            url: Some("http://example.com/schema.xsd".to_string()),
+            base_url: Some("http://example.com/".to_string()),
+            namespaces: HashMap::new(),
+            ..Default::default()
+        };
+
+        assert_eq!(source.url.as_deref(), Some("http://example.com/schema.xsd"));
+        assert_eq!(source.base_url.as_deref(), Some("http://example.com/"));
+    }
+
+    #[test]
+    fn test_schema_accessors() {
+        let mut schema = XsdSchema::new();
+        schema.source.url = Some("http://example.com/schema.xsd".to_string());
+        schema.source.base_url = Some("http://example.com/".to_string());
+
+        assert_eq!(schema.url(), Some("http://example.com/schema.xsd"));
+        assert_eq!(schema.base_url(), Some("http://example.com/"));
+    }
+
+    #[test]
+    fn test_create_qname() {
+        let mut schema = XsdSchema::new();
+        schema.set_target_namespace(Some("http://example.com/test".to_string()));
+        schema.add_namespace("tns", "http://example.com/test");
+
+        let qname = schema.create_qname("element");
+        assert_eq!(qname, "tns:element");
+
+        // Without matching namespace prefix
+        let mut schema2 = XsdSchema::new();
+        schema2.set_target_namespace(Some("http://example.com/test".to_string()));
+        let qname2 = schema2.create_qname("element");
+        assert_eq!(qname2, "element");
+    }
+
+    #[test]
+    fn test_empty_counts() {
+        let schema = XsdSchema::new();
+        assert_eq!(schema.element_count(), 0);
+        assert_eq!(schema.type_count(), 0);
+        assert_eq!(schema.element_names().count(), 0);
+        assert_eq!(schema.type_names().count(), 0);
+    }
+
+    #[test]
+    fn test_redefine_group_basic() {
+        // Test basic xs:redefine parsing with a group that references itself
+        // This tests that:
+        // 1. xs:redefine is parsed correctly
+        // 2. The redefined group has the redefine back-reference set
+        // 3. Self-references in the group are resolved to the original
+
+        // Create a temporary test schema with redefine
+        use std::io::Write;
+
+        // First create the "base" schema that will be redefined
+        let base_schema = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:group name="testGroup">
+        <xs:sequence>
+            <xs:element name="originalElement" type="xs:string"/>
+        </xs:sequence>
+    </xs:group>
+</xs:schema>"#;
+
+        // Create the "main" schema that redefines the group
+        let main_schema = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:redefine schemaLocation="base.xsd">
+        <xs:group name="testGroup">
+            <xs:sequence>
+                <xs:element name="newElement" type="xs:string"/>
+                <xs:group ref="testGroup"/>
+            </xs:sequence>
+        </xs:group>
+    </xs:redefine>
+
+    <xs:element name="root">
+        <xs:complexType>
+            <xs:group ref="testGroup"/>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>"#;
+
+        // Write temp files
+        let temp_dir = std::env::temp_dir().join("xmlschema_test_redefine");
+        std::fs::create_dir_all(&temp_dir).expect("Failed to create temp dir");
+
+        let base_path = temp_dir.join("base.xsd");
+        let main_path = temp_dir.join("main.xsd");
+
+        let mut base_file = std::fs::File::create(&base_path).expect("Failed to create base.xsd");
+        base_file.write_all(base_schema.as_bytes()).expect("Failed to write base.xsd");
+
+        let mut main_file = std::fs::File::create(&main_path).expect("Failed to create main.xsd");
+        main_file.write_all(main_schema.as_bytes()).expect("Failed to write main.xsd");
+
+        // Parse the main schema (which includes the redefine)
+        let result = XsdSchema::from_file(&main_path);
+
+        // Clean up temp files
+        let _ = std::fs::remove_file(&base_path);
+        let _ = std::fs::remove_file(&main_path);
+        let _ = std::fs::remove_dir(&temp_dir);
+
+        // Check results
+        let schema = result.expect("Failed to parse schema with redefine");
+
+        // Verify the schema has the redefine recorded
+        assert!(!schema.redefines.is_empty(), "Schema should have redefines");
+
+        // Verify the testGroup exists and has the redefine back-reference
+        let test_group_name = crate::namespaces::QName::local("testGroup");
+        let group = schema.maps.global_maps.groups.get(&test_group_name);
+        assert!(group.is_some(), "testGroup should exist in global maps");
+
+        let group = group.unwrap();
+        assert!(group.redefine.is_some(), "Redefined group should have redefine back-reference");
+
+        // Verify the group has content (particles from self-reference resolution)
+        assert!(!group.particles.is_empty(), "Redefined group should have particles after resolution");
+    }
+
+    const PERSON_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:complexType name="personType">
+    <xs:sequence>
+      <xs:element name="name" type="xs:string"/>
+    </xs:sequence>
+    <xs:attribute name="id" type="xs:string"/>
+  </xs:complexType>
+  <xs:simpleType name="codeType">
+    <xs:restriction base="xs:string">
+      <xs:pattern value="[A-Z]{3}"/>
+    </xs:restriction>
+  </xs:simpleType>
+</xs:schema>"#;
+
+    fn derive_employee(schema: &XsdSchema) -> XsdComplexType {
+        use super::super::attributes::AttributeUse;
+        use super::super::particles::Occurs;
+
+        schema
+            .derive_extension(&QName::local("personType"), |builder| {
+                builder
+                    .set_name(QName::local("employeeType"))
+                    .add_sequence_element(
+                        QName::local("salary"),
+                        QName::namespaced(XSD_NAMESPACE, "integer"),
+                        Occurs::once(),
+                    )
+                    .add_sequence_element(QName::local("dept"), QName::local("codeType"), Occurs::optional())
+                    .add_attribute(
+                        QName::local("badge"),
+                        QName::namespaced(XSD_NAMESPACE, "integer"),
+                        AttributeUse::Required,
+                    )
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_derive_extension_structure() {
+        let schema = XsdSchema::from_string(PERSON_XSD).unwrap();
+        let ct = derive_employee(&schema);
+
+        assert_eq!(ct.name, Some(QName::local("employeeType")));
+        assert_eq!(ct.base_type, Some(QName::local("personType")));
+        assert_eq!(ct.derivation, Some(DerivationMethod::Extension));
+
+        // Base content followed by the extension sequence
+        let group = ct.model_group().unwrap();
+        assert_eq!(group.particles.len(), 2);
+        match &group.particles[1] {
+            GroupParticle::Group(ext) => assert_eq!(ext.particles.len(), 2),
+            other => panic!("expected extension group, got {:?}", other),
+        }
+
+        // Inherited and added attributes
+        assert!(ct.attributes.get_attribute(&QName::local("id")).is_some());
+        let badge = ct.attributes.get_attribute(&QName::local("badge")).unwrap();
+        assert!(badge.simple_type().is_some());
+    }
+
+    #[test]
+    fn test_derive_extension_validates_documents() {
+        let mut schema = XsdSchema::from_string(PERSON_XSD).unwrap();
+        let ct = Arc::new(derive_employee(&schema));
+        schema.maps.global_maps.elements.insert(
+            QName::local("employee"),
+            Arc::new(XsdElement::complex(QName::local("employee"), ct)),
+        );
+
+        assert!(schema.is_valid_string(
+            r#"<employee id="e1" badge="7"><name>Ann</name><salary>100</salary><dept>ENG</dept></employee>"#
+        ));
+        assert!(schema.is_valid_string(
+            r#"<employee badge="7"><name>Ann</name><salary>100</salary></employee>"#
+        ));
+        // Missing the required attribute added by the extension
+        assert!(!schema.is_valid_string(
+            r#"<employee><name>Ann</name><salary>100</salary></employee>"#
+        ));
+        // Extension element type is enforced
+        assert!(!schema.is_valid_string(
+            r#"<employee badge="7"><name>Ann</name><salary>lots</salary></employee>"#
+        ));
+        // Named simple types from the schema are enforced
+        assert!(!schema.is_valid_string(
+            r#"<employee badge="7"><name>Ann</name><salary>100</salary><dept>eng</dept></employee>"#
+        ));
+        // Base content must come first
+        assert!(!schema.is_valid_string(
+            r#"<employee badge="7"><salary>100</salary><name>Ann</name></employee>"#
+        ));
+    }
+
+    #[test]
+    fn test_derive_extension_errors() {
+        use super::super::complex_types::DerivationFlags;
+
+        let mut schema = XsdSchema::from_string(PERSON_XSD).unwrap();
+        let sealed = ComplexTypeBuilder::new()
+            .set_name(QName::local("sealedType"))
+            .final_deriv(DerivationFlags::from_attr("extension"))
+            .build();
+        schema
+            .maps
+            .global_maps
+            .types
+            .insert(QName::local("sealedType"), GlobalType::Complex(Arc::new(sealed)));
+
+        assert!(schema.derive_extension(&QName::local("missingType"), |b| b).is_err());
+        assert!(schema.derive_extension(&QName::local("codeType"), |b| b).is_err());
+        assert!(schema.derive_extension(&QName::local("sealedType"), |b| b).is_err());
+
+        let result = schema.derive_extension(&QName::local("personType"), |b| {
+            b.add_sequence_element(
+                QName::local("x"),
+                QName::local("undefinedType"),
+                super::super::particles::Occurs::once(),
+            )
+        });
+        assert!(result.is_err());
+    }
+
+    const ORDER_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           targetNamespace="http://example.com/order"
+           elementFormDefault="qualified">
+  <xs:element name="order">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="id" type="xs:string"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+
+    #[test]
+    fn test_check_document_element() {
+        let schema = XsdSchema::from_string(ORDER_XSD).unwrap();
+
+        let doc = Document::from_string(r#"<order xmlns="http://example.com/order"><id>1</id></order>"#).unwrap();
+        let decl = schema.check_document_element(&doc).unwrap();
+        assert_eq!(decl.name.local_name, "order");
+
+        // Unknown local name in the target namespace
+        let doc = Document::from_string(r#"<invoice xmlns="http://example.com/order"/>"#).unwrap();
+        let err = schema.check_document_element(&doc).unwrap_err();
+        assert!(err.message.starts_with("Unknown root element"));
+        assert!(err.reason.unwrap().contains("No global element declaration"));
+
+        // Known local name in a foreign namespace
+        let doc = Document::from_string(r#"<order xmlns="http://example.com/other"/>"#).unwrap();
+        let err = schema.check_document_element(&doc).unwrap_err();
+        assert!(err.message.starts_with("Unknown root element"));
+        assert!(err.reason.unwrap().contains("http://example.com/other"));
+    }
+
+    #[test]
+    fn test_validate_reports_unknown_root_element() {
+        let schema = XsdSchema::from_string(ORDER_XSD).unwrap();
+
+        let doc = Document::from_string(r#"<invoice xmlns="http://example.com/order"><id>1</id></invoice>"#).unwrap();
+        let result = schema.validate_with_mode(&doc, ValidationMode::Lax);
+        assert!(!result.valid);
+        assert_eq!(result.errors.len(), 1);
+        assert!(result.errors[0].message.starts_with("Unknown root element"));
+
+        // A content model error is reported differently
+        let doc = Document::from_string(r#"<order xmlns="http://example.com/order"><name/></order>"#).unwrap();
+        let result = schema.validate_with_mode(&doc, ValidationMode::Lax);
+        assert!(!result.valid);
+        assert!(result.errors.iter().all(|e| !e.message.starts_with("Unknown root element")));
+    }
+
+    #[test]
+    fn test_validate_lax_lists_skipped_elements() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="note">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="to" type="xs:string"/>
+        <xs:any processContents="lax" minOccurs="0" maxOccurs="unbounded"/>
+      </xs:sequence>
+      <xs:attribute name="id" type="xs:integer"/>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+
+        let result = schema.validate_lax("<note><to>Ann</to><extra>1</extra><more/></note>");
+        assert!(result.result.valid);
+        assert_eq!(result.skipped, vec!["extra", "more"]);
+
+        let result = schema.validate_lax(r#"<note id="x"><to>Ann</to><extra/></note>"#);
+        assert!(!result.result.valid);
+        assert_eq!(result.skipped, vec!["extra"]);
+        assert!(result.result.errors.iter().all(|e| !e.message.contains("extra")));
+        assert!(!schema.is_lax_valid(r#"<note id="x"><to>Ann</to></note>"#));
+    }
+
+    const EDITOR_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="order">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="customer" type="customerType"/>
+        <xs:element name="note" type="xs:string" minOccurs="0"/>
+        <xs:element ref="payment"/>
+        <xs:group ref="shippingGroup"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+  <xs:element name="payment">
+    <xs:complexType>
+      <xs:choice>
+        <xs:element name="card" type="xs:string"/>
+        <xs:element name="cash" type="xs:decimal"/>
+      </xs:choice>
+    </xs:complexType>
+  </xs:element>
+  <xs:group name="shippingGroup">
+    <xs:sequence>
+      <xs:element name="address" type="xs:string"/>
+      <xs:element name="express" type="xs:boolean" minOccurs="0"/>
+    </xs:sequence>
+  </xs:group>
+  <xs:complexType name="customerType">
+    <xs:sequence>
+      <xs:element name="name" type="xs:string"/>
+      <xs:element name="email" type="xs:string" minOccurs="0" maxOccurs="unbounded"/>
+    </xs:sequence>
+  </xs:complexType>
+</xs:schema>"#;
+
+    fn local_names(names: Vec<&QName>) -> Vec<&str> {
+        names.into_iter().map(|qname| qname.local_name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_valid_elements_at_sequence() {
+        let schema = XsdSchema::from_string(EDITOR_XSD).unwrap();
+
+        // Required, optional, referenced and group-referenced elements
+        assert_eq!(
+            local_names(schema.valid_elements_at("/order")),
+            vec!["customer", "note", "payment", "address", "express"]
+        );
+        // Named type with an optional repeated element
+        assert_eq!(local_names(schema.valid_elements_at("/order/customer")), vec!["name", "email"]);
+    }
+
+    #[test]
+    fn test_valid_elements_at_choice() {
+        let schema = XsdSchema::from_string(EDITOR_XSD).unwrap();
+
+        assert_eq!(local_names(schema.valid_elements_at("/order/payment")), vec!["card", "cash"]);
+        assert_eq!(local_names(schema.valid_elements_at("payment")), vec!["card", "cash"]);
+    }
+
+    #[test]
+    fn test_valid_elements_at_leaf_and_unknown_paths() {
+        let schema = XsdSchema::from_string(EDITOR_XSD).unwrap();
+
+        assert!(schema.valid_elements_at("/order/note").is_empty());
+        assert!(schema.valid_elements_at("/order/missing").is_empty());
+        assert!(schema.valid_elements_at("/invoice").is_empty());
+        assert!(schema.valid_elements_at("").is_empty());
+        // Predicates and prefixes are ignored
+        assert_eq!(local_names(schema.valid_elements_at("/o:order[1]/o:customer[1]")), vec!["name", "email"]);
+    }
+
+    #[test]
+    fn test_is_valid_element_at() {
+        let schema = XsdSchema::from_string(EDITOR_XSD).unwrap();
+
+        assert!(schema.is_valid_element_at("/order", &QName::local("note")));
+        assert!(schema.is_valid_element_at("/order", &QName::local("express")));
+        assert!(schema.is_valid_element_at("/order/payment", &QName::local("cash")));
+        assert!(!schema.is_valid_element_at("/order", &QName::local("cash")));
+        assert!(!schema.is_valid_element_at("/order/customer", &QName::local("note")));
+
+        assert!(schema.is_valid_name_for_element("order"));
+        assert!(schema.is_valid_name_for_element("payment"));
+        assert!(!schema.is_valid_name_for_element("customer"));
+    }
+
+    #[test]
+    fn test_schema_for_namespace_import_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema_doc = |ns: &str, import: Option<(&str, &str)>, element: &str| {
+            let import = import
+                .map(|(ns, loc)| format!(r#"<xs:import namespace="{}" schemaLocation="{}"/>"#, ns, loc))
+                .unwrap_or_default();
+            format!(
+                r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="{}">
+  {}
+  <xs:element name="{}" type="xs:string"/>
+</xs:schema>"#,
+                ns, import, element
+            )
+        };
+        std::fs::write(
+            dir.path().join("a.xsd"),
+            schema_doc("urn:a", Some(("urn:b", "b.xsd")), "a"),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("b.xsd"),
+            schema_doc("urn:b", Some(("urn:c", "c.xsd")), "b"),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("c.xsd"), schema_doc("urn:c", None, "c")).unwrap();
+
+        let schema = XsdSchema::from_file(dir.path().join("a.xsd")).unwrap();
+
+        assert!(std::ptr::eq(schema.schema_for_namespace("urn:a").unwrap(), &schema));
+        let b = schema.schema_for_namespace("urn:b").unwrap();
+        assert!(b.lookup_element(&QName::namespaced("urn:b", "b")).is_some());
+        let c = schema.schema_for_namespace("urn:c").unwrap();
+        assert!(c.lookup_element(&QName::namespaced("urn:c", "c")).is_some());
+        assert!(schema.schema_for_namespace("urn:missing").is_none());
+
+        let namespaces: Vec<_> = schema
+            .all_loaded_schemas()
+            .iter()
+            .map(|s| s.target_namespace.as_deref().unwrap())
+            .collect();
+        assert_eq!(namespaces, vec!["urn:a", "urn:b", "urn:c"]);
+    }
+
+    #[test]
+    fn test_validation_result_merge() {
+        let mut result = ValidationResult::valid();
+        let mut other = ValidationResult::valid();
+        other.add_warning("w1".to_string());
+        result.merge(other);
+        assert!(result.valid);
+        assert_eq!(result.warnings, vec!["w1".to_string()]);
+
+        result.merge(ValidationResult::invalid(vec!["e1".to_string(), "e2".to_string()]));
+        result.merge(ValidationResult::valid());
+        assert!(!result.valid);
+        assert_eq!(result.error_messages(), vec!["e1", "e2"]);
+    }
+
+    #[test]
+    fn test_validate_arc_from_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<XsdSchema>();
+
+        let schema = Arc::new(XsdSchema::from_string(BOOK_XSD).unwrap());
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let schema = schema.clone_schema();
+                std::thread::spawn(move || {
+                    let category = if i % 2 == 0 { "fiction" } else { "poetry" };
+                    let doc = Document::from_string(&format!("<book><category>{}</category></book>", category))
+                        .unwrap();
+                    schema.validate_arc(&doc).valid
+                })
+            })
+            .collect();
+
+        let valid: Vec<bool> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+        assert_eq!(valid, [true, false, true, false, true, false, true, false]);
+        assert_eq!(Arc::strong_count(&schema), 1);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_validate_all() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<XsdSchema>();
+
+        let schema = XsdSchema::from_string(BOOK_XSD).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let mut paths = Vec::new();
+        for i in 0..20 {
+            let category = if i % 5 == 0 { "poetry" } else { "fiction" };
+            let path = dir.path().join(format!("book{:02}.xml", i));
+            std::fs::write(&path, format!("<book><category>{}</category></book>", category)).unwrap();
+            paths.push(path);
+        }
+
+        let result = schema.validate_all(paths.iter().map(PathBuf::as_path));
+        assert!(!result.valid);
+        assert_eq!(result.errors.len(), 4);
+        let files: Vec<String> = result
+            .errors
+            .iter()
+            .map(|error| {
+                let path = Path::new(error.message.split(": ").next().unwrap());
+                path.file_name().unwrap().to_string_lossy().into_owned()
+            })
+            .collect();
+        assert_eq!(files, vec!["book00.xml", "book05.xml", "book10.xml", "book15.xml"]);
+
+        let valid = paths.iter().enumerate().filter(|(i, _)| i % 5 != 0).map(|(_, path)| path.as_path());
+        assert!(schema.validate_all(valid).valid);
+
+        // Unreadable files are reported as invalid
+        let result = schema.validate_all([dir.path().join("missing.xml").as_path()]);
+        assert!(!result.valid);
+        assert!(result.errors[0].message.contains("Failed to read file"));
+    }
+
+    #[test]
+    fn test_validate_with_hints() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("notes.xsd"),
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:notes">
+  <xs:element name="note" type="xs:integer"/>
+</xs:schema>"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("main.xsd"),
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:main">
+  <xs:element name="main" type="xs:string"/>
+</xs:schema>"#,
+        )
+        .unwrap();
+        let schema = XsdSchema::from_file(dir.path().join("main.xsd")).unwrap();
+        let note = |value: &str, location: &str| {
+            format!(
+                r#"<note xmlns="urn:notes" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+      xsi:schemaLocation="urn:other other.xsd urn:notes {}">{}</note>"#,
+                location, value
+            )
+        };
+
+        // The hinted schema is resolved against the directory of the main schema
+        assert!(!schema.is_valid_string(&note("12", "notes.xsd")));
+        let result = schema.validate_string_with_hints(&note("12", "notes.xsd"));
+        assert!(result.valid, "{:?}", result.errors);
+        assert!(!schema.validate_string_with_hints(&note("twelve", "notes.xsd")).valid);
+
+        // A hint that cannot be loaded falls back to the main schema
+        let result = schema.validate_string_with_hints(&note("12", "missing.xsd"));
+        assert!(!result.valid);
+        assert!(result.warnings.iter().any(|w| w.contains("missing.xsd")), "{:?}", result.warnings);
 
-    /// Get types in this namespace
-    pub fn types(&self) -> impl Iterator<Item = (&QName, &GlobalType)> + '_ {
-        self.schema.types().filter(|(name, _)| self.matches_namespace(name))
+        // Documents of the main schema don't use the hints
+        let main = r#"<main xmlns="urn:main" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+      xsi:schemaLocation="urn:main missing.xsd">text</main>"#;
+        let result = schema.validate_string_with_hints(main);
+        assert!(result.valid && result.warnings.is_empty(), "{:?}", result);
     }
 
-    /// Get attributes in this namespace
-    pub fn attributes(&self) -> impl Iterator<Item = (&QName, &Arc<XsdAttribute>)> + '_ {
-        self.schema.attributes().filter(|(name, _)| self.matches_namespace(name))
+    #[test]
+    fn test_validate_assertions() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:complexType name="range">
+    <xs:attribute name="min" type="xs:integer" use="required"/>
+    <xs:attribute name="max" type="xs:integer" use="required"/>
+    <xs:assert test="@min &lt;= @max"/>
+  </xs:complexType>
+  <xs:complexType name="boundedRange">
+    <xs:complexContent>
+      <xs:extension base="range">
+        <xs:assert test="@max - @min le 100"/>
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:element name="range" type="range"/>
+  <xs:element name="bounded" type="boundedRange"/>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let bounded = schema.lookup_type(&QName::local("boundedRange")).and_then(GlobalType::as_complex).unwrap();
+        assert_eq!(bounded.assertions.len(), 2);
+
+        // The values are compared as numbers, not as strings
+        assert!(schema.is_valid_string(r#"<range min="9" max="10"/>"#));
+        assert!(schema.is_valid_string(r#"<range min="5" max="5"/>"#));
+        let result = schema.validate_string(r#"<range min="10" max="9"/>"#);
+        assert!(!result.valid);
+        assert!(result.errors[0].to_string().contains("@min <= @max"), "{:?}", result.errors);
+
+        // Derived types check the assertions of their base type too
+        let bounded = |min: i32, max: i32| format!(r#"<bounded min="{}" max="{}"/>"#, min, max);
+        assert!(schema.is_valid_string(&bounded(0, 100)));
+        assert!(!schema.is_valid_string(&bounded(0, 101)));
+        assert!(!schema.is_valid_string(&bounded(2, 1)));
     }
 
-    /// Get groups in this namespace
-    pub fn groups(&self) -> impl Iterator<Item = (&QName, &Arc<XsdGroup>)> + '_ {
-        self.schema.groups().filter(|(name, _)| self.matches_namespace(name))
+    #[test]
+    fn test_validate_type_alternatives() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:complexType name="publication">
+    <xs:sequence>
+      <xs:element name="title" type="xs:string"/>
+    </xs:sequence>
+    <xs:attribute name="kind" type="xs:string"/>
+  </xs:complexType>
+  <xs:complexType name="book">
+    <xs:complexContent>
+      <xs:extension base="publication">
+        <xs:attribute name="isbn" type="xs:string" use="required"/>
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:element name="publication" type="publication">
+    <xs:alternative test="@kind = 'book'" type="book"/>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let element = schema.lookup_element(&QName::local("publication")).unwrap();
+        assert_eq!(element.alternatives.len(), 1);
+        assert_eq!(element.alternatives[0].type_ref, QName::local("book"));
+
+        // A matching test selects the derived type
+        assert!(schema.is_valid_string(r#"<publication kind="book" isbn="0441013597"><title>Dune</title></publication>"#));
+        let result = schema.validate_string(r#"<publication kind="book"><title>Dune</title></publication>"#);
+        assert!(!result.valid);
+        assert!(result.errors[0].to_string().contains("isbn"), "{:?}", result.errors);
+
+        // Otherwise the declared type is used
+        assert!(schema.is_valid_string(r#"<publication kind="magazine"><title>Wired</title></publication>"#));
+        assert!(schema.is_valid_string(r#"<publication><title>Wired</title></publication>"#));
+        assert!(!schema.is_valid_string(
+            r#"<publication kind="magazine" isbn="0441013597"><title>Wired</title></publication>"#
+        ));
     }
 
-    /// Get attribute groups in this namespace
-    pub fn attribute_groups(&self) -> impl Iterator<Item = (&QName, &Arc<XsdAttributeGroup>)> + '_ {
-        self.schema.attribute_groups().filter(|(name, _)| self.matches_namespace(name))
+    const BOOK_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="isbnType">
+    <xs:restriction base="xs:string">
+      <xs:pattern value="\d{3}-\d{1,5}-\d{1,7}-\d{1,7}-\d{1}"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="categoryType">
+    <xs:restriction base="xs:string">
+      <xs:enumeration value="fiction"/>
+      <xs:enumeration value="non-fiction"/>
+      <xs:enumeration value="reference"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="codeType">
+    <xs:restriction base="xs:token">
+      <xs:minLength value="3"/>
+      <xs:maxLength value="10"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:element name="book">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="category" type="categoryType"/>
+      </xs:sequence>
+      <xs:attribute name="isbn" type="isbnType"/>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+
+    #[test]
+    fn test_content_model_summary() {
+        let schema = XsdSchema::from_string(include_str!("../../examples/data/book.xsd")).unwrap();
+        let book = |name: &str| QName::namespaced("http://example.com/book", name);
+        assert_eq!(
+            schema.content_model_summary(&book("bookType")),
+            "(title, author+, published?, pages?)"
+        );
+        assert_eq!(schema.content_model_summary(&book("personType")), "(firstName, lastName, email?)");
+        assert_eq!(schema.content_model_summary(&book("isbnType")), schema.facet_summary(&book("isbnType")));
+
+        let xsd = r###"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:group name="shelf">
+    <xs:choice>
+      <xs:element name="fiction" type="xs:string"/>
+      <xs:element name="nonfiction" type="xs:string"/>
+      <xs:element name="reference" type="xs:string"/>
+    </xs:choice>
+  </xs:group>
+  <xs:complexType name="library">
+    <xs:sequence>
+      <xs:element name="name" type="xs:string"/>
+      <xs:sequence minOccurs="0" maxOccurs="unbounded">
+        <xs:group ref="shelf"/>
+        <xs:choice minOccurs="2" maxOccurs="5">
+          <xs:sequence>
+            <xs:element name="a" type="xs:string"/>
+            <xs:any namespace="##other" processContents="lax" minOccurs="0"/>
+          </xs:sequence>
+          <xs:element name="b" type="xs:string" minOccurs="3" maxOccurs="unbounded"/>
+        </xs:choice>
+      </xs:sequence>
+    </xs:sequence>
+  </xs:complexType>
+  <xs:complexType name="record">
+    <xs:all>
+      <xs:element name="a" type="xs:string"/>
+      <xs:element name="b" type="xs:string" minOccurs="0"/>
+      <xs:element name="c" type="xs:string"/>
+    </xs:all>
+  </xs:complexType>
+  <xs:complexType name="note" mixed="true"/>
+  <xs:complexType name="marker"/>
+</xs:schema>"###;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let library = QName::local("library");
+        assert_eq!(
+            schema.content_model_summary(&library),
+            "(name, ((fiction | nonfiction | reference), ((a, any?) | b{3,}){2,5})*)"
+        );
+        assert_eq!(
+            schema.content_model_summary_with_depth(&library, 2),
+            "(name, ((fiction | nonfiction | reference), ((...) | b{3,}){2,5})*)"
+        );
+        assert_eq!(schema.content_model_summary_with_depth(&library, 0), "(name, (...)*)");
+        assert_eq!(schema.content_model_summary(&QName::local("record")), "{a, b?, c}");
+        assert_eq!(schema.content_model_summary(&QName::local("note")), "mixed ()");
+        assert_eq!(schema.content_model_summary(&QName::local("marker")), "EMPTY");
     }
-}
 
-/// Schema validation result
-#[derive(Debug, Clone)]
-pub struct ValidationResult {
-    /// Whether validation succeeded
-    pub valid: bool,
-    /// Validation errors
-    pub errors: Vec<String>,
-    /// Validation warnings
-    pub warnings: Vec<String>,
-}
+    #[test]
+    fn test_facet_summary() {
+        let schema = XsdSchema::from_string(BOOK_XSD).unwrap();
 
-impl ValidationResult {
-    /// Create a valid result
-    pub fn valid() -> Self {
-        Self {
-            valid: true,
-            errors: Vec::new(),
-            warnings: Vec::new(),
-        }
+        assert_eq!(
+            schema.facet_summary(&QName::local("isbnType")),
+            r"xs:string with pattern=\d{3}-\d{1,5}-\d{1,7}-\d{1,7}-\d{1}"
+        );
+        assert_eq!(
+            schema.facet_summary(&QName::local("categoryType")),
+            "enumeration of: fiction, non-fiction, reference"
+        );
+        assert_eq!(
+            schema.facet_summary(&QName::local("codeType")),
+            "xs:token with length between 3..10"
+        );
+        assert_eq!(schema.facet_summary(&QName::namespaced(XSD_NAMESPACE, "integer")), "xs:integer");
+        assert_eq!(schema.facet_summary(&QName::local("missing")), "unknown type missing");
     }
 
-    /// Create an invalid result with errors
-    pub fn invalid(errors: Vec<String>) -> Self {
-        Self {
-            valid: false,
-            errors,
-            warnings: Vec::new(),
+    /// Rejects integer elements with odd values and records element depths
+    struct EvenIntegers;
+
+    impl CustomValidator for EvenIntegers {
+        fn validate_element(
+            &self,
+            element: &Element,
+            schema_type: Option<&GlobalType>,
+            ctx: &ValidationContext,
+        ) -> Vec<String> {
+            let is_integer = schema_type
+                .and_then(|t| t.as_simple())
+                .is_some_and(|st| st.primitive_name() == Some("integer"));
+            match element.text.as_deref().map(str::parse::<i64>) {
+                Some(Ok(value)) if is_integer && value % 2 != 0 => {
+                    vec![format!("{} at level {} must be even", element.local_name(), ctx.level)]
+                }
+                _ => Vec::new(),
+            }
         }
     }
 
-    /// Add a warning
-    pub fn add_warning(&mut self, warning: String) {
-        self.warnings.push(warning);
-    }
+    #[test]
+    fn test_validate_with_extensions() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="pair">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="left" type="xs:integer"/>
+        <xs:element name="right" type="xs:integer"/>
+        <xs:element name="label" type="xs:string" minOccurs="0"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let extensions: Vec<Box<dyn CustomValidator>> = vec![Box::new(EvenIntegers)];
 
-    /// Add an error
-    pub fn add_error(&mut self, error: impl Into<String>) {
-        self.valid = false;
-        self.errors.push(error.into());
-    }
-}
+        let doc = Document::from_string("<pair><left>2</left><right>4</right><label>7</label></pair>").unwrap();
+        let result = schema.validate_with_extensions(&doc, &extensions);
+        assert!(result.valid, "{:?}", result.errors);
 
-/// Schema collection for managing multiple schemas
-///
-/// This is useful for handling imports and includes across namespaces.
-#[derive(Debug, Default)]
-pub struct SchemaCollection {
-    /// Schemas indexed by target namespace
-    pub schemas: HashMap<Option<String>, Vec<Arc<XsdSchema>>>,
-    /// Primary schema (first loaded)
-    pub primary: Option<Arc<XsdSchema>>,
-}
+        let doc = Document::from_string("<pair><left>3</left><right>4</right></pair>").unwrap();
+        let result = schema.validate_with_extensions(&doc, &extensions);
+        assert!(!result.valid);
+        assert_eq!(result.error_messages(), vec!["left at level 1 must be even"]);
 
-impl SchemaCollection {
-    /// Create a new empty collection
-    pub fn new() -> Self {
-        Self::default()
-    }
+        // Schema errors come first, custom rules still run
+        let doc = Document::from_string("<pair><left>5</left><right>x</right></pair>").unwrap();
+        let result = schema.validate_with_extensions(&doc, &extensions);
+        assert_eq!(result.errors.len(), 2, "{:?}", result.errors);
+        assert_eq!(result.errors[1].message, "left at level 1 must be even");
 
-    /// Add a schema to the collection
-    pub fn add(&mut self, schema: Arc<XsdSchema>) {
-        let namespace = schema.target_namespace.clone();
-        if self.primary.is_none() {
-            self.primary = Some(Arc::clone(&schema));
-        }
-        self.schemas.entry(namespace).or_default().push(schema);
+        // Without extensions the result is plain schema validation
+        assert_eq!(schema.validate_with_extensions(&doc, &[]).errors.len(), 1);
     }
 
-    /// Get schemas for a namespace
-    pub fn get(&self, namespace: Option<&str>) -> Option<&Vec<Arc<XsdSchema>>> {
-        self.schemas.get(&namespace.map(|s| s.to_string()))
+    #[test]
+    fn test_notations() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           xmlns:m="http://example.com/media"
+           targetNamespace="http://example.com/media">
+  <xs:notation name="jpeg" public="image/jpeg" system="viewer.exe"/>
+  <xs:notation name="png" public="image/png"/>
+  <xs:simpleType name="imageFormat">
+    <xs:restriction base="xs:NOTATION">
+      <xs:enumeration value="m:jpeg"/>
+      <xs:enumeration value="m:png"/>
+      <xs:enumeration value="m:gif"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:element name="image">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="format" type="xs:NOTATION" minOccurs="0"/>
+      </xs:sequence>
+      <xs:attribute name="type" type="m:imageFormat"/>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+
+        let jpeg = schema
+            .lookup_notation(&QName::namespaced("http://example.com/media", "jpeg"))
+            .expect("jpeg notation should be declared");
+        assert_eq!(jpeg.public(), Some("image/jpeg"));
+        assert_eq!(jpeg.system(), Some("viewer.exe"));
+        let png = schema
+            .lookup_notation(&QName::namespaced("http://example.com/media", "png"))
+            .unwrap();
+        assert_eq!(png.system(), None);
+        assert_eq!(schema.notations().count(), 2);
+
+        assert!(schema.has_notation("jpeg"));
+        assert!(schema.has_notation("m:png"));
+        assert!(!schema.has_notation("gif"));
+        assert!(!schema.has_notation("xs:jpeg"));
+
+        let validate = |xml: &str| schema.validate(&Document::from_string(xml).unwrap());
+        assert!(validate(r#"<m:image xmlns:m="http://example.com/media" type="m:jpeg"/>"#).valid);
+        assert!(validate(
+            r#"<m:image xmlns:m="http://example.com/media"><format xmlns:n="http://example.com/media">n:png</format></m:image>"#
+        )
+        .valid);
+
+        // In the enumeration, but not a declared notation
+        let result = validate(r#"<m:image xmlns:m="http://example.com/media" type="m:gif"/>"#);
+        assert!(!result.valid);
+        assert!(result.errors[0].message.contains("Notation 'm:gif' is not declared"), "{:?}", result.errors);
+        assert!(!validate(r#"<m:image xmlns:m="http://example.com/media"><format>bmp</format></m:image>"#).valid);
     }
 
-    /// Get the primary schema
-    pub fn primary(&self) -> Option<&Arc<XsdSchema>> {
-        self.primary.as_ref()
-    }
+    #[test]
+    fn test_validate_partial() {
+        let schema = XsdSchema::from_string(include_str!("../../examples/data/book.xsd")).unwrap();
+        let type_name = |local: &str| QName::new(Some("http://example.com/book".to_string()), local);
+        let fragment = |xml: &str| Document::from_string(xml).unwrap();
+
+        // No global element declares <editor>, so the fragment is not a valid document
+        let editor = fragment(
+            r#"<editor xmlns="http://example.com/book"><firstName>Ada</firstName><lastName>Lovelace</lastName></editor>"#,
+        );
+        assert!(!schema.is_valid(&editor));
+        let result = schema.validate_partial(editor.root().unwrap(), &type_name("personType"));
+        assert!(result.valid, "{:?}", result.errors);
 
-    /// Check if a namespace is loaded
-    pub fn has_namespace(&self, namespace: Option<&str>) -> bool {
-        self.schemas.contains_key(&namespace.map(|s| s.to_string()))
-    }
+        let editor = fragment(
+            r#"<editor xmlns="http://example.com/book"><lastName>Lovelace</lastName><firstName>Ada</firstName></editor>"#,
+        );
+        assert!(!schema.validate_partial(editor.root().unwrap(), &type_name("personType")).valid);
 
-    /// Get all loaded namespaces
-    pub fn namespaces(&self) -> impl Iterator<Item = Option<&String>> {
-        self.schemas.keys().map(|k| k.as_ref())
+        let book = fragment(
+            r#"<entry xmlns="http://example.com/book" isbn="978-0-123456-47-2"><title>T</title><author><firstName>A</firstName><lastName>B</lastName></author></entry>"#,
+        );
+        assert!(schema.validate_partial(book.root().unwrap(), &type_name("bookType")).valid);
+        let book = fragment(r#"<entry xmlns="http://example.com/book" isbn="bad"><title>T</title></entry>"#);
+        assert!(!schema.validate_partial(book.root().unwrap(), &type_name("bookType")).valid);
+
+        // Simple types validate the text content
+        let isbn = fragment("<code>978-0-123456-47-2</code>");
+        assert!(schema.validate_partial(isbn.root().unwrap(), &type_name("isbnType")).valid);
+        let isbn = fragment("<code>not an isbn</code>");
+        assert!(!schema.validate_partial(isbn.root().unwrap(), &type_name("isbnType")).valid);
+
+        let result = schema.validate_partial(isbn.root().unwrap(), &type_name("missingType"));
+        assert_eq!(result.error_messages(), vec!["Unknown type '{http://example.com/book}missingType'"]);
     }
 
-    /// Get count of schemas
-    pub fn len(&self) -> usize {
-        self.schemas.values().map(|v| v.len()).sum()
-    }
+    #[test]
+    fn test_required_and_optional_content() {
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:group name="shipping">
+        <xs:sequence>
+            <xs:element name="address" type="xs:string"/>
+            <xs:element name="instructions" type="xs:string" minOccurs="0"/>
+        </xs:sequence>
+    </xs:group>
+    <xs:element name="order">
+        <xs:complexType>
+            <xs:sequence>
+                <xs:element name="customer" type="xs:string"/>
+                <xs:element name="item" type="xs:string" maxOccurs="unbounded"/>
+                <xs:choice>
+                    <xs:element name="phone" type="xs:string"/>
+                    <xs:element name="email" type="xs:string"/>
+                </xs:choice>
+                <xs:sequence minOccurs="0">
+                    <xs:element name="comment" type="xs:string" maxOccurs="2"/>
+                </xs:sequence>
+                <xs:group ref="shipping" minOccurs="2" maxOccurs="2"/>
+            </xs:sequence>
+            <xs:attribute name="id" type="xs:string" use="required"/>
+            <xs:attribute name="date" type="xs:date" use="required"/>
+            <xs:attribute name="note" type="xs:string"/>
+            <xs:attribute name="legacy" type="xs:string" use="prohibited"/>
+        </xs:complexType>
+    </xs:element>
+    <xs:element name="code" type="xs:string"/>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let order = QName::local("order");
+
+        let required = schema.required_content(&order).unwrap();
+        assert_eq!(
+            required.required_elements,
+            vec![
+                (QName::local("customer"), Occurs::new(1, Some(1))),
+                (QName::local("item"), Occurs::new(1, None)),
+                (QName::local("address"), Occurs::new(2, Some(2))),
+            ]
+        );
+        assert_eq!(
+            required.required_attributes,
+            vec![
+                (QName::local("date"), AttributeUse::Required),
+                (QName::local("id"), AttributeUse::Required),
+            ]
+        );
 
-    /// Check if collection is empty
-    pub fn is_empty(&self) -> bool {
-        self.schemas.is_empty()
+        let optional = schema.optional_content(&order).unwrap();
+        assert_eq!(
+            optional.optional_elements,
+            vec![
+                (QName::local("phone"), Occurs::new(0, Some(1))),
+                (QName::local("email"), Occurs::new(0, Some(1))),
+                (QName::local("comment"), Occurs::new(0, Some(2))),
+                (QName::local("instructions"), Occurs::new(0, Some(2))),
+            ]
+        );
+        assert_eq!(optional.optional_attributes, vec![(QName::local("note"), AttributeUse::Optional)]);
+
+        // Simple-typed and undeclared elements
+        assert_eq!(schema.required_content(&QName::local("code")), Some(RequiredContent::default()));
+        assert_eq!(schema.optional_content(&QName::local("missing")), None);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use super::super::base::ValidityStatus;
-    use super::super::builtins::XSD_NAMESPACE;
+    #[test]
+    fn test_validate_and_project() {
+        let schema = XsdSchema::from_string(include_str!("../../examples/data/book.xsd")).unwrap();
+        let projection = [
+            ("isbn", "/book/@isbn"),
+            ("title", "/book/title/text()"),
+            ("authors", "/book/author/lastName"),
+            ("contact", "//author[email]/firstName"),
+            ("edition", "/book/@edition"),
+        ];
+
+        let valid = Document::from_string(include_str!("../../tests/fixtures/book.xml")).unwrap();
+        let data = schema.validate_and_project(&valid, &projection).unwrap();
+        assert_eq!(
+            data,
+            serde_json::json!({
+                "isbn": "978-0-13-468599-1",
+                "title": "The Rust Programming Language",
+                "authors": ["Klabnik", "Nichols"],
+                "contact": "Steve",
+                "edition": null,
+            })
+        );
+
+        // Invalid documents are rejected before anything is extracted
+        let invalid = Document::from_string(
+            r#"<book xmlns="http://example.com/book" isbn="not-an-isbn"><title>T</title></book>"#,
+        )
+        .unwrap();
+        assert!(matches!(schema.validate_and_project(&invalid, &projection), Err(Error::Validation(_))));
+
+        let err = schema.validate_and_project(&valid, &[("parent", "/book/..")]).unwrap_err();
+        assert!(matches!(err, Error::Value(_)));
+    }
 
     #[test]
-    fn test_schema_creation() {
-        let schema = XsdSchema::new();
-        assert_eq!(schema.version, XsdVersion::Xsd10);
-        assert!(schema.target_namespace.is_none());
-        assert!(!schema.is_built());
+    fn test_apply_defaults() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:complexType name="itemType">
+    <xs:sequence>
+      <xs:element name="label" type="xs:string" default="untitled" minOccurs="0"/>
+      <xs:element name="note" type="xs:string" minOccurs="0"/>
+    </xs:sequence>
+    <xs:attribute name="id" type="xs:string" use="required"/>
+    <xs:attribute name="status" type="xs:string" default="draft"/>
+    <xs:attribute name="unit" type="xs:string" fixed="kg"/>
+    <xs:attribute name="owner" type="xs:string"/>
+  </xs:complexType>
+  <xs:element name="order">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="item" type="itemType" maxOccurs="unbounded"/>
+      </xs:sequence>
+      <xs:attribute name="currency" type="xs:string" default="EUR"/>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+
+        let mut doc = Document::from_string(
+            r#"<order><item id="a" status="final"><label/><note/></item><item id="b"><label>Flour</label></item></order>"#,
+        )
+        .unwrap();
+        assert!(schema.is_valid(&doc));
+
+        // currency; unit and the empty label of item a; status and unit of item b
+        assert_eq!(schema.apply_defaults(&mut doc), 5);
+
+        let root = doc.root().unwrap();
+        assert_eq!(root.get_attribute("currency"), Some("EUR"));
+        let (a, b) = (&root.children[0], &root.children[1]);
+        assert_eq!(a.get_attribute("status"), Some("final"));
+        assert_eq!(a.get_attribute("unit"), Some("kg"));
+        assert_eq!(a.get_attribute("owner"), None);
+        assert_eq!(a.children[0].text.as_deref(), Some("untitled"));
+        assert_eq!(a.children[1].text, None);
+        assert_eq!(b.get_attribute("status"), Some("draft"));
+        assert_eq!(b.get_attribute("unit"), Some("kg"));
+        assert_eq!(b.children[0].text.as_deref(), Some("Flour"));
+        assert_eq!(b.children.len(), 1, "absent elements are not added");
+
+        assert!(schema.is_valid(&doc));
+        assert_eq!(schema.apply_defaults(&mut doc), 0);
+
+        let mut unknown = Document::from_string("<other/>").unwrap();
+        assert_eq!(schema.apply_defaults(&mut unknown), 0);
     }
 
     #[test]
-    fn test_schema_with_version() {
-        let schema = XsdSchema::with_version(XsdVersion::Xsd11);
-        assert_eq!(schema.version, XsdVersion::Xsd11);
-        assert!(schema.is_xsd11());
-        assert_eq!(schema.xsd_version(), "1.1");
+    fn test_ambiguous_content_models_reported_as_warnings() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:complexType name="nameType">
+    <xs:sequence>
+      <xs:element name="given" type="xs:string" minOccurs="0"/>
+      <xs:element name="given" type="xs:string"/>
+      <xs:element name="family" type="xs:string"/>
+    </xs:sequence>
+  </xs:complexType>
+  <xs:element name="contact">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="name" type="nameType"/>
+        <xs:element name="channel">
+          <xs:complexType>
+            <xs:choice>
+              <xs:sequence>
+                <xs:element name="phone" type="xs:string"/>
+                <xs:element name="extension" type="xs:string"/>
+              </xs:sequence>
+              <xs:element name="phone" type="xs:string"/>
+            </xs:choice>
+          </xs:complexType>
+        </xs:element>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+
+        let report = schema.check_ambiguity();
+        let found: Vec<(String, String)> = report
+            .violations
+            .iter()
+            .map(|v| (v.type_qname.local_name.clone(), v.ambiguous_element.local_name.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("channel".to_string(), "phone".to_string()), ("nameType".to_string(), "given".to_string())]
+        );
+        assert_eq!(
+            report.violations[1].competing_particles,
+            vec!["sequence/given[1]".to_string(), "sequence/given[2]".to_string()]
+        );
+
+        // Ambiguity does not make the schema unusable
+        assert_eq!(schema.warnings.len(), 2);
+        assert!(schema.warnings[0].contains("Unique Particle Attribution"));
+        assert_eq!(schema.errors().len(), 2);
+        assert!(schema.errors()[1].message.contains("'nameType' violates Unique Particle Attribution"));
+        let doc = Document::from_string(
+            "<contact><name><given>Ada</given><given>Augusta</given><family>Lovelace</family></name>\
+             <channel><phone>1</phone><extension>2</extension></channel></contact>",
+        )
+        .unwrap();
+        assert!(schema.is_valid(&doc));
+
+        let schema = XsdSchema::from_string(include_str!("../../examples/data/book.xsd")).unwrap();
+        assert!(schema.check_ambiguity().is_empty());
+        assert!(schema.warnings.is_empty());
     }
 
     #[test]
-    fn test_schema_with_namespace() {
-        let schema = XsdSchema::with_namespace("http://example.com/test");
-        assert_eq!(schema.target_namespace.as_deref(), Some("http://example.com/test"));
+    fn test_type_at_element_path() {
+        let schema = XsdSchema::from_string(include_str!("../../examples/data/book.xsd")).unwrap();
+        // Named types by local name, builtin types by their summary
+        let name = |names: &[&str]| {
+            schema.type_at_element_path(names).map(|t| match (t.name(), t.as_simple()) {
+                (Some(qname), _) => qname.local_name.clone(),
+                (None, Some(st)) => st.facet_summary(),
+                (None, None) => "anonymous".to_string(),
+            })
+        };
+
+        assert_eq!(name(&["book"]).as_deref(), Some("bookType"));
+        assert_eq!(name(&["book", "title"]).as_deref(), Some("xs:string"));
+        // Repeated elements give the item type
+        assert_eq!(name(&["book", "author"]).as_deref(), Some("personType"));
+        assert_eq!(name(&["book", "author", "lastName"]).as_deref(), Some("xs:string"));
+        assert_eq!(name(&["book", "author", "email"]).as_deref(), Some("emailType"));
+        assert_eq!(name(&["book", "pages"]).as_deref(), Some("xs:positiveInteger"));
+
+        assert!(schema.type_at_element_path(&[]).is_none());
+        assert!(schema.type_at_element_path(&["book", "isbn"]).is_none());
+        assert!(schema.type_at_element_path(&["book", "title", "text"]).is_none());
+        assert!(schema.type_at_element_path(&["magazine"]).is_none());
     }
 
     #[test]
-    fn test_schema_with_version_and_namespace() {
-        let schema = XsdSchema::with_version_and_namespace(
-            XsdVersion::Xsd11,
-            "http://example.com/test",
-        );
-        assert_eq!(schema.version, XsdVersion::Xsd11);
-        assert_eq!(schema.target_namespace.as_deref(), Some("http://example.com/test"));
+    fn test_type_at_element_path_choice_and_wildcard() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="entry">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:choice>
+          <xs:element name="value" type="xs:integer"/>
+          <xs:sequence>
+            <xs:element name="value" type="xs:date"/>
+            <xs:element name="note">
+              <xs:simpleType>
+                <xs:restriction base="xs:string">
+                  <xs:maxLength value="20"/>
+                </xs:restriction>
+              </xs:simpleType>
+            </xs:element>
+          </xs:sequence>
+        </xs:choice>
+        <xs:any processContents="lax" minOccurs="0"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+
+        // Anonymous types are returned from the declaration
+        let entry = schema.type_at_element_path(&["entry"]).unwrap();
+        assert!(entry.is_complex());
+        assert!(entry.name().is_none());
+        let note = schema.type_at_element_path(&["entry", "note"]).unwrap();
+        assert!(note.as_simple().unwrap().validate_value("short").is_ok());
+        assert!(note.as_simple().unwrap().validate_value("far too long for the note").is_err());
+
+        // Branches of the choice give the union of their types
+        let value = schema.type_at_element_path(&["entry", "value"]).unwrap();
+        let value = value.as_simple().unwrap();
+        assert!(value.validate_value("42").is_ok());
+        assert!(value.validate_value("2024-01-31").is_ok());
+        assert!(value.validate_value("forty-two").is_err());
+
+        // Elements matched by the wildcard have no known type
+        assert!(schema.type_at_element_path(&["entry", "extra"]).is_none());
     }
 
     #[test]
-    fn test_form_default_parse() {
-        assert_eq!(FormDefault::from_str("qualified"), Some(FormDefault::Qualified));
-        assert_eq!(FormDefault::from_str("unqualified"), Some(FormDefault::Unqualified));
-        assert_eq!(FormDefault::from_str("invalid"), None);
+    fn test_facet_summary_in_validation_errors() {
+        let schema = XsdSchema::from_string(BOOK_XSD).unwrap();
+
+        let doc = Document::from_string(r#"<book isbn="123"><category>poetry</category></book>"#).unwrap();
+        let errors = schema.iter_errors(&doc);
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors.iter().any(|e| e.message.contains("expected enumeration of: fiction, non-fiction, reference")));
+        assert!(errors.iter().any(|e| e.message.contains(r"expected xs:string with pattern=\d{3}")));
     }
 
     #[test]
-    fn test_form_default_is_qualified() {
-        assert!(FormDefault::Qualified.is_qualified());
-        assert!(!FormDefault::Unqualified.is_qualified());
+    fn test_iter_errors_structured() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="order">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="item" maxOccurs="unbounded">
+          <xs:complexType>
+            <xs:attribute name="qty" type="xs:integer"/>
+          </xs:complexType>
+        </xs:element>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let doc = Document::from_string(
+            r#"<order><item qty="x1"/><item qty="1"/><item qty="x2"/><item qty="x3"/><item qty="x4"/><item qty="x5"/></order>"#,
+        )
+        .unwrap();
+
+        // Errors come in document order
+        let values: Vec<String> = schema
+            .iter_errors_structured(&doc)
+            .filter_map(|error| ["x1", "x2", "x3", "x4", "x5"].into_iter().find(|v| error.message.contains(v)))
+            .map(str::to_string)
+            .collect();
+        assert_eq!(values, vec!["x1", "x2", "x3", "x4", "x5"]);
+
+        // Stopping early
+        let first: Vec<_> = schema.iter_errors_structured(&doc).take(3).collect();
+        assert_eq!(first.len(), 3);
+        assert!(first[2].message.contains("x3"));
+
+        // The same errors as the tree validator
+        let schema = XsdSchema::from_string(BOOK_XSD).unwrap();
+        let doc = Document::from_string(r#"<book isbn="123"><category>poetry</category><extra/></book>"#).unwrap();
+        let messages = |errors: Vec<exceptions::ValidationError>| -> Vec<String> {
+            errors.into_iter().map(|error| error.message).collect()
+        };
+        assert_eq!(messages(schema.iter_errors_structured(&doc).collect()), messages(schema.iter_errors(&doc)));
+        let valid = Document::from_string(r#"<book isbn="978-0-13-468599-1"><category>fiction</category></book>"#).unwrap();
+        assert!(schema.iter_errors_structured(&valid).next().is_none());
     }
 
     #[test]
-    fn test_derivation_default_parse() {
-        let dd = DerivationDefault::parse("#all");
-        assert!(dd.extension);
-        assert!(dd.restriction);
-        assert!(dd.substitution);
+    fn test_structured_validation_errors() {
+        let schema = XsdSchema::from_string(BOOK_XSD).unwrap();
+        let doc = Document::from_string(r#"<book isbn="123"><category>poetry</category><extra/></book>"#).unwrap();
 
-        let dd = DerivationDefault::parse("extension restriction");
-        assert!(dd.extension);
-        assert!(dd.restriction);
-        assert!(!dd.substitution);
+        let errors = schema.iter_errors(&doc);
+        let by_code = |code: &str| errors.iter().find(|e| e.error_code.as_deref() == Some(code)).unwrap();
 
-        let dd = DerivationDefault::parse("");
-        assert!(dd.is_empty());
+        let isbn = by_code("cvc-attribute.3");
+        assert_eq!(isbn.path.as_deref(), Some("/book/@isbn"));
+        assert_eq!(isbn.schema_component.as_deref(), Some("isbnType"));
+
+        let category = by_code("cvc-type.3.1.3");
+        assert_eq!(category.path.as_deref(), Some("/book/category"));
+        assert_eq!(category.schema_component.as_deref(), Some("categoryType"));
+
+        let extra = by_code("cvc-complex-type.2.4");
+        assert_eq!(extra.path.as_deref(), Some("/book/extra"));
+        assert_eq!(extra.schema_component.as_deref(), Some("book"));
+
+        // Strict mode stops at the first error, with the same details
+        let result = schema.validate_with_mode(&doc, ValidationMode::Strict);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].path.as_deref(), Some("/book/@isbn"));
+        assert_eq!(result.errors[0].error_code.as_deref(), Some("cvc-attribute.3"));
+        assert!(result.errors[0].message.starts_with("Invalid value for attribute 'isbn'"));
     }
 
     #[test]
-    fn test_schema_build() {
-        let mut schema = XsdSchema::new();
-        assert!(!schema.is_built());
-        assert!(schema.build().is_ok());
-        assert!(schema.is_built());
+    fn test_validation_errors_source_location() {
+        let schema = XsdSchema::from_string(BOOK_XSD).unwrap();
+        let xml = "<book\n  isbn=\"978-0-00-000000-0\">\n  <category>poetry</category>\n    <extra/>\n</book>";
+
+        // The first error is on the third line
+        let result = schema.validate_string(xml);
+        assert!(!result.valid);
+        assert_eq!(result.errors[0].path.as_deref(), Some("/book/category"));
+        assert_eq!(result.errors[0].source_line, Some(3));
+        assert_eq!(result.errors[0].source_column, Some(3));
+
+        // Unexpected children are located at their own start tag
+        let doc = Document::from_string(xml).unwrap();
+        let errors = schema.iter_errors(&doc);
+        let extra = errors.iter().find(|e| e.path.as_deref() == Some("/book/extra")).unwrap();
+        assert_eq!((extra.source_line, extra.source_column), (Some(4), Some(5)));
     }
 
     #[test]
-    fn test_schema_namespaces() {
-        let mut schema = XsdSchema::new();
-        schema.add_namespace("xs", XSD_NAMESPACE);
-        schema.add_namespace("tns", "http://example.com/test");
+    fn test_validate_identity_constraints() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="library">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="book" maxOccurs="unbounded">
+          <xs:complexType>
+            <xs:attribute name="id" type="xs:string"/>
+          </xs:complexType>
+        </xs:element>
+        <xs:element name="loan" minOccurs="0" maxOccurs="unbounded">
+          <xs:complexType>
+            <xs:attribute name="book" type="xs:string"/>
+          </xs:complexType>
+        </xs:element>
+      </xs:sequence>
+    </xs:complexType>
+    <xs:key name="bookKey">
+      <xs:selector xpath="book"/>
+      <xs:field xpath="@id"/>
+    </xs:key>
+    <xs:keyref name="loanRef" refer="bookKey">
+      <xs:selector xpath="loan"/>
+      <xs:field xpath="@book"/>
+    </xs:keyref>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+
+        let valid = r#"<library><book id="a"/><book id="b"/><loan book="b"/></library>"#;
+        let result = schema.validate_string(valid);
+        assert!(result.valid, "{:?}", result.errors);
+
+        // Duplicate key value
+        let doc = Document::from_string("<library>\n<book id=\"a\"/>\n<book id=\"a\"/>\n</library>").unwrap();
+        let errors = schema.iter_errors(&doc);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].error_code.as_deref(), Some("cvc-identity-constraint.4.2.2"));
+        assert_eq!(errors[0].path.as_deref(), Some("/library/book"));
+        assert_eq!(errors[0].source_line, Some(3));
+
+        // Dangling keyref
+        let doc = Document::from_string(r#"<library><book id="a"/><loan book="z"/></library>"#).unwrap();
+        let errors = schema.iter_errors(&doc);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].error_code.as_deref(), Some("cvc-identity-constraint.4.3"));
+        assert_eq!(errors[0].path.as_deref(), Some("/library/loan"));
+        assert!(errors[0].message.contains("loanRef"));
+    }
 
-        assert_eq!(schema.get_namespace("xs"), Some(XSD_NAMESPACE));
-        assert_eq!(schema.get_namespace("tns"), Some("http://example.com/test"));
-        assert_eq!(schema.get_namespace("unknown"), None);
+    #[test]
+    fn test_validate_xsi_type() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:complexType name="shape" abstract="true">
+    <xs:sequence>
+      <xs:element name="label" type="xs:string"/>
+    </xs:sequence>
+  </xs:complexType>
+  <xs:complexType name="circle">
+    <xs:complexContent>
+      <xs:extension base="shape">
+        <xs:sequence>
+          <xs:element name="radius" type="xs:decimal"/>
+        </xs:sequence>
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:complexType name="point">
+    <xs:sequence>
+      <xs:element name="x" type="xs:decimal"/>
+    </xs:sequence>
+  </xs:complexType>
+  <xs:element name="drawing">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="shape" type="shape" maxOccurs="unbounded"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let drawing = |shape: &str| {
+            let xml = format!(
+                r#"<drawing xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">{}</drawing>"#,
+                shape
+            );
+            let doc = Document::from_string(&xml).unwrap();
+            schema.validate_with_mode(&doc, ValidationMode::Strict)
+        };
+
+        // The content is validated against the derived type
+        let result = drawing(r#"<shape xsi:type="circle"><label>c</label><radius>2.5</radius></shape>"#);
+        assert!(result.valid, "{:?}", result.errors);
+        let result = drawing(r#"<shape xsi:type="circle"><label>c</label><radius>big</radius></shape>"#);
+        assert!(!result.valid);
+
+        // A type not derived from the declared type
+        let result = drawing(r#"<shape xsi:type="point"><x>1</x></shape>"#);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].error_code.as_deref(), Some("cvc-elt.4.3"));
+
+        // The abstract declared type requires xsi:type
+        let result = drawing("<shape><label>c</label></shape>");
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].error_code.as_deref(), Some("cvc-type.2"));
+
+        // Unprefixed attributes are in no namespace, even with xsi in scope
+        let result = drawing(r#"<shape type="circle"><label>c</label><radius>2.5</radius></shape>"#);
+        assert!(result.errors.iter().any(|e| e.error_code.as_deref() == Some("cvc-type.2")));
+        let result = drawing(r#"<shape xsi:type="circle" nil="true"><label>c</label><radius>2.5</radius></shape>"#);
+        assert_eq!(result.errors.len(), 1, "{:?}", result.errors);
+        assert!(result.errors[0].message.contains("'nil'"));
     }
 
     #[test]
-    fn test_resolve_qname() {
-        let mut schema = XsdSchema::new();
-        schema.target_namespace = Some("http://example.com/default".to_string());
-        schema.add_namespace("tns", "http://example.com/test");
-        schema.add_namespace("xs", XSD_NAMESPACE);
+    fn test_validate_abstract_components() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:complexType name="vehicle" abstract="true">
+    <xs:sequence>
+      <xs:element name="wheels" type="xs:int"/>
+    </xs:sequence>
+  </xs:complexType>
+  <xs:complexType name="car">
+    <xs:complexContent>
+      <xs:extension base="vehicle"/>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:element name="garage">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element ref="item" maxOccurs="unbounded"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+  <xs:element name="item" type="xs:string" abstract="true"/>
+  <xs:element name="tool" type="xs:string" substitutionGroup="item"/>
+  <xs:element name="vehicle" type="vehicle"/>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let errors = |xml: &str| {
+            let result = schema.validate_string(xml);
+            let streamed = schema.validate_reader(xml.as_bytes());
+            assert_eq!(result.valid, streamed.valid, "{}", xml);
+            result.errors.iter().filter_map(|error| error.error_code.clone()).collect::<Vec<_>>()
+        };
 
-        // Prefixed QName
-        let (ns, local) = schema.resolve_qname("tns:element");
-        assert_eq!(ns, Some("http://example.com/test"));
-        assert_eq!(local, "element");
+        assert!(errors("<garage><tool>hammer</tool></garage>").is_empty());
 
-        // Unprefixed QName uses target namespace
-        let (ns, local) = schema.resolve_qname("element");
-        assert_eq!(ns, Some("http://example.com/default"));
-        assert_eq!(local, "element");
+        // An abstract element can't appear, even as the document element
+        assert_eq!(errors("<garage><tool>saw</tool><item>hammer</item></garage>"), vec!["cvc-elt.2"]);
+        assert_eq!(errors("<item>hammer</item>"), vec!["cvc-elt.2"]);
+
+        // An abstract type needs an xsi:type naming a derived type
+        assert_eq!(errors("<vehicle><wheels>4</wheels></vehicle>"), vec!["cvc-type.2"]);
+        let xml = r#"<vehicle xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="car"><wheels>4</wheels></vehicle>"#;
+        assert!(errors(xml).is_empty());
     }
 
     #[test]
-    fn test_schema_imports() {
-        let mut schema = XsdSchema::new();
+    fn test_validate_xsi_nil() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="order">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="customer" nillable="true">
+          <xs:complexType>
+            <xs:sequence>
+              <xs:element name="name" type="xs:string"/>
+            </xs:sequence>
+            <xs:attribute name="id" type="xs:int" use="required"/>
+          </xs:complexType>
+        </xs:element>
+        <xs:element name="total" type="xs:decimal"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let order = |content: &str| {
+            let xml = format!(
+                r#"<order xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">{}</order>"#,
+                content
+            );
+            let doc = Document::from_string(&xml).unwrap();
+            schema.iter_errors(&doc)
+        };
 
-        schema.add_import(
-            "http://example.com/imported".to_string(),
-            Some("imported.xsd".to_string()),
-        );
+        // A nilled element has neither its content nor its attributes validated
+        let errors = order(r#"<customer xsi:nil="true"/><total>9.5</total>"#);
+        assert!(errors.is_empty(), "{:?}", errors);
 
-        assert!(schema.has_import("http://example.com/imported"));
-        assert!(!schema.has_import("http://example.com/unknown"));
+        // Nil on a non-nillable element
+        let errors = order(r#"<customer id="1"><name>Ann</name></customer><total xsi:nil="true">1</total>"#);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].error_code.as_deref(), Some("cvc-elt.3.1"));
 
-        let import = schema.get_import("http://example.com/imported").unwrap();
-        assert_eq!(import.namespace, "http://example.com/imported");
-        assert_eq!(import.location.as_deref(), Some("imported.xsd"));
+        // A nilled element must be empty
+        let errors = order(r#"<customer xsi:nil="1"><name>Ann</name></customer><total>9.5</total>"#);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].error_code.as_deref(), Some("cvc-elt.3.2.1"));
     }
 
     #[test]
-    fn test_resolve_form() {
-        let mut schema = XsdSchema::new();
-        schema.element_form_default = FormDefault::Qualified;
-        schema.attribute_form_default = FormDefault::Unqualified;
+    fn test_validate_id_references() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="library">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="author" maxOccurs="unbounded">
+          <xs:complexType>
+            <xs:attribute name="id" type="xs:ID" use="required"/>
+          </xs:complexType>
+        </xs:element>
+        <xs:element name="book" minOccurs="0" maxOccurs="unbounded">
+          <xs:complexType>
+            <xs:attribute name="author" type="xs:IDREF"/>
+            <xs:attribute name="coauthors" type="xs:IDREFS"/>
+          </xs:complexType>
+        </xs:element>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let library = |content: &str| {
+            let doc = Document::from_string(&format!("<library>{}</library>", content)).unwrap();
+            schema.iter_errors(&doc)
+        };
 
-        // Default forms
-        assert!(schema.resolve_element_form(None).is_qualified());
-        assert!(!schema.resolve_attribute_form(None).is_qualified());
+        // References are resolved against every ID of the document
+        let errors = library(r#"<author id="a1"/><author id="a2"/><book author="a1" coauthors="a2 a1"/>"#);
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        // Duplicate ID
+        let errors = library(r#"<author id="a1"/><author id="a1"/>"#);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].error_code.as_deref(), Some("cvc-id.2"));
+        assert_eq!(errors[0].path.as_deref(), Some("/library/author/@id"));
+        assert!(errors[0].message.contains("'a1'") && errors[0].message.contains("'id'"));
+
+        // Dangling IDREF and IDREFS items
+        let errors = library(r#"<author id="a1"/><book author="a9"/><book coauthors="a1 a7"/>"#);
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors.iter().all(|e| e.error_code.as_deref() == Some("cvc-id.1")));
+        assert!(errors[0].message.contains("'a9'") && errors[0].message.contains("'author'"));
+        assert_eq!(errors[0].path.as_deref(), Some("/library/book/@author"));
+        assert!(errors[1].message.contains("'a7'") && errors[1].message.contains("'coauthors'"));
+
+        // Strict validation reports the dangling reference too
+        let doc = Document::from_string(r#"<library><author id="a1"/><book author="a2"/></library>"#).unwrap();
+        assert!(!schema.validate_with_mode(&doc, ValidationMode::Strict).valid);
+    }
 
-        // Explicit forms override defaults
-        assert!(!schema.resolve_element_form(Some(FormDefault::Unqualified)).is_qualified());
-        assert!(schema.resolve_attribute_form(Some(FormDefault::Qualified)).is_qualified());
+    #[test]
+    fn test_validate_all_group() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="person">
+    <xs:complexType>
+      <xs:all>
+        <xs:element name="name" type="xs:string"/>
+        <xs:element name="age" type="xs:int"/>
+        <xs:element name="email" type="xs:string" minOccurs="0"/>
+      </xs:all>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let person = |content: &str| {
+            let doc = Document::from_string(&format!("<person>{}</person>", content)).unwrap();
+            schema.iter_errors(&doc)
+        };
+
+        // Any order, optional elements may be left out
+        let errors = person("<age>42</age><email>ann@example.com</email><name>Ann</name>");
+        assert!(errors.is_empty(), "{:?}", errors);
+        let errors = person("<age>42</age><name>Ann</name>");
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        // Children are still validated against their declarations
+        let errors = person("<age>old</age><name>Ann</name>");
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].error_code.as_deref(), Some("cvc-type.3.1.3"));
+
+        // Each element may appear at most once
+        let errors = person("<name>Ann</name><age>42</age><name>Bob</name>");
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].error_code.as_deref(), Some("cvc-complex-type.2.4"));
+        assert!(errors[0].message.contains("'name' occurs more than allowed"), "{}", errors[0].message);
+
+        // Required elements are reported after all the children
+        let errors = person("<email>ann@example.com</email><name>Ann</name>");
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].message.contains("Missing required element 'age'"), "{}", errors[0].message);
+
+        // Elements not in the group
+        let errors = person("<name>Ann</name><age>42</age><phone>555</phone>");
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].message.contains("Unexpected child element 'phone'"), "{}", errors[0].message);
+
+        // Elements with the same local name in another namespace
+        let errors = person(r#"<name xmlns="http://example.com/other">Ann</name><age>42</age>"#);
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[0].message.contains("Unexpected child element 'name'"), "{}", errors[0].message);
+        assert!(errors[1].message.contains("Missing required element 'name'"), "{}", errors[1].message);
     }
 
     #[test]
-    fn test_validation_result() {
-        let valid = ValidationResult::valid();
-        assert!(valid.valid);
-        assert!(valid.errors.is_empty());
+    fn test_validate_choice_group() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="payment">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="amount" type="xs:decimal"/>
+        <xs:choice>
+          <xs:element name="cash" type="xs:string"/>
+          <xs:sequence>
+            <xs:element name="card" type="xs:string"/>
+            <xs:element name="expiry" type="xs:gYearMonth"/>
+          </xs:sequence>
+          <xs:element name="voucher" type="xs:string"/>
+        </xs:choice>
+        <xs:element name="note" type="xs:string" minOccurs="0"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let payment = |content: &str| {
+            let doc = Document::from_string(&format!("<payment><amount>10</amount>{}</payment>", content)).unwrap();
+            schema.iter_errors(&doc)
+        };
 
-        let invalid = ValidationResult::invalid(vec!["test error".to_string()]);
-        assert!(!invalid.valid);
-        assert_eq!(invalid.errors.len(), 1);
+        // Each branch on its own
+        for content in [
+            "<cash>EUR</cash>",
+            "<card>4111</card><expiry>2030-01</expiry>",
+            "<voucher>X1</voucher><note>gift</note>",
+        ] {
+            let errors = payment(content);
+            assert!(errors.is_empty(), "{}: {:?}", content, errors);
+        }
+
+        // The chosen branch is validated
+        let errors = payment("<card>4111</card><expiry>soon</expiry>");
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].error_code.as_deref(), Some("cvc-type.3.1.3"));
+
+        // Content of two branches
+        let errors = payment("<cash>EUR</cash><voucher>X1</voucher>");
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].message.contains("Unexpected child element 'voucher'"), "{}", errors[0].message);
+
+        // No branch matches, all the alternatives are reported
+        let errors = payment("<cheque>42</cheque>");
+        assert!(!errors.is_empty());
+        assert_eq!(errors[0].error_code.as_deref(), Some("cvc-complex-type.2.4"));
+        let reason = errors[0].reason.as_deref().unwrap_or_default();
+        for alternative in ["cash", "card", "voucher"] {
+            assert!(reason.contains(alternative), "{}", reason);
+        }
     }
 
     #[test]
-    fn test_validation_result_modifications() {
-        let mut result = ValidationResult::valid();
-        assert!(result.valid);
+    fn test_validate_reader() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:orders" xmlns="urn:orders"
+           elementFormDefault="qualified">
+  <xs:element name="orders">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="order" maxOccurs="unbounded">
+          <xs:complexType>
+            <xs:all>
+              <xs:element name="item" type="xs:string"/>
+              <xs:element name="quantity" type="xs:positiveInteger"/>
+            </xs:all>
+            <xs:attribute name="ref" type="xs:IDREF"/>
+          </xs:complexType>
+        </xs:element>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+
+        // The streaming validation reports the errors of the tree validation
+        for (xml, expected) in [
+            (r#"<orders xmlns="urn:orders"><order><quantity>2</quantity><item>pen</item></order></orders>"#, 0),
+            (r#"<orders xmlns="urn:orders"><order><item>pen</item><quantity>0</quantity></order></orders>"#, 1),
+            (r#"<orders xmlns="urn:orders"><order><item>pen</item></order><note/></orders>"#, 2),
+            (r#"<orders xmlns="urn:orders"><order ref="x"><item>pen</item><quantity>1</quantity></order></orders>"#, 1),
+            (r#"<invoice xmlns="urn:orders"/>"#, 1),
+        ] {
+            let doc = Document::from_string(xml).unwrap();
+            let codes = |errors: &[exceptions::ValidationError]| {
+                errors.iter().map(|error| error.error_code.clone()).collect::<Vec<_>>()
+            };
+            let result = schema.validate_reader_with_mode(xml.as_bytes(), ValidationMode::Lax);
+            assert_eq!(result.errors.len(), expected, "{}: {:?}", xml, result.errors);
+            assert_eq!(codes(&result.errors), codes(&schema.iter_errors(&doc)), "{}", xml);
+        }
 
-        result.add_warning("Warning!".to_string());
-        assert!(result.valid);
-        assert_eq!(result.warnings.len(), 1);
+        // Strict validation stops at the first error
+        let xml = r#"<orders xmlns="urn:orders"><order><item>pen</item></order><note/></orders>"#;
+        assert_eq!(schema.validate_reader(xml.as_bytes()).errors.len(), 1);
 
-        result.add_error("Error!");
+        // Malformed XML is an error of the result
+        let result = schema.validate_reader(r#"<orders xmlns="urn:orders"><order></orders>"#.as_bytes());
         assert!(!result.valid);
-        assert_eq!(result.errors.len(), 1);
     }
 
     #[test]
-    fn test_schema_collection() {
-        let mut collection = SchemaCollection::new();
-        assert!(collection.is_empty());
-        assert_eq!(collection.len(), 0);
+    fn test_attribute_wildcards_merged_on_derivation() {
+        use super::super::wildcards::{NamespaceConstraint, ProcessContents};
+
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:t" xmlns:t="urn:t">
+  <xs:complexType name="baseType">
+    <xs:anyAttribute namespace="urn:a urn:b"/>
+  </xs:complexType>
+  <xs:complexType name="extendedType">
+    <xs:complexContent>
+      <xs:extension base="t:baseType">
+        <xs:anyAttribute namespace="urn:c" processContents="lax"/>
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:complexType name="inheritingType">
+    <xs:complexContent>
+      <xs:extension base="t:baseType"/>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:complexType name="restrictedType">
+    <xs:complexContent>
+      <xs:restriction base="t:baseType">
+        <xs:anyAttribute namespace="urn:b urn:c"/>
+      </xs:restriction>
+    </xs:complexContent>
+  </xs:complexType>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let wildcard = |name: &str| {
+            let ct = schema.maps.lookup_complex_type(&QName::namespaced("urn:t", name)).unwrap();
+            ct.attributes.any_attribute().map(|any| any.wildcard.clone())
+        };
+        let namespaces = |list: &[&str]| {
+            NamespaceConstraint::Enumeration(list.iter().map(|ns| ns.to_string()).collect())
+        };
 
-        let schema1 = Arc::new(XsdSchema::with_namespace("http://example.com/a"));
-        let schema2 = Arc::new(XsdSchema::with_namespace("http://example.com/b"));
-        let schema3 = Arc::new(XsdSchema::with_namespace("http://example.com/a"));
+        let extended = wildcard("extendedType").unwrap();
+        assert_eq!(extended.namespace, namespaces(&["urn:a", "urn:b", "urn:c"]));
+        assert_eq!(extended.process_contents, ProcessContents::Lax);
 
-        collection.add(Arc::clone(&schema1));
-        assert_eq!(collection.len(), 1);
-        assert!(collection.primary().is_some());
+        assert_eq!(wildcard("inheritingType").unwrap().namespace, namespaces(&["urn:a", "urn:b"]));
+        assert_eq!(wildcard("restrictedType").unwrap().namespace, namespaces(&["urn:b"]));
+    }
 
-        collection.add(Arc::clone(&schema2));
-        collection.add(Arc::clone(&schema3));
-        assert_eq!(collection.len(), 3);
+    const FORM_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:form" xmlns:f="urn:form">
+  <xs:attribute name="lang" type="xs:language"/>
+  <xs:complexType name="fieldType">
+    <xs:attribute name="id" type="xs:ID" use="required"/>
+    <xs:attribute name="size" type="xs:positiveInteger"/>
+    <xs:attribute name="visible" type="xs:boolean" default="true"/>
+    <xs:attribute name="version" type="xs:integer" fixed="2"/>
+    <xs:attribute name="legacy" type="xs:string" use="prohibited"/>
+    <xs:attribute ref="f:lang"/>
+  </xs:complexType>
+  <xs:simpleType name="codeType">
+    <xs:restriction base="xs:string"/>
+  </xs:simpleType>
+</xs:schema>"#;
 
-        assert!(collection.has_namespace(Some("http://example.com/a")));
-        assert!(collection.has_namespace(Some("http://example.com/b")));
-        assert!(!collection.has_namespace(Some("http://example.com/c")));
+    #[test]
+    fn test_validate_attribute_value() {
+        let schema = XsdSchema::from_string(FORM_XSD).unwrap();
+        let field = QName::namespaced("urn:form", "fieldType");
+        let attr = |name: &str, value: Option<&str>| {
+            schema.validate_attribute_value(&field, &QName::local(name), value)
+        };
 
-        let schemas_a = collection.get(Some("http://example.com/a")).unwrap();
-        assert_eq!(schemas_a.len(), 2);
+        // Required
+        assert_eq!(attr("id", Some("f1")).unwrap(), XsdValue::String("f1".to_string()));
+        assert!(attr("id", Some("1f")).is_err());
+        assert!(attr("id", None).is_err());
+
+        // Optional
+        assert_eq!(attr("size", Some("12")).unwrap(), XsdValue::Integer(12));
+        assert!(attr("size", Some("0")).is_err());
+        assert_eq!(attr("size", None).unwrap(), XsdValue::Null);
+
+        // With default
+        assert_eq!(attr("visible", Some("0")).unwrap(), XsdValue::Boolean(false));
+        assert_eq!(attr("visible", None).unwrap(), XsdValue::Boolean(true));
+
+        // With fixed value
+        assert_eq!(attr("version", Some("2")).unwrap(), XsdValue::Integer(2));
+        assert_eq!(attr("version", None).unwrap(), XsdValue::Integer(2));
+        assert!(attr("version", Some("3")).is_err());
+
+        // Prohibited and undeclared
+        assert!(attr("legacy", Some("x")).is_err());
+        assert!(attr("unknown", Some("x")).is_err());
+
+        // Global attribute, through the reference and directly by name
+        let lang = QName::namespaced("urn:form", "lang");
+        assert!(schema.validate_attribute_value(&field, &lang, Some("en-GB")).is_ok());
+        assert!(schema.validate_attribute_value(&field, &lang, Some("not a language")).is_err());
+
+        // Unknown and simple element types
+        assert!(schema.validate_attribute_value(&QName::local("missing"), &QName::local("id"), Some("a")).is_err());
+        let code = QName::namespaced("urn:form", "codeType");
+        assert!(schema.validate_attribute_value(&code, &QName::local("id"), Some("a")).is_err());
     }
 
     #[test]
-    fn test_namespace_view() {
-        let mut schema = XsdSchema::new();
-        schema.set_target_namespace(Some("http://example.com/test".to_string()));
-        schema.add_namespace("tns", "http://example.com/test");
+    fn test_validate_attribute_white_space() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="codeType">
+    <xs:restriction base="xs:token">
+      <xs:enumeration value="foo bar"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:complexType name="itemType">
+    <xs:attribute name="code" type="codeType"/>
+    <xs:attribute name="tag" type="xs:token" fixed="foo bar"/>
+    <xs:attribute name="note" type="xs:string" fixed="foo bar"/>
+  </xs:complexType>
+  <xs:element name="item" type="itemType"/>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let valid = |xml: &str| {
+            let streamed = schema.validate_reader(xml.as_bytes()).valid;
+            let result = schema.validate_string(xml);
+            assert_eq!(result.valid, streamed, "{}", xml);
+            result.valid
+        };
 
-        let view = NamespaceView::new(&schema, Some("http://example.com/test"));
-        // View should filter correctly (empty since no elements registered)
-        assert_eq!(view.elements().count(), 0);
+        // xs:token collapses white space before the facets and the fixed value are checked
+        assert!(valid(r#"<item code="  foo  bar  "/>"#));
+        assert!(valid(r#"<item tag="  foo  bar  "/>"#));
+        assert!(!valid(r#"<item code="foobar"/>"#));
+
+        // xs:string preserves it
+        assert!(valid(r#"<item note="foo bar"/>"#));
+        assert!(!valid(r#"<item note=" foo bar"/>"#));
+
+        let item = QName::local("itemType");
+        let attr = |name: &str, value: &str| schema.validate_attribute_value(&item, &QName::local(name), Some(value));
+        assert_eq!(attr("code", "  foo  bar  ").unwrap(), XsdValue::String("foo bar".to_string()));
+        assert!(attr("tag", " foo   bar").is_ok());
+        assert!(attr("note", "foo  bar").is_err());
     }
 
     #[test]
-    fn test_validator_trait() {
-        let schema = XsdSchema::new();
-        assert_eq!(schema.validation, ValidationMode::Strict);
-        assert!(!schema.has_errors());
-        assert!(schema.errors().is_empty());
-        assert_eq!(schema.validation_attempted(), ValidationStatus::None);
+    fn test_type_ancestors() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="quantity">
+    <xs:restriction base="xs:integer">
+      <xs:minInclusive value="0"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="smallQuantity">
+    <xs:restriction base="quantity">
+      <xs:maxInclusive value="10"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="quantities">
+    <xs:list itemType="quantity"/>
+  </xs:simpleType>
+  <xs:complexType name="base">
+    <xs:sequence>
+      <xs:element name="a" type="xs:string"/>
+    </xs:sequence>
+  </xs:complexType>
+  <xs:complexType name="derived">
+    <xs:complexContent>
+      <xs:extension base="base"/>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:complexType name="price">
+    <xs:simpleContent>
+      <xs:extension base="xs:decimal">
+        <xs:attribute name="currency" type="xs:string"/>
+      </xs:extension>
+    </xs:simpleContent>
+  </xs:complexType>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let xs = |name: &str| QName::namespaced(XSD_NAMESPACE, name);
+        let numeric = vec![xs("integer"), xs("decimal"), xs("anyAtomicType"), xs("anySimpleType"), xs("anyType")];
+
+        assert_eq!(schema.type_ancestors(&QName::local("quantity")), numeric);
+        assert_eq!(schema.type_ancestors(&QName::local("smallQuantity"))[0], QName::local("quantity"));
+        assert_eq!(schema.type_ancestors(&QName::local("smallQuantity"))[1..], numeric[..]);
+        assert_eq!(schema.type_ancestors(&xs("integer")), numeric[1..]);
+        assert_eq!(schema.type_ancestors(&QName::local("quantities")), vec![xs("anySimpleType"), xs("anyType")]);
+        assert_eq!(
+            schema.type_ancestors(&QName::local("derived")),
+            vec![QName::local("base"), xs("anyType")]
+        );
+        assert_eq!(schema.type_ancestors(&QName::local("price"))[..2], [xs("decimal"), xs("anyAtomicType")]);
+        assert!(schema.type_ancestors(&xs("anyType")).is_empty());
+        assert!(schema.type_ancestors(&QName::local("missing")).is_empty());
+
+        assert!(schema.is_derived_from(&QName::local("smallQuantity"), &xs("decimal")));
+        assert!(schema.is_derived_from(&QName::local("quantity"), &QName::local("quantity")));
+        assert!(!schema.is_derived_from(&QName::local("quantity"), &QName::local("smallQuantity")));
+        assert!(!schema.is_derived_from(&QName::local("derived"), &xs("anySimpleType")));
     }
 
     #[test]
-    fn test_validation_status() {
-        let schema = XsdSchema::new();
-        assert_eq!(schema.validation_attempted(), ValidationStatus::None);
-        assert_eq!(schema.validity(ValidationMode::Strict), ValidityStatus::NotKnown);
-
-        let mut schema = XsdSchema::new();
-        schema.build().unwrap();
-        assert_eq!(schema.validation_attempted(), ValidationStatus::Full);
-        assert_eq!(schema.validity(ValidationMode::Strict), ValidityStatus::Valid);
+    fn test_validate_schema_constraints() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="status" type="xs:string" default="open" fixed="closed"/>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        assert_eq!(schema.errors().len(), 1);
+        assert!(schema.errors()[0].message.contains("both a default and a fixed value"));
+        assert_eq!(schema.validate_schema_constraints().len(), 1);
 
-        let mut schema = XsdSchema::new();
-        schema.parse_error(ParseError::new("test error"));
-        assert_eq!(schema.validity(ValidationMode::Strict), ValidityStatus::Invalid);
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="size">
+    <xs:restriction base="xs:integer">
+      <xs:enumeration value="1"/>
+      <xs:enumeration value="large"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:complexType name="base">
+    <xs:sequence>
+      <xs:element name="a" type="xs:string" minOccurs="0"/>
+      <xs:element name="b" type="xs:integer" default="0"/>
+    </xs:sequence>
+  </xs:complexType>
+  <xs:complexType name="restricted">
+    <xs:complexContent>
+      <xs:restriction base="base">
+        <xs:sequence>
+          <xs:element name="b" type="xs:integer" default="zero"/>
+          <xs:element name="c" type="xs:string"/>
+        </xs:sequence>
+      </xs:restriction>
+    </xs:complexContent>
+  </xs:complexType>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let messages: Vec<String> = schema.errors().iter().map(|e| e.message.clone()).collect();
+        assert_eq!(messages.len(), 3, "{:?}", messages);
+        assert!(messages.iter().any(|m| m.contains("Enumeration value 'large' of type 'size'")));
+        assert!(messages.iter().any(|m| m.contains("default value 'zero' of element 'b'")));
+        assert!(messages.iter().any(|m| m.contains("Element 'c' of type 'restricted'")));
+
+        // The XSD meta-schema passes the checks
+        let meta_schema = XsdSchema::from_sources(&[
+            (include_str!("schemas/XSD_1.0/XMLSchema.xsd"), "XMLSchema.xsd"),
+            (include_str!("schemas/XML/xml.xsd"), "http://www.w3.org/2001/xml.xsd"),
+        ])
+        .unwrap();
+        assert!(meta_schema.validate_schema_constraints().is_empty(), "{:?}", meta_schema.validate_schema_constraints());
     }
 
     #[test]
-    fn test_schema_source() {
-        let source = SchemaSource {
-            url: Some("http://example.com/schema.xsd".to_string()),
-            base_url: Some("http://example.com/".to_string()),
-            namespaces: HashMap::new(),
-            ..Default::default()
-        };
-
-        assert_eq!(source.url.as_deref(), Some("http://example.com/schema.xsd"));
-        assert_eq!(source.base_url.as_deref(), Some("http://example.com/"));
+    fn test_canonicalize() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="sizes">
+    <xs:list itemType="xs:integer"/>
+  </xs:simpleType>
+  <xs:element name="settings">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="enabled" type="xs:boolean"/>
+        <xs:element name="count" type="xs:integer"/>
+        <xs:element name="ratio" type="xs:decimal"/>
+        <xs:element name="label" type="xs:token"/>
+        <xs:element name="sizes" type="sizes"/>
+        <xs:element name="note" type="xs:string"/>
+      </xs:sequence>
+      <xs:attribute name="debug" type="xs:boolean"/>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let doc = Document::from_string(
+            r#"<settings debug="0"><enabled>1</enabled><count>+42</count><ratio>01.50</ratio><label>a   b</label><sizes>+1  02</sizes><note>+1</note></settings>"#,
+        )
+        .unwrap();
+
+        let canonical = schema.canonicalize(&doc).unwrap();
+        let root = canonical.root.as_ref().unwrap();
+        assert_eq!(root.get_attribute("debug"), Some("false"));
+        let text = |i: usize| root.children[i].text.as_deref().unwrap();
+        assert_eq!(text(0), "true");
+        assert_eq!(text(1), "42");
+        assert_eq!(text(2), "1.5");
+        assert_eq!(text(3), "a b");
+        assert_eq!(text(4), "1 2");
+        // Strings have no other canonical form
+        assert_eq!(text(5), "+1");
+        assert!(schema.is_valid(&canonical));
+
+        // Invalid documents are not canonicalized
+        let invalid = Document::from_string(
+            r#"<settings><enabled>yes</enabled><count>1</count><ratio>1</ratio><label/><sizes/><note/></settings>"#,
+        )
+        .unwrap();
+        assert!(schema.canonicalize(&invalid).is_err());
     }
 
     #[test]
-    fn test_schema_accessors() {
-        let mut schema = XsdSchema::new();
-        schema.source.url = Some("http://example.com/schema.xsd".to_string());
-        schema.source.base_url = Some("http://example.com/".to_string());
+    fn test_circular_derivation_detected() {
+        let xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:complexType name="aType">
+    <xs:complexContent>
+      <xs:extension base="bType">
+        <xs:sequence><xs:element name="a" type="xs:string"/></xs:sequence>
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:complexType name="bType">
+    <xs:complexContent>
+      <xs:extension base="aType">
+        <xs:sequence><xs:element name="b" type="xs:string"/></xs:sequence>
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:complexType name="cType">
+    <xs:complexContent>
+      <xs:extension base="aType">
+        <xs:sequence><xs:element name="c" type="xs:string"/></xs:sequence>
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+</xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
 
-        assert_eq!(schema.url(), Some("http://example.com/schema.xsd"));
-        assert_eq!(schema.base_url(), Some("http://example.com/"));
+        // The cycle is reported once, from the first type that enters it
+        let circular: Vec<_> = schema
+            .errors
+            .iter()
+            .filter(|error| error.message.contains("Circular reference"))
+            .collect();
+        assert_eq!(circular.len(), 1);
+        assert!(circular[0].message.contains("aType"));
+
+        // Types on the cycle keep their own content only
+        let Some(GlobalType::Complex(a_type)) = schema.maps.global_maps.types.get(&QName::local("aType")) else {
+            panic!("aType is missing");
+        };
+        assert_eq!(a_type.model_group().unwrap().particles.len(), 1);
     }
 
+    const NAMESPACE_USAGE_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           targetNamespace="http://example.com/order"
+           xmlns="http://example.com/order"
+           elementFormDefault="qualified">
+  <xs:element name="order">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:any processContents="skip" minOccurs="0" maxOccurs="unbounded"/>
+      </xs:sequence>
+      <xs:attribute name="kind" type="xs:QName"/>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+
     #[test]
-    fn test_create_qname() {
-        let mut schema = XsdSchema::new();
-        schema.set_target_namespace(Some("http://example.com/test".to_string()));
-        schema.add_namespace("tns", "http://example.com/test");
+    fn test_validate_xml_namespace_usage_clean_document() {
+        let schema = XsdSchema::from_string(NAMESPACE_USAGE_XSD).unwrap();
+        let doc = Document::from_string(
+            r#"<o:order xmlns:o="http://example.com/order" kind="o:retail"/>"#,
+        )
+        .unwrap();
+        assert!(schema.validate_xml_namespace_usage(&doc).is_empty());
+    }
 
-        let qname = schema.create_qname("element");
-        assert_eq!(qname, "tns:element");
+    #[test]
+    fn test_validate_xml_namespace_usage_reports_problems() {
+        let schema = XsdSchema::from_string(NAMESPACE_USAGE_XSD).unwrap();
+        let doc = Document::from_string(
+            r#"<order xmlns="http://example.com/order" xmlns:x="http://example.com/x" kind="p:retail">
+                 <x:note xmlns:x="http://example.com/other"/>
+                 <y:extra/>
+               </order>"#,
+        )
+        .unwrap();
+
+        let usage_errors = schema.validate_xml_namespace_usage(&doc);
+        let kinds: Vec<_> = usage_errors.iter().map(|error| (error.kind, error.path.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (NamespaceUsageKind::UndeclaredPrefix, "/order"),
+                (NamespaceUsageKind::ShadowedDeclaration, "/order/note"),
+                (NamespaceUsageKind::UnknownNamespace, "/order/note"),
+                (NamespaceUsageKind::UndeclaredPrefix, "/order/extra"),
+            ]
+        );
+        assert!(usage_errors[0].message.contains("'p'"));
+        assert!(usage_errors[2].message.contains("http://example.com/other"));
 
-        // Without matching namespace prefix
-        let mut schema2 = XsdSchema::new();
-        schema2.set_target_namespace(Some("http://example.com/test".to_string()));
-        let qname2 = schema2.create_qname("element");
-        assert_eq!(qname2, "element");
+        // Reported as warnings, separately from the validity errors
+        let result = schema.validate_with_mode(&doc, ValidationMode::Lax);
+        assert_eq!(result.warnings.len(), 4);
+        assert!(result.warnings[0].ends_with("(at /order)"));
     }
 
     #[test]
-    fn test_empty_counts() {
-        let schema = XsdSchema::new();
-        assert_eq!(schema.element_count(), 0);
-        assert_eq!(schema.type_count(), 0);
-        assert_eq!(schema.element_names().count(), 0);
-        assert_eq!(schema.type_names().count(), 0);
+    fn test_find_types_using_attribute() {
+        let schema = XsdSchema::from_string(include_str!("../../examples/data/book.xsd")).unwrap();
+        let book_type = QName::namespaced("http://example.com/book", "bookType");
+
+        assert_eq!(schema.find_types_using_attribute(&QName::local("isbn")), vec![&book_type]);
+        assert_eq!(schema.find_types_using_attribute(&QName::local("edition")), vec![&book_type]);
+        assert_eq!(schema.get_attribute_use_context(&QName::local("isbn")), vec![book_type]);
+        assert!(schema.find_types_using_attribute(&QName::local("missing")).is_empty());
     }
 
     #[test]
-    fn test_redefine_group_basic() {
-        // Test basic xs:redefine parsing with a group that references itself
-        // This tests that:
-        // 1. xs:redefine is parsed correctly
-        // 2. The redefined group has the redefine back-reference set
-        // 3. Self-references in the group are resolved to the original
+    fn test_find_elements_using_type() {
+        let schema = XsdSchema::from_string(include_str!("../../examples/data/book.xsd")).unwrap();
+        let ns = "http://example.com/book";
 
-        // Create a temporary test schema with redefine
-        use std::io::Write;
+        assert_eq!(
+            schema.find_elements_using_type(&QName::namespaced(ns, "bookType")),
+            vec![&QName::namespaced(ns, "book")]
+        );
+        assert_eq!(
+            schema.find_elements_using_type(&QName::namespaced(ns, "personType")),
+            vec![&QName::namespaced(ns, "author")]
+        );
+        let string_elements = schema.find_elements_using_type(&QName::namespaced(XSD_NAMESPACE, "string"));
+        assert_eq!(string_elements.len(), 3);
+        assert!(string_elements.contains(&&QName::namespaced(ns, "title")));
+    }
 
-        // First create the "base" schema that will be redefined
-        let base_schema = r#"<?xml version="1.0" encoding="UTF-8"?>
+    #[test]
+    fn test_find_elements_by_type() {
+        let xsd = r#"<?xml version="1.0"?>
 <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
-    <xs:group name="testGroup">
-        <xs:sequence>
-            <xs:element name="originalElement" type="xs:string"/>
-        </xs:sequence>
-    </xs:group>
+  <xs:simpleType name="nameType">
+    <xs:restriction base="xs:string"/>
+  </xs:simpleType>
+  <xs:simpleType name="codeType">
+    <xs:restriction base="xs:string">
+      <xs:pattern value="[A-Z]+"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:element name="title" type="xs:string"/>
+  <xs:element name="name" type="nameType"/>
+  <xs:element name="alias" substitutionGroup="title"/>
+  <xs:element name="code" type="codeType"/>
+  <xs:element name="count" type="xs:integer"/>
+  <xs:attribute name="lang" type="nameType"/>
+  <xs:attribute name="ref" type="codeType"/>
 </xs:schema>"#;
+        let schema = XsdSchema::from_string(xsd).unwrap();
+        let string_type = builtin_type_qname("string").unwrap();
 
-        // Create the "main" schema that redefines the group
-        let main_schema = r#"<?xml version="1.0" encoding="UTF-8"?>
-<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
-    <xs:redefine schemaLocation="base.xsd">
-        <xs:group name="testGroup">
-            <xs:sequence>
-                <xs:element name="newElement" type="xs:string"/>
-                <xs:group ref="testGroup"/>
-            </xs:sequence>
-        </xs:group>
-    </xs:redefine>
+        assert_eq!(
+            schema.find_elements_by_type(string_type),
+            vec![QName::local("alias"), QName::local("name"), QName::local("title")]
+        );
+        assert_eq!(schema.find_elements_by_type(&QName::local("codeType")), vec![QName::local("code")]);
+        assert!(schema.find_elements_by_type(&QName::local("missing")).is_empty());
 
-    <xs:element name="root">
-        <xs:complexType>
-            <xs:group ref="testGroup"/>
-        </xs:complexType>
-    </xs:element>
-</xs:schema>"#;
+        assert_eq!(schema.find_attributes_by_type(string_type), vec![QName::local("lang")]);
+        assert_eq!(schema.find_attributes_by_type(&QName::local("codeType")), vec![QName::local("ref")]);
+    }
 
-        // Write temp files
-        let temp_dir = std::env::temp_dir().join("xmlschema_test_redefine");
-        std::fs::create_dir_all(&temp_dir).expect("Failed to create temp dir");
+    #[test]
+    fn test_usage_lookups_before_build() {
+        let mut schema = XsdSchema::new();
+        let mut ct = XsdComplexType::empty(Some(QName::local("itemType")));
+        ct.attributes
+            .add_attribute(Arc::new(XsdAttribute::new(QName::local("code"))))
+            .unwrap();
+        schema.maps.register_complex_type(QName::local("itemType"), Arc::new(ct));
+        let mut item = XsdElement::new(QName::local("item"), ElementType::Any);
+        item.type_name = Some(QName::local("itemType"));
+        schema.maps.global_maps.elements.insert(QName::local("item"), Arc::new(item));
+
+        // Scanned without the index
+        assert_eq!(schema.find_types_using_attribute(&QName::local("code")), vec![&QName::local("itemType")]);
+        assert_eq!(schema.find_elements_using_type(&QName::local("itemType")), vec![&QName::local("item")]);
 
-        let base_path = temp_dir.join("base.xsd");
-        let main_path = temp_dir.join("main.xsd");
+        schema.build().unwrap();
+        assert_eq!(schema.find_types_using_attribute(&QName::local("code")), vec![&QName::local("itemType")]);
+        assert_eq!(schema.find_elements_using_type(&QName::local("itemType")), vec![&QName::local("item")]);
+    }
 
-        let mut base_file = std::fs::File::create(&base_path).expect("Failed to create base.xsd");
-        base_file.write_all(base_schema.as_bytes()).expect("Failed to write base.xsd");
+    const LINT_XSD: &str = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="answerType">
+    <xs:restriction base="xs:integer">
+      <xs:minInclusive value="42"/>
+      <xs:maxInclusive value="42"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="rangeType">
+    <xs:restriction base="xs:integer">
+      <xs:minInclusive value="1"/>
+      <xs:maxInclusive value="10"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="codeType">
+    <xs:restriction base="xs:string">
+      <xs:length value="3"/>
+      <xs:maxLength value="5"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:complexType name="shapeType" abstract="true">
+    <xs:sequence><xs:element name="label" type="xs:string"/></xs:sequence>
+  </xs:complexType>
+  <xs:complexType name="baseType" abstract="true">
+    <xs:sequence><xs:element name="id" type="xs:string"/></xs:sequence>
+  </xs:complexType>
+  <xs:complexType name="itemType">
+    <xs:complexContent>
+      <xs:extension base="baseType">
+        <xs:sequence><xs:element ref="part"/></xs:sequence>
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:attributeGroup name="langGroup">
+    <xs:attribute name="lang" type="xs:language"/>
+  </xs:attributeGroup>
+  <xs:attributeGroup name="sizeGroup">
+    <xs:attribute name="width" type="xs:int"/>
+    <xs:attribute name="height" type="xs:int"/>
+  </xs:attributeGroup>
+  <xs:element name="part" type="codeType"/>
+  <xs:element name="item" type="itemType"/>
+  <xs:element name="orphan" type="xs:string"/>
+</xs:schema>"#;
 
-        let mut main_file = std::fs::File::create(&main_path).expect("Failed to create main.xsd");
-        main_file.write_all(main_schema.as_bytes()).expect("Failed to write main.xsd");
+    #[test]
+    fn test_check_redundant_constraints() {
+        let schema = XsdSchema::from_string(LINT_XSD).unwrap();
+        let warnings = schema.check_redundant_constraints();
+
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert_eq!(warnings[0].kind, LintKind::SingleValueRange);
+        assert_eq!(warnings[0].component, QName::local("answerType"));
+        assert!(warnings[0].to_string().contains("(42)"));
+        assert_eq!(warnings[1].kind, LintKind::RedundantLengthFacets);
+        assert_eq!(warnings[1].component, QName::local("codeType"));
+    }
 
-        // Parse the main schema (which includes the redefine)
-        let result = XsdSchema::from_file(&main_path);
+    #[test]
+    fn test_lint() {
+        let schema = XsdSchema::from_string(LINT_XSD).unwrap();
+        let report = schema.lint();
+        let components = |kind| report.of_kind(kind).map(|w| w.component.local_name.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(report.warnings.len(), 6, "{:?}", report.warnings);
+        assert_eq!(components(LintKind::SingleValueRange), ["answerType"]);
+        assert_eq!(components(LintKind::RedundantLengthFacets), ["codeType"]);
+        // baseType is extended by itemType
+        assert_eq!(components(LintKind::UnreachableAbstractType), ["shapeType"]);
+        // part is referenced by itemType
+        assert_eq!(components(LintKind::UnusedElement), ["item", "orphan"]);
+        assert_eq!(components(LintKind::SingleAttributeGroup), ["langGroup"]);
+    }
 
-        // Clean up temp files
-        let _ = std::fs::remove_file(&base_path);
-        let _ = std::fs::remove_file(&main_path);
-        let _ = std::fs::remove_dir(&temp_dir);
+    #[test]
+    fn test_precompile_patterns() {
+        let mut schema = XsdSchema::from_string(
+            r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="zipType">
+    <xs:restriction base="xs:string">
+      <xs:pattern value="\d{5}"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="brokenType">
+    <xs:restriction base="xs:string">
+      <xs:pattern value="[a-z"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:simpleType name="plainType">
+    <xs:restriction base="xs:string"/>
+  </xs:simpleType>
+</xs:schema>"#,
+        )
+        .unwrap();
+
+        assert!(schema.compiled_patterns(&QName::local("zipType")).is_none());
+
+        let failures = schema.precompile_patterns();
+        assert_eq!(failures.len(), 1, "{:?}", failures);
+        assert_eq!(failures[0].0, QName::local("brokenType"));
+        assert_eq!(failures[0].1, "^[a-z$");
+
+        let zip = schema.compiled_patterns(&QName::local("zipType")).unwrap();
+        assert_eq!(zip.len(), 1);
+        assert!(zip[0].is_match("12345"));
+        assert!(!zip[0].is_match("1234"));
+        assert!(schema.compiled_patterns(&QName::local("brokenType")).unwrap().is_empty());
+        assert!(schema.compiled_patterns(&QName::local("plainType")).is_none());
+    }
 
-        // Check results
-        let schema = result.expect("Failed to parse schema with redefine");
+    #[test]
+    fn test_lint_clean_schema() {
+        let schema = XsdSchema::from_string(
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="percentType">
+    <xs:restriction base="xs:decimal">
+      <xs:minInclusive value="0"/>
+      <xs:maxInclusive value="100.0"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:element name="root">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element ref="member" maxOccurs="unbounded"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+  <xs:element name="member" type="percentType"/>
+  <xs:element name="special" type="percentType" substitutionGroup="member"/>
+</xs:schema>"#,
+        )
+        .unwrap();
+        let report = schema.lint();
+
+        // The document root is never referenced
+        assert_eq!(report.warnings.len(), 1, "{:?}", report.warnings);
+        assert_eq!(report.warnings[0].kind, LintKind::UnusedElement);
+        assert_eq!(report.warnings[0].component, QName::local("root"));
+    }
 
-        // Verify the schema has the redefine recorded
-        assert!(!schema.redefines.is_empty(), "Schema should have redefines");
+    const SUBSTITUTION_XSD: &str = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="shapes">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element ref="shape" maxOccurs="unbounded"/>
+        <xs:element name="count" type="xs:int"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+  <xs:element name="pair">
+    <xs:complexType>
+      <xs:all>
+        <xs:element ref="shape"/>
+        <xs:element name="label" type="xs:string"/>
+      </xs:all>
+    </xs:complexType>
+  </xs:element>
+  <xs:element name="shape" type="xs:string" abstract="true"/>
+  <xs:element name="circle" type="xs:decimal" substitutionGroup="shape"/>
+  <xs:element name="square" type="xs:string" substitutionGroup="shape"/>
+  <xs:element name="bigCircle" type="xs:decimal" substitutionGroup="circle"/>
+  <xs:element name="polygon" type="xs:string" abstract="true" substitutionGroup="shape"/>
+</xs:schema>"#;
 
-        // Verify the testGroup exists and has the redefine back-reference
-        let test_group_name = crate::namespaces::QName::local("testGroup");
-        let group = schema.maps.global_maps.groups.get(&test_group_name);
-        assert!(group.is_some(), "testGroup should exist in global maps");
+    #[test]
+    fn test_collect_substitution_groups() {
+        let schema = XsdSchema::from_string(SUBSTITUTION_XSD).unwrap();
+        let groups = schema.collect_substitution_groups();
+
+        let names = |head: &str| -> Vec<String> {
+            let mut names: Vec<String> =
+                groups[&QName::local(head)].iter().map(|e| e.name.local_name.clone()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(groups.len(), 2);
+        assert_eq!(names("shape"), vec!["circle", "polygon", "square"]);
+        assert_eq!(names("circle"), vec!["bigCircle"]);
+
+        let mut members: Vec<String> = schema
+            .substitution_group_members(&QName::local("shape"))
+            .iter()
+            .map(|e| e.name.local_name.clone())
+            .collect();
+        members.sort();
+        assert_eq!(members, vec!["bigCircle", "circle", "polygon", "square"]);
+        assert!(schema.substitution_group_members(&QName::local("square")).is_empty());
+    }
 
-        let group = group.unwrap();
-        assert!(group.redefine.is_some(), "Redefined group should have redefine back-reference");
+    #[test]
+    fn test_substitution_heads() {
+        let schema = XsdSchema::from_string(SUBSTITUTION_XSD).unwrap();
+        assert_eq!(
+            schema.substitution_heads(&QName::local("bigCircle")),
+            vec![QName::local("circle"), QName::local("shape")]
+        );
+        assert!(schema.substitution_heads(&QName::local("polygon")).is_empty());
+        assert!(schema.substitution_heads(&QName::local("count")).is_empty());
+    }
 
-        // Verify the group has content (particles from self-reference resolution)
-        assert!(!group.particles.is_empty(), "Redefined group should have particles after resolution");
+    #[test]
+    fn test_validate_substitution_group_members() {
+        let schema = XsdSchema::from_string(SUBSTITUTION_XSD).unwrap();
+        let xml = "<shapes><circle>1.5</circle><square>a</square><bigCircle>20</bigCircle><count>3</count></shapes>";
+        let result = schema.validate_string(xml);
+        assert!(result.valid, "{:?}", result.error_messages());
+        assert!(schema.validate_reader(xml.as_bytes()).valid);
+
+        let result = schema.validate_string("<pair><label>x</label><square>a</square></pair>");
+        assert!(result.valid, "{:?}", result.error_messages());
+        assert!(schema.validate_reader("<pair><label>x</label><circle>2</circle></pair>".as_bytes()).valid);
+
+        // Members are validated against their own declarations
+        assert!(!schema.validate_string("<shapes><circle>round</circle><count>1</count></shapes>").valid);
+        assert!(!schema.validate_reader("<shapes><circle>round</circle><count>1</count></shapes>".as_bytes()).valid);
+    }
+
+    #[test]
+    fn test_validate_substitution_group_errors() {
+        let schema = XsdSchema::from_string(SUBSTITUTION_XSD).unwrap();
+        for xml in [
+            "<shapes><shape>a</shape><count>1</count></shapes>",
+            "<shapes><polygon>a</polygon><count>1</count></shapes>",
+            "<shapes><count>1</count><circle>1</circle></shapes>",
+        ] {
+            assert!(!schema.validate_string(xml).valid, "{}", xml);
+            assert!(!schema.validate_reader(xml.as_bytes()).valid, "{}", xml);
+        }
     }
 }
@@ -12,10 +12,11 @@ use crate::namespaces::QName;
 use crate::validators::base::{TypeValidator, ValidationStatus, Validator};
 use crate::validators::builtins::{get_builtin_type, validate_builtin, BuiltinType, XsdValue, XSD_NAMESPACE};
 use crate::validators::facets::{
-    EnumerationFacet, FractionDigitsFacet, LengthFacet, MaxExclusiveFacet, MaxInclusiveFacet,
+    EnumerationFacet, ExplicitTimezoneFacet, FractionDigitsFacet, LengthFacet, MaxExclusiveFacet, MaxInclusiveFacet,
     MaxLengthFacet, MinExclusiveFacet, MinInclusiveFacet, MinLengthFacet, PatternFacet,
     TotalDigitsFacet, WhiteSpace,
 };
+use rust_decimal::Decimal;
 use std::sync::Arc;
 
 // =============================================================================
@@ -64,6 +65,8 @@ pub struct FacetSet {
     pub total_digits: Option<TotalDigitsFacet>,
     /// Fraction digits facet
     pub fraction_digits: Option<FractionDigitsFacet>,
+    /// Explicit timezone facet (XSD 1.1)
+    pub explicit_timezone: Option<ExplicitTimezoneFacet>,
 }
 
 impl FacetSet {
@@ -102,8 +105,111 @@ impl FacetSet {
             facet.validate(&normalized)?;
         }
 
+        // Validate timezone presence of temporal values
+        if let Some(ref facet) = self.explicit_timezone {
+            facet.validate(&normalized)?;
+        }
+
+        // Validate the value range
+        if let Some(ref facet) = self.min_inclusive {
+            facet.validate_lexical(&normalized)?;
+        }
+        if let Some(ref facet) = self.min_exclusive {
+            facet.validate_lexical(&normalized)?;
+        }
+        if let Some(ref facet) = self.max_inclusive {
+            facet.validate_lexical(&normalized)?;
+        }
+        if let Some(ref facet) = self.max_exclusive {
+            facet.validate_lexical(&normalized)?;
+        }
+
+        // Validate the digit counts of decimal values
+        if let Some(ref facet) = self.total_digits {
+            facet.validate_lexical(&normalized)?;
+        }
+        if let Some(ref facet) = self.fraction_digits {
+            facet.validate_lexical(&normalized)?;
+        }
+
         Ok(())
     }
+
+    /// Check if no facet is set
+    pub fn is_empty(&self) -> bool {
+        self.length.is_none()
+            && self.min_length.is_none()
+            && self.max_length.is_none()
+            && self.patterns.is_empty()
+            && self.enumeration.is_none()
+            && self.white_space.is_none()
+            && self.min_inclusive.is_none()
+            && self.max_inclusive.is_none()
+            && self.min_exclusive.is_none()
+            && self.max_exclusive.is_none()
+            && self.total_digits.is_none()
+            && self.fraction_digits.is_none()
+            && self.explicit_timezone.is_none()
+    }
+
+    /// Describe the value range bounds, e.g. `between 1..100` or `> 0`
+    pub fn describe_range(&self) -> Option<String> {
+        let lower = match (&self.min_inclusive, &self.min_exclusive) {
+            (Some(f), _) => Some((">=", f.value.to_string())),
+            (None, Some(f)) => Some((">", f.value.to_string())),
+            (None, None) => None,
+        };
+        let upper = match (&self.max_inclusive, &self.max_exclusive) {
+            (Some(f), _) => Some(("<=", f.value.to_string())),
+            (None, Some(f)) => Some(("<", f.value.to_string())),
+            (None, None) => None,
+        };
+
+        match (lower, upper) {
+            (Some((">=", lo)), Some(("<=", hi))) => Some(format!("between {}..{}", lo, hi)),
+            (Some((op1, lo)), Some((op2, hi))) => Some(format!("{} {} and {} {}", op1, lo, op2, hi)),
+            (Some((op, bound)), None) | (None, Some((op, bound))) => Some(format!("{} {}", op, bound)),
+            (None, None) => None,
+        }
+    }
+
+    /// Describe the lexical constraints (patterns, lengths and digits)
+    ///
+    /// Enumerations and value ranges are described separately, see
+    /// [`SimpleType::facet_summary`].
+    pub fn describe_constraints(&self) -> Vec<String> {
+        let mut constraints = Vec::new();
+
+        for pattern in &self.patterns {
+            // Patterns are stored anchored; show them as written in the schema
+            let source = pattern.pattern.strip_prefix('^').unwrap_or(&pattern.pattern);
+            let source = source.strip_suffix('$').unwrap_or(source);
+            constraints.push(format!("pattern={}", source));
+        }
+
+        if let Some(ref length) = self.length {
+            constraints.push(format!("length={}", length.value));
+        } else {
+            match (&self.min_length, &self.max_length) {
+                (Some(min), Some(max)) => constraints.push(format!("length between {}..{}", min.value, max.value)),
+                (Some(min), None) => constraints.push(format!("length >= {}", min.value)),
+                (None, Some(max)) => constraints.push(format!("length <= {}", max.value)),
+                (None, None) => {}
+            }
+        }
+
+        if let Some(ref facet) = self.total_digits {
+            constraints.push(format!("totalDigits={}", facet.value));
+        }
+        if let Some(ref facet) = self.fraction_digits {
+            constraints.push(format!("fractionDigits={}", facet.value));
+        }
+        if let Some(ref facet) = self.explicit_timezone {
+            constraints.push(format!("explicitTimezone={}", facet));
+        }
+
+        constraints
+    }
 }
 
 // =============================================================================
@@ -158,6 +264,184 @@ pub trait SimpleType: TypeValidator {
     fn member_types(&self) -> &[Arc<dyn SimpleType + Send + Sync>] {
         &[]
     }
+
+    /// Get the local name of the built-in type this type is derived from
+    ///
+    /// Returns None for list and union types.
+    fn primitive_name(&self) -> Option<&str> {
+        SimpleType::base_type(self).and_then(|base| base.primitive_name())
+    }
+
+    /// Get the facets in effect for this type, including inherited ones
+    ///
+    /// Facets declared on this type override those of its base types;
+    /// patterns from every derivation step are kept, since all must match.
+    fn effective_facets(&self) -> FacetSet {
+        let mut effective = self.facets().clone();
+        let mut current = SimpleType::base_type(self);
+        while let Some(base) = current {
+            let facets = base.facets();
+            effective.length = effective.length.or_else(|| facets.length.clone());
+            effective.min_length = effective.min_length.or_else(|| facets.min_length.clone());
+            effective.max_length = effective.max_length.or_else(|| facets.max_length.clone());
+            effective.patterns.extend(facets.patterns.iter().cloned());
+            effective.enumeration = effective.enumeration.or_else(|| facets.enumeration.clone());
+            effective.white_space = effective.white_space.or(facets.white_space);
+            effective.min_inclusive = effective.min_inclusive.or_else(|| facets.min_inclusive.clone());
+            effective.max_inclusive = effective.max_inclusive.or_else(|| facets.max_inclusive.clone());
+            effective.min_exclusive = effective.min_exclusive.or_else(|| facets.min_exclusive.clone());
+            effective.max_exclusive = effective.max_exclusive.or_else(|| facets.max_exclusive.clone());
+            effective.total_digits = effective.total_digits.or_else(|| facets.total_digits.clone());
+            effective.fraction_digits = effective.fraction_digits.or_else(|| facets.fraction_digits.clone());
+            effective.explicit_timezone = effective.explicit_timezone.or_else(|| facets.explicit_timezone.clone());
+            current = SimpleType::base_type(base);
+        }
+        effective
+    }
+
+    /// Describe the constraints of this type in a human-readable form
+    ///
+    /// Examples: `xs:string with pattern=[a-z]{3,}, length between 3..10`,
+    /// `xs:integer between 1..100`, `enumeration of: fiction, non-fiction`.
+    fn facet_summary(&self) -> String {
+        let facets = self.effective_facets();
+        if let Some(ref enumeration) = facets.enumeration {
+            return format!("enumeration of: {}", enumeration.values.join(", "));
+        }
+
+        let mut summary = match self.variety() {
+            SimpleTypeVariety::Atomic => match self.primitive_name() {
+                Some(name) => format!("xs:{}", name),
+                None => "xs:anySimpleType".to_string(),
+            },
+            SimpleTypeVariety::List => match list_item_type(self) {
+                Some(item) => format!("list of {}", item.facet_summary()),
+                None => "list".to_string(),
+            },
+            SimpleTypeVariety::Union => {
+                let members: Vec<String> = union_member_types(self)
+                    .iter()
+                    .map(|member| member.facet_summary())
+                    .collect();
+                format!("union of ({})", members.join(" | "))
+            }
+        };
+
+        if let Some(range) = facets.describe_range() {
+            summary.push(' ');
+            summary.push_str(&range);
+        }
+        let constraints = facets.describe_constraints();
+        if !constraints.is_empty() {
+            summary.push_str(" with ");
+            summary.push_str(&constraints.join(", "));
+        }
+        summary
+    }
+
+    /// Get the canonical lexical representation of a value of this type
+    ///
+    /// The value is validated first. Lists canonicalize each item, unions
+    /// use the first member type that accepts the value, and atomic values
+    /// are rewritten from their decoded form: `1` becomes `true` for
+    /// `xs:boolean`, `+42` becomes `42` for `xs:integer` and `01.50` becomes
+    /// `1.5` for `xs:decimal`. Types without a distinct canonical form keep
+    /// their white space normalized value.
+    fn canonical_value(&self, value: &str) -> Result<String> {
+        match self.variety() {
+            SimpleTypeVariety::List => {
+                self.validate_value(value)?;
+                let Some(item_type) = list_item_type(self) else {
+                    return Ok(WhiteSpace::Collapse.normalize(value));
+                };
+                let items = value
+                    .split_whitespace()
+                    .map(|item| item_type.canonical_value(item))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(items.join(" "))
+            }
+            SimpleTypeVariety::Union => {
+                self.validate_value(value)?;
+                union_member_types(self)
+                    .iter()
+                    .find_map(|member| member.canonical_value(value).ok())
+                    .ok_or_else(|| {
+                        Error::Validation(ValidationError::new(format!(
+                            "Value '{}' does not match any member type of the union",
+                            value
+                        )))
+                    })
+            }
+            SimpleTypeVariety::Atomic => {
+                let normalized = self.white_space().normalize(value);
+                let canonical = match self.validate_value(&normalized)? {
+                    XsdValue::Boolean(b) => b.to_string(),
+                    XsdValue::Integer(i) => i.to_string(),
+                    XsdValue::Decimal(d) => canonical_decimal(&d),
+                    XsdValue::Float(f) | XsdValue::Double(f) => canonical_double(f),
+                    XsdValue::Binary(bytes) if self.primitive_name() == Some("base64Binary") => {
+                        use base64::Engine;
+                        base64::engine::general_purpose::STANDARD.encode(bytes)
+                    }
+                    binary @ XsdValue::Binary(_) => binary.to_string(),
+                    _ => normalized,
+                };
+                Ok(canonical)
+            }
+        }
+    }
+}
+
+/// Canonical decimal: no leading `+`, no superfluous zeros, and at least
+/// one digit after the decimal point
+fn canonical_decimal(value: &Decimal) -> String {
+    let canonical = value.normalize().to_string();
+    if canonical.contains('.') {
+        canonical
+    } else {
+        format!("{}.0", canonical)
+    }
+}
+
+/// Canonical float/double: a mantissa with a single digit before the
+/// decimal point followed by an exponent, e.g. `1.5E2`
+fn canonical_double(value: f64) -> String {
+    if !value.is_finite() {
+        return XsdValue::Double(value).to_string();
+    }
+    let formatted = format!("{:E}", value);
+    match formatted.split_once('E') {
+        Some((mantissa, exponent)) if !mantissa.contains('.') => format!("{}.0E{}", mantissa, exponent),
+        _ => formatted,
+    }
+}
+
+/// Find the item type of a list type, looking through restrictions of it
+pub(crate) fn list_item_type<T: SimpleType + ?Sized>(simple_type: &T) -> Option<&Arc<dyn SimpleType + Send + Sync>> {
+    simple_type
+        .item_type()
+        .or_else(|| SimpleType::base_type(simple_type).and_then(|base| list_item_type(base)))
+}
+
+/// Name a member type of a union in error messages
+///
+/// Anonymous and built-in types are described by their facets instead.
+fn member_label(member: &(dyn SimpleType + Send + Sync)) -> String {
+    match member.name() {
+        Some(name) => name.to_string(),
+        None => member.facet_summary(),
+    }
+}
+
+/// Find the member types of a union type, looking through restrictions of it
+fn union_member_types<T: SimpleType + ?Sized>(simple_type: &T) -> &[Arc<dyn SimpleType + Send + Sync>] {
+    let members = simple_type.member_types();
+    if members.is_empty() {
+        if let Some(base) = SimpleType::base_type(simple_type) {
+            return union_member_types(base);
+        }
+    }
+    members
 }
 
 // =============================================================================
@@ -232,16 +516,75 @@ impl XsdAtomicType {
         Ok(self)
     }
 
+    /// Add a compiled pattern facet
+    pub fn with_pattern_facet(mut self, facet: PatternFacet) -> Self {
+        self.facet_set.patterns.push(facet);
+        self
+    }
+
     /// Add an enumeration facet
     pub fn with_enumeration(mut self, values: Vec<String>) -> Self {
         self.facet_set.enumeration = Some(EnumerationFacet::new(values));
         self
     }
 
+    /// Set the white space facet
+    pub fn with_white_space(mut self, white_space: WhiteSpace) -> Self {
+        self.facet_set.white_space = Some(white_space);
+        self
+    }
+
+    /// Add an explicit timezone facet
+    pub fn with_explicit_timezone(mut self, facet: ExplicitTimezoneFacet) -> Self {
+        self.facet_set.explicit_timezone = Some(facet);
+        self
+    }
+
+    /// Add a total digits facet
+    pub fn with_total_digits(mut self, total_digits: u32) -> Self {
+        self.facet_set.total_digits = Some(TotalDigitsFacet::new(total_digits));
+        self
+    }
+
+    /// Add a fraction digits facet
+    pub fn with_fraction_digits(mut self, fraction_digits: u32) -> Self {
+        self.facet_set.fraction_digits = Some(FractionDigitsFacet::new(fraction_digits));
+        self
+    }
+
+    /// Add a min inclusive facet
+    pub fn with_min_inclusive(mut self, facet: MinInclusiveFacet) -> Self {
+        self.facet_set.min_inclusive = Some(facet);
+        self
+    }
+
+    /// Add a max inclusive facet
+    pub fn with_max_inclusive(mut self, facet: MaxInclusiveFacet) -> Self {
+        self.facet_set.max_inclusive = Some(facet);
+        self
+    }
+
+    /// Add a min exclusive facet
+    pub fn with_min_exclusive(mut self, facet: MinExclusiveFacet) -> Self {
+        self.facet_set.min_exclusive = Some(facet);
+        self
+    }
+
+    /// Add a max exclusive facet
+    pub fn with_max_exclusive(mut self, facet: MaxExclusiveFacet) -> Self {
+        self.facet_set.max_exclusive = Some(facet);
+        self
+    }
+
     /// Get the built-in type name
     pub fn builtin_name(&self) -> &str {
         &self.builtin_name
     }
+
+    /// Describe the facets of this type, e.g. `xs:string with length between 2..5`
+    pub fn describe_facets(&self) -> String {
+        self.facet_summary()
+    }
 }
 
 impl Validator for XsdAtomicType {
@@ -301,11 +644,10 @@ impl SimpleType for XsdAtomicType {
     }
 
     fn validate_value(&self, value: &str) -> Result<XsdValue> {
-        // First validate against facets
-        self.facet_set.validate(value)?;
-
-        // Then validate against the built-in type
-        validate_builtin(&self.builtin_name, value)
+        // Facets and the built-in type both check the white space normalized value
+        let normalized = self.white_space().normalize(value);
+        self.facet_set.validate(&normalized)?;
+        validate_builtin(&self.builtin_name, &normalized)
     }
 
     fn qualified_name_string(&self) -> Option<String> {
@@ -316,6 +658,10 @@ impl SimpleType for XsdAtomicType {
             Some(format!("{{{}}}{}", XSD_NAMESPACE, self.builtin_name))
         }
     }
+
+    fn primitive_name(&self) -> Option<&str> {
+        Some(&self.builtin_name)
+    }
 }
 
 /// Format a QName as {namespace}localName
@@ -485,18 +831,28 @@ impl SimpleType for XsdListType {
             }
         }
 
-        // Validate each item against the item type
-        let mut values = Vec::with_capacity(items.len());
-        for (i, item) in items.iter().enumerate() {
-            match self.item_type.validate_value(item) {
-                Ok(v) => values.push(v),
-                Err(e) => {
-                    return Err(Error::Validation(
-                        ValidationError::new(format!("Invalid list item at position {}", i + 1))
-                            .with_reason(format!("Item '{}': {}", item, e)),
-                    ));
-                }
-            }
+        // Validate each item against the item type, reporting every invalid position
+        let invalid: Vec<(usize, &str, Error)> = items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| self.item_type.validate_value(item).err().map(|e| (i + 1, *item, e)))
+            .collect();
+        if !invalid.is_empty() {
+            let positions: Vec<String> = invalid.iter().map(|(position, _, _)| position.to_string()).collect();
+            let reasons: Vec<String> = invalid
+                .iter()
+                .map(|(position, item, e)| format!("item {} '{}': {}", position, item, e))
+                .collect();
+            let plural = if invalid.len() > 1 { "s" } else { "" };
+            return Err(Error::Validation(
+                ValidationError::new(format!(
+                    "Invalid list item{} at position{} {}",
+                    plural,
+                    plural,
+                    positions.join(", ")
+                ))
+                .with_reason(reasons.join("; ")),
+            ));
         }
 
         // Return as string representation (list doesn't have a special XsdValue variant)
@@ -634,26 +990,23 @@ impl SimpleType for XsdUnionType {
         // First validate against union-level facets (pattern, enumeration)
         self.facet_set.validate(value)?;
 
-        // Try each member type in order
-        let mut last_error = None;
+        // Try each member type in order, the first one that accepts the value wins
+        let mut failures = Vec::with_capacity(self.member_types.len());
         for member in &self.member_types {
             match member.validate_value(value) {
                 Ok(v) => return Ok(v),
-                Err(e) => {
-                    last_error = Some(e);
-                }
+                Err(e) => failures.push((member_label(member.as_ref()), e)),
             }
         }
 
-        // No member type matched
+        let tried: Vec<&str> = failures.iter().map(|(label, _)| label.as_str()).collect();
+        let reasons: Vec<String> = failures.iter().map(|(label, e)| format!("{}: {}", label, e)).collect();
         Err(Error::Validation(
-            ValidationError::new("Value does not match any member type of the union")
-                .with_reason(format!(
-                    "Value '{}' failed validation against all {} member types. Last error: {}",
-                    value,
-                    self.member_types.len(),
-                    last_error.map(|e| e.to_string()).unwrap_or_default()
-                )),
+            ValidationError::new(format!(
+                "Value does not match any member type of the union ({})",
+                tried.join(", ")
+            ))
+            .with_reason(format!("Value '{}' is invalid for every member type: {}", value, reasons.join("; "))),
         ))
     }
 
@@ -735,12 +1088,66 @@ impl XsdRestrictedType {
         Ok(self)
     }
 
+    /// Add a compiled pattern facet
+    pub fn with_pattern_facet(mut self, facet: PatternFacet) -> Self {
+        self.facet_set.patterns.push(facet);
+        self
+    }
+
     /// Add an enumeration facet
     pub fn with_enumeration(mut self, values: Vec<String>) -> Self {
         self.facet_set.enumeration = Some(EnumerationFacet::new(values));
         self
     }
 
+    /// Set the white space facet
+    pub fn with_white_space(mut self, white_space: WhiteSpace) -> Self {
+        self.facet_set.white_space = Some(white_space);
+        self
+    }
+
+    /// Add an explicit timezone facet
+    pub fn with_explicit_timezone(mut self, facet: ExplicitTimezoneFacet) -> Self {
+        self.facet_set.explicit_timezone = Some(facet);
+        self
+    }
+
+    /// Add a total digits facet
+    pub fn with_total_digits(mut self, total_digits: u32) -> Self {
+        self.facet_set.total_digits = Some(TotalDigitsFacet::new(total_digits));
+        self
+    }
+
+    /// Add a fraction digits facet
+    pub fn with_fraction_digits(mut self, fraction_digits: u32) -> Self {
+        self.facet_set.fraction_digits = Some(FractionDigitsFacet::new(fraction_digits));
+        self
+    }
+
+    /// Add a min inclusive facet
+    pub fn with_min_inclusive(mut self, facet: MinInclusiveFacet) -> Self {
+        self.facet_set.min_inclusive = Some(facet);
+        self
+    }
+
+    /// Add a max inclusive facet
+    pub fn with_max_inclusive(mut self, facet: MaxInclusiveFacet) -> Self {
+        self.facet_set.max_inclusive = Some(facet);
+        self
+    }
+
+    /// Add a min exclusive facet
+    pub fn with_min_exclusive(mut self, facet: MinExclusiveFacet) -> Self {
+        self.facet_set.min_exclusive = Some(facet);
+        self
+    }
+
+    /// Add a max exclusive facet
+    pub fn with_max_exclusive(mut self, facet: MaxExclusiveFacet) -> Self {
+        self.facet_set.max_exclusive = Some(facet);
+        self
+    }
+
     /// Get the base type
     pub fn base(&self) -> &Arc<dyn SimpleType + Send + Sync> {
         &self.base_type_ref
@@ -804,11 +1211,16 @@ impl SimpleType for XsdRestrictedType {
     }
 
     fn validate_value(&self, value: &str) -> Result<XsdValue> {
-        // First validate against our own facets
-        self.facet_set.validate(value)?;
+        // First validate the normalized value against our own facets
+        let normalized = self.white_space().normalize(value);
+        self.facet_set.validate(&normalized)?;
 
         // Then delegate to the base type
-        self.base_type_ref.validate_value(value)
+        self.base_type_ref.validate_value(&normalized)
+    }
+
+    fn white_space(&self) -> WhiteSpace {
+        self.facet_set.white_space.unwrap_or_else(|| self.base_type_ref.white_space())
     }
 
     fn qualified_name_string(&self) -> Option<String> {
@@ -816,6 +1228,96 @@ impl SimpleType for XsdRestrictedType {
     }
 }
 
+// =============================================================================
+// Placeholder Type (for unresolvable type references)
+// =============================================================================
+
+/// Stand-in for a simple type whose declaration could not be resolved
+///
+/// Used by lenient schema parsing when a referenced type is missing, e.g.
+/// because an imported schema is unavailable. It records the name of the
+/// missing type and accepts any value.
+#[derive(Debug)]
+pub struct PlaceholderType {
+    /// Name of the unresolved type
+    original: QName,
+    /// Always empty
+    facet_set: FacetSet,
+}
+
+impl PlaceholderType {
+    /// Create a placeholder for an unresolved type
+    pub fn new(original: QName) -> Self {
+        Self {
+            original,
+            facet_set: FacetSet::default(),
+        }
+    }
+
+    /// Get the name of the type this placeholder stands for
+    pub fn original_name(&self) -> &QName {
+        &self.original
+    }
+}
+
+impl Validator for PlaceholderType {
+    fn is_built(&self) -> bool {
+        true
+    }
+
+    fn build(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn validation_attempted(&self) -> ValidationStatus {
+        ValidationStatus::Full
+    }
+
+    fn has_errors(&self) -> bool {
+        false
+    }
+
+    fn errors(&self) -> Vec<ParseError> {
+        Vec::new()
+    }
+}
+
+impl TypeValidator for PlaceholderType {
+    fn name(&self) -> Option<&QName> {
+        Some(&self.original)
+    }
+
+    fn is_builtin(&self) -> bool {
+        false
+    }
+
+    fn base_type(&self) -> Option<&dyn TypeValidator> {
+        None
+    }
+}
+
+impl SimpleType for PlaceholderType {
+    fn variety(&self) -> SimpleTypeVariety {
+        SimpleTypeVariety::Atomic
+    }
+
+    fn base_type(&self) -> Option<&dyn SimpleType> {
+        None
+    }
+
+    fn facets(&self) -> &FacetSet {
+        &self.facet_set
+    }
+
+    fn validate_value(&self, value: &str) -> Result<XsdValue> {
+        Ok(XsdValue::String(value.to_string()))
+    }
+
+    fn qualified_name_string(&self) -> Option<String> {
+        Some(format_qname(&self.original))
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -823,7 +1325,17 @@ impl SimpleType for XsdRestrictedType {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::validators::builtins::{XSD_INTEGER, XSD_STRING};
+    use crate::validators::builtins::{XSD_BOOLEAN, XSD_INTEGER, XSD_STRING, XSD_TOKEN};
+
+    #[test]
+    fn test_placeholder_type_accepts_any_value() {
+        let placeholder = PlaceholderType::new(QName::namespaced("http://example.com/ext", "codeType"));
+        assert_eq!(placeholder.original_name().local_name, "codeType");
+        assert_eq!(placeholder.qualified_name_string().as_deref(), Some("{http://example.com/ext}codeType"));
+        assert!(placeholder.validate_value("").is_ok());
+        assert!(placeholder.validate_value("anything at all").is_ok());
+        assert!(!placeholder.is_builtin());
+    }
 
     #[test]
     fn test_atomic_type_string() {
@@ -880,6 +1392,45 @@ mod tests {
         assert!(atomic.validate_value("yellow").is_err());
     }
 
+    #[test]
+    fn test_describe_facets() {
+        let atomic = XsdAtomicType::new(XSD_STRING)
+            .unwrap()
+            .with_pattern("^[a-z]{3,}$")
+            .unwrap()
+            .with_min_length(3)
+            .with_max_length(10);
+        assert_eq!(atomic.describe_facets(), "xs:string with pattern=[a-z]{3,}, length between 3..10");
+
+        let mut integer = XsdAtomicType::new(XSD_INTEGER).unwrap();
+        assert_eq!(integer.describe_facets(), "xs:integer");
+        integer.facet_set.min_inclusive = Some(MinInclusiveFacet::new_int(1));
+        integer.facet_set.max_inclusive = Some(MaxInclusiveFacet::new_int(100));
+        assert_eq!(integer.describe_facets(), "xs:integer between 1..100");
+
+        // Facets of the base type are inherited
+        let restricted = XsdRestrictedType::new(Arc::new(atomic)).with_length(5);
+        assert_eq!(restricted.facet_summary(), "xs:string with pattern=[a-z]{3,}, length=5");
+    }
+
+    #[test]
+    fn test_canonical_value() {
+        let canonical = |name: &str, value: &str| XsdAtomicType::new(name).unwrap().canonical_value(value);
+
+        assert_eq!(canonical("boolean", "1").unwrap(), "true");
+        assert_eq!(canonical("boolean", "false").unwrap(), "false");
+        assert_eq!(canonical(XSD_INTEGER, "+42").unwrap(), "42");
+        assert_eq!(canonical("decimal", "1.0").unwrap(), "1.0");
+        assert_eq!(canonical("decimal", "+010.500").unwrap(), "10.5");
+        assert_eq!(canonical("double", "150").unwrap(), "1.5E2");
+        assert_eq!(canonical("double", "1").unwrap(), "1.0E0");
+        assert_eq!(canonical("hexBinary", "0fb7").unwrap(), "0FB7");
+        assert!(canonical(XSD_INTEGER, "abc").is_err());
+
+        let list = XsdListType::new(Arc::new(XsdAtomicType::new("boolean").unwrap()));
+        assert_eq!(list.canonical_value(" 1  0 true ").unwrap(), "true false true");
+    }
+
     #[test]
     fn test_list_type() {
         let item_type = Arc::new(XsdAtomicType::new(XSD_INTEGER).unwrap());
@@ -905,6 +1456,26 @@ mod tests {
         assert!(list.validate_value("").is_ok());
     }
 
+    #[test]
+    fn test_list_item_errors() {
+        let item_type = Arc::new(XsdAtomicType::new(XSD_INTEGER).unwrap());
+        let list = XsdListType::new(item_type).with_min_length(1).with_max_length(3);
+
+        assert!(list.validate_value(" 1\t2\n3 ").is_ok());
+
+        let error = list.validate_value("1 x 3").unwrap_err().to_string();
+        assert!(error.contains("position 2"), "{}", error);
+        let error = list.validate_value("a 2 c").unwrap_err().to_string();
+        assert!(error.contains("positions 1, 3"), "{}", error);
+        assert!(error.contains("'a'") && error.contains("'c'"), "{}", error);
+
+        // Length facets count the items
+        let error = list.validate_value("  ").unwrap_err().to_string();
+        assert!(error.contains("at least 1 items"), "{}", error);
+        let error = list.validate_value("1 2 3 4").unwrap_err().to_string();
+        assert!(error.contains("at most 3 items"), "{}", error);
+    }
+
     #[test]
     fn test_union_type() {
         let int_type = Arc::new(XsdAtomicType::new(XSD_INTEGER).unwrap());
@@ -920,6 +1491,61 @@ mod tests {
         assert!(union.validate_value("invalid").is_err());
     }
 
+    #[test]
+    fn test_union_member_order() {
+        let int_type: Arc<dyn SimpleType + Send + Sync> = Arc::new(XsdAtomicType::new(XSD_INTEGER).unwrap());
+        let bool_type: Arc<dyn SimpleType + Send + Sync> = Arc::new(XsdAtomicType::new(XSD_BOOLEAN).unwrap());
+        let union = XsdUnionType::new(vec![int_type, bool_type]);
+
+        // Valid for the second member only
+        assert_eq!(union.validate_value("true").unwrap(), XsdValue::Boolean(true));
+        // "1" is taken by the first member
+        assert_eq!(union.validate_value("1").unwrap(), XsdValue::Integer(1));
+
+        // Invalid for all members, each of them is named
+        let error = union.validate_value("maybe").unwrap_err().to_string();
+        assert!(error.contains("xs:integer") && error.contains("xs:boolean"), "{}", error);
+
+        // An empty string is a valid xs:token, even if not an xs:integer
+        let token_type: Arc<dyn SimpleType + Send + Sync> = Arc::new(XsdAtomicType::new(XSD_TOKEN).unwrap());
+        let int_type: Arc<dyn SimpleType + Send + Sync> = Arc::new(XsdAtomicType::new(XSD_INTEGER).unwrap());
+        let union = XsdUnionType::new(vec![token_type, int_type]);
+        assert!(union.validate_value("").is_ok());
+        let union = XsdUnionType::new(vec![Arc::new(XsdAtomicType::new(XSD_INTEGER).unwrap())]);
+        assert!(union.validate_value("").is_err());
+    }
+
+    #[test]
+    fn test_white_space_normalization() {
+        // xs:token collapses white space before checking facets and the value
+        let token = XsdAtomicType::new(XSD_TOKEN)
+            .unwrap()
+            .with_enumeration(vec!["hello world".to_string()]);
+        assert_eq!(
+            token.validate_value("  hello  world  ").unwrap(),
+            token.validate_value("hello world").unwrap()
+        );
+        assert!(token.validate_value("hello\t\nworld").is_ok());
+
+        // Restrictions inherit the white space handling of their base type
+        let base: Arc<dyn SimpleType + Send + Sync> = Arc::new(XsdAtomicType::new(XSD_TOKEN).unwrap());
+        let code = XsdRestrictedType::new(base).with_max_length(5);
+        assert!(code.validate_value("  ab  cd ").is_ok());
+        assert!(code.validate_value("ab  cd e").is_err());
+
+        // xs:string preserves it, unless restricted with another mode
+        let string: Arc<dyn SimpleType + Send + Sync> = Arc::new(XsdAtomicType::new(XSD_STRING).unwrap());
+        let preserved = XsdRestrictedType::new(Arc::clone(&string)).with_length(3);
+        assert!(preserved.validate_value(" a ").is_ok());
+        assert!(preserved.validate_value("a").is_err());
+        let replaced = XsdRestrictedType::new(string)
+            .with_white_space(WhiteSpace::Replace)
+            .with_pattern("^a b$")
+            .unwrap();
+        assert!(replaced.validate_value("a\tb").is_ok());
+        assert!(replaced.validate_value("a  b").is_err());
+    }
+
     #[test]
     fn test_restricted_type() {
         let base_type = Arc::new(XsdAtomicType::new(XSD_STRING).unwrap());
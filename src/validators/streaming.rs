@@ -0,0 +1,751 @@
+//! Streaming Validation
+//!
+//! This module validates XML documents from a stream of start tag, text and
+//! end tag events, without building the document tree. Each open element
+//! keeps a frame with its declaration and its position in the content model,
+//! so the memory used depends on the depth of the document, not on its size.
+//!
+//! Identity constraints need the subtree of their element and are not checked
+//! by streaming validation.
+
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::sync::Arc;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::documents::Element;
+use crate::error::{Error, Result};
+use crate::namespaces::QName;
+
+use super::base::ValidationMode;
+use super::complex_types::ContentTypeLabel;
+use super::document_validation::{
+    check_all_complete, check_element_only_text, check_no_children, check_nilled_content, enter_namespace_scope,
+    instance_type, is_nilled, match_all_particle, report_missing_elements, resolve_element_qname,
+    unexpected_child_error, validate_attributes, validate_empty_content, validate_simple_content,
+    validate_simple_element,
+};
+use super::elements::XsdElement;
+use super::exceptions::ValidationError;
+use super::globals::GlobalType;
+use super::groups::{GroupParticle, ModelType, XsdGroup};
+use super::models::ModelVisitor;
+use super::schemas::{XsdSchema, XML_NAMESPACE};
+use super::simple_types::SimpleType;
+use super::validation::{ValidationContext, XmlValidator};
+
+/// Event reported by a [`StreamingValidator`]
+#[derive(Debug, Clone)]
+pub enum ValidationEvent {
+    /// An element was validated, at its end tag
+    Validated {
+        /// Path of the element, e.g. `/catalog/book`
+        path: String,
+        /// Whether no error was reported in the element or its content
+        valid: bool,
+    },
+    /// A validation error
+    Error(Box<ValidationError>),
+}
+
+/// Validator of a document read as a stream of events
+///
+/// The events are fed with [`start_element`](Self::start_element),
+/// [`characters`](Self::characters) and [`end_element`](Self::end_element),
+/// then [`finish`](Self::finish) at the end of the document. Each call
+/// returns the events for the errors found so far, and a
+/// [`ValidationEvent::Validated`] event at each end tag.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut validator = StreamingValidator::new(&schema);
+/// validator.start_element("note", &[("lang", "en")]);
+/// validator.characters("Hello");
+/// for event in validator.end_element("note") {
+///     println!("{:?}", event);
+/// }
+/// validator.finish();
+/// ```
+pub struct StreamingValidator<'a> {
+    schema: &'a XsdSchema,
+    context: ValidationContext,
+    stack: Vec<Frame>,
+    /// Whether the document element has started
+    root_seen: bool,
+    /// Number of errors reported so far
+    reported: usize,
+}
+
+/// An open element
+struct Frame {
+    /// Tag of the element, as in its start tag
+    tag: String,
+    /// The element with its attributes, but without its children
+    element: Element,
+    content: Content,
+    /// Text of the element, for the content that is validated at the end tag
+    text: String,
+    /// Number of child elements started so far
+    children: usize,
+    /// Number of errors reported before the element started
+    errors: usize,
+    /// Namespace scope to restore at the end tag
+    outer_namespaces: Option<HashMap<String, String>>,
+}
+
+/// How the content of an open element is validated
+enum Content {
+    /// Content that isn't validated: an undeclared element, xs:anyType or exceeding the maximum depth
+    Skipped,
+    /// Content of a nilled element, which must be empty
+    Nilled(Arc<XsdElement>),
+    /// Text of an element of simple type
+    Simple {
+        simple_type: Arc<dyn SimpleType + Send + Sync>,
+        decl: Arc<XsdElement>,
+    },
+    /// Text of an element of complex type with simple content
+    Text {
+        simple_type: Arc<dyn SimpleType + Send + Sync>,
+        decl: Arc<XsdElement>,
+        component: String,
+    },
+    /// Empty content
+    Empty { component: String },
+    /// Child elements, matched against the content model as they start
+    Children {
+        model: ChildModel,
+        component: String,
+        element_only: bool,
+    },
+}
+
+/// Position in the content model of an element with child elements
+enum ChildModel {
+    /// An empty model group, which allows no children
+    Empty,
+    /// A sequence or choice model group
+    Visitor(ModelVisitor),
+    /// An xs:all group, with the number of children matched by each particle
+    All { group: Arc<XsdGroup>, counts: Vec<u32> },
+}
+
+impl Content {
+    /// Check whether a text of the element is kept for the end tag
+    fn keeps_text(&self, text: &str) -> bool {
+        match self {
+            Content::Skipped => false,
+            Content::Children { element_only, .. } => *element_only && !text.trim().is_empty(),
+            _ => true,
+        }
+    }
+}
+
+impl<'a> StreamingValidator<'a> {
+    /// Create a validator of documents for a schema
+    pub fn new(schema: &'a XsdSchema) -> Self {
+        Self {
+            schema,
+            context: ValidationContext::new().with_mode(ValidationMode::Lax),
+            stack: Vec::new(),
+            root_seen: false,
+            reported: 0,
+        }
+    }
+
+    /// Get the number of open elements
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Validate a start tag
+    ///
+    /// The tag and the attribute names are as written in the document, with
+    /// their prefixes; `xmlns` attributes declare namespaces.
+    pub fn start_element(&mut self, tag: &str, attrs: &[(&str, &str)]) -> Vec<ValidationEvent> {
+        let element = start_tag_element(tag, attrs);
+        let errors = self.reported;
+        let decl = self.child_declaration(&element);
+        let decl = self.collect(decl).flatten();
+
+        self.context.element_path.push(element.local_name().to_string());
+        self.context.element_locations.push(None);
+        let outer_namespaces = enter_namespace_scope(&element, &mut self.context);
+        self.context.current_element = Some(element.local_name().to_string());
+        self.context.enter_level();
+
+        let content = match decl {
+            Some(decl) => {
+                let content = self.start_content(&element, decl);
+                self.collect(content).unwrap_or(Content::Skipped)
+            }
+            None => Content::Skipped,
+        };
+        self.stack.push(Frame {
+            tag: tag.to_string(),
+            element,
+            content,
+            text: String::new(),
+            children: 0,
+            errors,
+            outer_namespaces,
+        });
+        self.drain_events()
+    }
+
+    /// Validate a text of the current element
+    ///
+    /// The text of an element may be given in several parts.
+    pub fn characters(&mut self, text: &str) -> Vec<ValidationEvent> {
+        if let Some(frame) = self.stack.last_mut() {
+            if frame.content.keeps_text(text) {
+                frame.text.push_str(text);
+            }
+        }
+        self.drain_events()
+    }
+
+    /// Validate an end tag, closing the current element
+    ///
+    /// The end of the document element also checks the IDREF values of the
+    /// document against its ID values.
+    pub fn end_element(&mut self, tag: &str) -> Vec<ValidationEvent> {
+        let Some(mut frame) = self.stack.pop() else {
+            let outcome = self.context.report(ValidationError::new(format!("Unexpected end tag '{}'", tag)));
+            self.collect(outcome);
+            return self.drain_events();
+        };
+        if frame.tag != tag {
+            let error = ValidationError::new(format!("End tag '{}' does not match start tag '{}'", tag, frame.tag));
+            let outcome = self.context.report(error);
+            self.collect(outcome);
+        }
+
+        if !frame.text.trim().is_empty() {
+            frame.element.text = Some(std::mem::take(&mut frame.text));
+        }
+        // The checks of text content only look for the presence of children
+        if frame.children > 0 {
+            frame.element.children.push(Element::new(QName::local("")));
+        }
+        let outcome = self.end_content(&mut frame);
+        self.collect(outcome);
+
+        self.context.exit_level();
+        self.context.current_element = None;
+        if let Some(namespaces) = frame.outer_namespaces {
+            self.context.namespaces = namespaces;
+        }
+        let path = self.context.current_path();
+        self.context.element_locations.pop();
+        self.context.element_path.pop();
+        if self.stack.is_empty() {
+            let outcome = self.context.check_idrefs();
+            self.collect(outcome);
+        }
+
+        let mut events = self.drain_events();
+        events.push(ValidationEvent::Validated {
+            path,
+            valid: self.reported == frame.errors,
+        });
+        events
+    }
+
+    /// Validate the end of the document
+    pub fn finish(&mut self) -> Vec<ValidationEvent> {
+        let outcome = if !self.root_seen {
+            self.context.report(ValidationError::new("Document has no root element"))
+        } else if let Some(frame) = self.stack.last() {
+            let error = ValidationError::new(format!("Element '{}' is not closed", frame.tag));
+            self.context.report(error)
+        } else {
+            Ok(())
+        };
+        self.collect(outcome);
+        self.drain_events()
+    }
+
+    /// Validate a document read from a reader
+    ///
+    /// The errors are reported to the context according to its mode: a
+    /// strict context stops at the first error. Malformed XML is returned
+    /// as an error.
+    pub fn validate_stream<R: BufRead>(&mut self, reader: R, context: &mut ValidationContext) -> Result<()> {
+        let mut reader = Reader::from_reader(reader);
+        reader.trim_text(true);
+        let mut buf = Vec::new();
+
+        loop {
+            let events = match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(e)) => {
+                    let (tag, attrs) = start_tag(&e)?;
+                    self.start_element(&tag, &borrow_attributes(&attrs))
+                }
+                Ok(Event::Empty(e)) => {
+                    let (tag, attrs) = start_tag(&e)?;
+                    let mut events = self.start_element(&tag, &borrow_attributes(&attrs));
+                    events.extend(self.end_element(&tag));
+                    events
+                }
+                Ok(Event::End(e)) => {
+                    let tag = std::str::from_utf8(e.name().as_ref())
+                        .map_err(|e| Error::Xml(format!("Invalid element name: {}", e)))?
+                        .to_string();
+                    self.end_element(&tag)
+                }
+                Ok(Event::Text(e)) => {
+                    let text = e
+                        .unescape()
+                        .map_err(|e| Error::Xml(format!("Failed to unescape text: {}", e)))?;
+                    self.characters(&text)
+                }
+                Ok(Event::CData(e)) => {
+                    let text = std::str::from_utf8(&e).map_err(|e| Error::Xml(format!("Invalid CDATA: {}", e)))?;
+                    self.characters(text)
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => {
+                    return Err(Error::Xml(format!(
+                        "Error parsing XML at position {}: {}",
+                        reader.buffer_position(),
+                        e
+                    )))
+                }
+                _ => Vec::new(), // Comments, processing instructions, etc.
+            };
+            forward_errors(events, context)?;
+            buf.clear();
+        }
+
+        forward_errors(self.finish(), context)
+    }
+
+    /// Validate the start tag and the text of an element of a document tree
+    pub(crate) fn start_tree_element(&mut self, elem: &Element) -> Vec<ValidationEvent> {
+        let attrs = tree_attributes(elem);
+        let mut events = self.start_element(&tree_tag(elem), &borrow_attributes(&attrs));
+        if let Some(text) = &elem.text {
+            events.extend(self.characters(text));
+        }
+        events
+    }
+
+    /// Validate the end tag of an element of a document tree
+    pub(crate) fn end_tree_element(&mut self, elem: &Element) -> Vec<ValidationEvent> {
+        self.end_element(&tree_tag(elem))
+    }
+
+    /// Find the declaration of an element at its start tag
+    ///
+    /// The element is matched against the content model of its parent, or
+    /// against the global declarations for the document element.
+    fn child_declaration(&mut self, element: &Element) -> Result<Option<Arc<XsdElement>>> {
+        let schema = self.schema;
+        let Some(parent) = self.stack.last_mut() else {
+            if self.root_seen {
+                self.context.report(ValidationError::new(format!(
+                    "Unexpected element '{}' after the document element",
+                    element.local_name()
+                )))?;
+                return Ok(None);
+            }
+            self.root_seen = true;
+            return match schema.check_root_element(element) {
+                Ok(decl) => Ok(Some(Arc::clone(decl))),
+                Err(error) => {
+                    let mut error = ValidationError::from(error).with_error_code("cvc-elt.1.a");
+                    error.element_tag = Some(element.local_name().to_string());
+                    self.context.report(error)?;
+                    Ok(None)
+                }
+            };
+        };
+
+        parent.children += 1;
+        let Content::Children { model, component, .. } = &mut parent.content else {
+            // Children of text-only content are reported at the end tag
+            return Ok(None);
+        };
+        match model {
+            ChildModel::Empty => Ok(None),
+            ChildModel::Visitor(visitor) => {
+                let qname = resolve_element_qname(element, schema);
+                let heads = schema.substitution_heads(&qname);
+                if visitor.match_element_with_heads(element.local_name(), &heads).is_some() {
+                    visitor.advance(true);
+                    let decl = schema.lookup_element(&qname).cloned().or_else(|| visitor.find_element_decl(&qname));
+                    if decl.is_none() {
+                        self.context.skipped.push(qname.to_string());
+                    }
+                    Ok(decl)
+                } else {
+                    // Report the elements tried, such as all the alternatives of a choice
+                    let expected = match visitor.tried() {
+                        [] => visitor.expected(),
+                        tried => tried.to_vec(),
+                    };
+                    let error = unexpected_child_error(&parent.element, element, &expected, component, &self.context);
+                    visitor.advance(false);
+                    self.context.report(error)?;
+                    Ok(None)
+                }
+            }
+            ChildModel::All { group, counts } => {
+                let matched =
+                    match_all_particle(schema, group, counts, &parent.element, element, component, &mut self.context)?;
+                let decl = matched.and_then(|index| match &group.particles[index] {
+                    GroupParticle::Element(particle) => particle
+                        .element()
+                        .filter(|_| particle.name.local_name == element.local_name())
+                        .or_else(|| schema.lookup_element(&resolve_element_qname(element, schema)))
+                        .cloned(),
+                    _ => None,
+                });
+                if matched.is_some() && decl.is_none() {
+                    self.context.skipped.push(resolve_element_qname(element, schema).to_string());
+                }
+                Ok(decl)
+            }
+        }
+    }
+
+    /// Validate the start tag of a declared element, giving how to validate its content
+    fn start_content(&mut self, element: &Element, decl: Arc<XsdElement>) -> Result<Content> {
+        if self.context.is_max_depth_exceeded() {
+            return Ok(Content::Skipped);
+        }
+        // Content and attributes of a nilled element are not validated
+        if is_nilled(element, &decl, &mut self.context)? {
+            return Ok(Content::Nilled(decl));
+        }
+
+        let complex_type = match instance_type(self.schema, element, &decl, &mut self.context)? {
+            Some(GlobalType::Simple(simple_type)) => return Ok(Content::Simple { simple_type, decl }),
+            Some(GlobalType::Complex(complex_type)) => complex_type,
+            None => return Ok(Content::Skipped),
+        };
+        validate_attributes(self.schema, element, &complex_type, &mut self.context)?;
+
+        let component = complex_type
+            .name
+            .as_ref()
+            .map_or_else(|| decl.name.to_string(), QName::to_string);
+        let label = complex_type.content_type_label();
+        Ok(match label {
+            ContentTypeLabel::Empty => Content::Empty { component },
+            ContentTypeLabel::Simple => match complex_type.simple_type() {
+                Some(simple_type) => Content::Text {
+                    simple_type: Arc::clone(simple_type),
+                    decl,
+                    component,
+                },
+                None => Content::Skipped,
+            },
+            ContentTypeLabel::Mixed | ContentTypeLabel::ElementOnly => {
+                let model = match complex_type.model_group() {
+                    Some(group) if group.is_empty() => ChildModel::Empty,
+                    Some(group) if group.model == ModelType::All => ChildModel::All {
+                        group: Arc::clone(group),
+                        counts: vec![0; group.particles.len()],
+                    },
+                    Some(group) => ChildModel::Visitor(ModelVisitor::new(Arc::clone(group))),
+                    None => return Ok(Content::Skipped),
+                };
+                Content::Children {
+                    model,
+                    component,
+                    element_only: label == ContentTypeLabel::ElementOnly,
+                }
+            }
+        })
+    }
+
+    /// Validate the content of an element at its end tag
+    fn end_content(&mut self, frame: &mut Frame) -> Result<()> {
+        let schema = self.schema;
+        let context = &mut self.context;
+        let element = &frame.element;
+        match &mut frame.content {
+            Content::Skipped => Ok(()),
+            Content::Nilled(decl) => check_nilled_content(element, decl, context),
+            Content::Simple { simple_type, decl } => {
+                validate_simple_element(schema, element, simple_type.as_ref(), decl, context)
+            }
+            Content::Text {
+                simple_type,
+                decl,
+                component,
+            } => validate_simple_content(schema, element, simple_type.as_ref(), decl, component, context),
+            Content::Empty { component } => validate_empty_content(element, component, context),
+            Content::Children {
+                model,
+                component,
+                element_only,
+            } => {
+                if *element_only {
+                    check_element_only_text(element, component, context)?;
+                }
+                match model {
+                    ChildModel::Empty => check_no_children(element, frame.children, component, context),
+                    ChildModel::Visitor(visitor) => report_missing_elements(visitor, element, component, context),
+                    ChildModel::All { group, counts } => {
+                        check_all_complete(group, counts, frame.children > 0, element, component, context)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Keep the value of an outcome, collecting its error if any
+    ///
+    /// Validation errors are collected by the lax context; this is for the
+    /// other errors, such as a failed schema lookup.
+    fn collect<T>(&mut self, outcome: Result<T>) -> Option<T> {
+        match outcome {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.context.errors.push(ValidationError::new(error.to_string()));
+                None
+            }
+        }
+    }
+
+    /// Take the errors collected since the last call as events
+    fn drain_events(&mut self) -> Vec<ValidationEvent> {
+        self.reported += self.context.errors.len();
+        self.context.errors.drain(..).map(|error| ValidationEvent::Error(Box::new(error))).collect()
+    }
+}
+
+impl XmlValidator for StreamingValidator<'_> {
+    /// Validate a whole document, with a new validator for the same schema
+    fn validate(&self, data: &str, context: &mut ValidationContext) -> Result<()> {
+        StreamingValidator::new(self.schema).validate_stream(data.as_bytes(), context)
+    }
+}
+
+/// Report the errors of validation events to a context
+fn forward_errors(events: Vec<ValidationEvent>, context: &mut ValidationContext) -> Result<()> {
+    for event in events {
+        if let ValidationEvent::Error(error) = event {
+            context.raise_or_collect(*error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Build an element from a start tag, as the document parser does
+fn start_tag_element(tag: &str, attrs: &[(&str, &str)]) -> Element {
+    let (prefix, local_name) = match tag.split_once(':') {
+        Some((prefix, local_name)) => (Some(prefix.to_string()), local_name),
+        None => (None, tag),
+    };
+    let mut element = Element::new(QName::local(local_name));
+    element.prefix = prefix;
+
+    for &(name, value) in attrs {
+        if name == "xmlns" {
+            element.namespaces.set_default_namespace(value);
+        } else if let Some(prefix) = name.strip_prefix("xmlns:") {
+            element.namespaces.add_prefix(prefix, value);
+        } else {
+            let local_name = name.split_once(':').map_or(name, |(_, local_name)| local_name);
+            element.attributes.insert(QName::local(local_name), value.to_string());
+        }
+    }
+    element
+}
+
+/// Get the tag and the attributes of a start tag event
+fn start_tag(start: &BytesStart) -> Result<(String, Vec<(String, String)>)> {
+    let tag = std::str::from_utf8(start.name().as_ref())
+        .map_err(|e| Error::Xml(format!("Invalid element name: {}", e)))?
+        .to_string();
+
+    let mut attrs = Vec::new();
+    for attr_result in start.attributes() {
+        let attr = attr_result.map_err(|e| Error::Xml(format!("Failed to parse attribute: {}", e)))?;
+        let name = std::str::from_utf8(attr.key.as_ref())
+            .map_err(|e| Error::Xml(format!("Invalid attribute name: {}", e)))?
+            .to_string();
+        let value = attr
+            .unescape_value()
+            .map_err(|e| Error::Xml(format!("Failed to unescape attribute value: {}", e)))?
+            .to_string();
+        attrs.push((name, value));
+    }
+    Ok((tag, attrs))
+}
+
+/// Borrow the names and values of attributes
+/// The tag of a tree element, as written in its start tag
+fn tree_tag(elem: &Element) -> String {
+    match &elem.prefix {
+        Some(prefix) => format!("{}:{}", prefix, elem.local_name()),
+        None => elem.local_name().to_string(),
+    }
+}
+
+/// The namespace declarations and attributes of a tree element, as written in its start tag
+fn tree_attributes(elem: &Element) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    if let Some(uri) = elem.namespaces.get_default_namespace() {
+        attrs.push(("xmlns".to_string(), uri.to_string()));
+    }
+    for (prefix, uri) in elem.namespaces.iter() {
+        attrs.push((format!("xmlns:{}", prefix), uri.to_string()));
+    }
+    for (qname, value) in &elem.attributes {
+        let name = match (elem.attribute_prefixes.get(qname), qname.namespace.as_deref()) {
+            (Some(prefix), _) => format!("{}:{}", prefix, qname.local_name),
+            (None, Some(XML_NAMESPACE)) => format!("xml:{}", qname.local_name),
+            _ => qname.local_name.clone(),
+        };
+        attrs.push((name, value.clone()));
+    }
+    attrs
+}
+
+fn borrow_attributes(attrs: &[(String, String)]) -> Vec<(&str, &str)> {
+    attrs.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTES_SCHEMA: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="notes">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="note" maxOccurs="unbounded">
+          <xs:complexType>
+            <xs:sequence>
+              <xs:element name="to" type="xs:string"/>
+              <xs:element name="priority" type="xs:integer" minOccurs="0"/>
+            </xs:sequence>
+            <xs:attribute name="id" type="xs:ID" use="required"/>
+          </xs:complexType>
+        </xs:element>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+
+    fn errors(events: &[ValidationEvent]) -> Vec<&ValidationError> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                ValidationEvent::Error(error) => Some(error.as_ref()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_streaming_events() {
+        let schema = XsdSchema::from_string(NOTES_SCHEMA).unwrap();
+        let mut validator = StreamingValidator::new(&schema);
+
+        assert!(validator.start_element("notes", &[]).is_empty());
+        assert!(validator.start_element("note", &[("id", "n1")]).is_empty());
+        validator.start_element("to", &[]);
+        validator.characters("Tove");
+        let events = validator.end_element("to");
+        assert!(matches!(
+            events.as_slice(),
+            [ValidationEvent::Validated { path, valid: true }] if path == "/notes/note/to"
+        ));
+        assert_eq!(validator.depth(), 2);
+
+        validator.start_element("priority", &[]);
+        validator.characters("high");
+        let events = validator.end_element("priority");
+        assert_eq!(errors(&events).len(), 1);
+        assert_eq!(errors(&events)[0].path.as_deref(), Some("/notes/note/priority"));
+        assert!(matches!(events.last(), Some(ValidationEvent::Validated { valid: false, .. })));
+
+        // The note is invalid because of its priority
+        let events = validator.end_element("note");
+        assert!(matches!(events.as_slice(), [ValidationEvent::Validated { valid: false, .. }]));
+        validator.end_element("notes");
+        assert!(validator.finish().is_empty());
+        assert_eq!(validator.depth(), 0);
+    }
+
+    #[test]
+    fn test_streaming_content_model() {
+        let schema = XsdSchema::from_string(NOTES_SCHEMA).unwrap();
+        let mut validator = StreamingValidator::new(&schema);
+
+        validator.start_element("notes", &[]);
+        // Missing required attribute
+        let events = validator.start_element("note", &[]);
+        assert_eq!(errors(&events).len(), 1);
+        // Unexpected child, reported at its start tag
+        let events = validator.start_element("from", &[]);
+        assert!(errors(&events)[0].message.contains("Unexpected child element 'from'"));
+        validator.end_element("from");
+        validator.end_element("note");
+        validator.end_element("notes");
+        assert!(validator.finish().is_empty());
+    }
+
+    #[test]
+    fn test_streaming_well_formedness() {
+        let schema = XsdSchema::from_string(NOTES_SCHEMA).unwrap();
+        let mut validator = StreamingValidator::new(&schema);
+        assert_eq!(errors(&validator.finish()).len(), 1);
+
+        let mut validator = StreamingValidator::new(&schema);
+        validator.start_element("notes", &[]);
+        assert_eq!(errors(&validator.end_element("note")).len(), 1);
+
+        let mut validator = StreamingValidator::new(&schema);
+        validator.start_element("notes", &[]);
+        assert!(errors(&validator.finish())[0].message.contains("not closed"));
+    }
+
+    #[test]
+    fn test_xml_validator() {
+        let schema = XsdSchema::from_string(NOTES_SCHEMA).unwrap();
+        let validator = StreamingValidator::new(&schema);
+
+        assert!(validator.is_valid(r#"<notes><note id="a"><to>Tove</to></note><note id="b"><to/></note></notes>"#));
+        let errors = validator.iter_errors(r#"<notes><note id="a"><to>Tove</to></note><note id="a"><to/></note></notes>"#);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors.iter().any(|error| error.error_code.as_deref() == Some("cvc-id.2")));
+
+        let mut context = ValidationContext::new();
+        assert!(validator.validate("<notes><note/></notes>", &mut context).is_err());
+    }
+
+    #[test]
+    fn test_streaming_large_document() {
+        let schema = XsdSchema::from_string(NOTES_SCHEMA).unwrap();
+        let mut validator = StreamingValidator::new(&schema);
+        let mut max_depth = 0;
+
+        validator.start_element("notes", &[]);
+        for i in 0..10_000 {
+            let id = format!("n{}", i);
+            assert!(validator.start_element("note", &[("id", &id)]).is_empty());
+            validator.start_element("to", &[]);
+            max_depth = max_depth.max(validator.depth());
+            validator.characters("Tove");
+            validator.end_element("to");
+            assert!(errors(&validator.end_element("note")).is_empty());
+        }
+        assert!(errors(&validator.end_element("notes")).is_empty());
+        assert!(validator.finish().is_empty());
+
+        // Only the open elements are kept
+        assert_eq!(max_depth, 3);
+        assert!(validator.stack.capacity() <= 4);
+    }
+}
@@ -4,9 +4,11 @@
 //! XML documents against XSD schemas.
 
 use std::collections::HashMap;
+use crate::documents::Element;
 use crate::error::Result;
-use super::exceptions::{ValidationError, XsdValidatorError};
+use super::exceptions::{StopValidation, ValidationError, XsdValidatorError};
 use super::base::ValidationMode;
+use super::globals::GlobalType;
 
 /// Validation context for handling the validation process
 ///
@@ -26,7 +28,7 @@ pub struct ValidationContext {
     pub use_defaults: bool,
     /// Whether to preserve mixed content
     pub preserve_mixed: bool,
-    /// Whether to check identity constraints
+    /// Whether to check identity constraints (enabled by default)
     pub check_identities: bool,
     /// Whether to process skipped content
     pub process_skipped: bool,
@@ -36,12 +38,27 @@ pub struct ValidationContext {
     pub namespaces: HashMap<String, String>,
     /// ID map for tracking ID values (for xs:ID validation)
     pub id_map: HashMap<String, usize>,
+    /// xs:IDREF values, checked against `id_map` once the document is read
+    pub idrefs: Vec<IdrefUse>,
     /// Inherited attributes
     pub inherited: HashMap<String, String>,
     /// Current element being validated
     pub current_element: Option<String>,
     /// Current attribute being validated
     pub current_attribute: Option<String>,
+    /// Names of the elements from the root to the current element
+    pub element_path: Vec<String>,
+    /// Source locations (line, column) of the elements of `element_path`
+    pub element_locations: Vec<Option<(u32, u32)>>,
+    /// Error that stopped validation in strict mode
+    pub raised: Option<ValidationError>,
+    /// Names of the elements whose validation was skipped, as no schema
+    /// component was found for them
+    pub skipped: Vec<String>,
+    /// Maximum number of errors collected before validation stops
+    pub max_errors: usize,
+    /// Set when validation was stopped, once `max_errors` errors were collected
+    pub stopped: Option<StopValidation>,
 }
 
 impl ValidationContext {
@@ -54,14 +71,21 @@ impl ValidationContext {
             max_depth: None,
             use_defaults: true,
             preserve_mixed: false,
-            check_identities: false,
+            check_identities: true,
             process_skipped: false,
             use_location_hints: false,
             namespaces: HashMap::new(),
             id_map: HashMap::new(),
+            idrefs: Vec::new(),
             inherited: HashMap::new(),
             current_element: None,
             current_attribute: None,
+            element_path: Vec::new(),
+            element_locations: Vec::new(),
+            raised: None,
+            skipped: Vec::new(),
+            max_errors: usize::MAX,
+            stopped: None,
         }
     }
 
@@ -83,6 +107,15 @@ impl ValidationContext {
         self
     }
 
+    /// Set the maximum number of errors to collect
+    ///
+    /// Once `max` errors were collected, reporting an error stops the
+    /// validation with a [`StopValidation`].
+    pub fn with_max_errors(mut self, max: usize) -> Self {
+        self.max_errors = max;
+        self
+    }
+
     /// Enable identity constraint checking
     pub fn with_identity_check(mut self) -> Self {
         self.check_identities = true;
@@ -114,10 +147,21 @@ impl ValidationContext {
     pub fn clear(&mut self) {
         self.errors.clear();
         self.id_map.clear();
+        self.idrefs.clear();
         self.inherited.clear();
         self.level = 0;
         self.current_element = None;
         self.current_attribute = None;
+        self.element_path.clear();
+        self.element_locations.clear();
+        self.raised = None;
+        self.skipped.clear();
+        self.stopped = None;
+    }
+
+    /// Check whether validation was stopped by the error limit
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.is_some()
     }
 
     /// Check if there are any errors
@@ -135,17 +179,33 @@ impl ValidationContext {
         self.errors.push(error);
     }
 
+    /// Get the XPath of the current element, e.g. `/catalog/book/title`
+    pub fn current_path(&self) -> String {
+        format!("/{}", self.element_path.join("/"))
+    }
+
     /// Raise or collect an error based on validation mode
     pub fn raise_or_collect(&mut self, error: ValidationError) -> Result<()> {
         match self.mode {
             ValidationMode::Strict => {
                 // Convert to crate error type
-                let crate_error = crate::error::ValidationError::new(error.message())
+                let mut crate_error = crate::error::ValidationError::new(error.message())
                     .with_reason(error.reason.clone().unwrap_or_default());
+                crate_error.path = error.path.clone();
+                crate_error.schema_component = error.schema_component.clone();
+                self.raised = Some(error);
                 Err(crate::error::Error::Validation(crate_error))
             }
             ValidationMode::Lax => {
-                self.errors.push(error);
+                if self.stopped.is_none() && self.errors.len() < self.max_errors {
+                    self.errors.push(error);
+                }
+                if self.errors.len() >= self.max_errors {
+                    let stop = self.stopped.get_or_insert_with(|| {
+                        StopValidation::with_message(format!("{} errors collected", self.errors.len()))
+                    });
+                    return Err(crate::error::Error::LimitExceeded(stop.to_string()));
+                }
                 Ok(())
             }
             ValidationMode::Skip => {
@@ -164,6 +224,22 @@ impl ValidationContext {
         if let Some(r) = reason {
             error = error.with_reason(r);
         }
+        self.report(error)
+    }
+
+    /// Handle a validation error according to mode
+    ///
+    /// Errors without a path or source location get the ones of the
+    /// current element.
+    pub fn report(&mut self, mut error: ValidationError) -> Result<()> {
+        if error.path.is_none() && !self.element_path.is_empty() {
+            error.path = Some(self.current_path());
+        }
+        if error.source_line.is_none() {
+            if let Some(&Some((line, column))) = self.element_locations.last() {
+                error = error.with_source_line(line as usize).with_source_column(column as usize);
+            }
+        }
         if let Some(ref elem) = self.current_element {
             error = error.with_element(elem.clone());
         }
@@ -176,15 +252,72 @@ impl ValidationContext {
         *count += 1;
 
         if *count > 1 {
-            self.validation_error(
-                format!("Duplicate ID value: '{}'", id),
-                Some("xs:ID values must be unique within the document".to_string()),
+            self.report(
+                ValidationError::new(format!("Duplicate ID value: '{}'", id))
+                    .with_reason("xs:ID values must be unique within the document")
+                    .with_error_code("cvc-id.2"),
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Register the xs:ID value of an attribute or element
+    ///
+    /// Unlike `register_id`, a duplicate is reported with the name of the
+    /// node holding the value and the path to it.
+    pub fn register_id_value(&mut self, name: &str, id: &str, path: String) -> Result<()> {
+        let count = self.id_map.entry(id.to_string()).or_insert(0);
+        *count += 1;
+
+        if *count > 1 {
+            self.report(
+                ValidationError::new(format!("Duplicate ID value '{}' in '{}'", id, name))
+                    .with_reason("xs:ID values must be unique within the document")
+                    .with_path(path)
+                    .with_error_code("cvc-id.2"),
             )
         } else {
             Ok(())
         }
     }
 
+    /// Record an xs:IDREF value, to be resolved by `check_idrefs`
+    ///
+    /// References may point forward in the document, so they can only be
+    /// checked after every ID has been registered.
+    pub fn register_idref(&mut self, name: &str, idref: &str, path: String) {
+        self.idrefs.push(IdrefUse {
+            name: name.to_string(),
+            value: idref.to_string(),
+            path,
+            location: self.element_locations.last().copied().flatten(),
+        });
+    }
+
+    /// Report the recorded xs:IDREF values that match no registered ID
+    pub fn check_idrefs(&mut self) -> Result<()> {
+        let unresolved: Vec<IdrefUse> = std::mem::take(&mut self.idrefs)
+            .into_iter()
+            .filter(|idref| !self.id_map.contains_key(&idref.value))
+            .collect();
+
+        for idref in unresolved {
+            let mut error = ValidationError::new(format!(
+                "IDREF value '{}' in '{}' does not match any ID in the document",
+                idref.value, idref.name
+            ))
+            .with_reason("xs:IDREF values must refer to an xs:ID of the same document")
+            .with_path(idref.path)
+            .with_error_code("cvc-id.1");
+            if let Some((line, column)) = idref.location {
+                error = error.with_source_line(line as usize).with_source_column(column as usize);
+            }
+            self.report(error)?;
+        }
+        Ok(())
+    }
+
     /// Check if an IDREF is valid
     pub fn check_idref(&self, idref: &str) -> bool {
         self.id_map.contains_key(idref)
@@ -200,6 +333,19 @@ impl ValidationContext {
     }
 }
 
+/// An xs:IDREF value found while validating a document
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdrefUse {
+    /// Name of the attribute or element holding the value
+    pub name: String,
+    /// The referenced ID
+    pub value: String,
+    /// Path to the attribute or element
+    pub path: String,
+    /// Source location (line, column) of the element
+    pub location: Option<(u32, u32)>,
+}
+
 impl Default for ValidationContext {
     fn default() -> Self {
         Self::new()
@@ -220,9 +366,16 @@ impl Clone for ValidationContext {
             use_location_hints: self.use_location_hints,
             namespaces: self.namespaces.clone(),
             id_map: self.id_map.clone(),
+            idrefs: self.idrefs.clone(),
             inherited: self.inherited.clone(),
             current_element: self.current_element.clone(),
             current_attribute: self.current_attribute.clone(),
+            element_path: self.element_path.clone(),
+            element_locations: self.element_locations.clone(),
+            raised: self.raised.clone(),
+            skipped: self.skipped.clone(),
+            max_errors: self.max_errors,
+            stopped: self.stopped.clone(),
         }
     }
 }
@@ -392,6 +545,24 @@ pub trait XmlValidator {
     }
 }
 
+/// Trait for custom validation rules run alongside schema validation
+///
+/// Implementations check business rules that XSD cannot express, such as
+/// "publication date must be in the past". They are called once for each
+/// element of a document by `XsdSchema::validate_with_extensions`.
+pub trait CustomValidator: Send + Sync {
+    /// Validate an element, returning an error message for each violation
+    ///
+    /// `schema_type` is the declared type of the element, if the schema
+    /// knows it; `ctx.level` is the depth of the element (0 for the root).
+    fn validate_element(
+        &self,
+        element: &Element,
+        schema_type: Option<&GlobalType>,
+        ctx: &ValidationContext,
+    ) -> Vec<String>;
+}
+
 /// Trait for types that can decode XML to values
 pub trait XmlDecoder<T> {
     /// Decode XML data to a value
@@ -504,6 +675,26 @@ mod tests {
         assert_eq!(unresolved, vec!["id3"]);
     }
 
+    #[test]
+    fn test_check_idrefs() {
+        let mut context = ValidationContext::new().with_mode(ValidationMode::Lax);
+        context.register_idref("ref", "id2", "/doc/@ref".to_string());
+        context.register_idref("ref", "id3", "/doc/item/@ref".to_string());
+        context.register_id_value("id", "id1", "/doc/@id".to_string()).unwrap();
+        context.register_id_value("id", "id2", "/doc/item/@id".to_string()).unwrap();
+        context.register_id_value("id", "id2", "/doc/other/@id".to_string()).unwrap();
+        assert_eq!(context.error_count(), 1);
+        assert_eq!(context.errors[0].error_code.as_deref(), Some("cvc-id.2"));
+        assert_eq!(context.errors[0].path.as_deref(), Some("/doc/other/@id"));
+
+        context.check_idrefs().unwrap();
+        assert_eq!(context.error_count(), 2);
+        assert_eq!(context.errors[1].error_code.as_deref(), Some("cvc-id.1"));
+        assert_eq!(context.errors[1].path.as_deref(), Some("/doc/item/@ref"));
+        assert!(context.errors[1].message.contains("'id3'"));
+        assert!(context.idrefs.is_empty());
+    }
+
     #[test]
     fn test_context_clear() {
         let mut context = ValidationContext::new();
@@ -521,8 +712,21 @@ mod tests {
     #[test]
     fn test_raise_or_collect_strict() {
         let mut context = ValidationContext::new().with_mode(ValidationMode::Strict);
-        let result = context.raise_or_collect(ValidationError::new("Error"));
+        let result = context.raise_or_collect(ValidationError::new("Error").with_error_code("cvc-elt.1.a"));
         assert!(result.is_err());
+        assert!(!context.has_errors());
+        assert_eq!(context.raised.unwrap().error_code.as_deref(), Some("cvc-elt.1.a"));
+    }
+
+    #[test]
+    fn test_report_sets_current_path() {
+        let mut context = ValidationContext::new().with_mode(ValidationMode::Lax);
+        context.element_path = vec!["catalog".to_string(), "book".to_string()];
+        context.report(ValidationError::new("Error")).unwrap();
+        context.report(ValidationError::new("Error").with_path("/catalog/book/@id")).unwrap();
+
+        assert_eq!(context.errors[0].path(), Some("/catalog/book"));
+        assert_eq!(context.errors[1].path(), Some("/catalog/book/@id"));
     }
 
     #[test]
@@ -216,70 +216,96 @@ impl NamespaceConstraint {
         }
     }
 
+    /// Namespaces excluded by a negative constraint (`##other` or `notNamespace`)
+    ///
+    /// `##other` excludes the target namespace and no-namespace (the empty
+    /// string). Returns None for positive constraints.
+    fn excluded_namespaces(&self) -> Option<HashSet<String>> {
+        match self {
+            Self::Other { target_namespace } => {
+                let mut excluded = HashSet::new();
+                excluded.insert(String::new());
+                if let Some(tns) = target_namespace {
+                    excluded.insert(tns.clone());
+                }
+                Some(excluded)
+            }
+            Self::Not(set) => Some(set.clone()),
+            Self::Any | Self::Enumeration(_) => None,
+        }
+    }
+
+    /// Build a negative constraint, preferring the `##other` form of an operand
+    fn from_excluded(excluded: HashSet<String>, operands: [&Self; 2]) -> Self {
+        if excluded.is_empty() {
+            return Self::Any;
+        }
+        operands
+            .into_iter()
+            .find(|c| {
+                matches!(c, Self::Other { .. }) && c.excluded_namespaces().as_ref() == Some(&excluded)
+            })
+            .cloned()
+            .unwrap_or(Self::Not(excluded))
+    }
+
     /// Compute union with another constraint
+    ///
+    /// The result allows every namespace allowed by either constraint
+    /// (attribute wildcard union, XSD 1.1 Part 1 §3.10.6.3).
     pub fn union(&self, other: &Self) -> Self {
         match (self, other) {
             (Self::Any, _) | (_, Self::Any) => Self::Any,
 
-            (Self::Other { target_namespace: tns1 }, Self::Other { target_namespace: tns2 }) => {
-                if tns1 == tns2 {
-                    Self::Other {
-                        target_namespace: tns1.clone(),
-                    }
-                } else {
-                    Self::Any
-                }
-            }
-
             (Self::Enumeration(a), Self::Enumeration(b)) => {
                 Self::Enumeration(a.union(b).cloned().collect())
             }
 
-            (Self::Not(a), Self::Not(b)) => Self::Not(a.intersection(b).cloned().collect()),
+            (Self::Enumeration(set), negated) | (negated, Self::Enumeration(set)) => {
+                let excluded = negated.excluded_namespaces().unwrap_or_default();
+                let remaining = excluded.difference(set).cloned().collect();
+                Self::from_excluded(remaining, [self, other])
+            }
 
-            // Mixed cases become Any for simplicity
-            _ => Self::Any,
+            (a, b) => {
+                let a = a.excluded_namespaces().unwrap_or_default();
+                let b = b.excluded_namespaces().unwrap_or_default();
+                Self::from_excluded(a.intersection(&b).cloned().collect(), [self, other])
+            }
         }
     }
 
     /// Compute intersection with another constraint
-    pub fn intersection(&self, other: &Self) -> Self {
+    ///
+    /// The result allows only the namespaces allowed by both constraints
+    /// (attribute wildcard intersection, XSD 1.1 Part 1 §3.10.6.4).
+    pub fn intersect(&self, other: &Self) -> Self {
         match (self, other) {
             (Self::Any, x) | (x, Self::Any) => x.clone(),
 
-            (Self::Other { target_namespace: tns }, Self::Enumeration(set))
-            | (Self::Enumeration(set), Self::Other { target_namespace: tns }) => {
-                let excluded = tns.as_ref().map(|s| s.as_str());
-                let filtered: HashSet<_> = set
-                    .iter()
-                    .filter(|ns| !ns.is_empty() && Some(ns.as_str()) != excluded)
-                    .cloned()
-                    .collect();
-                Self::Enumeration(filtered)
-            }
-
             (Self::Enumeration(a), Self::Enumeration(b)) => {
                 Self::Enumeration(a.intersection(b).cloned().collect())
             }
 
-            (Self::Not(a), Self::Not(b)) => Self::Not(a.union(b).cloned().collect()),
-
-            (Self::Other { target_namespace: tns1 }, Self::Other { target_namespace: tns2 }) => {
-                // Intersection of two ##other
-                let mut not_set = HashSet::new();
-                not_set.insert(String::new());
-                if let Some(tns) = tns1 {
-                    not_set.insert(tns.clone());
-                }
-                if let Some(tns) = tns2 {
-                    not_set.insert(tns.clone());
-                }
-                Self::Not(not_set)
+            (Self::Enumeration(set), negated) | (negated, Self::Enumeration(set)) => {
+                let excluded = negated.excluded_namespaces().unwrap_or_default();
+                Self::Enumeration(set.difference(&excluded).cloned().collect())
             }
 
-            _ => self.clone(),
+            (a, b) => {
+                let a = a.excluded_namespaces().unwrap_or_default();
+                let b = b.excluded_namespaces().unwrap_or_default();
+                Self::from_excluded(a.union(&b).cloned().collect(), [self, other])
+            }
         }
     }
+
+    /// Compute intersection with another constraint
+    ///
+    /// Same as [`NamespaceConstraint::intersect`].
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.intersect(other)
+    }
 }
 
 /// Base wildcard component
@@ -365,6 +391,37 @@ impl XsdWildcard {
             .is_restriction_of(&other.namespace, self.target_namespace.as_deref())
     }
 
+    /// Compute the union of this wildcard with another
+    ///
+    /// Used when a type extends a base type that also has an attribute
+    /// wildcard. The process contents of this wildcard are kept, and only
+    /// QNames disallowed by both wildcards stay disallowed.
+    pub fn union(&self, other: &XsdWildcard) -> XsdWildcard {
+        XsdWildcard {
+            process_contents: self.process_contents,
+            namespace: self.namespace.union(&other.namespace),
+            not_qname: self.not_qname.intersection(&other.not_qname).cloned().collect(),
+            target_namespace: self.target_namespace.clone(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Compute the intersection of this wildcard with another
+    ///
+    /// Used when merging attribute wildcards that must all be satisfied,
+    /// such as a restricted type and its base. The process contents of this
+    /// wildcard are kept, and QNames disallowed by either wildcard stay
+    /// disallowed.
+    pub fn intersect(&self, other: &XsdWildcard) -> XsdWildcard {
+        XsdWildcard {
+            process_contents: self.process_contents,
+            namespace: self.namespace.intersect(&other.namespace),
+            not_qname: self.not_qname.union(&other.not_qname).cloned().collect(),
+            target_namespace: self.target_namespace.clone(),
+            errors: Vec::new(),
+        }
+    }
+
     /// Get parse errors
     pub fn errors(&self) -> &[ParseError] {
         &self.errors
@@ -754,4 +811,107 @@ mod tests {
         assert!(any.is_matching("{http://example.com}attr", None));
         assert!(!any.is_matching("{http://target.com}attr", None));
     }
+
+    fn set(namespaces: &[&str]) -> HashSet<String> {
+        namespaces.iter().map(|ns| ns.to_string()).collect()
+    }
+
+    fn other(tns: &str) -> NamespaceConstraint {
+        NamespaceConstraint::Other {
+            target_namespace: Some(tns.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_namespace_constraint_union_combinations() {
+        use NamespaceConstraint::{Any, Enumeration, Not};
+        let enum_a = Enumeration(set(&["urn:a", "urn:b"]));
+        let enum_b = Enumeration(set(&["urn:b", "urn:c"]));
+        let not_a = Not(set(&["urn:a", "urn:x"]));
+        let not_b = Not(set(&["urn:x", "urn:y"]));
+
+        // Any absorbs everything
+        for c in [&enum_a, &other("urn:t"), &not_a, &Any] {
+            assert_eq!(Any.union(c), Any);
+            assert_eq!(c.union(&Any), Any);
+        }
+
+        assert_eq!(enum_a.union(&enum_b), Enumeration(set(&["urn:a", "urn:b", "urn:c"])));
+
+        // Negations keep only what both exclude
+        assert_eq!(not_a.union(&not_b), Not(set(&["urn:x"])));
+        assert_eq!(not_a.union(&Not(set(&["urn:y"]))), Any);
+        assert_eq!(not_a.union(&enum_a), Not(set(&["urn:x"])));
+        assert_eq!(enum_a.union(&not_a), Not(set(&["urn:x"])));
+        assert_eq!(Not(set(&["urn:a"])).union(&enum_a), Any);
+
+        // ##other excludes the target namespace and no-namespace
+        assert_eq!(other("urn:t").union(&other("urn:t")), other("urn:t"));
+        assert_eq!(other("urn:t").union(&other("urn:u")), Not(set(&[""])));
+        assert_eq!(other("urn:t").union(&enum_a), other("urn:t"));
+        assert_eq!(other("urn:t").union(&Enumeration(set(&["urn:t"]))), Not(set(&[""])));
+        assert_eq!(other("urn:t").union(&Enumeration(set(&["", "urn:t"]))), Any);
+        assert_eq!(other("urn:t").union(&Not(set(&["", "urn:x"]))), Not(set(&[""])));
+    }
+
+    #[test]
+    fn test_namespace_constraint_intersect_combinations() {
+        use NamespaceConstraint::{Any, Enumeration, Not};
+        let enum_a = Enumeration(set(&["urn:a", "urn:b"]));
+        let enum_b = Enumeration(set(&["urn:b", "urn:c"]));
+        let not_a = Not(set(&["urn:a", "urn:x"]));
+        let not_b = Not(set(&["urn:x", "urn:y"]));
+
+        // Any is the identity
+        for c in [&enum_a, &other("urn:t"), &not_a, &Any] {
+            assert_eq!(&Any.intersect(c), c);
+            assert_eq!(&c.intersect(&Any), c);
+        }
+
+        assert_eq!(enum_a.intersect(&enum_b), Enumeration(set(&["urn:b"])));
+        assert_eq!(enum_a.intersect(&Enumeration(set(&["urn:z"]))), Enumeration(HashSet::new()));
+
+        // Negations exclude what either excludes
+        assert_eq!(not_a.intersect(&not_b), Not(set(&["urn:a", "urn:x", "urn:y"])));
+        assert_eq!(not_a.intersect(&enum_a), Enumeration(set(&["urn:b"])));
+        assert_eq!(enum_a.intersect(&not_a), Enumeration(set(&["urn:b"])));
+
+        // ##other excludes the target namespace and no-namespace
+        assert_eq!(other("urn:t").intersect(&other("urn:t")), other("urn:t"));
+        assert_eq!(other("urn:t").intersect(&other("urn:u")), Not(set(&["", "urn:t", "urn:u"])));
+        assert_eq!(
+            other("urn:t").intersect(&Enumeration(set(&["", "urn:t", "urn:a"]))),
+            Enumeration(set(&["urn:a"]))
+        );
+        assert_eq!(other("urn:t").intersect(&Not(set(&["urn:t"]))), other("urn:t"));
+        assert_eq!(other("urn:t").intersect(&not_a), Not(set(&["", "urn:t", "urn:a", "urn:x"])));
+
+        // The legacy name is an alias
+        assert_eq!(not_a.intersection(&not_b), not_a.intersect(&not_b));
+    }
+
+    #[test]
+    fn test_wildcard_union_and_intersect() {
+        let mut own = XsdWildcard::with_namespace(
+            NamespaceConstraint::Enumeration(set(&["urn:a"])),
+            ProcessContents::Lax,
+            Some("urn:t"),
+        );
+        own.not_qname.insert(QName::namespaced("urn:a", "x"));
+        own.not_qname.insert(QName::namespaced("urn:b", "y"));
+        let mut base = XsdWildcard::with_namespace(other("urn:t"), ProcessContents::Strict, Some("urn:t"));
+        base.not_qname.insert(QName::namespaced("urn:a", "x"));
+
+        let union = own.union(&base);
+        assert_eq!(union.process_contents, ProcessContents::Lax);
+        assert_eq!(union.namespace, other("urn:t"));
+        assert_eq!(union.not_qname, [QName::namespaced("urn:a", "x")].into_iter().collect());
+        assert!(union.is_namespace_allowed("urn:other"));
+
+        let intersection = own.intersect(&base);
+        assert_eq!(intersection.process_contents, ProcessContents::Lax);
+        assert_eq!(intersection.namespace, NamespaceConstraint::Enumeration(set(&["urn:a"])));
+        assert_eq!(intersection.not_qname.len(), 2);
+        assert!(!intersection.is_namespace_allowed("urn:other"));
+    }
 }
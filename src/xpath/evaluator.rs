@@ -0,0 +1,373 @@
+//! Basic XPath evaluation over documents
+//!
+//! This module evaluates location paths against a parsed [`Document`]. It
+//! supports the child, descendant, descendant-or-self, self and attribute
+//! axes, the `*`, `node()` and `text()` node tests, and predicates that are
+//! positions (`[2]`, `[last()]`), existence tests (`[@id]`, `[title]`) or
+//! equality comparisons (`[@lang='en']`, `[year!='2000']`).
+//!
+//! Elements have no parent links, so the parent axis is not supported.
+//! Name tests match the local name; prefixes are ignored.
+
+use super::parsers::{NodeTest, ParsedStep, ParsedXPath, PredicateKind, XPathParseError, XPathPredicate};
+use super::{XPathAxis, XPathNode, XPathNodeType, XPathResult};
+use crate::documents::{Document, Element};
+
+/// A node selected by an XPath expression
+#[derive(Debug, Clone, Copy)]
+pub enum SelectedNode<'a> {
+    /// The document node
+    Document(&'a Element),
+    /// An element
+    Element(&'a Element),
+    /// An attribute (name, value)
+    Attribute(&'a str, &'a str),
+    /// The text content of an element
+    Text(&'a str),
+}
+
+impl<'a> SelectedNode<'a> {
+    /// Get the string value of the node
+    ///
+    /// The string value of an element is the concatenated text of the
+    /// element and its descendants.
+    pub fn string_value(&self) -> String {
+        match self {
+            Self::Document(element) | Self::Element(element) => {
+                let mut value = String::new();
+                collect_text(element, &mut value);
+                value
+            }
+            Self::Attribute(_, value) | Self::Text(value) => value.to_string(),
+        }
+    }
+
+    /// Convert into an XPath result node
+    pub fn to_xpath_node(&self) -> XPathNode {
+        match self {
+            Self::Document(_) => XPathNode {
+                node_type: XPathNodeType::Root,
+                ..XPathNode::element("", self.string_value())
+            },
+            Self::Element(element) => {
+                let node = XPathNode::element(element.local_name(), self.string_value());
+                match element.namespace() {
+                    Some(ns) => node.with_namespace(ns),
+                    None => node,
+                }
+            }
+            Self::Attribute(name, value) => XPathNode::attribute(*name, *value),
+            Self::Text(text) => XPathNode {
+                node_type: XPathNodeType::Text,
+                ..XPathNode::element("", *text)
+            },
+        }
+    }
+
+    fn element(&self) -> Option<&'a Element> {
+        match self {
+            Self::Document(element) | Self::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+}
+
+fn collect_text(element: &Element, value: &mut String) {
+    if let Some(text) = &element.text {
+        value.push_str(text);
+    }
+    for child in &element.children {
+        collect_text(child, value);
+    }
+}
+
+/// Evaluates XPath location paths against a document
+#[derive(Debug, Clone, Copy)]
+pub struct XPathEvaluator<'a> {
+    root: &'a Element,
+}
+
+impl<'a> XPathEvaluator<'a> {
+    /// Create an evaluator for a document
+    ///
+    /// Returns `None` if the document has no root element.
+    pub fn new(doc: &'a Document) -> Option<Self> {
+        doc.root().map(Self::for_element)
+    }
+
+    /// Create an evaluator treating an element as the document element
+    pub fn for_element(root: &'a Element) -> Self {
+        Self { root }
+    }
+
+    /// Select the nodes matching an expression
+    ///
+    /// Relative expressions are evaluated with the document element as
+    /// context node.
+    pub fn select(&self, expression: &str) -> Result<Vec<SelectedNode<'a>>, XPathParseError> {
+        let path = parse_path(expression)?;
+        let context = if path.is_absolute {
+            SelectedNode::Document(self.root)
+        } else {
+            SelectedNode::Element(self.root)
+        };
+        select_steps(vec![context], &path.steps)
+    }
+
+    /// Evaluate an expression into an XPath result
+    pub fn evaluate(&self, expression: &str) -> Result<XPathResult, XPathParseError> {
+        let nodes = self.select(expression)?;
+        if nodes.is_empty() {
+            return Ok(XPathResult::Empty);
+        }
+        Ok(XPathResult::Nodes(
+            nodes
+                .iter()
+                .enumerate()
+                .map(|(i, node)| node.to_xpath_node().with_position(i + 1))
+                .collect(),
+        ))
+    }
+}
+
+/// Parse a location path, keeping the descendant step of a leading `//`
+pub(super) fn parse_path(expression: &str) -> Result<ParsedXPath, XPathParseError> {
+    let expression = expression.trim();
+    let mut path = ParsedXPath::parse(expression)?;
+    if expression.starts_with("//") {
+        // The parser drops the descendant step of a leading `//`
+        path.steps.insert(
+            0,
+            ParsedStep {
+                axis: XPathAxis::DescendantOrSelf,
+                node_test: NodeTest::Node,
+                predicates: Vec::new(),
+            },
+        );
+    }
+    Ok(path)
+}
+
+pub(super) fn select_steps<'a>(
+    mut nodes: Vec<SelectedNode<'a>>,
+    steps: &[ParsedStep],
+) -> Result<Vec<SelectedNode<'a>>, XPathParseError> {
+    for step in steps {
+        let mut next = Vec::new();
+        for node in &nodes {
+            let candidates = apply_axis(node, step)?;
+            next.extend(apply_predicates(candidates, &step.predicates)?);
+        }
+        nodes = next;
+    }
+    Ok(nodes)
+}
+
+fn apply_axis<'a>(node: &SelectedNode<'a>, step: &ParsedStep) -> Result<Vec<SelectedNode<'a>>, XPathParseError> {
+    let Some(element) = node.element() else {
+        // Attributes and text nodes have no children or attributes
+        return Ok(match step.axis {
+            XPathAxis::Self_ | XPathAxis::DescendantOrSelf if step.node_test == NodeTest::Node => vec![*node],
+            _ => Vec::new(),
+        });
+    };
+    let is_document = matches!(node, SelectedNode::Document(_));
+
+    let mut candidates = Vec::new();
+    match step.axis {
+        XPathAxis::Child => {
+            if is_document {
+                candidates.push(SelectedNode::Element(element));
+            } else {
+                if step.node_test == NodeTest::Text || step.node_test == NodeTest::Node {
+                    candidates.extend(element.text.as_deref().map(SelectedNode::Text));
+                }
+                candidates.extend(element.children.iter().map(SelectedNode::Element));
+            }
+        }
+        XPathAxis::Descendant | XPathAxis::DescendantOrSelf => {
+            if step.axis == XPathAxis::DescendantOrSelf {
+                candidates.push(*node);
+            }
+            if is_document {
+                candidates.push(SelectedNode::Element(element));
+            }
+            collect_descendants(element, &mut candidates);
+        }
+        XPathAxis::Self_ => candidates.push(*node),
+        XPathAxis::Attribute => {
+            if is_document {
+                return Ok(Vec::new());
+            }
+            let mut attributes: Vec<_> = element
+                .attributes
+                .iter()
+                .filter(|(name, _)| name_matches(&step.node_test, &name.local_name))
+                .map(|(name, value)| SelectedNode::Attribute(name.local_name.as_str(), value.as_str()))
+                .collect();
+            attributes.sort_by_key(|node| match node {
+                SelectedNode::Attribute(name, _) => *name,
+                _ => "",
+            });
+            return Ok(attributes);
+        }
+        axis => {
+            return Err(XPathParseError::InvalidSyntax(format!(
+                "the {} axis is not supported",
+                axis
+            )))
+        }
+    }
+
+    Ok(candidates.into_iter().filter(|candidate| node_test_matches(&step.node_test, candidate)).collect())
+}
+
+fn collect_descendants<'a>(element: &'a Element, nodes: &mut Vec<SelectedNode<'a>>) {
+    for child in &element.children {
+        nodes.push(SelectedNode::Element(child));
+        collect_descendants(child, nodes);
+    }
+}
+
+fn node_test_matches(test: &NodeTest, node: &SelectedNode<'_>) -> bool {
+    match (test, node) {
+        (NodeTest::Node, _) => true,
+        (NodeTest::Text, SelectedNode::Text(_)) => true,
+        (_, SelectedNode::Element(element)) => name_matches(test, element.local_name()),
+        _ => false,
+    }
+}
+
+fn name_matches(test: &NodeTest, local_name: &str) -> bool {
+    match test {
+        NodeTest::Wildcard | NodeTest::NamespaceWildcard(_) | NodeTest::Node => true,
+        NodeTest::Name { local, .. } => local == local_name,
+        _ => false,
+    }
+}
+
+fn apply_predicates<'a>(
+    mut nodes: Vec<SelectedNode<'a>>,
+    predicates: &[XPathPredicate],
+) -> Result<Vec<SelectedNode<'a>>, XPathParseError> {
+    for predicate in predicates {
+        let expression = predicate.expression.trim();
+        nodes = match predicate.kind {
+            PredicateKind::Position => {
+                let position = match predicate.position_value() {
+                    Some(position) => position,
+                    None if expression == "last()" => nodes.len() as i64,
+                    None => {
+                        return Err(XPathParseError::InvalidSyntax(format!(
+                            "unsupported predicate [{}]",
+                            expression
+                        )))
+                    }
+                };
+                usize::try_from(position - 1)
+                    .ok()
+                    .and_then(|index| nodes.get(index).copied())
+                    .into_iter()
+                    .collect()
+            }
+            PredicateKind::Comparison => {
+                let (path, negate, literal) = split_comparison(expression)?;
+                let mut kept = Vec::new();
+                for node in nodes {
+                    let values = relative_values(node, path)?;
+                    if values.iter().any(|value| (value == literal) != negate) {
+                        kept.push(node);
+                    }
+                }
+                kept
+            }
+            PredicateKind::NodeTest | PredicateKind::Function => {
+                let mut kept = Vec::new();
+                for node in nodes {
+                    if !select_steps(vec![node], &ParsedXPath::parse(expression)?.steps)?.is_empty() {
+                        kept.push(node);
+                    }
+                }
+                kept
+            }
+        };
+    }
+    Ok(nodes)
+}
+
+/// Split an equality predicate into its path, negation flag and literal
+fn split_comparison(expression: &str) -> Result<(&str, bool, &str), XPathParseError> {
+    let (left, right, negate) = match expression.split_once("!=") {
+        Some((left, right)) => (left, right, true),
+        None => match expression.split_once('=') {
+            Some((left, right)) => (left, right, false),
+            None => {
+                return Err(XPathParseError::InvalidSyntax(format!(
+                    "unsupported predicate [{}]",
+                    expression
+                )))
+            }
+        },
+    };
+    let right = right.trim();
+    let literal = right
+        .strip_prefix('\'')
+        .and_then(|r| r.strip_suffix('\''))
+        .or_else(|| right.strip_prefix('"').and_then(|r| r.strip_suffix('"')))
+        .unwrap_or(right);
+    Ok((left.trim(), negate, literal))
+}
+
+fn relative_values(node: SelectedNode<'_>, path: &str) -> Result<Vec<String>, XPathParseError> {
+    let steps = ParsedXPath::parse(path)?.steps;
+    Ok(select_steps(vec![node], &steps)?.iter().map(SelectedNode::string_value).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIBRARY: &str = r#"<library xmlns="http://example.com/lib">
+        <book id="b1" lang="en"><title>Dune</title><year>1965</year></book>
+        <book id="b2" lang="fr"><title>Vendredi</title><year>1967</year></book>
+        <magazine id="m1"><title>Wired</title></magazine>
+    </library>"#;
+
+    fn values(doc: &Document, expression: &str) -> Vec<String> {
+        let evaluator = XPathEvaluator::new(doc).unwrap();
+        evaluator.select(expression).unwrap().iter().map(SelectedNode::string_value).collect()
+    }
+
+    #[test]
+    fn test_select_paths() {
+        let doc = Document::from_string(LIBRARY).unwrap();
+
+        assert_eq!(values(&doc, "/library/book/title/text()"), vec!["Dune", "Vendredi"]);
+        assert_eq!(values(&doc, "/library/book/@id"), vec!["b1", "b2"]);
+        assert_eq!(values(&doc, "book/year"), vec!["1965", "1967"]);
+        assert_eq!(values(&doc, "//title"), vec!["Dune", "Vendredi", "Wired"]);
+        assert_eq!(values(&doc, "/library/*/@id"), vec!["b1", "b2", "m1"]);
+        assert_eq!(values(&doc, "/lib:library/lib:magazine/lib:title"), vec!["Wired"]);
+        assert!(values(&doc, "/book").is_empty());
+        assert_eq!(values(&doc, "/library/book[1]/title"), vec!["Dune"]);
+    }
+
+    #[test]
+    fn test_select_predicates() {
+        let doc = Document::from_string(LIBRARY).unwrap();
+
+        assert_eq!(values(&doc, "/library/book[last()]/title"), vec!["Vendredi"]);
+        assert_eq!(values(&doc, "/library/book[@lang='fr']/title"), vec!["Vendredi"]);
+        assert_eq!(values(&doc, "/library/book[year!=\"1965\"]/@id"), vec!["b2"]);
+        assert_eq!(values(&doc, "/library/*[year]/@id"), vec!["b1", "b2"]);
+        assert_eq!(values(&doc, "//book[@lang='en'][1]/year"), vec!["1965"]);
+        assert!(values(&doc, "/library/book[3]").is_empty());
+
+        let evaluator = XPathEvaluator::new(&doc).unwrap();
+        assert!(evaluator.select("/library/book/..").is_err());
+        assert!(matches!(evaluator.evaluate("/library/dvd").unwrap(), XPathResult::Empty));
+        let result = evaluator.evaluate("//magazine/@id").unwrap();
+        assert_eq!(result.as_string(), "m1");
+        assert_eq!(result.as_nodes().unwrap()[0].node_type, XPathNodeType::Attribute);
+    }
+}
@@ -0,0 +1,795 @@
+//! XPath expressions over elements
+//!
+//! This module parses and evaluates the subset of XPath 2.0 needed by XSD 1.1
+//! assertions: location paths, literals, `$variables`, the boolean operators,
+//! general and value comparisons (`=`, `!=`, `<`, `<=`, `>`, `>=`, `eq`, ...),
+//! arithmetic and a small library of functions.
+//!
+//! Values are untyped: a comparison is numeric when both operands are
+//! numbers, otherwise the string values are compared. Location paths are
+//! evaluated like in [`XPathEvaluator`](super::XPathEvaluator).
+
+use std::cmp::Ordering;
+
+use super::evaluator::{parse_path, select_steps, SelectedNode};
+use super::parsers::{ParsedXPath, XPathParseError};
+use super::{XPathContext, XPathNode, XPathResult};
+use crate::documents::Element;
+
+/// Functions with their minimum and maximum number of arguments
+const FUNCTIONS: [(&str, usize, usize); 17] = [
+    ("true", 0, 0),
+    ("false", 0, 0),
+    ("not", 1, 1),
+    ("boolean", 1, 1),
+    ("count", 1, 1),
+    ("exists", 1, 1),
+    ("empty", 1, 1),
+    ("string", 0, 1),
+    ("number", 0, 1),
+    ("string-length", 0, 1),
+    ("normalize-space", 0, 1),
+    ("contains", 2, 2),
+    ("starts-with", 2, 2),
+    ("ends-with", 2, 2),
+    ("concat", 2, usize::MAX),
+    ("sum", 1, 1),
+    ("abs", 1, 1),
+];
+
+/// Operators written as names
+const KEYWORD_OPERATORS: [&str; 10] = ["and", "or", "div", "mod", "eq", "ne", "lt", "le", "gt", "ge"];
+
+/// A compiled XPath expression
+#[derive(Debug, Clone)]
+pub struct XPathExpression {
+    expression: String,
+    root: Expr,
+}
+
+impl XPathExpression {
+    /// Parse an expression
+    pub fn parse(expression: &str) -> Result<Self, XPathParseError> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser { tokens, position: 0 };
+        let root = parser.parse_or()?;
+        if let Some(token) = parser.next() {
+            return Err(XPathParseError::InvalidSyntax(format!(
+                "unexpected {:?} in '{}'",
+                token, expression
+            )));
+        }
+        Ok(Self {
+            expression: expression.to_string(),
+            root,
+        })
+    }
+
+    /// Get the source of the expression
+    pub fn as_str(&self) -> &str {
+        &self.expression
+    }
+
+    /// Evaluate the expression with an element as context node
+    pub fn evaluate(&self, element: &Element, context: &XPathContext) -> Result<XPathResult, XPathParseError> {
+        Ok(evaluate(&self.root, element, context)?.into_result())
+    }
+
+    /// Evaluate the expression into its effective boolean value
+    pub fn evaluate_boolean(&self, element: &Element, context: &XPathContext) -> Result<bool, XPathParseError> {
+        Ok(evaluate(&self.root, element, context)?.boolean())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Literal(String),
+    Variable(String),
+    Path(String),
+    Function(String),
+    Operator(&'static str),
+    LeftParen,
+    RightParen,
+    Comma,
+}
+
+impl Token {
+    /// Whether an operator, rather than an operand, may follow the token
+    fn ends_operand(&self) -> bool {
+        matches!(
+            self,
+            Token::Number(_) | Token::Literal(_) | Token::Variable(_) | Token::Path(_) | Token::RightParen
+        )
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, XPathParseError> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let operand = !tokens.last().is_some_and(Token::ends_operand);
+        let next = chars.get(i + 1).copied();
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LeftParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RightParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '=' => {
+                tokens.push(Token::Operator("="));
+                i += 1;
+            }
+            '!' if next == Some('=') => {
+                tokens.push(Token::Operator("!="));
+                i += 2;
+            }
+            '<' | '>' => {
+                let operator = match (c, next == Some('=')) {
+                    ('<', true) => "<=",
+                    ('<', false) => "<",
+                    (_, true) => ">=",
+                    (_, false) => ">",
+                };
+                tokens.push(Token::Operator(operator));
+                i += operator.len();
+            }
+            '+' => {
+                tokens.push(Token::Operator("+"));
+                i += 1;
+            }
+            '-' => {
+                tokens.push(Token::Operator("-"));
+                i += 1;
+            }
+            '*' if !operand => {
+                tokens.push(Token::Operator("*"));
+                i += 1;
+            }
+            '\'' | '"' => {
+                let (literal, end) = scan_literal(&chars, i)?;
+                tokens.push(Token::Literal(literal));
+                i = end;
+            }
+            '$' => {
+                let end = scan_while(&chars, i + 1, |c| is_name_char(c) || c == ':');
+                if end == i + 1 {
+                    return Err(XPathParseError::InvalidSyntax(format!(
+                        "missing variable name in '{}'",
+                        expression
+                    )));
+                }
+                tokens.push(Token::Variable(chars[i + 1..end].iter().collect()));
+                i = end;
+            }
+            _ if !operand => {
+                let end = scan_while(&chars, i, is_name_char);
+                let name: String = chars[i..end].iter().collect();
+                let Some(operator) = KEYWORD_OPERATORS.iter().copied().find(|op| *op == name) else {
+                    return Err(XPathParseError::InvalidSyntax(format!(
+                        "expected an operator at '{}' in '{}'",
+                        chars[i..].iter().collect::<String>(),
+                        expression
+                    )));
+                };
+                tokens.push(Token::Operator(operator));
+                i = end;
+            }
+            _ if c.is_ascii_digit() || (c == '.' && next.is_some_and(|c| c.is_ascii_digit())) => {
+                let end = scan_while(&chars, i, |c| c.is_ascii_digit() || c == '.');
+                let text: String = chars[i..end].iter().collect();
+                let number = text
+                    .parse()
+                    .map_err(|_| XPathParseError::InvalidSyntax(format!("invalid number '{}'", text)))?;
+                tokens.push(Token::Number(number));
+                i = end;
+            }
+            _ => {
+                let (token, end) = scan_path(&chars, i)?;
+                tokens.push(token);
+                i = end;
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn scan_while(chars: &[char], start: usize, accept: impl Fn(char) -> bool) -> usize {
+    chars[start..]
+        .iter()
+        .position(|c| !accept(*c))
+        .map_or(chars.len(), |n| start + n)
+}
+
+/// Scan a quoted string, where a doubled quote stands for the quote itself
+fn scan_literal(chars: &[char], start: usize) -> Result<(String, usize), XPathParseError> {
+    let quote = chars[start];
+    let mut literal = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        if chars[i] == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                literal.push(quote);
+                i += 2;
+                continue;
+            }
+            return Ok((literal, i + 1));
+        }
+        literal.push(chars[i]);
+        i += 1;
+    }
+    Err(XPathParseError::UnexpectedEnd)
+}
+
+/// Scan a location path, or the name of a function call
+fn scan_path(chars: &[char], start: usize) -> Result<(Token, usize), XPathParseError> {
+    let mut i = start;
+    while i < chars.len() {
+        let c = chars[i];
+        let wildcard = c == '*' && (i == start || matches!(chars[i - 1], '/' | ':' | '@'));
+        if is_name_char(c) || matches!(c, '@' | '/' | ':' | '.') || wildcard {
+            i += 1;
+        } else if c == '[' {
+            i = scan_predicate(chars, i)?;
+        } else if c == '(' {
+            let text: String = chars[start..i].iter().collect();
+            let last_step = text.rsplit('/').next().unwrap_or_default();
+            if matches!(last_step, "text" | "node") && chars.get(i + 1) == Some(&')') {
+                i += 2;
+            } else if !text.is_empty() && !text.contains(['/', '@', '[', '.']) {
+                return Ok((Token::Function(text), i));
+            } else {
+                break;
+            }
+        } else {
+            break;
+        }
+    }
+
+    if i == start {
+        return Err(XPathParseError::InvalidSyntax(format!(
+            "unexpected character '{}' in '{}'",
+            chars[start],
+            chars.iter().collect::<String>()
+        )));
+    }
+    Ok((Token::Path(chars[start..i].iter().collect()), i))
+}
+
+/// Skip a predicate, returning the position after its closing bracket
+fn scan_predicate(chars: &[char], start: usize) -> Result<usize, XPathParseError> {
+    let mut depth = 0;
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(i + 1);
+                }
+            }
+            '\'' | '"' => {
+                i = scan_literal(chars, i)?.1;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    Err(XPathParseError::UnexpectedEnd)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn from_operator(operator: &str) -> Option<Self> {
+        match operator {
+            "=" | "eq" => Some(Self::Eq),
+            "!=" | "ne" => Some(Self::Ne),
+            "<" | "lt" => Some(Self::Lt),
+            "<=" | "le" => Some(Self::Le),
+            ">" | "gt" => Some(Self::Gt),
+            ">=" | "ge" => Some(Self::Ge),
+            _ => None,
+        }
+    }
+
+    fn holds(self, ordering: Option<Ordering>) -> bool {
+        match self {
+            Self::Eq => ordering == Some(Ordering::Equal),
+            Self::Ne => ordering != Some(Ordering::Equal),
+            Self::Lt => ordering == Some(Ordering::Less),
+            Self::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            Self::Gt => ordering == Some(Ordering::Greater),
+            Self::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Arithmetic {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(f64),
+    Literal(String),
+    Variable(String),
+    Path(ParsedXPath),
+    Function(String, Vec<Expr>),
+    Negate(Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Compare(Comparison, Box<Expr>, Box<Expr>),
+    Arithmetic(Arithmetic, Box<Expr>, Box<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn next_operator(&mut self, operators: &[&str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Operator(operator)) if operators.contains(operator) => {
+                let operator = *operator;
+                self.position += 1;
+                Some(operator)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, XPathParseError> {
+        let mut left = self.parse_and()?;
+        while self.next_operator(&["or"]).is_some() {
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, XPathParseError> {
+        let mut left = self.parse_comparison()?;
+        while self.next_operator(&["and"]).is_some() {
+            left = Expr::And(Box::new(left), Box::new(self.parse_comparison()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, XPathParseError> {
+        let left = self.parse_additive()?;
+        let operators = ["=", "!=", "<", "<=", ">", ">=", "eq", "ne", "lt", "le", "gt", "ge"];
+        match self.next_operator(&operators).and_then(Comparison::from_operator) {
+            Some(comparison) => Ok(Expr::Compare(
+                comparison,
+                Box::new(left),
+                Box::new(self.parse_additive()?),
+            )),
+            None => Ok(left),
+        }
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, XPathParseError> {
+        let mut left = self.parse_multiplicative()?;
+        while let Some(operator) = self.next_operator(&["+", "-"]) {
+            let arithmetic = if operator == "+" {
+                Arithmetic::Add
+            } else {
+                Arithmetic::Subtract
+            };
+            left = Expr::Arithmetic(arithmetic, Box::new(left), Box::new(self.parse_multiplicative()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, XPathParseError> {
+        let mut left = self.parse_unary()?;
+        while let Some(operator) = self.next_operator(&["*", "div", "mod"]) {
+            let arithmetic = match operator {
+                "*" => Arithmetic::Multiply,
+                "div" => Arithmetic::Divide,
+                _ => Arithmetic::Modulo,
+            };
+            left = Expr::Arithmetic(arithmetic, Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, XPathParseError> {
+        if self.next_operator(&["-"]).is_some() {
+            return Ok(Expr::Negate(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, XPathParseError> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::Literal(literal)) => Ok(Expr::Literal(literal)),
+            Some(Token::Variable(name)) => Ok(Expr::Variable(name)),
+            Some(Token::Path(path)) => Ok(Expr::Path(parse_path(&path)?)),
+            Some(Token::LeftParen) => {
+                let expr = self.parse_or()?;
+                self.expect_right_paren()?;
+                Ok(expr)
+            }
+            Some(Token::Function(name)) => self.parse_function(name),
+            Some(token) => Err(XPathParseError::InvalidSyntax(format!("unexpected {:?}", token))),
+            None => Err(XPathParseError::UnexpectedEnd),
+        }
+    }
+
+    fn parse_function(&mut self, name: String) -> Result<Expr, XPathParseError> {
+        let name = name.strip_prefix("fn:").map(str::to_string).unwrap_or(name);
+        let Some((_, min_args, max_args)) = FUNCTIONS.iter().find(|(function, ..)| *function == name) else {
+            return Err(XPathParseError::InvalidSyntax(format!(
+                "unsupported function {}()",
+                name
+            )));
+        };
+
+        self.next(); // The opening parenthesis
+        let mut args = Vec::new();
+        if self.peek() == Some(&Token::RightParen) {
+            self.next();
+        } else {
+            loop {
+                args.push(self.parse_or()?);
+                match self.next() {
+                    Some(Token::Comma) => continue,
+                    Some(Token::RightParen) => break,
+                    Some(token) => return Err(XPathParseError::InvalidSyntax(format!("unexpected {:?}", token))),
+                    None => return Err(XPathParseError::UnexpectedEnd),
+                }
+            }
+        }
+
+        if args.len() < *min_args || args.len() > *max_args {
+            return Err(XPathParseError::InvalidSyntax(format!(
+                "wrong number of arguments for {}(): {}",
+                name,
+                args.len()
+            )));
+        }
+        Ok(Expr::Function(name, args))
+    }
+
+    fn expect_right_paren(&mut self) -> Result<(), XPathParseError> {
+        match self.next() {
+            Some(Token::RightParen) => Ok(()),
+            Some(token) => Err(XPathParseError::InvalidSyntax(format!(
+                "expected ')', found {:?}",
+                token
+            ))),
+            None => Err(XPathParseError::UnexpectedEnd),
+        }
+    }
+}
+
+/// An intermediate value of an evaluation
+#[derive(Debug, Clone)]
+enum Value {
+    Nodes(Vec<XPathNode>),
+    Boolean(bool),
+    Number(f64),
+    String(String),
+}
+
+impl Value {
+    fn from_result(result: &XPathResult) -> Self {
+        match result {
+            XPathResult::Nodes(nodes) => Self::Nodes(nodes.clone()),
+            XPathResult::Boolean(b) => Self::Boolean(*b),
+            XPathResult::Number(n) => Self::Number(*n),
+            XPathResult::String(s) => Self::String(s.clone()),
+            XPathResult::Empty => Self::Nodes(Vec::new()),
+        }
+    }
+
+    fn into_result(self) -> XPathResult {
+        match self {
+            Self::Nodes(nodes) if nodes.is_empty() => XPathResult::Empty,
+            Self::Nodes(nodes) => XPathResult::Nodes(nodes),
+            Self::Boolean(b) => XPathResult::Boolean(b),
+            Self::Number(n) => XPathResult::Number(n),
+            Self::String(s) => XPathResult::String(s),
+        }
+    }
+
+    /// Get the effective boolean value
+    fn boolean(&self) -> bool {
+        match self {
+            Self::Nodes(nodes) => !nodes.is_empty(),
+            Self::Boolean(b) => *b,
+            Self::Number(n) => *n != 0.0 && !n.is_nan(),
+            Self::String(s) => !s.is_empty(),
+        }
+    }
+
+    fn string(&self) -> String {
+        match self {
+            Self::Nodes(nodes) => nodes.first().map(|node| node.value.clone()).unwrap_or_default(),
+            Self::Boolean(b) => b.to_string(),
+            Self::Number(n) => format_number(*n),
+            Self::String(s) => s.clone(),
+        }
+    }
+
+    fn number(&self) -> f64 {
+        match self {
+            Self::Number(n) => *n,
+            Self::Boolean(b) => f64::from(u8::from(*b)),
+            _ => parse_number(&self.string()),
+        }
+    }
+
+    /// Get the string values of the items of the value
+    fn items(&self) -> Vec<String> {
+        match self {
+            Self::Nodes(nodes) => nodes.iter().map(|node| node.value.clone()).collect(),
+            _ => vec![self.string()],
+        }
+    }
+}
+
+fn format_number(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".to_string()
+    } else if n == f64::INFINITY {
+        "INF".to_string()
+    } else if n == f64::NEG_INFINITY {
+        "-INF".to_string()
+    } else {
+        n.to_string()
+    }
+}
+
+fn parse_number(s: &str) -> f64 {
+    let s = s.trim();
+    match s {
+        "INF" => f64::INFINITY,
+        "-INF" => f64::NEG_INFINITY,
+        _ if s.chars().any(|c| c.is_ascii_digit()) => s.parse().unwrap_or(f64::NAN),
+        _ => f64::NAN,
+    }
+}
+
+/// Compare two values, true if any pair of their items compares
+fn compare(comparison: Comparison, left: &Value, right: &Value) -> bool {
+    if matches!(left, Value::Boolean(_)) || matches!(right, Value::Boolean(_)) {
+        return comparison.holds(left.boolean().partial_cmp(&right.boolean()));
+    }
+    let right_items = right.items();
+    left.items().iter().any(|l| {
+        right_items.iter().any(|r| {
+            let (ln, rn) = (parse_number(l), parse_number(r));
+            let ordering = if ln.is_nan() || rn.is_nan() {
+                Some(l.cmp(r))
+            } else {
+                ln.partial_cmp(&rn)
+            };
+            comparison.holds(ordering)
+        })
+    })
+}
+
+fn evaluate(expr: &Expr, element: &Element, context: &XPathContext) -> Result<Value, XPathParseError> {
+    Ok(match expr {
+        Expr::Number(n) => Value::Number(*n),
+        Expr::Literal(s) => Value::String(s.clone()),
+        Expr::Variable(name) => match context.variables.get(name) {
+            Some(result) => Value::from_result(result),
+            None => return Err(XPathParseError::InvalidSyntax(format!("unbound variable ${}", name))),
+        },
+        Expr::Path(path) => {
+            let start = if path.is_absolute {
+                SelectedNode::Document(element)
+            } else {
+                SelectedNode::Element(element)
+            };
+            let nodes = select_steps(vec![start], &path.steps)?;
+            Value::Nodes(
+                nodes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, node)| node.to_xpath_node().with_position(i + 1))
+                    .collect(),
+            )
+        }
+        Expr::Function(name, args) => evaluate_function(name, args, element, context)?,
+        Expr::Negate(operand) => Value::Number(-evaluate(operand, element, context)?.number()),
+        Expr::Or(left, right) => {
+            Value::Boolean(evaluate(left, element, context)?.boolean() || evaluate(right, element, context)?.boolean())
+        }
+        Expr::And(left, right) => {
+            Value::Boolean(evaluate(left, element, context)?.boolean() && evaluate(right, element, context)?.boolean())
+        }
+        Expr::Compare(comparison, left, right) => {
+            let left = evaluate(left, element, context)?;
+            let right = evaluate(right, element, context)?;
+            Value::Boolean(compare(*comparison, &left, &right))
+        }
+        Expr::Arithmetic(arithmetic, left, right) => {
+            let left = evaluate(left, element, context)?.number();
+            let right = evaluate(right, element, context)?.number();
+            Value::Number(match arithmetic {
+                Arithmetic::Add => left + right,
+                Arithmetic::Subtract => left - right,
+                Arithmetic::Multiply => left * right,
+                Arithmetic::Divide => left / right,
+                Arithmetic::Modulo => left % right,
+            })
+        }
+    })
+}
+
+fn evaluate_function(
+    name: &str,
+    args: &[Expr],
+    element: &Element,
+    context: &XPathContext,
+) -> Result<Value, XPathParseError> {
+    let mut values = Vec::with_capacity(args.len());
+    for arg in args {
+        values.push(evaluate(arg, element, context)?);
+    }
+    // Functions with an optional argument default to the context node
+    let string_arg = || match values.first() {
+        Some(value) => value.string(),
+        None => SelectedNode::Element(element).string_value(),
+    };
+
+    Ok(match name {
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        "not" => Value::Boolean(!values[0].boolean()),
+        "boolean" => Value::Boolean(values[0].boolean()),
+        "count" => Value::Number(values[0].items().len() as f64),
+        "exists" => Value::Boolean(!values[0].items().is_empty()),
+        "empty" => Value::Boolean(values[0].items().is_empty()),
+        "string" => Value::String(string_arg()),
+        "number" => Value::Number(match values.first() {
+            Some(value) => value.number(),
+            None => parse_number(&string_arg()),
+        }),
+        "string-length" => Value::Number(string_arg().chars().count() as f64),
+        "normalize-space" => Value::String(string_arg().split_whitespace().collect::<Vec<_>>().join(" ")),
+        "contains" => Value::Boolean(values[0].string().contains(&values[1].string())),
+        "starts-with" => Value::Boolean(values[0].string().starts_with(&values[1].string())),
+        "ends-with" => Value::Boolean(values[0].string().ends_with(&values[1].string())),
+        "concat" => Value::String(values.iter().map(Value::string).collect()),
+        "sum" => Value::Number(values[0].items().iter().map(|item| parse_number(item)).sum()),
+        "abs" => Value::Number(values[0].number().abs()),
+        _ => {
+            return Err(XPathParseError::InvalidSyntax(format!(
+                "unsupported function {}()",
+                name
+            )))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documents::Document;
+
+    const RANGE: &str = r#"<range min="2" max="10" unit="cm"><step>3</step><step>4</step>long</range>"#;
+
+    fn check(expression: &str) -> bool {
+        let doc = Document::from_string(RANGE).unwrap();
+        let context = XPathContext::new().with_variable("limit", XPathResult::Number(5.0));
+        XPathExpression::parse(expression)
+            .unwrap()
+            .evaluate_boolean(doc.root().unwrap(), &context)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_comparisons() {
+        assert!(check("@min <= @max"));
+        assert!(!check("@min > @max"));
+        assert!(check("@min < 10 and @max = 10"));
+        assert!(check("@unit = 'cm'"));
+        assert!(check("@unit != \"mm\""));
+        assert!(check("@max ge 10 and @min lt @max"));
+        assert!(check("step = 4"));
+        assert!(!check("step > 4"));
+        assert!(!check("@missing = 1"));
+        assert!(check("@min<=$limit"));
+    }
+
+    #[test]
+    fn test_arithmetic_and_functions() {
+        assert!(check("@max - @min = 8"));
+        assert!(check("sum(step) = 7"));
+        assert!(check("count(step) * 2 = 4"));
+        assert!(check("@max div 4 = 2.5 and @max mod 4 = 2"));
+        assert!(check("-@min = -2"));
+        assert!(check("not(@missing) or false()"));
+        assert!(check("exists(@unit) and empty(@missing)"));
+        assert!(check("string-length(@unit) = 2"));
+        assert!(check("contains(string(.), 'long')"));
+        assert!(check("starts-with(concat(@unit, 'x'), 'cmx')"));
+        assert!(check("count(step[. = '3']) = 1"));
+        assert!(check("(@min + 1) * 2 = 6"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(XPathExpression::parse("@min <=").is_err());
+        assert!(XPathExpression::parse("unknown(@min)").is_err());
+        assert!(XPathExpression::parse("not(1, 2)").is_err());
+        assert!(XPathExpression::parse("'unterminated").is_err());
+        assert!(XPathExpression::parse("(@min").is_err());
+
+        let doc = Document::from_string(RANGE).unwrap();
+        let expression = XPathExpression::parse("$undefined = 1").unwrap();
+        assert!(expression.evaluate(doc.root().unwrap(), &XPathContext::new()).is_err());
+        assert_eq!(expression.as_str(), "$undefined = 1");
+    }
+
+    #[test]
+    fn test_evaluate_result() {
+        let doc = Document::from_string(RANGE).unwrap();
+        let root = doc.root().unwrap();
+        let context = XPathContext::new();
+
+        let result = XPathExpression::parse("step")
+            .unwrap()
+            .evaluate(root, &context)
+            .unwrap();
+        assert_eq!(result.as_nodes().map(Vec::len), Some(2));
+        let result = XPathExpression::parse("@max * 2")
+            .unwrap()
+            .evaluate(root, &context)
+            .unwrap();
+        assert!(matches!(result, XPathResult::Number(n) if n == 20.0));
+        let result = XPathExpression::parse("@none")
+            .unwrap()
+            .evaluate(root, &context)
+            .unwrap();
+        assert!(matches!(result, XPathResult::Empty));
+    }
+}
@@ -15,6 +15,9 @@
 //! - Path splitting and analysis
 //! - Simple step-based selectors
 //! - NCName validation
+//! - Location path evaluation over documents
+//! - Boolean, comparison and arithmetic expressions over paths
+//! - Location path navigation of schema components
 //!
 //! For full XPath 2.0/3.0 support, integration with a dedicated
 //! XPath engine would be required.
@@ -22,6 +25,8 @@
 mod selectors;
 mod proxy;
 mod parsers;
+mod evaluator;
+mod expressions;
 
 pub use selectors::{
     ElementSelector, PathStep, PathStepKind, split_path, is_ncname, is_ncname_char,
@@ -30,9 +35,14 @@ pub use proxy::SchemaProxy;
 pub use parsers::{
     IdentityXPathParser, AssertionXPathParser, ParsedXPath, XPathAxis, XPathPredicate,
 };
+pub use evaluator::{SelectedNode, XPathEvaluator};
+pub use expressions::XPathExpression;
 
 use std::collections::HashMap;
 
+use crate::namespaces::QName;
+use parsers::XPathParseError;
+
 /// Namespace mapping type
 pub type NamespaceMap = HashMap<String, String>;
 
@@ -174,6 +184,8 @@ pub struct XPathContext {
     pub size: usize,
     /// Variable bindings
     pub variables: HashMap<String, XPathResult>,
+    /// Proxy of the schema whose components are navigated
+    pub schema: Option<SchemaProxy>,
 }
 
 impl Default for XPathContext {
@@ -191,6 +203,7 @@ impl XPathContext {
             position: 1,
             size: 1,
             variables: HashMap::new(),
+            schema: None,
         }
     }
 
@@ -206,6 +219,12 @@ impl XPathContext {
         self
     }
 
+    /// Set the schema proxy
+    pub fn with_schema(mut self, schema: SchemaProxy) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
     /// Set the context node
     pub fn with_context_node(mut self, node: XPathNode) -> Self {
         self.context_node = Some(node);
@@ -218,6 +237,9 @@ impl XPathContext {
     }
 
     /// Expand a prefixed name to Clark notation
+    ///
+    /// Names not resolved by the context's namespaces are resolved by the
+    /// schema proxy, if any.
     pub fn expand_name(&self, name: &str) -> String {
         if let Some(pos) = name.find(':') {
             let prefix = &name[..pos];
@@ -226,7 +248,22 @@ impl XPathContext {
                 return format!("{{{}}}{}", uri, local);
             }
         }
-        name.to_string()
+        match &self.schema {
+            Some(schema) => schema.resolve_name(name),
+            None => name.to_string(),
+        }
+    }
+
+    /// Evaluate a location path on the schema components
+    ///
+    /// The path is evaluated by the schema proxy, with the context's
+    /// namespaces added to it, from the named complex type.
+    pub fn evaluate_schema_path(&self, path: &str, type_name: &QName) -> Result<XPathResult, XPathParseError> {
+        let schema = self
+            .schema
+            .as_ref()
+            .ok_or_else(|| XPathParseError::InvalidSyntax("no schema to evaluate the path on".to_string()))?;
+        schema.clone().with_namespaces(self.namespaces.clone()).evaluate_xpath(path, type_name)
     }
 }
 
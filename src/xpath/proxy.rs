@@ -2,10 +2,20 @@
 //!
 //! This module provides a proxy interface between XPath evaluation
 //! and the schema/document being validated.
+//!
+//! A proxy built on a schema can also evaluate location paths on the
+//! schema components: the children of an element are the elements declared
+//! in the content model of its type, and its attributes the attributes
+//! declared by that type.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
-use super::{NamespaceMap, XPathNode, XPathNodeType, XPathResult};
+use super::evaluator::parse_path;
+use super::parsers::{NodeTest, ParsedStep, XPathParseError};
+use super::{NamespaceMap, XPathAxis, XPathNode, XPathNodeType, XPathResult};
+use crate::namespaces::QName;
+use crate::validators::{GlobalType, GroupParticle, XsdAttribute, XsdComplexType, XsdElement, XsdGroup, XsdSchema};
 
 /// Schema proxy for XPath operations
 ///
@@ -21,6 +31,8 @@ pub struct SchemaProxy {
     target_namespace: Option<String>,
     /// Type information cache
     types: HashMap<String, TypeInfo>,
+    /// The schema whose components are navigated
+    schema: Option<Arc<XsdSchema>>,
 }
 
 impl Default for SchemaProxy {
@@ -52,9 +64,28 @@ impl SchemaProxy {
             default_namespace: None,
             target_namespace: None,
             types: HashMap::new(),
+            schema: None,
         }
     }
 
+    /// Create a proxy for the components of a schema
+    ///
+    /// The namespace declarations and the target namespace of the schema
+    /// are used to resolve prefixed names.
+    pub fn from_schema(schema: Arc<XsdSchema>) -> Self {
+        let mut proxy = Self::new().with_namespaces(schema.namespace_declarations().clone());
+        // The default namespace of the schema document is for its QName references
+        proxy.namespaces.remove("");
+        proxy.target_namespace = schema.target_namespace.clone();
+        proxy.schema = Some(schema);
+        proxy
+    }
+
+    /// Get the schema whose components are navigated
+    pub fn schema(&self) -> Option<&Arc<XsdSchema>> {
+        self.schema.as_ref()
+    }
+
     /// Create with custom namespace mappings
     pub fn with_namespaces(mut self, namespaces: NamespaceMap) -> Self {
         self.namespaces.extend(namespaces);
@@ -185,6 +216,236 @@ impl SchemaProxy {
         }
     }
 
+    /// Get the child elements declared in the content model of a complex type
+    ///
+    /// References are resolved to the global element declarations. Returns
+    /// an empty list for unknown and simple types.
+    pub fn children_of(&self, type_name: &QName) -> Vec<Arc<XsdElement>> {
+        self.complex_type(type_name)
+            .map(|complex_type| self.content_elements(complex_type))
+            .unwrap_or_default()
+    }
+
+    /// Get the attributes declared by a complex type, sorted by name
+    pub fn attributes_of(&self, type_name: &QName) -> Vec<Arc<XsdAttribute>> {
+        self.complex_type(type_name)
+            .map(|complex_type| declared_attributes(complex_type))
+            .unwrap_or_default()
+    }
+
+    /// Get the type name of a global element declaration
+    ///
+    /// Returns `None` for unknown elements and elements of anonymous type.
+    pub fn type_of(&self, element_name: &QName) -> Option<QName> {
+        let schema = self.schema.as_ref()?;
+        let element = schema.lookup_element(element_name)?;
+        match element.effective_type(schema) {
+            Some(element_type) => element_type.name().cloned(),
+            None => element.type_name.clone(),
+        }
+    }
+
+    /// Evaluate a location path on the schema components
+    ///
+    /// Relative paths start from the named complex type; absolute paths
+    /// start from a document node whose children are the global elements.
+    /// The child, descendant, descendant-or-self, self and attribute axes
+    /// are supported; predicates are not.
+    pub fn evaluate_xpath(&self, path: &str, type_name: &QName) -> Result<XPathResult, XPathParseError> {
+        let parsed = parse_path(path)?;
+        let start = if parsed.is_absolute {
+            ComponentNode::Document
+        } else {
+            let complex_type = self.complex_type(type_name).ok_or_else(|| {
+                XPathParseError::InvalidSyntax(format!("'{}' is not a complex type of the schema", type_name.to_string()))
+            })?;
+            ComponentNode::Type(Arc::clone(complex_type))
+        };
+
+        let mut nodes = vec![start];
+        for step in &parsed.steps {
+            if !step.predicates.is_empty() {
+                return Err(XPathParseError::InvalidSyntax(
+                    "predicates are not supported on schema components".to_string(),
+                ));
+            }
+            // A declaration reached more than once is selected once
+            let mut seen = HashSet::new();
+            let mut next = Vec::new();
+            for node in &nodes {
+                next.extend(self.apply_axis(node, step)?.into_iter().filter(|candidate| {
+                    self.node_test_matches(&step.node_test, candidate) && seen.insert(self.node_key(candidate))
+                }));
+            }
+            nodes = next;
+        }
+
+        if nodes.is_empty() {
+            return Ok(XPathResult::Empty);
+        }
+        Ok(XPathResult::Nodes(
+            nodes
+                .iter()
+                .enumerate()
+                .map(|(i, node)| node.to_xpath_node().with_position(i + 1))
+                .collect(),
+        ))
+    }
+
+    fn complex_type(&self, type_name: &QName) -> Option<&Arc<XsdComplexType>> {
+        self.schema.as_ref()?.lookup_type(type_name).and_then(GlobalType::as_complex)
+    }
+
+    /// Get the complex type of a component node, if it has element or attribute content
+    fn node_type(&self, node: &ComponentNode) -> Option<Arc<XsdComplexType>> {
+        match node {
+            ComponentNode::Type(complex_type) => Some(Arc::clone(complex_type)),
+            ComponentNode::Element(element) => match element.effective_type(self.schema.as_ref()?) {
+                Some(GlobalType::Complex(complex_type)) => Some(complex_type),
+                _ => None,
+            },
+            ComponentNode::Document | ComponentNode::Attribute(_) => None,
+        }
+    }
+
+    /// Get a key telling apart the components reached by a path
+    ///
+    /// Type derivation may copy a declaration, so declarations are compared
+    /// by name and type rather than by address.
+    fn node_key(&self, node: &ComponentNode) -> String {
+        match node {
+            ComponentNode::Document => "/".to_string(),
+            ComponentNode::Type(complex_type) => type_key(complex_type),
+            ComponentNode::Element(element) => {
+                let element_type = self.node_type(node).map(|complex_type| type_key(&complex_type));
+                format!("{} {}", element.name.to_string(), element_type.unwrap_or_default())
+            }
+            ComponentNode::Attribute(attribute) => format!("@{}", attribute.name().to_string()),
+        }
+    }
+
+    fn content_elements(&self, complex_type: &XsdComplexType) -> Vec<Arc<XsdElement>> {
+        let mut elements = Vec::new();
+        if let Some(group) = complex_type.content.as_group() {
+            self.collect_group_elements(group, &mut elements);
+        }
+        elements
+    }
+
+    fn collect_group_elements(&self, group: &XsdGroup, elements: &mut Vec<Arc<XsdElement>>) {
+        for particle in &group.particles {
+            match particle {
+                GroupParticle::Element(particle) => {
+                    let declaration = particle.element().cloned().or_else(|| {
+                        let name = particle.element_ref.as_ref().unwrap_or(&particle.name);
+                        self.schema.as_ref()?.lookup_element(name).cloned()
+                    });
+                    elements.extend(declaration);
+                }
+                GroupParticle::Group(nested) => self.collect_group_elements(nested, elements),
+                GroupParticle::Any(_) => {}
+            }
+        }
+    }
+
+    fn children(&self, node: &ComponentNode) -> Vec<ComponentNode> {
+        match node {
+            ComponentNode::Document => {
+                let Some(schema) = &self.schema else {
+                    return Vec::new();
+                };
+                let mut elements: Vec<_> = schema.elements().map(|(_, element)| Arc::clone(element)).collect();
+                elements.sort_by_key(|element| element.name.to_string());
+                elements.into_iter().map(ComponentNode::Element).collect()
+            }
+            _ => self
+                .node_type(node)
+                .map(|complex_type| self.content_elements(&complex_type))
+                .unwrap_or_default()
+                .into_iter()
+                .map(ComponentNode::Element)
+                .collect(),
+        }
+    }
+
+    /// Collect the descendants of a node, visiting each complex type once
+    fn collect_descendants(
+        &self,
+        node: &ComponentNode,
+        visited: &mut HashSet<String>,
+        nodes: &mut Vec<ComponentNode>,
+    ) {
+        if let Some(complex_type) = self.node_type(node) {
+            if !visited.insert(type_key(&complex_type)) {
+                return;
+            }
+        }
+        for child in self.children(node) {
+            nodes.push(child.clone());
+            self.collect_descendants(&child, visited, nodes);
+        }
+    }
+
+    fn apply_axis(&self, node: &ComponentNode, step: &ParsedStep) -> Result<Vec<ComponentNode>, XPathParseError> {
+        Ok(match step.axis {
+            XPathAxis::Child => self.children(node),
+            XPathAxis::Descendant | XPathAxis::DescendantOrSelf => {
+                let mut nodes = Vec::new();
+                if step.axis == XPathAxis::DescendantOrSelf {
+                    nodes.push(node.clone());
+                }
+                self.collect_descendants(node, &mut HashSet::new(), &mut nodes);
+                nodes
+            }
+            XPathAxis::Self_ => vec![node.clone()],
+            XPathAxis::Attribute => self
+                .node_type(node)
+                .map(|complex_type| declared_attributes(&complex_type))
+                .unwrap_or_default()
+                .into_iter()
+                .map(ComponentNode::Attribute)
+                .collect(),
+            axis => {
+                return Err(XPathParseError::InvalidSyntax(format!(
+                    "the {} axis is not supported on schema components",
+                    axis
+                )))
+            }
+        })
+    }
+
+    /// Check a node test against a component
+    ///
+    /// Prefixed names must match the namespace bound to the prefix;
+    /// unprefixed names match the default namespace, if one is set.
+    fn node_test_matches(&self, test: &NodeTest, node: &ComponentNode) -> bool {
+        let name = match node {
+            ComponentNode::Element(element) => &element.name,
+            ComponentNode::Attribute(attribute) => attribute.name(),
+            ComponentNode::Document | ComponentNode::Type(_) => return *test == NodeTest::Node,
+        };
+        match test {
+            NodeTest::Node | NodeTest::Wildcard => true,
+            NodeTest::NamespaceWildcard(prefix) => {
+                self.namespaces.get(prefix).map(String::as_str) == name.namespace.as_deref()
+            }
+            NodeTest::Name { prefix, local } => {
+                let namespace = match prefix {
+                    Some(prefix) => match self.namespaces.get(prefix) {
+                        Some(uri) => Some(uri),
+                        None => return false,
+                    },
+                    None => match &self.default_namespace {
+                        Some(uri) => Some(uri),
+                        None => return *local == name.local_name,
+                    },
+                };
+                *local == name.local_name && namespace.map(String::as_str) == name.namespace.as_deref()
+            }
+            _ => false,
+        }
+    }
+
     /// Check if a value matches a type
     pub fn check_type(&self, value: &str, type_name: &str) -> bool {
         if let Some(type_info) = self.types.get(type_name) {
@@ -196,6 +457,66 @@ impl SchemaProxy {
     }
 }
 
+/// A schema component reached by a location path
+#[derive(Debug, Clone)]
+enum ComponentNode {
+    /// The document node, whose children are the global elements
+    Document,
+    /// The complex type a relative path starts from
+    Type(Arc<XsdComplexType>),
+    /// An element declaration
+    Element(Arc<XsdElement>),
+    /// An attribute declaration
+    Attribute(Arc<XsdAttribute>),
+}
+
+impl ComponentNode {
+    fn to_xpath_node(&self) -> XPathNode {
+        let (node, namespace) = match self {
+            Self::Document => (
+                XPathNode {
+                    node_type: XPathNodeType::Root,
+                    ..XPathNode::element("", "")
+                },
+                None,
+            ),
+            Self::Type(_) => (XPathNode::element("", ""), None),
+            Self::Element(element) => (
+                XPathNode::element(element.name.local_name.as_str(), ""),
+                element.name.namespace.as_ref(),
+            ),
+            Self::Attribute(attribute) => (
+                XPathNode::attribute(attribute.name().local_name.as_str(), attribute.value_constraint().unwrap_or("")),
+                attribute.name().namespace.as_ref(),
+            ),
+        };
+        match namespace {
+            Some(ns) => node.with_namespace(ns.as_str()),
+            None => node,
+        }
+    }
+}
+
+/// Get the name of a complex type, or the address of an anonymous one
+fn type_key(complex_type: &Arc<XsdComplexType>) -> String {
+    match &complex_type.name {
+        Some(name) => name.to_string(),
+        None => format!("{:p}", Arc::as_ptr(complex_type)),
+    }
+}
+
+/// Get the attributes declared by a complex type, sorted by name
+fn declared_attributes(complex_type: &XsdComplexType) -> Vec<Arc<XsdAttribute>> {
+    let mut attributes: Vec<_> = complex_type
+        .attributes
+        .iter_attributes()
+        .filter(|attribute| !attribute.is_prohibited())
+        .cloned()
+        .collect();
+    attributes.sort_by_key(|attribute| attribute.name().to_string());
+    attributes
+}
+
 /// Type information for schema-aware XPath
 #[derive(Debug, Clone)]
 pub struct TypeInfo {
@@ -292,6 +613,7 @@ pub enum TypeVariety {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::xpath::XPathContext;
 
     #[test]
     fn test_schema_proxy_new() {
@@ -366,6 +688,95 @@ mod tests {
         }
     }
 
+    const ORDER_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           xmlns:o="urn:orders" targetNamespace="urn:orders">
+  <xs:complexType name="orderType">
+    <xs:sequence>
+      <xs:element name="id" type="xs:string"/>
+      <xs:element name="customer" type="xs:string"/>
+      <xs:element name="part" type="o:partType" maxOccurs="unbounded"/>
+    </xs:sequence>
+    <xs:attribute name="date" type="xs:date"/>
+    <xs:attribute name="status" type="xs:string" fixed="open"/>
+  </xs:complexType>
+  <xs:complexType name="partType">
+    <xs:sequence>
+      <xs:element name="part" type="o:partType" minOccurs="0"/>
+    </xs:sequence>
+  </xs:complexType>
+  <xs:element name="order" type="o:orderType"/>
+</xs:schema>"#;
+
+    fn order_proxy() -> SchemaProxy {
+        SchemaProxy::from_schema(Arc::new(XsdSchema::from_string(ORDER_XSD).unwrap()))
+    }
+
+    fn node_names(result: XPathResult) -> Vec<String> {
+        result
+            .as_nodes()
+            .map(|nodes| nodes.iter().map(|node| node.name.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_schema_components() {
+        let proxy = order_proxy();
+        let order_type = QName::namespaced("urn:orders", "orderType");
+
+        let children: Vec<_> = proxy.children_of(&order_type).iter().map(|e| e.name.local_name.clone()).collect();
+        assert_eq!(children, ["id", "customer", "part"]);
+        let attributes: Vec<_> =
+            proxy.attributes_of(&order_type).iter().map(|a| a.name().local_name.clone()).collect();
+        assert_eq!(attributes, ["date", "status"]);
+        assert_eq!(proxy.type_of(&QName::namespaced("urn:orders", "order")), Some(order_type));
+
+        assert!(proxy.children_of(&QName::namespaced("urn:orders", "missing")).is_empty());
+        assert_eq!(proxy.type_of(&QName::namespaced("urn:orders", "missing")), None);
+    }
+
+    #[test]
+    fn test_evaluate_xpath_on_schema() {
+        let proxy = order_proxy();
+        let order_type = QName::namespaced("urn:orders", "orderType");
+
+        let result = proxy.evaluate_xpath("./child::*", &order_type).unwrap();
+        assert_eq!(node_names(result), ["id", "customer", "part"]);
+        let result = proxy.evaluate_xpath("o:part", &order_type).unwrap();
+        assert_eq!(node_names(result), ["part"]);
+        assert!(matches!(proxy.evaluate_xpath("x:part", &order_type).unwrap(), XPathResult::Empty));
+
+        // Attributes carry their fixed value
+        let result = proxy.evaluate_xpath("@status", &order_type).unwrap();
+        assert_eq!(result.as_string(), "open");
+
+        // Recursive types are walked once; the part declarations of both
+        // types have the same name and type, so they are selected once
+        let result = proxy.evaluate_xpath(".//part", &order_type).unwrap();
+        assert_eq!(node_names(result), ["part"]);
+        let result = proxy.evaluate_xpath(".//*", &order_type).unwrap();
+        assert_eq!(node_names(result), ["id", "customer", "part"]);
+
+        // Absolute paths start from the global elements
+        let result = proxy.evaluate_xpath("/o:order/o:id", &order_type).unwrap();
+        assert_eq!(node_names(result), ["id"]);
+
+        assert!(proxy.evaluate_xpath("part[1]", &order_type).is_err());
+        assert!(proxy.evaluate_xpath("part", &QName::namespaced("urn:orders", "missing")).is_err());
+        assert!(SchemaProxy::new().evaluate_xpath("part", &order_type).is_err());
+    }
+
+    #[test]
+    fn test_xpath_context_schema() {
+        let context = XPathContext::new().with_schema(order_proxy());
+        assert_eq!(context.expand_name("o:part"), "{urn:orders}part");
+
+        let order_type = QName::namespaced("urn:orders", "orderType");
+        let result = context.evaluate_schema_path("child::o:customer", &order_type).unwrap();
+        assert_eq!(node_names(result), ["customer"]);
+        assert!(XPathContext::new().evaluate_schema_path("*", &order_type).is_err());
+    }
+
     #[test]
     fn test_type_variety() {
         let atomic = TypeInfo::new("string").with_variety(TypeVariety::Atomic);
@@ -70,6 +70,15 @@ impl PathStep {
             };
         }
 
+        if step == ".//" || step == "descendant-or-self::node()" {
+            return Self {
+                kind: PathStepKind::DescendantOrSelf,
+                name: String::new(),
+                prefix: None,
+                predicate: None,
+            };
+        }
+
         if step == ".." || step == "parent::node()" {
             return Self {
                 kind: PathStepKind::Parent,
@@ -366,6 +375,13 @@ mod tests {
         assert_eq!(step.kind, PathStepKind::Self_);
     }
 
+    #[test]
+    fn test_path_step_parse_descendant() {
+        let step = PathStep::parse(".//");
+        assert_eq!(step.kind, PathStepKind::DescendantOrSelf);
+        assert!(ElementSelector::new(".//a").steps()[1].kind == PathStepKind::DescendantOrSelf);
+    }
+
     #[test]
     fn test_path_step_parse_parent() {
         let step = PathStep::parse("..");
@@ -69,6 +69,106 @@ fn test_cli_inspect_json_output() {
     assert_eq!(json["statistics"]["globalTypes"], 5);
 }
 
+#[test]
+fn test_cli_inspect_typescript() {
+    let output = Command::new(xmlschema_bin())
+        .args(["inspect", "--typescript", schemas_dir().join("book.xsd").to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "inspect --typescript should succeed");
+    assert!(stdout.contains("export interface BookType {"));
+    assert!(stdout.contains("  author: PersonType[];"));
+    assert!(stdout.contains(r#"export type StatusType = "draft" | "published" | "out-of-print";"#));
+}
+
+#[test]
+fn test_cli_inspect_markdown() {
+    let output = Command::new(xmlschema_bin())
+        .args(["inspect", "--markdown", schemas_dir().join("book.xsd").to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "inspect --markdown should succeed");
+    assert!(stdout.starts_with("# Schema documentation\n"));
+    assert!(stdout.contains("### bookType"));
+    assert!(stdout.contains("| `book:book` | [`book:bookType`](#booktype) |  |"));
+}
+
+#[test]
+fn test_cli_inspect_lint() {
+    let schema_file = std::env::temp_dir().join("cli_test_lint.xsd");
+    std::fs::write(&schema_file, r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="codeType">
+        <xs:restriction base="xs:string">
+            <xs:length value="3"/>
+            <xs:minLength value="1"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:element name="code" type="codeType"/>
+</xs:schema>"#).unwrap();
+
+    let output = Command::new(xmlschema_bin())
+        .args(["inspect", "--lint", schema_file.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "inspect --lint should succeed");
+    assert!(stdout.contains("warning: Type 'codeType' has a length facet with redundant minLength/maxLength"));
+    assert!(stdout.contains("2 lint warning(s)"));
+
+    let output = Command::new(xmlschema_bin())
+        .args(["inspect", "--lint", "--json", schema_file.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Should output valid JSON");
+    assert_eq!(json["warnings"][0]["kind"], "RedundantLengthFacets");
+    assert_eq!(json["warnings"][1]["component"], "code");
+
+    std::fs::remove_file(&schema_file).ok();
+}
+
+#[test]
+fn test_cli_inspect_notations() {
+    let schema_file = std::env::temp_dir().join("cli_test_notations.xsd");
+    std::fs::write(&schema_file, r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:notation name="jpeg" public="image/jpeg" system="viewer.exe"/>
+    <xs:element name="image" type="xs:NOTATION"/>
+</xs:schema>"#).unwrap();
+
+    let output = Command::new(xmlschema_bin())
+        .args(["inspect", schema_file.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "inspect should succeed");
+    assert!(stdout.contains("Notations: 1"), "should show notation count");
+    assert!(stdout.contains("=== Notations ==="), "should list notations");
+    assert!(stdout.contains(r#"jpeg public="image/jpeg" system="viewer.exe""#));
+
+    let output = Command::new(xmlschema_bin())
+        .args(["inspect", "--json", schema_file.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .expect("Output should be valid JSON");
+
+    assert_eq!(json["statistics"]["notations"], 1);
+    assert_eq!(json["notations"][0]["name"], "jpeg");
+    assert_eq!(json["notations"][0]["public"], "image/jpeg");
+    assert_eq!(json["notations"][0]["system"], "viewer.exe");
+
+    std::fs::remove_file(&schema_file).ok();
+}
+
 #[test]
 fn test_cli_inspect_element_lookup() {
     let output = Command::new(xmlschema_bin())
@@ -107,6 +207,59 @@ fn test_cli_inspect_type_lookup() {
     assert!(stdout.contains("complex"), "should show type kind");
 }
 
+#[test]
+fn test_cli_inspect_content_model() {
+    let schema = schemas_dir().join("book.xsd");
+    let output = Command::new(xmlschema_bin())
+        .args(["inspect", "--content-model", schema.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "content model listing should succeed");
+    assert!(stdout.contains("bookType: (title, author+, published?, pages?)"), "should describe bookType");
+    assert!(stdout.contains("personType: "), "should list complex types");
+    assert!(!stdout.contains("isbnType"), "should not list simple types");
+
+    let output = Command::new(xmlschema_bin())
+        .args(["inspect", "--content-model", "--type-name", "bookType", schema.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success(), "content model lookup should succeed");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "(title, author+, published?, pages?)");
+}
+
+#[test]
+fn test_cli_inspect_explain_type() {
+    let schema = schemas_dir().join("book.xsd");
+    let output = Command::new(xmlschema_bin())
+        .args(["inspect", "--explain-type", "bookType", schema.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "type explanation should succeed");
+    assert!(stdout.contains("Kind: complex"), "should report the kind");
+    assert!(stdout.contains("author: personType [1..unbounded]"), "should list child elements");
+    assert!(stdout.contains("isbn: isbnType (required)"), "should list required attributes");
+    assert!(stdout.contains("edition: positiveInteger (optional)"), "should list optional attributes");
+}
+
+#[test]
+fn test_cli_inspect_explain_type_requires_exact_name() {
+    let schema = schemas_dir().join("book.xsd");
+    let output = Command::new(xmlschema_bin())
+        .args(["inspect", "--explain-type", "book", schema.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success(), "a partial type name should not match");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not found"));
+}
+
 #[test]
 fn test_cli_inspect_nonexistent_element() {
     let output = Command::new(xmlschema_bin())
@@ -205,6 +358,106 @@ fn test_cli_validate_lax_mode() {
     assert!(output.status.success(), "lax validation should succeed for valid document");
 }
 
+#[test]
+fn test_cli_validate_multiple_files_terminal() {
+    let output = Command::new(xmlschema_bin())
+        .args([
+            "validate",
+            "--schema", schemas_dir().join("book.xsd").to_str().unwrap(),
+            fixtures_dir().join("book_simple.xml").to_str().unwrap(),
+            fixtures_dir().join("book_invalid.xml").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(!output.status.success(), "should fail when any document is invalid");
+    assert!(stdout.contains("book_simple.xml: Document is valid"));
+    assert!(stdout.contains("book_invalid.xml: Document is invalid"));
+    assert!(stdout.contains("Validated 2 file(s): 1 valid, 1 invalid"));
+}
+
+#[test]
+fn test_cli_validate_json_reporter() {
+    let output = Command::new(xmlschema_bin())
+        .args([
+            "validate",
+            "--schema", schemas_dir().join("book.xsd").to_str().unwrap(),
+            "--reporter", "json",
+            fixtures_dir().join("book_simple.xml").to_str().unwrap(),
+            fixtures_dir().join("book_invalid.xml").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each line should be JSON"))
+        .collect();
+
+    assert!(!output.status.success());
+    assert_eq!(lines.len(), 3, "two results and a summary");
+    assert_eq!(lines[0]["valid"], true);
+    assert_eq!(lines[1]["valid"], false);
+    assert_eq!(lines[2]["summary"]["total"], 2);
+}
+
+#[test]
+fn test_cli_validate_junit_reporter() {
+    let output = Command::new(xmlschema_bin())
+        .args([
+            "validate",
+            "--schema", schemas_dir().join("book.xsd").to_str().unwrap(),
+            "--reporter", "junit",
+            fixtures_dir().join("book_invalid.xml").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(!output.status.success());
+    assert!(stdout.contains(r#"<testsuites name="xmlschema" tests="1" failures="1">"#));
+    assert!(stdout.contains("<failure"));
+}
+
+#[test]
+fn test_cli_validate_sarif_reporter() {
+    let output = Command::new(xmlschema_bin())
+        .args([
+            "validate",
+            "--schema", schemas_dir().join("book.xsd").to_str().unwrap(),
+            "--reporter", "sarif",
+            fixtures_dir().join("book_invalid.xml").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let log: serde_json::Value = serde_json::from_str(&stdout).expect("output should be JSON");
+
+    assert!(!output.status.success());
+    assert_eq!(log["version"], "2.1.0");
+    assert!(!log["runs"][0]["results"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_cli_validate_unknown_reporter() {
+    let output = Command::new(xmlschema_bin())
+        .args([
+            "validate",
+            "--schema", schemas_dir().join("book.xsd").to_str().unwrap(),
+            "--reporter", "html",
+            fixtures_dir().join("book_simple.xml").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success(), "should fail for unknown reporter");
+}
+
 // ============================================================================
 // XML to JSON Command Tests
 // ============================================================================
@@ -284,6 +537,57 @@ fn test_cli_xml2json_badgerfish_format() {
         .expect("BadgerFish output should be valid JSON");
 }
 
+#[test]
+fn test_cli_xml2json_jsonml_format() {
+    let output = Command::new(xmlschema_bin())
+        .args([
+            "xml2json",
+            "--format", "jsonml",
+            fixtures_dir().join("book_simple.xml").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success(), "xml2json --format jsonml should succeed");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout)
+        .expect("JsonML output should be valid JSON");
+
+    assert_eq!(
+        json,
+        serde_json::json!([
+            "book",
+            {"isbn": "978-0-13-468599-1", "xmlns": "http://example.com/book"},
+            ["title", "The Rust Programming Language"],
+            ["author", ["firstName", "Steve"], ["lastName", "Klabnik"]]
+        ])
+    );
+}
+
+#[test]
+fn test_cli_xml2json_columnar_format() {
+    let output = Command::new(xmlschema_bin())
+        .args([
+            "xml2json",
+            "--format", "columnar",
+            fixtures_dir().join("table.xml").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success(), "xml2json --format columnar should succeed");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout)
+        .expect("Columnar output should be valid JSON");
+
+    assert_eq!(
+        json,
+        serde_json::json!({"table": {"name": ["Ada", "Alan"], "born": ["1815", "1912"]}})
+    );
+}
+
 #[test]
 fn test_cli_xml2json_output_file() {
     let temp_dir = std::env::temp_dir();
@@ -330,6 +634,45 @@ fn test_cli_xml2json_invalid_format() {
     let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(stderr.contains("Unknown format") || stderr.contains("invalid_format"),
             "should report unknown format");
+    assert!(stderr.contains("jsonml, columnar"), "should list the available formats");
+}
+
+#[test]
+fn test_cli_xml2json_namespace_prefix() {
+    let output = Command::new(xmlschema_bin())
+        .args([
+            "xml2json",
+            "--namespace-prefix", "bk=http://example.com/book",
+            fixtures_dir().join("book_simple.xml").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success(), "xml2json --namespace-prefix should succeed");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout)
+        .expect("Output should be valid JSON");
+
+    assert_eq!(json["book"]["@xmlns:bk"], "http://example.com/book");
+    assert!(json["book"].get("@xmlns").is_none(), "in-document prefix should be replaced");
+}
+
+#[test]
+fn test_cli_xml2json_invalid_namespace_prefix() {
+    let output = Command::new(xmlschema_bin())
+        .args([
+            "xml2json",
+            "--namespace-prefix", "bk",
+            fixtures_dir().join("book_simple.xml").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success(), "should fail for a malformed namespace prefix");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("PREFIX=URI"), "should report the expected syntax");
 }
 
 // ============================================================================
@@ -361,6 +704,123 @@ fn test_validate_unbounded_followed_by_optional() {
     assert!(stdout.contains("Document is valid"), "should report document valid");
 }
 
+// ============================================================================
+// Compare Command Tests
+// ============================================================================
+
+/// Write a copy of book_simple.xml with lexically different but equal values
+fn write_equivalent_book(name: &str, edition: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, format!(r#"<?xml version="1.0"?>
+<book xmlns="http://example.com/book" isbn="978-0-13-468599-1" edition="{}">
+    <title>The Rust Programming Language</title>
+    <author>
+        <firstName>Steve</firstName>
+        <lastName>Klabnik</lastName>
+    </author>
+</book>
+"#, edition)).expect("Failed to write temp file");
+    path
+}
+
+#[test]
+fn test_cli_compare_semantic() {
+    let file1 = write_equivalent_book("compare_edition_2.xml", "2");
+    let file2 = write_equivalent_book("compare_edition_02.xml", "02");
+
+    // Structurally the edition attribute differs
+    let output = Command::new(xmlschema_bin())
+        .args(["compare", file1.to_str().unwrap(), file2.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "documents differ without a schema");
+    assert!(stdout.contains("~ /book[1]/@edition: '2' -> '02'"));
+
+    // With the schema both are the positive integer 2
+    let output = Command::new(xmlschema_bin())
+        .args([
+            "compare",
+            "--schema", schemas_dir().join("book.xsd").to_str().unwrap(),
+            file1.to_str().unwrap(),
+            file2.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "documents should be equivalent with the schema");
+    assert!(stdout.contains("Documents are equivalent"));
+
+    let _ = std::fs::remove_file(file1);
+    let _ = std::fs::remove_file(file2);
+}
+
+#[test]
+fn test_cli_compare_json() {
+    let output = Command::new(xmlschema_bin())
+        .args([
+            "compare",
+            "--json",
+            fixtures_dir().join("book_simple.xml").to_str().unwrap(),
+            fixtures_dir().join("book.xml").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout).expect("output should be JSON");
+
+    assert!(!output.status.success(), "different documents should fail");
+    assert_eq!(json["equal"], false);
+    assert!(json["changed"].is_array());
+}
+
+fn write_order_schema(name: &str, attributes: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, format!(r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="order">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="item" type="xs:string" maxOccurs="unbounded"/>
+      </xs:sequence>
+      {}
+    </xs:complexType>
+  </xs:element>
+</xs:schema>
+"#, attributes)).expect("Failed to write temp file");
+    path
+}
+
+#[test]
+fn test_cli_diff() {
+    let old = write_order_schema("diff_order_old.xsd", "");
+    let optional = write_order_schema("diff_order_optional.xsd", r#"<xs:attribute name="id" type="xs:string"/>"#);
+    let required = write_order_schema("diff_order_required.xsd", r#"<xs:attribute name="id" type="xs:string" use="required"/>"#);
+
+    let output = Command::new(xmlschema_bin())
+        .args(["diff", old.to_str().unwrap(), optional.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "an optional attribute is not a breaking change");
+    assert!(stdout.contains("~ order: optional attribute 'id' added"));
+
+    let output = Command::new(xmlschema_bin())
+        .args(["diff", "--json", old.to_str().unwrap(), required.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout).expect("output should be JSON");
+    assert!(!output.status.success(), "a required attribute is a breaking change");
+    assert_eq!(json["breaking"], true);
+    assert_eq!(json["changed"][0]["change"], "required attribute 'id' added");
+
+    for path in [old, optional, required] {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
 // ============================================================================
 // Error Handling Tests
 // ============================================================================
@@ -437,7 +897,7 @@ fn test_cli_version() {
 
 #[test]
 fn test_cli_subcommand_help() {
-    for subcommand in ["inspect", "validate", "xml2json"] {
+    for subcommand in ["inspect", "validate", "xml2json", "compare"] {
         let output = Command::new(xmlschema_bin())
             .args([subcommand, "--help"])
             .output()
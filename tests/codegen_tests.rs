@@ -0,0 +1,51 @@
+//! Integration tests for TypeScript code generation
+//!
+//! Compares the declarations generated for the book schema against a golden
+//! file, and type-checks them with `tsc` when it is installed.
+
+#![cfg(feature = "codegen")]
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use xmlschema::validators::XsdSchema;
+
+fn project_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn book_typescript() -> String {
+    let schema = XsdSchema::from_file(project_dir().join("tests/comparison/schemas/book.xsd"))
+        .expect("Failed to parse book.xsd");
+    schema.to_typescript_interfaces()
+}
+
+#[test]
+fn test_book_schema_typescript_matches_golden_file() {
+    let expected = std::fs::read_to_string(project_dir().join("tests/fixtures/book.ts"))
+        .expect("Failed to read book.ts");
+    assert_eq!(book_typescript(), expected);
+}
+
+#[test]
+fn test_book_schema_typescript_compiles() {
+    if Command::new("tsc").arg("--version").output().is_err() {
+        eprintln!("tsc is not installed, skipping");
+        return;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("book.ts");
+    std::fs::write(&path, book_typescript()).unwrap();
+
+    let output = Command::new("tsc")
+        .args(["--noEmit", "--strict"])
+        .arg(&path)
+        .output()
+        .expect("Failed to run tsc");
+    assert!(
+        output.status.success(),
+        "tsc failed:\n{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
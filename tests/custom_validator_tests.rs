@@ -0,0 +1,110 @@
+//! Integration tests for custom validation rules
+//!
+//! Runs a business rule ("publication date must be in the past") alongside
+//! schema validation of the book fixtures.
+
+use std::path::PathBuf;
+
+use chrono::{NaiveDate, Utc};
+use xmlschema::documents::{Document, Element};
+use xmlschema::validators::{CustomValidator, GlobalType, ValidationContext, XsdSchema};
+
+fn project_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn book_schema() -> XsdSchema {
+    XsdSchema::from_file(project_dir().join("examples/data/book.xsd")).expect("Failed to parse book.xsd")
+}
+
+fn book_document() -> String {
+    std::fs::read_to_string(project_dir().join("tests/fixtures/book.xml")).expect("Failed to read book.xml")
+}
+
+/// Requires `published` dates to be in the past
+struct PublishedInPast;
+
+impl CustomValidator for PublishedInPast {
+    fn validate_element(
+        &self,
+        element: &Element,
+        schema_type: Option<&GlobalType>,
+        _ctx: &ValidationContext,
+    ) -> Vec<String> {
+        let is_date = schema_type
+            .and_then(|t| t.as_simple())
+            .is_some_and(|st| st.primitive_name() == Some("date"));
+        if element.local_name() != "published" || !is_date {
+            return Vec::new();
+        }
+
+        match element.text.as_deref().map(|text| NaiveDate::parse_from_str(text, "%Y-%m-%d")) {
+            Some(Ok(date)) if date > Utc::now().date_naive() => {
+                vec![format!("Publication date {} must be in the past", date)]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Counts the authors of a book
+struct AtMostOneAuthor;
+
+impl CustomValidator for AtMostOneAuthor {
+    fn validate_element(
+        &self,
+        element: &Element,
+        schema_type: Option<&GlobalType>,
+        ctx: &ValidationContext,
+    ) -> Vec<String> {
+        let is_book_type = schema_type
+            .and_then(|t| t.name())
+            .is_some_and(|name| name.local_name == "bookType");
+        let authors = element.children.iter().filter(|c| c.local_name() == "author").count();
+        if ctx.level == 0 && is_book_type && authors > 1 {
+            vec![format!("Book has {} authors, at most one is allowed", authors)]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[test]
+fn test_custom_validator_accepts_past_date() {
+    let schema = book_schema();
+    let doc = Document::from_string(&book_document()).unwrap();
+    let extensions: Vec<Box<dyn CustomValidator>> = vec![Box::new(PublishedInPast)];
+
+    let result = schema.validate_with_extensions(&doc, &extensions);
+    assert!(result.valid, "unexpected errors: {:?}", result.errors);
+}
+
+#[test]
+fn test_custom_validator_rejects_future_date() {
+    let schema = book_schema();
+    let xml = book_document().replace("2019-08-12", "2999-01-01");
+    let doc = Document::from_string(&xml).unwrap();
+
+    // Structurally valid
+    assert!(schema.validate(&doc).valid);
+
+    let extensions: Vec<Box<dyn CustomValidator>> = vec![Box::new(PublishedInPast)];
+    let result = schema.validate_with_extensions(&doc, &extensions);
+    assert!(!result.valid);
+    assert_eq!(result.error_messages(), vec!["Publication date 2999-01-01 must be in the past"]);
+}
+
+#[test]
+fn test_multiple_custom_validators() {
+    let schema = book_schema();
+    let xml = book_document().replace("2019-08-12", "2999-01-01");
+    let doc = Document::from_string(&xml).unwrap();
+
+    let extensions: Vec<Box<dyn CustomValidator>> = vec![Box::new(PublishedInPast), Box::new(AtMostOneAuthor)];
+    let result = schema.validate_with_extensions(&doc, &extensions);
+    assert_eq!(result.errors.len(), 2, "{:?}", result.errors);
+    assert_eq!(result.errors[0].message, "Book has 2 authors, at most one is allowed");
+    assert_eq!(result.errors[0].path.as_deref(), Some("/book"));
+    assert!(result.errors[1].message.starts_with("Publication date"));
+    assert_eq!(result.errors[1].path.as_deref(), Some("/book/published"));
+}
@@ -0,0 +1,91 @@
+//! Integration tests for Markdown documentation
+//!
+//! Checks the sections and tables generated for the book schema.
+
+use std::path::PathBuf;
+
+use xmlschema::validators::XsdSchema;
+
+fn book_markdown() -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/comparison/schemas/book.xsd");
+    let schema = XsdSchema::from_file(path).expect("Failed to parse book.xsd");
+    schema.to_markdown()
+}
+
+#[test]
+fn test_book_markdown_sections() {
+    let md = book_markdown();
+
+    assert!(md.starts_with("# Schema documentation\n\nTarget namespace: `http://example.com/book`\n"));
+    let headings: Vec<_> = md.lines().filter(|line| line.starts_with('#')).collect();
+    assert_eq!(
+        headings,
+        [
+            "# Schema documentation",
+            "## Global elements",
+            "## Complex types",
+            "### bookType",
+            "### personType",
+            "## Simple types",
+            "### emailType",
+            "### isbnType",
+            "### statusType",
+            "## Namespace reference",
+        ]
+    );
+}
+
+#[test]
+fn test_book_markdown_tables() {
+    let md = book_markdown();
+
+    // Global elements link to their type's section
+    assert!(md.contains("| `book:book` | [`book:bookType`](#booktype) |  |"));
+
+    // Attributes and child elements of bookType
+    assert!(md.contains("| `edition` | `xs:positiveInteger` | optional | `1` |"));
+    assert!(md.contains("| `isbn` | [`book:isbnType`](#isbntype) | required |  |"));
+    assert!(md.contains("| `book:title` | `xs:string` | 1 |  |"));
+    assert!(md.contains("| `book:author` | [`book:personType`](#persontype) | 1..* |  |"));
+    assert!(md.contains("| `book:published` | `xs:date` | 0..1 |  |"));
+
+    // Simple type constraints
+    assert!(md.contains("### emailType\n\n- **Variety:** atomic\n- **Base type:** `xs:string`\n"));
+    assert!(md.contains("- **Constraints:** `xs:string with pattern=[^@]+@[^@]+\\.[^@]+, length <= 255`"));
+    assert!(md.contains("- **Values:** `draft`, `published`, `out-of-print`"));
+
+    // Namespace reference
+    assert!(md.contains("| `book` | `http://example.com/book` |"));
+    assert!(md.contains("| `xs` | `http://www.w3.org/2001/XMLSchema` |"));
+}
+
+#[test]
+fn test_markdown_annotations() {
+    let schema = XsdSchema::from_string(
+        r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:complexType name="noteType">
+    <xs:annotation><xs:documentation>A short note.</xs:documentation></xs:annotation>
+    <xs:simpleContent>
+      <xs:extension base="xs:string">
+        <xs:attribute name="lang" type="xs:language" fixed="en"/>
+      </xs:extension>
+    </xs:simpleContent>
+  </xs:complexType>
+  <xs:element name="note" type="noteType">
+    <xs:annotation>
+      <xs:documentation>First paragraph.</xs:documentation>
+      <xs:documentation>Second paragraph.</xs:documentation>
+    </xs:annotation>
+  </xs:element>
+</xs:schema>"#,
+    )
+    .unwrap();
+    let md = schema.generate_markdown_documentation();
+
+    assert!(md.contains("| `note` | [`noteType`](#notetype) | First paragraph.<br><br>Second paragraph. |"));
+    assert!(md.contains(
+        "### noteType\n\nA short note.\n\nDerived by extension from `xs:string`. Simple content of type `xs:string`.\n"
+    ));
+    assert!(md.contains("| `lang` | `xs:language` | optional | `en` (fixed) |"));
+    assert!(md.contains("## Simple types\n\n*None.*\n"));
+}
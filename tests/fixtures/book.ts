@@ -0,0 +1,22 @@
+export type EmailType = string;
+
+export type IsbnType = string;
+
+export type StatusType = "draft" | "published" | "out-of-print";
+
+export interface BookType {
+  title: string;
+  author: PersonType[];
+  published?: string;
+  pages?: number;
+  edition?: number;
+  isbn: IsbnType;
+}
+
+export interface PersonType {
+  firstName: string;
+  lastName: string;
+  email?: EmailType;
+}
+
+export type Book = BookType;
@@ -0,0 +1,165 @@
+//! Integration tests for loading schema imports over HTTP
+//!
+//! Run with `cargo test --features http`.
+
+#![cfg(feature = "http")]
+
+use std::path::PathBuf;
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+use xmlschema::loaders::{HttpLoader, ResourceLoader};
+use xmlschema::namespaces::QName;
+use xmlschema::validators::XsdSchema;
+
+const TYPES_XSD: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:types">
+  <xs:simpleType name="code">
+    <xs:restriction base="xs:string">
+      <xs:pattern value="[A-Z]{3}"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:element name="code" type="xs:string"/>
+</xs:schema>"#;
+
+/// Serve the types schema, expecting it to be downloaded once
+async fn types_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/schemas/types.xsd"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(TYPES_XSD))
+        .expect(1)
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_http_import_is_downloaded_once() {
+    let server = types_server().await;
+    let url = format!("{}/schemas/types.xsd", server.uri());
+
+    // The main schema and the schema it includes import the same URL
+    let dir = tempfile::tempdir().unwrap();
+    let import = format!(r#"<xs:import namespace="urn:types" schemaLocation="{}"/>"#, url);
+    std::fs::write(
+        dir.path().join("main.xsd"),
+        format!(
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:t="urn:types"
+           targetNamespace="urn:main">
+  {}
+  <xs:include schemaLocation="part.xsd"/>
+  <xs:element name="order" type="t:code"/>
+</xs:schema>"#,
+            import
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("part.xsd"),
+        format!(
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:t="urn:types"
+           targetNamespace="urn:main">
+  {}
+  <xs:element name="item" type="t:code"/>
+</xs:schema>"#,
+            import
+        ),
+    )
+    .unwrap();
+
+    // Blocking downloads must not run on the runtime's threads
+    let main_path: PathBuf = dir.path().join("main.xsd");
+    let schema = tokio::task::spawn_blocking(move || XsdSchema::from_file(main_path))
+        .await
+        .unwrap()
+        .expect("Failed to parse main.xsd");
+
+    assert!(schema.lookup_type(&QName::namespaced("urn:types", "code")).is_some());
+    assert!(schema.is_valid_string(r#"<order xmlns="urn:main">ABC</order>"#));
+    assert!(!schema.is_valid_string(r#"<order xmlns="urn:main">abc</order>"#));
+    server.verify().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_http_loader_cache() {
+    let server = types_server().await;
+    let url = format!("{}/schemas/types.xsd", server.uri());
+
+    // Blocking clients must not be created on the runtime's threads either
+    let (first, second) = tokio::task::spawn_blocking(move || {
+        let loader = HttpLoader::new();
+        let shared = HttpLoader::with_cache(loader.cache().clone());
+        let first = loader.load_resource(&url).unwrap();
+        let second = shared.load_resource(&format!("{}/schemas/./types.xsd", server_root(&url))).unwrap();
+        (first, second)
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(first, TYPES_XSD);
+    assert_eq!(second, TYPES_XSD);
+    server.verify().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_http_loader_error_status() {
+    let server = MockServer::start().await;
+    let url = format!("{}/missing.xsd", server.uri());
+
+    let result = tokio::task::spawn_blocking(move || HttpLoader::new().load_resource(&url))
+        .await
+        .unwrap();
+    assert!(result.unwrap_err().to_string().contains("missing.xsd"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_from_url_resolves_relative_includes() {
+    let server = MockServer::start().await;
+    let main_xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:include schemaLocation="common/part.xsd"/>
+  <xs:element name="order" type="orderCode"/>
+</xs:schema>"#;
+    let part_xsd = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="orderCode">
+    <xs:restriction base="xs:string">
+      <xs:pattern value="[0-9]{4}"/>
+    </xs:restriction>
+  </xs:simpleType>
+</xs:schema>"#;
+    for (route, body, content_type) in [
+        ("/schemas/main.xsd", main_xsd, "application/xml; charset=utf-8"),
+        ("/schemas/common/part.xsd", part_xsd, "text/xml"),
+        ("/schemas/plain.xsd", main_xsd, "text/plain"),
+    ] {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, content_type))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let root = server.uri();
+    let (schema, plain) = tokio::task::spawn_blocking(move || {
+        let schema = XsdSchema::from_url(&format!("{}/schemas/main.xsd", root));
+        let plain = XsdSchema::from_url(&format!("{}/schemas/plain.xsd", root));
+        (schema, plain)
+    })
+    .await
+    .unwrap();
+
+    let schema = schema.expect("Failed to load the schema from its URL");
+    assert!(schema.lookup_type(&QName::local("orderCode")).is_some());
+    assert!(schema.is_valid_string("<order>1234</order>"));
+    assert!(!schema.is_valid_string("<order>12AB</order>"));
+    assert!(plain.unwrap_err().to_string().contains("text/plain"));
+    server.verify().await;
+}
+
+/// Strip the path of a URL to the types schema
+fn server_root(url: &str) -> &str {
+    url.trim_end_matches("/schemas/types.xsd")
+}
@@ -0,0 +1,58 @@
+//! Integration tests for document size estimation
+//!
+//! Checks the estimates for the book schema.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use xmlschema::namespaces::QName;
+use xmlschema::sizing::SizeEstimateOptions;
+use xmlschema::validators::XsdSchema;
+
+const BOOK_NS: &str = "http://example.com/book";
+
+fn book_schema() -> XsdSchema {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/comparison/schemas/book.xsd");
+    XsdSchema::from_file(path).expect("Failed to parse book.xsd")
+}
+
+#[test]
+fn test_book_size_bounds() {
+    let schema = book_schema();
+    let (min, max) = schema.estimated_document_size(&QName::namespaced(BOOK_NS, "book"), None);
+
+    // <book xmlns="..." isbn=""><title/><author><firstName/><lastName/></author></book>
+    assert_eq!(min, 101);
+    // Every optional element and attribute present, ten authors
+    assert_eq!(max, 4891);
+}
+
+#[test]
+fn test_book_size_sample_values() {
+    let schema = book_schema();
+    let root = QName::namespaced(BOOK_NS, "book");
+    let samples = HashMap::from([
+        (QName::local("isbn"), "978-0-00-000000-0".to_string()),
+        (QName::namespaced(BOOK_NS, "title"), "Rust".to_string()),
+    ]);
+
+    let (min, _) = schema.estimated_document_size(&root, Some(&samples));
+    // The isbn value, and `<title>Rust</title>` instead of `<title/>`
+    assert_eq!(min, 101 + 17 + 11);
+}
+
+#[test]
+fn test_book_size_unbounded_occurs() {
+    let schema = book_schema();
+    let root = QName::namespaced(BOOK_NS, "book");
+    let options = SizeEstimateOptions {
+        unbounded_occurs: 1,
+        ..SizeEstimateOptions::default()
+    };
+
+    let (min, max) = schema.estimated_document_size_with(&root, None, &options);
+    let (default_min, default_max) = schema.estimated_document_size(&root, None);
+    assert_eq!(min, default_min);
+    // A single author of at most 459 bytes instead of ten
+    assert_eq!(max, default_max - 9 * 459);
+}
@@ -0,0 +1,75 @@
+//! Integration tests for streaming validation
+//!
+//! Checks that the memory used to validate a document from a reader doesn't
+//! grow with the size of the document.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use xmlschema::validators::{ValidationMode, XsdSchema};
+
+/// Allocator keeping track of the peak of allocated memory
+struct PeakAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: PeakAllocator = PeakAllocator;
+
+const NOTES_SCHEMA: &str = r#"<?xml version="1.0"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="notes">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="note" maxOccurs="unbounded">
+          <xs:complexType>
+            <xs:sequence>
+              <xs:element name="to" type="xs:string"/>
+              <xs:element name="priority" type="xs:integer" minOccurs="0"/>
+            </xs:sequence>
+            <xs:attribute name="lang" type="xs:language"/>
+          </xs:complexType>
+        </xs:element>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>"#;
+
+#[test]
+fn test_validate_large_document_from_reader() {
+    let schema = XsdSchema::from_string(NOTES_SCHEMA).expect("Failed to parse the schema");
+    let mut xml = String::from("<notes>");
+    for i in 0..10_000 {
+        xml.push_str(&format!(r#"<note lang="en"><to>Reader {}</to><priority>{}</priority></note>"#, i, i % 5));
+    }
+    xml.push_str("</notes>");
+
+    // Warm up the lazily built state of the library
+    let result = schema.validate_reader(r#"<notes><note lang="en"><to/><priority>1</priority></note></notes>"#.as_bytes());
+    assert!(result.valid, "{:?}", result.errors);
+
+    let baseline = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+    let result = schema.validate_reader_with_mode(xml.as_bytes(), ValidationMode::Lax);
+    let peak = PEAK.load(Ordering::SeqCst) - baseline;
+
+    assert!(result.valid, "{:?}", result.errors);
+    assert!(peak < 1024 * 1024, "validation used {} bytes", peak);
+}